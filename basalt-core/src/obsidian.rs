@@ -17,16 +17,32 @@
 //! ```
 use std::{io, path::PathBuf, result};
 
+mod anchor;
+mod canvas;
 mod config;
+mod link;
+mod link_index;
 mod note;
+mod publish;
+mod query;
+mod tag;
 mod vault;
 mod vault_entry;
+mod vault_index;
 
+pub use anchor::Anchors;
+pub use canvas::{Canvas, CanvasEdge, CanvasNode, CanvasNodeKind};
 pub use config::ObsidianConfig;
-pub use note::Note;
-pub use vault::Vault;
+pub use link::{code_ranges, Link, LinkKind};
+pub use link_index::{IndexedLink, LinkIndex};
+pub use note::{Note, ReadOnly};
+pub use publish::{publish, PublishFilter, PublishOptions, PublishReport, DEFAULT_TEMPLATE};
+pub use query::{Filter, Query, TaskState};
+pub use tag::Tag;
+pub use vault::{LinkUpdate, Vault};
 pub use vault_entry::FindNote;
 pub use vault_entry::VaultEntry;
+pub use vault_index::{IndexedNote, VaultIndex};
 
 /// A [`std::result::Result`] type for fallible operations in [`crate::obsidian`].
 ///
@@ -58,6 +74,15 @@ pub enum Error {
     #[error("Empty filename for path: {0}")]
     EmptyFileName(PathBuf),
 
+    /// Note name with a path separator, or `.` or `..`, which would place the note in another
+    /// folder.
+    #[error("Invalid note name: {0}")]
+    InvalidName(String),
+
+    /// Note that is not valid UTF-8, which is not rewritten so that its bytes are kept as is.
+    #[error("Not valid UTF-8: {0}")]
+    InvalidUtf8(PathBuf),
+
     /// JSON (de)serialization error, from [`serde_json::Error`].
     #[error("JSON (de)serialization error: {0}")]
     Json(#[from] serde_json::Error),
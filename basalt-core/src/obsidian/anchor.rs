@@ -0,0 +1,100 @@
+use crate::markdown::{self, MarkdownNode};
+
+use super::link::code_ranges;
+
+/// The headings and block IDs of a note, which links can point to with `[[Note#Heading]]` and
+/// `[[Note#^block-id]]`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Anchors {
+    /// Text of the headings in the order they appear in the note.
+    pub headings: Vec<String>,
+
+    /// Block IDs without the `^`, in the order they appear in the note.
+    pub block_ids: Vec<String>,
+}
+
+/// Returns the block ID at the end of the line, e.g. `id-1` for `Some text ^id-1`.
+fn block_id(line: &str) -> Option<&str> {
+    let (before, id) = line
+        .trim_end()
+        .rsplit_once('^')
+        .filter(|(before, _)| before.is_empty() || before.ends_with(char::is_whitespace))?;
+
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .then_some(id)
+        .filter(|_| !before.trim_start().starts_with('#'))
+}
+
+impl Anchors {
+    /// Parses the headings and block IDs of the note contents. Block IDs inside code are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Anchors;
+    ///
+    /// let anchors = Anchors::parse("# Shed\n\nPaint the door ^door\n\n## Pond\n");
+    ///
+    /// assert_eq!(anchors.headings, vec!["Shed", "Pond"]);
+    /// assert_eq!(anchors.block_ids, vec!["door"]);
+    /// ```
+    pub fn parse(contents: &str) -> Self {
+        let headings = markdown::from_str(contents)
+            .into_iter()
+            .filter_map(|node| match node.markdown_node {
+                MarkdownNode::Heading { text, .. } => {
+                    Some(text.into_iter().map(|node| node.content).collect())
+                }
+                _ => None,
+            })
+            .collect();
+
+        let code = code_ranges(contents);
+        let mut offset = 0;
+        let mut block_ids = vec![];
+
+        for line in contents.split_inclusive('\n') {
+            let in_code = code.iter().any(|range| range.contains(&offset));
+            if let Some(id) = block_id(line).filter(|_| !in_code) {
+                block_ids.push(id.to_string());
+            }
+            offset += line.len();
+        }
+
+        Self {
+            headings,
+            block_ids,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let anchors = Anchors::parse(indoc! {"
+            # Projects
+
+            Ideas for the garden. ^ideas
+
+            - Paint the door ^task-1
+            - Fix the roof^not-an-id
+
+            > A quote
+            ^quote
+
+            ```
+            let x = 1; ^code
+            ```
+
+            ## Pond #water
+        "});
+
+        assert_eq!(anchors.headings, ["Projects", "Pond #water"]);
+        assert_eq!(anchors.block_ids, ["ideas", "task-1", "quote"]);
+    }
+}
@@ -0,0 +1,284 @@
+use std::{fs, path::Path, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::obsidian::{Error, Result};
+
+/// An Obsidian canvas, stored as a `.canvas` file in the [JSON Canvas](https://jsoncanvas.org)
+/// format.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::{Canvas, CanvasNodeKind};
+///
+/// let canvas: Canvas = r#"{
+///   "nodes": [
+///     { "id": "a", "type": "text", "text": "Idea", "x": 0, "y": 0, "width": 200, "height": 60 },
+///     { "id": "b", "type": "file", "file": "Plan.md", "x": 300, "y": 0, "width": 200, "height": 60 }
+///   ],
+///   "edges": [{ "id": "e", "fromNode": "a", "toNode": "b", "label": "leads to" }]
+/// }"#
+/// .parse()
+/// .unwrap();
+///
+/// assert_eq!(canvas.nodes[1].title(), "Plan");
+/// assert_eq!(canvas.outgoing("a")[0].to_node, "b");
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+pub struct Canvas {
+    /// Cards of the canvas.
+    #[serde(default)]
+    pub nodes: Vec<CanvasNode>,
+
+    /// Connections between the cards.
+    #[serde(default)]
+    pub edges: Vec<CanvasEdge>,
+}
+
+/// A card on a canvas.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CanvasNode {
+    /// Unique identifier of the card, referenced by edges.
+    pub id: String,
+
+    /// Content of the card.
+    #[serde(flatten)]
+    pub kind: CanvasNodeKind,
+
+    /// Horizontal position of the left edge in pixels.
+    pub x: i64,
+
+    /// Vertical position of the top edge in pixels.
+    pub y: i64,
+
+    /// Width in pixels.
+    pub width: i64,
+
+    /// Height in pixels.
+    pub height: i64,
+}
+
+/// Content of a canvas card.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CanvasNodeKind {
+    /// A card with Markdown text.
+    Text {
+        /// Markdown text of the card.
+        text: String,
+    },
+
+    /// A card that embeds a file of the vault.
+    File {
+        /// Path of the file relative to the vault root.
+        file: String,
+
+        /// Heading or block of the file, e.g. `#Heading`.
+        subpath: Option<String>,
+    },
+
+    /// A card that embeds a web page.
+    Link {
+        /// Address of the web page.
+        url: String,
+    },
+
+    /// A group that visually contains the cards inside its bounds.
+    Group {
+        /// Label of the group.
+        label: Option<String>,
+    },
+}
+
+/// A connection between two canvas cards.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanvasEdge {
+    /// Unique identifier of the edge.
+    pub id: String,
+
+    /// Identifier of the card the edge starts from.
+    pub from_node: String,
+
+    /// Identifier of the card the edge points to.
+    pub to_node: String,
+
+    /// Label of the edge.
+    pub label: Option<String>,
+}
+
+impl FromStr for Canvas {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_str(s).map_err(Error::from)
+    }
+}
+
+impl Canvas {
+    /// Reads and parses the canvas file at the given path.
+    pub fn read(path: &Path) -> Result<Canvas> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Returns the card with the given identifier.
+    pub fn node(&self, id: &str) -> Option<&CanvasNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Returns the edges that start from the card with the given identifier.
+    pub fn outgoing(&self, id: &str) -> Vec<&CanvasEdge> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.from_node == id)
+            .collect()
+    }
+
+    /// Returns the edges that point to the card with the given identifier.
+    pub fn incoming(&self, id: &str) -> Vec<&CanvasEdge> {
+        self.edges
+            .iter()
+            .filter(|edge| edge.to_node == id)
+            .collect()
+    }
+
+    /// Returns the innermost group that contains the card.
+    fn parent(&self, node: &CanvasNode) -> Option<&CanvasNode> {
+        self.nodes
+            .iter()
+            .filter(|group| group.id != node.id && group.is_group() && group.contains(node))
+            .min_by_key(|group| group.width * group.height)
+    }
+
+    /// Returns the cards in reading order with their nesting depth: top to bottom and left to
+    /// right, with the cards inside a group listed right after the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Canvas;
+    ///
+    /// let canvas: Canvas = r#"{
+    ///   "nodes": [
+    ///     { "id": "note", "type": "text", "text": "Inside", "x": 20, "y": 40, "width": 100, "height": 40 },
+    ///     { "id": "below", "type": "text", "text": "Below", "x": 0, "y": 300, "width": 100, "height": 40 },
+    ///     { "id": "group", "type": "group", "label": "Ideas", "x": 0, "y": 0, "width": 400, "height": 200 }
+    ///   ]
+    /// }"#
+    /// .parse()
+    /// .unwrap();
+    ///
+    /// let order: Vec<_> = canvas
+    ///     .reading_order()
+    ///     .into_iter()
+    ///     .map(|(node, depth)| (node.id.as_str(), depth))
+    ///     .collect();
+    ///
+    /// assert_eq!(order, [("group", 0), ("note", 1), ("below", 0)]);
+    /// ```
+    pub fn reading_order(&self) -> Vec<(&CanvasNode, usize)> {
+        fn visit<'a>(
+            nodes: &[(&'a CanvasNode, Option<&str>)],
+            parent: Option<&str>,
+            depth: usize,
+            order: &mut Vec<(&'a CanvasNode, usize)>,
+        ) {
+            let mut children: Vec<_> = nodes
+                .iter()
+                .filter(|(_, node_parent)| *node_parent == parent)
+                .map(|(node, _)| *node)
+                .collect();
+            children.sort_by_key(|node| (node.y, node.x));
+
+            for node in children {
+                order.push((node, depth));
+                visit(nodes, Some(&node.id), depth + 1, order);
+            }
+        }
+
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node| (node, self.parent(node).map(|group| group.id.as_str())))
+            .collect();
+
+        let mut order = Vec::with_capacity(self.nodes.len());
+        visit(&nodes, None, 0, &mut order);
+        order
+    }
+}
+
+impl CanvasNode {
+    /// Returns `true` for group cards.
+    pub fn is_group(&self) -> bool {
+        matches!(self.kind, CanvasNodeKind::Group { .. })
+    }
+
+    /// Returns `true` if the other card lies completely within the bounds of this card.
+    pub fn contains(&self, other: &CanvasNode) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x + other.width <= self.x + self.width
+            && other.y + other.height <= self.y + self.height
+    }
+
+    /// Returns a short title of the card: the first line of a text card without heading
+    /// markers, the file name of a file card, the address of a link card or the label of a
+    /// group.
+    pub fn title(&self) -> String {
+        match &self.kind {
+            CanvasNodeKind::Text { text } => text
+                .lines()
+                .map(|line| line.trim_start_matches('#').trim())
+                .find(|line| !line.is_empty())
+                .unwrap_or_default()
+                .to_string(),
+            CanvasNodeKind::File { file, subpath } => {
+                let name = Path::new(file)
+                    .file_stem()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| file.clone());
+                format!("{name}{}", subpath.as_deref().unwrap_or_default())
+            }
+            CanvasNodeKind::Link { url } => url.clone(),
+            CanvasNodeKind::Group { label } => label.clone().unwrap_or_else(|| "Group".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let canvas: Canvas = indoc! {r###"
+            {
+              "nodes": [
+                { "id": "1", "type": "text", "text": "## Goals\nShip it", "x": 0, "y": 0, "width": 1, "height": 1, "color": "1" },
+                { "id": "2", "type": "file", "file": "Notes/Plan.md", "subpath": "#Next", "x": 0, "y": 0, "width": 1, "height": 1 },
+                { "id": "3", "type": "link", "url": "https://obsidian.md", "x": 0, "y": 0, "width": 1, "height": 1 },
+                { "id": "4", "type": "group", "x": 0, "y": 0, "width": 1, "height": 1 }
+              ],
+              "edges": [
+                { "id": "a", "fromNode": "1", "fromSide": "right", "toNode": "2" }
+              ]
+            }
+        "###}
+        .parse()
+        .unwrap();
+
+        let titles: Vec<_> = canvas.nodes.iter().map(CanvasNode::title).collect();
+        assert_eq!(
+            titles,
+            ["Goals", "Plan#Next", "https://obsidian.md", "Group"]
+        );
+        assert_eq!(canvas.incoming("2").len(), 1);
+        assert!(canvas.outgoing("2").is_empty());
+        assert!("{ \"nodes\": 1 }".parse::<Canvas>().is_err());
+        assert_eq!("{}".parse::<Canvas>().unwrap(), Canvas::default());
+    }
+}
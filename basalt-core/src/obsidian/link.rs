@@ -0,0 +1,299 @@
+use std::{
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Resolves `.` and `..` components without touching the file system, as the path might not
+/// exist.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .fold(PathBuf::new(), |mut normalized, component| {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                component => normalized.push(component),
+            }
+            normalized
+        })
+}
+
+/// The syntax used to write a [`Link`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LinkKind {
+    /// Obsidian wikilink, e.g. `[[Note]]`, `[[Folder/Note#Heading|Alias]]` or `![[Image.png]]`.
+    Wiki,
+    /// Markdown link, e.g. `[Text](Folder/Note.md)`.
+    Markdown,
+}
+
+/// A link to another note or file found in note contents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Link {
+    /// The syntax of the link.
+    pub kind: LinkKind,
+
+    /// The link target without heading or block references, e.g. `Folder/Note` for
+    /// `[[Folder/Note#Heading]]`. Percent-encoded characters of markdown links are decoded.
+    pub target: String,
+
+    /// Byte range of the target in the source text. Replacing the range rewrites the target
+    /// while keeping headings, aliases and link text intact.
+    pub range: Range<usize>,
+}
+
+/// Decodes percent-encoded characters, e.g. `My%20Note.md` to `My Note.md`. Invalid sequences
+/// are kept as is.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).unwrap_or_else(|_| value.to_string())
+}
+
+/// Percent-encodes the characters of a path that are not unreserved in URLs (RFC 3986), e.g.
+/// `Notes/My Note (1).md` to `Notes/My%20Note%20%281%29.md`. Path separators are kept.
+pub(crate) fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Returns the byte ranges of fenced code blocks and inline code spans. Links within code are
+/// not links.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::code_ranges;
+///
+/// assert_eq!(code_ranges("See `[[Note]]` and [[Other]]"), vec![4..14]);
+/// ```
+pub fn code_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut fence: Option<(usize, &str)> = None;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+
+        match (fence, marker) {
+            (None, Some(marker)) => fence = Some((offset, marker)),
+            (Some((start, open)), Some(marker)) if open == marker => {
+                ranges.push(start..offset + line.len());
+                fence = None;
+            }
+            (Some(_), _) => {}
+            (None, None) => {
+                let mut start = None;
+                for (i, c) in line.char_indices().filter(|(_, c)| *c == '`') {
+                    match start.take() {
+                        Some(start) => ranges.push(offset + start..offset + i + c.len_utf8()),
+                        None => start = Some(i),
+                    }
+                }
+            }
+        }
+
+        offset += line.len();
+    }
+
+    if let Some((start, _)) = fence {
+        ranges.push(start..text.len());
+    }
+
+    ranges
+}
+
+impl Link {
+    /// Finds all wikilinks and markdown links in the given text. Links inside code blocks and
+    /// inline code are ignored, as well as markdown links without a target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{Link, LinkKind};
+    ///
+    /// let links = Link::parse("See [[Folder/Note#Heading|note]] and [other](Other%20Note.md).");
+    ///
+    /// assert_eq!(links, vec![
+    ///   Link { kind: LinkKind::Wiki, target: "Folder/Note".into(), range: 6..17 },
+    ///   Link { kind: LinkKind::Markdown, target: "Other Note.md".into(), range: 45..60 },
+    /// ]);
+    /// ```
+    pub fn parse(text: &str) -> Vec<Link> {
+        let code = code_ranges(text);
+        let in_code = |index: usize| code.iter().any(|range| range.contains(&index));
+
+        let mut links = vec![];
+        let mut index = 0;
+
+        while let Some(start) = text[index..].find('[').map(|i| i + index) {
+            index = start + 1;
+
+            if in_code(start) {
+                continue;
+            }
+
+            if text[start..].starts_with("[[") {
+                let inner_start = start + 2;
+                let Some(end) = text[inner_start..].find("]]").map(|i| i + inner_start) else {
+                    continue;
+                };
+
+                let inner = &text[inner_start..end];
+                if inner.contains('\n') {
+                    continue;
+                }
+
+                let target_len = inner.find(['#', '|', '^']).unwrap_or(inner.len());
+                let target = inner[..target_len].trim_end();
+
+                if !target.is_empty() {
+                    links.push(Link {
+                        kind: LinkKind::Wiki,
+                        target: target.to_string(),
+                        range: inner_start..inner_start + target.len(),
+                    });
+                }
+
+                index = end + 2;
+                continue;
+            }
+
+            let Some(text_end) = text[start..].find("](").map(|i| i + start) else {
+                break;
+            };
+
+            if text[start + 1..text_end].contains(['[', '\n']) {
+                continue;
+            }
+
+            let url_start = text_end + 2;
+            let Some(url_end) = text[url_start..].find([')', '\n']).map(|i| i + url_start) else {
+                break;
+            };
+
+            // Title, e.g. `[Text](Note.md "Title")`, is not part of the target.
+            let url = &text[url_start..url_end];
+            let url = url.split(" \"").next().unwrap_or(url).trim_end();
+            let target_len = url.find('#').unwrap_or(url.len());
+            let target = &url[..target_len];
+
+            if !target.is_empty() {
+                links.push(Link {
+                    kind: LinkKind::Markdown,
+                    target: percent_decode(target),
+                    range: url_start..url_start + target.len(),
+                });
+            }
+
+            index = url_end;
+        }
+
+        links
+    }
+
+    /// Returns `true` if the link points to an external resource, e.g. `https://obsidian.md`,
+    /// instead of a file in the vault.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Link;
+    ///
+    /// let links = Link::parse("[Obsidian](https://obsidian.md) [Note](Note.md)");
+    ///
+    /// assert!(links[0].is_external());
+    /// assert!(!links[1].is_external());
+    /// ```
+    pub fn is_external(&self) -> bool {
+        self.kind == LinkKind::Markdown
+            && self.target.split_once(':').is_some_and(|(scheme, _)| {
+                !scheme.is_empty()
+                    && scheme
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = indoc! {r#"
+            # [[Heading Link]]
+
+            ![[Image.png]] [Title](Note.md "Title") [Empty]()
+
+            `[[Inline code]]` [[Folder/Note^block]]
+
+            ```
+            [[Code block]]
+            ```
+        "#};
+
+        let targets: Vec<_> = Link::parse(text)
+            .into_iter()
+            .map(|link| (link.kind, text[link.range].to_string(), link.target))
+            .collect();
+
+        assert_eq!(
+            targets,
+            vec![
+                (LinkKind::Wiki, "Heading Link".into(), "Heading Link".into()),
+                (LinkKind::Wiki, "Image.png".into(), "Image.png".into()),
+                (LinkKind::Markdown, "Note.md".into(), "Note.md".into()),
+                (LinkKind::Wiki, "Folder/Note".into(), "Folder/Note".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        let path = "Notes/My Note (1) #2 100% ä.md";
+        let encoded = percent_encode(path);
+
+        assert_eq!(
+            encoded,
+            "Notes/My%20Note%20%281%29%20%232%20100%25%20%C3%A4.md"
+        );
+        assert_eq!(percent_decode(&encoded), path);
+    }
+}
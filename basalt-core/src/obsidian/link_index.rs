@@ -0,0 +1,506 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use super::{link::normalize, Canvas, CanvasNodeKind, Link, LinkKind, Note, Result, Vault};
+
+/// A [`Link`] together with the file it resolves to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedLink {
+    /// The link as written in the note.
+    pub link: Link,
+
+    /// Path of the linked file, or `None` if the link does not resolve to any file in the vault.
+    pub target: Option<PathBuf>,
+}
+
+/// Index of the links between notes in a vault.
+///
+/// Links are resolved similarly to Obsidian: wikilinks with a bare name, such as `[[Note]]`,
+/// match a note or attachment with that name in any folder, or a note with that alias, while links
+/// with a path are resolved relative to the vault root or the linking note. External links are not
+/// indexed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkIndex {
+    vault_path: PathBuf,
+    files: HashSet<PathBuf>,
+    names: HashMap<String, Vec<PathBuf>>,
+    /// Notes by their lowercase aliases.
+    aliases: HashMap<String, PathBuf>,
+    links: BTreeMap<PathBuf, Vec<IndexedLink>>,
+    /// Files embedded in the file cards of each canvas.
+    canvases: BTreeMap<PathBuf, Vec<PathBuf>>,
+}
+
+/// Returns `true` for files that are neither notes nor canvases.
+fn is_attachment(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext != "md" && ext != "canvas")
+}
+
+impl LinkIndex {
+    /// Creates an empty index that resolves links against the given vault files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{LinkIndex, Note};
+    /// use std::path::Path;
+    ///
+    /// let mut index = LinkIndex::new(
+    ///     Path::new("vault"),
+    ///     vec!["vault/Note.md".into(), "vault/Folder/Other.md".into()],
+    /// );
+    ///
+    /// let note = Note {
+    ///     name: "Note".into(),
+    ///     path: "vault/Note.md".into(),
+    /// };
+    ///
+    /// index.insert(&note, "[[Other]] [[Missing]]");
+    ///
+    /// assert_eq!(index.links(&note.path)[0].target, Some("vault/Folder/Other.md".into()));
+    /// assert_eq!(index.links(&note.path)[1].target, None);
+    /// ```
+    pub fn new(vault_path: &Path, files: Vec<PathBuf>) -> Self {
+        let mut names: HashMap<String, Vec<PathBuf>> = HashMap::new();
+
+        files.iter().for_each(|path| {
+            if let Some(name) = path.file_name() {
+                names
+                    .entry(name.to_string_lossy().to_string())
+                    .or_default()
+                    .push(path.clone());
+            }
+        });
+
+        // Obsidian prefers the shortest path when several files share the name.
+        names
+            .values_mut()
+            .for_each(|paths| paths.sort_by_key(|path| path.components().count()));
+
+        Self {
+            vault_path: vault_path.to_path_buf(),
+            files: files.into_iter().collect(),
+            names,
+            aliases: HashMap::new(),
+            links: BTreeMap::new(),
+            canvases: BTreeMap::new(),
+        }
+    }
+
+    /// Builds the index of all notes and canvases in the vault. The `progress` callback is called
+    /// after each indexed note with the number of indexed notes and the total number of notes.
+    /// Canvases that cannot be parsed are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{LinkIndex, Vault};
+    ///
+    /// let vault = Vault {
+    ///     name: "MyVault".into(),
+    ///     path: "path/to/my_vault".into(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let index = LinkIndex::build(&vault, |_, _| {}).unwrap();
+    /// assert!(index.orphans().is_empty());
+    /// ```
+    pub fn build(vault: &Vault, mut progress: impl FnMut(usize, usize)) -> Result<Self> {
+        let mut index = Self::new(&vault.path, vault.files());
+        let notes = vault.notes();
+
+        let contents = notes
+            .iter()
+            .map(Note::read_to_string)
+            .collect::<Result<Vec<_>>>()?;
+
+        // Aliases are known before the links are resolved, as links can point to any note.
+        notes.iter().zip(&contents).for_each(|(note, contents)| {
+            index.insert_aliases(&note.path, &Note::property(contents, "aliases"));
+        });
+
+        for (i, (note, contents)) in notes.iter().zip(&contents).enumerate() {
+            index.insert(note, contents);
+            progress(i + 1, notes.len());
+        }
+
+        vault
+            .files()
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "canvas"))
+            .for_each(|path| {
+                if let Ok(canvas) = Canvas::read(&path) {
+                    index.insert_canvas(&path, &canvas);
+                }
+            });
+
+        Ok(index)
+    }
+
+    /// Resolves the link target relative to the note at `from`.
+    pub fn resolve(&self, from: &Path, link: &Link) -> Option<PathBuf> {
+        if link.is_external() {
+            return None;
+        }
+
+        let target = link.target.trim();
+
+        if link.kind == LinkKind::Wiki && !target.contains('/') {
+            return [format!("{target}.md"), target.to_string()]
+                .iter()
+                .find_map(|name| self.names.get(name).and_then(|paths| paths.first()))
+                .or_else(|| self.aliases.get(&target.to_lowercase()))
+                .cloned();
+        }
+
+        let note_dir = from.parent().unwrap_or(&self.vault_path);
+        let dirs = match link.kind {
+            LinkKind::Wiki => [&self.vault_path, note_dir],
+            LinkKind::Markdown => [note_dir, &self.vault_path],
+        };
+
+        dirs.iter()
+            .flat_map(|dir| {
+                let path = normalize(&dir.join(target));
+                let mut with_extension = path.clone().into_os_string();
+                with_extension.push(".md");
+                [PathBuf::from(with_extension), path]
+            })
+            .find(|path| self.files.contains(path))
+    }
+
+    /// Returns the path where a file for the unresolved link is created, e.g. when creating the
+    /// missing note of a broken link. Wikilinks are created relative to the vault root and
+    /// markdown links relative to the linking note. Targets without an extension get `.md`.
+    ///
+    /// Returns `None` if the path is outside the vault, e.g. for `[[../../Note]]` or an absolute
+    /// target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{Link, LinkIndex};
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let index = LinkIndex::new(Path::new("vault"), vec![]);
+    /// let from = Path::new("vault/Folder/Note.md");
+    /// let links = Link::parse("[[Missing]] [other](Sub/Other%20note.md)");
+    ///
+    /// assert_eq!(index.new_file_path(from, &links[0]), Some(PathBuf::from("vault/Missing.md")));
+    /// assert_eq!(
+    ///     index.new_file_path(from, &links[1]),
+    ///     Some(PathBuf::from("vault/Folder/Sub/Other note.md"))
+    /// );
+    ///
+    /// let outside = Link::parse("[[../../Note]] [note](/tmp/Note.md)");
+    /// assert_eq!(index.new_file_path(from, &outside[0]), None);
+    /// assert_eq!(index.new_file_path(from, &outside[1]), None);
+    /// ```
+    pub fn new_file_path(&self, from: &Path, link: &Link) -> Option<PathBuf> {
+        let dir = match link.kind {
+            LinkKind::Wiki => &self.vault_path,
+            LinkKind::Markdown => from.parent().unwrap_or(&self.vault_path),
+        };
+
+        let path = normalize(&dir.join(link.target.trim()));
+
+        let path = match path.extension() {
+            Some(_) => path,
+            None => {
+                let mut with_extension = path.into_os_string();
+                with_extension.push(".md");
+                with_extension.into()
+            }
+        };
+
+        let vault_path = normalize(&self.vault_path);
+        (path.starts_with(&vault_path) && path != vault_path).then_some(path)
+    }
+
+    /// Parses and indexes the links of the note. Existing links of the note are replaced.
+    pub fn insert(&mut self, note: &Note, contents: &str) {
+        self.insert_links(&note.path, Link::parse(contents));
+    }
+
+    /// Adds the aliases of the note at the path, which bare wikilinks resolve to when no file has
+    /// the name. Aliases inserted after links do not resolve those links.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{Link, LinkIndex};
+    /// use std::path::Path;
+    ///
+    /// let mut index = LinkIndex::new(Path::new("vault"), vec!["vault/Ideas.md".into()]);
+    /// index.insert_aliases(Path::new("vault/Ideas.md"), &["Plans".into()]);
+    ///
+    /// let link = &Link::parse("[[plans]]")[0];
+    /// assert_eq!(index.resolve(Path::new("vault/Inbox.md"), link), Some("vault/Ideas.md".into()));
+    /// ```
+    pub fn insert_aliases(&mut self, path: &Path, aliases: &[String]) {
+        aliases.iter().for_each(|alias| {
+            self.aliases
+                .entry(alias.to_lowercase())
+                .or_insert_with(|| path.to_path_buf());
+        });
+    }
+
+    /// Resolves and indexes the already parsed links of the note at the path.
+    pub(crate) fn insert_links(&mut self, path: &Path, links: Vec<Link>) {
+        let links = links
+            .into_iter()
+            .filter(|link| !link.is_external())
+            .map(|link| IndexedLink {
+                target: self.resolve(path, &link),
+                link,
+            })
+            .collect();
+
+        self.links.insert(path.to_path_buf(), links);
+    }
+
+    /// Indexes the files embedded in the file cards of the canvas at the path. Existing files of
+    /// the canvas are replaced.
+    pub fn insert_canvas(&mut self, path: &Path, canvas: &Canvas) {
+        let files = canvas
+            .nodes
+            .iter()
+            .filter_map(|node| match &node.kind {
+                CanvasNodeKind::File { file, .. } => Some(normalize(&self.vault_path.join(file))),
+                _ => None,
+            })
+            .collect();
+
+        self.canvases.insert(path.to_path_buf(), files);
+    }
+
+    /// Returns the indexed notes.
+    pub fn notes(&self) -> impl Iterator<Item = &Path> {
+        self.links.keys().map(PathBuf::as_path)
+    }
+
+    /// Returns the outgoing links of the note.
+    pub fn links(&self, note: &Path) -> &[IndexedLink] {
+        self.links.get(note).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns the notes linking to the given file.
+    pub fn backlinks(&self, path: &Path) -> Vec<&Path> {
+        self.links
+            .iter()
+            .filter(|(note, links)| {
+                note.as_path() != path
+                    && links
+                        .iter()
+                        .any(|link| link.target.as_deref() == Some(path))
+            })
+            .map(|(note, _)| note.as_path())
+            .collect()
+    }
+
+    /// Returns links that do not resolve to any file, together with the note they are in.
+    pub fn broken_links(&self) -> Vec<(&Path, &Link)> {
+        self.links
+            .iter()
+            .flat_map(|(note, links)| {
+                links
+                    .iter()
+                    .filter(|link| link.target.is_none())
+                    .map(|link| (note.as_path(), &link.link))
+            })
+            .collect()
+    }
+
+    /// Returns the number of notes linking to each linked file, most linked first.
+    pub fn link_counts(&self) -> Vec<(&Path, usize)> {
+        let mut counts: HashMap<&Path, usize> = HashMap::new();
+
+        self.links.iter().for_each(|(note, links)| {
+            links
+                .iter()
+                .filter_map(|link| link.target.as_deref())
+                .filter(|target| *target != note.as_path())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .for_each(|target| *counts.entry(target).or_default() += 1);
+        });
+
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|(a_path, a), (b_path, b)| b.cmp(a).then(a_path.cmp(b_path)));
+        counts
+    }
+
+    /// Returns the attachments, i.e. files that are neither notes nor canvases, that no note links
+    /// to and no canvas embeds, sorted by path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{LinkIndex, Note};
+    /// use std::path::Path;
+    ///
+    /// let mut index = LinkIndex::new(
+    ///     Path::new("vault"),
+    ///     vec!["vault/Note.md".into(), "vault/Used.png".into(), "vault/Unused.pdf".into()],
+    /// );
+    ///
+    /// index.insert(&Note::from(Path::new("vault/Note.md")), "![[Used.png]]");
+    ///
+    /// assert_eq!(index.unused_attachments(), vec![Path::new("vault/Unused.pdf")]);
+    /// ```
+    pub fn unused_attachments(&self) -> Vec<&Path> {
+        let used: HashSet<&Path> = self
+            .links
+            .values()
+            .flatten()
+            .filter_map(|link| link.target.as_deref())
+            .chain(self.canvases.values().flatten().map(PathBuf::as_path))
+            .collect();
+
+        let mut unused: Vec<_> = self
+            .files
+            .iter()
+            .map(PathBuf::as_path)
+            .filter(|path| is_attachment(path) && !used.contains(path))
+            .collect();
+        unused.sort();
+        unused
+    }
+
+    /// Returns links to attachments that do not exist, together with the note they are in.
+    /// Links count as attachment links when the target has an extension other than `.md` or
+    /// `.canvas`.
+    pub fn missing_attachments(&self) -> Vec<(&Path, &Link)> {
+        self.broken_links()
+            .into_iter()
+            .filter(|(_, link)| is_attachment(Path::new(link.target.trim())))
+            .collect()
+    }
+
+    /// Returns notes without any incoming or outgoing links.
+    pub fn orphans(&self) -> Vec<&Path> {
+        let linked: HashSet<&Path> = self
+            .links
+            .values()
+            .flatten()
+            .filter_map(|link| link.target.as_deref())
+            .collect();
+
+        self.links
+            .iter()
+            .filter(|(note, links)| links.is_empty() && !linked.contains(note.as_path()))
+            .map(|(note, _)| note.as_path())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(path: &str) -> Note {
+        Note {
+            name: Path::new(path)
+                .file_stem()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+            path: PathBuf::from("vault").join(path),
+        }
+    }
+
+    #[test]
+    fn test_link_index() {
+        let files = [
+            "Index.md",
+            "Orphan.md",
+            "Folder/Note.md",
+            "Folder/Sub/Deep.md",
+            "Assets/Image.png",
+        ];
+
+        let mut index = LinkIndex::new(
+            Path::new("vault"),
+            files.iter().map(|file| note(file).path).collect(),
+        );
+
+        index.insert(
+            &note("Index.md"),
+            "[[Note]] [[Folder/Sub/Deep|Deep]] ![[Image.png]] [[Missing]] [web](https://obsidian.md)",
+        );
+        index.insert(
+            &note("Folder/Note.md"),
+            "[Index](../Index.md) [Deep](Sub/Deep.md)",
+        );
+        index.insert(&note("Folder/Sub/Deep.md"), "");
+        index.insert(&note("Orphan.md"), "");
+
+        let targets: Vec<_> = index
+            .links(&note("Index.md").path)
+            .iter()
+            .map(|link| link.target.clone())
+            .collect();
+
+        assert_eq!(
+            targets,
+            vec![
+                Some(note("Folder/Note.md").path),
+                Some(note("Folder/Sub/Deep.md").path),
+                Some(note("Assets/Image.png").path),
+                None,
+            ]
+        );
+
+        assert_eq!(
+            index.links(&note("Folder/Note.md").path)[0].target,
+            Some(note("Index.md").path)
+        );
+
+        let deep = note("Folder/Sub/Deep.md").path;
+        assert_eq!(index.link_counts()[0], (deep.as_path(), 2));
+        assert_eq!(index.broken_links().len(), 1);
+        assert_eq!(index.orphans(), vec![note("Orphan.md").path.as_path()]);
+    }
+
+    #[test]
+    fn test_attachments() {
+        let files = [
+            "Index.md",
+            "Board.canvas",
+            "Assets/Linked.png",
+            "Assets/Embedded.pdf",
+            "Assets/Unused.jpg",
+        ];
+
+        let mut index = LinkIndex::new(
+            Path::new("vault"),
+            files.iter().map(|file| note(file).path).collect(),
+        );
+
+        index.insert(
+            &note("Index.md"),
+            "![[Linked.png]] ![[Missing.png]] [[Missing note]] [doc](Assets/Gone.pdf)",
+        );
+        index.insert_canvas(
+            &note("Board.canvas").path,
+            &r#"{ "nodes": [{ "id": "1", "type": "file", "file": "Assets/Embedded.pdf", "x": 0, "y": 0, "width": 1, "height": 1 }] }"#
+                .parse()
+                .unwrap(),
+        );
+
+        assert_eq!(
+            index.unused_attachments(),
+            vec![note("Assets/Unused.jpg").path.as_path()]
+        );
+
+        let missing: Vec<_> = index
+            .missing_attachments()
+            .into_iter()
+            .map(|(_, link)| link.target.as_str())
+            .collect();
+        assert_eq!(missing, ["Missing.png", "Assets/Gone.pdf"]);
+    }
+}
@@ -1,6 +1,36 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
 
-use crate::obsidian::{Error, Result};
+use crate::obsidian::{tag::frontmatter_range, Error, Result};
+
+/// Returns the path of the note with the name in the directory, appending `.md` to the name.
+fn file_path(dir: &Path, name: &str) -> PathBuf {
+    let mut path = dir.join(name).into_os_string();
+    path.push(".md");
+    path.into()
+}
+
+/// The reason a note opens read-only.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReadOnly {
+    /// The frontmatter sets the `readonly` property to `true`.
+    Frontmatter,
+    /// The file permissions do not allow writing to the note.
+    Permissions,
+}
+
+impl fmt::Display for ReadOnly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadOnly::Frontmatter => write!(f, "its frontmatter sets readonly: true"),
+            ReadOnly::Permissions => write!(f, "the file is not writable"),
+        }
+    }
+}
 
 /// Represents a single note (Markdown file) within a vault.
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -12,8 +42,33 @@ pub struct Note {
     pub path: PathBuf,
 }
 
+impl From<&Path> for Note {
+    /// Creates a note from the path of the markdown file, using the file name without the
+    /// extension as the note name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Note;
+    /// use std::path::Path;
+    ///
+    /// let note = Note::from(Path::new("path/to/Example.md"));
+    /// assert_eq!(note.name, "Example");
+    /// ```
+    fn from(path: &Path) -> Self {
+        Self {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: path.to_path_buf(),
+        }
+    }
+}
+
 impl Note {
-    /// Reads the note's contents from disk to a `String`.
+    /// Reads the note's contents from disk to a `String`. Invalid UTF-8 sequences are replaced with
+    /// U+FFFD, so a note with a few broken bytes still opens instead of reading as empty.
     ///
     /// # Examples
     ///
@@ -28,10 +83,14 @@ impl Note {
     /// _ = Note::read_to_string(&note);
     /// ```
     pub fn read_to_string(note: &Note) -> Result<String> {
-        fs::read_to_string(&note.path).map_err(Error::Io)
+        fs::read(&note.path)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .map_err(Error::Io)
     }
 
-    /// Replaces the content in the notes' markdown file with the given content.
+    /// Reads the note's contents from disk to a `String` without replacing invalid UTF-8, for
+    /// contents that are written back to the note. Returns an [`Error::InvalidUtf8`] if the note
+    /// is not valid UTF-8.
     ///
     /// # Examples
     ///
@@ -43,9 +102,403 @@ impl Note {
     ///     path: "path/to/Example.md".into(),
     /// };
     ///
+    /// assert!(Note::read_to_string_strict(&note).is_err());
+    /// ```
+    pub fn read_to_string_strict(note: &Note) -> Result<String> {
+        String::from_utf8(fs::read(&note.path)?).map_err(|_| Error::InvalidUtf8(note.path.clone()))
+    }
+
+    /// Replaces the content in the notes' markdown file with the given content.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use basalt_core::obsidian::Note;
+    ///
+    /// let note = Note {
+    ///     name: "Example".to_string(),
+    ///     path: "path/to/Example.md".into(),
+    /// };
+    ///
     /// _ = Note::write(&note, String::from("# Heading"));
     /// ```
     pub fn write(note: &Note, contents: String) -> Result<()> {
         fs::write(&note.path, contents).map_err(Error::Io)
     }
+
+    /// Creates a new empty note at the given path. Missing directories are created.
+    ///
+    /// Returns an [`Error`] if the file already exists.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use basalt_core::obsidian::Note;
+    /// use std::path::Path;
+    ///
+    /// _ = Note::create(Path::new("path/to/New note.md"));
+    /// ```
+    pub fn create(path: &Path) -> Result<Note> {
+        let name = path
+            .file_stem()
+            .ok_or_else(|| Error::EmptyFileName(path.to_path_buf()))?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        OpenOptions::new().write(true).create_new(true).open(path)?;
+
+        Ok(Note {
+            name: name.to_string_lossy().to_string(),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Deletes the note's markdown file from disk.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use basalt_core::obsidian::Note;
+    ///
+    /// let note = Note {
+    ///     name: "Example".to_string(),
+    ///     path: "path/to/Example.md".into(),
+    /// };
+    ///
+    /// _ = Note::delete(&note);
+    /// ```
+    pub fn delete(note: &Note) -> Result<()> {
+        fs::remove_file(&note.path).map_err(Error::Io)
+    }
+
+    /// Copies the note into the given directory as a new note with the given name. Missing
+    /// directories are created.
+    ///
+    /// Returns an [`Error`] if a note with the same name already exists in the directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use basalt_core::obsidian::Note;
+    /// use std::path::Path;
+    ///
+    /// let note = Note {
+    ///     name: "Example".to_string(),
+    ///     path: "path/to/Example.md".into(),
+    /// };
+    ///
+    /// _ = Note::copy_to(&note, Path::new("path/to"), "Example copy");
+    /// ```
+    pub fn copy_to(note: &Note, dir: &Path, name: &str) -> Result<Note> {
+        let path = Note::path_in(dir, name)?;
+        let contents = fs::read(&note.path)?;
+
+        fs::create_dir_all(dir)?;
+
+        // `create_new` makes sure we never overwrite an existing note.
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?
+            .write_all(&contents)?;
+
+        Ok(Note {
+            name: name.to_string(),
+            path,
+        })
+    }
+
+    /// Moves the note into the given directory keeping the note name. Missing directories are
+    /// created.
+    ///
+    /// Returns an [`Error`] if a note with the same name already exists in the directory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use basalt_core::obsidian::Note;
+    /// use std::path::Path;
+    ///
+    /// let note = Note {
+    ///     name: "Example".to_string(),
+    ///     path: "path/to/Example.md".into(),
+    /// };
+    ///
+    /// _ = Note::move_to(&note, Path::new("path/to/Archive"));
+    /// ```
+    pub fn move_to(note: &Note, dir: &Path) -> Result<Note> {
+        let file_name = note
+            .path
+            .file_name()
+            .ok_or_else(|| Error::EmptyFileName(note.path.clone()))?;
+        let path = dir.join(file_name);
+
+        if path.exists() {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            )));
+        }
+
+        fs::create_dir_all(dir)?;
+        fs::rename(&note.path, &path)?;
+
+        Ok(Note {
+            name: note.name.clone(),
+            path,
+        })
+    }
+
+    /// Returns the name of the note this note is a sync conflict copy of, or `None` if the note
+    /// is not a conflict copy.
+    ///
+    /// Recognized are Syncthing conflicts, e.g. `Note.sync-conflict-20250101-120000-ABCDEF`, and
+    /// conflicted copies of Obsidian Sync and Dropbox, e.g. `Note (conflicted copy 2025-01-01)`
+    /// or `Note (Laptop's conflicted copy)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Note;
+    ///
+    /// let conflict = Note {
+    ///     name: "Note.sync-conflict-20250101-120000-ABCDEF".to_string(),
+    ///     path: "path/to/Note.sync-conflict-20250101-120000-ABCDEF.md".into(),
+    /// };
+    ///
+    /// assert_eq!(conflict.conflict_of(), Some("Note"));
+    /// assert_eq!(Note::default().conflict_of(), None);
+    /// ```
+    pub fn conflict_of(&self) -> Option<&str> {
+        if let Some((name, _)) = self.name.split_once(".sync-conflict-") {
+            return Some(name);
+        }
+
+        let (name, suffix) = self.name.trim_end().strip_suffix(')')?.rsplit_once('(')?;
+
+        suffix
+            .to_lowercase()
+            .contains("conflict")
+            .then(|| name.trim_end())
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Returns why the note with the given contents is read-only, or `None` if it can be edited.
+    /// A note is read-only when its frontmatter sets `readonly: true`, or when the file
+    /// permissions do not allow writing to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{Note, ReadOnly};
+    ///
+    /// let note = Note::default();
+    ///
+    /// assert_eq!(
+    ///     Note::read_only(&note, "---\nreadonly: true\n---\n# Archive"),
+    ///     Some(ReadOnly::Frontmatter)
+    /// );
+    /// assert_eq!(Note::read_only(&note, "readonly: true"), None);
+    /// ```
+    pub fn read_only(note: &Note, contents: &str) -> Option<ReadOnly> {
+        let frontmatter = frontmatter_range(contents).map_or("", |range| &contents[range]);
+
+        let readonly = frontmatter.lines().any(|line| {
+            line.strip_prefix("readonly:")
+                .is_some_and(|value| value.trim().eq_ignore_ascii_case("true"))
+        });
+
+        if readonly {
+            return Some(ReadOnly::Frontmatter);
+        }
+
+        fs::metadata(&note.path)
+            .is_ok_and(|metadata| metadata.permissions().readonly())
+            .then_some(ReadOnly::Permissions)
+    }
+
+    /// Returns the values of the frontmatter property, written either inline as `key: value` and
+    /// `key: [a, b]`, or as a list with one `- value` per line. Quotes around the values are
+    /// removed. Returns an empty list if the note has no such property.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Note;
+    ///
+    /// let contents = "---\nstatus: \"reading\"\nauthors: [Herbert, Anderson]\ngenres:\n  - sci-fi\n---\n";
+    ///
+    /// assert_eq!(Note::property(contents, "status"), ["reading"]);
+    /// assert_eq!(Note::property(contents, "authors"), ["Herbert", "Anderson"]);
+    /// assert_eq!(Note::property(contents, "genres"), ["sci-fi"]);
+    /// assert!(Note::property(contents, "rating").is_empty());
+    /// ```
+    pub fn property(contents: &str, key: &str) -> Vec<String> {
+        let frontmatter = frontmatter_range(contents).map_or("", |range| &contents[range]);
+        let unquote = |value: &str| value.trim().trim_matches(['"', '\'']).trim().to_string();
+
+        let mut lines = frontmatter.lines().skip_while(|line| {
+            line.split_once(':')
+                .is_none_or(|(name, _)| name.trim_end() != key || line.starts_with(' '))
+        });
+
+        let Some((_, value)) = lines.next().and_then(|line| line.split_once(':')) else {
+            return vec![];
+        };
+
+        let value = value.trim();
+        let values: Vec<String> = match value.strip_prefix('[') {
+            Some(list) => list.trim_end_matches(']').split(',').map(unquote).collect(),
+            None if value.is_empty() => lines
+                .map_while(|line| line.trim_start().strip_prefix("- "))
+                .map(unquote)
+                .collect(),
+            None => vec![unquote(value)],
+        };
+
+        values
+            .into_iter()
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+
+    /// Returns the path of the note with the given name in the directory. The `.md` extension is
+    /// appended to the name, so that dots in the name are kept, e.g. `v1.2 notes.md`.
+    ///
+    /// Returns an [`Error::InvalidName`] if the name contains a path separator or is `.` or `..`.
+    /// Dots elsewhere in the name are allowed, e.g. `Wait... v1.2`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Note;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// assert_eq!(
+    ///     Note::path_in(Path::new("vault"), "v1.2 notes").unwrap(),
+    ///     PathBuf::from("vault/v1.2 notes.md")
+    /// );
+    /// assert_eq!(
+    ///     Note::path_in(Path::new("vault"), "Notes..draft").unwrap(),
+    ///     PathBuf::from("vault/Notes..draft.md")
+    /// );
+    /// assert!(Note::path_in(Path::new("vault"), "../Escape").is_err());
+    /// assert!(Note::path_in(Path::new("vault"), "..").is_err());
+    /// ```
+    pub fn path_in(dir: &Path, name: &str) -> Result<PathBuf> {
+        match name.contains(['/', '\\']) || name == "." || name == ".." {
+            true => Err(Error::InvalidName(name.to_string())),
+            false => Ok(file_path(dir, name)),
+        }
+    }
+
+    /// Returns a note name based on `name` that does not collide with an existing note in the
+    /// given directory. A running number is appended to the name when required, e.g. `Note 2`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Note;
+    /// use std::path::Path;
+    ///
+    /// assert_eq!(Note::available_name(Path::new("./nonexistent"), "Note"), "Note");
+    /// ```
+    pub fn available_name(dir: &Path, name: &str) -> String {
+        (1..)
+            .map(|n| match n {
+                1 => name.to_string(),
+                n => format!("{name} {n}"),
+            })
+            .find(|name| !file_path(dir, name).exists())
+            .unwrap_or_else(|| name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_to() {
+        let dir = std::env::temp_dir().join("basalt_core_note_copy_to");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let note = Note {
+            name: "Note".into(),
+            path: dir.join("Note.md"),
+        };
+        Note::write(&note, "# Note".into()).unwrap();
+
+        let name = Note::available_name(&dir, "Note");
+        assert_eq!(name, "Note 2");
+
+        let copy = Note::copy_to(&note, &dir.join("Templates"), "Note").unwrap();
+        assert_eq!(copy.path, dir.join("Templates/Note.md"));
+        assert_eq!(Note::read_to_string(&copy).unwrap(), "# Note");
+
+        assert!(Note::copy_to(&note, &dir, "Note").is_err());
+
+        // Dots in the name are kept, and names of other folders are rejected.
+        let copy = Note::copy_to(&note, &dir, "v1.2 notes").unwrap();
+        assert_eq!(copy.path, dir.join("v1.2 notes.md"));
+        assert_eq!(Note::available_name(&dir, "v1.2 notes"), "v1.2 notes 2");
+        assert_eq!(Note::available_name(&dir, "v1.3 notes"), "v1.3 notes");
+        let copy = Note::copy_to(&note, &dir, "Wait... v1.2").unwrap();
+        assert_eq!(copy.path, dir.join("Wait... v1.2.md"));
+        for name in ["../Note", "Folder/Note", ".", "..", "Folder\\Note"] {
+            assert!(matches!(
+                Note::copy_to(&note, &dir, name),
+                Err(Error::InvalidName(_))
+            ));
+        }
+
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_invalid_utf8() {
+        let dir = std::env::temp_dir().join("basalt_core_note_invalid_utf8");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let note = Note::create(&dir.join("Note.md")).unwrap();
+        fs::write(&note.path, b"# Caf\xe9\n\nText\xff\n").unwrap();
+        assert_eq!(
+            Note::read_to_string(&note).unwrap(),
+            "# Caf\u{FFFD}\n\nText\u{FFFD}\n"
+        );
+        assert!(matches!(
+            Note::read_to_string_strict(&note),
+            Err(Error::InvalidUtf8(path)) if path == note.path
+        ));
+
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_only() {
+        let dir = std::env::temp_dir().join("basalt_core_note_read_only");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let note = Note::create(&dir.join("Note.md")).unwrap();
+        assert_eq!(Note::read_only(&note, "---\nreadonly: false\n---\n"), None);
+        assert_eq!(
+            Note::read_only(&note, "---\ntags: [archive]\nreadonly: True\n---\n"),
+            Some(ReadOnly::Frontmatter)
+        );
+
+        let mut permissions = fs::metadata(&note.path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&note.path, permissions).unwrap();
+        assert_eq!(
+            Note::read_only(&note, "# Note"),
+            Some(ReadOnly::Permissions)
+        );
+
+        _ = fs::remove_dir_all(&dir);
+    }
 }
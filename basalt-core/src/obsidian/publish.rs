@@ -0,0 +1,512 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use pulldown_cmark::{html, Options, Parser};
+
+use super::{tag::frontmatter_range, Link, LinkIndex, LinkKind, Note, Result, Tag, Vault};
+
+/// Template used when no custom template is configured.
+pub const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <title>{{title}}</title>
+  <style>
+    body { max-width: 48rem; margin: 2rem auto; padding: 0 1rem; font-family: sans-serif; line-height: 1.6; }
+    img { max-width: 100%; }
+    pre { overflow-x: auto; }
+  </style>
+</head>
+<body>
+  <nav><a href="{{root}}/index.html">Index</a></nav>
+  <h1>{{title}}</h1>
+  {{content}}
+</body>
+</html>
+"#;
+
+/// Selects the notes that are published.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum PublishFilter {
+    /// Every note in the vault.
+    #[default]
+    All,
+    /// Notes in the folder, relative to the vault root, and its subfolders.
+    Folder(PathBuf),
+    /// Notes with the tag or a tag nested under it.
+    Tag(String),
+}
+
+impl From<&str> for PublishFilter {
+    /// Parses the filter as written by the user: empty for all notes, `#tag` for a tag and
+    /// anything else for a folder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::PublishFilter;
+    ///
+    /// assert_eq!(PublishFilter::from(""), PublishFilter::All);
+    /// assert_eq!(PublishFilter::from("#blog"), PublishFilter::Tag("blog".into()));
+    /// assert_eq!(PublishFilter::from("Blog/"), PublishFilter::Folder("Blog".into()));
+    /// ```
+    fn from(value: &str) -> Self {
+        let value = value.trim();
+
+        match value.strip_prefix('#') {
+            _ if value.trim_matches('/').is_empty() => PublishFilter::All,
+            Some(tag) => PublishFilter::Tag(tag.to_string()),
+            None => PublishFilter::Folder(value.trim_matches('/').into()),
+        }
+    }
+}
+
+/// Options for [`publish`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublishOptions {
+    /// Directory the site is written to. Created if it does not exist.
+    pub output_dir: PathBuf,
+
+    /// Selects the published notes.
+    pub filter: PublishFilter,
+
+    /// HTML template of each page. `{{title}}` is replaced with the note name, `{{content}}`
+    /// with the rendered note and `{{root}}` with the relative path to the output directory.
+    pub template: String,
+}
+
+/// Summary of a finished [`publish`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PublishReport {
+    /// Number of published notes.
+    pub notes: usize,
+
+    /// Number of copied attachments.
+    pub attachments: usize,
+
+    /// Directory the site was written to.
+    pub output_dir: PathBuf,
+}
+
+fn is_note(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "md")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn encode_url_segment(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|c| match c {
+            ' ' => "%20".to_string(),
+            '#' => "%23".to_string(),
+            '%' => "%25".to_string(),
+            '(' => "%28".to_string(),
+            ')' => "%29".to_string(),
+            '?' => "%3F".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Returns the vault relative path of the published file, notes get the `.html` extension.
+fn output_path(relative: &Path) -> PathBuf {
+    match is_note(relative) {
+        true => relative.with_extension("html"),
+        false => relative.to_path_buf(),
+    }
+}
+
+/// Returns the URL of `to` relative to the page of `from`, both relative to the vault root.
+fn relative_url(from: &Path, to: &Path) -> String {
+    let names = |path: &Path| -> Vec<String> {
+        path.components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let from_dir = from.parent().map(names).unwrap_or_default();
+    let to = names(&output_path(to));
+
+    let common = from_dir.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    std::iter::repeat_n("..".to_string(), from_dir.len() - common)
+        .chain(to[common..].iter().map(|name| encode_url_segment(name)))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Rewrites the links of the note to point to the published pages and attachments. Links to
+/// notes that are not published, and links that do not resolve, are replaced with their text.
+/// Returns the rewritten text and the linked attachments.
+fn rewrite_links(
+    text: &str,
+    note: &Path,
+    vault_path: &Path,
+    index: &LinkIndex,
+    published: &HashSet<PathBuf>,
+) -> (String, Vec<PathBuf>) {
+    let relative = |path: &Path| path.strip_prefix(vault_path).unwrap_or(path).to_path_buf();
+    let from = relative(note);
+
+    let mut attachments = vec![];
+    let mut rewritten = String::with_capacity(text.len());
+    let mut cursor = 0;
+
+    for link in Link::parse(text) {
+        if link.is_external() || link.range.start < cursor {
+            continue;
+        }
+
+        let url = match index.resolve(note, &link) {
+            Some(target) if is_note(&target) && published.contains(&target) => {
+                Some(relative_url(&from, &relative(&target)))
+            }
+            Some(target) if !is_note(&target) => {
+                let url = relative_url(&from, &relative(&target));
+                attachments.push(target);
+                Some(url)
+            }
+            _ => None,
+        };
+
+        match link.kind {
+            LinkKind::Wiki => {
+                let Some(end) = text[link.range.start..]
+                    .find("]]")
+                    .map(|i| i + link.range.start)
+                else {
+                    continue;
+                };
+
+                let mut start = link.range.start - 2;
+                let embed = text[..start].ends_with('!');
+                if embed {
+                    start -= 1;
+                }
+
+                let display = text[link.range.start..end]
+                    .split_once('|')
+                    .map_or(link.target.as_str(), |(_, alias)| alias.trim());
+
+                rewritten.push_str(&text[cursor..start]);
+                match url {
+                    Some(url) if embed => rewritten.push_str(&format!("![{display}]({url})")),
+                    Some(url) => rewritten.push_str(&format!("[{display}]({url})")),
+                    None => rewritten.push_str(display),
+                }
+                cursor = end + 2;
+            }
+            LinkKind::Markdown => match url {
+                Some(url) => {
+                    rewritten.push_str(&text[cursor..link.range.start]);
+                    rewritten.push_str(&url);
+                    cursor = link.range.end;
+                }
+                None => {
+                    let text_end = link.range.start - 2;
+                    let (Some(start), Some(end)) = (
+                        text[cursor..text_end].rfind('[').map(|i| i + cursor),
+                        text[link.range.end..].find(')').map(|i| i + link.range.end),
+                    ) else {
+                        continue;
+                    };
+
+                    let start = match text[..start].ends_with('!') {
+                        true => start - 1,
+                        false => start,
+                    };
+
+                    rewritten.push_str(&text[cursor..start]);
+                    rewritten.push_str(text[start..text_end].trim_start_matches(['!', '[']));
+                    cursor = end + 1;
+                }
+            },
+        }
+    }
+
+    rewritten.push_str(&text[cursor..]);
+
+    (rewritten, attachments)
+}
+
+fn render_page(template: &str, title: &str, content: &str, root: &str) -> String {
+    template
+        .replace("{{title}}", &escape_html(title))
+        .replace("{{root}}", root)
+        .replace("{{content}}", content)
+}
+
+fn root_url(relative: &Path) -> String {
+    match relative.components().count().saturating_sub(1) {
+        0 => ".".to_string(),
+        depth => vec![".."; depth].join("/"),
+    }
+}
+
+/// Exports the notes selected by the filter to HTML pages in the output directory, together
+/// with the attachments they link to. Folder structure of the vault is kept, and an
+/// `index.html` listing the published notes is written to the root of the output directory.
+///
+/// The `progress` callback is called after each published note with the number of published
+/// notes and the total number of notes to publish.
+///
+/// # Examples
+///
+/// ```no_run
+/// use basalt_core::obsidian::{publish, PublishFilter, PublishOptions, Vault, DEFAULT_TEMPLATE};
+///
+/// let vault = Vault {
+///     name: "MyVault".into(),
+///     path: "path/to/my_vault".into(),
+///     ..Default::default()
+/// };
+///
+/// let options = PublishOptions {
+///     output_dir: "path/to/site".into(),
+///     filter: PublishFilter::Folder("Blog".into()),
+///     template: DEFAULT_TEMPLATE.into(),
+/// };
+///
+/// let report = publish(&vault, &options, |done, total| println!("{done}/{total}")).unwrap();
+/// println!("Published {} notes to {}", report.notes, report.output_dir.display());
+/// ```
+pub fn publish(
+    vault: &Vault,
+    options: &PublishOptions,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<PublishReport> {
+    let relative = |path: &Path| path.strip_prefix(&vault.path).unwrap_or(path).to_path_buf();
+
+    let files: Vec<PathBuf> = vault
+        .files()
+        .into_iter()
+        .filter(|path| !path.starts_with(&options.output_dir))
+        .collect();
+
+    let index = LinkIndex::new(&vault.path, files.clone());
+
+    let mut notes = vec![];
+    for path in files.iter().filter(|path| is_note(path)) {
+        let note = Note::from(path.as_path());
+
+        let selected = match &options.filter {
+            PublishFilter::All => true,
+            PublishFilter::Folder(folder) => relative(path).starts_with(folder),
+            PublishFilter::Tag(tag) => Tag::parse(&Note::read_to_string(&note)?)
+                .iter()
+                .any(|note_tag| note_tag.matches(tag)),
+        };
+
+        if selected {
+            notes.push(note);
+        }
+    }
+
+    let published: HashSet<PathBuf> = notes.iter().map(|note| note.path.clone()).collect();
+    let mut attachments = BTreeSet::new();
+    let markdown_options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+
+    for (i, note) in notes.iter().enumerate() {
+        let contents = Note::read_to_string(note)?;
+        let body =
+            frontmatter_range(&contents).map_or(contents.as_str(), |range| &contents[range.end..]);
+
+        let (body, linked) = rewrite_links(body, &note.path, &vault.path, &index, &published);
+        attachments.extend(linked);
+
+        let mut content = String::new();
+        html::push_html(&mut content, Parser::new_ext(&body, markdown_options));
+
+        let relative = relative(&note.path);
+        let path = options.output_dir.join(output_path(&relative));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(
+            path,
+            render_page(
+                &options.template,
+                &note.name,
+                &content,
+                &root_url(&relative),
+            ),
+        )?;
+
+        progress(i + 1, notes.len());
+    }
+
+    for attachment in &attachments {
+        let path = options.output_dir.join(relative(attachment));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(attachment, path)?;
+    }
+
+    let items: String = notes
+        .iter()
+        .map(|note| {
+            let relative = relative(&note.path);
+            format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                relative_url(Path::new("index.html"), &relative),
+                escape_html(&relative.with_extension("").to_string_lossy())
+            )
+        })
+        .collect();
+
+    fs::create_dir_all(&options.output_dir)?;
+    fs::write(
+        options.output_dir.join("index.html"),
+        render_page(
+            &options.template,
+            &vault.name,
+            &format!("<ul>\n{items}</ul>\n"),
+            ".",
+        ),
+    )?;
+
+    Ok(PublishReport {
+        notes: notes.len(),
+        attachments: attachments.len(),
+        output_dir: options.output_dir.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_rewrite_links() {
+        let vault_path = Path::new("vault");
+        let index = LinkIndex::new(
+            vault_path,
+            vec![
+                "vault/Blog/Post.md".into(),
+                "vault/Blog/Other post.md".into(),
+                "vault/Private.md".into(),
+                "vault/Assets/Image.png".into(),
+            ],
+        );
+
+        let published = HashSet::from([
+            "vault/Blog/Post.md".into(),
+            "vault/Blog/Other post.md".into(),
+        ]);
+
+        let text = indoc! {"
+            [[Other post|Next]] [[Private]] [[Missing#Heading]]
+            ![[Image.png]] [back](Post.md) [secret](../Private.md) `[[Code]]`
+        "};
+
+        let (rewritten, attachments) = rewrite_links(
+            text,
+            Path::new("vault/Blog/Post.md"),
+            vault_path,
+            &index,
+            &published,
+        );
+
+        assert_eq!(
+            rewritten,
+            indoc! {"
+                [Next](Other%20post.html) Private Missing
+                ![Image.png](../Assets/Image.png) [back](Post.html) secret `[[Code]]`
+            "}
+        );
+        assert_eq!(attachments, vec![PathBuf::from("vault/Assets/Image.png")]);
+    }
+
+    #[test]
+    fn test_publish() {
+        let dir = std::env::temp_dir().join("basalt_core_publish");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("Vault/Blog")).unwrap();
+        fs::create_dir_all(dir.join("Vault/Assets")).unwrap();
+
+        fs::write(
+            dir.join("Vault/Blog/Post.md"),
+            "---\ntags: [blog]\n---\n[[Other post]] [[Private]] ![[Image.png]]\n",
+        )
+        .unwrap();
+        fs::write(dir.join("Vault/Blog/Other post.md"), "[[Post]]\n").unwrap();
+        fs::write(dir.join("Vault/Private.md"), "#blog ![[Secret.png]]\n").unwrap();
+        fs::write(dir.join("Vault/Assets/Image.png"), "image").unwrap();
+        fs::write(dir.join("Vault/Assets/Secret.png"), "secret").unwrap();
+
+        let vault = Vault {
+            name: "Vault".into(),
+            path: dir.join("Vault"),
+            ..Default::default()
+        };
+
+        let mut options = PublishOptions {
+            output_dir: dir.join("Site"),
+            filter: PublishFilter::Folder("Blog".into()),
+            template: "<title>{{title}}</title><a href=\"{{root}}\"></a>{{content}}".into(),
+        };
+
+        let mut progress = vec![];
+        let report = publish(&vault, &options, |done, total| progress.push((done, total))).unwrap();
+
+        assert_eq!(
+            report,
+            PublishReport {
+                notes: 2,
+                attachments: 1,
+                output_dir: dir.join("Site"),
+            }
+        );
+        assert_eq!(progress, [(1, 2), (2, 2)]);
+        assert_eq!(
+            fs::read_to_string(dir.join("Site/Blog/Post.html")).unwrap(),
+            indoc! {r#"
+                <title>Post</title><a href=".."></a><p><a href="Other%20post.html">Other post</a> Private <img src="../Assets/Image.png" alt="Image.png" /></p>
+            "#}
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("Site/Assets/Image.png")).unwrap(),
+            "image"
+        );
+        assert!(!dir.join("Site/Private.html").exists());
+        assert!(!dir.join("Site/Assets/Secret.png").exists());
+
+        let index = fs::read_to_string(dir.join("Site/index.html")).unwrap();
+        assert!(index.starts_with("<title>Vault</title><a href=\".\"></a><ul>"));
+        assert!(index.contains("<li><a href=\"Blog/Post.html\">Blog/Post</a></li>"));
+        assert!(index.contains("<li><a href=\"Blog/Other%20post.html\">Blog/Other post</a></li>"));
+        assert!(!index.contains("Private"));
+
+        _ = fs::remove_dir_all(dir.join("Site"));
+        options.filter = PublishFilter::Tag("blog".into());
+
+        let report = publish(&vault, &options, |_, _| {}).unwrap();
+        assert_eq!((report.notes, report.attachments), (2, 2));
+        assert!(dir.join("Site/Blog/Post.html").exists());
+        assert!(dir.join("Site/Private.html").exists());
+        assert!(!dir.join("Site/Blog/Other post.html").exists());
+        assert!(dir.join("Site/Assets/Secret.png").exists());
+
+        _ = fs::remove_dir_all(&dir);
+    }
+}
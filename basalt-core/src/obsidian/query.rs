@@ -0,0 +1,222 @@
+use std::path::Path;
+
+use super::{link::code_ranges, Note, Tag};
+
+/// Which tasks a [`Filter::Task`] looks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskState {
+    /// Any task, done or not.
+    Any,
+    /// Tasks that are not done, `- [ ]`.
+    Open,
+    /// Tasks that are done, `- [x]`.
+    Done,
+}
+
+/// A single condition of a [`Query`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Notes whose name or contents contain the text, ignoring case.
+    Text(String),
+    /// Notes with the tag or a tag nested under it, written `tag:name` or `tag:#name`.
+    Tag(String),
+    /// Notes whose path relative to the vault root contains the text, ignoring case, written
+    /// `path:text`.
+    Path(String),
+    /// Notes with tasks, written `task:open`, `task:done` or `task:any`.
+    Task(TaskState),
+    /// Notes whose frontmatter property has the value, ignoring case, written `key:value`, e.g.
+    /// `status:reading`. A property with a list of values matches if one of them is the value, and
+    /// `key:` matches notes with the property.
+    Property(String, String),
+    /// Notes that do not match the filter, written with a leading `-`, e.g. `-tag:draft`.
+    Not(Box<Filter>),
+}
+
+/// Returns `Some(true)` for done tasks, `Some(false)` for open tasks and `None` for other lines.
+fn task_state(line: &str) -> Option<bool> {
+    let line = line.trim_start();
+    let rest = match line.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+            (rest.len() < line.len())
+                .then_some(rest)?
+                .strip_prefix(['.', ')'])?
+        }
+    };
+
+    match rest.strip_prefix(' ')?.get(..3)? {
+        "[ ]" => Some(false),
+        "[x]" | "[X]" => Some(true),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the text has a task in the state, outside of code blocks.
+fn has_task(text: &str, state: TaskState) -> bool {
+    let code = code_ranges(text);
+    let mut offset = 0;
+
+    text.split_inclusive('\n').any(|line| {
+        let start = offset;
+        offset += line.len();
+
+        if code.iter().any(|range| range.contains(&start)) {
+            return false;
+        }
+
+        match (task_state(line), state) {
+            (None, _) => false,
+            (Some(_), TaskState::Any) => true,
+            (Some(done), TaskState::Done) => done,
+            (Some(done), TaskState::Open) => !done,
+        }
+    })
+}
+
+impl Filter {
+    /// Parses a single term of a query.
+    fn parse(term: &str) -> Self {
+        if let Some(term) = term.strip_prefix('-').filter(|term| !term.is_empty()) {
+            return Self::Not(Box::new(Self::parse(term)));
+        }
+
+        match term.split_once(':') {
+            Some(("tag", tag)) => Self::Tag(tag.trim_start_matches('#').to_string()),
+            Some(("path", path)) => Self::Path(path.to_lowercase()),
+            Some(("task", state)) => Self::Task(match state {
+                "open" | "todo" => TaskState::Open,
+                "done" => TaskState::Done,
+                _ => TaskState::Any,
+            }),
+            Some((key, value)) if !key.is_empty() && !key.starts_with('"') => {
+                Self::Property(key.to_string(), value.trim_matches('"').to_string())
+            }
+            _ => Self::Text(term.trim_matches('"').to_lowercase()),
+        }
+    }
+
+    fn matches(&self, path: &Path, contents: &str) -> bool {
+        match self {
+            Self::Text(text) => {
+                let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                name.to_lowercase().contains(text) || contents.to_lowercase().contains(text)
+            }
+            Self::Tag(tag) => Tag::parse(contents).iter().any(|found| found.matches(tag)),
+            Self::Path(text) => path.to_string_lossy().to_lowercase().contains(text),
+            Self::Task(state) => has_task(contents, *state),
+            Self::Property(key, value) => {
+                let values = Note::property(contents, key);
+                match value.is_empty() {
+                    true => !values.is_empty(),
+                    false => values.iter().any(|found| found.eq_ignore_ascii_case(value)),
+                }
+            }
+            Self::Not(filter) => !filter.matches(path, contents),
+        }
+    }
+}
+
+/// A query that selects notes by their text, tags, path, tasks and properties, e.g.
+/// `tag:book -task:done "reading list"`.
+///
+/// Terms are separated by whitespace and every term must match. Text in double quotes is a single
+/// term. A leading `-` negates a term.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    /// The filters of the terms, all of which must match.
+    pub filters: Vec<Filter>,
+}
+
+impl From<&str> for Query {
+    /// Parses the query as written by the user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{Filter, Query, TaskState};
+    ///
+    /// assert_eq!(
+    ///     Query::from(r#"tag:#book -task:done "Reading list""#).filters,
+    ///     vec![
+    ///         Filter::Tag("book".into()),
+    ///         Filter::Not(Box::new(Filter::Task(TaskState::Done))),
+    ///         Filter::Text("reading list".into()),
+    ///     ]
+    /// );
+    /// ```
+    fn from(value: &str) -> Self {
+        let mut terms = vec![];
+        let mut term = String::new();
+        let mut quoted = false;
+
+        for c in value.chars() {
+            match c {
+                '"' => {
+                    quoted = !quoted;
+                    term.push(c);
+                }
+                c if c.is_whitespace() && !quoted => terms.push(std::mem::take(&mut term)),
+                c => term.push(c),
+            }
+        }
+        terms.push(term);
+
+        Self {
+            filters: terms
+                .iter()
+                .filter(|term| !term.trim_matches('"').is_empty())
+                .map(|term| Filter::parse(term))
+                .collect(),
+        }
+    }
+}
+
+impl Query {
+    /// Returns `true` if the note matches every filter of the query. The path is relative to the
+    /// vault root. An empty query matches every note.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    /// use basalt_core::obsidian::Query;
+    ///
+    /// let query = Query::from("tag:book path:reading task:open");
+    /// let contents = "#book/fiction\n\n- [x] Buy\n- [ ] Read\n";
+    ///
+    /// assert!(query.matches(Path::new("Reading/Dune.md"), contents));
+    /// assert!(!query.matches(Path::new("Archive/Dune.md"), contents));
+    /// ```
+    pub fn matches(&self, path: &Path, contents: &str) -> bool {
+        self.filters
+            .iter()
+            .all(|filter| filter.matches(path, contents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let path = Path::new("Books/Dune.md");
+        let contents = "---\ntags: [book]\n---\nA desert planet.\n\n```\n- [ ] Not a task\n```\n\n1. [x] Read\n";
+
+        let matches = |query: &str| Query::from(query).matches(path, contents);
+
+        assert!(matches(""));
+        assert!(matches("dune DESERT"));
+        assert!(matches("tag:#book task:done"));
+        assert!(matches("-task:open -tag:book/fiction"));
+        assert!(matches("\"desert planet\" path:books/"));
+        assert!(matches("tags:BOOK"));
+        assert!(!matches("\"planet desert\""));
+        assert!(!matches("tag:boo"));
+        assert!(!matches("-tag:book"));
+        assert!(!matches("path:archive"));
+        assert!(!matches("status:"));
+    }
+}
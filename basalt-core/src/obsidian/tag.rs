@@ -0,0 +1,234 @@
+use std::ops::Range;
+
+use super::link::code_ranges;
+
+/// A tag found in note contents, either inline, e.g. `#project/basalt`, or in the `tags`
+/// property of the frontmatter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    /// The tag name without the leading `#`, e.g. `project/basalt`.
+    pub name: String,
+
+    /// Byte range of the name in the source text.
+    pub range: Range<usize>,
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || "_-/".contains(c)
+}
+
+/// Returns the byte range of the frontmatter block, including the `---` delimiters.
+pub(crate) fn frontmatter_range(text: &str) -> Option<Range<usize>> {
+    let rest = text.strip_prefix("---\n")?;
+    let mut offset = 4;
+
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return Some(0..offset);
+        }
+    }
+
+    None
+}
+
+/// Parses the values of the `tags` property, written either inline as `tags: [a, b]` and
+/// `tags: a, b`, or as a list with one `- tag` per line.
+fn frontmatter_tags(text: &str, range: Range<usize>) -> Vec<Tag> {
+    let mut tags = vec![];
+    let mut in_tags = false;
+    let mut offset = range.start;
+
+    let mut push = |value: &str, start: usize| {
+        let trimmed = value.trim_matches(|c: char| c.is_whitespace() || "[]\"',#".contains(c));
+        if !trimmed.is_empty() {
+            let start = start + value.find(trimmed).unwrap_or_default();
+            tags.push(Tag {
+                name: trimmed.to_string(),
+                range: start..start + trimmed.len(),
+            });
+        }
+    };
+
+    for line in text[range].split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        if let Some(values) = line.strip_prefix("tags:") {
+            in_tags = true;
+            let mut value_start = start + "tags:".len();
+            for value in values.split(',') {
+                push(value, value_start);
+                value_start += value.len() + 1;
+            }
+            continue;
+        }
+
+        match line.trim_start().strip_prefix("- ") {
+            Some(value) if in_tags && line.starts_with([' ', '-']) => {
+                push(value, start + line.len() - line.trim_start().len() + 2)
+            }
+            _ => in_tags = false,
+        }
+    }
+
+    tags
+}
+
+impl Tag {
+    /// Finds all tags in the given text. Tags inside code are ignored, as well as `#` followed by
+    /// digits only, e.g. `#1`, since Obsidian does not consider those tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Tag;
+    ///
+    /// let tags = Tag::parse("---\ntags: [daily]\n---\nSee #project/basalt and `#code`.");
+    ///
+    /// assert_eq!(
+    ///     tags.iter().map(|tag| tag.name.as_str()).collect::<Vec<_>>(),
+    ///     vec!["daily", "project/basalt"],
+    /// );
+    /// ```
+    pub fn parse(text: &str) -> Vec<Tag> {
+        let frontmatter = frontmatter_range(text);
+        let code = code_ranges(text);
+
+        let mut tags = frontmatter
+            .clone()
+            .map(|range| frontmatter_tags(text, range))
+            .unwrap_or_default();
+
+        let body_start = frontmatter.map_or(0, |range| range.end);
+
+        for (index, _) in text[body_start..].match_indices('#') {
+            let index = body_start + index;
+            let preceded_by_space = text[..index]
+                .chars()
+                .next_back()
+                .is_none_or(char::is_whitespace);
+
+            if !preceded_by_space || code.iter().any(|range| range.contains(&index)) {
+                continue;
+            }
+
+            let start = index + 1;
+            let len = text[start..]
+                .find(|c: char| !is_tag_char(c))
+                .unwrap_or(text.len() - start);
+            let name = &text[start..start + len];
+
+            if name.chars().all(|c| c.is_numeric() || c == '/') {
+                continue;
+            }
+
+            tags.push(Tag {
+                name: name.to_string(),
+                range: start..start + len,
+            });
+        }
+
+        tags
+    }
+
+    /// Returns `true` if the tag is the given tag or nested under it, e.g. `project/basalt` matches
+    /// `project`. Tags are case-insensitive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Tag;
+    ///
+    /// let tag = &Tag::parse("#Project/Basalt")[0];
+    ///
+    /// assert!(tag.matches("project"));
+    /// assert!(tag.matches("project/basalt"));
+    /// assert!(!tag.matches("proj"));
+    /// ```
+    pub fn matches(&self, tag: &str) -> bool {
+        let name = self.name.to_lowercase();
+        let tag = tag.trim_start_matches('#').to_lowercase();
+
+        name == tag || name.starts_with(&format!("{tag}/"))
+    }
+
+    /// Returns the name of the tag after renaming the tag `from` to `to`, or `None` if the tag does
+    /// not match `from`. Nested tags keep their place under the renamed tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Tag;
+    ///
+    /// let tag = &Tag::parse("#project/basalt")[0];
+    ///
+    /// assert_eq!(tag.renamed("Project", "work/code"), Some("work/code/basalt".to_string()));
+    /// assert_eq!(tag.renamed("project/basalt", "basalt"), Some("basalt".to_string()));
+    /// assert_eq!(tag.renamed("idea", "ideas"), None);
+    /// ```
+    pub fn renamed(&self, from: &str, to: &str) -> Option<String> {
+        let from = from.trim_start_matches('#');
+        let to = to.trim_start_matches('#');
+
+        self.matches(from)
+            .then(|| format!("{to}{}", self.name.get(from.len()..).unwrap_or_default()))
+    }
+
+    /// Returns `true` if the name can be used as a tag: letters, digits, `_`, `-` and `/` for
+    /// nested tags, with at least one character that is not a digit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Tag;
+    ///
+    /// assert!(Tag::is_valid_name("project/2024"));
+    /// assert!(!Tag::is_valid_name("2024"));
+    /// assert!(!Tag::is_valid_name("my project"));
+    /// ```
+    pub fn is_valid_name(name: &str) -> bool {
+        name.chars().all(is_tag_char) && !name.chars().all(|c| c.is_numeric() || c == '/')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = indoc! {"
+            ---
+            aliases: [Other]
+            tags:
+              - daily
+              - work/meeting
+            ---
+            # Heading #inline
+
+            Text #tag, issue#1 and #2024 [link](Note.md#heading)
+
+            ```
+            #code
+            ```
+        "};
+
+        let tags: Vec<_> = Tag::parse(text)
+            .into_iter()
+            .map(|tag| (text[tag.range].to_string(), tag.name))
+            .collect();
+
+        assert_eq!(
+            tags,
+            vec![
+                ("daily".into(), "daily".into()),
+                ("work/meeting".into(), "work/meeting".into()),
+                ("inline".into(), "inline".into()),
+                ("tag".into(), "tag".into()),
+            ]
+        );
+    }
+}
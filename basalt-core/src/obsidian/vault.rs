@@ -1,8 +1,35 @@
-use std::{path::PathBuf, result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    result,
+};
 
 use serde::{Deserialize, Deserializer};
 
-use super::vault_entry::VaultEntry;
+use super::{
+    link::{normalize, percent_encode},
+    vault_entry::VaultEntry,
+    Error, Link, LinkKind, Note, Result,
+};
+
+fn flatten_files(entries: Vec<VaultEntry>) -> Vec<Note> {
+    entries
+        .into_iter()
+        .flat_map(|entry| match entry {
+            VaultEntry::File(note) => vec![note],
+            VaultEntry::Directory { entries, .. } => flatten_files(entries),
+        })
+        .collect()
+}
+
+/// Notes changed by [`Vault::update_links`] and [`Vault::rename_links`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkUpdate {
+    /// Number of notes whose links were rewritten.
+    pub modified: usize,
+    /// Notes that were left alone, as they are not valid UTF-8.
+    pub skipped: Vec<PathBuf>,
+}
 
 /// Represents a single Obsidian vault.
 ///
@@ -43,13 +70,340 @@ impl Vault {
     /// assert_eq!(vault.entries(), vec![]);
     /// ```
     pub fn entries(&self) -> Vec<VaultEntry> {
-        match self.path.as_path().try_into() {
-            Ok(VaultEntry::Directory { entries, .. }) => entries
-                .into_iter()
-                .filter(|entry| !entry.name().starts_with('.'))
-                .collect(),
-            _ => vec![],
+        self.entries_with_progress(|_, _| {})
+    }
+
+    /// Returns the vault entries like [`Vault::entries`]. The `progress` callback is called after
+    /// each top-level entry is read with the number of read entries and the total number of
+    /// top-level entries, as the size of the nested folders is not known up front.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs;
+    ///
+    /// use basalt_core::obsidian::Vault;
+    ///
+    /// let path = std::env::temp_dir().join("basalt_core_vault_entries_with_progress");
+    /// _ = fs::remove_dir_all(&path);
+    /// fs::create_dir_all(path.join(".obsidian")).unwrap();
+    /// fs::write(path.join("Note.md"), "").unwrap();
+    ///
+    /// let vault = Vault {
+    ///     name: "MyVault".into(),
+    ///     path,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut progress = vec![];
+    /// let entries = vault.entries_with_progress(|done, total| progress.push((done, total)));
+    ///
+    /// assert_eq!(entries.len(), 1);
+    /// assert_eq!(progress, vec![(1, 1)]);
+    /// ```
+    pub fn entries_with_progress(&self, mut progress: impl FnMut(usize, usize)) -> Vec<VaultEntry> {
+        if !self.path.is_dir() {
+            return vec![];
         }
+
+        // Hidden folders, like `.obsidian` or `.git`, are skipped before they are read.
+        let paths: Vec<PathBuf> = fs::read_dir(&self.path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                !path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+            })
+            .collect();
+
+        let total = paths.len();
+
+        paths
+            .iter()
+            .enumerate()
+            .filter_map(|(i, path)| {
+                let entry = path.as_path().try_into().ok();
+                progress(i + 1, total);
+                entry
+            })
+            .collect()
+    }
+
+    /// Returns all Markdown notes in this vault including the notes in nested folders.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Vault;
+    ///
+    /// let vault = Vault {
+    ///     name: "MyVault".into(),
+    ///     path: "path/to/my_vault".into(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(vault.notes(), vec![]);
+    /// ```
+    pub fn notes(&self) -> Vec<Note> {
+        flatten_files(self.entries())
+            .into_iter()
+            .filter(|note| note.path.extension().is_some_and(|ext| ext == "md"))
+            .collect()
+    }
+
+    /// Returns the paths of all files in this vault including notes and attachments. Files in
+    /// hidden folders like `.obsidian` are excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Vault;
+    ///
+    /// let vault = Vault {
+    ///     name: "MyVault".into(),
+    ///     path: "path/to/my_vault".into(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(vault.files().is_empty());
+    /// ```
+    pub fn files(&self) -> Vec<PathBuf> {
+        flatten_files(self.entries())
+            .into_iter()
+            .map(|note| note.path)
+            .collect()
+    }
+
+    /// Rewrites links pointing to the note at `from` to point to `to` in all notes of the vault.
+    /// Returns the number of notes that were modified and the notes that were skipped, as they
+    /// are not valid UTF-8.
+    ///
+    /// Only links that refer to the note by path are rewritten. Wikilinks that consist of the
+    /// note name only, e.g. `[[Note]]`, are resolved by Obsidian regardless of the folder.
+    /// Relative links of the moved note itself, e.g. `[Index](../Index.md)`, are rewritten to keep
+    /// pointing to the same files. Rewritten targets are relative to the vault root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Vault;
+    ///
+    /// let vault = Vault {
+    ///     name: "MyVault".into(),
+    ///     path: "path/to/my_vault".into(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let from = vault.path.join("Inbox/Note.md");
+    /// let to = vault.path.join("Archive/Note.md");
+    ///
+    /// assert_eq!(vault.update_links(&from, &to).unwrap().modified, 0);
+    /// ```
+    pub fn update_links(&self, from: &Path, to: &Path) -> Result<LinkUpdate> {
+        self.rewrite_links(from, to, false)
+    }
+
+    /// Rewrites links pointing to the note at `from` to point to `to` after the note was renamed,
+    /// like [`Vault::update_links`]. Wikilinks that consist of the note name only, e.g.
+    /// `[[Note]]`, are rewritten too when the name changed, and keep consisting of the name.
+    /// Returns the number of notes that were modified and the notes that were skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Vault;
+    ///
+    /// let vault = Vault {
+    ///     name: "MyVault".into(),
+    ///     path: "path/to/my_vault".into(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let from = vault.path.join("Ideas.md");
+    /// let to = vault.path.join("Garden ideas.md");
+    ///
+    /// assert_eq!(vault.rename_links(&from, &to).unwrap().modified, 0);
+    /// ```
+    pub fn rename_links(&self, from: &Path, to: &Path) -> Result<LinkUpdate> {
+        self.rewrite_links(from, to, true)
+    }
+
+    fn rewrite_links(&self, from: &Path, to: &Path, by_name: bool) -> Result<LinkUpdate> {
+        let Ok(relative) = to.strip_prefix(&self.path) else {
+            return Ok(LinkUpdate::default());
+        };
+
+        let name = |path: &Path| {
+            path.file_stem()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        let (from_name, to_name) = (name(from), name(to));
+        let by_name = by_name && from_name != to_name;
+
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let vault_path = normalize(&self.path);
+        let from_dir = from.parent().filter(|dir| Some(*dir) != to.parent());
+        let (from, to) = (normalize(from), normalize(to));
+
+        let is_name = |link: &Link| link.kind == LinkKind::Wiki && !link.target.contains('/');
+
+        // Paths the link target may refer to relative to the directory, with `.md` appended when
+        // the target has no extension.
+        let candidates = |dir: &Path, link: &Link| {
+            let path = normalize(&dir.join(&link.target));
+            let mut note = path.clone().into_os_string();
+            note.push(".md");
+            let count = if path.extension().is_none() { 2 } else { 1 };
+            [path, note.into()].into_iter().take(count)
+        };
+
+        let mut update = LinkUpdate::default();
+
+        for note in self.notes() {
+            let contents = match Note::read_to_string_strict(&note) {
+                Ok(contents) => contents,
+                Err(Error::InvalidUtf8(path)) => {
+                    update.skipped.push(path);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            let note_dir = note.path.parent().unwrap_or(&self.path);
+
+            // Relative links of the moved note itself, which no longer resolve from its new
+            // directory, are rewritten to the path of the file they pointed to.
+            let moved_dir = from_dir.filter(|_| normalize(&note.path) == to);
+
+            let links = Link::parse(&contents);
+            let targets: Vec<(&Link, String)> = links
+                .iter()
+                .filter(|link| !link.is_external())
+                .filter_map(|link| {
+                    if is_name(link) {
+                        let target = link.target.trim();
+                        let matches =
+                            by_name && target.strip_suffix(".md").unwrap_or(target) == from_name;
+                        return matches.then(|| (link, format!("{to_name}.md")));
+                    }
+
+                    let points_to_from = [self.path.as_path(), note_dir]
+                        .iter()
+                        .any(|dir| candidates(dir, link).any(|path| path == from));
+                    if points_to_from {
+                        return Some((link, relative.clone()));
+                    }
+
+                    let dir = moved_dir?;
+                    if candidates(&self.path, link).any(|path| path.is_file()) {
+                        return None;
+                    }
+                    let path = candidates(dir, link).find(|path| path.is_file())?;
+                    let path = path.strip_prefix(&vault_path).ok()?;
+                    Some((link, path.to_string_lossy().replace('\\', "/")))
+                })
+                .collect();
+
+            if targets.is_empty() {
+                continue;
+            }
+
+            let mut updated = contents.clone();
+
+            // Replace from the end so that the ranges of preceding links stay valid.
+            for (link, target) in targets.iter().rev() {
+                let target = match link.target.ends_with(".md") {
+                    true => target.as_str(),
+                    false => target.strip_suffix(".md").unwrap_or(target),
+                };
+                let target = match link.kind {
+                    LinkKind::Wiki => target.to_string(),
+                    LinkKind::Markdown => percent_encode(target),
+                };
+
+                updated.replace_range(link.range.clone(), &target);
+            }
+
+            Note::write(&note, updated)?;
+            update.modified += 1;
+        }
+
+        Ok(update)
+    }
+
+    /// Returns the directory where the vault's templates are stored.
+    ///
+    /// The directory is read from the Obsidian core templates plugin configuration
+    /// (`.obsidian/templates.json`). When the configuration is missing or does not define a
+    /// folder, `Templates` directory under the vault root is used instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Vault;
+    ///
+    /// let vault = Vault {
+    ///     name: "MyVault".into(),
+    ///     path: "path/to/my_vault".into(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(vault.templates_dir(), vault.path.join("Templates"));
+    /// ```
+    pub fn templates_dir(&self) -> PathBuf {
+        #[derive(Deserialize)]
+        struct Json {
+            folder: Option<String>,
+        }
+
+        let folder = fs::read_to_string(self.path.join(".obsidian/templates.json"))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Json>(&contents).ok())
+            .and_then(|json| json.folder)
+            .filter(|folder| !folder.trim().is_empty())
+            .unwrap_or_else(|| "Templates".to_string());
+
+        self.path.join(folder)
+    }
+
+    /// Returns `true` if the path is inside the vault folder. The path does not need to exist:
+    /// `.` and `..` are resolved first, and symlinks of the part of the path that exists, so that
+    /// a path through a symlink to a folder outside the vault is not inside.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::Vault;
+    ///
+    /// let vault = Vault {
+    ///     name: "MyVault".into(),
+    ///     path: std::env::temp_dir(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(vault.contains(&vault.path.join("Folder/New note.md")));
+    /// assert!(!vault.contains(&vault.path.join("../New note.md")));
+    /// assert!(!vault.contains(&vault.path));
+    /// ```
+    pub fn contains(&self, path: &Path) -> bool {
+        let (Ok(vault_path), Ok(path)) = (self.path.canonicalize(), std::path::absolute(path))
+        else {
+            return false;
+        };
+        let path = normalize(&path);
+
+        path.ancestors()
+            .find_map(|ancestor| {
+                let rest = path.strip_prefix(ancestor).ok()?;
+                ancestor
+                    .canonicalize()
+                    .ok()
+                    .map(|existing| existing.join(rest))
+            })
+            .is_some_and(|path| path.starts_with(&vault_path) && path != vault_path)
     }
 }
 
@@ -86,3 +440,127 @@ impl<'de> Deserialize<'de> for Vault {
         deserialized.try_into().map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_links() {
+        let dir = std::env::temp_dir().join("basalt_core_vault_update_links");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("Inbox")).unwrap();
+
+        let vault = Vault {
+            name: "Vault".into(),
+            path: dir.clone(),
+            ..Default::default()
+        };
+
+        let index = Note {
+            name: "Index".into(),
+            path: dir.join("Index.md"),
+        };
+        Note::write(
+            &index,
+            "[[Inbox/My Note#Heading|Alias]] [[My Note]] [Link](Inbox/My%20Note.md) [Other](Other.md)"
+                .into(),
+        )
+        .unwrap();
+
+        let note = Note {
+            name: "My Note".into(),
+            path: dir.join("Inbox/My Note.md"),
+        };
+        Note::write(
+            &note,
+            "[Index](../Index.md) [[../Index#Heading]] [Draft](Draft%20%281%29.md) [Other](Other.md)"
+                .into(),
+        )
+        .unwrap();
+        fs::write(dir.join("Inbox/Draft (1).md"), "").unwrap();
+        fs::write(dir.join("Other.md"), "").unwrap();
+
+        let archived = Note::move_to(&note, &dir.join("Archive/2025")).unwrap();
+        // Notes that are not valid UTF-8 are left alone.
+        fs::write(dir.join("Latin1.md"), b"[[Inbox/My Note]] Caf\xe9").unwrap();
+
+        let update = vault.update_links(&note.path, &archived.path).unwrap();
+        assert_eq!(update.modified, 2);
+        assert_eq!(update.skipped, [dir.join("Latin1.md")]);
+        assert_eq!(
+            fs::read(dir.join("Latin1.md")).unwrap(),
+            b"[[Inbox/My Note]] Caf\xe9"
+        );
+
+        assert_eq!(
+            Note::read_to_string(&index).unwrap(),
+            "[[Archive/2025/My Note#Heading|Alias]] [[My Note]] [Link](Archive/2025/My%20Note.md) [Other](Other.md)"
+        );
+        assert_eq!(
+            Note::read_to_string(&archived).unwrap(),
+            "[Index](Index.md) [[Index#Heading]] [Draft](Inbox/Draft%20%281%29.md) [Other](Other.md)"
+        );
+
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_contains() {
+        let dir = std::env::temp_dir().join("basalt_core_vault_contains");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("Vault/Notes")).unwrap();
+        fs::create_dir_all(dir.join("Outside")).unwrap();
+        std::os::unix::fs::symlink(dir.join("Outside"), dir.join("Vault/Link")).unwrap();
+
+        let vault = Vault {
+            name: "Vault".into(),
+            path: dir.join("Vault"),
+            ..Default::default()
+        };
+
+        assert!(vault.contains(&dir.join("Vault/Notes/New note.md")));
+        assert!(vault.contains(&dir.join("Vault/Missing/Deep/New note.md")));
+        assert!(vault.contains(&dir.join("Vault/Notes/../New note.md")));
+        assert!(!vault.contains(&dir.join("Vault/Notes/../../New note.md")));
+        assert!(!vault.contains(&dir.join("Vault/Link/New note.md")));
+        assert!(!vault.contains(&dir.join("Outside/New note.md")));
+
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rename_links() {
+        let dir = std::env::temp_dir().join("basalt_core_vault_rename_links");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("Garden")).unwrap();
+
+        let vault = Vault {
+            name: "Vault".into(),
+            path: dir.clone(),
+            ..Default::default()
+        };
+
+        let index = Note {
+            name: "Index".into(),
+            path: dir.join("Index.md"),
+        };
+        Note::write(
+            &index,
+            "[[Ideas#Shed|Shed]] [[Garden/Ideas]] [Ideas](Garden/Ideas.md) [[Ideas list]]".into(),
+        )
+        .unwrap();
+
+        let from = dir.join("Garden/Ideas.md");
+        let to = dir.join("Garden/Plans.md");
+        assert_eq!(vault.rename_links(&from, &to).unwrap().modified, 1);
+
+        assert_eq!(
+            Note::read_to_string(&index).unwrap(),
+            "[[Plans#Shed|Shed]] [[Garden/Plans]] [Ideas](Garden/Plans.md) [[Ideas list]]"
+        );
+
+        _ = fs::remove_dir_all(&dir);
+    }
+}
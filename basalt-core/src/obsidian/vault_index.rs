@@ -0,0 +1,412 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::markdown::{self, MarkdownNode};
+
+use super::{Link, LinkIndex, Note, Result, Tag, Vault};
+
+/// Version of the serialized index. Indexes written with another version are rebuilt.
+const VERSION: u32 = 2;
+
+/// Number of temporary files written by [`VaultIndex::save`], to give each of them its own name.
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the modification time of the file in nanoseconds since the Unix epoch.
+fn modified(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    u64::try_from(nanos).ok()
+}
+
+/// The indexed contents of a note.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexedNote {
+    /// The note name, i.e. the file name without the `.md` extension.
+    pub title: String,
+
+    /// Text of the headings in the order they appear in the note.
+    pub headings: Vec<String>,
+
+    /// Names of the tags in the note without duplicates.
+    pub tags: Vec<String>,
+
+    /// Alternative names of the note from the `aliases` property of the frontmatter.
+    pub aliases: Vec<String>,
+
+    /// Links to other notes and files. External links are not indexed.
+    pub links: Vec<Link>,
+
+    /// Modification time of the note when it was indexed, in nanoseconds since the Unix epoch.
+    pub modified: u64,
+}
+
+impl IndexedNote {
+    /// Indexes the contents of the note.
+    pub fn new(note: &Note, contents: &str, modified: u64) -> Self {
+        let headings = markdown::from_str(contents)
+            .into_iter()
+            .filter_map(|node| match node.markdown_node {
+                MarkdownNode::Heading { text, .. } => {
+                    Some(text.into_iter().map(|node| node.content).collect())
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut tags: Vec<String> = Vec::new();
+        Tag::parse(contents).into_iter().for_each(|tag| {
+            if !tags.contains(&tag.name) {
+                tags.push(tag.name);
+            }
+        });
+
+        Self {
+            title: note.name.clone(),
+            headings,
+            tags,
+            aliases: Note::property(contents, "aliases"),
+            links: Link::parse(contents)
+                .into_iter()
+                .filter(|link| !link.is_external())
+                .collect(),
+            modified,
+        }
+    }
+}
+
+/// Persistent index of the note titles, headings, tags and links of a vault.
+///
+/// The index is updated incrementally: only notes that were modified since they were indexed are
+/// read again, so that an index loaded from disk is up to date after reading the modification
+/// times of the notes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaultIndex {
+    version: u32,
+    notes: BTreeMap<PathBuf, IndexedNote>,
+}
+
+impl Default for VaultIndex {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            notes: BTreeMap::new(),
+        }
+    }
+}
+
+impl VaultIndex {
+    /// Reads the index from the file. An index written by another version of the index is
+    /// discarded and an empty index is returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::VaultIndex;
+    /// use std::path::Path;
+    ///
+    /// assert!(VaultIndex::load(Path::new("nonexistent/index.json")).is_err());
+    /// ```
+    pub fn load(path: &Path) -> Result<Self> {
+        let index: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        Ok(match index.version {
+            VERSION => index,
+            _ => Self::default(),
+        })
+    }
+
+    /// Writes the index to the file, creating the parent directories when missing.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // The index is written to a temporary file first, so that an interrupted write does not
+        // leave a truncated index behind. Every write has its own temporary file, as the index of
+        // the same vault can be saved by several threads or processes at once.
+        let temporary = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&temporary, serde_json::to_string(self)?)?;
+        fs::rename(temporary, path)?;
+
+        Ok(())
+    }
+
+    /// Brings the index up to date with the notes of the vault. Notes that were modified since
+    /// they were indexed are read again and removed notes are dropped. The `progress` callback is
+    /// called after each checked note with the number of checked notes and the total number of
+    /// notes.
+    ///
+    /// Returns the number of notes that were indexed again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{Vault, VaultIndex};
+    ///
+    /// let vault = Vault {
+    ///     name: "MyVault".into(),
+    ///     path: "path/to/my_vault".into(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let mut index = VaultIndex::default();
+    /// assert_eq!(index.update(&vault, |_, _| {}), 0);
+    /// ```
+    pub fn update(&mut self, vault: &Vault, mut progress: impl FnMut(usize, usize)) -> usize {
+        let notes = vault.notes();
+        let mut updated = 0;
+
+        let paths: HashSet<_> = notes.iter().map(|note| note.path.as_path()).collect();
+        self.notes.retain(|path, _| paths.contains(path.as_path()));
+
+        for (i, note) in notes.iter().enumerate() {
+            if self.update_note(note) {
+                updated += 1;
+            }
+
+            progress(i + 1, notes.len());
+        }
+
+        updated
+    }
+
+    /// Indexes the note again if it was modified since it was indexed, e.g. after it was saved.
+    /// Returns `true` if the note was indexed again.
+    pub fn update_note(&mut self, note: &Note) -> bool {
+        let modified = modified(&note.path).unwrap_or_default();
+        let is_current = self
+            .notes
+            .get(&note.path)
+            .is_some_and(|indexed| indexed.modified == modified);
+
+        if is_current {
+            return false;
+        }
+
+        match Note::read_to_string(note) {
+            Ok(contents) => {
+                self.notes.insert(
+                    note.path.clone(),
+                    IndexedNote::new(note, &contents, modified),
+                );
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Indexes the contents of the note. The current modification time of the note is stored with
+    /// the contents.
+    pub fn insert(&mut self, note: &Note, contents: &str) {
+        let modified = modified(&note.path).unwrap_or_default();
+        self.notes.insert(
+            note.path.clone(),
+            IndexedNote::new(note, contents, modified),
+        );
+    }
+
+    /// Removes the note from the index.
+    pub fn remove(&mut self, path: &Path) {
+        self.notes.remove(path);
+    }
+
+    /// Returns the indexed contents of the note.
+    pub fn get(&self, path: &Path) -> Option<&IndexedNote> {
+        self.notes.get(path)
+    }
+
+    /// Returns the indexed notes.
+    pub fn notes(&self) -> impl Iterator<Item = (&Path, &IndexedNote)> {
+        self.notes.iter().map(|(path, note)| (path.as_path(), note))
+    }
+
+    /// Returns the note a wikilink target points to, found by the note name, e.g. `Ideas`, by the
+    /// path without the `.md` extension, e.g. `Folder/Ideas`, or by one of the aliases of the note,
+    /// ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{Note, VaultIndex};
+    ///
+    /// let mut index = VaultIndex::default();
+    /// let note = Note {
+    ///     name: "Ideas".into(),
+    ///     path: "vault/Garden/Ideas.md".into(),
+    /// };
+    ///
+    /// index.insert(&note, "---\naliases: [Plans]\n---\n# Garden");
+    ///
+    /// assert_eq!(index.find("Ideas"), Some(note.path.as_path()));
+    /// assert_eq!(index.find("plans"), Some(note.path.as_path()));
+    /// assert_eq!(index.find("Garden/Ideas.md"), Some(note.path.as_path()));
+    /// assert_eq!(index.find("Shed/Ideas"), None);
+    /// ```
+    pub fn find(&self, target: &str) -> Option<&Path> {
+        let target = target.trim();
+        let target = target.strip_suffix(".md").unwrap_or(target);
+
+        let by_alias = || {
+            self.notes().find(|(_, note)| {
+                !target.contains('/')
+                    && note
+                        .aliases
+                        .iter()
+                        .any(|alias| alias.eq_ignore_ascii_case(target))
+            })
+        };
+
+        self.notes()
+            .find(|(path, note)| match target.contains('/') {
+                true => path.with_extension("").ends_with(target),
+                false => note.title == target,
+            })
+            .or_else(by_alias)
+            .map(|(path, _)| path)
+    }
+
+    /// Returns the notes with a title or heading containing the query, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_core::obsidian::{Note, VaultIndex};
+    ///
+    /// let mut index = VaultIndex::default();
+    /// let note = Note {
+    ///     name: "Ideas".into(),
+    ///     path: "vault/Ideas.md".into(),
+    /// };
+    ///
+    /// index.insert(&note, "# Garden\n\n## Tomatoes");
+    ///
+    /// assert_eq!(index.search("tomato"), vec![note.path.as_path()]);
+    /// assert_eq!(index.search("IDEAS"), vec![note.path.as_path()]);
+    /// assert!(index.search("potato").is_empty());
+    /// ```
+    pub fn search(&self, query: &str) -> Vec<&Path> {
+        let query = query.to_lowercase();
+
+        self.notes()
+            .filter(|(_, note)| {
+                std::iter::once(&note.title)
+                    .chain(&note.headings)
+                    .any(|text| text.to_lowercase().contains(&query))
+            })
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Returns the notes with the tag or one of its nested tags, e.g. `project` matches
+    /// `project/basalt`.
+    pub fn tagged(&self, tag: &str) -> Vec<&Path> {
+        self.notes()
+            .filter(|(_, note)| {
+                note.tags.iter().any(|name| {
+                    name.strip_prefix(tag)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+                })
+            })
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Returns the names of the tags in the indexed notes, sorted and without duplicates.
+    pub fn tags(&self) -> Vec<String> {
+        let tags: BTreeSet<_> = self
+            .notes()
+            .flat_map(|(_, note)| note.tags.iter().cloned())
+            .collect();
+
+        tags.into_iter().collect()
+    }
+
+    /// Builds the [`LinkIndex`] of the vault from the indexed links without reading the notes.
+    pub fn link_index(&self, vault: &Vault) -> LinkIndex {
+        let mut link_index = LinkIndex::new(&vault.path, vault.files());
+
+        // Aliases are known before the links are resolved, as links can point to any note.
+        self.notes
+            .iter()
+            .for_each(|(path, note)| link_index.insert_aliases(path, &note.aliases));
+
+        self.notes
+            .iter()
+            .for_each(|(path, note)| link_index.insert_links(path, note.links.clone()));
+
+        link_index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_index() {
+        let dir = std::env::temp_dir().join("basalt_core_vault_index");
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let vault = Vault {
+            name: "Vault".into(),
+            path: dir.clone(),
+            ..Default::default()
+        };
+
+        let note = Note {
+            name: "Note".into(),
+            path: dir.join("Note.md"),
+        };
+        let other = Note {
+            name: "Other".into(),
+            path: dir.join("Other.md"),
+        };
+
+        Note::write(&note, "# Heading\n\n#project/basalt [[Other]]".into()).unwrap();
+        Note::write(&other, "#idea #idea".into()).unwrap();
+
+        let mut index = VaultIndex::default();
+        assert_eq!(index.update(&vault, |_, _| {}), 2);
+
+        let indexed = index.get(&note.path).unwrap();
+        assert_eq!(indexed.title, "Note");
+        assert_eq!(indexed.headings, vec!["Heading"]);
+        assert_eq!(indexed.tags, vec!["project/basalt"]);
+        assert_eq!(indexed.links[0].target, "Other");
+        assert_eq!(indexed.modified, modified(&note.path).unwrap());
+        assert_eq!(index.get(&other.path).unwrap().tags, vec!["idea"]);
+        assert_eq!(index.tagged("project"), vec![note.path.as_path()]);
+        assert!(index.tagged("proj").is_empty());
+        assert_eq!(index.tags(), vec!["idea", "project/basalt"]);
+        assert_eq!(
+            index.link_index(&vault).backlinks(&other.path),
+            vec![note.path.as_path()]
+        );
+
+        let path = dir.join(".cache/index.json");
+        index.save(&path).unwrap();
+
+        let mut loaded = VaultIndex::load(&path).unwrap();
+        assert_eq!(loaded, index);
+        assert_eq!(loaded.update(&vault, |_, _| {}), 0);
+
+        Note::delete(&other).unwrap();
+        assert_eq!(loaded.update(&vault, |_, _| {}), 0);
+        assert_eq!(loaded.get(&other.path), None);
+
+        fs::write(&path, r#"{"version":0,"notes":{}}"#).unwrap();
+        assert_eq!(VaultIndex::load(&path).unwrap(), VaultIndex::default());
+    }
+}
@@ -0,0 +1,178 @@
+//! Accessibility modes for limited terminals and low-vision users.
+//!
+//! The modes are applied to the screen buffer after every pane and modal was rendered, so that
+//! the widgets do not need to know about them:
+//!
+//! - ASCII only mode replaces the box-drawing, block, arrow and bullet glyphs, and the stylized
+//!   letters of headings, with ASCII characters that every terminal font can show.
+//! - High-contrast mode drops dimmed text, shows gray text in the default foreground color and
+//!   uses the bright variants of the other colors.
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+
+use crate::config::UiConfig;
+
+/// Returns the ASCII replacement of the glyph, or `None` for characters without a replacement.
+fn ascii_char(c: char) -> Option<char> {
+    let replacement = match c {
+        '─' | '━' | '═' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍' | '╴' | '╶' | '╸' | '╺' => {
+            '-'
+        }
+        '│' | '┃' | '║' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏' | '╵' | '╷' | '╹' | '╻' => {
+            '|'
+        }
+        '╱' => '/',
+        '╲' => '\\',
+        '╳' => 'X',
+        '\u{2500}'..='\u{257F}' => '+',
+        '▀' | '▔' => '-',
+        '▄' | '▁' => '_',
+        '▌' | '▐' | '▏' | '▕' => '|',
+        '░' => '.',
+        '▒' => ':',
+        '▓' => '%',
+        '\u{2580}'..='\u{259F}' => '#',
+        '↑' | '▲' | '▴' | '△' => '^',
+        '↓' | '▼' | '▾' | '▽' => 'v',
+        '→' | '▶' | '▸' | '►' | '»' | '›' => '>',
+        '←' | '◀' | '◂' | '◄' | '«' | '‹' | '↵' => '<',
+        '⇄' | '𝌆' => '=',
+        '·' | '⋅' | '…' => '.',
+        '—' | '–' => '-',
+        '●' | '⬤' | '•' | '▪' | '◆' | '✺' => '*',
+        '◦' | '▫' | '○' | '◇' => 'o',
+        '■' | '✗' | '✓' | '✔' => 'x',
+        '□' => '_',
+        '⚠' => '!',
+        c => return plain_letter(c),
+    };
+
+    Some(replacement)
+}
+
+/// Returns the plain letter or digit of a mathematical alphanumeric symbol, e.g. `𝓐` or `𝕬`, which
+/// are used for the stylized text of headings.
+fn plain_letter(c: char) -> Option<char> {
+    let offset = match c {
+        'ℂ' => return Some('C'),
+        'ℍ' => return Some('H'),
+        'ℕ' => return Some('N'),
+        'ℙ' => return Some('P'),
+        'ℚ' => return Some('Q'),
+        'ℝ' => return Some('R'),
+        'ℤ' => return Some('Z'),
+        '\u{1D400}'..='\u{1D6A3}' => (c as u32 - 0x1D400) % 52,
+        '\u{1D7CE}'..='\u{1D7FF}' => return char::from_digit((c as u32 - 0x1D7CE) % 10, 10),
+        _ => return None,
+    };
+
+    match offset {
+        0..26 => char::from_u32('A' as u32 + offset),
+        _ => char::from_u32('a' as u32 + offset - 26),
+    }
+}
+
+/// Returns the high-contrast variant of the color.
+fn high_contrast_color(color: Color) -> Color {
+    match color {
+        Color::DarkGray | Color::Gray => Color::Reset,
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Yellow => Color::LightYellow,
+        Color::Blue => Color::LightBlue,
+        Color::Magenta => Color::LightMagenta,
+        Color::Cyan => Color::LightCyan,
+        color => color,
+    }
+}
+
+/// Applies the enabled accessibility modes to the rendered buffer.
+pub fn apply(buf: &mut Buffer, config: &UiConfig) {
+    if !config.ascii_only && !config.high_contrast {
+        return;
+    }
+
+    buf.content.iter_mut().for_each(|cell| {
+        if config.ascii_only {
+            let mut chars = cell.symbol().chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                if let Some(replacement) = ascii_char(c) {
+                    cell.set_char(replacement);
+                }
+            }
+        }
+
+        if config.high_contrast {
+            cell.fg = high_contrast_color(cell.fg);
+            cell.bg = match cell.bg {
+                Color::DarkGray | Color::Gray => Color::Reset,
+                color => color,
+            };
+            cell.modifier.remove(Modifier::DIM);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        layout::Rect,
+        style::Stylize,
+        text::Line,
+        widgets::{Block, BorderType, Widget},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_ascii_only() {
+        let area = Rect::new(0, 0, 12, 3);
+        let mut buf = Buffer::empty(area);
+
+        Block::bordered()
+            .border_type(BorderType::Rounded)
+            .render(area, &mut buf);
+        Line::from("▶ 𝓗𝓲 ◆ 𝟓 é").render(Rect::new(1, 1, 10, 1), &mut buf);
+
+        apply(
+            &mut buf,
+            &UiConfig {
+                ascii_only: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            buf,
+            Buffer::with_lines(["+----------+", "|> Hi * 5 é|", "+----------+"])
+        );
+    }
+
+    #[test]
+    fn test_high_contrast() {
+        let area = Rect::new(0, 0, 3, 1);
+        let mut buf = Buffer::empty(area);
+
+        Line::from(vec![
+            "a".dark_gray(),
+            "b".red().dim(),
+            "c".black().on_gray(),
+        ])
+        .render(area, &mut buf);
+
+        apply(
+            &mut buf,
+            &UiConfig {
+                high_contrast: true,
+                ..Default::default()
+            },
+        );
+
+        let mut expected = Buffer::with_lines(["abc"]);
+        expected[(1, 0)].set_fg(Color::LightRed);
+        expected[(2, 0)].set_fg(Color::Black);
+        assert_eq!(buf, expected);
+    }
+}
@@ -1,64 +1,306 @@
-use basalt_core::obsidian::{Note, Vault};
+use basalt_core::obsidian::{
+    Anchors, Link, LinkIndex, LinkUpdate, Note, PublishFilter, PublishOptions, Vault, VaultEntry,
+    VaultIndex, DEFAULT_TEMPLATE,
+};
 use ratatui::{
+    backend::Backend,
     buffer::Buffer,
-    crossterm::event::{self, Event, KeyEvent, KeyEventKind},
-    layout::{Constraint, Layout, Rect, Size},
-    widgets::{StatefulWidget, StatefulWidgetRef},
-    DefaultTerminal,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Flex, Layout, Rect, Size},
+    text::Line,
+    widgets::{StatefulWidget, StatefulWidgetRef, Widget},
+    DefaultTerminal, Terminal,
 };
 
-use std::{cell::RefCell, fmt::Debug, io::Result};
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Display},
+    fs,
+    io::{Result, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
+    accessibility,
+    attachments_modal::{self, AttachmentsModal, AttachmentsModalState},
+    background::{self, Job},
+    canvas_modal::{self, CanvasModal, CanvasModalState},
+    capture, clipboard,
+    color_support::{self, ColorSupport},
     command,
     config::{self, Config},
+    config_report_modal::{self, ConfigReportModal, ConfigReportModalState},
+    crash, date_format,
+    diagnostics_modal::{self, DiagnosticsModal, DiagnosticsModalState},
+    diff,
+    duplicates_modal::{self, DuplicatesModal, DuplicatesModalState},
+    error_log_modal::{self, ErrorLogModal, ErrorLogModalState},
     explorer::{self, Explorer, ExplorerState},
+    extract,
+    focus_timer::{self, FocusTimer},
     help_modal::{self, HelpModal, HelpModalState},
-    note_editor::{self, markdown_parser::Node, Editor, EditorState, Mode},
+    history::History,
+    input_modal::{self, InputAction, InputModal, InputModalState},
+    jobs_modal::{self, JobsModal, JobsModalState},
+    keys_debug_modal::{self, KeysDebugModal, KeysDebugModalState},
+    keys_export, link_fix,
+    note_diff_modal::{self, NoteDiffModal, NoteDiffModalState},
+    note_editor::{
+        self, markdown_parser::Node, query_block, AnchorKind, Completion, Editor, EditorState, Mode,
+    },
+    note_history_modal::{self, NoteHistoryModal, NoteHistoryModalState},
+    outgoing_links_modal::{self, OutgoingLinksModal, OutgoingLinksModalState},
     outline::{self, Outline, OutlineState},
+    print::{self, PrintFormat},
+    quick_switcher,
+    quit_modal::{self, QuitModal, QuitModalState},
+    replace_modal::{self, ReplaceModal, ReplaceModalState, Scope},
+    responsive::Breakpoint,
+    save_conflict_modal::{self, SaveConflictModal, SaveConflictModalState},
+    screen_reader::{self, Announcements, Focus},
+    session::{NotePosition, RecentVault, Session},
     splash_modal::{self, SplashModal, SplashModalState},
     statusbar::{StatusBar, StatusBarState},
     stylized_text::{self, FontStyle},
-    text_counts::{CharCount, WordCount},
+    tag_browser_modal::{self, TagBrowserModal, TagBrowserModalState},
+    text_counts::{CharCount, CountScope, WordCount},
+    type_ahead::TypeAhead,
+    vault_loading_modal::{self, VaultLoadingModal, VaultLoadingModalState},
+    vault_replace_modal::{self, VaultReplaceModal, VaultReplaceModalState, VaultReplaceReport},
     vault_selector_modal::{self, VaultSelectorModal, VaultSelectorModalState},
+    vault_stats_modal::{self, VaultStatsModal, VaultStatsModalState},
+    watcher::{self, Watcher},
+    web_clip::{self, WebClip},
+    writing_stats::WritingStats,
 };
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const HELP_TEXT: &str = include_str!("./help.txt");
 
+const PANE_DEFAULT_WIDTH: u16 = 35;
+const PANE_MIN_WIDTH: u16 = 16;
+const PANE_COLLAPSED_WIDTH: u16 = 4;
+const PANE_RESIZE_STEP: i16 = 2;
+const NOTE_EDITOR_MIN_WIDTH: u16 = 20;
+
+/// Output directory of `publish`, relative to the vault root, when none is configured.
+const PUBLISH_DEFAULT_DIR: &str = ".publish";
+
+/// How long the main loop waits for terminal events before checking for background messages.
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Largest count that can be typed before a command.
+const COUNT_MAX: usize = 9999;
+
+/// Steps of a smooth half page scroll, see [`UiConfig::smooth_scroll`](config::UiConfig).
+const SMOOTH_SCROLL_STEPS: usize = 4;
+
+/// How long the main loop waits between the steps of a smooth scroll.
+const SMOOTH_SCROLL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Returns the message that reports the failed operation, e.g. `Could not create note: <error>`.
+fn error<'a>(context: impl Display, err: impl Display) -> Option<Message<'a>> {
+    Some(Message::Error(format!("{context}: {err}")))
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub enum ScrollAmount {
     #[default]
     One,
     HalfPage,
+    /// One of the steps of a smooth half page scroll, as the step and the number of steps. The
+    /// steps add up to a half page.
+    HalfPageStep(usize, usize),
 }
 
 pub fn calc_scroll_amount(scroll_amount: &ScrollAmount, height: usize) -> usize {
     match scroll_amount {
         ScrollAmount::One => 1,
         ScrollAmount::HalfPage => height / 2,
+        ScrollAmount::HalfPageStep(step, steps) => {
+            let half = height / 2;
+            let steps = (*steps).max(1);
+            half * (step + 1) / steps - half * step / steps
+        }
     }
 }
 
+/// State of a vault that another vault was opened over.
+#[derive(Clone)]
+struct CachedVault<'a> {
+    vault: &'a Vault,
+    explorer: ExplorerState<'a>,
+    note_editor: EditorState<'a>,
+    outline: OutlineState,
+    selected_note: Option<SelectedNote>,
+    vault_index: VaultIndex,
+    history: History,
+    active_pane: ActivePane,
+}
+
 #[derive(Default, Clone)]
 pub struct AppState<'a> {
     screen_size: Size,
-    is_running: bool,
+    pub(crate) is_running: bool,
+
+    pub(crate) vault: Option<&'a Vault>,
+    /// Index of the notes of the open vault, updated in the background when the vault changes.
+    vault_index: VaultIndex,
+    background: Option<background::Sender>,
+    /// Watches the open vault for notes renamed outside Basalt, stopped when it is dropped.
+    watcher: Option<Arc<Watcher>>,
 
     active_pane: ActivePane,
+    zen_mode: bool,
+    explorer_width: u16,
+    outline_width: u16,
     explorer: ExplorerState<'a>,
     note_editor: EditorState<'a>,
     outline: OutlineState,
-    selected_note: Option<SelectedNote>,
+    pub(crate) selected_note: Option<SelectedNote>,
+    /// Notes opened in the open vault, gone back and forward through.
+    history: History,
+    /// Note to open once its vault has been scanned, when switching to a note of another vault.
+    pending_note: Option<PathBuf>,
+    /// State of the vaults that were open before, restored when switching back to them.
+    vault_cache: Vec<CachedVault<'a>>,
+    /// Headings and block IDs of the last linked note whose anchors were completed. The note is
+    /// only read when its anchors are first completed.
+    link_anchors: Option<(PathBuf, Anchors)>,
+    /// Result of the last background operation, shown in the status bar until the next key
+    /// press.
+    status_message: Option<String>,
+    /// Warning about the last change, e.g. an embed of a missing file, shown in the status bar
+    /// until the next key press.
+    status_warning: Option<String>,
+    /// Words written in the open vault on the day, shown in the status bar on that day.
+    words_today: (chrono::NaiveDate, usize),
+    /// The session as it was at the start. Only the note positions are kept up to date, so that
+    /// a note opens where it was left even if another instance wrote the session file since.
+    session: Session,
+    /// Vaults opened since the start, the oldest first, written to the session file on quit.
+    opened_vaults: Vec<RecentVault>,
+    focus_timer: FocusTimer,
+    /// Format the open note is printed in when the application quits.
+    print: Option<PrintFormat>,
+    /// Scope of the counts of the status bar, once switched from the configured scope.
+    count_scope: Option<CountScope>,
+    /// The last error, shown in the status bar until the error log is opened.
+    pub(crate) last_error: Option<String>,
+    /// Keys pressed since macro recording started, `None` when not recording.
+    recording_macro: Option<Vec<KeyEvent>>,
+    /// The last recorded macro, played with `macro_play`.
+    recorded_macro: Vec<KeyEvent>,
+    /// Keys of the macro to play after the current messages are handled.
+    macro_keys: Vec<KeyEvent>,
+    playing_macro: bool,
+    /// Steps of the smooth scroll in progress, handled one per frame.
+    smooth_scroll: VecDeque<Message<'a>>,
+    /// Count typed before a command, which repeats movement commands.
+    pending_count: Option<usize>,
+    /// Characters typed to jump to an item of the explorer, the outline or the vault selector,
+    /// with the pane they were typed in.
+    type_ahead: (ActivePane, TypeAhead),
+    /// Whether the explorer and the outline were collapsed because they did not fit the screen,
+    /// so they are opened again once the screen is large enough.
+    explorer_auto_collapsed: bool,
+    outline_auto_collapsed: bool,
+    /// Focus at the last announcement, compared after every update to announce the changes.
+    focus: Focus,
+    /// The last announced change, shown above the status bar.
+    announcement: Option<String>,
 
-    splash_modal: SplashModalState<'a>,
+    pub(crate) splash_modal: SplashModalState<'a>,
+    vault_loading_modal: VaultLoadingModalState<'a>,
     help_modal: HelpModalState,
     vault_selector_modal: VaultSelectorModalState<'a>,
+    input_modal: InputModalState,
+    vault_stats_modal: VaultStatsModalState,
+    diagnostics_modal: DiagnosticsModalState,
+    attachments_modal: AttachmentsModalState,
+    duplicates_modal: DuplicatesModalState,
+    note_diff_modal: NoteDiffModalState,
+    note_history_modal: NoteHistoryModalState,
+    replace_modal: ReplaceModalState,
+    vault_replace_modal: VaultReplaceModalState,
+    canvas_modal: CanvasModalState,
+    tag_browser_modal: TagBrowserModalState,
+    outgoing_links_modal: OutgoingLinksModalState,
+    jobs_modal: JobsModalState,
+    config_report_modal: ConfigReportModalState,
+    error_log_modal: ErrorLogModalState,
+    quit_modal: QuitModalState,
+    save_conflict_modal: SaveConflictModalState,
+    keys_debug_modal: KeysDebugModalState,
 }
 
 impl<'a> AppState<'a> {
+    /// Returns the focused pane and its selections for screen reader announcements.
+    fn focus(&self) -> Focus {
+        let pane = self.active_component();
+
+        Focus {
+            pane: pane.into(),
+            note: self.selected_note.as_ref().map(|note| note.name.clone()),
+            mode: self.note_editor.mode(),
+            item: match pane {
+                ActivePane::Explorer => self.explorer.highlighted_item().map(|item| match item {
+                    explorer::Item::Directory { .. } => format!("{} folder", item.name()),
+                    explorer::Item::Search { .. } => {
+                        format!("{} saved search", item.name())
+                    }
+                    explorer::Item::File(..) => item.name().to_string(),
+                }),
+                ActivePane::Outline => self
+                    .outline
+                    .selected()
+                    .map(|item| item.content().to_string()),
+                _ => None,
+            },
+        }
+    }
+
     pub fn active_component(&self) -> ActivePane {
+        if self.quit_modal.visible {
+            return ActivePane::QuitModal;
+        }
+
+        if self.save_conflict_modal.visible {
+            return ActivePane::SaveConflictModal;
+        }
+
+        if self.config_report_modal.visible {
+            return ActivePane::ConfigReportModal;
+        }
+
+        if self.keys_debug_modal.visible {
+            return ActivePane::KeysDebugModal;
+        }
+
+        if self.error_log_modal.visible {
+            return ActivePane::ErrorLogModal;
+        }
+
+        if self.vault_loading_modal.visible {
+            return ActivePane::VaultLoadingModal;
+        }
+
+        if self.input_modal.visible {
+            return ActivePane::InputModal;
+        }
+
+        if self.replace_modal.visible {
+            return ActivePane::ReplaceModal;
+        }
+
+        if self.vault_replace_modal.visible {
+            return ActivePane::VaultReplaceModal;
+        }
+
         if self.help_modal.visible {
             return ActivePane::HelpModal;
         }
@@ -67,6 +309,46 @@ impl<'a> AppState<'a> {
             return ActivePane::VaultSelectorModal;
         }
 
+        if self.vault_stats_modal.visible {
+            return ActivePane::VaultStatsModal;
+        }
+
+        if self.diagnostics_modal.visible {
+            return ActivePane::DiagnosticsModal;
+        }
+
+        if self.attachments_modal.visible {
+            return ActivePane::AttachmentsModal;
+        }
+
+        if self.duplicates_modal.visible {
+            return ActivePane::DuplicatesModal;
+        }
+
+        if self.note_diff_modal.visible {
+            return ActivePane::NoteDiffModal;
+        }
+
+        if self.note_history_modal.visible {
+            return ActivePane::NoteHistoryModal;
+        }
+
+        if self.canvas_modal.visible {
+            return ActivePane::CanvasModal;
+        }
+
+        if self.tag_browser_modal.visible {
+            return ActivePane::TagBrowserModal;
+        }
+
+        if self.outgoing_links_modal.visible {
+            return ActivePane::OutgoingLinksModal;
+        }
+
+        if self.jobs_modal.visible {
+            return ActivePane::JobsModal;
+        }
+
         if self.splash_modal.visible {
             return ActivePane::Splash;
         }
@@ -74,6 +356,573 @@ impl<'a> AppState<'a> {
         self.active_pane
     }
 
+    /// Returns the rendered widths of the explorer and outline panes.
+    ///
+    /// Open panes are kept between the minimum width and the width that still leaves room for
+    /// the note editor and the other pane.
+    pub fn pane_widths(&self) -> (u16, u16) {
+        // Horizontal margin of the main layout
+        let available = self.screen_size.width.saturating_sub(2);
+
+        let clamp = |width: u16, other: u16| {
+            let max = available
+                .saturating_sub(other + NOTE_EDITOR_MIN_WIDTH)
+                .max(PANE_MIN_WIDTH);
+            width.clamp(PANE_MIN_WIDTH, max)
+        };
+
+        let outline_open = self.outline.is_open();
+
+        let explorer = if self.explorer.open {
+            clamp(
+                self.explorer_width,
+                if outline_open {
+                    PANE_MIN_WIDTH
+                } else {
+                    PANE_COLLAPSED_WIDTH
+                },
+            )
+        } else {
+            PANE_COLLAPSED_WIDTH
+        };
+
+        let outline = if outline_open {
+            clamp(self.outline_width, explorer)
+        } else {
+            PANE_COLLAPSED_WIDTH
+        };
+
+        (explorer, outline)
+    }
+
+    /// Collapses the explorer and the outline when they do not fit the breakpoint of the screen
+    /// size. The outline is collapsed below the regular breakpoint and the explorer on tiny
+    /// screens.
+    pub(crate) fn fit_panes(&mut self) {
+        let breakpoint = Breakpoint::from(self.screen_size);
+
+        let explorer_fits = breakpoint > Breakpoint::Tiny;
+        if !explorer_fits && self.explorer.open {
+            self.explorer.open = false;
+            self.explorer_auto_collapsed = true;
+        } else if explorer_fits && self.explorer_auto_collapsed {
+            self.explorer.open = true;
+            self.explorer_auto_collapsed = false;
+        }
+
+        let outline_fits = breakpoint == Breakpoint::Regular;
+        if !outline_fits && self.outline.is_open() {
+            self.outline.close();
+            self.outline_auto_collapsed = true;
+        } else if outline_fits && self.outline_auto_collapsed {
+            self.outline.open();
+            self.outline_auto_collapsed = false;
+        }
+    }
+
+    /// Returns the pane after the focused pane in the order of the explorer, the note editor and
+    /// the outline, or the pane before it if `forward` is not set. Collapsed panes are skipped.
+    fn cycle_pane(&self, forward: bool) -> ActivePane {
+        const PANES: [ActivePane; 3] = [
+            ActivePane::Explorer,
+            ActivePane::NoteEditor,
+            ActivePane::Outline,
+        ];
+
+        let index = PANES
+            .iter()
+            .position(|pane| *pane == self.active_pane)
+            .unwrap_or(1);
+        let step = if forward { 1 } else { PANES.len() - 1 };
+
+        (1..PANES.len())
+            .map(|i| PANES[(index + i * step) % PANES.len()])
+            .find(|pane| match pane {
+                ActivePane::Explorer => self.explorer.is_open(),
+                ActivePane::Outline => self.outline.is_open(),
+                _ => true,
+            })
+            .unwrap_or(self.active_pane)
+    }
+
+    fn resize_pane(&mut self, amount: i16) {
+        let (explorer, outline) = self.pane_widths();
+        let resize = |width: u16| width.saturating_add_signed(amount);
+
+        match self.active_pane {
+            ActivePane::Explorer if self.explorer.open => self.explorer_width = resize(explorer),
+            ActivePane::Outline if self.outline.is_open() => self.outline_width = resize(outline),
+            _ => return,
+        }
+
+        (self.explorer_width, self.outline_width) = self.pane_widths();
+    }
+
+    /// Refreshes the explorer and the open vault reports after notes were created, deleted or
+    /// changed on disk.
+    fn vault_changed(&mut self) -> Option<Message<'a>> {
+        self.index_vault();
+        self.refresh_vault()
+    }
+
+    /// Shows the files of the vault again in the explorer and returns the message to refresh the
+    /// open modal that lists them.
+    fn refresh_vault(&mut self) -> Option<Message<'a>> {
+        if let Some(vault) = self.vault {
+            self.explorer.refresh(vault.entries());
+        }
+
+        if self.diagnostics_modal.visible {
+            return Some(Message::DiagnosticsModal(
+                diagnostics_modal::Message::Refresh,
+            ));
+        }
+
+        if self.attachments_modal.visible {
+            return Some(Message::AttachmentsModal(
+                attachments_modal::Message::Refresh,
+            ));
+        }
+
+        if self.duplicates_modal.visible {
+            return Some(Message::DuplicatesModal(duplicates_modal::Message::Refresh));
+        }
+
+        None
+    }
+
+    /// Logs the notes that were skipped when updating the links, or the failure to update them.
+    fn links_updated(&mut self, update: basalt_core::obsidian::Result<LinkUpdate>) {
+        match update {
+            Ok(update) if update.skipped.is_empty() => {}
+            Ok(update) => {
+                let names: Vec<_> = update
+                    .skipped
+                    .iter()
+                    .map(|path| Note::from(path.as_path()).name)
+                    .collect();
+                self.log_error(format!(
+                    "Links were not updated in {}: not valid UTF-8",
+                    names.join(", ")
+                ));
+            }
+            Err(err) => self.log_error(format!("Could not update links: {err}")),
+        }
+    }
+
+    /// Adds the error to the error log and shows it in the status bar until the log is opened.
+    fn log_error(&mut self, message: String) {
+        let time = chrono::Local::now().format("%H:%M:%S").to_string();
+        self.error_log_modal.push(&time, &message);
+        self.last_error = Some(message);
+    }
+
+    /// Returns the message asking how to save the open note, if it has unsaved changes and its
+    /// file was changed outside Basalt since it was opened or last saved.
+    fn save_conflict(&self) -> Option<Message<'a>> {
+        if !self.note_editor.modified || !self.note_editor.changed_on_disk() {
+            return None;
+        }
+
+        let name = self.selected_note.as_ref()?.name.clone();
+        Some(Message::SaveConflictModal(
+            save_conflict_modal::Message::Open(name),
+        ))
+    }
+
+    /// Returns the names of the notes with unsaved changes, including the notes of the vaults
+    /// that were switched away from.
+    pub(crate) fn unsaved_notes(&self) -> Vec<String> {
+        let cached = self
+            .vault_cache
+            .iter()
+            .filter(|cached| cached.note_editor.modified)
+            .filter_map(|cached| cached.selected_note.as_ref());
+
+        self.selected_note
+            .as_ref()
+            .filter(|_| self.note_editor.modified)
+            .into_iter()
+            .chain(cached)
+            .map(|note| note.name.clone())
+            .collect()
+    }
+
+    /// Shows the scanned entries of the vault in the explorer and remembers the vault for the next
+    /// start. The state of the vault that was open is kept, so that switching back to it restores
+    /// it, while opening the open vault again scans it from scratch.
+    fn open_vault(
+        &mut self,
+        config: &Config,
+        vault: &'a Vault,
+        entries: Vec<VaultEntry>,
+    ) -> Option<Message<'a>> {
+        let registers = std::mem::take(&mut self.note_editor.registers);
+        self.cache_vault();
+        self.vault_cache
+            .retain(|cached| cached.vault.path != vault.path);
+        self.vault = Some(vault);
+
+        let expanded: Vec<PathBuf> = Session::load()
+            .expanded_folders
+            .get(&vault.name)
+            .into_iter()
+            .flatten()
+            .map(|folder| vault.path.join(folder))
+            .collect();
+        self.explorer = ExplorerState::new(&vault.name, entries)
+            .with_attachments(&config.attachments.extensions)
+            .with_saved_searches(&vault.path, &config.saved_searches)
+            .with_expanded_folders(&expanded);
+        self.explorer_auto_collapsed = false;
+        self.fit_panes();
+        self.note_editor = EditorState::default();
+        self.note_editor.registers = registers;
+        self.outline = OutlineState::new(&[], 0, self.outline.is_open(), self.outline.config);
+        self.selected_note = None;
+        self.vault_index = VaultIndex::default();
+        self.history = History::default();
+
+        self.vault_opened(vault, ActivePane::Explorer)
+    }
+
+    /// Shows the vault as it was left when another vault was opened, with the same note open in
+    /// the note editor. Returns `None` if the vault has not been open before.
+    fn restore_vault(&mut self, vault: &'a Vault) -> Option<Message<'a>> {
+        let index = self
+            .vault_cache
+            .iter()
+            .position(|cached| cached.vault.path == vault.path)?;
+
+        // Registers are shared by the vaults, for moving blocks between them.
+        let registers = std::mem::take(&mut self.note_editor.registers);
+        self.cache_vault();
+        let cached = self.vault_cache.remove(index);
+        self.vault = Some(cached.vault);
+        self.explorer = cached.explorer;
+        self.note_editor = cached.note_editor;
+        self.note_editor.registers = registers;
+        self.outline = OutlineState {
+            config: self.outline.config,
+            ..cached.outline
+        };
+        self.selected_note = cached.selected_note;
+        self.vault_index = cached.vault_index;
+        self.history = cached.history;
+        self.fit_panes();
+
+        self.vault_opened(vault, cached.active_pane)
+    }
+
+    /// Returns the path of the note the wikilink target points to. Notes that are not indexed yet,
+    /// as the index is updated in the background, are looked up in the vault.
+    fn find_linked_note(&self, target: &str) -> Option<PathBuf> {
+        if let Some(path) = self.vault_index.find(target) {
+            return Some(path.to_path_buf());
+        }
+
+        let target = target.trim();
+        let target = target.strip_suffix(".md").unwrap_or(target);
+
+        self.vault?
+            .notes()
+            .into_iter()
+            .find(|note| match target.contains('/') {
+                true => note.path.with_extension("").ends_with(target),
+                false => note.name == target,
+            })
+            .map(|note| note.path)
+    }
+
+    /// Returns the name of the note at the path as a wikilink target, or its path relative to the
+    /// vault without the extension when the name links to another note. Obsidian links a name
+    /// shared by several notes to the one with the shortest path.
+    fn link_name(&self, path: &Path) -> String {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        let vault = self.vault;
+
+        let linked = vault
+            .map(Vault::notes)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|note| note.name == name)
+            .min_by_key(|note| note.path.components().count());
+
+        match linked {
+            Some(linked) if linked.path != path => vault
+                .and_then(|vault| path.strip_prefix(&vault.path).ok())
+                .unwrap_or(path)
+                .with_extension("")
+                .to_string_lossy()
+                .to_string(),
+            _ => name.to_string(),
+        }
+    }
+
+    /// Completes the link anchor typed at the cursor of the note editor with the headings or
+    /// block IDs of the linked note, the typed link target with the names and aliases of the
+    /// notes, or the typed tag with the tags of the vault, and closes the completion when none is
+    /// typed. Anchors in the same note, e.g. `[[#Heading`, are completed from the edited note.
+    fn complete(&mut self) {
+        let before_cursor = self.note_editor.text_before_cursor().map(str::to_string);
+
+        if let Some(target) = before_cursor.as_deref().and_then(note_editor::embed_target) {
+            let completion = Completion::new(target, &self.attachment_names())
+                .map(|completion| completion.closing("]]"));
+            self.note_editor.set_completion(completion);
+            return;
+        }
+
+        if let Some(target) = before_cursor.as_deref().and_then(note_editor::link_target) {
+            let completion = Completion::new(target, &self.link_targets())
+                .map(|completion| completion.closing("]]"));
+            self.note_editor.set_completion(completion);
+            return;
+        }
+
+        let Some(anchor) = before_cursor.as_deref().and_then(note_editor::link_anchor) else {
+            let completion = before_cursor
+                .as_deref()
+                .and_then(note_editor::tag_prefix)
+                .and_then(|partial| Completion::new(partial, &self.vault_index.tags()));
+            self.note_editor.set_completion(completion);
+            return;
+        };
+
+        let anchors = match anchor.target.trim() {
+            "" => Some(Anchors::parse(self.note_editor.content())),
+            target => match self.find_linked_note(target) {
+                Some(path) => {
+                    if !matches!(&self.link_anchors, Some((cached, _)) if *cached == path) {
+                        let contents = fs::read_to_string(&path).unwrap_or_default();
+                        self.link_anchors = Some((path, Anchors::parse(&contents)));
+                    }
+                    self.link_anchors
+                        .as_ref()
+                        .map(|(_, anchors)| anchors.clone())
+                }
+                None => None,
+            },
+        };
+
+        let completion = anchors.and_then(|anchors| {
+            let candidates = match anchor.kind {
+                AnchorKind::Heading => anchors.headings,
+                AnchorKind::Block => anchors.block_ids,
+            };
+            Completion::new(anchor.partial, &candidates).map(|completion| completion.closing("]]"))
+        });
+
+        self.note_editor.set_completion(completion);
+    }
+
+    /// Returns the names of the indexed notes and their aliases as link targets with the alias as
+    /// the display text, e.g. `Ideas|Plans`, sorted ignoring case.
+    fn link_targets(&self) -> Vec<String> {
+        let mut targets: Vec<_> = self
+            .vault_index
+            .notes()
+            .flat_map(|(_, note)| {
+                std::iter::once(note.title.clone()).chain(
+                    note.aliases
+                        .iter()
+                        .map(|alias| format!("{}|{alias}", note.title)),
+                )
+            })
+            .collect();
+        targets.sort_by_key(|target| target.to_lowercase());
+        targets
+    }
+
+    /// Returns the file names of the canvases and attachments listed in the explorer, as embed
+    /// targets, sorted ignoring case.
+    fn attachment_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .explorer
+            .attachments()
+            .into_iter()
+            .filter_map(|path| Some(path.file_name()?.to_string_lossy().to_string()))
+            .collect();
+        names.sort_by_key(|name| name.to_lowercase());
+        names
+    }
+
+    /// Warns about embeds of files that do not exist in the pasted text and the cursor line, e.g.
+    /// a pasted image path that is misspelled.
+    fn warn_missing_embeds(&mut self, pasted: &str) {
+        let (Some(vault), Some(note)) = (self.vault, &self.selected_note) else {
+            return;
+        };
+
+        let index = self.vault_index.link_index(vault);
+        let line = self.note_editor.cursor_line().unwrap_or_default();
+        let mut missing: Vec<String> = [pasted, line.as_str()]
+            .into_iter()
+            .flat_map(|text| {
+                outgoing_links_modal::outgoing_links(text, Path::new(&note.path), &index)
+            })
+            .filter(|link| link.embed && link.is_broken())
+            .map(|link| link.link.target)
+            .collect();
+        missing.sort();
+        missing.dedup();
+
+        if !missing.is_empty() {
+            self.status_warning = Some(format!("Missing {}", missing.join(", ")));
+        }
+    }
+
+    /// Remembers the folders expanded in the explorer, so that they are expanded again when the
+    /// vault is opened next.
+    fn remember_expanded_folders(&self) {
+        let Some(vault) = self.vault else {
+            return;
+        };
+
+        let folders = self
+            .explorer
+            .expanded_folders()
+            .into_iter()
+            .filter_map(|folder| Some(folder.strip_prefix(&vault.path).ok()?.to_path_buf()))
+            .collect();
+
+        let mut session = Session::load();
+        session.set_expanded_folders(&vault.name, folders);
+        _ = session.save();
+    }
+
+    /// Remembers where the cursor is in the open note and where the note was changed last, so
+    /// that the note opens there again.
+    fn remember_note_position(&mut self) {
+        let path = self.note_editor.path();
+        if path.as_os_str().is_empty() {
+            return;
+        }
+
+        let position = NotePosition {
+            path: path.to_path_buf(),
+            cursor: self.note_editor.cursor_offset(),
+            last_edit: self.note_editor.last_edit(),
+        };
+
+        if self.session.note_position(path) == Some(&position) {
+            return;
+        }
+
+        self.session.set_note_position(position);
+
+        let mut session = Session::load();
+        session.note_positions = self.session.note_positions.clone();
+        _ = session.save();
+    }
+
+    /// Remembers the vaults opened since the start for the next start. The session file is only
+    /// written on quit, not on every switch between vaults.
+    fn remember_opened_vaults(&mut self) {
+        if self.opened_vaults.is_empty() {
+            return;
+        }
+
+        let mut session = Session::load();
+        self.opened_vaults
+            .drain(..)
+            .for_each(|vault| session.opened(&vault.name, vault.opened_at));
+        _ = session.save();
+    }
+
+    /// Adds the words written in the note editor to the writing statistics of the vault.
+    fn record_words(&mut self, words: usize) {
+        let Some(vault) = self.vault else {
+            return;
+        };
+
+        let today = chrono::Local::now().date_naive();
+        let mut stats = WritingStats::load();
+        stats.add(&vault.name, today, words);
+        _ = stats.save();
+
+        self.words_today = match self.words_today {
+            (day, words_today) if day == today => (today, words_today + words),
+            _ => (today, stats.words(&vault.name, today)),
+        };
+    }
+
+    /// Keeps the state of the open vault for switching back to it.
+    fn cache_vault(&mut self) {
+        let Some(vault) = self.vault.take() else {
+            return;
+        };
+
+        self.vault_cache.push(CachedVault {
+            vault,
+            explorer: std::mem::take(&mut self.explorer),
+            note_editor: std::mem::take(&mut self.note_editor),
+            outline: self.outline.clone(),
+            selected_note: self.selected_note.take(),
+            vault_index: std::mem::take(&mut self.vault_index),
+            history: std::mem::take(&mut self.history),
+            active_pane: self.active_pane,
+        });
+    }
+
+    /// Remembers the vault for the next start and focuses the pane, or opens the note that was
+    /// switched to.
+    fn vault_opened(&mut self, vault: &Vault, active_pane: ActivePane) -> Option<Message<'a>> {
+        self.opened_vaults.push(RecentVault {
+            name: vault.name.clone(),
+            opened_at: chrono::Local::now().timestamp(),
+        });
+
+        let today = chrono::Local::now().date_naive();
+        self.words_today = (today, WritingStats::load().words(&vault.name, today));
+
+        self.index_vault();
+        self.watcher = self
+            .background
+            .as_ref()
+            .map(|background| Arc::new(Watcher::spawn(vault.clone(), background.clone())));
+
+        match self
+            .pending_note
+            .take()
+            .filter(|path| path.starts_with(&vault.path))
+        {
+            Some(path) => Some(Message::OpenNote(Note::from(path.as_path()))),
+            None => Some(Message::SetActivePane(active_pane)),
+        }
+    }
+
+    /// Evaluates the query blocks of the open note again, as the notes they match might have
+    /// changed.
+    fn refresh_query_blocks(&mut self) {
+        if let Some(vault) = self.vault {
+            let results = query_block::evaluate(self.note_editor.nodes(), vault);
+            self.note_editor.set_query_results(results);
+        }
+    }
+
+    /// Brings the index of the open vault up to date in the background.
+    fn index_vault(&self) {
+        if let (Some(vault), Some(background)) = (self.vault, &self.background) {
+            background.spawn(Job::IndexVault(vault.clone()));
+        }
+    }
+
+    /// Shows the changes of the vault index in the tag browser and the query blocks.
+    fn index_changed(&mut self) {
+        if self.tag_browser_modal.visible {
+            self.tag_browser_modal.refresh(&self.vault_index);
+        }
+        self.refresh_query_blocks();
+    }
+
+    /// Returns `true` if the note at the path is open in the note editor.
+    fn is_selected_note(&self, path: &Path) -> bool {
+        self.selected_note
+            .as_ref()
+            .is_some_and(|note| Path::new(&note.path) == path)
+    }
+
     pub fn set_running(&self, is_running: bool) -> Self {
         Self {
             is_running,
@@ -85,49 +934,212 @@ impl<'a> AppState<'a> {
 #[derive(Clone, Debug, PartialEq)]
 pub enum Message<'a> {
     Quit,
+    /// Quits and prints the open note to stdout.
+    QuitAndPrint,
+    /// Saves all notes with unsaved changes.
+    SaveAll,
+    ArchiveNote,
+    Publish,
+    KeysDebug,
+    /// Asks for the note the key bindings are exported to.
+    KeysExport,
+    /// Writes the key bindings as markdown to the note, relative to the vault root.
+    ExportKeys(String),
+    MacroRecord,
+    CountDigit(usize),
+    /// Adds the character to the type-ahead query and selects the first matching item of the
+    /// focused list.
+    TypeAhead(char),
+    /// Handles the message count times, or once if the message is not a movement.
+    Repeat(usize, Option<Box<Message<'a>>>),
+    /// Plays the named macro of the configuration, or the last recorded macro.
+    MacroPlay(Option<String>),
+    InsertFromUrl,
+    QuickCapture,
+    /// Lists the notes of the open vault, or of all vaults, to open one by typing.
+    QuickSwitcher,
+    /// Opens the previous note of the navigation history.
+    HistoryBack,
+    /// Opens the next note of the navigation history, after going back.
+    HistoryForward,
+    /// Opens the note, opening the vault of the note first if it is not open.
+    SwitchToNote(PathBuf),
+    SearchReplace(Scope),
+    /// Asks for the tag to rename across the vault and its new name.
+    TagRename,
+    /// Lists the programs of the `[open_with]` section to run one with the open note.
+    OpenWith,
+    /// Runs the program of the `[open_with]` section with the name.
+    RunOpenWith(String),
+    FocusTimer(focus_timer::Message),
+    /// Switches the counts of the status bar between the whole note and the current block.
+    CycleCountScope,
+    /// Copies the link to the open note, e.g. `[[Note]]`, to the clipboard.
+    CopyLinkToNote,
+    /// Copies the link to the heading under the outline selector, e.g. `[[Note#Heading]]`, to
+    /// the clipboard.
+    CopyLinkToHeading,
+    /// Asks for the new text of the heading of the open note, to rename it and the links to it
+    /// across the vault.
+    HeadingRename(String),
+    PaneGrow,
+    PaneShrink,
+    ToggleZenMode,
     Exec(String),
     Spawn(String),
+    /// Asks for the arguments of the command, then runs it like `Exec`.
+    ExecPrompt(String),
     Resize(Size),
+    /// Focuses the pane after the focused pane, skipping collapsed panes.
+    SwitchPaneNext,
+    /// Focuses the pane before the focused pane, skipping collapsed panes.
+    SwitchPanePrevious,
+    /// Focuses the pane, opening it if it is collapsed.
+    FocusPane(ActivePane),
+    RunJob(Job),
     SetActivePane(ActivePane),
     OpenVault(&'a Vault),
+    /// The entries of the vault at the path, scanned in the background.
+    VaultScanned(PathBuf, Vec<VaultEntry>),
+    /// Files of the vault at the path were added, removed or modified outside Basalt.
+    VaultFilesChanged(PathBuf, watcher::Changes),
+    /// Updates the links to the renamed notes, given as their old and new paths.
+    FixRenamedLinks(Vec<(PathBuf, PathBuf)>),
+    /// Asks for the note the first broken link of the links points to instead.
+    FixLink(Vec<Link>),
+    /// Replaces the link target at the range of the note editor content with the new target, if
+    /// the range still contains the old target.
+    ReplaceLinkTarget(Range<usize>, String, String),
     SelectNote(SelectedNote),
+    /// Opens the note where the cursor was when the note was closed before.
+    OpenNote(Note),
+    OpenNoteAt(Note, usize),
+    /// Moves the cursor of the note editor to the byte offset of the open note and focuses it.
+    JumpToOffset(usize),
+    /// Opens a file that is not a note with the configured opener.
+    OpenAttachment(PathBuf),
+    DeleteAttachments(Vec<PathBuf>),
+    /// Moves the attachments to the folder, relative to the vault root.
+    MoveAttachments(Vec<PathBuf>, String),
+    CreateNote(PathBuf),
+    DeleteNote(Note),
+    MergeNotes(Note, Note),
+    ReplaceNote(Note, Note),
     UpdateSelectedNoteContent((String, Option<Vec<Node>>)),
+    DuplicateNote(Note, String),
+    SaveNoteAsTemplate(Note, String),
+    PublishNotes(String),
+    ClipUrl(String),
+    /// Appends the text to the inbox note.
+    Capture(String),
+    /// Moves the text of the note editor into the target note, given as `Note#Heading`.
+    ExtractTo(String, String),
+    /// The clipped page for the note at the path it was clipped for, or for a new note.
+    WebClipped(Option<PathBuf>, std::result::Result<WebClip, String>),
+    /// The index of the vault at the path was brought up to date.
+    VaultIndexed(PathBuf, VaultIndex),
+    SearchPattern(Scope, String),
+    ReplacedMatches(String, usize),
+    VaultReplaced(VaultReplaceReport),
+    SetStatus(String),
 
     Splash(splash_modal::Message),
+    VaultLoadingModal(vault_loading_modal::Message),
     Explorer(explorer::Message),
     NoteEditor(note_editor::Message),
     Outline(outline::Message),
     HelpModal(help_modal::Message),
     VaultSelectorModal(vault_selector_modal::Message),
+    VaultStatsModal(vault_stats_modal::Message),
+    DiagnosticsModal(diagnostics_modal::Message),
+    AttachmentsModal(attachments_modal::Message),
+    DuplicatesModal(duplicates_modal::Message),
+    NoteDiffModal(note_diff_modal::Message),
+    NoteHistoryModal(note_history_modal::Message),
+    /// Replaces the note editor content with a version of the note from its git history, given
+    /// as the content and the commit.
+    RestoreNoteVersion(String, String),
+    InputModal(input_modal::Message),
+    ReplaceModal(replace_modal::Message),
+    VaultReplaceModal(vault_replace_modal::Message),
+    CanvasModal(canvas_modal::Message),
+    TagBrowserModal(tag_browser_modal::Message),
+    OutgoingLinksModal(outgoing_links_modal::Message),
+    JobsModal(jobs_modal::Message),
+    ConfigReportModal(config_report_modal::Message),
+    ErrorLogModal(error_log_modal::Message),
+    /// Reports a failed operation in the status bar and the error log.
+    Error(String),
+    QuitModal(quit_modal::Message),
+    SaveConflictModal(save_conflict_modal::Message),
+    KeysDebugModal(keys_debug_modal::Message),
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub enum ActivePane {
     #[default]
     Splash,
+    VaultLoadingModal,
     Explorer,
     NoteEditor,
     Outline,
     HelpModal,
     VaultSelectorModal,
+    VaultStatsModal,
+    DiagnosticsModal,
+    AttachmentsModal,
+    DuplicatesModal,
+    NoteDiffModal,
+    NoteHistoryModal,
+    InputModal,
+    ReplaceModal,
+    VaultReplaceModal,
+    CanvasModal,
+    TagBrowserModal,
+    OutgoingLinksModal,
+    JobsModal,
+    ConfigReportModal,
+    ErrorLogModal,
+    QuitModal,
+    SaveConflictModal,
+    KeysDebugModal,
 }
 
 impl From<ActivePane> for &str {
     fn from(value: ActivePane) -> Self {
         match value {
             ActivePane::Splash => "Splash",
+            ActivePane::VaultLoadingModal => "Opening Vault",
             ActivePane::Explorer => "Explorer",
             ActivePane::NoteEditor => "Note Editor",
             ActivePane::Outline => "Outline",
             ActivePane::HelpModal => "Help",
             ActivePane::VaultSelectorModal => "Vault Selector",
+            ActivePane::VaultStatsModal => "Vault Statistics",
+            ActivePane::DiagnosticsModal => "Diagnostics",
+            ActivePane::AttachmentsModal => "Attachments",
+            ActivePane::DuplicatesModal => "Duplicates",
+            ActivePane::NoteDiffModal => "Diff",
+            ActivePane::NoteHistoryModal => "History",
+            ActivePane::InputModal => "Input",
+            ActivePane::ReplaceModal => "Replace",
+            ActivePane::VaultReplaceModal => "Vault Replace",
+            ActivePane::CanvasModal => "Canvas",
+            ActivePane::TagBrowserModal => "Tags",
+            ActivePane::OutgoingLinksModal => "Links",
+            ActivePane::JobsModal => "Jobs",
+            ActivePane::ConfigReportModal => "Configuration",
+            ActivePane::ErrorLogModal => "Errors",
+            ActivePane::QuitModal => "Quit",
+            ActivePane::SaveConflictModal => "Changed on Disk",
+            ActivePane::KeysDebugModal => "Keys",
         }
     }
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct SelectedNote {
-    name: String,
+    pub(crate) name: String,
     path: String,
     content: String,
 }
@@ -142,96 +1154,327 @@ impl From<&Note> for SelectedNote {
     }
 }
 
+/// Returns the version shown in the splash screen and the help.
+pub fn version() -> String {
+    stylized_text::stylize(&format!("{VERSION}~beta"), FontStyle::Script)
+}
+
 fn help_text(version: &str) -> String {
     HELP_TEXT.replace("%version-notice", version)
 }
 
 pub struct App<'a> {
-    state: AppState<'a>,
-    config: Config<'a>,
-    terminal: RefCell<DefaultTerminal>,
+    pub(crate) state: AppState<'a>,
+    pub(crate) config: Config<'a>,
+    /// Colors of the terminal, resolved from the configuration when the application starts.
+    colors: ColorSupport,
 }
 
 impl<'a> App<'a> {
-    pub fn new(state: AppState<'a>, terminal: DefaultTerminal) -> Self {
+    /// Creates the application with the configuration loaded from the user configuration file.
+    pub fn new(mut state: AppState<'a>) -> Self {
+        let (config, config_report) = config::load();
+
+        if let Some(report) = config_report {
+            report
+                .problems
+                .iter()
+                .for_each(|problem| state.log_error(format!("Configuration {problem}")));
+            state.config_report_modal = ConfigReportModalState::new(report);
+        }
+
+        App::with_config(state, config)
+    }
+
+    pub fn with_config(mut state: AppState<'a>, config: Config<'a>) -> Self {
+        if let Some(logo) = config
+            .splash_screen
+            .logo
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+        {
+            state.splash_modal.set_logo(logo);
+        }
+
         Self {
-            state,
-            // TODO: Surface toast if read config returns error
-            config: config::load().unwrap(),
-            terminal: RefCell::new(terminal),
+            state: AppState {
+                explorer_width: config.explorer_settings.width.unwrap_or(PANE_DEFAULT_WIDTH),
+                outline_width: config.outline_settings.width.unwrap_or(PANE_DEFAULT_WIDTH),
+                outline: OutlineState {
+                    config: config.outline_settings,
+                    ..state.outline
+                },
+                ..state
+            },
+            colors: config.ui.colors.resolve(),
+            config,
         }
     }
 
-    pub fn start(terminal: DefaultTerminal, vaults: Vec<&Vault>) -> Result<()> {
-        let version = stylized_text::stylize(&format!("{VERSION}~beta"), FontStyle::Script);
-        let size = terminal.size()?;
+    /// Returns the state the application starts in, showing the splash screen with the vaults.
+    pub fn initial_state(
+        version: &'a str,
+        screen_size: Size,
+        vaults: Vec<&'a Vault>,
+    ) -> AppState<'a> {
+        let session = Session::load();
 
-        let state = AppState {
-            screen_size: size,
-            help_modal: HelpModalState::new(&help_text(&version)),
+        let mut state = AppState {
+            screen_size,
+            help_modal: HelpModalState::new(&help_text(version)),
             vault_selector_modal: VaultSelectorModalState::new(vaults.clone()),
-            splash_modal: SplashModalState::new(&version, vaults, true),
+            splash_modal: SplashModalState::new(version, vaults, true),
             ..Default::default()
         };
+        state
+            .vault_selector_modal
+            .vault_selector_state
+            .arrange(session.clone());
+        state
+            .splash_modal
+            .vault_selector_state
+            .arrange(session.clone());
+        state.session = session;
+        state
+    }
 
-        App::new(state, terminal).run()
+    /// Runs the application until it quits. Returns the open note in the format to print, if
+    /// printing was asked for with `print` or the `quit_and_print` command.
+    pub fn start(
+        mut terminal: DefaultTerminal,
+        vaults: Vec<&Vault>,
+        print: Option<PrintFormat>,
+    ) -> Result<Option<String>> {
+        let version = version();
+        let mut state = App::initial_state(&version, terminal.size()?, vaults);
+        state.print = print;
+
+        App::new(state).run(&mut terminal)
     }
 
-    fn run(&'a mut self) -> Result<()> {
+    fn run(&'a mut self, terminal: &mut DefaultTerminal) -> Result<Option<String>> {
         self.state.is_running = true;
 
-        let mut state = self.state.clone();
+        let mut state = std::mem::take(&mut self.state);
         let config = self.config.clone();
-        while state.is_running {
-            self.draw(&mut state.clone())?;
-            let event = event::read()?;
 
-            let mut message = App::handle_event(&config, &state, &event);
-            while message.is_some() {
-                message = App::update(self.terminal.get_mut(), &config, &mut state, message);
-            }
+        let (sender, receiver) = background::channel();
+        state.background = Some(sender);
+
+        state.fit_panes();
+
+        let mut message = App::startup_message(&config, &mut state);
+        while message.is_some() {
+            message = App::update(terminal, &config, &mut state, message);
         }
 
-        Ok(())
-    }
+        // The screen is only drawn again after an event or a background job produced messages,
+        // so an idle application does not redraw.
+        let mut redraw = true;
 
-    fn draw(&self, state: &mut AppState<'a>) -> Result<()> {
-        let mut terminal = self.terminal.borrow_mut();
+        while state.is_running {
+            if redraw {
+                self.draw(terminal, &mut state)?;
+                redraw = false;
+            }
 
-        terminal.draw(move |frame| {
-            let area = frame.area();
-            let buf = frame.buffer_mut();
-            self.render_ref(area, buf, state);
-        })?;
+            let mut messages: Vec<Message> = receiver.try_iter().collect();
 
-        Ok(())
-    }
+            let poll_interval = if state.smooth_scroll.is_empty() {
+                EVENT_POLL_INTERVAL
+            } else {
+                SMOOTH_SCROLL_INTERVAL
+            };
 
-    fn handle_event(
-        config: &'a Config,
-        state: &AppState<'_>,
-        event: &Event,
-    ) -> Option<Message<'a>> {
-        match event {
-            Event::Resize(cols, rows) => Some(Message::Resize(Size::new(*cols, *rows))),
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                App::handle_key_event(config, state, key_event)
+            if event::poll(poll_interval)? {
+                let event = event::read()?;
+                if matches!(event, Event::Key(_) | Event::Paste(_)) {
+                    redraw |= state.status_message.take().is_some();
+                    redraw |= state.status_warning.take().is_some();
+                }
+
+                if let (Some(keys), Event::Key(key)) = (&mut state.recording_macro, &event) {
+                    if key.kind == KeyEventKind::Press {
+                        keys.push(*key);
+                    }
+                }
+
+                messages.extend(App::handle_event(&config, &state, &event));
             }
-            _ => None,
-        }
-    }
 
-    #[rustfmt::skip]
+            messages.extend(state.smooth_scroll.pop_front());
+            messages.extend(
+                state
+                    .focus_timer
+                    .tick(Instant::now())
+                    .map(Message::FocusTimer),
+            );
+            messages.extend(state.jobs_modal.tick(Instant::now()));
+
+            redraw |= !messages.is_empty();
+
+            for message in messages {
+                let mut message = Some(message);
+                while message.is_some() {
+                    message = App::update(terminal, &config, &mut state, message);
+                }
+            }
+
+            // Macro keys are handled one at a time like pressed keys, so each key sees the state
+            // left by the previous key.
+            if !state.macro_keys.is_empty() {
+                redraw = true;
+                state.playing_macro = true;
+                for key in std::mem::take(&mut state.macro_keys) {
+                    let mut message = App::handle_key_event(&config, &state, &key);
+                    while message.is_some() {
+                        message = App::update(terminal, &config, &mut state, message);
+                    }
+                }
+                state.playing_macro = false;
+            }
+
+            if redraw && config.ui.announcements != Announcements::Off {
+                App::announce(terminal, &config, &mut state)?;
+            }
+        }
+
+        let width = state.screen_size.width;
+        Ok(state
+            .print
+            .filter(|_| state.selected_note.is_some())
+            .map(|format| print::note(&mut state.note_editor, format, width)))
+    }
+
+    /// Announces the focus and selection changes since the last announcement.
+    fn announce(
+        terminal: &mut DefaultTerminal,
+        config: &Config,
+        state: &mut AppState<'a>,
+    ) -> Result<()> {
+        let focus = state.focus();
+        let Some(announcement) = focus.announcement(&state.focus) else {
+            return Ok(());
+        };
+        state.focus = focus;
+
+        match config.ui.announcements {
+            Announcements::Osc => {
+                let backend = terminal.backend_mut();
+                backend.write_all(screen_reader::osc_notification(&announcement).as_bytes())?;
+                Write::flush(backend)?;
+            }
+            _ => state.announcement = Some(announcement),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the message that opens a vault directly on startup, skipping the splash screen,
+    /// when enabled in the configuration.
+    pub(crate) fn startup_message(
+        config: &Config,
+        state: &mut AppState<'a>,
+    ) -> Option<Message<'a>> {
+        let vaults = state.splash_modal.clone().items();
+
+        // The last vault is opened even if it has been hidden from the list since.
+        let last_vault = config
+            .splash_screen
+            .open_last_vault
+            .then(Session::load)
+            .and_then(|session| session.last_vault)
+            .and_then(|name| {
+                state
+                    .splash_modal
+                    .vault_selector_state
+                    .all()
+                    .iter()
+                    .find(|vault| vault.name == name)
+                    .copied()
+            });
+
+        let vault = match vaults.as_slice() {
+            [vault] if config.splash_screen.skip_single_vault => Some(*vault),
+            _ => last_vault,
+        }?;
+
+        state.splash_modal.hide();
+        Some(Message::OpenVault(vault))
+    }
+
+    pub(crate) fn draw<B: Backend>(
+        &self,
+        terminal: &mut Terminal<B>,
+        state: &mut AppState<'a>,
+    ) -> Result<()> {
+        terminal.draw(move |frame| {
+            let area = frame.area();
+            let buf = frame.buffer_mut();
+            self.render_ref(area, buf, state);
+            accessibility::apply(buf, &self.config.ui);
+            color_support::apply(buf, self.colors);
+        })?;
+
+        Ok(())
+    }
+
+    fn handle_event(
+        config: &'a Config,
+        state: &AppState<'_>,
+        event: &Event,
+    ) -> Option<Message<'a>> {
+        match event {
+            Event::Resize(cols, rows) => Some(Message::Resize(Size::new(*cols, *rows))),
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                App::handle_key_event(config, state, key_event)
+            }
+            // Pasted text is only delivered as a whole with bracketed paste, which is enabled on
+            // startup. Other panes ignore pasted text.
+            Event::Paste(text) => match state.active_component() {
+                ActivePane::InputModal => Some(Message::InputModal(input_modal::Message::Paste(
+                    text.clone(),
+                ))),
+                ActivePane::NoteEditor if state.note_editor.is_editing() => Some(
+                    Message::NoteEditor(note_editor::Message::Paste(text.clone())),
+                ),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    #[rustfmt::skip]
     fn handle_active_component_event(config: &'a Config, state: &AppState<'_>, key: &KeyEvent, active_component: ActivePane) -> Option<Message<'a>> {
         match active_component {
             ActivePane::Splash => config.splash.key_to_message(key.into()),
+            ActivePane::VaultLoadingModal => vault_loading_modal::handle_event(key).map(Message::VaultLoadingModal),
             ActivePane::Explorer => config.explorer.key_to_message(key.into()),
             ActivePane::Outline => config.outline.key_to_message(key.into()),
+            ActivePane::HelpModal if state.help_modal.searching => help_modal::handle_search_event(key).map(Message::HelpModal),
             ActivePane::HelpModal => config.help_modal.key_to_message(key.into()),
             ActivePane::VaultSelectorModal => config.vault_selector_modal.key_to_message(key.into()),
+            ActivePane::VaultStatsModal => config.vault_stats_modal.key_to_message(key.into()),
+            ActivePane::DiagnosticsModal => config.diagnostics_modal.key_to_message(key.into()),
+            ActivePane::AttachmentsModal => config.attachments_modal.key_to_message(key.into()),
+            ActivePane::DuplicatesModal => config.duplicates_modal.key_to_message(key.into()),
+            ActivePane::NoteDiffModal => config.note_diff_modal.key_to_message(key.into()),
+            ActivePane::NoteHistoryModal => config.note_history_modal.key_to_message(key.into()),
+            ActivePane::InputModal => input_modal::handle_event(key, &state.input_modal).map(Message::InputModal),
+            ActivePane::ReplaceModal => replace_modal::handle_event(key).map(Message::ReplaceModal),
+            ActivePane::VaultReplaceModal => config.vault_replace_modal.key_to_message(key.into()),
+            ActivePane::CanvasModal => config.canvas_modal.key_to_message(key.into()),
+            ActivePane::TagBrowserModal => config.tag_browser_modal.key_to_message(key.into()),
+            ActivePane::OutgoingLinksModal => config.outgoing_links_modal.key_to_message(key.into()),
+            ActivePane::JobsModal => config.jobs_modal.key_to_message(key.into()),
+            ActivePane::ConfigReportModal => config_report_modal::handle_event(key).map(Message::ConfigReportModal),
+            ActivePane::ErrorLogModal => error_log_modal::handle_event(key).map(Message::ErrorLogModal),
+            ActivePane::QuitModal => quit_modal::handle_event(key).map(Message::QuitModal),
+            ActivePane::SaveConflictModal => save_conflict_modal::handle_event(key).map(Message::SaveConflictModal),
+            ActivePane::KeysDebugModal => keys_debug_modal::handle_event(key).map(Message::KeysDebugModal),
             ActivePane::NoteEditor => {
                     if state.note_editor.is_editing() {
-                        note_editor::handle_editing_event(key).map(Message::NoteEditor)
+                        note_editor::handle_editing_event(key, &state.note_editor).map(Message::NoteEditor)
                     } else {
                         config.note_editor.key_to_message(key.into())
                 }
@@ -239,34 +1482,272 @@ impl<'a> App<'a> {
         }
     }
 
-    fn handle_key_event(
+    pub(crate) fn handle_key_event(
         config: &'a Config,
         state: &AppState<'_>,
         key: &KeyEvent,
     ) -> Option<Message<'a>> {
         let global_message = config.global.key_to_message(key.into());
+        let active_component = state.active_component();
+
+        // The note editor can stay in edit mode after focusing another pane, e.g. when notes open
+        // in edit mode from the explorer.
+        let is_editing = match active_component {
+            ActivePane::NoteEditor => state.note_editor.is_editing(),
+            ActivePane::HelpModal => state.help_modal.searching,
+            ActivePane::InputModal
+            | ActivePane::ReplaceModal
+            | ActivePane::ConfigReportModal
+            | ActivePane::ErrorLogModal
+            | ActivePane::VaultLoadingModal
+            | ActivePane::KeysDebugModal
+            | ActivePane::QuitModal
+            | ActivePane::SaveConflictModal => true,
+            _ => false,
+        };
+
+        let takes_count = matches!(
+            active_component,
+            ActivePane::Explorer | ActivePane::Outline | ActivePane::NoteEditor
+        ) && !is_editing;
+
+        // Characters that are not bound to a command jump to the matching item of a list. Digits
+        // are left to counts. Once a query is typed, every character continues it until a pause,
+        // so that names with bound letters can be typed.
+        let takes_type_ahead = matches!(
+            active_component,
+            ActivePane::Explorer | ActivePane::Outline | ActivePane::VaultSelectorModal
+        ) && !is_editing;
+        let type_ahead = match (key.code, key.modifiers.difference(KeyModifiers::SHIFT)) {
+            (KeyCode::Char(c), KeyModifiers::NONE) if takes_type_ahead => Some(c),
+            _ => None,
+        };
+
+        if let Some(c) = type_ahead {
+            let (pane, query) = &state.type_ahead;
+            if *pane == active_component && query.is_active(Instant::now()) {
+                return Some(Message::TypeAhead(c));
+            }
+        }
 
-        let is_editing = state.note_editor.is_editing();
+        let message = match global_message {
+            Some(message) if !is_editing => Some(message),
+            _ => App::handle_active_component_event(config, state, key, active_component),
+        };
 
-        if global_message.is_some() && !is_editing {
-            return global_message;
+        if let (Some(c), None) = (type_ahead, &message) {
+            if !(takes_count && c.is_ascii_digit()) {
+                return Some(Message::TypeAhead(c));
+            }
         }
 
-        let active_component = state.active_component();
-        App::handle_active_component_event(config, state, key, active_component)
+        if !takes_count {
+            return message;
+        }
+
+        // Digits that are not bound to a command are collected into a count, which repeats the
+        // next command. Zero only continues a count.
+        match (key.code, key.modifiers, message) {
+            (KeyCode::Char(digit @ '0'..='9'), KeyModifiers::NONE, None)
+                if digit != '0' || state.pending_count.is_some() =>
+            {
+                digit
+                    .to_digit(10)
+                    .map(|digit| Message::CountDigit(digit as usize))
+            }
+            (_, _, message) => match state.pending_count {
+                Some(count) => Some(Message::Repeat(count, message.map(Box::new))),
+                None => message,
+            },
+        }
     }
 
-    fn update(
-        terminal: &mut DefaultTerminal,
+    /// Returns `true` for the movement messages that a count repeats.
+    fn repeats(message: &Message) -> bool {
+        matches!(
+            message,
+            Message::Explorer(
+                explorer::Message::Up
+                    | explorer::Message::Down
+                    | explorer::Message::ScrollUp(_)
+                    | explorer::Message::ScrollDown(_)
+            ) | Message::Outline(outline::Message::Up | outline::Message::Down)
+                | Message::NoteEditor(
+                    note_editor::Message::CursorUp
+                        | note_editor::Message::CursorDown
+                        | note_editor::Message::CursorLeft
+                        | note_editor::Message::CursorRight
+                        | note_editor::Message::CursorWordForward
+                        | note_editor::Message::CursorWordBackward
+                        | note_editor::Message::ScrollUp(_)
+                        | note_editor::Message::ScrollDown(_)
+                        | note_editor::Message::NextHeading
+                        | note_editor::Message::PreviousHeading
+                        | note_editor::Message::MoveNodeUp
+                        | note_editor::Message::MoveNodeDown
+                )
+        )
+    }
+
+    /// Returns the steps of a half page scroll of the note editor, the explorer or the help modal,
+    /// or `None` for other messages. In edit mode the note editor moves the cursor instead of
+    /// scrolling.
+    fn smooth_scroll_steps(
+        state: &AppState<'a>,
+        message: &Message<'a>,
+    ) -> Option<Vec<Message<'a>>> {
+        let to_message: fn(ScrollAmount) -> Message<'a> = match message {
+            Message::NoteEditor(note_editor::Message::ScrollUp(ScrollAmount::HalfPage))
+                if !state.note_editor.is_editing() =>
+            {
+                |amount| Message::NoteEditor(note_editor::Message::ScrollUp(amount))
+            }
+            Message::NoteEditor(note_editor::Message::ScrollDown(ScrollAmount::HalfPage))
+                if !state.note_editor.is_editing() =>
+            {
+                |amount| Message::NoteEditor(note_editor::Message::ScrollDown(amount))
+            }
+            Message::Explorer(explorer::Message::ScrollUp(ScrollAmount::HalfPage)) => {
+                |amount| Message::Explorer(explorer::Message::ScrollUp(amount))
+            }
+            Message::Explorer(explorer::Message::ScrollDown(ScrollAmount::HalfPage)) => {
+                |amount| Message::Explorer(explorer::Message::ScrollDown(amount))
+            }
+            Message::HelpModal(help_modal::Message::ScrollUp(ScrollAmount::HalfPage)) => {
+                |amount| Message::HelpModal(help_modal::Message::ScrollUp(amount))
+            }
+            Message::HelpModal(help_modal::Message::ScrollDown(ScrollAmount::HalfPage)) => {
+                |amount| Message::HelpModal(help_modal::Message::ScrollDown(amount))
+            }
+            _ => return None,
+        };
+
+        Some(
+            (0..SMOOTH_SCROLL_STEPS)
+                .map(|step| to_message(ScrollAmount::HalfPageStep(step, SMOOTH_SCROLL_STEPS)))
+                .collect(),
+        )
+    }
+
+    pub(crate) fn update<B: Backend>(
+        terminal: &mut Terminal<B>,
         config: &Config,
         state: &mut AppState<'a>,
         message: Option<Message<'a>>,
     ) -> Option<Message<'a>> {
-        match message? {
-            Message::Quit => state.is_running = false,
-            Message::Resize(size) => state.screen_size = size,
+        let message = message?;
+        crash::record(&message);
+
+        if config.ui.smooth_scroll && !state.playing_macro {
+            if let Some(steps) = App::smooth_scroll_steps(state, &message) {
+                state.smooth_scroll.extend(steps);
+                return None;
+            }
+        }
+
+        match message {
+            Message::Quit => {
+                // The open quit modal confirms quitting with unsaved changes.
+                let unsaved = state.unsaved_notes();
+                if !unsaved.is_empty() && !state.quit_modal.visible {
+                    return Some(Message::QuitModal(quit_modal::Message::Open(unsaved)));
+                }
+
+                state.remember_note_position();
+                state.remember_opened_vaults();
+                state.is_running = false;
+            }
+            Message::QuitAndPrint => {
+                state.print = state.print.or(Some(PrintFormat::Raw));
+                return Some(Message::Quit);
+            }
+            Message::SaveAll => {
+                // The quit is cancelled, as the open note is not saved until the conflict is
+                // resolved.
+                if let Some(message) = state.save_conflict() {
+                    state.quit_modal.visible = false;
+                    return Some(message);
+                }
+
+                let unsaved = state.unsaved_notes();
+
+                state.vault_cache.iter_mut().for_each(|cached| {
+                    cached.note_editor.save();
+                    if let Some(note) = cached.selected_note.as_mut() {
+                        note.content = cached.note_editor.content().to_string();
+                    }
+                });
+
+                state.note_editor.save();
+                let mut message = Some(Message::UpdateSelectedNoteContent((
+                    state.note_editor.content().to_string(),
+                    None,
+                )));
+                while message.is_some() {
+                    message = App::update(terminal, config, state, message);
+                }
+
+                state.status_message = Some(match unsaved.len() {
+                    0 => "No unsaved notes".to_string(),
+                    1 => format!("Saved {}", unsaved[0]),
+                    count => format!("Saved {count} notes"),
+                });
+
+                // Quitting from the quit modal remembers the session like any other quit.
+                if state.quit_modal.visible {
+                    return Some(Message::Quit);
+                }
+            }
+            Message::Resize(size) => {
+                state.screen_size = size;
+                state.fit_panes();
+            }
+            Message::RunJob(job) => {
+                if let Some(background) = &state.background {
+                    background.spawn(job);
+                }
+            }
+            Message::PaneGrow => state.resize_pane(PANE_RESIZE_STEP),
+            Message::PaneShrink => state.resize_pane(-PANE_RESIZE_STEP),
+            Message::ToggleZenMode => {
+                state.zen_mode = !state.zen_mode;
+
+                if state.zen_mode {
+                    state.explorer.set_active(false);
+                    state.outline.set_active(false);
+                    return Some(Message::SetActivePane(ActivePane::NoteEditor));
+                }
+            }
+            Message::SwitchPaneNext => {
+                return Some(Message::FocusPane(state.cycle_pane(true)));
+            }
+            Message::SwitchPanePrevious => {
+                return Some(Message::FocusPane(state.cycle_pane(false)));
+            }
+            Message::FocusPane(active_pane) => {
+                state.vault?;
+
+                match active_pane {
+                    ActivePane::Explorer if !state.explorer.is_open() => {
+                        state.explorer.open = true;
+                        state.explorer_auto_collapsed = false;
+                    }
+                    ActivePane::Outline if !state.outline.is_open() => {
+                        state.outline.open();
+                        state.outline_auto_collapsed = false;
+                    }
+                    _ => {}
+                }
+
+                state.explorer.set_active(false);
+                state.note_editor.set_active(false);
+                state.outline.set_active(false);
+                return Some(Message::SetActivePane(active_pane));
+            }
             Message::SetActivePane(active_pane) => match active_pane {
+                // Panes hidden by zen mode are revealed when they are focused.
                 ActivePane::Explorer => {
+                    state.zen_mode = false;
                     state.active_pane = active_pane;
                     // TODO: use event/message
                     state.explorer.set_active(true);
@@ -277,168 +1758,3020 @@ impl<'a> App<'a> {
                     state.note_editor.set_active(true);
                 }
                 ActivePane::Outline => {
+                    state.zen_mode = false;
                     state.active_pane = active_pane;
                     // TODO: use event/message
                     state.outline.set_active(true);
                 }
                 _ => {}
             },
+            // Scanning a large vault takes a while, so the vault is scanned in the background
+            // behind a progress modal, after the startup commands of the vault ran. Without a
+            // background thread, e.g. in headless mode, the vault is scanned right away. A vault
+            // that was open before is restored instead, without running its startup commands.
             Message::OpenVault(vault) => {
-                state.explorer = ExplorerState::new(&vault.name, vault.entries());
-                state.note_editor = EditorState::default();
-                return Some(Message::SetActivePane(ActivePane::Explorer));
+                if let Some(message) = state.restore_vault(vault) {
+                    return Some(message);
+                }
+
+                let commands = config
+                    .vault_startup
+                    .get(&vault.name)
+                    .cloned()
+                    .unwrap_or_default();
+
+                match &state.background {
+                    Some(background) => {
+                        state.vault_loading_modal.start(vault);
+                        background.spawn(Job::ScanVault(vault.clone(), commands));
+                    }
+                    None => {
+                        for command in commands {
+                            if let Some(Message::Error(error)) =
+                                command::vault_command(command, &vault.name, &vault.path)
+                            {
+                                state.log_error(error);
+                            }
+                        }
+                        return state.open_vault(config, vault, vault.entries());
+                    }
+                }
+            }
+            Message::VaultScanned(path, entries) => {
+                if let Some(vault) = state.vault_loading_modal.finish(&path) {
+                    return state.open_vault(config, vault, entries);
+                }
             }
             Message::SelectNote(selected_note) => {
                 state.selected_note = Some(selected_note.clone());
+                state.history.visit(Path::new(&selected_note.path));
+                // The linked notes might have been changed in the open note.
+                state.link_anchors = None;
+
+                state.remember_note_position();
 
                 // TODO: This should be behind an event/message
                 let active = state.note_editor.active();
+                let registers = std::mem::take(&mut state.note_editor.registers);
                 state.note_editor = EditorState::default();
                 state.note_editor.set_active(active);
+                state.note_editor.registers = registers;
                 state.note_editor.set_path(selected_note.path.into());
                 state.note_editor.set_content(&selected_note.content);
-
-                if !config.experimental_editor {
-                    state.note_editor.mode = Mode::Read;
+                if let Some(position) = state.session.note_position(state.note_editor.path()) {
+                    state
+                        .note_editor
+                        .restore_position(position.cursor, position.last_edit);
                 }
+                let read_only = Note::read_only(
+                    &Note::from(state.note_editor.path()),
+                    &selected_note.content,
+                );
+                state.note_editor.set_read_only(read_only);
+
+                state.note_editor.mode = match config.experimental_editor {
+                    // Read-only notes never open in edit mode.
+                    true if state.note_editor.read_only().is_some() => {
+                        match config.editor.default_mode {
+                            Mode::Edit => Mode::View,
+                            mode => mode,
+                        }
+                    }
+                    true => config.editor.default_mode,
+                    false => Mode::Read,
+                };
 
                 // TODO: This should be behind an event/message
                 state.outline = OutlineState::new(
                     state.note_editor.nodes(),
                     state.note_editor.current_row,
                     state.outline.is_open(),
+                    state.outline.config,
                 );
+                state.refresh_query_blocks();
             }
             Message::UpdateSelectedNoteContent((updated_content, nodes)) => {
                 if let Some(selected_note) = state.selected_note.as_mut() {
+                    // Only a saved note is indexed again, as the index follows the note files.
+                    state
+                        .vault_index
+                        .update_note(&Note::from(Path::new(&selected_note.path)));
                     selected_note.content = updated_content;
+                    state.refresh_query_blocks();
                     return nodes.map(|nodes| Message::Outline(outline::Message::SetNodes(nodes)));
                 }
             }
-            Message::Exec(command) => {
-                let (note_name, note_path) = state
-                    .selected_note
-                    .as_ref()
-                    .map(|note| (note.name.as_str(), note.path.as_str()))
-                    .unwrap_or_default();
+            Message::OpenNote(note) => {
+                state.explorer.select_path(&note.path);
+                state.explorer.select();
 
-                return command::sync_command(
-                    terminal,
-                    command,
-                    state.explorer.title,
-                    note_name,
-                    note_path,
-                );
+                let mut message = Some(Message::SelectNote(SelectedNote::from(&note)));
+                while message.is_some() {
+                    message = App::update(terminal, config, state, message);
+                }
+
+                return Some(Message::JumpToOffset(state.note_editor.cursor_offset()));
             }
+            Message::OpenNoteAt(note, offset) => {
+                let mut message = Some(Message::OpenNote(note));
+                while message.is_some() {
+                    message = App::update(terminal, config, state, message);
+                }
 
-            Message::Spawn(command) => {
-                let (note_name, note_path) = state
-                    .selected_note
-                    .as_ref()
-                    .map(|note| (note.name.as_str(), note.path.as_str()))
-                    .unwrap_or_default();
+                return Some(Message::JumpToOffset(offset));
+            }
+            Message::JumpToOffset(offset) => {
+                state.explorer.set_active(false);
+                state.outline.set_active(false);
 
-                return command::spawn_command(command, state.explorer.title, note_name, note_path);
+                let row = state.note_editor.row_at_offset(offset);
+                state.note_editor.set_row(row);
+                state.active_pane = ActivePane::NoteEditor;
+                state.note_editor.set_active(true);
+
+                return Some(Message::Outline(outline::Message::SelectAt(row)));
             }
+            Message::CreateNote(path) => {
+                if !state.vault.is_some_and(|vault| vault.contains(&path)) {
+                    return Some(Message::Error(format!(
+                        "Could not create {}: the path is outside the vault",
+                        path.display()
+                    )));
+                }
 
-            Message::HelpModal(message) => {
-                return help_modal::update(&message, state.screen_size, &mut state.help_modal);
+                let note = match Note::create(&path) {
+                    Ok(note) => note,
+                    Err(err) => return error("Could not create note", err),
+                };
+                let message = state.vault_changed();
+                state.explorer.select_path(&note.path);
+                return message;
             }
-            Message::VaultSelectorModal(message) => {
-                return vault_selector_modal::update(&message, &mut state.vault_selector_modal);
+            Message::DeleteNote(note) => {
+                if let Err(err) = Note::delete(&note) {
+                    return error(format!("Could not delete {}", note.name), err);
+                }
+
+                state.history.remove(&note.path);
+                if state.is_selected_note(&note.path) {
+                    state.selected_note = None;
+                    let registers = std::mem::take(&mut state.note_editor.registers);
+                    state.note_editor = EditorState::default();
+                    state.note_editor.registers = registers;
+                    state.outline =
+                        OutlineState::new(&[], 0, state.outline.is_open(), state.outline.config);
+                }
+
+                return state.vault_changed();
             }
-            Message::Splash(message) => {
-                return splash_modal::update(&message, &mut state.splash_modal);
+            Message::ReplaceNote(note, with) => {
+                let vault = state.vault?;
+
+                // Persist unsaved changes of the open note before it is written over or deleted.
+                state.note_editor.save();
+
+                if let Err(err) =
+                    Note::read_to_string_strict(&with).and_then(|c| Note::write(&note, c))
+                {
+                    return error(format!("Could not replace {}", note.name), err);
+                }
+                // The note is replaced already, so the failure is logged without stopping.
+                state.links_updated(vault.update_links(&with.path, &note.path));
+
+                if state.is_selected_note(&note.path) {
+                    let mut message = Some(Message::SelectNote(SelectedNote::from(&note)));
+                    while message.is_some() {
+                        message = App::update(terminal, config, state, message);
+                    }
+                }
+
+                return Some(Message::DeleteNote(with));
             }
-            Message::Explorer(message) => {
-                return explorer::update(&message, state.screen_size, &mut state.explorer);
+            Message::MergeNotes(into, from) => {
+                let vault = state.vault?;
+
+                // Persist unsaved changes of the open note before it is written over or deleted.
+                state.note_editor.save();
+
+                let merged = Note::read_to_string_strict(&into).and_then(|into_contents| {
+                    let from_contents = Note::read_to_string_strict(&from)?;
+                    Note::write(&into, diff::merge(&into_contents, &from_contents))
+                });
+
+                if let Err(err) = merged {
+                    return error(format!("Could not merge into {}", into.name), err);
+                }
+                // The notes are merged already, so the failure is logged without stopping.
+                state.links_updated(vault.update_links(&from.path, &into.path));
+
+                if state.is_selected_note(&into.path) {
+                    let mut message = Some(Message::SelectNote(SelectedNote::from(&into)));
+                    while message.is_some() {
+                        message = App::update(terminal, config, state, message);
+                    }
+                }
+
+                return Some(Message::DeleteNote(from));
             }
-            Message::Outline(message) => {
-                return outline::update(&message, &mut state.outline);
+            Message::ArchiveNote => {
+                let vault = state.vault?;
+                let note = state.explorer.selected_note.clone()?;
+                let dir = vault.path.join(date_format::expand(
+                    &config.archive_folder,
+                    &chrono::Local::now(),
+                ));
+
+                if note.path.parent() == Some(dir.as_path()) {
+                    return None;
+                }
+
+                // Persist unsaved changes before the file is moved away from under the editor.
+                state.note_editor.save();
+
+                let archived = match Note::move_to(&note, &dir) {
+                    Ok(archived) => archived,
+                    Err(err) => return error(format!("Could not archive {}", note.name), err),
+                };
+                // The note is archived already, so the failure is logged without stopping.
+                state.links_updated(vault.update_links(&note.path, &archived.path));
+
+                let mut message = state.vault_changed();
+                while message.is_some() {
+                    message = App::update(terminal, config, state, message);
+                }
+                state.explorer.select_path(&archived.path);
+                state.explorer.select();
+
+                return Some(Message::SelectNote(SelectedNote::from(&archived)));
             }
-            Message::NoteEditor(message) => {
-                return note_editor::update(&message, state.screen_size, &mut state.note_editor);
+            Message::DuplicateNote(note, name) => {
+                state.vault?;
+                let dir = note.path.parent()?;
+                let copy = match Note::copy_to(&note, dir, &Note::available_name(dir, &name)) {
+                    Ok(copy) => copy,
+                    Err(err) => return error(format!("Could not duplicate {}", note.name), err),
+                };
+
+                let message = state.vault_changed();
+                state.explorer.select_path(&copy.path);
+                return message;
             }
-        };
+            Message::SaveNoteAsTemplate(note, name) => {
+                let vault = state.vault?;
+                let dir = vault.templates_dir();
+                let template = match Note::copy_to(&note, &dir, &Note::available_name(&dir, &name))
+                {
+                    Ok(template) => template,
+                    Err(err) => return error("Could not save template", err),
+                };
 
-        None
-    }
+                let message = state.vault_changed();
+                state.explorer.select_path(&template.path);
+                return message;
+            }
+            Message::KeysExport => {
+                state.vault?;
 
-    fn render_splash(&self, area: Rect, buf: &mut Buffer, state: &mut SplashModalState<'a>) {
-        SplashModal::default().render_ref(area, buf, state)
-    }
+                return Some(Message::InputModal(input_modal::Message::Open {
+                    title: "Export key bindings to note".into(),
+                    value: "Key bindings".into(),
+                    action: InputAction::KeysExport,
+                }));
+            }
+            // The note is written over, so that exporting again updates it.
+            Message::ExportKeys(name) => {
+                let vault = state.vault?;
+                let name = name.strip_suffix(".md").unwrap_or(&name);
+                let path = vault.path.join(format!("{name}.md"));
 
-    fn render_main(&self, area: Rect, buf: &mut Buffer, state: &mut AppState<'a>) {
-        let [content, statusbar] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
-            .horizontal_margin(1)
-            .areas(area);
+                if !vault.contains(&path) {
+                    return Some(Message::Error(format!(
+                        "Could not export key bindings to {}: the path is outside the vault",
+                        path.display()
+                    )));
+                }
 
-        let (left, right) = if state.explorer.open {
-            (Constraint::Length(35), Constraint::Fill(1))
-        } else {
-            (Constraint::Length(4), Constraint::Fill(1))
-        };
+                if let Err(err) = path
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(&path, keys_export::markdown(config)))
+                {
+                    return error("Could not export key bindings", err);
+                }
 
-        let [explorer_pane, note, outline] = Layout::horizontal([
-            left,
-            right,
-            if state.outline.is_open() {
-                Constraint::Length(35)
-            } else {
-                Constraint::Length(4)
-            },
-        ])
-        .areas(content);
+                let mut message = state.vault_changed();
+                while message.is_some() {
+                    message = App::update(terminal, config, state, message);
+                }
+                return Some(Message::OpenNoteAt(Note::from(path.as_path()), 0));
+            }
+            Message::Publish => {
+                state.vault?;
 
-        Explorer::new().render(explorer_pane, buf, &mut state.explorer);
-        Editor::default().render(note, buf, &mut state.note_editor);
-        Outline.render(outline, buf, &mut state.outline);
+                return Some(Message::InputModal(input_modal::Message::Open {
+                    title: "Publish folder or #tag (empty for all notes)".into(),
+                    value: String::new(),
+                    action: InputAction::Publish,
+                }));
+            }
+            Message::PublishNotes(filter) => {
+                let vault = state.vault?;
 
-        let (_, counts) = state
-            .selected_note
-            .clone()
-            .map(|note| {
-                let content = note.content.as_str();
-                (
-                    note.name,
-                    (WordCount::from(content), CharCount::from(content)),
-                )
-            })
-            .unzip();
+                let template = match &config.publish.template {
+                    Some(path) => match fs::read_to_string(path) {
+                        Ok(template) => template,
+                        Err(err) => return error("Could not read publish template", err),
+                    },
+                    None => DEFAULT_TEMPLATE.to_string(),
+                };
 
-        let (word_count, char_count) = counts.unwrap_or_default();
+                let output_dir = config
+                    .publish
+                    .output_dir
+                    .clone()
+                    .unwrap_or_else(|| PUBLISH_DEFAULT_DIR.into());
 
-        let mut status_bar_state = StatusBarState::new(
-            state.active_pane.into(),
-            word_count.into(),
-            char_count.into(),
-        );
+                return Some(Message::RunJob(Job::Publish(
+                    vault.clone(),
+                    PublishOptions {
+                        output_dir: vault.path.join(output_dir),
+                        filter: PublishFilter::from(filter.as_str()),
+                        template,
+                    },
+                )));
+            }
+            Message::SetStatus(message) => state.status_message = Some(message),
+            Message::CountDigit(digit) => {
+                state.pending_count = Some(
+                    state
+                        .pending_count
+                        .unwrap_or_default()
+                        .saturating_mul(10)
+                        .saturating_add(digit)
+                        .min(COUNT_MAX),
+                );
+            }
+            Message::TypeAhead(c) => {
+                let pane = state.active_component();
+                if state.type_ahead.0 != pane {
+                    state.type_ahead = (pane, TypeAhead::default());
+                }
 
-        let status_bar = StatusBar::default();
-        status_bar.render_ref(statusbar, buf, &mut status_bar_state);
+                let query = state.type_ahead.1.push(c, Instant::now()).to_string();
+                let found = match pane {
+                    ActivePane::Explorer => state.explorer.type_ahead(&query),
+                    ActivePane::Outline => state.outline.type_ahead(&query),
+                    ActivePane::VaultSelectorModal => state
+                        .vault_selector_modal
+                        .vault_selector_state
+                        .type_ahead(&query),
+                    _ => return None,
+                };
 
-        self.render_modals(area, buf, state)
-    }
+                state.status_message = Some(match found {
+                    true => format!("Jump to {query}"),
+                    false => format!("Nothing starts with {query}"),
+                });
+            }
+            Message::Repeat(count, message) => {
+                state.pending_count = None;
+                let message = *message?;
 
-    fn render_modals(&self, area: Rect, buf: &mut Buffer, state: &mut AppState<'a>) {
-        if state.splash_modal.visible {
-            self.render_splash(area, buf, &mut state.splash_modal);
-        }
+                if App::repeats(&message) {
+                    for _ in 1..count {
+                        let mut message = Some(message.clone());
+                        while message.is_some() {
+                            message = App::update(terminal, config, state, message);
+                        }
+                    }
+                }
 
-        if state.vault_selector_modal.visible {
-            VaultSelectorModal::default().render(area, buf, &mut state.vault_selector_modal);
-        }
+                return Some(message);
+            }
+            Message::SearchReplace(scope) => {
+                let title = match scope {
+                    Scope::Note => {
+                        state.selected_note.as_ref()?;
+                        "Find (/pattern/ for regex)"
+                    }
+                    Scope::Vault => {
+                        state.vault?;
+                        "Find in vault (/pattern/ for regex)"
+                    }
+                };
 
-        if state.help_modal.visible {
-            HelpModal.render(area, buf, &mut state.help_modal);
-        }
-    }
-}
+                return Some(Message::InputModal(input_modal::Message::Open {
+                    title: title.into(),
+                    value: String::new(),
+                    action: InputAction::SearchPattern(scope),
+                }));
+            }
+            Message::SearchPattern(scope, pattern) => {
+                if let Err(err) = replace_modal::parse_pattern(&pattern) {
+                    return Some(Message::SetStatus(err));
+                }
 
-impl<'a> StatefulWidgetRef for App<'a> {
-    type State = AppState<'a>;
+                return Some(Message::InputModal(input_modal::Message::Open {
+                    title: format!("Replace {pattern} with"),
+                    value: String::new(),
+                    action: InputAction::ReplaceWith(scope, pattern),
+                }));
+            }
+            Message::VaultReplaced(report) => {
+                let replaced = match report.replaced {
+                    1 => "1 match".to_string(),
+                    replaced => format!("{replaced} matches"),
+                };
 
-    fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        self.render_main(area, buf, state);
+                state.status_message = Some(match &report.backup_dir {
+                    Some(dir) => format!(
+                        "Replaced {replaced} in {} notes, backup in {}",
+                        report.notes.len(),
+                        dir.display()
+                    ),
+                    None => format!("Replaced {replaced} in {} notes", report.notes.len()),
+                });
+
+                let selected = report
+                    .notes
+                    .iter()
+                    .find(|note| state.is_selected_note(&note.path));
+
+                if let Some(note) = selected {
+                    let mut message = Some(Message::SelectNote(SelectedNote::from(note)));
+                    while message.is_some() {
+                        message = App::update(terminal, config, state, message);
+                    }
+                }
+
+                return state.vault_changed();
+            }
+            Message::ReplacedMatches(content, replaced) => {
+                state.status_message = Some(match replaced {
+                    1 => "Replaced 1 match".to_string(),
+                    _ => format!("Replaced {replaced} matches"),
+                });
+
+                if replaced == 0 {
+                    return None;
+                }
+
+                state.note_editor.replace_content(&content);
+
+                return Some(Message::UpdateSelectedNoteContent((
+                    content,
+                    Some(state.note_editor.nodes().to_vec()),
+                )));
+            }
+            Message::InsertFromUrl => {
+                state.vault?;
+
+                return Some(Message::InputModal(input_modal::Message::Open {
+                    title: "Insert from URL".into(),
+                    value: String::new(),
+                    action: InputAction::InsertFromUrl,
+                }));
+            }
+            Message::QuickCapture => {
+                state.vault?;
+
+                return Some(Message::InputModal(input_modal::Message::Open {
+                    title: "Quick capture".into(),
+                    value: String::new(),
+                    action: InputAction::QuickCapture,
+                }));
+            }
+            Message::HistoryBack => {
+                let Some(path) = state.history.back() else {
+                    return Some(Message::SetStatus("No previous note".into()));
+                };
+                return Some(Message::OpenNote(Note::from(path)));
+            }
+            Message::HistoryForward => {
+                let Some(path) = state.history.forward() else {
+                    return Some(Message::SetStatus("No next note".into()));
+                };
+                return Some(Message::OpenNote(Note::from(path)));
+            }
+            Message::QuickSwitcher => {
+                let vault = state.vault?;
+                let vaults = match config.quick_switcher.all_vaults {
+                    true => state.vault_selector_modal.vault_selector_state.all(),
+                    false => &[vault],
+                };
+                let notes: Vec<_> = quick_switcher::notes(vault, vaults)
+                    .into_iter()
+                    .chain(quick_switcher::aliases(vault, &state.vault_index))
+                    .collect();
+
+                return Some(Message::InputModal(input_modal::Message::Select {
+                    title: "Open note".into(),
+                    value: String::new(),
+                    options: notes.iter().map(|(label, _)| label.clone()).collect(),
+                    action: InputAction::OpenNote(notes),
+                }));
+            }
+            Message::TagRename => {
+                state.vault?;
+
+                return Some(Message::InputModal(input_modal::Message::Select {
+                    title: "Rename tag".into(),
+                    value: String::new(),
+                    options: state.vault_index.tags(),
+                    action: InputAction::TagRename,
+                }));
+            }
+            Message::OpenWith => {
+                if state.selected_note.is_none() {
+                    return Some(Message::SetStatus("No note is open".into()));
+                }
+                if config.open_with.is_empty() {
+                    return Some(Message::SetStatus(
+                        "No programs in the [open_with] section".into(),
+                    ));
+                }
+
+                return Some(Message::InputModal(input_modal::Message::Select {
+                    title: "Open with".into(),
+                    value: String::new(),
+                    options: config.open_with.keys().cloned().collect(),
+                    action: InputAction::OpenWith(config.open_with.keys().cloned().collect()),
+                }));
+            }
+            Message::RunOpenWith(name) => {
+                return config.open_with.get(&name).cloned().map(Message::from);
+            }
+            Message::FocusTimer(message) => {
+                if message == focus_timer::Message::Ended {
+                    state.status_message = Some("Focus session ended".into());
+                }
+                return focus_timer::update(&message, &config.timer, &mut state.focus_timer);
+            }
+            Message::CycleCountScope => {
+                let scope = state.count_scope.unwrap_or(config.count_scope).next();
+                state.count_scope = Some(scope);
+                return Some(Message::SetStatus(match scope {
+                    CountScope::Note => "Counting the whole note".into(),
+                    CountScope::Block => "Counting the block at the cursor".into(),
+                }));
+            }
+            Message::CopyLinkToNote | Message::CopyLinkToHeading => {
+                let path = PathBuf::from(&state.selected_note.as_ref()?.path);
+                let heading = match message {
+                    Message::CopyLinkToHeading => match state.outline.selected_heading() {
+                        Some(heading) => Some(extract::heading_anchor(&heading)),
+                        None => return Some(Message::SetStatus("No heading selected".into())),
+                    },
+                    _ => None,
+                };
+
+                let link = extract::link(&state.link_name(&path), heading.as_deref());
+                return Some(match clipboard::write(&link) {
+                    true => Message::SetStatus(format!("Copied {link}")),
+                    false => {
+                        Message::Error(format!("Could not copy {link}: no clipboard tool found"))
+                    }
+                });
+            }
+            Message::HeadingRename(heading) => {
+                state.vault?;
+                let path = PathBuf::from(&state.selected_note.as_ref()?.path);
+
+                return Some(Message::InputModal(input_modal::Message::Open {
+                    title: format!("Rename #{heading} to"),
+                    value: heading.clone(),
+                    action: InputAction::HeadingRename {
+                        path,
+                        from: heading,
+                    },
+                }));
+            }
+            Message::SwitchToNote(path) => {
+                if state
+                    .vault
+                    .is_some_and(|vault| path.starts_with(&vault.path))
+                {
+                    return Some(Message::OpenNote(Note::from(path.as_path())));
+                }
+
+                let vault = state
+                    .vault_selector_modal
+                    .vault_selector_state
+                    .all()
+                    .iter()
+                    .find(|vault| path.starts_with(&vault.path))
+                    .copied()?;
+                state.pending_note = Some(path);
+                return Some(Message::OpenVault(vault));
+            }
+            Message::Capture(text) => {
+                let vault = state.vault?;
+                let now = chrono::Local::now();
+                let path = capture::inbox_path(&vault.path, &config.capture.inbox, &now);
+                let timestamp = date_format::expand(&config.capture.timestamp, &now);
+                let note = Note::from(path.as_path());
+
+                // Captures into the open note with unsaved changes are saved with the changes,
+                // so that saving the note does not drop the capture.
+                if state.is_selected_note(&path) && state.note_editor.modified {
+                    let content = capture::append(state.note_editor.content(), &timestamp, &text);
+                    state.note_editor.replace_content(&content);
+                    state.status_message = Some(format!("Captured to {}", note.name));
+
+                    return Some(Message::UpdateSelectedNoteContent((
+                        content,
+                        Some(state.note_editor.nodes().to_vec()),
+                    )));
+                }
+
+                let exists = path.exists();
+                let contents = Note::read_to_string(&note).unwrap_or_default();
+                let written = path
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .map_err(Into::into)
+                    .and_then(|_| {
+                        Note::write(&note, capture::append(&contents, &timestamp, &text))
+                    });
+
+                if let Err(err) = written {
+                    return error(format!("Could not capture to {}", note.name), err);
+                }
+                state.status_message = Some(format!("Captured to {}", note.name));
+
+                if state.is_selected_note(&path) {
+                    let row = state.note_editor.current_row;
+                    let mut message = Some(Message::SelectNote(SelectedNote::from(&note)));
+                    while message.is_some() {
+                        message = App::update(terminal, config, state, message);
+                    }
+                    state.note_editor.set_row(row);
+                }
+
+                return match exists {
+                    true => {
+                        state.vault_index.update_note(&note);
+                        None
+                    }
+                    false => state.vault_changed(),
+                };
+            }
+            Message::ExtractTo(text, target) => {
+                let vault = state.vault?;
+                let (name, heading) = extract::split_target(&target);
+                if name.is_empty() {
+                    return None;
+                }
+
+                let path = extract::note_path(&vault.path, name);
+                if state.is_selected_note(&path) {
+                    state.status_message = Some("Cannot extract into the open note".into());
+                    return None;
+                }
+
+                let exists = path.exists();
+                let note = Note::from(path.as_path());
+                let contents = Note::read_to_string(&note).unwrap_or_default();
+                let written = path
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .map_err(Into::into)
+                    .and_then(|_| Note::write(&note, extract::append(&contents, heading, &text)));
+
+                if let Err(err) = written {
+                    return error(format!("Could not extract to {}", note.name), err);
+                }
+
+                let link = config
+                    .extract
+                    .leave_link
+                    .then(|| extract::link(name, heading));
+                state.note_editor.replace_extracted(link.as_deref());
+                state.status_message = Some(format!("Extracted to {}", note.name));
+
+                let mut message = match exists {
+                    true => {
+                        state.vault_index.update_note(&note);
+                        None
+                    }
+                    false => state.vault_changed(),
+                };
+                while message.is_some() {
+                    message = App::update(terminal, config, state, message);
+                }
+
+                return Some(Message::UpdateSelectedNoteContent((
+                    state.note_editor.content().to_string(),
+                    (!state.note_editor.is_editing()).then(|| state.note_editor.nodes().to_vec()),
+                )));
+            }
+            Message::ClipUrl(url) => {
+                let url = url.trim();
+                if url.is_empty() {
+                    return None;
+                }
+
+                if !web_clip::is_web_url(url) {
+                    return Some(Message::Error(format!(
+                        "Could not clip {url}: only http and https addresses can be clipped"
+                    )));
+                }
+
+                let note = state
+                    .selected_note
+                    .as_ref()
+                    .map(|note| PathBuf::from(&note.path));
+
+                state.status_message = Some(format!("Fetching {url}"));
+                return Some(Message::RunJob(Job::WebClip(url.to_string(), note)));
+            }
+            Message::VaultIndexed(path, index) => {
+                // The index of a vault that was switched away from is dropped, it is indexed
+                // again when it is opened.
+                state.vault.filter(|vault| vault.path == path)?;
+                state.vault_index = index;
+                state.index_changed();
+            }
+            Message::VaultFilesChanged(path, changes) => {
+                let vault = state.vault.filter(|vault| vault.path == path)?;
+                let renames = link_fix::renames(&changes, &state.vault_index);
+                let broken = link_fix::broken_links(vault, &changes, &renames);
+
+                changes.update_index(&mut state.vault_index);
+                state.index_changed();
+
+                if !changes.removed.is_empty() || !changes.added.is_empty() {
+                    let mut message = state.refresh_vault();
+                    while message.is_some() {
+                        message = App::update(terminal, config, state, message);
+                    }
+                }
+
+                if broken.is_empty() {
+                    return None;
+                }
+
+                let name = |path: &Path| Note::from(path).name;
+                let renamed = match renames.as_slice() {
+                    [(from, to)] => format!("{} was renamed to {}", name(from), name(to)),
+                    renames => format!("{} notes were renamed", renames.len()),
+                };
+                let notes = match broken.len() {
+                    1 => "1 note".to_string(),
+                    count => format!("{count} notes"),
+                };
+
+                return Some(Message::InputModal(input_modal::Message::Confirm {
+                    title: "Fix links".into(),
+                    question: format!("{renamed} outside Basalt. Update the links in {notes}?"),
+                    action: InputAction::FixRenamedLinks(renames),
+                }));
+            }
+            Message::FixRenamedLinks(renames) => {
+                let vault = state.vault?;
+
+                // Persist unsaved changes before the links in the open note are rewritten.
+                state.note_editor.save();
+
+                let mut updated = 0;
+                for (from, to) in &renames {
+                    match vault.rename_links(from, to) {
+                        Ok(update) => {
+                            updated += update.modified;
+                            state.links_updated(Ok(update));
+                        }
+                        Err(err) => return error("Could not update links", err),
+                    }
+                }
+
+                let selected = state
+                    .selected_note
+                    .as_ref()
+                    .map(|note| PathBuf::from(&note.path));
+                if let Some(path) = selected.filter(|path| path.exists()) {
+                    let note = Note::from(path.as_path());
+                    let mut message = Some(Message::SelectNote(SelectedNote::from(&note)));
+                    while message.is_some() {
+                        message = App::update(terminal, config, state, message);
+                    }
+                }
+
+                state.status_message = Some(match updated {
+                    1 => "Updated the links in 1 note".to_string(),
+                    _ => format!("Updated the links in {updated} notes"),
+                });
+                return state.vault_changed();
+            }
+            Message::FixLink(links) => {
+                let vault = state.vault?;
+                let from = state.note_editor.path().to_path_buf();
+                let index = LinkIndex::new(&vault.path, vault.files());
+
+                let Some(link) = links.into_iter().find(|link| {
+                    !link.is_external()
+                        && !link.target.trim().is_empty()
+                        && index.resolve(&from, link).is_none()
+                }) else {
+                    return Some(Message::SetStatus(
+                        "No broken link in the current block".into(),
+                    ));
+                };
+
+                let source = state
+                    .note_editor
+                    .content_slice(link.range.clone())
+                    .to_string();
+                let notes = quick_switcher::notes(vault, &[vault]);
+                let labels: Vec<_> = notes.iter().map(|(label, _)| label.clone()).collect();
+
+                return Some(Message::InputModal(input_modal::Message::Select {
+                    title: format!("Fix link to {}", link.target),
+                    value: String::new(),
+                    options: link_fix::suggestions(&link.target, &labels),
+                    action: InputAction::FixLink {
+                        link,
+                        source,
+                        notes,
+                    },
+                }));
+            }
+            // The note may have changed while the notes were listed.
+            Message::ReplaceLinkTarget(range, target, source) => {
+                if state.note_editor.content().get(range.clone()) != Some(source.as_str()) {
+                    return None;
+                }
+
+                let mut content = state.note_editor.content().to_string();
+                content.replace_range(range, &target);
+                state.note_editor.replace_content(&content);
+
+                return Some(Message::UpdateSelectedNoteContent((
+                    content,
+                    Some(state.note_editor.nodes().to_vec()),
+                )));
+            }
+            Message::WebClipped(_, Err(err)) => return error("Could not clip page", err),
+            Message::WebClipped(Some(path), Ok(clip)) if state.is_selected_note(&path) => {
+                state.note_editor.insert(&clip.insert_text());
+                state.status_message = Some(format!("Inserted {}", clip.title));
+
+                return Some(Message::UpdateSelectedNoteContent((
+                    state.note_editor.content().to_string(),
+                    (!state.note_editor.is_editing()).then(|| state.note_editor.nodes().to_vec()),
+                )));
+            }
+            Message::WebClipped(Some(path), Ok(clip)) => {
+                // The note the page was clipped for was closed while the page was fetched, so the
+                // clip is added to the end of the note file.
+                let note = Note::from(path.as_path());
+                let written = Note::read_to_string_strict(&note).and_then(|contents| {
+                    let contents = match contents.trim_end() {
+                        "" => clip.insert_text(),
+                        contents => format!("{contents}\n\n{}", clip.insert_text()),
+                    };
+                    Note::write(&note, contents)
+                });
+
+                if let Err(err) = written {
+                    return error(format!("Could not insert {}", clip.title), err);
+                }
+
+                state.vault_index.update_note(&note);
+                state.status_message = Some(format!("Inserted {} into {}", clip.title, note.name));
+            }
+            Message::WebClipped(None, Ok(clip)) => {
+                // The clip is saved as a new note in the vault root when no note was open.
+                let vault = state.vault?;
+                let name = Note::available_name(&vault.path, &clip.note_name());
+                let note = match Note::path_in(&vault.path, &name)
+                    .and_then(|path| Note::create(&path))
+                    .and_then(|note| {
+                        Note::write(&note, clip.note_contents(&config.date_format.today()))?;
+                        Ok(note)
+                    }) {
+                    Ok(note) => note,
+                    Err(err) => return error(format!("Could not save {}", clip.title), err),
+                };
+
+                let mut message = state.vault_changed();
+                while message.is_some() {
+                    message = App::update(terminal, config, state, message);
+                }
+
+                state.status_message = Some(format!("Clipped {}", clip.title));
+                return Some(Message::OpenNoteAt(note, 0));
+            }
+            Message::Exec(command) => {
+                let (note_name, note_path) = state
+                    .selected_note
+                    .as_ref()
+                    .map(|note| (note.name.as_str(), note.path.as_str()))
+                    .unwrap_or_default();
+                let vault_path = state
+                    .vault
+                    .map(|vault| vault.path.to_string_lossy())
+                    .unwrap_or_default();
+
+                return command::sync_command(
+                    terminal,
+                    command,
+                    state.explorer.title,
+                    &vault_path,
+                    note_name,
+                    note_path,
+                );
+            }
+            Message::ExecPrompt(command) => {
+                let program = command.split_whitespace().next().unwrap_or_default();
+
+                return Some(Message::InputModal(input_modal::Message::Open {
+                    title: format!("Run {program}"),
+                    value: String::new(),
+                    action: InputAction::ExecPrompt(command),
+                }));
+            }
+
+            Message::OpenAttachment(path) => {
+                return command::open_file(&config.attachments.opener, &path);
+            }
+            Message::DeleteAttachments(paths) => {
+                let deleted = paths
+                    .iter()
+                    .filter(|path| fs::remove_file(path).is_ok())
+                    .count();
+
+                state.status_message =
+                    Some(format!("Deleted {deleted}/{} attachments", paths.len()));
+                if deleted < paths.len() {
+                    state.log_error(format!(
+                        "Could not delete {} attachments",
+                        paths.len() - deleted
+                    ));
+                }
+                return state.vault_changed();
+            }
+            Message::MoveAttachments(paths, folder) => {
+                let folder = state.vault?.path.join(folder);
+                if let Err(err) = fs::create_dir_all(&folder) {
+                    return error(format!("Could not create {}", folder.display()), err);
+                }
+
+                // Attachments are never moved over existing files.
+                let moved = paths
+                    .iter()
+                    .filter(|path| {
+                        let target = folder.join(path.file_name().unwrap_or_default());
+                        !target.exists() && fs::rename(path, target).is_ok()
+                    })
+                    .count();
+
+                state.status_message = Some(format!("Moved {moved}/{} attachments", paths.len()));
+                return state.vault_changed();
+            }
+
+            Message::Spawn(command) => {
+                let (note_name, note_path) = state
+                    .selected_note
+                    .as_ref()
+                    .map(|note| (note.name.as_str(), note.path.as_str()))
+                    .unwrap_or_default();
+                let vault_path = state
+                    .vault
+                    .map(|vault| vault.path.to_string_lossy())
+                    .unwrap_or_default();
+
+                return command::spawn_command(
+                    command,
+                    state.explorer.title,
+                    &vault_path,
+                    note_name,
+                    note_path,
+                );
+            }
+
+            Message::HelpModal(message) => {
+                return help_modal::update(&message, state.screen_size, &mut state.help_modal);
+            }
+            Message::VaultSelectorModal(message) => {
+                return vault_selector_modal::update(&message, &mut state.vault_selector_modal);
+            }
+            Message::VaultStatsModal(message) => {
+                return vault_stats_modal::update(
+                    &message,
+                    state.screen_size,
+                    state.vault,
+                    config.text_counts,
+                    &mut state.vault_stats_modal,
+                );
+            }
+            Message::DiagnosticsModal(message) => {
+                return diagnostics_modal::update(
+                    &message,
+                    state.vault,
+                    &mut state.diagnostics_modal,
+                );
+            }
+            Message::AttachmentsModal(message) => {
+                return attachments_modal::update(
+                    &message,
+                    state.vault,
+                    &mut state.attachments_modal,
+                );
+            }
+            Message::DuplicatesModal(message) => {
+                return duplicates_modal::update(
+                    &message,
+                    state.screen_size,
+                    state.vault,
+                    &mut state.duplicates_modal,
+                );
+            }
+            Message::NoteDiffModal(message) => {
+                return note_diff_modal::update(
+                    &message,
+                    state.screen_size,
+                    &state.note_editor,
+                    &mut state.note_diff_modal,
+                );
+            }
+            Message::NoteHistoryModal(message) => {
+                return note_history_modal::update(
+                    &message,
+                    state.screen_size,
+                    &state.note_editor,
+                    &mut state.note_history_modal,
+                );
+            }
+            // The restored version is not saved, so that it can be reviewed first.
+            Message::RestoreNoteVersion(content, commit) => {
+                if state.note_editor.read_only().is_some() {
+                    return Some(Message::SetStatus("Note is read-only".into()));
+                }
+
+                state.note_editor.replace_content(&content);
+                state.status_message =
+                    Some(format!("Restored the version of {commit}, not saved yet"));
+
+                return Some(Message::UpdateSelectedNoteContent((
+                    content,
+                    Some(state.note_editor.nodes().to_vec()),
+                )));
+            }
+            Message::InputModal(message) => {
+                return input_modal::update(&message, &mut state.input_modal);
+            }
+            Message::CanvasModal(message) => {
+                return canvas_modal::update(&message, state.vault, &mut state.canvas_modal);
+            }
+            Message::TagBrowserModal(message) => {
+                return tag_browser_modal::update(
+                    &message,
+                    state.vault,
+                    &state.vault_index,
+                    &mut state.tag_browser_modal,
+                );
+            }
+            Message::OutgoingLinksModal(message) => {
+                let note = state
+                    .selected_note
+                    .as_ref()
+                    .map(|_| (state.note_editor.path(), state.note_editor.content()));
+
+                return outgoing_links_modal::update(
+                    &message,
+                    state.vault,
+                    &state.vault_index,
+                    note,
+                    &mut state.outgoing_links_modal,
+                );
+            }
+            Message::JobsModal(message) => {
+                return jobs_modal::update(&message, &mut state.jobs_modal);
+            }
+            Message::VaultReplaceModal(message) => {
+                // Unsaved changes are saved first, so that the search sees the editor content.
+                if matches!(
+                    message,
+                    vault_replace_modal::Message::Open { .. }
+                        | vault_replace_modal::Message::RenameTag { .. }
+                        | vault_replace_modal::Message::RenameHeading { .. }
+                ) {
+                    state.note_editor.save();
+                }
+
+                return vault_replace_modal::update(
+                    &message,
+                    state.vault,
+                    &mut state.vault_replace_modal,
+                );
+            }
+            Message::MacroRecord => match state.recording_macro.take() {
+                Some(mut keys) => {
+                    // The key that stopped the recording is recorded last.
+                    keys.pop();
+                    let message = format!("Recorded a macro of {} keys", keys.len());
+                    state.recorded_macro = keys;
+                    return Some(Message::SetStatus(message));
+                }
+                None => state.recording_macro = Some(vec![]),
+            },
+            Message::MacroPlay(name) => {
+                if state.playing_macro || state.recording_macro.is_some() {
+                    return Some(Message::SetStatus(
+                        "Macros cannot be played while recording or playing a macro".into(),
+                    ));
+                }
+
+                let keys = match name {
+                    Some(name) => match config.macros.get(&name) {
+                        Some(keys) => keys.iter().map(KeyEvent::from).collect(),
+                        None => return Some(Message::SetStatus(format!("Unknown macro {name}"))),
+                    },
+                    None => state.recorded_macro.clone(),
+                };
+
+                if keys.is_empty() {
+                    return Some(Message::SetStatus("No macro recorded".into()));
+                }
+
+                state.macro_keys = keys;
+            }
+            Message::KeysDebug => {
+                return Some(Message::KeysDebugModal(keys_debug_modal::Message::Open(
+                    state.active_component(),
+                )));
+            }
+            Message::KeysDebugModal(message) => {
+                return keys_debug_modal::update(
+                    &message,
+                    config,
+                    &state.config_report_modal.report,
+                    &mut state.keys_debug_modal,
+                );
+            }
+            Message::ConfigReportModal(message) => {
+                return config_report_modal::update(&message, &mut state.config_report_modal);
+            }
+            Message::ErrorLogModal(message) => {
+                let message = error_log_modal::update(&message, &mut state.error_log_modal);
+                if state.error_log_modal.visible {
+                    state.last_error = None;
+                }
+                return message;
+            }
+            Message::Error(message) => state.log_error(message),
+            Message::QuitModal(message) => {
+                return quit_modal::update(&message, &mut state.quit_modal);
+            }
+            Message::SaveConflictModal(message) => {
+                return save_conflict_modal::update(
+                    &message,
+                    &mut state.note_editor,
+                    &mut state.save_conflict_modal,
+                );
+            }
+            Message::ReplaceModal(message) => {
+                return replace_modal::update(
+                    &message,
+                    &state.note_editor,
+                    &mut state.replace_modal,
+                );
+            }
+            Message::Splash(message) => {
+                return splash_modal::update(&message, &mut state.splash_modal);
+            }
+            Message::VaultLoadingModal(message) => {
+                return vault_loading_modal::update(
+                    &message,
+                    state.vault.is_some(),
+                    &mut state.splash_modal,
+                    &mut state.vault_loading_modal,
+                );
+            }
+            Message::Explorer(message) => {
+                let expanded = state.explorer.expanded_folders();
+                let result = explorer::update(&message, state.screen_size, &mut state.explorer);
+
+                if state.explorer.expanded_folders() != expanded {
+                    state.remember_expanded_folders();
+                }
+                return result;
+            }
+            Message::Outline(message) => {
+                return outline::update(&message, &mut state.outline);
+            }
+            Message::NoteEditor(message) => {
+                let word_count = |state: &AppState| {
+                    usize::from(WordCount::from(
+                        config
+                            .text_counts
+                            .text(&state.note_editor.edited_content())
+                            .as_ref(),
+                    ))
+                };
+                let words = matches!(
+                    message,
+                    note_editor::Message::KeyEvent(_)
+                        | note_editor::Message::Paste(_)
+                        | note_editor::Message::AcceptCompletion
+                )
+                .then(|| word_count(state));
+
+                let result =
+                    note_editor::update(&message, state.screen_size, &mut state.note_editor);
+
+                if let Some(written) = words.map(|words| word_count(state).saturating_sub(words)) {
+                    if written > 0 {
+                        state.record_words(written);
+                    }
+                }
+
+                if matches!(
+                    message,
+                    note_editor::Message::KeyEvent(_)
+                        | note_editor::Message::Delete
+                        | note_editor::Message::Paste(_)
+                        | note_editor::Message::AcceptCompletion
+                ) {
+                    state.complete();
+                }
+
+                if let note_editor::Message::Paste(text) = &message {
+                    state.warn_missing_embeds(text);
+                }
+
+                return result;
+            }
+        };
+
+        None
+    }
+
+    fn render_splash(&self, area: Rect, buf: &mut Buffer, state: &mut SplashModalState<'a>) {
+        SplashModal::default()
+            .chrome(self.config.chrome(&self.config.splash))
+            .render_ref(area, buf, state)
+    }
+
+    fn render_zen_mode(&self, area: Rect, buf: &mut Buffer, state: &mut AppState<'a>) {
+        // Borders and horizontal padding of the editor take up four columns.
+        let width = self.config.zen_mode.max_width.saturating_add(4);
+
+        let [note] = Layout::horizontal([Constraint::Max(width)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        Editor::default()
+            .chrome(self.config.chrome(&self.config.note_editor))
+            .dim_inactive(true)
+            .line_numbers(
+                self.config.editor.line_numbers,
+                self.config.editor.line_numbers_in_read_mode,
+            )
+            .sticky_header(self.config.reader.sticky_header)
+            .scroll_off(self.config.ui.scroll_off)
+            .render(note, buf, &mut state.note_editor);
+
+        self.render_modals(area, buf, state)
+    }
+
+    fn render_main(&self, area: Rect, buf: &mut Buffer, state: &mut AppState<'a>) {
+        if state.zen_mode {
+            return self.render_zen_mode(area, buf, state);
+        }
+
+        let announcement_height = match self.config.ui.announcements {
+            Announcements::Line => 1,
+            _ => 0,
+        };
+
+        let [content, announcement, statusbar] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(announcement_height),
+            Constraint::Length(1),
+        ])
+        .horizontal_margin(1)
+        .areas(area);
+
+        let (explorer_width, outline_width) = state.pane_widths();
+
+        let [explorer_pane, note, outline] = Layout::horizontal([
+            Constraint::Length(explorer_width),
+            Constraint::Fill(1),
+            Constraint::Length(outline_width),
+        ])
+        .areas(content);
+
+        Explorer::new()
+            .chrome(self.config.chrome(&self.config.explorer))
+            .render(explorer_pane, buf, &mut state.explorer);
+        Editor::default()
+            .chrome(self.config.chrome(&self.config.note_editor))
+            .line_numbers(
+                self.config.editor.line_numbers,
+                self.config.editor.line_numbers_in_read_mode,
+            )
+            .read_mode_max_width(self.config.reader.max_width)
+            .detect_direction(self.config.reader.detect_direction)
+            .sticky_header(self.config.reader.sticky_header)
+            .minimap(self.config.reader.minimap)
+            .scroll_off(self.config.ui.scroll_off)
+            .render(note, buf, &mut state.note_editor);
+        Outline::default()
+            .chrome(self.config.chrome(&self.config.outline))
+            .render(outline, buf, &mut state.outline);
+
+        let scope = state.count_scope.unwrap_or(self.config.count_scope);
+        let (scope, counted) = match (state.note_editor.selected_text(), scope) {
+            (Some(selection), _) => (Some("selection"), Some(selection)),
+            (None, CountScope::Block) if state.selected_note.is_some() => {
+                (Some("block"), Some(state.note_editor.current_node_text()))
+            }
+            _ => (
+                None,
+                state
+                    .selected_note
+                    .as_ref()
+                    .map(|note| note.content.clone()),
+            ),
+        };
+
+        let (word_count, char_count) = counted
+            .map(|text| {
+                let text = self.config.text_counts.text(&text);
+                (
+                    WordCount::from(text.as_ref()),
+                    CharCount::from(text.as_ref()),
+                )
+            })
+            .unwrap_or_default();
+
+        let mut status_bar_state = StatusBarState::new(
+            state.active_pane.into(),
+            word_count.into(),
+            char_count.into(),
+        );
+
+        if let Some(scope) = scope {
+            status_bar_state = status_bar_state.with_count_scope(scope);
+        }
+
+        let history = state.history.breadcrumbs();
+        if let Some(history) = &history {
+            status_bar_state = status_bar_state.with_history(history);
+        }
+
+        if let Some(message) = &state.status_message {
+            status_bar_state = status_bar_state.with_message(message);
+        }
+
+        if let Some(warning) = &state.status_warning {
+            status_bar_state = status_bar_state.with_warning(warning);
+        }
+
+        if let Some(error) = &state.last_error {
+            status_bar_state = status_bar_state.with_error(error);
+        }
+
+        if state.recording_macro.is_some() {
+            status_bar_state = status_bar_state.with_recording();
+        }
+
+        if let Some(count) = state.pending_count {
+            status_bar_state = status_bar_state.with_count(count);
+        }
+
+        let timer = state
+            .focus_timer
+            .left(Instant::now())
+            .map(focus_timer::format_time);
+        if let Some(timer) = &timer {
+            status_bar_state = status_bar_state.with_timer(timer);
+        }
+
+        let (day, words_today) = state.words_today;
+        if day == chrono::Local::now().date_naive() {
+            status_bar_state = status_bar_state.with_words_today(words_today);
+        }
+
+        let unsaved = state.unsaved_notes().len();
+        if unsaved > 0 {
+            status_bar_state = status_bar_state.with_unsaved(unsaved);
+        }
+
+        if state.selected_note.is_some() {
+            status_bar_state = status_bar_state.with_cursor(
+                state.note_editor.cursor_position(),
+                state.note_editor.progress(),
+            );
+        }
+
+        let status_bar = StatusBar::default();
+        status_bar.render_ref(statusbar, buf, &mut status_bar_state);
+
+        if let Some(text) = &state.announcement {
+            Line::from(text.as_str()).render(announcement, buf);
+        }
+
+        self.render_modals(area, buf, state)
+    }
+
+    fn render_modals(&self, area: Rect, buf: &mut Buffer, state: &mut AppState<'a>) {
+        if state.splash_modal.visible {
+            self.render_splash(area, buf, &mut state.splash_modal);
+        }
+
+        if state.vault_loading_modal.visible {
+            VaultLoadingModal::default()
+                .chrome(self.config.ui.chrome)
+                .render(area, buf, &mut state.vault_loading_modal);
+        }
+
+        if state.vault_selector_modal.visible {
+            VaultSelectorModal::default()
+                .chrome(self.config.chrome(&self.config.vault_selector_modal))
+                .render(area, buf, &mut state.vault_selector_modal);
+        }
+
+        if state.help_modal.visible {
+            HelpModal::default()
+                .chrome(self.config.chrome(&self.config.help_modal))
+                .render(area, buf, &mut state.help_modal);
+        }
+
+        if state.vault_stats_modal.visible {
+            VaultStatsModal::default()
+                .chrome(self.config.chrome(&self.config.vault_stats_modal))
+                .render(area, buf, &mut state.vault_stats_modal);
+        }
+
+        if state.diagnostics_modal.visible {
+            DiagnosticsModal::default()
+                .chrome(self.config.chrome(&self.config.diagnostics_modal))
+                .render(area, buf, &mut state.diagnostics_modal);
+        }
+
+        if state.attachments_modal.visible {
+            AttachmentsModal::default()
+                .chrome(self.config.chrome(&self.config.attachments_modal))
+                .render(area, buf, &mut state.attachments_modal);
+        }
+
+        if state.duplicates_modal.visible {
+            DuplicatesModal::default()
+                .chrome(self.config.chrome(&self.config.duplicates_modal))
+                .render(area, buf, &mut state.duplicates_modal);
+        }
+
+        if state.note_diff_modal.visible {
+            NoteDiffModal::default()
+                .chrome(self.config.chrome(&self.config.note_diff_modal))
+                .render(area, buf, &mut state.note_diff_modal);
+        }
+
+        if state.note_history_modal.visible {
+            NoteHistoryModal::default()
+                .chrome(self.config.chrome(&self.config.note_history_modal))
+                .render(area, buf, &mut state.note_history_modal);
+        }
+
+        if state.canvas_modal.visible {
+            CanvasModal::default()
+                .chrome(self.config.chrome(&self.config.canvas_modal))
+                .render(area, buf, &mut state.canvas_modal);
+        }
+
+        if state.tag_browser_modal.visible {
+            TagBrowserModal::default()
+                .chrome(self.config.chrome(&self.config.tag_browser_modal))
+                .render(area, buf, &mut state.tag_browser_modal);
+        }
+
+        if state.outgoing_links_modal.visible {
+            OutgoingLinksModal::default()
+                .chrome(self.config.chrome(&self.config.outgoing_links_modal))
+                .render(area, buf, &mut state.outgoing_links_modal);
+        }
+
+        if state.jobs_modal.visible {
+            JobsModal::default()
+                .chrome(self.config.chrome(&self.config.jobs_modal))
+                .render(area, buf, &mut state.jobs_modal);
+        }
+
+        if state.vault_replace_modal.visible {
+            VaultReplaceModal::default()
+                .chrome(self.config.chrome(&self.config.vault_replace_modal))
+                .render(area, buf, &mut state.vault_replace_modal);
+        }
+
+        if state.replace_modal.visible {
+            ReplaceModal::default()
+                .chrome(self.config.ui.chrome)
+                .render(area, buf, &mut state.replace_modal);
+        }
+
+        if state.input_modal.visible {
+            InputModal::default().chrome(self.config.ui.chrome).render(
+                area,
+                buf,
+                &mut state.input_modal,
+            );
+        }
+
+        if state.keys_debug_modal.visible {
+            KeysDebugModal::default()
+                .chrome(self.config.ui.chrome)
+                .render(area, buf, &mut state.keys_debug_modal);
+        }
+
+        if state.config_report_modal.visible {
+            ConfigReportModal::default()
+                .chrome(self.config.ui.chrome)
+                .render(area, buf, &mut state.config_report_modal);
+        }
+
+        if state.error_log_modal.visible {
+            ErrorLogModal::default()
+                .chrome(self.config.ui.chrome)
+                .render(area, buf, &mut state.error_log_modal);
+        }
+
+        if state.quit_modal.visible {
+            QuitModal::default().chrome(self.config.ui.chrome).render(
+                area,
+                buf,
+                &mut state.quit_modal,
+            );
+        }
+
+        if state.save_conflict_modal.visible {
+            SaveConflictModal::default()
+                .chrome(self.config.ui.chrome)
+                .render(area, buf, &mut state.save_conflict_modal);
+        }
+    }
+}
+
+impl<'a> StatefulWidgetRef for App<'a> {
+    type State = AppState<'a>;
+
+    fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        self.render_main(area, buf, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn test_calc_scroll_amount() {
+        assert_eq!(calc_scroll_amount(&ScrollAmount::HalfPage, 21), 10);
+
+        [(21, 4), (7, 4), (40, 3), (2, 4)]
+            .into_iter()
+            .for_each(|(height, steps)| {
+                let total: usize = (0..steps)
+                    .map(|step| {
+                        calc_scroll_amount(&ScrollAmount::HalfPageStep(step, steps), height)
+                    })
+                    .sum();
+                assert_eq!(total, height / 2, "height {height} in {steps} steps");
+            });
+    }
+
+    #[test]
+    fn test_smooth_scroll_steps() {
+        let mut state = AppState::default();
+        let half_page =
+            Message::NoteEditor(note_editor::Message::ScrollDown(ScrollAmount::HalfPage));
+
+        assert_eq!(
+            App::smooth_scroll_steps(&state, &half_page),
+            Some(
+                (0..SMOOTH_SCROLL_STEPS)
+                    .map(|step| Message::NoteEditor(note_editor::Message::ScrollDown(
+                        ScrollAmount::HalfPageStep(step, SMOOTH_SCROLL_STEPS)
+                    )))
+                    .collect()
+            )
+        );
+        assert_eq!(
+            App::smooth_scroll_steps(
+                &state,
+                &Message::NoteEditor(note_editor::Message::ScrollDown(ScrollAmount::One))
+            ),
+            None
+        );
+
+        state.note_editor.set_mode(Mode::Edit);
+        assert_eq!(App::smooth_scroll_steps(&state, &half_page), None);
+    }
+
+    #[test]
+    fn test_cycle_pane() {
+        let mut state = AppState {
+            active_pane: ActivePane::Explorer,
+            explorer: ExplorerState {
+                open: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.outline.open();
+
+        let tests = [
+            (
+                true,
+                true,
+                ActivePane::Explorer,
+                true,
+                ActivePane::NoteEditor,
+            ),
+            (
+                true,
+                true,
+                ActivePane::NoteEditor,
+                true,
+                ActivePane::Outline,
+            ),
+            (true, true, ActivePane::Outline, true, ActivePane::Explorer),
+            (true, true, ActivePane::Explorer, false, ActivePane::Outline),
+            (
+                true,
+                false,
+                ActivePane::NoteEditor,
+                true,
+                ActivePane::Explorer,
+            ),
+            (
+                false,
+                true,
+                ActivePane::Outline,
+                true,
+                ActivePane::NoteEditor,
+            ),
+            (
+                false,
+                true,
+                ActivePane::NoteEditor,
+                false,
+                ActivePane::Outline,
+            ),
+            (
+                false,
+                false,
+                ActivePane::NoteEditor,
+                false,
+                ActivePane::NoteEditor,
+            ),
+        ];
+
+        tests.into_iter().for_each(
+            |(explorer_open, outline_open, active_pane, forward, expected)| {
+                state.explorer.open = explorer_open;
+                if outline_open {
+                    state.outline.open();
+                } else {
+                    state.outline.close();
+                }
+                state.active_pane = active_pane;
+
+                assert_eq!(
+                    state.cycle_pane(forward),
+                    expected,
+                    "From {active_pane:?} with explorer {explorer_open} and outline {outline_open}"
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_count_prefix() {
+        let mut config = Config::default();
+        config
+            .explorer
+            .key_bindings
+            .insert("j".into(), Message::Explorer(explorer::Message::Down));
+        config
+            .explorer
+            .key_bindings
+            .insert("enter".into(), Message::Explorer(explorer::Message::Open));
+
+        let mut state = AppState {
+            active_pane: ActivePane::Explorer,
+            ..Default::default()
+        };
+
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        assert_eq!(
+            App::handle_key_event(&config, &state, &key(KeyCode::Char('0'))),
+            None
+        );
+        assert_eq!(
+            App::handle_key_event(&config, &state, &key(KeyCode::Char('1'))),
+            Some(Message::CountDigit(1))
+        );
+
+        state.pending_count = Some(1);
+        assert_eq!(
+            App::handle_key_event(&config, &state, &key(KeyCode::Char('0'))),
+            Some(Message::CountDigit(0))
+        );
+
+        state.pending_count = Some(10);
+        let down = App::handle_key_event(&config, &state, &key(KeyCode::Char('j')));
+        assert_eq!(
+            down,
+            Some(Message::Repeat(
+                10,
+                Some(Box::new(Message::Explorer(explorer::Message::Down)))
+            ))
+        );
+        assert!(App::repeats(&Message::Explorer(explorer::Message::Down)));
+        assert!(!App::repeats(&Message::Explorer(explorer::Message::Open)));
+
+        state.active_pane = ActivePane::HelpModal;
+        assert_eq!(
+            App::handle_key_event(&config, &state, &key(KeyCode::Char('1'))),
+            None
+        );
+    }
+
+    fn run<'a>(
+        terminal: &mut Terminal<TestBackend>,
+        config: &'a Config,
+        state: &mut AppState<'a>,
+        message: Message<'a>,
+    ) {
+        let mut message = Some(message);
+        while message.is_some() {
+            message = App::update(terminal, config, state, message);
+        }
+    }
+
+    #[test]
+    fn test_pane_widths() {
+        let mut state = AppState {
+            screen_size: Size::new(100, 24),
+            explorer: ExplorerState {
+                open: true,
+                ..Default::default()
+            },
+            explorer_width: 35,
+            outline_width: 35,
+            ..Default::default()
+        };
+        state.outline.open();
+
+        let mut widths = |explorer, outline| {
+            (state.explorer_width, state.outline_width) = (explorer, outline);
+            state.pane_widths()
+        };
+
+        // The panes leave room for the note editor, and do not get narrower than the minimum.
+        assert_eq!(widths(35, 35), (35, 35));
+        assert_eq!(widths(5, 35), (PANE_MIN_WIDTH, 35));
+        assert_eq!(widths(90, 35), (62, PANE_MIN_WIDTH));
+        assert_eq!(widths(35, 90), (35, 43));
+
+        // Collapsed panes leave their room to the open pane.
+        state.outline.close();
+        state.explorer_width = 90;
+        assert_eq!(state.pane_widths(), (74, PANE_COLLAPSED_WIDTH));
+        state.explorer.open = false;
+        state.outline.open();
+        state.outline_width = 90;
+        assert_eq!(state.pane_widths(), (PANE_COLLAPSED_WIDTH, 74));
+
+        // Too narrow screens keep the minimum width.
+        state.screen_size = Size::new(30, 24);
+        assert_eq!(state.pane_widths(), (PANE_COLLAPSED_WIDTH, PANE_MIN_WIDTH));
+    }
+
+    #[test]
+    fn test_resize_pane() {
+        let config = testing::config("");
+        let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+        let mut state = AppState {
+            screen_size: Size::new(100, 24),
+            active_pane: ActivePane::Explorer,
+            explorer: ExplorerState {
+                open: true,
+                ..Default::default()
+            },
+            explorer_width: 35,
+            outline_width: 35,
+            ..Default::default()
+        };
+        state.outline.open();
+
+        run(&mut terminal, &config, &mut state, Message::PaneGrow);
+        assert_eq!(state.pane_widths(), (35 + PANE_RESIZE_STEP as u16, 35));
+
+        (0..20).for_each(|_| run(&mut terminal, &config, &mut state, Message::PaneShrink));
+        assert_eq!(state.pane_widths(), (PANE_MIN_WIDTH, 35));
+
+        // Growing the explorer takes the room of the outline, down to its minimum width.
+        (0..40).for_each(|_| run(&mut terminal, &config, &mut state, Message::PaneGrow));
+        assert_eq!(state.pane_widths(), (62, PANE_MIN_WIDTH));
+        assert_eq!(
+            (state.explorer_width, state.outline_width),
+            (62, PANE_MIN_WIDTH)
+        );
+
+        state.active_pane = ActivePane::Outline;
+        run(&mut terminal, &config, &mut state, Message::PaneGrow);
+        assert_eq!(state.pane_widths(), (62, PANE_MIN_WIDTH));
+
+        // The note editor is not resized.
+        state.active_pane = ActivePane::NoteEditor;
+        run(&mut terminal, &config, &mut state, Message::PaneShrink);
+        assert_eq!(state.pane_widths(), (62, PANE_MIN_WIDTH));
+    }
+
+    #[test]
+    fn test_open_vault_in_background() {
+        testing::isolate_user_dirs();
+        let config = testing::config("");
+        let vault = testing::vault("basalt-app-open-vault", &[("Inbox.md", "# Inbox\n")]);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+
+        let (sender, receiver) = background::channel();
+        let mut state = AppState {
+            background: Some(sender),
+            ..Default::default()
+        };
+
+        // Handles the background messages until the scan is done.
+        let scan = |terminal: &mut _, state: &mut _| {
+            for message in receiver.iter() {
+                let scanned = matches!(message, Message::VaultScanned(..));
+                run(terminal, &config, state, message);
+                if scanned {
+                    break;
+                }
+            }
+        };
+
+        run(
+            &mut terminal,
+            &config,
+            &mut state,
+            Message::OpenVault(&vault),
+        );
+        assert_eq!(state.active_component(), ActivePane::VaultLoadingModal);
+        assert_eq!(state.vault, None);
+
+        scan(&mut terminal, &mut state);
+        assert_eq!(state.active_component(), ActivePane::Explorer);
+        assert_eq!(state.vault, Some(&vault));
+
+        run(
+            &mut terminal,
+            &config,
+            &mut state,
+            Message::OpenVault(&vault),
+        );
+        run(
+            &mut terminal,
+            &config,
+            &mut state,
+            Message::VaultLoadingModal(vault_loading_modal::Message::Cancel),
+        );
+        assert_eq!(state.active_component(), ActivePane::VaultSelectorModal);
+
+        scan(&mut terminal, &mut state);
+        assert_eq!(state.active_component(), ActivePane::VaultSelectorModal);
+    }
+
+    #[test]
+    fn test_quick_switcher_all_vaults() {
+        let work = testing::vault("basalt-app-switch-work", &[("Plan.md", "# Plan\n")]);
+        let personal = testing::vault("basalt-app-switch-personal", &[("Ideas.md", "# Ideas\n")]);
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&work, &personal], 80, 24);
+        let script = "vault basalt-app-switch-work\nquick_switcher\ntype Ideas\nkeys enter";
+
+        // Only the notes of the open vault are listed by default.
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app
+            .state()
+            .contains("vault: basalt-app-switch-work\nnote: \n"));
+
+        let config = testing::config("[quick_switcher]\nall_vaults = true");
+        let mut app = testing::app(&config, vec![&work, &personal], 80, 24);
+
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app
+            .state()
+            .starts_with("pane: Note Editor\nvault: basalt-app-switch-personal\nnote: Ideas\n"));
+
+        // Notes of the open vault are opened without opening the vault again.
+        assert_eq!(
+            app.run_script("quick_switcher\ntype Ideas\nkeys enter"),
+            Ok(())
+        );
+        assert!(app.state().contains("note: Ideas\n"));
+    }
+
+    #[test]
+    fn test_web_clip() {
+        let vault = testing::vault(
+            "basalt-app-web-clip",
+            &[("Inbox.md", "# Inbox\n"), ("Ideas.md", "# Ideas\n")],
+        );
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 80, 24);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-web-clip
+            quick_switcher
+            type Ideas
+            keys enter
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+
+        app.send(Message::ClipUrl("file:///etc/passwd".into()));
+        assert!(app
+            .state()
+            .contains("only http and https addresses can be clipped"));
+
+        // A clip for a note that was closed while the page was fetched goes into that note.
+        let clip = WebClip::from_html(
+            "https://example.com/post",
+            "<title>Post</title><article><p>Text</p></article>",
+        );
+        app.send(Message::WebClipped(
+            Some(vault.path.join("Inbox.md")),
+            Ok(clip),
+        ));
+        assert_eq!(
+            fs::read_to_string(vault.path.join("Inbox.md")).unwrap(),
+            "# Inbox\n\nSource: [Post](https://example.com/post)\n\nText\n"
+        );
+        assert!(app.state().contains("note: Ideas\nunsaved: \n"));
+    }
+
+    #[test]
+    fn test_keys_export() {
+        let vault = testing::vault("basalt-app-keys-export", &[("Inbox.md", "# Inbox\n")]);
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 80, 24);
+
+        assert_eq!(
+            app.run_script("vault basalt-app-keys-export\nkeys_export\nkeys enter"),
+            Ok(())
+        );
+        assert!(app.state().contains("note: Key bindings\n"));
+
+        let markdown = fs::read_to_string(vault.path.join("Key bindings.md")).unwrap();
+        assert_eq!(markdown, keys_export::markdown(&config));
+
+        // Names that lead outside the vault are refused.
+        let outside = std::env::temp_dir().join("basalt-app-keys-export-outside.md");
+        _ = fs::remove_file(&outside);
+        [
+            "../basalt-app-keys-export-outside",
+            &outside.to_string_lossy(),
+        ]
+        .into_iter()
+        .for_each(|name| {
+            app.send(Message::ExportKeys(name.to_string()));
+            assert!(app.state().contains("the path is outside the vault"));
+            assert!(!outside.exists());
+        });
+    }
+
+    #[test]
+    fn test_complete_link_anchors() {
+        let vault = testing::vault(
+            "basalt-app-anchors",
+            &[
+                ("Inbox.md", "# Inbox\n"),
+                ("Projects.md", "# Shed\n\nPaint the door ^door\n\n## Pond\n"),
+            ],
+        );
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-anchors
+            explorer_open
+            keys tab i end enter
+            type See [[Projects#
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("│Shed  │") && screen.contains("│Pond  │"));
+
+        let script = indoc::indoc! {"
+            keys down tab
+            type  and [[Projects#^d
+            keys enter esc
+            save_all
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert_eq!(
+            fs::read_to_string(vault.path.join("Inbox.md")).unwrap(),
+            "# Inbox\nSee [[Projects#Pond]] and [[Projects#^door]]\n"
+        );
+    }
+
+    #[test]
+    fn test_aliases() {
+        let vault = testing::vault(
+            "basalt-app-aliases",
+            &[
+                ("Inbox.md", "# Inbox\n"),
+                ("Projects/Ideas.md", "---\naliases: [Plans]\n---\n# Ideas\n"),
+            ],
+        );
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+        assert_eq!(app.run_script("vault basalt-app-aliases"), Ok(()));
+
+        let mut index = VaultIndex::default();
+        index.update(&vault, |_, _| {});
+        app.send(Message::VaultIndexed(vault.path.clone(), index));
+
+        let script = indoc::indoc! {"
+            quick_switcher
+            type plans
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("Plans → Projects/Ideas"));
+
+        let script = indoc::indoc! {"
+            keys esc
+            quick_switcher
+            type Inbox
+            keys enter i end enter
+            type See [[pla
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("│Ideas|Plans  │"));
+
+        let script = indoc::indoc! {"
+            keys enter esc
+            save_all
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert_eq!(
+            fs::read_to_string(vault.path.join("Inbox.md")).unwrap(),
+            "# Inbox\nSee [[Ideas|Plans]]\n"
+        );
+    }
+
+    #[test]
+    fn test_tags() {
+        let vault = testing::vault(
+            "basalt-app-tags",
+            &[
+                ("Inbox.md", "# Inbox\n"),
+                ("Projects.md", "#project/alpha #project/beta #idea\n"),
+            ],
+        );
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+        assert_eq!(app.run_script("vault basalt-app-tags"), Ok(()));
+
+        let mut index = VaultIndex::default();
+        index.update(&vault, |_, _| {});
+        app.send(Message::VaultIndexed(vault.path.clone(), index));
+
+        let script = indoc::indoc! {"
+            quick_switcher
+            type Inbox
+            keys enter i end enter
+            type See #proj
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("│project/alpha  │") && screen.contains("│project/beta   │"));
+
+        let script = indoc::indoc! {"
+            keys down enter
+            type  done
+            keys esc
+            save_all
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert_eq!(
+            fs::read_to_string(vault.path.join("Inbox.md")).unwrap(),
+            "# Inbox\nSee #project/beta done\n"
+        );
+
+        let script = indoc::indoc! {"
+            tag_rename
+            type proj
+            keys enter
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("Rename #project/alpha to"));
+
+        let script = indoc::indoc! {"
+            keys backspace backspace backspace backspace backspace
+            type gamma
+            keys enter
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app
+            .screen()
+            .contains("Replace #project/alpha with #project/gamma in vault"));
+    }
+
+    #[test]
+    fn test_tag_browser() {
+        let vault = testing::vault(
+            "basalt-app-tag-browser",
+            &[
+                ("Inbox.md", "# Inbox\n"),
+                ("Projects.md", "# Projects\n\nSee #project/alpha\n"),
+                ("Ideas.md", "#project #idea\n"),
+            ],
+        );
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+        assert_eq!(app.run_script("vault basalt-app-tag-browser"), Ok(()));
+
+        let mut index = VaultIndex::default();
+        index.update(&vault, |_, _| {});
+        app.send(Message::VaultIndexed(vault.path.clone(), index));
+
+        assert_eq!(app.run_script("tag_browser_modal_toggle\nkeys j"), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("▾ #project 2") && screen.contains("#alpha 1"));
+
+        assert_eq!(app.run_script("keys enter j enter"), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Ln 3, Col 1") && screen.contains("PROJECTS"));
+    }
+
+    #[test]
+    fn test_vault_index_updates() {
+        let vault = testing::vault("basalt-app-index-updates", &[("Ideas.md", "#idea\n")]);
+        let other = testing::vault("basalt-app-index-other", &[("Other.md", "#other\n")]);
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault, &other], 80, 16);
+        assert_eq!(app.run_script("vault basalt-app-index-updates"), Ok(()));
+
+        let mut index = VaultIndex::default();
+        index.update(&vault, |_, _| {});
+        app.send(Message::VaultIndexed(vault.path.clone(), index));
+
+        // The index of another vault that finishes late is dropped.
+        let mut index = VaultIndex::default();
+        index.update(&other, |_, _| {});
+        app.send(Message::VaultIndexed(other.path.clone(), index));
+
+        assert_eq!(app.run_script("tag_browser_modal_toggle"), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("#idea") && !screen.contains("#other"));
+
+        // Notes modified outside Basalt are indexed again.
+        fs::write(vault.path.join("Ideas.md"), "#idea #garden\n").unwrap();
+        app.send(Message::VaultFilesChanged(
+            vault.path.clone(),
+            watcher::Changes {
+                modified: vec![vault.path.join("Ideas.md")],
+                ..Default::default()
+            },
+        ));
+        assert!(app.screen().contains("#garden"));
+    }
+
+    #[test]
+    fn test_query_blocks() {
+        let vault = testing::vault(
+            "basalt-app-query-blocks",
+            &[
+                ("Reading.md", "```basalt-query\ntag:book\n```\n"),
+                ("Dune.md", "#book\n"),
+            ],
+        );
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+        assert_eq!(
+            app.run_script(
+                "vault basalt-app-query-blocks\nquick_switcher\ntype Reading\nkeys enter"
+            ),
+            Ok(())
+        );
+        let screen = app.screen();
+        assert!(screen.contains("• Dune") && screen.contains("tag:book · 1 note"));
+
+        fs::write(vault.path.join("Emma.md"), "#book\n").unwrap();
+        app.send(Message::VaultIndexed(
+            vault.path.clone(),
+            VaultIndex::default(),
+        ));
+        let screen = app.screen();
+        assert!(screen.contains("• Emma") && screen.contains("tag:book · 2 notes"));
+    }
+
+    #[test]
+    fn test_outgoing_links() {
+        let vault = testing::vault(
+            "basalt-app-outgoing-links",
+            &[
+                (
+                    "Inbox.md",
+                    "# Inbox\n\nSee [[Missing]]\n\nand [[Projects]]\n",
+                ),
+                ("Projects.md", "# Projects\n"),
+            ],
+        );
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-outgoing-links
+            quick_switcher
+            type Inbox
+            keys enter
+            outgoing_links_modal_toggle
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(
+            screen.contains("✗ [[Missing]] broken")
+                && screen.contains("[[Projects]] → Projects.md")
+        );
+
+        assert_eq!(app.run_script("keys j g"), Ok(()));
+        assert!(app.screen().contains("Ln 5, Col 1"));
+
+        assert_eq!(
+            app.run_script("outgoing_links_modal_toggle\nkeys j enter"),
+            Ok(())
+        );
+        assert!(app.screen().contains("PROJECTS"));
+    }
+
+    #[test]
+    fn test_note_history() {
+        let vault = testing::vault("basalt-app-note-history", &[("Pond.md", "# Pond\n\nKoi\n")]);
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=Basalt",
+                    "-c",
+                    "user.email=basalt@example.com",
+                ])
+                .args(args)
+                .current_dir(&vault.path)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?} failed");
+        };
+
+        git(&["init", "--quiet"]);
+        git(&["add", "Pond.md"]);
+        git(&["commit", "--quiet", "-m", "Add pond"]);
+        fs::write(vault.path.join("Pond.md"), "# Pond\n\nKoi and frogs\n").unwrap();
+        git(&["commit", "--quiet", "-am", "Add frogs"]);
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 120, 30);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-note-history
+            quick_switcher
+            type Pond
+            keys enter
+            note_history
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Add frogs") && screen.contains("Add pond"));
+        assert!(screen.contains("+ Koi and frogs") && screen.contains("- Koi"));
+
+        assert_eq!(app.run_script("keys j enter"), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Restored the version of"));
+        assert!(screen.contains("Koi") && !screen.contains("frogs"));
+    }
+
+    #[test]
+    fn test_history() {
+        let vault = testing::vault(
+            "basalt-app-history",
+            &[
+                ("Ideas.md", "# Ideas\n"),
+                ("Inbox.md", "# Inbox\n"),
+                ("Projects.md", "# Projects\n"),
+            ],
+        );
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 120, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-history
+            quick_switcher
+            type Ideas
+            keys enter
+            quick_switcher
+            type Inbox
+            keys enter
+            quick_switcher
+            type Projects
+            keys enter
+            history_back
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Ideas ← Inbox → Projects") && screen.contains("INBOX"));
+
+        assert_eq!(app.run_script("history_back\nhistory_back"), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Ideas → Inbox") && screen.contains("No previous note"));
+
+        assert_eq!(app.run_script("history_forward\nhistory_forward"), Ok(()));
+        assert!(app.screen().contains("Inbox ← Projects") && app.screen().contains("PROJECTS"));
+    }
+
+    #[test]
+    fn test_expanded_folders() {
+        let vault = testing::vault(
+            "basalt-app-expanded-folders",
+            &[
+                ("Garden/Pond/Fish.md", "# Fish\n"),
+                ("Garden/Shed.md", "# Shed\n"),
+                ("Inbox.md", "# Inbox\n"),
+            ],
+        );
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-expanded-folders
+            explorer_collapse_all
+            explorer_expand_all
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("Fish"));
+
+        // Reopening the vault scans it again, with the same folders expanded.
+        assert_eq!(app.run_script("vault basalt-app-expanded-folders"), Ok(()));
+        assert!(app.screen().contains("Fish"));
+
+        let script = indoc::indoc! {"
+            explorer_collapse_all
+            vault basalt-app-expanded-folders
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Garden") && !screen.contains("Shed"));
+    }
+
+    #[test]
+    fn test_embed_completion() {
+        let vault = testing::vault(
+            "basalt-app-embed-completion",
+            &[
+                ("Inbox.md", "# Inbox\n"),
+                ("Assets/Shed.png", ""),
+                ("Assets/Roof.jpg", ""),
+            ],
+        );
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 120, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-embed-completion
+            quick_switcher
+            type Inbox
+            keys enter i end enter
+            type ![[sh
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("│Shed.png  │"));
+
+        assert_eq!(app.run_script("keys enter"), Ok(()));
+        app.send(Message::NoteEditor(note_editor::Message::Paste(
+            " ![[Shde.png]]".into(),
+        )));
+        assert!(app.screen().contains("⚠ Missing Shde.png"));
+
+        assert_eq!(app.run_script("keys esc\nsave_all"), Ok(()));
+        assert_eq!(
+            fs::read_to_string(vault.path.join("Inbox.md")).unwrap(),
+            "# Inbox\n![[Shed.png]] ![[Shde.png]]\n"
+        );
+    }
+
+    #[test]
+    fn test_words_today() {
+        let vault = testing::vault("basalt-app-words-today", &[("Journal.md", "# Journal\n")]);
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 120, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-words-today
+            quick_switcher
+            type Journal
+            keys enter i end enter
+            type Rain all day
+            keys backspace backspace backspace
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("✎ 3 today"));
+
+        // Removed words are not taken back, rewriting them counts again.
+        assert_eq!(app.run_script("type night"), Ok(()));
+        assert!(app.screen().contains("✎ 4 today"));
+    }
+
+    #[test]
+    fn test_save_conflict() {
+        let vault = testing::vault("basalt-app-save-conflict", &[("Pond.md", "# Pond\n")]);
+        let path = vault.path.join("Pond.md");
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 120, 20);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-save-conflict
+            quick_switcher
+            type Pond
+            keys enter i end enter
+            type Koi
+            keys esc
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        fs::write(&path, "# Pond\n\nFrogs from the phone\n").unwrap();
+
+        assert_eq!(app.run_script("note_editor_experimental_save"), Ok(()));
+        assert!(app.screen().contains("Pond was changed outside Basalt"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# Pond\n\nFrogs from the phone\n"
+        );
+
+        assert_eq!(app.run_script("keys d"), Ok(()));
+        assert!(app.screen().contains("- Frogs from the phone"));
+
+        assert_eq!(app.run_script("keys esc\nsave_all\nkeys o"), Ok(()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Pond\nKoi\n");
+
+        fs::write(&path, "# Pond\n\nToads\n").unwrap();
+        let script = indoc::indoc! {"
+            keys i end
+            type  and carp
+            keys esc
+            note_editor_experimental_save
+            keys r
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Toads") && !screen.contains("carp"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Pond\n\nToads\n");
+    }
+
+    #[test]
+    fn test_note_position() {
+        let pond = (1..=30)
+            .map(|block| format!("Block {block}"))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let vault = testing::vault(
+            "basalt-app-note-position",
+            &[
+                ("Pond.md", &format!("# Pond\n\n{pond}\n")),
+                ("Shed.md", "# Shed\n"),
+            ],
+        );
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 120, 20);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-note-position
+            quick_switcher
+            type Pond
+            keys enter j j j j i end
+            type  with frogs
+            keys esc
+            note_editor_experimental_save
+            keys shift+g
+            quick_switcher
+            type Shed
+            keys enter
+            quick_switcher
+            type Pond
+            keys enter
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Block 30") && !screen.contains("Block 4 with frogs"));
+
+        assert_eq!(app.run_script("note_editor_goto_last_edit"), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Block 4 with frogs") && !screen.contains("Block 30"));
+    }
+
+    #[test]
+    fn test_count_scope() {
+        let vault = testing::vault(
+            "basalt-app-count-scope",
+            &[("Pond.md", "# Pond\n\nFrogs and toads\n")],
+        );
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 120, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-count-scope
+            quick_switcher
+            type Pond
+            keys enter
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("4 words"));
+
+        assert_eq!(app.run_script("cycle_count_scope\nkeys j"), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("block") && screen.contains("3 words"));
+
+        assert_eq!(app.run_script("keys i shift+right shift+right"), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("selection") && screen.contains("2 chars"));
+
+        assert_eq!(app.run_script("keys esc\ncycle_count_scope"), Ok(()));
+        let screen = app.screen();
+        assert!(!screen.contains("block") && screen.contains("4 words"));
+    }
+
+    #[test]
+    fn test_focus_timer() {
+        let vault = testing::vault("basalt-app-focus-timer", &[("Journal.md", "# Journal\n")]);
+
+        let config = testing::config("[timer]\nminutes = 50");
+        let mut app = testing::app(&config, vec![&vault], 120, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-focus-timer
+            focus_timer_start
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("⏱ 50:00") && screen.contains("Focus session of 50 minutes"));
+
+        app.send(Message::FocusTimer(focus_timer::Message::Ended));
+        let screen = app.screen();
+        assert!(!screen.contains("⏱") && screen.contains("Focus session ended"));
+
+        assert_eq!(app.run_script("focus_timer_stop"), Ok(()));
+        assert!(app.screen().contains("No focus session running"));
+    }
+
+    #[test]
+    fn test_vault_startup() {
+        let vault = testing::vault("basalt-app-vault-startup", &[("Journal.md", "# Journal\n")]);
+
+        let config = testing::config(indoc::indoc! {r#"
+            [vault_startup]
+            basalt-app-vault-startup = ["touch Pulled.md", "false"]
+        "#});
+        let mut app = testing::app(&config, vec![&vault], 120, 16);
+
+        assert_eq!(app.run_script("vault basalt-app-vault-startup"), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Pulled") && screen.contains("Journal"));
+        assert!(screen.contains("Command `false` failed: exit status: 1"));
+    }
+
+    #[test]
+    fn test_exec_prompt() {
+        let vault = testing::vault("basalt-app-exec-prompt", &[("Journal.md", "# Journal\n")]);
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+
+        assert_eq!(
+            app.run_script(
+                "vault basalt-app-exec-prompt\nexec_prompt:rg %1 %vault_path\ntype todo"
+            ),
+            Ok(())
+        );
+        let screen = app.screen();
+        assert!(
+            screen.contains("Run rg") && screen.contains("todo"),
+            "{screen}"
+        );
+    }
+
+    #[test]
+    fn test_duplicates_confirm() {
+        for (command, question) in [
+            ("duplicates_modal_replace", "Replace Note with Note copy"),
+            ("duplicates_modal_merge", "Merge Note copy into Note"),
+        ] {
+            let vault = testing::vault(
+                "basalt-app-duplicates-confirm",
+                &[
+                    ("Note.md", "# Note\n\nOld\n"),
+                    ("Note copy.md", "# Note\n\nNew\n"),
+                ],
+            );
+            let config = testing::config("");
+            let mut app = testing::app(&config, vec![&vault], 100, 16);
+
+            let (original, duplicate) =
+                (vault.path.join("Note.md"), vault.path.join("Note copy.md"));
+            app.run_script("vault basalt-app-duplicates-confirm")
+                .unwrap();
+            app.send(Message::DuplicatesModal(duplicates_modal::Message::Open(
+                vec![duplicates_modal::Duplicate::conflict(
+                    Note::from(original.as_path()),
+                    Note::from(duplicate.as_path()),
+                )],
+            )));
+
+            // Nothing changes until the question is answered with yes.
+            assert_eq!(app.run_script(command), Ok(()));
+            assert!(app.screen().contains(question), "{}", app.screen());
+            assert_eq!(app.run_script("keys n"), Ok(()));
+            assert_eq!(fs::read_to_string(&original).unwrap(), "# Note\n\nOld\n");
+            assert!(duplicate.exists());
+
+            assert_eq!(app.run_script(&format!("{command}\nkeys y")), Ok(()));
+            assert!(fs::read_to_string(&original).unwrap().contains("New"));
+            assert!(!duplicate.exists());
+        }
+    }
+
+    #[test]
+    fn test_duplicates_unsaved() {
+        for command in ["duplicates_modal_replace", "duplicates_modal_merge"] {
+            let vault = testing::vault(
+                "basalt-app-duplicates-unsaved",
+                &[
+                    ("Note.md", "# Note\n\nOld\n"),
+                    ("Note copy.md", "# Note\n\nNew\n"),
+                ],
+            );
+            let config = testing::config("experimental_editor = true");
+            let mut app = testing::app(&config, vec![&vault], 100, 16);
+
+            let (original, duplicate) =
+                (vault.path.join("Note.md"), vault.path.join("Note copy.md"));
+            let script = indoc::indoc! {"
+                vault basalt-app-duplicates-unsaved
+                quick_switcher
+                type Note copy
+                keys enter i end
+                type  edited
+                keys esc
+            "};
+            assert_eq!(app.run_script(script), Ok(()));
+            assert!(app.state().contains("unsaved: Note copy\n"));
+
+            app.send(Message::DuplicatesModal(duplicates_modal::Message::Open(
+                vec![duplicates_modal::Duplicate::conflict(
+                    Note::from(original.as_path()),
+                    Note::from(duplicate.as_path()),
+                )],
+            )));
+
+            // The unsaved changes of the removed note are kept in the original.
+            assert_eq!(app.run_script(&format!("{command}\nkeys y")), Ok(()));
+            assert!(fs::read_to_string(&original)
+                .unwrap()
+                .contains("# Note edited"));
+            assert!(!duplicate.exists());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_with() {
+        let vault = testing::vault("basalt-app-open-with", &[("Journal.md", "# Journal\n")]);
+        let config = testing::config(indoc::indoc! {r#"
+            [open_with]
+            "Dry run" = "spawn:true %note"
+            Pager = "exec:less %note_path"
+        "#});
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+
+        assert_eq!(
+            app.run_script("vault basalt-app-open-with\nopen_with"),
+            Ok(())
+        );
+        assert!(app.screen().contains("No note is open"), "{}", app.screen());
+
+        let script = indoc::indoc! {"
+            quick_switcher
+            type Journal
+            keys enter
+            open_with
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(
+            screen.contains("Open with") && screen.contains("Dry run") && screen.contains("Pager"),
+            "{screen}"
+        );
+
+        assert_eq!(
+            app.run_script("type Dry\nkeys enter\njobs_modal_toggle"),
+            Ok(())
+        );
+        assert!(app.screen().contains("true Journal"), "{}", app.screen());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_jobs_modal() {
+        let vault = testing::vault("basalt-app-jobs-modal", &[("Journal.md", "# Journal\n")]);
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+
+        let script =
+            app.run_script("vault basalt-app-jobs-modal\nspawn:sleep 5\njobs_modal_toggle");
+        let running = app.screen();
+
+        // The process is killed and reaped before asserting, so that it never outlives the test.
+        let kill = app.run_script("keys x");
+        let killed = app.screen();
+
+        assert_eq!(script, Ok(()));
+        assert!(
+            running.contains("Jobs")
+                && running.contains("sleep 5 running")
+                && running.contains("1 running"),
+            "{running}"
+        );
+
+        assert_eq!(kill, Ok(()));
+        assert!(
+            killed.contains("sleep 5 killed") && killed.contains("0 running"),
+            "{killed}"
+        );
+    }
+
+    #[test]
+    fn test_type_ahead() {
+        let vault = testing::vault(
+            "basalt-app-type-ahead",
+            &[
+                ("Ideas.md", "# Ideas\n"),
+                ("Inbox.md", "# Inbox\n"),
+                ("Inventory.md", "# Inventory\n\n## Tools\n\n## Furniture\n"),
+                ("Inquest.md", "# Inquest\n"),
+                ("Projects.md", "# Projects\n"),
+            ],
+        );
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 120, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-type-ahead
+            keys i n v enter
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("INVENTORY") && screen.contains("Jump to inv"));
+
+        assert_eq!(app.run_script("keys x"), Ok(()));
+        assert!(app.screen().contains("Nothing starts with invx"));
+
+        // The query starts over in another list.
+        assert_eq!(app.run_script("focus_outline\nkeys f"), Ok(()));
+        assert!(app.screen().contains("Jump to f"));
+
+        // Bound characters continue the query, `q` does not quit and `s` does not sort.
+        assert_eq!(app.run_script("focus_explorer\ntype inquest"), Ok(()));
+        assert!(app.is_running());
+        assert!(app.screen().contains("Jump to inquest"));
+        assert_eq!(app.run_script("keys enter"), Ok(()));
+        assert!(app.screen().contains("INQUEST"));
+    }
+
+    #[test]
+    fn test_copy_link() {
+        let vault = testing::vault(
+            "basalt-app-copy-link",
+            &[
+                ("Garden.md", "# Garden\n\n## Pond: koi\n"),
+                ("Archive/Garden.md", "# Old garden\n"),
+            ],
+        );
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 120, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-copy-link
+            quick_switcher
+            type Archive/Garden
+            keys enter
+            copy_link_to_note
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("[[Archive/Garden]]"));
+
+        let script = indoc::indoc! {"
+            focus_explorer
+            explorer_goto_bottom
+            explorer_open
+            focus_outline
+            outline_down
+            copy_link_to_heading
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("[[Garden#Pond koi]]"));
+    }
+
+    #[test]
+    fn test_goto_top_and_bottom() {
+        let vault = testing::vault(
+            "basalt-app-goto",
+            &[
+                ("Alpha.md", "# Alpha\n"),
+                ("Beta.md", "# Beta\n"),
+                ("Omega.md", "# Omega\n\nFirst\n\nSecond\n\nLast\n"),
+            ],
+        );
+
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-goto
+            explorer_open
+            keys shift+g enter
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(app.screen().contains("OMEGA"));
+
+        assert_eq!(app.run_script("keys tab shift+g"), Ok(()));
+        assert!(app.screen().contains("Ln 7, Col 1"));
+
+        assert_eq!(app.run_script("keys g"), Ok(()));
+        assert!(app.screen().contains("Ln 1, Col 1"));
+    }
+
+    #[test]
+    fn test_fix_renamed_links() {
+        let vault = testing::vault(
+            "basalt-app-renamed-links",
+            &[
+                (
+                    "Inbox.md",
+                    "# Inbox\n\nSee [[Ideas]] and [[Ideas#Pond|the pond]]\n",
+                ),
+                ("Ideas.md", "# Pond\n"),
+            ],
+        );
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+        assert_eq!(app.run_script("vault basalt-app-renamed-links"), Ok(()));
+
+        let (from, to) = (
+            vault.path.join("Ideas.md"),
+            vault.path.join("Garden ideas.md"),
+        );
+        fs::rename(&from, &to).unwrap();
+        app.send(Message::VaultFilesChanged(
+            vault.path.clone(),
+            watcher::Changes {
+                removed: vec![from],
+                added: vec![to],
+                ..Default::default()
+            },
+        ));
+        assert!(app
+            .screen()
+            .contains("Ideas was renamed to Garden ideas outside Basalt"));
+
+        assert_eq!(app.run_script("keys y"), Ok(()));
+        assert_eq!(
+            fs::read_to_string(vault.path.join("Inbox.md")).unwrap(),
+            "# Inbox\n\nSee [[Garden ideas]] and [[Garden ideas#Pond|the pond]]\n"
+        );
+    }
+
+    #[test]
+    fn test_fix_link() {
+        let vault = testing::vault(
+            "basalt-app-fix-link",
+            &[
+                ("Inbox.md", "# Inbox\n\nSee [[Idaes#Pond]] and [[Shed]]\n"),
+                ("Garden/Ideas.md", "# Pond\n"),
+                ("Projects.md", "# Projects\n"),
+            ],
+        );
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&vault], 80, 16);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-fix-link
+            quick_switcher
+            type Inbox
+            keys enter down ctrl+alt+l
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        let screen = app.screen();
+        assert!(screen.contains("Fix link to Idaes") && screen.contains("Garden/Ideas"));
+
+        assert_eq!(app.run_script("keys enter\nsave_all"), Ok(()));
+        assert_eq!(
+            fs::read_to_string(vault.path.join("Inbox.md")).unwrap(),
+            "# Inbox\n\nSee [[Ideas#Pond]] and [[Shed]]\n"
+        );
+    }
+
+    #[test]
+    fn test_switch_vaults() {
+        let work = testing::vault("basalt-app-cache-work", &[("Plan.md", "# Plan\n")]);
+        let personal = testing::vault("basalt-app-cache-personal", &[("Ideas.md", "# Ideas\n")]);
+
+        let config = testing::config("experimental_editor = true");
+        let mut app = testing::app(&config, vec![&work, &personal], 80, 24);
+
+        let script = indoc::indoc! {"
+            vault basalt-app-cache-work
+            explorer_open
+            keys tab i end
+            type  today
+            keys esc
+            vault basalt-app-cache-personal
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert_eq!(
+            app.state(),
+            "pane: Explorer\nvault: basalt-app-cache-personal\nnote: \nunsaved: Plan\nerror: "
+        );
+
+        // Switching back restores the open note with its unsaved changes.
+        assert_eq!(app.run_script("vault basalt-app-cache-work"), Ok(()));
+        assert_eq!(
+            app.state(),
+            "pane: Note Editor\nvault: basalt-app-cache-work\nnote: Plan\nunsaved: Plan\nerror: "
+        );
+
+        assert_eq!(
+            app.run_script("vault basalt-app-cache-personal\nsave_all"),
+            Ok(())
+        );
+        assert_eq!(
+            fs::read_to_string(work.path.join("Plan.md")).unwrap(),
+            "# Plan today\n"
+        );
+
+        // Switching vaults does not write the session file, the opened vaults are written on quit.
+        assert!(Session::load()
+            .recent_vaults
+            .iter()
+            .all(|vault| !vault.name.starts_with("basalt-app-cache")));
+
+        // Saving from the quit modal writes the opened vaults and the position in the open note.
+        let script = indoc::indoc! {"
+            explorer_open
+            keys tab i end
+            type  later
+            keys esc
+            quit
+            keys s
+        "};
+        assert_eq!(app.run_script(script), Ok(()));
+        assert!(!app.is_running());
+        assert_eq!(
+            fs::read_to_string(personal.path.join("Ideas.md")).unwrap(),
+            "# Ideas later\n"
+        );
+
+        let session = Session::load();
+        ["basalt-app-cache-work", "basalt-app-cache-personal"]
+            .into_iter()
+            .for_each(|name| {
+                assert!(session.recent_vaults.iter().any(|vault| vault.name == name));
+            });
+        assert!(session
+            .note_position(&personal.path.join("Ideas.md"))
+            .is_some());
     }
 }
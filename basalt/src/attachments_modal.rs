@@ -0,0 +1,430 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use basalt_core::obsidian::{Link, LinkIndex, LinkKind, Note, Vault, VaultEntry};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{
+    app::Message as AppMessage,
+    background::Job,
+    chrome::Chrome,
+    input_modal::{self, InputAction},
+    responsive,
+};
+
+/// Folder relative to the vault root that is suggested when moving attachments.
+const MOVE_FOLDER: &str = "Unused attachments";
+
+/// Returns the folders of the vault relative to the vault root, parents before their children.
+fn folders(entries: &[VaultEntry], root: &Path) -> Vec<String> {
+    entries
+        .iter()
+        .flat_map(|entry| match entry {
+            VaultEntry::Directory { path, entries, .. } => {
+                let folder = path.strip_prefix(root).unwrap_or(path);
+                [folder.to_string_lossy().to_string()]
+                    .into_iter()
+                    .chain(folders(entries, root))
+                    .collect()
+            }
+            VaultEntry::File(_) => vec![],
+        })
+        .collect()
+}
+
+/// A problem found in the attachments of a vault.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttachmentIssue {
+    /// An attachment that no note links to and no canvas embeds, which is safe to delete.
+    Unused(PathBuf),
+    /// A link in `note` to an attachment that does not exist.
+    Missing { note: Note, link: Link },
+}
+
+impl AttachmentIssue {
+    /// Collects the unused attachments and the links to missing attachments of the index, unused
+    /// attachments first.
+    pub fn collect(index: &LinkIndex) -> Vec<AttachmentIssue> {
+        let unused = index
+            .unused_attachments()
+            .into_iter()
+            .map(|path| AttachmentIssue::Unused(path.to_path_buf()));
+
+        let missing =
+            index
+                .missing_attachments()
+                .into_iter()
+                .map(|(note, link)| AttachmentIssue::Missing {
+                    note: Note::from(note),
+                    link: link.clone(),
+                });
+
+        unused.chain(missing).collect()
+    }
+
+    fn unused(&self) -> Option<&Path> {
+        match self {
+            AttachmentIssue::Unused(path) => Some(path),
+            AttachmentIssue::Missing { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    Close,
+    Up,
+    Down,
+    /// Marks or unmarks the selected unused attachment for a batch action.
+    Mark,
+    /// Marks all unused attachments, or unmarks them when all are marked.
+    MarkAll,
+    Open,
+    Delete,
+    Move,
+    Refresh,
+    Progress(usize, usize),
+    Loaded(Vec<AttachmentIssue>),
+    Failed(String),
+}
+
+pub fn update<'a>(
+    message: &Message,
+    vault: Option<&Vault>,
+    state: &mut AttachmentsModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Toggle if state.visible => state.hide(),
+        Message::Toggle => {
+            state.visible = true;
+            return update(&Message::Refresh, vault, state);
+        }
+        Message::Close => state.hide(),
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::Mark => {
+            let path = state.selected_item()?.unused()?.to_path_buf();
+            if !state.marked.remove(&path) {
+                state.marked.insert(path);
+            }
+        }
+        Message::MarkAll => {
+            let unused: BTreeSet<_> = state
+                .items
+                .iter()
+                .filter_map(AttachmentIssue::unused)
+                .map(Path::to_path_buf)
+                .collect();
+
+            state.marked = match state.marked == unused {
+                true => BTreeSet::new(),
+                false => unused,
+            };
+        }
+        Message::Open => {
+            let message = match state.selected_item()? {
+                AttachmentIssue::Unused(path) => AppMessage::OpenAttachment(path.clone()),
+                AttachmentIssue::Missing { note, link } => {
+                    let message = AppMessage::OpenNoteAt(note.clone(), link.range.start);
+                    state.hide();
+                    message
+                }
+            };
+            return Some(message);
+        }
+        Message::Delete => {
+            let paths = state.batch();
+            if !paths.is_empty() {
+                return Some(AppMessage::InputModal(input_modal::Message::Confirm {
+                    title: "Delete attachments".into(),
+                    question: format!("Delete {} attachments?", paths.len()),
+                    action: InputAction::DeleteAttachments(paths),
+                }));
+            }
+        }
+        Message::Move => {
+            let paths = state.batch();
+            if !paths.is_empty() {
+                let vault = vault?;
+                return Some(AppMessage::InputModal(input_modal::Message::Select {
+                    title: format!("Move {} attachments to folder", paths.len()),
+                    value: MOVE_FOLDER.into(),
+                    options: folders(&vault.entries(), &vault.path),
+                    action: InputAction::MoveAttachments(paths),
+                }));
+            }
+        }
+        Message::Refresh => {
+            let vault = vault?;
+            state.vault_path = vault.path.clone();
+
+            if state.progress.is_none() {
+                state.progress = Some((0, 0));
+                return Some(AppMessage::RunJob(Job::Attachments(vault.clone())));
+            }
+        }
+        Message::Progress(done, total) => state.progress = Some((*done, *total)),
+        Message::Loaded(items) => {
+            state.progress = None;
+            state.error = None;
+            state.items = items.clone();
+            state.marked.retain(|path| {
+                items
+                    .iter()
+                    .any(|item| item.unused() == Some(path.as_path()))
+            });
+            state.list_state.select(match items.is_empty() {
+                true => None,
+                false => Some(
+                    state
+                        .list_state
+                        .selected()
+                        .unwrap_or_default()
+                        .min(items.len() - 1),
+                ),
+            });
+        }
+        Message::Failed(error) => {
+            state.progress = None;
+            state.error = Some(error.clone());
+            state.items.clear();
+            state.marked.clear();
+            state.list_state.select(None);
+        }
+    };
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AttachmentsModalState {
+    pub(crate) items: Vec<AttachmentIssue>,
+    /// Unused attachments marked for a batch action.
+    pub(crate) marked: BTreeSet<PathBuf>,
+    pub(crate) list_state: ListState,
+    /// Number of scanned notes and total number of notes while the scan is running.
+    pub(crate) progress: Option<(usize, usize)>,
+    pub(crate) error: Option<String>,
+    pub(crate) vault_path: PathBuf,
+    pub(crate) visible: bool,
+}
+
+impl AttachmentsModalState {
+    pub fn selected_item(&self) -> Option<&AttachmentIssue> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.items.get(index))
+    }
+
+    /// Returns the attachments a batch action applies to: the marked attachments, or the selected
+    /// unused attachment when none are marked.
+    fn batch(&self) -> Vec<PathBuf> {
+        match self.marked.is_empty() {
+            true => self
+                .selected_item()
+                .and_then(AttachmentIssue::unused)
+                .map(Path::to_path_buf)
+                .into_iter()
+                .collect(),
+            false => self.marked.iter().cloned().collect(),
+        }
+    }
+
+    pub fn next(&mut self) {
+        if !self.items.is_empty() {
+            let index = self.list_state.selected().map_or(0, |index| index + 1);
+            self.list_state
+                .select(Some(index.min(self.items.len() - 1)));
+        }
+    }
+
+    pub fn previous(&mut self) {
+        let index = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some(index.saturating_sub(1)));
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.vault_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn list_item(&self, issue: &AttachmentIssue) -> ListItem<'static> {
+        match issue {
+            AttachmentIssue::Unused(path) => ListItem::new(Line::from(vec![
+                match self.marked.contains(path) {
+                    true => Span::from("[x] ").green(),
+                    false => Span::from("[ ] ").dark_gray(),
+                },
+                Span::from(self.relative(path)),
+                Span::from(" unused").dark_gray().italic(),
+            ])),
+            AttachmentIssue::Missing { note, link } => {
+                let target = match link.kind {
+                    LinkKind::Wiki => format!("[[{}]]", link.target),
+                    LinkKind::Markdown => format!("({})", link.target),
+                };
+
+                ListItem::new(Line::from(vec![
+                    Span::from(" ✗  ").red(),
+                    Span::from(self.relative(&note.path)),
+                    Span::from(" → ").dark_gray(),
+                    Span::from(target).red(),
+                ]))
+            }
+        }
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(70), Constraint::Percentage(60))
+}
+
+#[derive(Default)]
+pub struct AttachmentsModal {
+    chrome: Chrome,
+}
+
+impl AttachmentsModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for AttachmentsModal {
+    type State = AttachmentsModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let unused = state
+            .items
+            .iter()
+            .filter(|item| item.unused().is_some())
+            .count();
+        let missing = state.items.len() - unused;
+
+        let summary = match state.progress {
+            Some((done, total)) => format!(" Scanning {done}/{total} "),
+            None if state.marked.is_empty() => format!(" {unused} unused · {missing} missing "),
+            None => format!(
+                " {} marked · {unused} unused · {missing} missing ",
+                state.marked.len()
+            ),
+        };
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(" Attachments ")
+            .title(Line::from(summary).alignment(Alignment::Right))
+            .title_bottom(
+                Line::from(
+                    " (space) mark · (a) mark all · (d) delete · (m) move · (enter) open · (esc) close ",
+                )
+                .alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if let Some(error) = &state.error {
+            Paragraph::new(error.as_str()).red().render(inner_area, buf);
+            return;
+        }
+
+        if state.progress.is_none() && state.items.is_empty() {
+            Paragraph::new("No unused or missing attachments")
+                .dark_gray()
+                .italic()
+                .render(inner_area, buf);
+            return;
+        }
+
+        let items: Vec<_> = state
+            .items
+            .iter()
+            .map(|issue| state.list_item(issue))
+            .collect();
+
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_style(Style::default().reversed().dark_gray())
+                .highlight_symbol(""),
+            inner_area,
+            buf,
+            &mut state.list_state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch() {
+        let mut state = AttachmentsModalState::default();
+        let missing = AttachmentIssue::Missing {
+            note: Note::from(Path::new("vault/Index.md")),
+            link: Link::parse("![[Gone.png]]")[0].clone(),
+        };
+
+        update(
+            &Message::Loaded(vec![
+                AttachmentIssue::Unused("vault/a.png".into()),
+                AttachmentIssue::Unused("vault/b.pdf".into()),
+                missing,
+            ]),
+            None,
+            &mut state,
+        );
+
+        assert_eq!(state.batch(), [PathBuf::from("vault/a.png")]);
+
+        update(&Message::MarkAll, None, &mut state);
+        assert_eq!(state.marked.len(), 2);
+
+        update(&Message::Mark, None, &mut state);
+        assert_eq!(
+            update(&Message::Delete, None, &mut state),
+            Some(AppMessage::InputModal(input_modal::Message::Confirm {
+                title: "Delete attachments".into(),
+                question: "Delete 1 attachments?".into(),
+                action: InputAction::DeleteAttachments(vec!["vault/b.pdf".into()]),
+            }))
+        );
+
+        update(&Message::Down, None, &mut state);
+        update(&Message::Down, None, &mut state);
+        update(&Message::Mark, None, &mut state);
+        assert_eq!(state.marked.len(), 1);
+
+        update(
+            &Message::Loaded(vec![AttachmentIssue::Unused("vault/a.png".into())]),
+            None,
+            &mut state,
+        );
+        assert!(state.marked.is_empty());
+    }
+}
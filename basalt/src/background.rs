@@ -0,0 +1,279 @@
+//! Runs long running work, like scanning a vault, on a background thread. The results are
+//! delivered to the main loop as messages, so that the UI stays responsive in the meantime.
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::mpsc,
+    thread,
+};
+
+use basalt_core::obsidian::{publish, Error, LinkIndex, Note, PublishOptions, Vault};
+
+use crate::{
+    app::Message, attachments_modal, command, crash, diagnostics_modal, duplicates_modal,
+    index_cache, text_counts::CountMode, vault_loading_modal, vault_replace_modal,
+    vault_stats_modal, web_clip::WebClip,
+};
+
+/// Work that can be run in the background.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Job {
+    /// Reads the entries of the vault that is being opened, after running its startup commands.
+    ScanVault(Vault, Vec<String>),
+    VaultStats(Vault, CountMode),
+    Diagnostics(Vault),
+    /// Finds the unused and missing attachments of the vault.
+    Attachments(Vault),
+    Duplicates(Vault),
+    Publish(Vault, PublishOptions),
+    /// Fetches the page at the URL for the note at the path, or for a new note without a path.
+    WebClip(String, Option<PathBuf>),
+    /// Searches the notes of the vault with a pattern and its replacement.
+    VaultReplace(Vault, String, String),
+    /// Finds the tags of the vault to rename, given as the old and new tag.
+    RenameTag(Vault, String, String),
+    /// Finds the heading of the note and the links to it to rename, given as the path of the note
+    /// and the old and new heading.
+    RenameHeading(Vault, PathBuf, String, String),
+    /// Brings the cached index of the vault up to date.
+    IndexVault(Vault),
+}
+
+/// Reads the notes of the vault, reporting the progress to the vault replace modal. Notes that
+/// are not valid UTF-8 are skipped and reported, so that their bytes are not rewritten.
+fn read_notes(vault: &Vault, sender: &Sender) -> Vec<(Note, String)> {
+    let notes = vault.notes();
+    let total = notes.len();
+    let mut skipped = Vec::new();
+
+    let notes = notes
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, note)| {
+            let contents = Note::read_to_string_strict(&note);
+            sender.send(Message::VaultReplaceModal(
+                vault_replace_modal::Message::Progress(i + 1, total),
+            ));
+            match contents {
+                Ok(contents) => Some((note, contents)),
+                Err(Error::InvalidUtf8(_)) => {
+                    skipped.push(note.name);
+                    None
+                }
+                Err(_) => None,
+            }
+        })
+        .collect();
+
+    if !skipped.is_empty() {
+        sender.send(Message::Error(format!(
+            "Skipped {}: not valid UTF-8",
+            skipped.join(", ")
+        )));
+    }
+
+    notes
+}
+
+impl Job {
+    fn run(self, sender: &Sender) {
+        match self {
+            Job::ScanVault(vault, commands) => {
+                for command in commands {
+                    sender.send(Message::VaultLoadingModal(
+                        vault_loading_modal::Message::Running(vault.path.clone(), command.clone()),
+                    ));
+                    if let Some(message) = command::vault_command(command, &vault.name, &vault.path)
+                    {
+                        sender.send(message);
+                    }
+                }
+
+                let entries = vault.entries_with_progress(|done, total| {
+                    sender.send(Message::VaultLoadingModal(
+                        vault_loading_modal::Message::Progress(vault.path.clone(), done, total),
+                    ));
+                });
+
+                sender.send(Message::VaultScanned(vault.path, entries));
+            }
+            Job::VaultStats(vault, count_mode) => {
+                let stats =
+                    vault_stats_modal::VaultStats::compute(&vault, count_mode, |done, total| {
+                        sender.send(Message::VaultStatsModal(
+                            vault_stats_modal::Message::Progress(done, total),
+                        ));
+                    });
+
+                sender.send(Message::VaultStatsModal(
+                    vault_stats_modal::Message::Loaded(stats),
+                ));
+            }
+            Job::Diagnostics(vault) => {
+                let index = LinkIndex::build(&vault, |done, total| {
+                    sender.send(Message::DiagnosticsModal(
+                        diagnostics_modal::Message::Progress(done, total),
+                    ));
+                });
+
+                sender.send(Message::DiagnosticsModal(match index {
+                    Ok(index) => diagnostics_modal::Message::Loaded(
+                        diagnostics_modal::Diagnostic::collect(&index),
+                    ),
+                    Err(err) => diagnostics_modal::Message::Failed(err.to_string()),
+                }));
+            }
+            Job::Attachments(vault) => {
+                let index = LinkIndex::build(&vault, |done, total| {
+                    sender.send(Message::AttachmentsModal(
+                        attachments_modal::Message::Progress(done, total),
+                    ));
+                });
+
+                sender.send(Message::AttachmentsModal(match index {
+                    Ok(index) => attachments_modal::Message::Loaded(
+                        attachments_modal::AttachmentIssue::collect(&index),
+                    ),
+                    Err(err) => attachments_modal::Message::Failed(err.to_string()),
+                }));
+            }
+            Job::Duplicates(vault) => {
+                let notes = vault.notes();
+                let total = notes.len();
+
+                let notes: Vec<_> = notes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, note)| {
+                        let contents = Note::read_to_string(&note).unwrap_or_default();
+                        sender.send(Message::DuplicatesModal(
+                            duplicates_modal::Message::Progress(i + 1, total),
+                        ));
+                        (note, contents)
+                    })
+                    .collect();
+
+                sender.send(Message::DuplicatesModal(duplicates_modal::Message::Loaded(
+                    duplicates_modal::find_duplicates(&notes),
+                )));
+            }
+            Job::Publish(vault, options) => {
+                let report = publish(&vault, &options, |done, total| {
+                    sender.send(Message::SetStatus(format!("Publishing {done}/{total}")));
+                });
+
+                sender.send(match report {
+                    Ok(report) => Message::SetStatus(format!(
+                        "Published {} notes and {} attachments to {}",
+                        report.notes,
+                        report.attachments,
+                        report.output_dir.display()
+                    )),
+                    Err(err) => Message::Error(format!("Publish failed: {err}")),
+                });
+            }
+            Job::VaultReplace(vault, pattern, replacement) => {
+                let notes = read_notes(&vault, sender);
+
+                sender.send(Message::VaultReplaceModal(
+                    vault_replace_modal::Message::Loaded(vault_replace_modal::find_in_notes(
+                        notes,
+                        &pattern,
+                        &replacement,
+                    )),
+                ));
+            }
+            Job::RenameTag(vault, from, to) => {
+                let notes = read_notes(&vault, sender);
+
+                sender.send(Message::VaultReplaceModal(
+                    vault_replace_modal::Message::Loaded(Ok(vault_replace_modal::find_tags(
+                        notes, &from, &to,
+                    ))),
+                ));
+            }
+            Job::RenameHeading(vault, path, from, to) => {
+                let notes = read_notes(&vault, sender);
+                let index = LinkIndex::new(
+                    &vault.path,
+                    notes.iter().map(|(note, _)| note.path.clone()).collect(),
+                );
+
+                sender.send(Message::VaultReplaceModal(
+                    vault_replace_modal::Message::Loaded(Ok(
+                        vault_replace_modal::find_heading_links(notes, &index, &path, &from, &to),
+                    )),
+                ));
+            }
+            Job::WebClip(url, note) => sender.send(Message::WebClipped(note, WebClip::fetch(&url))),
+            Job::IndexVault(vault) => {
+                let index = index_cache::load(&vault);
+                sender.send(Message::VaultIndexed(vault.path, index));
+            }
+        }
+    }
+}
+
+pub type Receiver = mpsc::Receiver<Message<'static>>;
+
+#[derive(Clone, Debug)]
+pub struct Sender(mpsc::Sender<Message<'static>>);
+
+pub fn channel() -> (Sender, Receiver) {
+    let (sender, receiver) = mpsc::channel();
+    (Sender(sender), receiver)
+}
+
+impl Sender {
+    /// Sends the message to the main loop. Messages sent after the main loop has exited are
+    /// dropped.
+    pub fn send(&self, message: Message<'static>) {
+        _ = self.0.send(message);
+    }
+
+    /// Runs the job on a new thread. A panic of the job is reported as an error.
+    pub fn spawn(&self, job: Job) {
+        let sender = self.clone();
+        thread::spawn(move || {
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| job.run(&sender))) {
+                sender.send(Message::Error(format!(
+                    "Background job failed: {}",
+                    crash::panic_message(payload.as_ref())
+                )));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn test_read_notes() {
+        let vault = testing::vault("basalt-background-read-notes", &[("Inbox.md", "# Inbox\n")]);
+        fs::write(vault.path.join("Latin1.md"), b"# Caf\xe9\n").unwrap();
+
+        let (sender, receiver) = channel();
+        let notes = read_notes(&vault, &sender);
+        assert_eq!(
+            notes
+                .iter()
+                .map(|(note, contents)| (note.name.as_str(), contents.as_str()))
+                .collect::<Vec<_>>(),
+            [("Inbox", "# Inbox\n")]
+        );
+
+        let errors: Vec<_> = receiver
+            .try_iter()
+            .filter_map(|message| match message {
+                Message::Error(error) => Some(error),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors, ["Skipped Latin1: not valid UTF-8"]);
+    }
+}
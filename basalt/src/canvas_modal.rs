@@ -0,0 +1,371 @@
+use std::path::{Path, PathBuf};
+
+use basalt_core::obsidian::{Canvas, CanvasNode, CanvasNodeKind, Note, Vault};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Clear, List, ListItem, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{app::Message as AppMessage, chrome::Chrome, responsive};
+
+/// Maximum number of lines shown below the title of a text card.
+const TEXT_PREVIEW_LINES: usize = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Opens the modal with the canvas file.
+    Open(Note),
+    Close,
+    Up,
+    Down,
+    /// Opens the note of the selected file card.
+    Select,
+}
+
+pub fn update<'a>(
+    message: &Message,
+    vault: Option<&Vault>,
+    state: &mut CanvasModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Open(note) => {
+            state.visible = true;
+            state.vault_path = vault.map(|vault| vault.path.clone()).unwrap_or_default();
+            state.load(note);
+        }
+        Message::Close => state.hide(),
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::Select => {
+            let CanvasNodeKind::File { file, subpath } = &state.selected_card()?.kind else {
+                return None;
+            };
+
+            let path = state.vault_path.join(file);
+            if path.extension().is_none_or(|ext| ext != "md") || !path.exists() {
+                return Some(AppMessage::SetStatus(format!("{file} is not a note")));
+            }
+
+            let note = Note::from(path.as_path());
+            let offset = subpath
+                .as_deref()
+                .and_then(|subpath| heading_offset(&note, subpath))
+                .unwrap_or_default();
+
+            state.hide();
+            return Some(AppMessage::OpenNoteAt(note, offset));
+        }
+    };
+
+    None
+}
+
+/// Returns the byte offset of the heading a subpath like `#Heading` points to.
+fn heading_offset(note: &Note, subpath: &str) -> Option<usize> {
+    let heading = subpath.strip_prefix('#')?;
+    let content = Note::read_to_string(note).ok()?;
+
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if line.starts_with('#') && line.trim_start_matches('#').trim() == heading {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CanvasModalState {
+    pub(crate) name: String,
+    pub(crate) canvas: Canvas,
+    /// Cards of the canvas in reading order with their nesting depth in groups.
+    pub(crate) cards: Vec<(CanvasNode, usize)>,
+    pub(crate) list_state: ListState,
+    pub(crate) error: Option<String>,
+    pub(crate) vault_path: PathBuf,
+    pub(crate) visible: bool,
+}
+
+impl CanvasModalState {
+    fn load(&mut self, note: &Note) {
+        self.name = note.name.clone();
+        self.list_state = ListState::default();
+
+        match Canvas::read(&note.path) {
+            Ok(canvas) => {
+                self.cards = canvas
+                    .reading_order()
+                    .into_iter()
+                    .map(|(node, depth)| (node.clone(), depth))
+                    .collect();
+                self.canvas = canvas;
+                self.error = None;
+                self.list_state
+                    .select((!self.cards.is_empty()).then_some(0));
+            }
+            Err(err) => {
+                self.canvas = Canvas::default();
+                self.cards.clear();
+                self.error = Some(err.to_string());
+            }
+        }
+    }
+
+    pub fn selected_card(&self) -> Option<&CanvasNode> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.cards.get(index))
+            .map(|(node, _)| node)
+    }
+
+    pub fn next(&mut self) {
+        if !self.cards.is_empty() {
+            let index = self.list_state.selected().map_or(0, |index| index + 1);
+            self.list_state
+                .select(Some(index.min(self.cards.len() - 1)));
+        }
+    }
+
+    pub fn previous(&mut self) {
+        let index = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some(index.saturating_sub(1)));
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    /// Returns the title of the card with the given identifier, or the identifier of cards that
+    /// do not exist.
+    fn card_title(&self, id: &str) -> String {
+        self.canvas
+            .node(id)
+            .map_or_else(|| id.to_string(), CanvasNode::title)
+    }
+
+    /// Returns the card as lines: the title, a preview of the text of text cards and the edges
+    /// from and to the card.
+    fn list_item(&self, node: &CanvasNode, depth: usize) -> ListItem<'static> {
+        let indent = "  ".repeat(depth);
+
+        let title = match &node.kind {
+            CanvasNodeKind::Text { .. } => {
+                vec![Span::from("◦ ").dark_gray(), Span::from(node.title())]
+            }
+            CanvasNodeKind::File { file, .. } => vec![
+                Span::from("▪ ").cyan(),
+                Span::from(node.title()).cyan(),
+                Span::from(
+                    match Path::new(file).extension().is_some_and(|ext| ext == "md") {
+                        true => "",
+                        false => " file",
+                    },
+                )
+                .dark_gray()
+                .italic(),
+            ],
+            CanvasNodeKind::Link { .. } => vec![
+                Span::from("● ").dark_gray(),
+                Span::from(node.title()).underlined(),
+            ],
+            CanvasNodeKind::Group { .. } => {
+                vec![
+                    Span::from("▾ ").dark_gray(),
+                    Span::from(node.title()).bold(),
+                ]
+            }
+        };
+
+        let mut lines = vec![Line::from_iter(
+            [Span::from(indent.clone())].into_iter().chain(title),
+        )];
+
+        if let CanvasNodeKind::Text { text } = &node.kind {
+            let body: Vec<_> = text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .skip(1)
+                .collect();
+
+            lines.extend(
+                body.iter()
+                    .take(TEXT_PREVIEW_LINES)
+                    .map(|line| Line::from(format!("{indent}  {line}")).dark_gray()),
+            );
+
+            if body.len() > TEXT_PREVIEW_LINES {
+                lines.push(Line::from(format!("{indent}  …")).dark_gray());
+            }
+        }
+
+        let label = |label: &Option<String>| {
+            label
+                .as_ref()
+                .map(|label| Span::from(format!(" · {label}")).italic())
+                .unwrap_or_default()
+        };
+
+        lines.extend(self.canvas.outgoing(&node.id).into_iter().map(|edge| {
+            Line::from(vec![
+                Span::from(format!("{indent}  → ")),
+                Span::from(self.card_title(&edge.to_node)),
+                label(&edge.label),
+            ])
+            .dark_gray()
+        }));
+
+        lines.extend(self.canvas.incoming(&node.id).into_iter().map(|edge| {
+            Line::from(vec![
+                Span::from(format!("{indent}  ← ")),
+                Span::from(self.card_title(&edge.from_node)),
+                label(&edge.label),
+            ])
+            .dark_gray()
+        }));
+
+        ListItem::new(Text::from(lines))
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(70), Constraint::Percentage(80))
+}
+
+#[derive(Default)]
+pub struct CanvasModal {
+    chrome: Chrome,
+}
+
+impl CanvasModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for CanvasModal {
+    type State = CanvasModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let summary = format!(
+            " {} cards · {} edges ",
+            state.canvas.nodes.len(),
+            state.canvas.edges.len()
+        );
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(" {} ", state.name))
+            .title(Line::from(summary).alignment(Alignment::Right))
+            .title_bottom(
+                Line::from(" (enter) open note · (esc) close ").alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if let Some(error) = &state.error {
+            Paragraph::new(error.as_str()).red().render(inner_area, buf);
+            return;
+        }
+
+        if state.cards.is_empty() {
+            Paragraph::new("Empty canvas")
+                .dark_gray()
+                .italic()
+                .render(inner_area, buf);
+            return;
+        }
+
+        let items: Vec<_> = state
+            .cards
+            .iter()
+            .map(|(node, depth)| state.list_item(node, *depth))
+            .collect();
+
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_style(Style::default().reversed().dark_gray())
+                .highlight_symbol(""),
+            inner_area,
+            buf,
+            &mut state.list_state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    #[test]
+    fn test_canvas_modal() {
+        let dir = std::env::temp_dir().join("basalt-canvas-modal");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Plan.md"), "# Plan\n\n## Next\n\nShip it\n").unwrap();
+        fs::write(
+            dir.join("Board.canvas"),
+            r###"{
+              "nodes": [
+                { "id": "group", "type": "group", "label": "Ideas", "x": 0, "y": 0, "width": 600, "height": 400 },
+                { "id": "text", "type": "text", "text": "## Goals\nFewer meetings\nMore notes", "x": 20, "y": 40, "width": 200, "height": 100 },
+                { "id": "plan", "type": "file", "file": "Plan.md", "subpath": "#Next", "x": 300, "y": 40, "width": 200, "height": 100 },
+                { "id": "site", "type": "link", "url": "https://obsidian.md", "x": 0, "y": 500, "width": 200, "height": 100 }
+              ],
+              "edges": [{ "id": "edge", "fromNode": "text", "toNode": "plan", "label": "leads to" }]
+            }"###,
+        )
+        .unwrap();
+
+        let vault = Vault {
+            path: dir.clone(),
+            ..Default::default()
+        };
+
+        let mut state = CanvasModalState::default();
+        update(
+            &Message::Open(Note::from(dir.join("Board.canvas").as_path())),
+            Some(&vault),
+            &mut state,
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal
+            .draw(|frame| {
+                CanvasModal::default().render(frame.area(), frame.buffer_mut(), &mut state)
+            })
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+
+        update(&Message::Down, Some(&vault), &mut state);
+        update(&Message::Down, Some(&vault), &mut state);
+
+        assert_eq!(
+            update(&Message::Select, Some(&vault), &mut state),
+            Some(AppMessage::OpenNoteAt(
+                Note::from(dir.join("Plan.md").as_path()),
+                8
+            ))
+        );
+        assert!(!state.visible);
+    }
+}
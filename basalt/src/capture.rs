@@ -0,0 +1,91 @@
+//! Quick capture of short notes into an inbox note. Every capture is appended to the inbox as a
+//! list item with a timestamp.
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, TimeZone};
+
+use crate::date_format;
+
+/// Returns the path of the inbox note in the vault. Date format specifiers in the inbox are
+/// expanded, e.g. `Inbox/%Y-%m` to `Inbox/2025-03.md`, and the `.md` extension is added when
+/// missing.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::capture;
+/// use chrono::{TimeZone, Utc};
+/// use std::path::Path;
+///
+/// let date = Utc.with_ymd_and_hms(2025, 3, 7, 9, 30, 0).unwrap();
+///
+/// assert_eq!(
+///     capture::inbox_path(Path::new("vault"), "Inbox/%Y-%m", &date),
+///     Path::new("vault/Inbox/2025-03.md")
+/// );
+/// assert_eq!(
+///     capture::inbox_path(Path::new("vault"), "Inbox/%Y.%m.%d", &date),
+///     Path::new("vault/Inbox/2025.03.07.md")
+/// );
+/// ```
+pub fn inbox_path<Tz: TimeZone>(vault_path: &Path, inbox: &str, datetime: &DateTime<Tz>) -> PathBuf
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let path = vault_path.join(date_format::expand(inbox, datetime));
+
+    match path.extension().is_some_and(|ext| ext == "md") {
+        true => path,
+        false => {
+            let mut with_extension = path.into_os_string();
+            with_extension.push(".md");
+            with_extension.into()
+        }
+    }
+}
+
+/// Returns the contents with the captured text appended as a list item, prefixed with the
+/// timestamp. Lines after the first line of the text are indented below the item.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::capture;
+///
+/// assert_eq!(
+///     capture::append("# Inbox", "09:30", "Call back"),
+///     "# Inbox\n- 09:30 Call back\n"
+/// );
+/// ```
+pub fn append(contents: &str, timestamp: &str, text: &str) -> String {
+    let separator = match contents.is_empty() || contents.ends_with('\n') {
+        true => "",
+        false => "\n",
+    };
+
+    let item = text.trim().lines().collect::<Vec<_>>().join("\n  ");
+
+    format!("{contents}{separator}- {timestamp} {item}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append() {
+        let tests = [
+            ("", "Idea", "- 12:00 Idea\n"),
+            ("- 11:00 Milk\n", "Eggs", "- 11:00 Milk\n- 12:00 Eggs\n"),
+            (
+                "# Inbox",
+                " First line\nSecond line \n",
+                "# Inbox\n- 12:00 First line\n  Second line\n",
+            ),
+        ];
+
+        tests.into_iter().for_each(|(contents, text, expected)| {
+            assert_eq!(append(contents, "12:00", text), expected, "With {text:?}");
+        });
+    }
+}
@@ -0,0 +1,179 @@
+//! Borders, padding and title alignment of the panes and modals.
+//!
+//! Every pane and modal draws its block from a [`Chrome`], which is read from the table of the
+//! pane or modal in the configuration and falls back to the settings of the `[ui]` table. Unset
+//! settings keep the look of the pane or modal.
+use ratatui::{
+    layout::Alignment,
+    style::{Color, Style},
+    widgets::{Block, BorderType, Borders, Padding},
+};
+use serde::{de, Deserialize, Deserializer};
+
+/// Border of a pane or modal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Border {
+    #[default]
+    Rounded,
+    Plain,
+    Double,
+    Thick,
+    /// No border. The title is still drawn on the top row.
+    None,
+}
+
+/// Alignment of the title of a pane or modal.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleAlignment {
+    Left,
+    Center,
+    Right,
+}
+
+impl From<TitleAlignment> for Alignment {
+    fn from(value: TitleAlignment) -> Self {
+        match value {
+            TitleAlignment::Left => Alignment::Left,
+            TitleAlignment::Center => Alignment::Center,
+            TitleAlignment::Right => Alignment::Right,
+        }
+    }
+}
+
+/// Deserializes a color name like `dark gray` or a hex color like `#1e1e2e`.
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| de::Error::custom(format!("invalid color `{value}`")))
+}
+
+/// Borders, padding and title alignment of a pane or modal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Chrome {
+    /// Border of the pane or modal. Panes use this border when they are not focused.
+    pub border: Option<Border>,
+    /// Border of the focused pane. Defaults to a thick border. Ignored when the border is `none`.
+    pub active_border: Option<Border>,
+    /// Color of the border.
+    #[serde(deserialize_with = "color")]
+    pub border_color: Option<Color>,
+    /// Columns between the border and the content on the left and right.
+    pub padding: Option<u16>,
+    pub title_alignment: Option<TitleAlignment>,
+}
+
+impl Chrome {
+    /// Returns the chrome with the unset settings taken from the defaults.
+    pub fn or(self, defaults: Chrome) -> Chrome {
+        Chrome {
+            border: self.border.or(defaults.border),
+            active_border: self.active_border.or(defaults.active_border),
+            border_color: self.border_color.or(defaults.border_color),
+            padding: self.padding.or(defaults.padding),
+            title_alignment: self.title_alignment.or(defaults.title_alignment),
+        }
+    }
+
+    /// Returns the block of the pane or modal with all borders. The border of `active` blocks is
+    /// the border of focused panes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::chrome::{Border, Chrome};
+    /// use ratatui::widgets::{Block, BorderType};
+    ///
+    /// let chrome = Chrome {
+    ///     border: Some(Border::Double),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(chrome.block(false), Block::bordered().border_type(BorderType::Double));
+    /// assert_eq!(chrome.block(true), Block::bordered().border_type(BorderType::Thick));
+    /// ```
+    pub fn block<'a>(&self, active: bool) -> Block<'a> {
+        let border = match (self.border.unwrap_or_default(), active) {
+            (Border::None, _) => Border::None,
+            (_, true) => self.active_border.unwrap_or(Border::Thick),
+            (border, false) => border,
+        };
+
+        let block = match border {
+            Border::None => Block::new(),
+            Border::Rounded => Block::bordered().border_type(BorderType::Rounded),
+            Border::Plain => Block::bordered().border_type(BorderType::Plain),
+            Border::Double => Block::bordered().border_type(BorderType::Double),
+            Border::Thick => Block::bordered().border_type(BorderType::Thick),
+        };
+
+        match self.border_color {
+            Some(color) => block.border_style(Style::new().fg(color)),
+            None => block,
+        }
+    }
+
+    /// Returns the borders, or no borders when the border is `none`. Used by collapsed panes that
+    /// leave out one of the sides.
+    pub fn borders(&self, borders: Borders) -> Borders {
+        match self.border {
+            Some(Border::None) => Borders::NONE,
+            _ => borders,
+        }
+    }
+
+    /// Returns the padding with the configured padding on the left and right.
+    pub fn padding(&self, padding: Padding) -> Padding {
+        match self.padding {
+            Some(columns) => Padding {
+                left: columns,
+                right: columns,
+                ..padding
+            },
+            None => padding,
+        }
+    }
+
+    /// Returns the configured alignment of the title, or the given default alignment.
+    pub fn title_alignment(&self, alignment: Alignment) -> Alignment {
+        self.title_alignment.map_or(alignment, Alignment::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chrome() {
+        let defaults = Chrome {
+            border: Some(Border::Plain),
+            padding: Some(2),
+            ..Default::default()
+        };
+        let chrome: Chrome = toml::from_str(indoc::indoc! {r##"
+            border = "none"
+            border_color = "#ff0000"
+            title_alignment = "center"
+        "##})
+        .unwrap();
+        let chrome = chrome.or(defaults);
+
+        assert_eq!(
+            chrome.block(true),
+            Block::new().border_style(Color::Rgb(255, 0, 0))
+        );
+        assert_eq!(chrome.borders(Borders::LEFT), Borders::NONE);
+        assert_eq!(
+            chrome.padding(Padding::uniform(1)),
+            Padding::new(2, 2, 1, 1)
+        );
+        assert_eq!(chrome.title_alignment(Alignment::Right), Alignment::Center);
+        assert_eq!(defaults.block(false), Block::bordered());
+        assert!(toml::from_str::<Chrome>("border_color = \"greyish\"").is_err());
+    }
+}
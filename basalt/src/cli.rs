@@ -0,0 +1,725 @@
+//! Command line arguments of Basalt. The arguments are parsed, and the usage, the help, the shell
+//! completions of `basalt completions <shell>` and the man page of `basalt man` are generated
+//! from the same definitions, so they stay in line with what `main` accepts.
+use std::{fmt::Write, iter::Peekable};
+
+/// Value taken by an argument.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    None,
+    /// One of the choices, which may be left out when optional.
+    Choices {
+        choices: &'static [&'static str],
+        optional: bool,
+    },
+    /// A path to a file.
+    File(&'static str),
+    /// Free text, e.g. a size, shown as is in the usage.
+    Text(&'static str),
+}
+
+/// An option, like `--print`, or a subcommand, like `man`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Arg {
+    pub name: &'static str,
+    /// Short name of an option, e.g. `-h`.
+    pub short: Option<&'static str>,
+    pub value: Value,
+    pub help: &'static str,
+    /// Option that must be given with this option. Options without one exclude each other.
+    pub requires: Option<&'static str>,
+}
+
+/// Options of the interactive and the headless mode.
+pub const OPTIONS: [Arg; 4] = [
+    Arg {
+        name: "--print",
+        short: None,
+        value: Value::Choices {
+            choices: &["raw", "ansi"],
+            optional: true,
+        },
+        help: "Print the open note to stdout on quit, as written (raw) or as rendered with colors (ansi)",
+        requires: None,
+    },
+    Arg {
+        name: "--headless",
+        short: None,
+        value: Value::File("script|-"),
+        help: "Run the script on a virtual terminal and print the screen and the state, reading the script from stdin with -",
+        requires: None,
+    },
+    Arg {
+        name: "--size",
+        short: None,
+        value: Value::Text("<width>x<height>"),
+        help: "Size of the virtual terminal in headless mode (default: 80x24)",
+        requires: Some("--headless"),
+    },
+    Arg {
+        name: "--keys-export",
+        short: None,
+        value: Value::None,
+        help: "Print the key bindings of the configuration as markdown",
+        requires: None,
+    },
+];
+
+/// Options that print information about Basalt and exit. Other arguments given with them are
+/// ignored.
+pub const INFO_OPTIONS: [Arg; 2] = [
+    Arg {
+        name: "--help",
+        short: Some("-h"),
+        value: Value::None,
+        help: "Print the usage and the options",
+        requires: None,
+    },
+    Arg {
+        name: "--version",
+        short: Some("-V"),
+        value: Value::None,
+        help: "Print the version",
+        requires: None,
+    },
+];
+
+/// Subcommands, which print something and exit.
+pub const SUBCOMMANDS: [Arg; 2] = [
+    Arg {
+        name: "completions",
+        short: None,
+        value: Value::Choices {
+            choices: &SHELLS,
+            optional: false,
+        },
+        help: "Print the completion script of the shell",
+        requires: None,
+    },
+    Arg {
+        name: "man",
+        short: None,
+        value: Value::None,
+        help: "Print the man page",
+        requires: None,
+    },
+];
+
+/// Shells with completion scripts.
+pub const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+impl Value {
+    /// Returns the value as shown in the usage, e.g. `[raw|ansi]` or `<script|->`.
+    fn usage(&self) -> Option<String> {
+        match self {
+            Value::None => None,
+            Value::Choices {
+                choices,
+                optional: true,
+            } => Some(format!("[{}]", choices.join("|"))),
+            Value::Choices { choices, .. } => Some(format!("<{}>", choices.join("|"))),
+            Value::File(name) => Some(format!("<{name}>")),
+            Value::Text(text) => Some(text.to_string()),
+        }
+    }
+}
+
+impl Arg {
+    /// Returns the argument with its value as shown in the usage, e.g. `--print [raw|ansi]`.
+    fn usage(&self) -> String {
+        match self.value.usage() {
+            Some(value) => format!("{} {value}", self.name),
+            None => self.name.to_string(),
+        }
+    }
+
+    /// Returns the names of the argument with its value as listed in the help, e.g. `-h, --help`
+    /// or `--print [raw|ansi]`.
+    fn names(&self) -> String {
+        let names = match self.short {
+            Some(short) => format!("{short}, {}", self.name),
+            None => self.name.to_string(),
+        };
+        match self.value.usage() {
+            Some(value) => format!("{names} {value}"),
+            None => names,
+        }
+    }
+
+    fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.short == Some(name)
+    }
+}
+
+/// Returns the options of the interactive and the headless mode, and the options that print
+/// information.
+fn all_options() -> impl Iterator<Item = &'static Arg> {
+    OPTIONS.iter().chain(&INFO_OPTIONS)
+}
+
+/// Returns the usage, listing the options that exclude each other and the subcommands.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::cli;
+///
+/// assert!(cli::usage().starts_with("Usage: basalt [--print [raw|ansi] | --headless"));
+/// ```
+pub fn usage() -> String {
+    let options: Vec<String> = OPTIONS
+        .iter()
+        .filter(|option| option.requires.is_none())
+        .map(|option| {
+            OPTIONS
+                .iter()
+                .filter(|arg| arg.requires == Some(option.name))
+                .fold(option.usage(), |usage, arg| {
+                    format!("{usage} [{}]", arg.usage())
+                })
+        })
+        .collect();
+
+    let mut out = format!("Usage: basalt [{}]", options.join(" | "));
+    SUBCOMMANDS
+        .iter()
+        .chain(&INFO_OPTIONS)
+        .for_each(|arg| _ = write!(out, "\n       basalt {}", arg.usage()));
+
+    out
+}
+
+/// Returns the usage followed by the options and the subcommands with their descriptions, as
+/// printed by `basalt --help`.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::cli;
+///
+/// assert!(cli::help().contains("  -h, --help"));
+/// ```
+pub fn help() -> String {
+    let sections = [
+        ("Options", all_options().collect::<Vec<_>>()),
+        ("Commands", SUBCOMMANDS.iter().collect()),
+    ];
+
+    let width = sections
+        .iter()
+        .flat_map(|(_, args)| args.iter().map(|arg| arg.names().len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut out = usage();
+    sections.iter().for_each(|(section, args)| {
+        _ = write!(out, "\n\n{section}:");
+        args.iter().for_each(|arg| {
+            _ = write!(out, "\n  {:width$}  {}", arg.names(), arg.help);
+        });
+    });
+    out.push('\n');
+
+    out
+}
+
+/// Arguments given on the command line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Args {
+    /// The subcommand with its value, e.g. `completions` with `bash`.
+    pub subcommand: Option<(&'static str, Option<String>)>,
+    /// The options with their values, in the given order.
+    pub options: Vec<(&'static str, Option<String>)>,
+}
+
+impl Args {
+    /// Returns whether the option was given.
+    pub fn has(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| *option == name)
+    }
+
+    /// Returns the value given to the option.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(option, _)| *option == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+}
+
+/// Takes the value of the argument from the arguments that follow it.
+fn take_value(
+    arg: &Arg,
+    rest: &mut Peekable<impl Iterator<Item = String>>,
+) -> Result<Option<String>, String> {
+    let value = match arg.value {
+        Value::None => return Ok(None),
+        Value::Choices {
+            choices,
+            optional: true,
+        } => rest.next_if(|value| choices.contains(&value.as_str())),
+        Value::Choices { choices, .. } => rest
+            .next()
+            .filter(|value| choices.contains(&value.as_str())),
+        Value::File(_) | Value::Text(_) => {
+            rest.next_if(|value| value == "-" || !value.starts_with('-'))
+        }
+    };
+
+    match (value, arg.value) {
+        (None, Value::Choices { optional: true, .. }) => Ok(None),
+        (None, _) => Err(format!(
+            "{} expects {}",
+            arg.name,
+            arg.value.usage().unwrap_or_default()
+        )),
+        (value, _) => Ok(value),
+    }
+}
+
+/// Parses the arguments, without the program name, as defined by [`OPTIONS`] and
+/// [`SUBCOMMANDS`]. Returns the problem when the arguments do not match the usage.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::cli;
+///
+/// let args = cli::parse(["--headless", "script", "--size", "80x24"].map(String::from)).unwrap();
+/// assert_eq!(args.value("--size"), Some("80x24"));
+///
+/// assert_eq!(
+///     cli::parse(["--size", "80x24"].map(String::from)),
+///     Err("--size can only be given with --headless".into())
+/// );
+/// ```
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut rest = args.into_iter().peekable();
+    let mut args = Args::default();
+
+    if let Some(subcommand) = rest
+        .peek()
+        .and_then(|name| SUBCOMMANDS.iter().find(|arg| arg.name == name))
+    {
+        rest.next();
+        args.subcommand = Some((subcommand.name, take_value(subcommand, &mut rest)?));
+        return match rest.next() {
+            Some(name) => Err(format!("Unexpected argument {name}")),
+            None => Ok(args),
+        };
+    }
+
+    while let Some(name) = rest.next() {
+        if let Some(info) = INFO_OPTIONS.iter().find(|option| option.is_named(&name)) {
+            return Ok(Args {
+                subcommand: None,
+                options: vec![(info.name, None)],
+            });
+        }
+
+        let option = OPTIONS
+            .iter()
+            .find(|option| option.name == name)
+            .ok_or(format!("Unexpected argument {name}"))?;
+
+        if args.has(option.name) {
+            return Err(format!("{name} is given more than once"));
+        }
+
+        let value = take_value(option, &mut rest)?;
+        args.options.push((option.name, value));
+    }
+
+    let given: Vec<&Arg> = args
+        .options
+        .iter()
+        .filter_map(|(name, _)| OPTIONS.iter().find(|option| option.name == *name))
+        .collect();
+
+    let exclusive: Vec<&str> = given
+        .iter()
+        .filter(|option| option.requires.is_none())
+        .map(|option| option.name)
+        .collect();
+    if let [first, second, ..] = exclusive[..] {
+        return Err(format!("{second} cannot be given with {first}"));
+    }
+
+    if let Some((option, required)) = given.iter().find_map(|option| {
+        let required = option.requires.filter(|required| !args.has(required))?;
+        Some((option.name, required))
+    }) {
+        return Err(format!("{option} can only be given with {required}"));
+    }
+
+    Ok(args)
+}
+
+/// Returns the completion script of the shell, or `None` when the shell is not supported.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::cli;
+///
+/// assert!(cli::completions("fish").unwrap().contains("-l keys-export"));
+/// assert_eq!(cli::completions("nu"), None);
+/// ```
+pub fn completions(shell: &str) -> Option<String> {
+    match shell {
+        "bash" => Some(bash()),
+        "zsh" => Some(zsh()),
+        "fish" => Some(fish()),
+        _ => None,
+    }
+}
+
+fn words<'a>(args: impl IntoIterator<Item = &'a Arg>) -> String {
+    args.into_iter()
+        .flat_map(|arg| arg.short.into_iter().chain([arg.name]))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bash() -> String {
+    let mut out = String::from(
+        "_basalt() {\n    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n    local prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n\n    case \"$prev\" in\n",
+    );
+
+    OPTIONS.iter().chain(&SUBCOMMANDS).for_each(|arg| {
+        let reply = match arg.value {
+            Value::None => return,
+            Value::Choices { choices, .. } => {
+                format!(
+                    "COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+                    choices.join(" ")
+                )
+            }
+            Value::File(_) => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+            Value::Text(_) => "COMPREPLY=()".to_string(),
+        };
+        _ = writeln!(
+            out,
+            "        {})\n            {reply}\n            return\n            ;;",
+            arg.name
+        );
+    });
+
+    _ = write!(
+        out,
+        "    esac\n\n    if [ \"$COMP_CWORD\" -eq 1 ]; then\n        COMPREPLY=($(compgen -W \"{} {}\" -- \"$cur\"))\n    else\n        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n    fi\n}}\n\ncomplete -F _basalt basalt\n",
+        words(all_options()),
+        words(&SUBCOMMANDS),
+        words(all_options()),
+    );
+
+    out
+}
+
+/// Escapes the help text for a zsh `_arguments` spec or `_describe` item in single quotes.
+fn zsh_escape(help: &str) -> String {
+    help.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn zsh() -> String {
+    let mut out = String::from("#compdef basalt\n\n");
+
+    out.push_str("case $words[2] in\n");
+    SUBCOMMANDS.iter().for_each(|arg| {
+        let spec = match arg.value {
+            Value::Choices { choices, .. } => {
+                format!("_arguments '2:{}:({})'", arg.name, choices.join(" "))
+            }
+            _ => "_message 'no more arguments'".to_string(),
+        };
+        _ = writeln!(
+            out,
+            "    {})\n        {spec}\n        return\n        ;;",
+            arg.name
+        );
+    });
+    out.push_str("esac\n\n");
+
+    out.push_str("local -a subcommands\nsubcommands=(\n");
+    SUBCOMMANDS.iter().for_each(|arg| {
+        _ = writeln!(out, "    '{}:{}'", arg.name, zsh_escape(arg.help));
+    });
+    out.push_str(")\n\n_arguments \\\n");
+
+    all_options().for_each(|arg| {
+        let name = arg.name.trim_start_matches("--");
+        let value = match arg.value {
+            Value::None => String::new(),
+            Value::Choices { choices, optional } => format!(
+                "{}{name}:({})",
+                if optional { "::" } else { ":" },
+                choices.join(" ")
+            ),
+            Value::File(_) => format!(":{name}:_files"),
+            Value::Text(_) => format!(":{name}: "),
+        };
+        let help = zsh_escape(arg.help);
+        _ = match arg.short {
+            Some(short) => writeln!(
+                out,
+                "    '({short} {})'{{{short},{}}}'[{help}]{value}' \\",
+                arg.name, arg.name
+            ),
+            None => writeln!(out, "    '{}[{help}]{value}' \\", arg.name),
+        };
+    });
+    out.push_str("    '1: :{_describe command subcommands}'\n");
+
+    out
+}
+
+fn fish() -> String {
+    let mut out = String::from("complete -c basalt -f\n");
+
+    SUBCOMMANDS.iter().for_each(|arg| {
+        _ = writeln!(
+            out,
+            "complete -c basalt -n __fish_use_subcommand -a {} -d '{}'",
+            arg.name,
+            arg.help.replace('\'', "\\'")
+        );
+        if let Value::Choices { choices, .. } = arg.value {
+            _ = writeln!(
+                out,
+                "complete -c basalt -n '__fish_seen_subcommand_from {}' -a '{}'",
+                arg.name,
+                choices.join(" ")
+            );
+        }
+    });
+
+    all_options().for_each(|arg| {
+        let short = arg
+            .short
+            .map(|short| format!(" -s {}", short.trim_start_matches('-')))
+            .unwrap_or_default();
+        let value = match arg.value {
+            Value::None => String::new(),
+            Value::Choices { choices, optional } => format!(
+                " {} -a '{}'",
+                if optional { "-f" } else { "-x" },
+                choices.join(" ")
+            ),
+            Value::File(_) => " -r -F".to_string(),
+            Value::Text(_) => " -x".to_string(),
+        };
+        _ = writeln!(
+            out,
+            "complete -c basalt{short} -l {}{value} -d '{}'",
+            arg.name.trim_start_matches("--"),
+            arg.help.replace('\'', "\\'")
+        );
+    });
+
+    out
+}
+
+/// Escapes the text for roff, so that dashes are printed as is and lines starting with a dot or
+/// an apostrophe are not read as requests.
+fn roff_escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    match text.starts_with(['.', '\'']) {
+        true => format!("\\&{text}"),
+        false => text,
+    }
+}
+
+/// Returns the man page of the version in roff.
+pub fn man(version: &str) -> String {
+    let mut out = format!(
+        ".TH BASALT 1 \"\" \"basalt {}\" \"User Commands\"\n",
+        roff_escape(version)
+    );
+
+    out.push_str(".SH NAME\nbasalt \\- TUI application to manage Obsidian notes\n");
+
+    out.push_str(".SH SYNOPSIS\n");
+    usage()
+        .lines()
+        .map(|line| line.trim().trim_start_matches("Usage: "))
+        .for_each(|line| _ = writeln!(out, ".br\n{}", roff_escape(line)));
+
+    out.push_str(concat!(
+        ".SH DESCRIPTION\n",
+        "Basalt opens the Obsidian vaults known to Obsidian in the terminal, to browse, read and ",
+        "edit notes. Press ? inside Basalt to list the key mappings and commands.\n",
+    ));
+
+    let sections = [
+        ("OPTIONS", all_options().collect::<Vec<_>>()),
+        ("COMMANDS", SUBCOMMANDS.iter().collect()),
+    ];
+    for (section, args) in sections {
+        _ = writeln!(out, ".SH {section}");
+        args.iter().for_each(|arg| {
+            let names = arg
+                .short
+                .into_iter()
+                .chain([arg.name])
+                .map(|name| format!("\\fB{}\\fR", roff_escape(name)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let value = arg
+                .value
+                .usage()
+                .map(|value| format!(" \\fI{}\\fR", roff_escape(&value)))
+                .unwrap_or_default();
+            _ = writeln!(out, ".TP\n{names}{value}\n{}", roff_escape(arg.help));
+        });
+    }
+
+    out.push_str(concat!(
+        ".SH FILES\n",
+        ".TP\n\\fI~/.basalt.toml\\fR, \\fI$XDG_CONFIG_HOME/basalt/config.toml\\fR\n",
+        "The configuration, read from the first file found.\n",
+    ));
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+
+    use super::*;
+
+    #[test]
+    fn test_completions() {
+        SHELLS.iter().for_each(|shell| {
+            let script = completions(shell).unwrap();
+            all_options()
+                .chain(&SUBCOMMANDS)
+                .for_each(|arg| assert!(script.contains(arg.name.trim_start_matches("--"))));
+        });
+
+        assert_snapshot!(bash());
+        assert_snapshot!(zsh());
+        assert_snapshot!(fish());
+    }
+
+    /// Returns the argument with a valid value, e.g. `["--print", "raw"]`.
+    fn example(arg: &Arg) -> Vec<String> {
+        let value = match arg.value {
+            Value::None => None,
+            Value::Choices { choices, .. } => Some(choices[0]),
+            Value::File(_) => Some("script.txt"),
+            Value::Text(_) => Some("80x24"),
+        };
+        [arg.name]
+            .into_iter()
+            .chain(value)
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let scripts: Vec<_> = SHELLS
+            .iter()
+            .filter_map(|shell| completions(shell))
+            .collect();
+
+        // Every argument that parses is completed.
+        all_options().chain(&SUBCOMMANDS).for_each(|arg| {
+            let required = OPTIONS
+                .iter()
+                .filter(|option| Some(option.name) == arg.requires);
+            let args = required.flat_map(example).chain(example(arg));
+            let parsed = parse(args).unwrap();
+
+            parsed
+                .subcommand
+                .iter()
+                .map(|(name, _)| name)
+                .chain(parsed.options.iter().map(|(name, _)| name))
+                .for_each(|name| {
+                    let name = name.trim_start_matches("--");
+                    assert!(scripts.iter().all(|script| script.contains(name)), "{name}");
+                });
+        });
+
+        let parse = |args: &str| parse(args.split_whitespace().map(String::from));
+
+        assert_eq!(parse(""), Ok(Args::default()));
+        assert_eq!(
+            parse("--size 80x24 --headless -"),
+            Ok(Args {
+                subcommand: None,
+                options: vec![
+                    ("--size", Some("80x24".into())),
+                    ("--headless", Some("-".into()))
+                ],
+            })
+        );
+        assert_eq!(parse("--print").map(|args| args.has("--print")), Ok(true));
+        assert_eq!(
+            parse("--print html"),
+            Err("Unexpected argument html".into())
+        );
+        assert_eq!(
+            parse("--headless"),
+            Err("--headless expects <script|->".into())
+        );
+        assert_eq!(
+            parse("completions nu"),
+            Err("completions expects <bash|zsh|fish>".into())
+        );
+        assert_eq!(
+            parse("man --print"),
+            Err("Unexpected argument --print".into())
+        );
+        assert_eq!(parse("--vault"), Err("Unexpected argument --vault".into()));
+        assert_eq!(
+            parse("--print --print"),
+            Err("--print is given more than once".into())
+        );
+        assert_eq!(
+            parse("--keys-export --print ansi"),
+            Err("--print cannot be given with --keys-export".into())
+        );
+
+        // Information options are given alone, the other arguments are ignored.
+        assert_eq!(
+            parse("--print ansi -h --vault"),
+            Ok(Args {
+                subcommand: None,
+                options: vec![("--help", None)],
+            })
+        );
+        assert_eq!(parse("-V").map(|args| args.has("--version")), Ok(true));
+        assert_eq!(
+            parse("--version").map(|args| args.has("--version")),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_usage() {
+        assert_eq!(
+            usage(),
+            "Usage: basalt [--print [raw|ansi] | --headless <script|-> [--size <width>x<height>] | --keys-export]
+       basalt completions <bash|zsh|fish>
+       basalt man
+       basalt --help
+       basalt --version"
+        );
+    }
+
+    #[test]
+    fn test_help() {
+        assert_snapshot!(help());
+    }
+
+    #[test]
+    fn test_man() {
+        assert_snapshot!(man("1.2.3"));
+    }
+}
@@ -0,0 +1,93 @@
+//! Reads and writes the system clipboard with the platform clipboard tools: `wl-paste` and
+//! `wl-copy` on Wayland, `xclip` or `xsel` on X11 and `osascript`, `pbpaste` or `pbcopy` on macOS.
+use std::{
+    io::Write,
+    process::{self, Stdio},
+};
+
+/// Contents of the clipboard, as rich text when it is available.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Clipboard {
+    Html(String),
+    Text(String),
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = process::Command::new(program).args(args).output().ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+        .filter(|contents| !contents.is_empty())
+}
+
+/// Decodes the clipboard HTML returned by AppleScript, written as `«data HTML3C68313E...»`.
+fn decode_applescript_data(data: &str) -> Option<String> {
+    let hex = data.trim().strip_prefix("«data HTML")?.strip_suffix('»')?;
+
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(String::from_utf8_lossy(&bytes).to_string())
+}
+
+fn read_html() -> Option<String> {
+    run("wl-paste", &["--no-newline", "--type", "text/html"])
+        .or_else(|| {
+            run(
+                "xclip",
+                &["-selection", "clipboard", "-target", "text/html", "-out"],
+            )
+        })
+        .or_else(|| {
+            run("osascript", &["-e", "the clipboard as «class HTML»"])
+                .and_then(|data| decode_applescript_data(&data))
+        })
+}
+
+fn read_text() -> Option<String> {
+    run("wl-paste", &["--no-newline"])
+        .or_else(|| run("xclip", &["-selection", "clipboard", "-out"]))
+        .or_else(|| run("xsel", &["--clipboard", "--output"]))
+        .or_else(|| run("pbpaste", &[]))
+}
+
+/// Reads the clipboard, preferring HTML over plain text. Returns `None` when the clipboard is
+/// empty or no clipboard tool is available.
+pub fn read() -> Option<Clipboard> {
+    read_html()
+        .map(Clipboard::Html)
+        .or_else(|| read_text().map(Clipboard::Text))
+}
+
+/// Runs the program with the text as its input. Returns `false` when the program is missing or
+/// fails.
+fn pipe(program: &str, args: &[&str], text: &str) -> bool {
+    let Ok(mut child) = process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+
+    let written = child
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+
+    child.wait().is_ok_and(|status| status.success()) && written
+}
+
+/// Writes the text to the clipboard. Returns `false` when no clipboard tool is available.
+pub fn write(text: &str) -> bool {
+    pipe("wl-copy", &[], text)
+        || pipe("xclip", &["-selection", "clipboard", "-in"], text)
+        || pipe("xsel", &["--clipboard", "--input"], text)
+        || pipe("pbcopy", &[], text)
+}
@@ -0,0 +1,231 @@
+//! Color capabilities of the terminal and the fallback palettes for terminals without true color.
+//!
+//! RGB colors are drawn as is only on terminals with true color support. Other terminals get the
+//! nearest color of the xterm 256-color palette or of the 16 basic colors, so that colors do not
+//! turn into unrelated colors or escape sequences the terminal does not understand.
+use std::env;
+
+use ratatui::{buffer::Buffer, style::Color};
+use serde::Deserialize;
+
+/// Intensities of the red, green and blue channels of the 6×6×6 color cube of the 256-color
+/// palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 basic colors with their xterm RGB values.
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Colors the terminal can draw.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum ColorSupport {
+    /// Detected from the `COLORTERM` and `TERM` environment variables.
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    /// 24-bit RGB colors.
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    /// The xterm 256-color palette.
+    #[serde(rename = "256")]
+    Palette256,
+    /// The 16 basic colors.
+    #[serde(rename = "16")]
+    Basic,
+}
+
+impl ColorSupport {
+    /// Returns the color support of a terminal with the given `COLORTERM` and `TERM` environment
+    /// variables.
+    fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        let term = term.unwrap_or_default();
+
+        match colorterm {
+            Some("truecolor" | "24bit") => ColorSupport::TrueColor,
+            _ if term.contains("direct") => ColorSupport::TrueColor,
+            _ if term.contains("256") => ColorSupport::Palette256,
+            _ => ColorSupport::Basic,
+        }
+    }
+
+    /// Resolves automatic detection to the color support of the terminal, which is read from the
+    /// environment.
+    pub fn resolve(self) -> Self {
+        match self {
+            ColorSupport::Auto => Self::from_env(
+                env::var("COLORTERM").ok().as_deref(),
+                env::var("TERM").ok().as_deref(),
+            ),
+            support => support,
+        }
+    }
+
+    /// Returns the nearest color the terminal can draw.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::color_support::ColorSupport;
+    /// use ratatui::style::Color;
+    ///
+    /// assert_eq!(ColorSupport::TrueColor.color(Color::Rgb(255, 135, 0)), Color::Rgb(255, 135, 0));
+    /// assert_eq!(ColorSupport::Palette256.color(Color::Rgb(255, 135, 0)), Color::Indexed(208));
+    /// assert_eq!(ColorSupport::Basic.color(Color::Rgb(255, 135, 0)), Color::Yellow);
+    /// ```
+    pub fn color(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorSupport::Palette256, Color::Rgb(r, g, b)) => {
+                Color::Indexed(palette_index(r, g, b))
+            }
+            (ColorSupport::Basic, Color::Rgb(r, g, b)) => basic_color((r, g, b)),
+            (ColorSupport::Basic, Color::Indexed(index)) if index >= 16 => {
+                basic_color(palette_rgb(index))
+            }
+            (ColorSupport::Basic, Color::Indexed(index)) => BASIC_COLORS[usize::from(index)].0,
+            (_, color) => color,
+        }
+    }
+}
+
+/// Returns the squared distance between two RGB colors.
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    [(r1, r2), (g1, g2), (b1, b2)]
+        .into_iter()
+        .map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+        .sum()
+}
+
+/// Returns the index of the nearest level of the color cube.
+fn cube_level(value: u8) -> u8 {
+    (0..6)
+        .min_by_key(|&i| CUBE_LEVELS[usize::from(i)].abs_diff(value))
+        .unwrap_or_default()
+}
+
+/// Returns the index of the nearest color in the 256-color palette, either in the color cube or
+/// in the grayscale ramp.
+fn palette_index(r: u8, g: u8, b: u8) -> u8 {
+    let (ri, gi, bi) = (cube_level(r), cube_level(g), cube_level(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    match distance((r, g, b), palette_rgb(gray)) < distance((r, g, b), palette_rgb(cube)) {
+        true => gray,
+        false => cube,
+    }
+}
+
+/// Returns the RGB value of a color of the 256-color palette.
+fn palette_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..16 => BASIC_COLORS[usize::from(index)].1,
+        16..232 => {
+            let index = index - 16;
+            let level = |i: u8| CUBE_LEVELS[usize::from(i)];
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let value = 8 + 10 * (index - 232);
+            (value, value, value)
+        }
+    }
+}
+
+/// Returns the nearest of the 16 basic colors.
+fn basic_color(rgb: (u8, u8, u8)) -> Color {
+    BASIC_COLORS
+        .iter()
+        .min_by_key(|(_, basic)| distance(rgb, *basic))
+        .map(|(color, _)| *color)
+        .unwrap_or_default()
+}
+
+/// Maps the colors of the rendered buffer to the colors the terminal can draw.
+pub fn apply(buf: &mut Buffer, support: ColorSupport) {
+    if matches!(support, ColorSupport::TrueColor | ColorSupport::Auto) {
+        return;
+    }
+
+    buf.content.iter_mut().for_each(|cell| {
+        cell.fg = support.color(cell.fg);
+        cell.bg = support.color(cell.bg);
+        cell.underline_color = support.color(cell.underline_color);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_env() {
+        let tests = [
+            (
+                Some("truecolor"),
+                Some("xterm-256color"),
+                ColorSupport::TrueColor,
+            ),
+            (Some("24bit"), None, ColorSupport::TrueColor),
+            (None, Some("xterm-direct"), ColorSupport::TrueColor),
+            (None, Some("screen-256color"), ColorSupport::Palette256),
+            (None, Some("xterm"), ColorSupport::Basic),
+            (None, Some("linux"), ColorSupport::Basic),
+            (None, None, ColorSupport::Basic),
+        ];
+
+        tests.into_iter().for_each(|(colorterm, term, expected)| {
+            assert_eq!(
+                ColorSupport::from_env(colorterm, term),
+                expected,
+                "With COLORTERM {colorterm:?} and TERM {term:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_palette() {
+        let tests = [
+            (Color::Rgb(0, 0, 0), Color::Indexed(16), Color::Black),
+            (
+                Color::Rgb(128, 128, 128),
+                Color::Indexed(244),
+                Color::DarkGray,
+            ),
+            (
+                Color::Rgb(95, 135, 255),
+                Color::Indexed(69),
+                Color::LightBlue,
+            ),
+            (Color::Indexed(196), Color::Indexed(196), Color::LightRed),
+            (Color::Indexed(3), Color::Indexed(3), Color::Yellow),
+            (Color::Magenta, Color::Magenta, Color::Magenta),
+        ];
+
+        tests.into_iter().for_each(|(color, palette, basic)| {
+            assert_eq!(
+                ColorSupport::Palette256.color(color),
+                palette,
+                "With {color}"
+            );
+            assert_eq!(ColorSupport::Basic.color(color), basic, "With {color}");
+        });
+    }
+}
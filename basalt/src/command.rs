@@ -1,16 +1,28 @@
 use ratatui::{
+    backend::Backend,
     crossterm::{
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
         ExecutableCommand,
     },
-    DefaultTerminal,
+    Terminal,
 };
 use serde::{Deserialize, Deserializer};
-use std::{io::stdout, process};
+use std::{
+    fmt,
+    io::stdout,
+    path::Path,
+    process::{self, Stdio},
+    str::FromStr,
+    time::Instant,
+};
 
 use crate::{
-    app::{Message, ScrollAmount},
-    explorer, help_modal, note_editor, outline, splash_modal, vault_selector_modal,
+    app::{ActivePane, Message, ScrollAmount},
+    attachments_modal, canvas_modal, diagnostics_modal, duplicates_modal, error_log_modal,
+    explorer, focus_timer, help_modal, jobs_modal, note_diff_modal, note_editor,
+    note_history_modal, outgoing_links_modal, outline,
+    replace_modal::Scope,
+    splash_modal, tag_browser_modal, vault_replace_modal, vault_selector_modal, vault_stats_modal,
 };
 
 trait ReplaceVar {
@@ -26,10 +38,42 @@ impl ReplaceVar for String {
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum Command {
     Quit,
+    QuitAndPrint,
+    SaveAll,
+    QuickCapture,
+    QuickSwitcher,
+    HistoryBack,
+    HistoryForward,
+    ArchiveNote,
+    Publish,
+    NoteInsertFromUrl,
+    NoteSearchReplace,
+    VaultSearchReplace,
+    TagRename,
+    OpenWith,
+    CopyLinkToNote,
+    CopyLinkToHeading,
+    FocusTimerStart,
+    CycleCountScope,
+    FocusTimerStop,
+    PaneGrow,
+    PaneShrink,
+    ToggleZenMode,
+    FocusExplorer,
+    FocusEditor,
+    FocusOutline,
+    KeysDebug,
+    KeysExport,
+    ErrorLogModalToggle,
+    MacroRecord,
+    MacroPlay,
 
     SplashUp,
     SplashDown,
     SplashOpen,
+    SplashPin,
+    SplashHide,
+    SplashShowHidden,
 
     ExplorerUp,
     ExplorerDown,
@@ -37,21 +81,36 @@ pub(crate) enum Command {
     ExplorerSort,
     ExplorerToggle,
     ExplorerToggleOutline,
+    ExplorerDuplicateNote,
+    ExplorerSaveNoteAsTemplate,
+    ExplorerResolveConflict,
     ExplorerSwitchPaneNext,
     ExplorerSwitchPanePrevious,
     ExplorerScrollUpOne,
     ExplorerScrollDownOne,
     ExplorerScrollUpHalfPage,
     ExplorerScrollDownHalfPage,
+    ExplorerGotoTop,
+    ExplorerGotoBottom,
+    ExplorerExpandAll,
+    ExplorerCollapseAll,
+    ExplorerExpandSubtree,
+    ExplorerCollapseSubtree,
+    ExplorerShowPath,
 
     OutlineUp,
     OutlineDown,
     OutlineSelect,
     OutlineExpand,
+    OutlineCycleDepth,
     OutlineToggle,
     OutlineToggleExplorer,
     OutlineSwitchPaneNext,
     OutlineSwitchPanePrevious,
+    OutlineGotoTop,
+    OutlineGotoBottom,
+    OutlineShowHeading,
+    OutlineRenameHeading,
 
     HelpModalScrollUpOne,
     HelpModalScrollDownOne,
@@ -59,6 +118,9 @@ pub(crate) enum Command {
     HelpModalScrollDownHalfPage,
     HelpModalToggle,
     HelpModalClose,
+    HelpModalSearch,
+    HelpModalSearchNext,
+    HelpModalSearchPrevious,
 
     NoteEditorScrollUpOne,
     NoteEditorScrollDownOne,
@@ -70,6 +132,11 @@ pub(crate) enum Command {
     NoteEditorToggleOutline,
     NoteEditorCursorUp,
     NoteEditorCursorDown,
+    NoteEditorGotoTop,
+    NoteEditorGotoBottom,
+    NoteEditorNextHeading,
+    NoteEditorPreviousHeading,
+    NoteEditorGotoLastEdit,
 
     // # Experimental editor
     NoteEditorExperimentalCursorWordForward,
@@ -77,27 +144,159 @@ pub(crate) enum Command {
     NoteEditorExperimentalSetEditMode,
     NoteEditorExperimentalSetReadMode,
     NoteEditorExperimentalSave,
+    NoteEditorExperimentalPasteAsMarkdown,
+    NoteEditorExperimentalMoveNodeUp,
+    NoteEditorExperimentalMoveNodeDown,
+    NoteEditorExperimentalYankNode,
+    NoteEditorExperimentalDeleteNode,
+    NoteEditorExperimentalRegisters,
+    NoteEditorExperimentalPasteNode,
+    NoteEditorExperimentalExtract,
+    NoteEditorExperimentalFixLink,
     NoteEditorExperimentalExitMode,
     NoteEditorExperimentalCursorLeft,
     NoteEditorExperimentalCursorRight,
 
     VaultSelectorModalUp,
     VaultSelectorModalDown,
+    VaultSelectorModalGotoTop,
+    VaultSelectorModalGotoBottom,
     VaultSelectorModalClose,
     VaultSelectorModalOpen,
     VaultSelectorModalToggle,
+    VaultSelectorModalPin,
+    VaultSelectorModalHide,
+    VaultSelectorModalShowHidden,
+
+    DiagnosticsModalToggle,
+    DiagnosticsModalClose,
+    DiagnosticsModalUp,
+    DiagnosticsModalDown,
+    DiagnosticsModalOpen,
+    DiagnosticsModalCreateNote,
+
+    AttachmentsModalToggle,
+    AttachmentsModalClose,
+    AttachmentsModalUp,
+    AttachmentsModalDown,
+    AttachmentsModalMark,
+    AttachmentsModalMarkAll,
+    AttachmentsModalOpen,
+    AttachmentsModalDelete,
+    AttachmentsModalMove,
+
+    DuplicatesModalToggle,
+    DuplicatesModalClose,
+    DuplicatesModalUp,
+    DuplicatesModalDown,
+    DuplicatesModalScrollUpHalfPage,
+    DuplicatesModalScrollDownHalfPage,
+    DuplicatesModalSwap,
+    DuplicatesModalDelete,
+    DuplicatesModalReplace,
+    DuplicatesModalMerge,
+    VaultReplaceModalClose,
+    VaultReplaceModalUp,
+    VaultReplaceModalDown,
+    VaultReplaceModalToggle,
+    VaultReplaceModalToggleBackup,
+    VaultReplaceModalApply,
+
+    NoteDiff,
+    NoteDiffModalClose,
+    NoteDiffModalToggleSource,
+    NoteDiffModalScrollUpOne,
+    NoteDiffModalScrollDownOne,
+    NoteDiffModalScrollUpHalfPage,
+    NoteDiffModalScrollDownHalfPage,
+    NoteHistory,
+    NoteHistoryModalClose,
+    NoteHistoryModalUp,
+    NoteHistoryModalDown,
+    NoteHistoryModalScrollUpHalfPage,
+    NoteHistoryModalScrollDownHalfPage,
+    NoteHistoryModalRestore,
+
+    VaultStatsModalToggle,
+    VaultStatsModalClose,
+    VaultStatsModalScrollUpOne,
+    VaultStatsModalScrollDownOne,
+    VaultStatsModalScrollUpHalfPage,
+    VaultStatsModalScrollDownHalfPage,
+
+    CanvasModalClose,
+    CanvasModalUp,
+    CanvasModalDown,
+    CanvasModalOpen,
+
+    TagBrowserModalToggle,
+    TagBrowserModalClose,
+    TagBrowserModalUp,
+    TagBrowserModalDown,
+    TagBrowserModalExpand,
+    TagBrowserModalCollapse,
+    TagBrowserModalOpen,
+    TagBrowserModalBack,
+
+    OutgoingLinksModalToggle,
+    OutgoingLinksModalClose,
+    OutgoingLinksModalUp,
+    OutgoingLinksModalDown,
+    OutgoingLinksModalOpen,
+    OutgoingLinksModalJump,
+
+    JobsModalToggle,
+    JobsModalClose,
+    JobsModalUp,
+    JobsModalDown,
+    JobsModalKill,
 
     Exec(String),
     Spawn(String),
+    /// Asks for arguments before running the command like `Exec`.
+    ExecPrompt(String),
+    Macro(String),
 }
 
 fn str_to_command(s: &str) -> Option<Command> {
     match s {
         "quit" => Some(Command::Quit),
+        "quit_and_print" => Some(Command::QuitAndPrint),
+        "save_all" => Some(Command::SaveAll),
+        "quick_capture" => Some(Command::QuickCapture),
+        "quick_switcher" => Some(Command::QuickSwitcher),
+        "history_back" => Some(Command::HistoryBack),
+        "history_forward" => Some(Command::HistoryForward),
+        "archive_note" => Some(Command::ArchiveNote),
+        "publish" => Some(Command::Publish),
+        "note_insert_from_url" => Some(Command::NoteInsertFromUrl),
+        "note_search_replace" => Some(Command::NoteSearchReplace),
+        "vault_search_replace" => Some(Command::VaultSearchReplace),
+        "tag_rename" => Some(Command::TagRename),
+        "open_with" => Some(Command::OpenWith),
+        "copy_link_to_note" => Some(Command::CopyLinkToNote),
+        "copy_link_to_heading" => Some(Command::CopyLinkToHeading),
+        "focus_timer_start" => Some(Command::FocusTimerStart),
+        "cycle_count_scope" => Some(Command::CycleCountScope),
+        "focus_timer_stop" => Some(Command::FocusTimerStop),
+        "pane_grow" => Some(Command::PaneGrow),
+        "pane_shrink" => Some(Command::PaneShrink),
+        "toggle_zen_mode" => Some(Command::ToggleZenMode),
+        "focus_explorer" => Some(Command::FocusExplorer),
+        "focus_editor" => Some(Command::FocusEditor),
+        "focus_outline" => Some(Command::FocusOutline),
+        "keys_debug" => Some(Command::KeysDebug),
+        "keys_export" => Some(Command::KeysExport),
+        "error_log_modal_toggle" => Some(Command::ErrorLogModalToggle),
+        "macro_record" => Some(Command::MacroRecord),
+        "macro_play" => Some(Command::MacroPlay),
 
         "splash_up" => Some(Command::SplashUp),
         "splash_down" => Some(Command::SplashDown),
         "splash_open" => Some(Command::SplashOpen),
+        "splash_pin" => Some(Command::SplashPin),
+        "splash_hide" => Some(Command::SplashHide),
+        "splash_show_hidden" => Some(Command::SplashShowHidden),
 
         "explorer_up" => Some(Command::ExplorerUp),
         "explorer_down" => Some(Command::ExplorerDown),
@@ -105,21 +304,36 @@ fn str_to_command(s: &str) -> Option<Command> {
         "explorer_sort" => Some(Command::ExplorerSort),
         "explorer_toggle" => Some(Command::ExplorerToggle),
         "explorer_toggle_outline" => Some(Command::ExplorerToggleOutline),
+        "explorer_duplicate_note" => Some(Command::ExplorerDuplicateNote),
+        "explorer_save_note_as_template" => Some(Command::ExplorerSaveNoteAsTemplate),
+        "explorer_resolve_conflict" => Some(Command::ExplorerResolveConflict),
         "explorer_switch_pane_next" => Some(Command::ExplorerSwitchPaneNext),
         "explorer_switch_pane_previous" => Some(Command::ExplorerSwitchPanePrevious),
         "explorer_scroll_up_one" => Some(Command::ExplorerScrollUpOne),
         "explorer_scroll_down_one" => Some(Command::ExplorerScrollDownOne),
         "explorer_scroll_up_half_page" => Some(Command::ExplorerScrollUpHalfPage),
         "explorer_scroll_down_half_page" => Some(Command::ExplorerScrollDownHalfPage),
+        "explorer_goto_top" => Some(Command::ExplorerGotoTop),
+        "explorer_goto_bottom" => Some(Command::ExplorerGotoBottom),
+        "explorer_expand_all" => Some(Command::ExplorerExpandAll),
+        "explorer_collapse_all" => Some(Command::ExplorerCollapseAll),
+        "explorer_expand_subtree" => Some(Command::ExplorerExpandSubtree),
+        "explorer_collapse_subtree" => Some(Command::ExplorerCollapseSubtree),
+        "explorer_show_path" => Some(Command::ExplorerShowPath),
 
         "outline_up" => Some(Command::OutlineUp),
         "outline_down" => Some(Command::OutlineDown),
         "outline_select" => Some(Command::OutlineSelect),
         "outline_expand" => Some(Command::OutlineExpand),
+        "outline_cycle_depth" => Some(Command::OutlineCycleDepth),
         "outline_toggle" => Some(Command::OutlineToggle),
         "outline_toggle_explorer" => Some(Command::OutlineToggleExplorer),
         "outline_switch_pane_next" => Some(Command::OutlineSwitchPaneNext),
         "outline_switch_pane_previous" => Some(Command::OutlineSwitchPanePrevious),
+        "outline_goto_top" => Some(Command::OutlineGotoTop),
+        "outline_goto_bottom" => Some(Command::OutlineGotoBottom),
+        "outline_show_heading" => Some(Command::OutlineShowHeading),
+        "outline_rename_heading" => Some(Command::OutlineRenameHeading),
 
         "help_modal_scroll_up_one" => Some(Command::HelpModalScrollUpOne),
         "help_modal_scroll_down_one" => Some(Command::HelpModalScrollDownOne),
@@ -127,6 +341,9 @@ fn str_to_command(s: &str) -> Option<Command> {
         "help_modal_scroll_down_half_page" => Some(Command::HelpModalScrollDownHalfPage),
         "help_modal_toggle" => Some(Command::HelpModalToggle),
         "help_modal_close" => Some(Command::HelpModalClose),
+        "help_modal_search" => Some(Command::HelpModalSearch),
+        "help_modal_search_next" => Some(Command::HelpModalSearchNext),
+        "help_modal_search_previous" => Some(Command::HelpModalSearchPrevious),
 
         "note_editor_scroll_up_one" => Some(Command::NoteEditorScrollUpOne),
         "note_editor_scroll_down_one" => Some(Command::NoteEditorScrollDownOne),
@@ -138,6 +355,11 @@ fn str_to_command(s: &str) -> Option<Command> {
         "note_editor_toggle_outline" => Some(Command::NoteEditorToggleOutline),
         "note_editor_cursor_up" => Some(Command::NoteEditorCursorUp),
         "note_editor_cursor_down" => Some(Command::NoteEditorCursorDown),
+        "note_editor_goto_top" => Some(Command::NoteEditorGotoTop),
+        "note_editor_goto_bottom" => Some(Command::NoteEditorGotoBottom),
+        "note_editor_next_heading" => Some(Command::NoteEditorNextHeading),
+        "note_editor_previous_heading" => Some(Command::NoteEditorPreviousHeading),
+        "note_editor_goto_last_edit" => Some(Command::NoteEditorGotoLastEdit),
 
         "note_editor_experimental_cursor_word_forward" => {
             Some(Command::NoteEditorExperimentalCursorWordForward)
@@ -152,40 +374,182 @@ fn str_to_command(s: &str) -> Option<Command> {
             Some(Command::NoteEditorExperimentalSetReadMode)
         }
         "note_editor_experimental_save" => Some(Command::NoteEditorExperimentalSave),
+        "note_editor_experimental_paste_as_markdown" => {
+            Some(Command::NoteEditorExperimentalPasteAsMarkdown)
+        }
+        "note_editor_experimental_move_node_up" => Some(Command::NoteEditorExperimentalMoveNodeUp),
+        "note_editor_experimental_move_node_down" => {
+            Some(Command::NoteEditorExperimentalMoveNodeDown)
+        }
+        "note_editor_experimental_yank_node" => Some(Command::NoteEditorExperimentalYankNode),
+        "note_editor_experimental_delete_node" => Some(Command::NoteEditorExperimentalDeleteNode),
+        "note_editor_experimental_registers" => Some(Command::NoteEditorExperimentalRegisters),
+        "note_editor_experimental_paste_node" => Some(Command::NoteEditorExperimentalPasteNode),
+        "note_editor_experimental_extract" => Some(Command::NoteEditorExperimentalExtract),
+        "note_editor_experimental_fix_link" => Some(Command::NoteEditorExperimentalFixLink),
         "note_editor_experimental_exit_mode" => Some(Command::NoteEditorExperimentalExitMode),
         "note_editor_experimental_cursor_left" => Some(Command::NoteEditorExperimentalCursorLeft),
         "note_editor_experimental_cursor_right" => Some(Command::NoteEditorExperimentalCursorRight),
 
         "vault_selector_modal_up" => Some(Command::VaultSelectorModalUp),
         "vault_selector_modal_down" => Some(Command::VaultSelectorModalDown),
+        "vault_selector_modal_goto_top" => Some(Command::VaultSelectorModalGotoTop),
+        "vault_selector_modal_goto_bottom" => Some(Command::VaultSelectorModalGotoBottom),
         "vault_selector_modal_close" => Some(Command::VaultSelectorModalClose),
         "vault_selector_modal_open" => Some(Command::VaultSelectorModalOpen),
         "vault_selector_modal_toggle" => Some(Command::VaultSelectorModalToggle),
+        "vault_selector_modal_pin" => Some(Command::VaultSelectorModalPin),
+        "vault_selector_modal_hide" => Some(Command::VaultSelectorModalHide),
+        "vault_selector_modal_show_hidden" => Some(Command::VaultSelectorModalShowHidden),
+
+        "diagnostics_modal_toggle" => Some(Command::DiagnosticsModalToggle),
+        "diagnostics_modal_close" => Some(Command::DiagnosticsModalClose),
+        "diagnostics_modal_up" => Some(Command::DiagnosticsModalUp),
+        "diagnostics_modal_down" => Some(Command::DiagnosticsModalDown),
+        "diagnostics_modal_open" => Some(Command::DiagnosticsModalOpen),
+        "diagnostics_modal_create_note" => Some(Command::DiagnosticsModalCreateNote),
+        "attachments_modal_toggle" => Some(Command::AttachmentsModalToggle),
+        "attachments_modal_close" => Some(Command::AttachmentsModalClose),
+        "attachments_modal_up" => Some(Command::AttachmentsModalUp),
+        "attachments_modal_down" => Some(Command::AttachmentsModalDown),
+        "attachments_modal_mark" => Some(Command::AttachmentsModalMark),
+        "attachments_modal_mark_all" => Some(Command::AttachmentsModalMarkAll),
+        "attachments_modal_open" => Some(Command::AttachmentsModalOpen),
+        "attachments_modal_delete" => Some(Command::AttachmentsModalDelete),
+        "attachments_modal_move" => Some(Command::AttachmentsModalMove),
+
+        "duplicates_modal_toggle" => Some(Command::DuplicatesModalToggle),
+        "duplicates_modal_close" => Some(Command::DuplicatesModalClose),
+        "duplicates_modal_up" => Some(Command::DuplicatesModalUp),
+        "duplicates_modal_down" => Some(Command::DuplicatesModalDown),
+        "duplicates_modal_scroll_up_half_page" => Some(Command::DuplicatesModalScrollUpHalfPage),
+        "duplicates_modal_scroll_down_half_page" => {
+            Some(Command::DuplicatesModalScrollDownHalfPage)
+        }
+        "duplicates_modal_swap" => Some(Command::DuplicatesModalSwap),
+        "duplicates_modal_delete" => Some(Command::DuplicatesModalDelete),
+        "duplicates_modal_replace" => Some(Command::DuplicatesModalReplace),
+        "duplicates_modal_merge" => Some(Command::DuplicatesModalMerge),
+        "vault_replace_modal_close" => Some(Command::VaultReplaceModalClose),
+        "vault_replace_modal_up" => Some(Command::VaultReplaceModalUp),
+        "vault_replace_modal_down" => Some(Command::VaultReplaceModalDown),
+        "vault_replace_modal_toggle" => Some(Command::VaultReplaceModalToggle),
+        "vault_replace_modal_toggle_backup" => Some(Command::VaultReplaceModalToggleBackup),
+        "vault_replace_modal_apply" => Some(Command::VaultReplaceModalApply),
+
+        "note_diff" => Some(Command::NoteDiff),
+        "note_diff_modal_close" => Some(Command::NoteDiffModalClose),
+        "note_diff_modal_toggle_source" => Some(Command::NoteDiffModalToggleSource),
+        "note_diff_modal_scroll_up_one" => Some(Command::NoteDiffModalScrollUpOne),
+        "note_diff_modal_scroll_down_one" => Some(Command::NoteDiffModalScrollDownOne),
+        "note_diff_modal_scroll_up_half_page" => Some(Command::NoteDiffModalScrollUpHalfPage),
+        "note_diff_modal_scroll_down_half_page" => Some(Command::NoteDiffModalScrollDownHalfPage),
+        "note_history" => Some(Command::NoteHistory),
+        "note_history_modal_close" => Some(Command::NoteHistoryModalClose),
+        "note_history_modal_up" => Some(Command::NoteHistoryModalUp),
+        "note_history_modal_down" => Some(Command::NoteHistoryModalDown),
+        "note_history_modal_scroll_up_half_page" => Some(Command::NoteHistoryModalScrollUpHalfPage),
+        "note_history_modal_scroll_down_half_page" => {
+            Some(Command::NoteHistoryModalScrollDownHalfPage)
+        }
+        "note_history_modal_restore" => Some(Command::NoteHistoryModalRestore),
+
+        "vault_stats_modal_toggle" => Some(Command::VaultStatsModalToggle),
+        "vault_stats_modal_close" => Some(Command::VaultStatsModalClose),
+        "vault_stats_modal_scroll_up_one" => Some(Command::VaultStatsModalScrollUpOne),
+        "vault_stats_modal_scroll_down_one" => Some(Command::VaultStatsModalScrollDownOne),
+        "vault_stats_modal_scroll_up_half_page" => Some(Command::VaultStatsModalScrollUpHalfPage),
+        "vault_stats_modal_scroll_down_half_page" => {
+            Some(Command::VaultStatsModalScrollDownHalfPage)
+        }
+
+        "canvas_modal_close" => Some(Command::CanvasModalClose),
+        "canvas_modal_up" => Some(Command::CanvasModalUp),
+        "canvas_modal_down" => Some(Command::CanvasModalDown),
+        "canvas_modal_open" => Some(Command::CanvasModalOpen),
+
+        "tag_browser_modal_toggle" => Some(Command::TagBrowserModalToggle),
+        "tag_browser_modal_close" => Some(Command::TagBrowserModalClose),
+        "tag_browser_modal_up" => Some(Command::TagBrowserModalUp),
+        "tag_browser_modal_down" => Some(Command::TagBrowserModalDown),
+        "tag_browser_modal_expand" => Some(Command::TagBrowserModalExpand),
+        "tag_browser_modal_collapse" => Some(Command::TagBrowserModalCollapse),
+        "tag_browser_modal_open" => Some(Command::TagBrowserModalOpen),
+        "tag_browser_modal_back" => Some(Command::TagBrowserModalBack),
+
+        "outgoing_links_modal_toggle" => Some(Command::OutgoingLinksModalToggle),
+        "outgoing_links_modal_close" => Some(Command::OutgoingLinksModalClose),
+        "outgoing_links_modal_up" => Some(Command::OutgoingLinksModalUp),
+        "outgoing_links_modal_down" => Some(Command::OutgoingLinksModalDown),
+        "outgoing_links_modal_open" => Some(Command::OutgoingLinksModalOpen),
+        "outgoing_links_modal_jump" => Some(Command::OutgoingLinksModalJump),
+
+        "jobs_modal_toggle" => Some(Command::JobsModalToggle),
+        "jobs_modal_close" => Some(Command::JobsModalClose),
+        "jobs_modal_up" => Some(Command::JobsModalUp),
+        "jobs_modal_down" => Some(Command::JobsModalDown),
+        "jobs_modal_kill" => Some(Command::JobsModalKill),
 
         _ => None,
     }
 }
 
-impl<'de> Deserialize<'de> for Command {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
+/// Parses the command as written in the key bindings, e.g. `explorer_up` or `exec:vi %note_path`.
+impl FromStr for Command {
+    type Err = String;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Some(command) = s
             .strip_prefix("exec:")
             .map(|command| Command::Exec(command.to_string()))
             .or(s
                 .strip_prefix("spawn:")
                 .map(|command| Command::Spawn(command.to_string())))
+            .or(s
+                .strip_prefix("exec_prompt:")
+                .map(|command| Command::ExecPrompt(command.to_string())))
+            .or(s
+                .strip_prefix("macro:")
+                .map(|name| Command::Macro(name.to_string())))
         {
             return Ok(command);
         }
 
-        str_to_command(&s).ok_or(serde::de::Error::custom(format!(
-            "{s} is not a valid command"
-        )))
+        str_to_command(s).ok_or(format!("{s} is not a valid command"))
+    }
+}
+
+/// Writes the command as in the key bindings, e.g. `explorer_up` or `exec:vi %note_path`.
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Exec(command) => write!(f, "exec:{command}"),
+            Command::Spawn(command) => write!(f, "spawn:{command}"),
+            Command::ExecPrompt(command) => write!(f, "exec_prompt:{command}"),
+            Command::Macro(name) => write!(f, "macro:{name}"),
+            // Command names are the names of the variants in snake case.
+            command => {
+                format!("{command:?}")
+                    .chars()
+                    .enumerate()
+                    .try_for_each(|(index, c)| match c.is_ascii_uppercase() {
+                        true if index > 0 => write!(f, "_{}", c.to_ascii_lowercase()),
+                        true => write!(f, "{}", c.to_ascii_lowercase()),
+                        false => write!(f, "{c}"),
+                    })
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Command {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
     }
 }
 
@@ -193,10 +557,45 @@ impl From<Command> for Message<'_> {
     fn from(value: Command) -> Self {
         match value {
             Command::Quit => Message::Quit,
+            Command::QuitAndPrint => Message::QuitAndPrint,
+            Command::SaveAll => Message::SaveAll,
+            Command::QuickCapture => Message::QuickCapture,
+            Command::QuickSwitcher => Message::QuickSwitcher,
+            Command::HistoryBack => Message::HistoryBack,
+            Command::HistoryForward => Message::HistoryForward,
+            Command::ArchiveNote => Message::ArchiveNote,
+            Command::Publish => Message::Publish,
+            Command::KeysDebug => Message::KeysDebug,
+            Command::KeysExport => Message::KeysExport,
+            Command::ErrorLogModalToggle => {
+                Message::ErrorLogModal(error_log_modal::Message::Toggle)
+            }
+            Command::MacroRecord => Message::MacroRecord,
+            Command::MacroPlay => Message::MacroPlay(None),
+            Command::Macro(name) => Message::MacroPlay(Some(name)),
+            Command::NoteInsertFromUrl => Message::InsertFromUrl,
+            Command::NoteSearchReplace => Message::SearchReplace(Scope::Note),
+            Command::VaultSearchReplace => Message::SearchReplace(Scope::Vault),
+            Command::TagRename => Message::TagRename,
+            Command::OpenWith => Message::OpenWith,
+            Command::CopyLinkToNote => Message::CopyLinkToNote,
+            Command::CopyLinkToHeading => Message::CopyLinkToHeading,
+            Command::FocusTimerStart => Message::FocusTimer(focus_timer::Message::Start),
+            Command::CycleCountScope => Message::CycleCountScope,
+            Command::FocusTimerStop => Message::FocusTimer(focus_timer::Message::Stop),
+            Command::PaneGrow => Message::PaneGrow,
+            Command::PaneShrink => Message::PaneShrink,
+            Command::ToggleZenMode => Message::ToggleZenMode,
+            Command::FocusExplorer => Message::FocusPane(ActivePane::Explorer),
+            Command::FocusEditor => Message::FocusPane(ActivePane::NoteEditor),
+            Command::FocusOutline => Message::FocusPane(ActivePane::Outline),
 
             Command::SplashUp => Message::Splash(splash_modal::Message::Up),
             Command::SplashDown => Message::Splash(splash_modal::Message::Down),
             Command::SplashOpen => Message::Splash(splash_modal::Message::Open),
+            Command::SplashPin => Message::Splash(splash_modal::Message::Pin),
+            Command::SplashHide => Message::Splash(splash_modal::Message::Hide),
+            Command::SplashShowHidden => Message::Splash(splash_modal::Message::ShowHidden),
 
             Command::ExplorerUp => Message::Explorer(explorer::Message::Up),
             Command::ExplorerDown => Message::Explorer(explorer::Message::Down),
@@ -204,6 +603,13 @@ impl From<Command> for Message<'_> {
             Command::ExplorerSort => Message::Explorer(explorer::Message::Sort),
             Command::ExplorerToggle => Message::Explorer(explorer::Message::Toggle),
             Command::ExplorerToggleOutline => Message::Explorer(explorer::Message::ToggleOutline),
+            Command::ExplorerDuplicateNote => Message::Explorer(explorer::Message::DuplicateNote),
+            Command::ExplorerResolveConflict => {
+                Message::Explorer(explorer::Message::ResolveConflict)
+            }
+            Command::ExplorerSaveNoteAsTemplate => {
+                Message::Explorer(explorer::Message::SaveNoteAsTemplate)
+            }
             Command::ExplorerSwitchPaneNext => Message::Explorer(explorer::Message::SwitchPaneNext),
             Command::ExplorerSwitchPanePrevious => {
                 Message::Explorer(explorer::Message::SwitchPanePrevious)
@@ -220,17 +626,31 @@ impl From<Command> for Message<'_> {
             Command::ExplorerScrollDownHalfPage => {
                 Message::Explorer(explorer::Message::ScrollDown(ScrollAmount::HalfPage))
             }
+            Command::ExplorerGotoTop => Message::Explorer(explorer::Message::GotoTop),
+            Command::ExplorerGotoBottom => Message::Explorer(explorer::Message::GotoBottom),
+            Command::ExplorerExpandAll => Message::Explorer(explorer::Message::ExpandAll),
+            Command::ExplorerCollapseAll => Message::Explorer(explorer::Message::CollapseAll),
+            Command::ExplorerExpandSubtree => Message::Explorer(explorer::Message::ExpandSubtree),
+            Command::ExplorerCollapseSubtree => {
+                Message::Explorer(explorer::Message::CollapseSubtree)
+            }
+            Command::ExplorerShowPath => Message::Explorer(explorer::Message::ShowPath),
 
             Command::OutlineUp => Message::Outline(outline::Message::Up),
             Command::OutlineDown => Message::Outline(outline::Message::Down),
             Command::OutlineSelect => Message::Outline(outline::Message::Select),
             Command::OutlineExpand => Message::Outline(outline::Message::Expand),
+            Command::OutlineCycleDepth => Message::Outline(outline::Message::CycleDepth),
             Command::OutlineToggle => Message::Outline(outline::Message::Toggle),
             Command::OutlineToggleExplorer => Message::Outline(outline::Message::ToggleExplorer),
             Command::OutlineSwitchPaneNext => Message::Outline(outline::Message::SwitchPaneNext),
             Command::OutlineSwitchPanePrevious => {
                 Message::Outline(outline::Message::SwitchPanePrevious)
             }
+            Command::OutlineGotoTop => Message::Outline(outline::Message::GotoTop),
+            Command::OutlineGotoBottom => Message::Outline(outline::Message::GotoBottom),
+            Command::OutlineShowHeading => Message::Outline(outline::Message::ShowHeading),
+            Command::OutlineRenameHeading => Message::Outline(outline::Message::RenameHeading),
 
             Command::HelpModalScrollUpOne => {
                 Message::HelpModal(help_modal::Message::ScrollUp(ScrollAmount::One))
@@ -246,6 +666,11 @@ impl From<Command> for Message<'_> {
             }
             Command::HelpModalToggle => Message::HelpModal(help_modal::Message::Toggle),
             Command::HelpModalClose => Message::HelpModal(help_modal::Message::Close),
+            Command::HelpModalSearch => Message::HelpModal(help_modal::Message::Search),
+            Command::HelpModalSearchNext => Message::HelpModal(help_modal::Message::SearchNext),
+            Command::HelpModalSearchPrevious => {
+                Message::HelpModal(help_modal::Message::SearchPrevious)
+            }
 
             Command::NoteEditorScrollUpOne => {
                 Message::NoteEditor(note_editor::Message::ScrollUp(ScrollAmount::One))
@@ -267,6 +692,17 @@ impl From<Command> for Message<'_> {
             }
             Command::NoteEditorCursorUp => Message::NoteEditor(note_editor::Message::CursorUp),
             Command::NoteEditorCursorDown => Message::NoteEditor(note_editor::Message::CursorDown),
+            Command::NoteEditorGotoTop => Message::NoteEditor(note_editor::Message::GotoTop),
+            Command::NoteEditorGotoBottom => Message::NoteEditor(note_editor::Message::GotoBottom),
+            Command::NoteEditorNextHeading => {
+                Message::NoteEditor(note_editor::Message::NextHeading)
+            }
+            Command::NoteEditorPreviousHeading => {
+                Message::NoteEditor(note_editor::Message::PreviousHeading)
+            }
+            Command::NoteEditorGotoLastEdit => {
+                Message::NoteEditor(note_editor::Message::GotoLastEdit)
+            }
             Command::NoteEditorToggleExplorer => {
                 Message::NoteEditor(note_editor::Message::ToggleExplorer)
             }
@@ -281,6 +717,33 @@ impl From<Command> for Message<'_> {
                 Message::NoteEditor(note_editor::Message::ReadMode)
             }
             Command::NoteEditorExperimentalSave => Message::NoteEditor(note_editor::Message::Save),
+            Command::NoteEditorExperimentalPasteAsMarkdown => {
+                Message::NoteEditor(note_editor::Message::PasteAsMarkdown)
+            }
+            Command::NoteEditorExperimentalMoveNodeUp => {
+                Message::NoteEditor(note_editor::Message::MoveNodeUp)
+            }
+            Command::NoteEditorExperimentalMoveNodeDown => {
+                Message::NoteEditor(note_editor::Message::MoveNodeDown)
+            }
+            Command::NoteEditorExperimentalYankNode => {
+                Message::NoteEditor(note_editor::Message::YankNode)
+            }
+            Command::NoteEditorExperimentalDeleteNode => {
+                Message::NoteEditor(note_editor::Message::DeleteNode)
+            }
+            Command::NoteEditorExperimentalRegisters => {
+                Message::NoteEditor(note_editor::Message::Registers)
+            }
+            Command::NoteEditorExperimentalPasteNode => {
+                Message::NoteEditor(note_editor::Message::PasteNode)
+            }
+            Command::NoteEditorExperimentalExtract => {
+                Message::NoteEditor(note_editor::Message::Extract)
+            }
+            Command::NoteEditorExperimentalFixLink => {
+                Message::NoteEditor(note_editor::Message::FixLink)
+            }
             Command::NoteEditorExperimentalExitMode => {
                 Message::NoteEditor(note_editor::Message::ExitMode)
             }
@@ -308,29 +771,284 @@ impl From<Command> for Message<'_> {
             Command::VaultSelectorModalDown => {
                 Message::VaultSelectorModal(vault_selector_modal::Message::Down)
             }
+            Command::VaultSelectorModalGotoTop => {
+                Message::VaultSelectorModal(vault_selector_modal::Message::GotoTop)
+            }
+            Command::VaultSelectorModalGotoBottom => {
+                Message::VaultSelectorModal(vault_selector_modal::Message::GotoBottom)
+            }
             Command::VaultSelectorModalOpen => {
                 Message::VaultSelectorModal(vault_selector_modal::Message::Select)
             }
+            Command::VaultSelectorModalPin => {
+                Message::VaultSelectorModal(vault_selector_modal::Message::Pin)
+            }
+            Command::VaultSelectorModalHide => {
+                Message::VaultSelectorModal(vault_selector_modal::Message::Hide)
+            }
+            Command::VaultSelectorModalShowHidden => {
+                Message::VaultSelectorModal(vault_selector_modal::Message::ShowHidden)
+            }
+
+            Command::DiagnosticsModalToggle => {
+                Message::DiagnosticsModal(diagnostics_modal::Message::Toggle)
+            }
+            Command::DiagnosticsModalClose => {
+                Message::DiagnosticsModal(diagnostics_modal::Message::Close)
+            }
+            Command::DiagnosticsModalUp => {
+                Message::DiagnosticsModal(diagnostics_modal::Message::Up)
+            }
+            Command::DiagnosticsModalDown => {
+                Message::DiagnosticsModal(diagnostics_modal::Message::Down)
+            }
+            Command::DiagnosticsModalOpen => {
+                Message::DiagnosticsModal(diagnostics_modal::Message::Open)
+            }
+            Command::DiagnosticsModalCreateNote => {
+                Message::DiagnosticsModal(diagnostics_modal::Message::CreateNote)
+            }
+
+            Command::AttachmentsModalToggle => {
+                Message::AttachmentsModal(attachments_modal::Message::Toggle)
+            }
+            Command::AttachmentsModalClose => {
+                Message::AttachmentsModal(attachments_modal::Message::Close)
+            }
+            Command::AttachmentsModalUp => {
+                Message::AttachmentsModal(attachments_modal::Message::Up)
+            }
+            Command::AttachmentsModalDown => {
+                Message::AttachmentsModal(attachments_modal::Message::Down)
+            }
+            Command::AttachmentsModalMark => {
+                Message::AttachmentsModal(attachments_modal::Message::Mark)
+            }
+            Command::AttachmentsModalMarkAll => {
+                Message::AttachmentsModal(attachments_modal::Message::MarkAll)
+            }
+            Command::AttachmentsModalOpen => {
+                Message::AttachmentsModal(attachments_modal::Message::Open)
+            }
+            Command::AttachmentsModalDelete => {
+                Message::AttachmentsModal(attachments_modal::Message::Delete)
+            }
+            Command::AttachmentsModalMove => {
+                Message::AttachmentsModal(attachments_modal::Message::Move)
+            }
+
+            Command::DuplicatesModalToggle => {
+                Message::DuplicatesModal(duplicates_modal::Message::Toggle)
+            }
+            Command::DuplicatesModalClose => {
+                Message::DuplicatesModal(duplicates_modal::Message::Close)
+            }
+            Command::DuplicatesModalUp => Message::DuplicatesModal(duplicates_modal::Message::Up),
+            Command::DuplicatesModalDown => {
+                Message::DuplicatesModal(duplicates_modal::Message::Down)
+            }
+            Command::DuplicatesModalScrollUpHalfPage => Message::DuplicatesModal(
+                duplicates_modal::Message::ScrollUp(ScrollAmount::HalfPage),
+            ),
+            Command::DuplicatesModalScrollDownHalfPage => Message::DuplicatesModal(
+                duplicates_modal::Message::ScrollDown(ScrollAmount::HalfPage),
+            ),
+            Command::DuplicatesModalSwap => {
+                Message::DuplicatesModal(duplicates_modal::Message::Swap)
+            }
+            Command::DuplicatesModalDelete => {
+                Message::DuplicatesModal(duplicates_modal::Message::Delete)
+            }
+            Command::DuplicatesModalReplace => {
+                Message::DuplicatesModal(duplicates_modal::Message::Replace)
+            }
+            Command::DuplicatesModalMerge => {
+                Message::DuplicatesModal(duplicates_modal::Message::Merge)
+            }
+            Command::VaultReplaceModalClose => {
+                Message::VaultReplaceModal(vault_replace_modal::Message::Close)
+            }
+            Command::VaultReplaceModalUp => {
+                Message::VaultReplaceModal(vault_replace_modal::Message::Up)
+            }
+            Command::VaultReplaceModalDown => {
+                Message::VaultReplaceModal(vault_replace_modal::Message::Down)
+            }
+            Command::VaultReplaceModalToggle => {
+                Message::VaultReplaceModal(vault_replace_modal::Message::Toggle)
+            }
+            Command::VaultReplaceModalToggleBackup => {
+                Message::VaultReplaceModal(vault_replace_modal::Message::ToggleBackup)
+            }
+            Command::VaultReplaceModalApply => {
+                Message::VaultReplaceModal(vault_replace_modal::Message::Apply)
+            }
+
+            Command::NoteDiff => Message::NoteDiffModal(note_diff_modal::Message::Toggle),
+            Command::NoteDiffModalClose => Message::NoteDiffModal(note_diff_modal::Message::Close),
+            Command::NoteDiffModalToggleSource => {
+                Message::NoteDiffModal(note_diff_modal::Message::ToggleSource)
+            }
+            Command::NoteDiffModalScrollUpOne => {
+                Message::NoteDiffModal(note_diff_modal::Message::ScrollUp(ScrollAmount::One))
+            }
+            Command::NoteDiffModalScrollDownOne => {
+                Message::NoteDiffModal(note_diff_modal::Message::ScrollDown(ScrollAmount::One))
+            }
+            Command::NoteDiffModalScrollUpHalfPage => {
+                Message::NoteDiffModal(note_diff_modal::Message::ScrollUp(ScrollAmount::HalfPage))
+            }
+            Command::NoteDiffModalScrollDownHalfPage => {
+                Message::NoteDiffModal(note_diff_modal::Message::ScrollDown(ScrollAmount::HalfPage))
+            }
+            Command::NoteHistory => Message::NoteHistoryModal(note_history_modal::Message::Toggle),
+            Command::NoteHistoryModalClose => {
+                Message::NoteHistoryModal(note_history_modal::Message::Close)
+            }
+            Command::NoteHistoryModalUp => {
+                Message::NoteHistoryModal(note_history_modal::Message::Up)
+            }
+            Command::NoteHistoryModalDown => {
+                Message::NoteHistoryModal(note_history_modal::Message::Down)
+            }
+            Command::NoteHistoryModalScrollUpHalfPage => Message::NoteHistoryModal(
+                note_history_modal::Message::ScrollUp(ScrollAmount::HalfPage),
+            ),
+            Command::NoteHistoryModalScrollDownHalfPage => Message::NoteHistoryModal(
+                note_history_modal::Message::ScrollDown(ScrollAmount::HalfPage),
+            ),
+            Command::NoteHistoryModalRestore => {
+                Message::NoteHistoryModal(note_history_modal::Message::Restore)
+            }
+
+            Command::VaultStatsModalToggle => {
+                Message::VaultStatsModal(vault_stats_modal::Message::Toggle)
+            }
+            Command::VaultStatsModalClose => {
+                Message::VaultStatsModal(vault_stats_modal::Message::Close)
+            }
+            Command::VaultStatsModalScrollUpOne => {
+                Message::VaultStatsModal(vault_stats_modal::Message::ScrollUp(ScrollAmount::One))
+            }
+            Command::VaultStatsModalScrollDownOne => {
+                Message::VaultStatsModal(vault_stats_modal::Message::ScrollDown(ScrollAmount::One))
+            }
+            Command::VaultStatsModalScrollUpHalfPage => Message::VaultStatsModal(
+                vault_stats_modal::Message::ScrollUp(ScrollAmount::HalfPage),
+            ),
+            Command::VaultStatsModalScrollDownHalfPage => Message::VaultStatsModal(
+                vault_stats_modal::Message::ScrollDown(ScrollAmount::HalfPage),
+            ),
+            Command::CanvasModalClose => Message::CanvasModal(canvas_modal::Message::Close),
+            Command::CanvasModalUp => Message::CanvasModal(canvas_modal::Message::Up),
+            Command::CanvasModalDown => Message::CanvasModal(canvas_modal::Message::Down),
+            Command::CanvasModalOpen => Message::CanvasModal(canvas_modal::Message::Select),
+            Command::TagBrowserModalToggle => {
+                Message::TagBrowserModal(tag_browser_modal::Message::Toggle)
+            }
+            Command::TagBrowserModalClose => {
+                Message::TagBrowserModal(tag_browser_modal::Message::Close)
+            }
+            Command::TagBrowserModalUp => Message::TagBrowserModal(tag_browser_modal::Message::Up),
+            Command::TagBrowserModalDown => {
+                Message::TagBrowserModal(tag_browser_modal::Message::Down)
+            }
+            Command::TagBrowserModalExpand => {
+                Message::TagBrowserModal(tag_browser_modal::Message::Expand)
+            }
+            Command::TagBrowserModalCollapse => {
+                Message::TagBrowserModal(tag_browser_modal::Message::Collapse)
+            }
+            Command::TagBrowserModalOpen => {
+                Message::TagBrowserModal(tag_browser_modal::Message::Open)
+            }
+            Command::TagBrowserModalBack => {
+                Message::TagBrowserModal(tag_browser_modal::Message::Back)
+            }
+            Command::OutgoingLinksModalToggle => {
+                Message::OutgoingLinksModal(outgoing_links_modal::Message::Toggle)
+            }
+            Command::OutgoingLinksModalClose => {
+                Message::OutgoingLinksModal(outgoing_links_modal::Message::Close)
+            }
+            Command::OutgoingLinksModalUp => {
+                Message::OutgoingLinksModal(outgoing_links_modal::Message::Up)
+            }
+            Command::OutgoingLinksModalDown => {
+                Message::OutgoingLinksModal(outgoing_links_modal::Message::Down)
+            }
+            Command::OutgoingLinksModalOpen => {
+                Message::OutgoingLinksModal(outgoing_links_modal::Message::Open)
+            }
+            Command::OutgoingLinksModalJump => {
+                Message::OutgoingLinksModal(outgoing_links_modal::Message::Jump)
+            }
+            Command::JobsModalToggle => Message::JobsModal(jobs_modal::Message::Toggle),
+            Command::JobsModalClose => Message::JobsModal(jobs_modal::Message::Close),
+            Command::JobsModalUp => Message::JobsModal(jobs_modal::Message::Up),
+            Command::JobsModalDown => Message::JobsModal(jobs_modal::Message::Down),
+            Command::JobsModalKill => Message::JobsModal(jobs_modal::Message::Kill),
             Command::Exec(command) => Message::Exec(command),
             Command::Spawn(command) => Message::Spawn(command),
+            Command::ExecPrompt(command) => Message::ExecPrompt(command),
         }
     }
 }
 
+/// Fills the `%1` to `%9` placeholders of the command with the words of the arguments, in order.
+/// Arguments are appended to a command without placeholders.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::command::fill_arguments;
+///
+/// assert_eq!(fill_arguments("rg %1 %vault_path", "todo"), "rg todo %vault_path");
+/// assert_eq!(fill_arguments("git log", "-n 5"), "git log -n 5");
+/// ```
+pub fn fill_arguments(command: &str, arguments: &str) -> String {
+    let placeholders: Vec<String> = (1..=9).map(|n| format!("%{n}")).collect();
+
+    if !placeholders
+        .iter()
+        .any(|placeholder| command.contains(placeholder))
+    {
+        return format!("{command} {arguments}").trim_end().to_string();
+    }
+
+    let mut words = arguments.split_whitespace();
+    placeholders
+        .iter()
+        .fold(command.to_string(), |command, placeholder| {
+            command.replace_var(placeholder, words.next().unwrap_or_default())
+        })
+}
+
 pub fn run_command<'a>(
     command: String,
     vault_name: &str,
+    vault_path: &str,
     note_name: &str,
     note_path: &str,
     mut callback: impl FnMut(&str, &[&str]) -> Option<Message<'a>>,
 ) -> Option<Message<'a>> {
-    let expanded = command
-        .replace_var("%vault", vault_name)
-        // Order matters, otherwise all mentions of %note_path would be replaced with %note value
-        .replace_var("%note_path", note_path)
-        .replace_var("%note", note_name);
+    // The command is split into words before the variables are replaced, so that a path with
+    // spaces stays a single argument.
+    let words = command
+        .split_whitespace()
+        .map(|word| {
+            word.to_string()
+                // Order matters, otherwise %vault_path would be replaced with the %vault value
+                .replace_var("%vault_path", vault_path)
+                .replace_var("%vault", vault_name)
+                // Order matters, otherwise all mentions of %note_path would be replaced with %note
+                // value
+                .replace_var("%note_path", note_path)
+                .replace_var("%note", note_name)
+        })
+        .collect::<Vec<_>>();
 
-    let args = expanded.split_whitespace().collect::<Vec<_>>();
+    let args = words.iter().map(String::as_str).collect::<Vec<_>>();
 
     match args.as_slice() {
         [command, args @ ..] => callback(command, args),
@@ -338,14 +1056,17 @@ pub fn run_command<'a>(
     }
 }
 
-pub fn sync_command<'a>(
-    terminal: &mut DefaultTerminal,
+pub fn sync_command<'a, B: Backend>(
+    terminal: &mut Terminal<B>,
     command: String,
     vault_name: &str,
+    vault_path: &str,
     note_name: &str,
     note_path: &str,
 ) -> Option<Message<'a>> {
-    fn enter_alternate_screen(terminal: &mut DefaultTerminal) -> Result<(), std::io::Error> {
+    fn enter_alternate_screen<B: Backend>(
+        terminal: &mut Terminal<B>,
+    ) -> Result<(), std::io::Error> {
         disable_raw_mode()?;
         stdout().execute(LeaveAlternateScreen)?;
         stdout().execute(EnterAlternateScreen)?;
@@ -356,17 +1077,20 @@ pub fn sync_command<'a>(
     run_command(
         command,
         vault_name,
+        vault_path,
         note_name,
         note_path,
         |command, args| {
-            // TODO:Error handling
-            process::Command::new(command)
-                .arg(args.join(" "))
-                .status()
-                .ok()?;
-            enter_alternate_screen(terminal)
-                .map(|_| Message::Explorer(explorer::Message::Open))
-                .ok()
+            let status = process::Command::new(command).args(args).status();
+            enter_alternate_screen(terminal).ok()?;
+
+            match status {
+                Ok(status) if status.success() => Some(Message::Explorer(explorer::Message::Open)),
+                Ok(status) => Some(Message::Error(format!(
+                    "Command `{command}` failed: {status}"
+                ))),
+                Err(err) => Some(Message::Error(format!("Could not run `{command}`: {err}"))),
+            }
         },
     )
 }
@@ -374,21 +1098,192 @@ pub fn sync_command<'a>(
 pub fn spawn_command<'a>(
     command: String,
     vault_name: &str,
+    vault_path: &str,
     note_name: &str,
     note_path: &str,
 ) -> Option<Message<'a>> {
     run_command(
         command,
         vault_name,
+        vault_path,
         note_name,
         note_path,
+        |command, args| match process::Command::new(command).args(args).spawn() {
+            Ok(child) => Some(Message::JobsModal(jobs_modal::Message::Spawned(
+                jobs_modal::Job::new(
+                    child,
+                    format!("{command} {}", args.join(" "))
+                        .trim_end()
+                        .to_string(),
+                    Instant::now(),
+                ),
+            ))),
+            Err(err) => Some(Message::Error(format!("Could not run `{command}`: {err}"))),
+        },
+    )
+}
+
+/// Runs the command in the vault folder and waits for it to finish. The output is captured so that
+/// it does not draw over the interface, and a failed command is reported with the last line of its
+/// error output.
+pub fn vault_command<'a>(
+    command: String,
+    vault_name: &str,
+    vault_path: &Path,
+) -> Option<Message<'a>> {
+    run_command(
+        command,
+        vault_name,
+        &vault_path.to_string_lossy(),
+        "",
+        "",
         |command, args| {
-            // TODO:Error handling
-            _ = process::Command::new(command)
-                .arg(args.join(" "))
-                .spawn()
-                .ok();
-            None
+            let output = process::Command::new(command)
+                .args(args)
+                .current_dir(vault_path)
+                .stdin(Stdio::null())
+                // Git would otherwise ask for credentials on the terminal behind the interface.
+                .env("GIT_TERMINAL_PROMPT", "0")
+                .output();
+
+            match output {
+                Ok(output) if output.status.success() => None,
+                Ok(output) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let reason = stderr
+                        .lines()
+                        .map(str::trim)
+                        .rfind(|line| !line.is_empty())
+                        .map(str::to_string)
+                        .unwrap_or_else(|| output.status.to_string());
+                    Some(Message::Error(format!(
+                        "Command `{command}` failed: {reason}"
+                    )))
+                }
+                Err(err) => Some(Message::Error(format!("Could not run `{command}`: {err}"))),
+            }
         },
     )
 }
+
+/// Returns the program and arguments of the opener command for the file. `%file` is replaced with
+/// the path of the file, which is appended to openers without `%file`. An empty opener has no
+/// arguments.
+fn opener_args(opener: &str, path: &str) -> Vec<String> {
+    let mut args: Vec<_> = opener
+        .split_whitespace()
+        .map(|arg| arg.to_string().replace_var("%file", path))
+        .collect();
+
+    if !args.is_empty() && !opener.contains("%file") {
+        args.push(path.to_string());
+    }
+
+    args
+}
+
+/// Opens the file with the opener command in the background.
+pub fn open_file<'a>(opener: &str, path: &Path) -> Option<Message<'a>> {
+    let path = path.to_string_lossy();
+
+    match opener_args(opener, &path).as_slice() {
+        [command, args @ ..] => process::Command::new(command)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .err()
+            .map(|err| Message::Error(format!("Could not open {path}: {err}"))),
+        [] => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opener_args() {
+        assert_eq!(
+            opener_args("xdg-open %file", "My Vault/Report.pdf"),
+            ["xdg-open", "My Vault/Report.pdf"]
+        );
+        assert_eq!(
+            opener_args("open -a Preview", "Scan.png"),
+            ["open", "-a", "Preview", "Scan.png"]
+        );
+        assert!(opener_args(" ", "Scan.png").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_command_arguments() {
+        let dir = std::env::temp_dir().join("basalt-command-spawn-arguments");
+        _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Every word is a separate argument, so two files are created.
+        let command = fill_arguments("touch %vault_path/%1 %vault_path/%2", "first second");
+        let Some(Message::JobsModal(message)) =
+            spawn_command(command, "Notes", &dir.to_string_lossy(), "", "")
+        else {
+            panic!("touch was not spawned");
+        };
+
+        let mut state = jobs_modal::JobsModalState::default();
+        jobs_modal::update(&message, &mut state);
+        while state.running() > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            state.tick(Instant::now());
+        }
+
+        let mut files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["first", "second"]);
+    }
+
+    #[test]
+    fn test_run_command_paths_with_spaces() {
+        let mut arguments = vec![];
+        run_command(
+            "vi %note_path -c %note".to_string(),
+            "Notes",
+            "/Mobile Documents/Notes",
+            "My Note",
+            "/Mobile Documents/Notes/My Note.md",
+            |command, args| {
+                arguments.push(command.to_string());
+                arguments.extend(args.iter().map(|arg| arg.to_string()));
+                None
+            },
+        );
+
+        assert_eq!(
+            arguments,
+            ["vi", "/Mobile Documents/Notes/My Note.md", "-c", "My Note"]
+        );
+    }
+
+    #[test]
+    fn test_command_to_string() {
+        [
+            "quit",
+            "explorer_up",
+            "note_editor_experimental_cursor_word_forward",
+            "vault_selector_modal_show_hidden",
+            "open_with",
+            "exec:vi %note_path",
+            "spawn:open obsidian://open?vault=%vault",
+            "exec_prompt:rg %1 %vault_path",
+            "macro:heading",
+        ]
+        .into_iter()
+        .for_each(|name| {
+            assert_eq!(name.parse::<Command>().unwrap().to_string(), name);
+        });
+    }
+}
@@ -1,14 +1,31 @@
+mod expand;
 mod key_binding;
+mod validation;
 
 use core::fmt;
-use std::{collections::BTreeMap, fs::read_to_string};
+use std::{
+    collections::BTreeMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
 
-use etcetera::{choose_base_strategy, home_dir, BaseStrategy};
+use etcetera::home_dir;
 use key_binding::KeyBinding;
 use serde::Deserialize;
 
-use crate::{app::Message, command::Command};
+use crate::{
+    app::Message,
+    chrome::Chrome,
+    color_support::ColorSupport,
+    command::Command,
+    date_format::DateFormat,
+    dirs,
+    note_editor::{LineNumbers, Mode},
+    screen_reader::Announcements,
+    text_counts::{CountMode, CountScope},
+};
 pub(crate) use key_binding::Key;
+pub use validation::{ConfigProblem, ConfigReport};
 
 #[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
@@ -31,9 +48,49 @@ pub enum ConfigError {
     UserConfigNotFound(String),
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PublishConfig {
+    /// Directory the site is written to. Relative paths are relative to the vault root.
+    /// Defaults to `.publish` in the vault.
+    pub output_dir: Option<PathBuf>,
+    /// Path to an HTML template used for every page instead of the built-in template.
+    pub template: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AttachmentsConfig {
+    /// Extensions of the files listed in the explorer besides notes and canvases.
+    pub extensions: Vec<String>,
+    /// Command that opens attachments, where `%file` is replaced with the path of the file.
+    pub opener: String,
+}
+
+impl Default for AttachmentsConfig {
+    fn default() -> Self {
+        let opener = match cfg!(target_os = "macos") {
+            true => "open %file",
+            false => "xdg-open %file",
+        };
+
+        Self {
+            extensions: ["pdf", "png", "jpg", "jpeg", "gif", "svg", "webp"]
+                .map(String::from)
+                .to_vec(),
+            opener: opener.into(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ConfigSection<'a> {
     pub key_bindings: BTreeMap<String, Message<'a>>,
+    /// The key bindings as written in the configuration, by the same keys as `key_bindings`, for
+    /// listing the keymap.
+    pub(crate) bindings: BTreeMap<String, KeyBinding>,
+    /// Borders, padding and title alignment of the pane or modal.
+    pub chrome: Chrome,
 }
 
 impl ConfigSection<'_> {
@@ -43,6 +100,15 @@ impl ConfigSection<'_> {
         config.key_bindings.into_iter().for_each(|(key, message)| {
             self.key_bindings.insert(key, message);
         });
+        self.bindings.extend(config.bindings);
+    }
+
+    /// Takes self and another config and merges the sections together. Key bindings are merged
+    /// with [`Self::merge_key_bindings`] and the settings in another config take precedence, if
+    /// set.
+    pub(crate) fn merge(&mut self, config: Self) {
+        self.chrome = config.chrome.or(self.chrome);
+        self.merge_key_bindings(config);
     }
 
     pub fn key_to_message(&self, key: Key) -> Option<Message<'_>> {
@@ -60,9 +126,223 @@ impl fmt::Display for ConfigSection<'_> {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ZenModeConfig {
+    /// Maximum width of the note text in columns.
+    pub max_width: u16,
+}
+
+impl Default for ZenModeConfig {
+    fn default() -> Self {
+        Self { max_width: 80 }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ReaderConfig {
+    /// Maximum width of the note text in read mode in columns. The note is centered on wider
+    /// panes.
+    pub max_width: Option<u16>,
+    /// Detects right-to-left paragraphs, which are shown in reading order and aligned to the
+    /// right.
+    pub detect_direction: bool,
+    /// Pins the nearest heading above the viewport to the top of the note editor.
+    pub sticky_header: bool,
+    /// Shows a map of the whole note with the viewport marked on the right edge of the note
+    /// editor, and how far the viewport is through the note in percents.
+    pub minimap: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Draws borders, scrollbars, the logo and symbols with ASCII characters only.
+    pub ascii_only: bool,
+    /// Shows gray and dimmed text in the default foreground color and bright colors otherwise.
+    pub high_contrast: bool,
+    /// Announces focus and selection changes for screen readers.
+    pub announcements: Announcements,
+    /// Colors of the terminal, RGB colors are mapped to the nearest color the terminal can draw.
+    pub colors: ColorSupport,
+    /// Lines kept visible above and below the cursor of the note editor when moving the cursor.
+    /// The view only scrolls when the cursor leaves the screen if unset.
+    pub scroll_off: Option<usize>,
+    /// Scrolls half pages in a few steps instead of jumping.
+    pub smooth_scroll: bool,
+    /// Borders, padding and title alignment of the panes and modals without their own.
+    #[serde(flatten)]
+    pub chrome: Chrome,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    pub line_numbers: LineNumbers,
+    /// Shows line numbers also in read mode.
+    pub line_numbers_in_read_mode: bool,
+    /// Mode of the note editor when a note is opened. Notes are always opened in read mode
+    /// without the experimental editor.
+    pub default_mode: Mode,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SplashConfig {
+    /// Opens the vault directly when there is only one vault.
+    pub skip_single_vault: bool,
+    /// Opens the vault that was opened last directly.
+    pub open_last_vault: bool,
+    /// Path to a text file with ASCII art that replaces the logo.
+    pub logo: Option<PathBuf>,
+}
+
+/// Settings of the explorer pane.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ExplorerConfig {
+    /// Width of the pane in columns, when the pane is open.
+    pub width: Option<u16>,
+}
+
+/// Blocks listed in the outline next to the headings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OutlineConfig {
+    /// Width of the pane in columns, when the pane is open.
+    pub width: Option<u16>,
+    /// Lists the items of top-level lists.
+    pub list_items: bool,
+    /// Lists the tasks of top-level lists.
+    pub tasks: bool,
+    /// Lists the code blocks by their language, or by their first line.
+    pub code_blocks: bool,
+    /// Deepest heading level that is listed, e.g. `2` for H1 and H2. All headings are listed
+    /// when not set.
+    pub max_depth: Option<usize>,
+}
+
+/// Built-in key bindings layered on top of the default key bindings. User key bindings override
+/// the profile.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeymapProfile {
+    /// The default key bindings, which are vim-like.
+    #[default]
+    Vim,
+    Emacs,
+    /// Common desktop application shortcuts, e.g. Ctrl+S to save.
+    Standard,
+}
+
+impl KeymapProfile {
+    /// Returns the key bindings of the profile in the configuration format.
+    fn key_bindings(self) -> &'static str {
+        match self {
+            KeymapProfile::Vim => "",
+            KeymapProfile::Emacs => {
+                include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/keymaps/emacs.toml"))
+            }
+            KeymapProfile::Standard => {
+                include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/keymaps/standard.toml"
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// Note the captures are appended to, relative to the vault root. Date format specifiers are
+    /// expanded, so that captures can go to a note per day or month.
+    pub inbox: String,
+    /// Date format specifiers of the timestamp in front of every capture.
+    pub timestamp: String,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            inbox: "Inbox".into(),
+            timestamp: "%Y-%m-%d %H:%M".into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TimerConfig {
+    /// Length of a focus session in minutes.
+    pub minutes: u64,
+    /// Command spawned when a focus session ends, e.g. to send a notification. Nothing runs when
+    /// empty.
+    pub on_end: String,
+}
+
+impl Default for TimerConfig {
+    fn default() -> Self {
+        Self {
+            minutes: 25,
+            on_end: String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ExtractConfig {
+    /// Leaves a link to the target note in place of the extracted text.
+    pub leave_link: bool,
+}
+
+impl Default for ExtractConfig {
+    fn default() -> Self {
+        Self { leave_link: true }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct QuickSwitcherConfig {
+    /// Lists the notes of all vaults instead of the open vault only.
+    pub all_vaults: bool,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Config<'a> {
     pub experimental_editor: bool,
+    pub keymap_profile: KeymapProfile,
+    pub archive_folder: String,
+    pub text_counts: CountMode,
+    pub count_scope: CountScope,
+    pub date_format: DateFormat,
+    /// Named macros, the keys are replayed as if pressed when the macro is played.
+    pub macros: BTreeMap<String, Vec<Key>>,
+    /// Named queries listed as virtual folders at the top of the explorer.
+    pub saved_searches: BTreeMap<String, String>,
+    /// Commands run in the vault folder by vault name when the vault is opened, before it is
+    /// scanned, e.g. `git pull`.
+    pub vault_startup: BTreeMap<String, Vec<String>>,
+    /// Programs listed by the `open_with` command by name, run like the `exec:` and `spawn:`
+    /// commands of key bindings, e.g. `spawn:code %note_path`.
+    pub(crate) open_with: BTreeMap<String, Command>,
+    pub zen_mode: ZenModeConfig,
+    pub editor: EditorConfig,
+    pub reader: ReaderConfig,
+    pub ui: UiConfig,
+    pub splash_screen: SplashConfig,
+    pub explorer_settings: ExplorerConfig,
+    pub outline_settings: OutlineConfig,
+    pub publish: PublishConfig,
+    pub attachments: AttachmentsConfig,
+    pub capture: CaptureConfig,
+    pub extract: ExtractConfig,
+    pub timer: TimerConfig,
+    pub quick_switcher: QuickSwitcherConfig,
     pub global: ConfigSection<'a>,
     pub splash: ConfigSection<'a>,
     pub explorer: ConfigSection<'a>,
@@ -70,6 +350,17 @@ pub struct Config<'a> {
     pub help_modal: ConfigSection<'a>,
     pub note_editor: ConfigSection<'a>,
     pub vault_selector_modal: ConfigSection<'a>,
+    pub vault_stats_modal: ConfigSection<'a>,
+    pub diagnostics_modal: ConfigSection<'a>,
+    pub attachments_modal: ConfigSection<'a>,
+    pub duplicates_modal: ConfigSection<'a>,
+    pub note_diff_modal: ConfigSection<'a>,
+    pub note_history_modal: ConfigSection<'a>,
+    pub vault_replace_modal: ConfigSection<'a>,
+    pub canvas_modal: ConfigSection<'a>,
+    pub tag_browser_modal: ConfigSection<'a>,
+    pub outgoing_links_modal: ConfigSection<'a>,
+    pub jobs_modal: ConfigSection<'a>,
 }
 
 impl Default for Config<'_> {
@@ -82,40 +373,147 @@ impl From<TomlConfig> for Config<'_> {
     fn from(value: TomlConfig) -> Self {
         Self {
             experimental_editor: value.experimental_editor,
+            keymap_profile: value.keymap_profile,
+            archive_folder: value.archive_folder,
+            text_counts: value.text_counts,
+            count_scope: value.count_scope,
+            date_format: value.date_format,
+            macros: value.macros,
+            saved_searches: value.saved_searches,
+            vault_startup: value.vault_startup,
+            open_with: value.open_with,
+            zen_mode: value.zen_mode,
+            editor: value.editor,
+            reader: value.reader,
+            ui: value.ui,
+            splash_screen: value.splash.settings,
+            explorer_settings: value.explorer.settings,
+            outline_settings: value.outline.settings,
+            publish: value.publish,
+            attachments: value.attachments,
+            capture: value.capture,
+            extract: value.extract,
+            timer: value.timer,
+            quick_switcher: value.quick_switcher,
             global: value.global.into(),
-            splash: value.splash.into(),
-            explorer: value.explorer.into(),
-            outline: value.outline.into(),
+            splash: value.splash.section.into(),
+            explorer: value.explorer.section.into(),
+            outline: value.outline.section.into(),
             help_modal: value.help_modal.into(),
             note_editor: value.note_editor.into(),
             vault_selector_modal: value.vault_selector_modal.into(),
+            vault_stats_modal: value.vault_stats_modal.into(),
+            diagnostics_modal: value.diagnostics_modal.into(),
+            attachments_modal: value.attachments_modal.into(),
+            duplicates_modal: value.duplicates_modal.into(),
+            note_diff_modal: value.note_diff_modal.into(),
+            note_history_modal: value.note_history_modal.into(),
+            vault_replace_modal: value.vault_replace_modal.into(),
+            canvas_modal: value.canvas_modal.into(),
+            tag_browser_modal: value.tag_browser_modal.into(),
+            outgoing_links_modal: value.outgoing_links_modal.into(),
+            jobs_modal: value.jobs_modal.into(),
         }
     }
 }
 
 impl From<TomlConfigSection> for ConfigSection<'_> {
-    fn from(TomlConfigSection { key_bindings }: TomlConfigSection) -> Self {
+    fn from(
+        TomlConfigSection {
+            key_bindings,
+            chrome,
+        }: TomlConfigSection,
+    ) -> Self {
         Self {
             key_bindings: key_bindings
+                .as_ref()
+                .iter()
+                .map(|KeyBinding { key, command }| (key.to_string(), command.clone().into()))
+                .collect(),
+            bindings: key_bindings
                 .into_iter()
-                .map(|KeyBinding { key, command }| (key.to_string(), command.into()))
+                .map(|binding| (binding.key.to_string(), binding))
                 .collect(),
+            chrome,
         }
     }
 }
 
-impl Config<'_> {
+impl<'a> Config<'a> {
+    /// Returns the chrome of the pane or modal, where unset settings fall back to the `[ui]`
+    /// table.
+    pub fn chrome(&self, section: &ConfigSection) -> Chrome {
+        section.chrome.or(self.ui.chrome)
+    }
+
+    /// Returns the tables with key bindings and their sections, the global table first.
+    pub fn sections(&self) -> [(&'static str, &ConfigSection<'a>); 18] {
+        [
+            ("global", &self.global),
+            ("splash", &self.splash),
+            ("explorer", &self.explorer),
+            ("outline", &self.outline),
+            ("note_editor", &self.note_editor),
+            ("help_modal", &self.help_modal),
+            ("vault_selector_modal", &self.vault_selector_modal),
+            ("vault_stats_modal", &self.vault_stats_modal),
+            ("diagnostics_modal", &self.diagnostics_modal),
+            ("attachments_modal", &self.attachments_modal),
+            ("duplicates_modal", &self.duplicates_modal),
+            ("note_diff_modal", &self.note_diff_modal),
+            ("note_history_modal", &self.note_history_modal),
+            ("vault_replace_modal", &self.vault_replace_modal),
+            ("canvas_modal", &self.canvas_modal),
+            ("tag_browser_modal", &self.tag_browser_modal),
+            ("outgoing_links_modal", &self.outgoing_links_modal),
+            ("jobs_modal", &self.jobs_modal),
+        ]
+    }
+
     /// Takes self and another config and merges the `key_bindings` together overwriting the
     /// existing entries with the value from another config.
     pub(crate) fn merge(&mut self, config: Self) -> Self {
         self.experimental_editor = config.experimental_editor;
-        self.global.merge_key_bindings(config.global);
-        self.explorer.merge_key_bindings(config.explorer);
-        self.splash.merge_key_bindings(config.splash);
-        self.note_editor.merge_key_bindings(config.note_editor);
-        self.help_modal.merge_key_bindings(config.help_modal);
-        self.vault_selector_modal
-            .merge_key_bindings(config.vault_selector_modal);
+        self.keymap_profile = config.keymap_profile;
+        self.archive_folder = config.archive_folder;
+        self.text_counts = config.text_counts;
+        self.count_scope = config.count_scope;
+        self.date_format = config.date_format;
+        self.macros.extend(config.macros);
+        self.saved_searches.extend(config.saved_searches);
+        self.vault_startup.extend(config.vault_startup);
+        self.open_with.extend(config.open_with);
+        self.zen_mode = config.zen_mode;
+        self.editor = config.editor;
+        self.reader = config.reader;
+        self.ui = config.ui;
+        self.splash_screen = config.splash_screen;
+        self.explorer_settings = config.explorer_settings;
+        self.outline_settings = config.outline_settings;
+        self.publish = config.publish;
+        self.attachments = config.attachments;
+        self.capture = config.capture;
+        self.extract = config.extract;
+        self.timer = config.timer;
+        self.quick_switcher = config.quick_switcher;
+        self.global.merge(config.global);
+        self.explorer.merge(config.explorer);
+        self.outline.merge(config.outline);
+        self.splash.merge(config.splash);
+        self.note_editor.merge(config.note_editor);
+        self.help_modal.merge(config.help_modal);
+        self.vault_selector_modal.merge(config.vault_selector_modal);
+        self.vault_stats_modal.merge(config.vault_stats_modal);
+        self.diagnostics_modal.merge(config.diagnostics_modal);
+        self.attachments_modal.merge(config.attachments_modal);
+        self.duplicates_modal.merge(config.duplicates_modal);
+        self.note_diff_modal.merge(config.note_diff_modal);
+        self.note_history_modal.merge(config.note_history_modal);
+        self.vault_replace_modal.merge(config.vault_replace_modal);
+        self.canvas_modal.merge(config.canvas_modal);
+        self.tag_browser_modal.merge(config.tag_browser_modal);
+        self.outgoing_links_modal.merge(config.outgoing_links_modal);
+        self.jobs_modal.merge(config.jobs_modal);
         self.clone()
     }
 }
@@ -128,6 +526,17 @@ impl fmt::Display for Config<'_> {
         writeln!(f, "[note_editor]\n{}", self.note_editor)?;
         writeln!(f, "[help_modal]\n{}", self.help_modal)?;
         writeln!(f, "[vault_selector_modal]\n{}", self.vault_selector_modal)?;
+        writeln!(f, "[vault_stats_modal]\n{}", self.vault_stats_modal)?;
+        writeln!(f, "[diagnostics_modal]\n{}", self.diagnostics_modal)?;
+        writeln!(f, "[attachments_modal]\n{}", self.attachments_modal)?;
+        writeln!(f, "[duplicates_modal]\n{}", self.duplicates_modal)?;
+        writeln!(f, "[note_diff_modal]\n{}", self.note_diff_modal)?;
+        writeln!(f, "[note_history_modal]\n{}", self.note_history_modal)?;
+        writeln!(f, "[vault_replace_modal]\n{}", self.vault_replace_modal)?;
+        writeln!(f, "[canvas_modal]\n{}", self.canvas_modal)?;
+        writeln!(f, "[tag_browser_modal]\n{}", self.tag_browser_modal)?;
+        writeln!(f, "[outgoing_links_modal]\n{}", self.outgoing_links_modal)?;
+        writeln!(f, "[jobs_modal]\n{}", self.jobs_modal)?;
 
         Ok(())
     }
@@ -137,6 +546,8 @@ impl<'a> From<BTreeMap<String, Message<'a>>> for ConfigSection<'a> {
     fn from(value: BTreeMap<String, Message<'a>>) -> Self {
         Self {
             key_bindings: value,
+            bindings: BTreeMap::new(),
+            chrome: Chrome::default(),
         }
     }
 }
@@ -151,6 +562,35 @@ impl<'a, const N: usize> From<[(String, Message<'a>); N]> for ConfigSection<'a>
 struct TomlConfigSection {
     #[serde(default)]
     key_bindings: KeyBindings,
+    #[serde(flatten)]
+    chrome: Chrome,
+}
+
+/// Splash section holds the splash screen settings next to the key bindings.
+#[derive(Clone, Debug, PartialEq, Deserialize, Default)]
+struct TomlSplashSection {
+    #[serde(flatten)]
+    settings: SplashConfig,
+    #[serde(flatten)]
+    section: TomlConfigSection,
+}
+
+/// Explorer section holds the explorer settings next to the key bindings.
+#[derive(Clone, Debug, PartialEq, Deserialize, Default)]
+struct TomlExplorerSection {
+    #[serde(flatten)]
+    settings: ExplorerConfig,
+    #[serde(flatten)]
+    section: TomlConfigSection,
+}
+
+/// Outline section holds the outline settings next to the key bindings.
+#[derive(Clone, Debug, PartialEq, Deserialize, Default)]
+struct TomlOutlineSection {
+    #[serde(flatten)]
+    settings: OutlineConfig,
+    #[serde(flatten)]
+    section: TomlConfigSection,
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Default)]
@@ -177,24 +617,133 @@ impl<const N: usize> From<[(Key, Command); N]> for KeyBindings {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Deserialize, Default)]
+fn default_archive_folder() -> String {
+    "Archive/%Y".to_string()
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
 struct TomlConfig {
     #[serde(default)]
     experimental_editor: bool,
     #[serde(default)]
+    keymap_profile: KeymapProfile,
+    #[serde(default = "default_archive_folder")]
+    archive_folder: String,
+    #[serde(default)]
+    text_counts: CountMode,
+    #[serde(default)]
+    count_scope: CountScope,
+    #[serde(default)]
+    date_format: DateFormat,
+    #[serde(default)]
+    macros: BTreeMap<String, Vec<Key>>,
+    #[serde(default)]
+    saved_searches: BTreeMap<String, String>,
+    #[serde(default)]
+    vault_startup: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    open_with: BTreeMap<String, Command>,
+    #[serde(default)]
+    zen_mode: ZenModeConfig,
+    #[serde(default)]
+    editor: EditorConfig,
+    #[serde(default)]
+    reader: ReaderConfig,
+    #[serde(default)]
+    ui: UiConfig,
+    #[serde(default)]
+    publish: PublishConfig,
+    #[serde(default)]
+    attachments: AttachmentsConfig,
+    #[serde(default)]
+    capture: CaptureConfig,
+    #[serde(default)]
+    extract: ExtractConfig,
+    #[serde(default)]
+    timer: TimerConfig,
+    #[serde(default)]
+    quick_switcher: QuickSwitcherConfig,
+    #[serde(default)]
     global: TomlConfigSection,
     #[serde(default)]
-    splash: TomlConfigSection,
+    splash: TomlSplashSection,
     #[serde(default)]
-    explorer: TomlConfigSection,
+    explorer: TomlExplorerSection,
     #[serde(default)]
-    outline: TomlConfigSection,
+    outline: TomlOutlineSection,
     #[serde(default)]
     help_modal: TomlConfigSection,
     #[serde(default)]
     note_editor: TomlConfigSection,
     #[serde(default)]
     vault_selector_modal: TomlConfigSection,
+    #[serde(default)]
+    vault_stats_modal: TomlConfigSection,
+    #[serde(default)]
+    diagnostics_modal: TomlConfigSection,
+    #[serde(default)]
+    attachments_modal: TomlConfigSection,
+    #[serde(default)]
+    duplicates_modal: TomlConfigSection,
+    #[serde(default)]
+    note_diff_modal: TomlConfigSection,
+    #[serde(default)]
+    note_history_modal: TomlConfigSection,
+    #[serde(default)]
+    vault_replace_modal: TomlConfigSection,
+    #[serde(default)]
+    canvas_modal: TomlConfigSection,
+    #[serde(default)]
+    tag_browser_modal: TomlConfigSection,
+    #[serde(default)]
+    outgoing_links_modal: TomlConfigSection,
+    #[serde(default)]
+    jobs_modal: TomlConfigSection,
+}
+
+impl Default for TomlConfig {
+    fn default() -> Self {
+        Self {
+            experimental_editor: false,
+            keymap_profile: KeymapProfile::default(),
+            archive_folder: default_archive_folder(),
+            text_counts: CountMode::default(),
+            count_scope: CountScope::default(),
+            date_format: DateFormat::default(),
+            macros: BTreeMap::new(),
+            saved_searches: BTreeMap::new(),
+            vault_startup: BTreeMap::new(),
+            open_with: BTreeMap::new(),
+            zen_mode: ZenModeConfig::default(),
+            editor: EditorConfig::default(),
+            reader: ReaderConfig::default(),
+            ui: UiConfig::default(),
+            publish: PublishConfig::default(),
+            attachments: AttachmentsConfig::default(),
+            capture: CaptureConfig::default(),
+            extract: ExtractConfig::default(),
+            timer: TimerConfig::default(),
+            quick_switcher: QuickSwitcherConfig::default(),
+            global: TomlConfigSection::default(),
+            splash: TomlSplashSection::default(),
+            explorer: TomlExplorerSection::default(),
+            outline: TomlOutlineSection::default(),
+            help_modal: TomlConfigSection::default(),
+            note_editor: TomlConfigSection::default(),
+            vault_selector_modal: TomlConfigSection::default(),
+            vault_stats_modal: TomlConfigSection::default(),
+            diagnostics_modal: TomlConfigSection::default(),
+            attachments_modal: TomlConfigSection::default(),
+            duplicates_modal: TomlConfigSection::default(),
+            note_diff_modal: TomlConfigSection::default(),
+            note_history_modal: TomlConfigSection::default(),
+            vault_replace_modal: TomlConfigSection::default(),
+            canvas_modal: TomlConfigSection::default(),
+            tag_browser_modal: TomlConfigSection::default(),
+            outgoing_links_modal: TomlConfigSection::default(),
+            jobs_modal: TomlConfigSection::default(),
+        }
+    }
 }
 
 /// Finds and reads the user configuration file in order of priority.
@@ -206,22 +755,221 @@ struct TomlConfig {
 ///
 /// It first attempts to find the config file in the home directory. If not found, it then checks
 /// the config directory.
-fn read_user_config<'a>() -> Result<Config<'a>, ConfigError> {
-    let home_dir_path = home_dir().map(|home_dir| home_dir.join(".basalt.toml"));
-    let config_dir_path =
-        choose_base_strategy().map(|strategy| strategy.config_dir().join("basalt/config.toml"));
+fn user_config_path() -> Result<PathBuf, ConfigError> {
+    let home_dir_path = home_dir()
+        .ok()
+        .map(|home_dir| home_dir.join(".basalt.toml"));
+    let config_dir_path = dirs::config_dir().map(|dir| dir.join("config.toml"));
 
-    let config_path = [home_dir_path, config_dir_path]
+    [home_dir_path, config_dir_path]
         .into_iter()
         .flatten()
         .find(|path| path.exists())
         .ok_or(ConfigError::UserConfigNotFound(
             "Could not find user config".to_string(),
-        ))?;
+        ))
+}
 
-    toml::from_str::<TomlConfig>(&read_to_string(config_path)?)
+/// Appends the key bindings of the profile to the key bindings of the base configuration, so that
+/// the profile bindings take precedence.
+fn layer_key_bindings(base: &mut toml::Table, profile: toml::Table) {
+    for (section, value) in profile {
+        let Some(bindings) = value
+            .get("key_bindings")
+            .and_then(toml::Value::as_array)
+            .cloned()
+        else {
+            continue;
+        };
+
+        let section = base
+            .entry(section)
+            .or_insert_with(|| toml::Table::new().into());
+
+        if let Some(base_bindings) = section
+            .as_table_mut()
+            .map(|table| {
+                table
+                    .entry("key_bindings")
+                    .or_insert(toml::Value::Array(vec![]))
+            })
+            .and_then(toml::Value::as_array_mut)
+        {
+            base_bindings.extend(bindings);
+        }
+    }
+}
+
+/// Layers the table over the base table. Tables are layered key by key and key bindings are
+/// appended, so that the bindings of the layer take precedence. Other values are replaced.
+fn layer_table(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(layer)) => layer_table(base, layer),
+            (Some(toml::Value::Array(base)), toml::Value::Array(layer))
+                if key == "key_bindings" =>
+            {
+                base.extend(layer)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Returns the keymap profile selected in the configuration text, if any. Invalid profiles are
+/// reported by the validation of the user configuration.
+fn keymap_profile(text: &str) -> Option<KeymapProfile> {
+    toml::from_str::<toml::Table>(text)
+        .ok()
+        .and_then(|table| table.get("keymap_profile")?.clone().try_into().ok())
+}
+
+/// Returns the files listed by `include` in the configuration text. An invalid list is reported
+/// by the validation of the user configuration.
+fn includes(text: &str) -> Vec<String> {
+    toml::from_str::<toml::Table>(text)
+        .ok()
+        .and_then(|table| table.get("include")?.clone().try_into().ok())
+        .unwrap_or_default()
+}
+
+/// A file of the user configuration with the name it is reported by. The user configuration file
+/// itself has no name, included files are named by their path relative to it.
+struct UserConfigFile {
+    name: Option<String>,
+    text: String,
+}
+
+impl UserConfigFile {
+    /// Returns the problem in the file, naming the file when it is included.
+    fn problem(&self, line: Option<usize>, message: String) -> ConfigProblem {
+        ConfigProblem {
+            line,
+            section: None,
+            message: match &self.name {
+                Some(name) => format!("{name}: {message}"),
+                None => message,
+            },
+        }
+    }
+}
+
+/// Reads the files included by the file and the files they include, in the order they are
+/// layered: every included file comes before the file including it, so that the including file
+/// takes precedence. `chain` holds the files being read, from the user configuration file on, so
+/// that a file including itself through other files is reported instead of read again.
+fn read_includes(
+    path: &Path,
+    file: &UserConfigFile,
+    root: &Path,
+    chain: &mut Vec<PathBuf>,
+    files: &mut Vec<UserConfigFile>,
+    problems: &mut Vec<ConfigProblem>,
+) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    for include in includes(&file.text) {
+        let line = file
+            .text
+            .find(&format!("\"{include}\""))
+            .map(|offset| file.text[..offset].matches('\n').count() + 1);
+
+        let include_path = match expand::expand(&include) {
+            Ok(expanded) => dir.join(expanded),
+            Err(err) => {
+                problems.push(file.problem(line, format!("include: {err}")));
+                continue;
+            }
+        };
+        let canonical = include_path
+            .canonicalize()
+            .unwrap_or_else(|_| include_path.clone());
+
+        if chain.contains(&canonical) {
+            let cycle = chain
+                .iter()
+                .chain([&canonical])
+                .map(|path| {
+                    path.strip_prefix(root)
+                        .unwrap_or(path)
+                        .display()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(" → ");
+            problems.push(file.problem(
+                line,
+                format!("include: {include} is skipped, it includes itself: {cycle}"),
+            ));
+            continue;
+        }
+
+        match read_to_string(&include_path) {
+            Ok(text) => {
+                let included = UserConfigFile {
+                    name: Some(
+                        include_path
+                            .strip_prefix(root)
+                            .unwrap_or(&include_path)
+                            .display()
+                            .to_string(),
+                    ),
+                    text,
+                };
+
+                chain.push(canonical);
+                read_includes(&include_path, &included, root, chain, files, problems);
+                chain.pop();
+                files.push(included);
+            }
+            Err(err) => problems
+                .push(file.problem(line, format!("include: could not read {include}: {err}"))),
+        }
+    }
+}
+
+/// Validates the files of the user configuration and layers them in order, leaving out the
+/// settings and key bindings with problems, which are returned next to the config.
+fn parse_user_config<'a>(
+    files: &[UserConfigFile],
+    base: &toml::Table,
+) -> (Config<'a>, Vec<ConfigProblem>) {
+    let mut table = toml::Table::new();
+    let mut problems = vec![];
+
+    for file in files {
+        let (layer, file_problems) = validation::validate(&file.text, base);
+        problems.extend(file_problems.into_iter().map(|problem| ConfigProblem {
+            section: problem.section.clone(),
+            ..file.problem(problem.line, problem.message)
+        }));
+        layer_table(&mut table, layer);
+    }
+
+    let config = toml::Value::Table(table)
+        .try_into::<TomlConfig>()
         .map(Config::from)
-        .map_err(ConfigError::from)
+        .unwrap_or_else(|err| {
+            problems.push(ConfigProblem {
+                line: None,
+                section: None,
+                message: err.message().trim().to_string(),
+            });
+            Config::default()
+        });
+
+    (config, problems)
+}
+
+/// Parses the base configuration with the key bindings of the profile.
+fn base_config(profile: KeymapProfile) -> toml::Table {
+    let mut base: toml::Table =
+        toml::from_str(BASE_CONFIGURATION_STR).expect("base configuration is valid");
+    let profile = toml::from_str(profile.key_bindings()).expect("keymap profile is valid");
+    layer_key_bindings(&mut base, profile);
+    base
 }
 
 const BASE_CONFIGURATION_STR: &str =
@@ -231,37 +979,122 @@ const BASE_CONFIGURATION_STR: &str =
 ///
 /// The configuration is built by layering sources with increasing precedence:
 /// 1. Base configuration from embedded config.toml (lowest priority)
-/// 2. User-specific configuration from user's config directory
-/// 3. System overrides (Ctrl+C) that cannot be changed by users (highest priority)
+/// 2. Key bindings of the keymap profile selected in the user configuration
+/// 3. User-specific configuration from user's config directory, with the files it includes layered
+///    before it
+/// 4. System overrides (Ctrl+C) that cannot be changed by users (highest priority)
 ///
 /// # Configuration Precedence
-/// System overrides > User config > Base config
-pub fn load<'a>() -> Result<Config<'a>, ConfigError> {
+/// System overrides > User config > Keymap profile > Base config
+///
+/// Problems in the user configuration are returned in the report, which is `None` when the user
+/// configuration has no problems or does not exist. The problematic parts of the user
+/// configuration are ignored.
+pub fn load<'a>() -> (Config<'a>, Option<ConfigReport>) {
+    let user_config = user_config_path()
+        .ok()
+        .map(|path| (read_to_string(&path), path));
+
+    let (config, problems) = match user_config {
+        Some((Ok(text), path)) => {
+            let (config, problems) = from_user_config_file(&path, text);
+            (config, Some((problems, path)))
+        }
+        Some((Err(err), path)) => (
+            from_user_config(None).0,
+            Some((
+                vec![ConfigProblem {
+                    line: None,
+                    section: None,
+                    message: err.to_string(),
+                }],
+                path,
+            )),
+        ),
+        None => (from_user_config(None).0, None),
+    };
+
+    let report = problems
+        .filter(|(problems, _)| !problems.is_empty())
+        .map(|(problems, path)| ConfigReport { path, problems });
+
+    (config, report)
+}
+
+/// Returns the configuration with the user configuration text layered over the base
+/// configuration, like [`load`] does with the user configuration file, and the problems found in
+/// the user configuration.
+pub fn from_user_config<'a>(text: Option<&str>) -> (Config<'a>, Vec<ConfigProblem>) {
+    let files = text.map(|text| {
+        vec![UserConfigFile {
+            name: None,
+            text: text.to_string(),
+        }]
+    });
+
+    from_user_config_files(files.as_deref(), vec![])
+}
+
+/// Returns the configuration with the user configuration file at the path and the files it
+/// includes layered over the base configuration, and the problems found in them. Included paths
+/// are relative to the file including them.
+pub fn from_user_config_file<'a>(path: &Path, text: String) -> (Config<'a>, Vec<ConfigProblem>) {
+    let file = UserConfigFile { name: None, text };
+    let root = path.parent().unwrap_or(Path::new(""));
+    let mut chain = vec![path.canonicalize().unwrap_or_else(|_| path.to_path_buf())];
+    let (mut files, mut problems) = (vec![], vec![]);
+
+    read_includes(path, &file, root, &mut chain, &mut files, &mut problems);
+    files.push(file);
+
+    from_user_config_files(Some(&files), problems)
+}
+
+fn from_user_config_files<'a>(
+    files: Option<&[UserConfigFile]>,
+    mut problems: Vec<ConfigProblem>,
+) -> (Config<'a>, Vec<ConfigProblem>) {
+    // The profile of the user configuration file is used over the profiles of included files.
+    let profile = files
+        .unwrap_or_default()
+        .iter()
+        .rev()
+        .find_map(|file| keymap_profile(&file.text))
+        .unwrap_or_default();
+
     // TODO: Use compile time toml parsing instead to check the build error during compile time
     // Requires a custom proc-macro workspace crate
-    let mut base_config: Config = toml::from_str::<TomlConfig>(BASE_CONFIGURATION_STR)?.into();
+    let base_table = base_config(profile);
+    let mut base_config: Config = toml::Value::Table(base_table.clone())
+        .try_into::<TomlConfig>()
+        .expect("base configuration is valid")
+        .into();
 
-    // TODO: Parsing errors related to the configuration file should ideally be surfaced as warnings.
-    // This is pending a solution for toast notifications and proper warning/error logging.
-    if let Ok(user_config) = read_user_config() {
+    if let Some(files) = files {
+        let (user_config, user_problems) = parse_user_config(files, &base_table);
         base_config.merge(user_config);
+        problems.extend(user_problems);
     }
 
-    let system_key_binding_overrides: ConfigSection =
+    let mut system_key_binding_overrides: ConfigSection =
         [(Key::CTRL_C.to_string(), Message::Quit)].into();
+    system_key_binding_overrides
+        .bindings
+        .insert(Key::CTRL_C.to_string(), (Key::CTRL_C, Command::Quit).into());
 
     base_config
         .global
         .merge_key_bindings(system_key_binding_overrides);
 
-    Ok(base_config)
+    (base_config, problems)
 }
 
 #[cfg(test)]
 mod tests {
-    use ratatui::crossterm::event::KeyModifiers;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
     use super::*;
+    use crate::explorer;
     // use insta::assert_snapshot;
 
     #[test]
@@ -301,6 +1134,7 @@ mod tests {
                     (Key::from('?'), Command::HelpModalToggle),
                 ]
                 .into(),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -314,4 +1148,179 @@ mod tests {
             expected_config
         );
     }
+
+    #[test]
+    fn test_splash_config() {
+        let dummy_toml = r#"
+        [splash]
+        open_last_vault = true
+        key_bindings = [
+         { key = "enter", command = "splash_open" },
+        ]
+    "#;
+
+        let config: Config = toml::from_str::<TomlConfig>(dummy_toml).unwrap().into();
+
+        assert_eq!(
+            config.splash_screen,
+            SplashConfig {
+                open_last_vault: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            config
+                .splash
+                .key_to_message(Key::from((KeyCode::Enter, KeyModifiers::NONE))),
+            Some(Message::Splash(crate::splash_modal::Message::Open))
+        );
+
+        assert!(toml::from_str::<TomlConfig>(BASE_CONFIGURATION_STR).is_ok());
+    }
+
+    #[test]
+    fn test_editor_config() {
+        let config: Config = toml::from_str::<TomlConfig>("[editor]\ndefault_mode = \"edit\"")
+            .unwrap()
+            .into();
+
+        assert_eq!(config.editor.default_mode, Mode::Edit);
+        assert_eq!(Config::default().editor.default_mode, Mode::Read);
+        assert!(toml::from_str::<TomlConfig>("[editor]\ndefault_mode = \"insert\"").is_err());
+    }
+
+    #[test]
+    fn test_macros() {
+        let user_config = r#"
+        [macros]
+        heading = ["i", "home", "shift+h", "esc"]
+        broken = ["i", "hyperactive+x"]
+        "#;
+
+        let (config, problems) = from_user_config(Some(user_config));
+
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(4));
+        assert_eq!(config.macros.keys().collect::<Vec<_>>(), vec!["heading"]);
+
+        let events: Vec<KeyEvent> = config.macros["heading"]
+            .iter()
+            .map(KeyEvent::from)
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Home, KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT),
+                KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keymap_profiles() {
+        for profile in [KeymapProfile::Emacs, KeymapProfile::Standard] {
+            let (_, problems) =
+                validation::validate(profile.key_bindings(), &base_config(KeymapProfile::Vim));
+            assert_eq!(problems, vec![], "{profile:?}");
+        }
+
+        let base_table = base_config(KeymapProfile::Emacs);
+        let mut config: Config = toml::Value::Table(base_table.clone())
+            .try_into::<TomlConfig>()
+            .unwrap()
+            .into();
+
+        let user_config = r#"
+        keymap_profile = "emacs"
+
+        [explorer]
+        key_bindings = [
+         { key = "ctrl+p", command = "explorer_open" },
+        ]
+        "#;
+
+        assert_eq!(keymap_profile(user_config), Some(KeymapProfile::Emacs));
+
+        let files = [UserConfigFile {
+            name: None,
+            text: user_config.to_string(),
+        }];
+        let (user_config, problems) = parse_user_config(&files, &base_table);
+        assert_eq!(problems, vec![]);
+        config.merge(user_config);
+
+        let ctrl = |c| Key::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert_eq!(
+            config.explorer.key_to_message(ctrl('n')),
+            Some(Message::Explorer(explorer::Message::Down))
+        );
+        assert_eq!(
+            config.explorer.key_to_message(ctrl('p')),
+            Some(Message::Explorer(explorer::Message::Open))
+        );
+        assert_eq!(
+            config
+                .explorer
+                .key_to_message(Key::new(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Some(Message::Explorer(explorer::Message::Down))
+        );
+    }
+
+    #[test]
+    fn test_include() {
+        let dir = std::env::temp_dir().join("basalt-config-include");
+        _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("keymaps")).unwrap();
+
+        let write = |name: &str, text: &str| std::fs::write(dir.join(name), text).unwrap();
+        write(
+            "keymaps/vim.toml",
+            indoc::indoc! {r#"
+                include = ["../config.toml"]
+                archive_folder = "Vim"
+
+                [explorer]
+                key_bindings = [{ key = "x", command = "explorer_down" }]
+                width = 40
+            "#},
+        );
+        write("theme-dark.toml", "[ui]\nhigh_contrast = true\nfancy = 1\n");
+        let text = indoc::indoc! {r#"
+            include = ["keymaps/vim.toml", "theme-dark.toml", "missing.toml"]
+            archive_folder = "Old"
+
+            [explorer]
+            key_bindings = [{ key = "y", command = "explorer_up" }]
+        "#};
+        write("config.toml", text);
+
+        let (config, problems) = from_user_config_file(&dir.join("config.toml"), text.into());
+
+        // The including file takes precedence, the settings and bindings it does not set are
+        // taken from the included files.
+        assert_eq!(config.archive_folder, "Old");
+        assert_eq!(config.explorer_settings.width, Some(40));
+        assert!(config.ui.high_contrast);
+        assert_eq!(
+            config.explorer.key_to_message('x'.into()),
+            Some(Message::Explorer(explorer::Message::Down))
+        );
+        assert_eq!(
+            config.explorer.key_to_message('y'.into()),
+            Some(Message::Explorer(explorer::Message::Up))
+        );
+
+        let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert!(problems[0].starts_with(
+            "line 1: keymaps/vim.toml: include: ../config.toml is skipped, it includes itself"
+        ));
+        assert!(problems[1].starts_with("line 1: include: could not read missing.toml"));
+        assert_eq!(
+            problems[2],
+            "line 3: theme-dark.toml: Unknown setting `fancy` in [ui]"
+        );
+    }
 }
@@ -0,0 +1,125 @@
+//! Expands `~` and environment variables in the paths and commands of the configuration, e.g.
+//! `output_dir = "~/site"` or `{ key = "ctrl+e", command = "exec:$EDITOR %note_path" }`.
+//! Commands run without a shell, so nothing else would expand them.
+use std::env;
+
+use etcetera::home_dir;
+
+/// A variable that could not be expanded.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub(crate) enum ExpandError {
+    #[error("${0} is not set")]
+    Unset(String),
+    #[error("${{{0} is missing the closing }}")]
+    Unclosed(String),
+    #[error("~ cannot be expanded, the home directory was not found")]
+    NoHome,
+}
+
+/// Returns the value with `~` at the start of a word replaced with the home directory, and `$VAR`
+/// and `${VAR}` replaced with the environment variable. `$HOME` falls back to the home directory
+/// of the platform. A `$` that does not start a variable name is kept as is.
+pub(crate) fn expand(value: &str) -> Result<String, ExpandError> {
+    expand_with(value, |name| match env::var(name) {
+        Ok(value) => Some(value),
+        Err(_) if name == "HOME" => home(),
+        Err(_) => None,
+    })
+}
+
+fn home() -> Option<String> {
+    home_dir()
+        .ok()
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn expand_with(value: &str, var: impl Fn(&str) -> Option<String>) -> Result<String, ExpandError> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(c) = rest.chars().next() {
+        let prev = value[..value.len() - rest.len()].chars().next_back();
+        let at_word_start = !matches!(prev, Some(prev) if !prev.is_whitespace());
+        let ends_word = rest[c.len_utf8()..]
+            .chars()
+            .next()
+            .is_none_or(|next| matches!(next, '/' | '\\') || next.is_whitespace());
+
+        match c {
+            '~' if at_word_start && ends_word => {
+                out.push_str(&var("HOME").ok_or(ExpandError::NoHome)?);
+                rest = &rest[1..];
+            }
+            '$' if rest[1..].starts_with('{') => {
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| ExpandError::Unclosed(rest[2..].to_string()))?;
+                let name = &rest[2..end];
+                out.push_str(&var(name).ok_or_else(|| ExpandError::Unset(name.to_string()))?);
+                rest = &rest[end + 1..];
+            }
+            '$' if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') => {
+                let end = rest[1..]
+                    .find(|c| !is_name_char(c))
+                    .map_or(rest.len(), |end| end + 1);
+                let name = &rest[1..end];
+                out.push_str(&var(name).ok_or_else(|| ExpandError::Unset(name.to_string()))?);
+                rest = &rest[end..];
+            }
+            c => {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let var = |name: &str| match name {
+            "HOME" => Some("/home/ada".to_string()),
+            "EDITOR" => Some("hx".to_string()),
+            _ => None,
+        };
+
+        let cases = [
+            ("~/site", Ok("/home/ada/site")),
+            ("~", Ok("/home/ada")),
+            ("$HOME/site", Ok("/home/ada/site")),
+            ("${HOME}site", Ok("/home/adasite")),
+            ("$EDITOR %note_path", Ok("hx %note_path")),
+            ("cp %file ~/inbox", Ok("cp %file /home/ada/inbox")),
+            ("notes~/draft", Ok("notes~/draft")),
+            ("~ada/site", Ok("~ada/site")),
+            ("costs $5 or $", Ok("costs $5 or $")),
+            (
+                "$VISUAL %note_path",
+                Err(ExpandError::Unset("VISUAL".into())),
+            ),
+            ("${EDITOR", Err(ExpandError::Unclosed("EDITOR".into()))),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(
+                expand_with(value, var),
+                expected.map(String::from),
+                "{value}"
+            );
+        }
+
+        assert_eq!(
+            ExpandError::Unset("VISUAL".into()).to_string(),
+            "$VISUAL is not set"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, str::FromStr};
 
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
@@ -52,6 +52,33 @@ impl Key {
     pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
         Self { code, modifiers }
     }
+
+    /// Returns the key as written in the configuration, e.g. `ctrl+alt+k` or `page_down`.
+    pub fn to_config_string(&self) -> String {
+        let modifiers = [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+            (KeyModifiers::SUPER, "super"),
+            (KeyModifiers::HYPER, "hyper"),
+            (KeyModifiers::META, "meta"),
+        ]
+        .into_iter()
+        .filter(|(modifier, _)| self.modifiers.contains(*modifier))
+        .map(|(_, name)| name.to_string());
+
+        let code = match self.code {
+            KeyCode::Char(' ') => "space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::F(n) => format!("f{n}"),
+            KeyCode::Esc => "esc".to_string(),
+            KeyCode::PageUp => "page_up".to_string(),
+            KeyCode::PageDown => "page_down".to_string(),
+            code => code.to_string().to_lowercase().replace(' ', "_"),
+        };
+
+        modifiers.chain([code]).collect::<Vec<_>>().join("+")
+    }
 }
 
 impl From<char> for Key {
@@ -72,6 +99,15 @@ impl From<(char, KeyModifiers)> for Key {
     }
 }
 
+/// Parses the key as written in the configuration, e.g. `ctrl+alt+k` or `esc`.
+impl FromStr for Key {
+    type Err = ConfigError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        KeyVisitor.visit_str(value)
+    }
+}
+
 impl<'de> Deserialize<'de> for Key {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -173,3 +209,17 @@ impl From<&KeyEvent> for Key {
         }
     }
 }
+
+impl From<&Key> for KeyEvent {
+    fn from(key: &Key) -> Self {
+        // Terminals report shifted letters in upper case, which text input relies on.
+        let code = match key.code {
+            KeyCode::Char(char) if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(char.to_ascii_uppercase())
+            }
+            code => code,
+        };
+
+        KeyEvent::new(code, key.modifiers)
+    }
+}
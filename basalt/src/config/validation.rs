@@ -0,0 +1,803 @@
+//! Validates the user configuration against the schema of the known tables and settings. Every
+//! problem is reported with its line number, and the settings and key bindings with problems are
+//! left out so that the rest of the user configuration can still be used.
+use std::{collections::BTreeMap, fmt, ops::Range, path::PathBuf};
+
+use toml::{
+    de::{DeTable, DeValue},
+    Table, Value,
+};
+
+use super::{
+    expand::{expand, ExpandError},
+    key_binding::KeyBinding,
+    Key, TomlConfig,
+};
+
+const KEY_BINDINGS: &str = "key_bindings";
+
+/// Files layered before the file listing them, read when the configuration is loaded.
+const INCLUDE: &str = "include";
+
+/// Settings at the root of the configuration, outside of any table.
+const ROOT_SETTINGS: &[&str] = &[
+    "experimental_editor",
+    "keymap_profile",
+    "archive_folder",
+    "text_counts",
+    "count_scope",
+    "date_format",
+];
+
+/// Tables of named entries, where every setting is an entry, e.g. a macro, and how an entry is
+/// called in problems.
+const NAMED_TABLES: &[(&str, &str)] = &[
+    ("macros", "Macro"),
+    ("saved_searches", "Saved search"),
+    ("vault_startup", "Startup commands of vault"),
+    ("open_with", "Program"),
+];
+
+/// Tables of the configuration and their settings next to the key bindings.
+const SETTINGS: &[(&str, &[&str])] = &[
+    ("zen_mode", &["max_width"]),
+    (
+        "editor",
+        &["line_numbers", "line_numbers_in_read_mode", "default_mode"],
+    ),
+    (
+        "reader",
+        &["max_width", "detect_direction", "sticky_header", "minimap"],
+    ),
+    (
+        "ui",
+        &[
+            "ascii_only",
+            "high_contrast",
+            "announcements",
+            "colors",
+            "scroll_off",
+            "smooth_scroll",
+        ],
+    ),
+    ("publish", &["output_dir", "template"]),
+    ("attachments", &["extensions", "opener"]),
+    ("capture", &["inbox", "timestamp"]),
+    ("extract", &["leave_link"]),
+    ("timer", &["minutes", "on_end"]),
+    ("quick_switcher", &["all_vaults"]),
+    ("splash", &["skip_single_vault", "open_last_vault", "logo"]),
+    ("explorer", &["width"]),
+    (
+        "outline",
+        &["width", "list_items", "tasks", "code_blocks", "max_depth"],
+    ),
+];
+
+/// Settings with a path or a command, where `~` and environment variables are expanded.
+const EXPANDED_SETTINGS: &[(&str, &str)] = &[
+    ("publish", "output_dir"),
+    ("publish", "template"),
+    ("attachments", "opener"),
+    ("timer", "on_end"),
+];
+
+/// Prefixes of the commands that run a program, where `~` and environment variables are expanded
+/// in the rest of the command.
+const PROGRAM_PREFIXES: [&str; 3] = ["exec:", "spawn:", "exec_prompt:"];
+
+/// Prefixes of the commands listed by the `open_with` command.
+const OPEN_WITH_PREFIXES: [&str; 2] = ["exec:", "spawn:"];
+
+/// Expands `~` and environment variables in the command when it runs a program.
+fn expand_command(command: &mut String) -> Result<(), ExpandError> {
+    if let Some(prefix) = PROGRAM_PREFIXES
+        .into_iter()
+        .find(|prefix| command.starts_with(prefix))
+    {
+        *command = format!("{prefix}{}", expand(&command[prefix.len()..])?);
+    }
+    Ok(())
+}
+
+/// Settings of the borders, padding and title alignment, which are accepted in the `[ui]` table and
+/// in the tables with key bindings of the panes and modals.
+const CHROME_SETTINGS: &[&str] = &[
+    "border",
+    "active_border",
+    "border_color",
+    "padding",
+    "title_alignment",
+];
+
+/// Tables with key bindings. Global key bindings are looked up before the bindings of the active
+/// pane.
+const SECTIONS: &[&str] = &[
+    "global",
+    "splash",
+    "explorer",
+    "outline",
+    "help_modal",
+    "note_editor",
+    "vault_selector_modal",
+    "vault_stats_modal",
+    "diagnostics_modal",
+    "attachments_modal",
+    "duplicates_modal",
+    "note_diff_modal",
+    "note_history_modal",
+    "vault_replace_modal",
+    "canvas_modal",
+    "tag_browser_modal",
+    "outgoing_links_modal",
+    "jobs_modal",
+];
+
+/// A problem in the user configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigProblem {
+    /// Line of the problem in the configuration file, starting from 1.
+    pub line: Option<usize>,
+    /// Table of the key bindings the problem is about.
+    pub section: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// The problems found in the user configuration file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigReport {
+    pub path: PathBuf,
+    pub problems: Vec<ConfigProblem>,
+}
+
+/// A key binding and where it comes from. Bindings of the base configuration have no line.
+#[derive(Clone, Debug)]
+struct Binding {
+    key: String,
+    /// The key as written in the configuration.
+    name: String,
+    command: String,
+    line: Option<usize>,
+}
+
+/// Returns the value under the key in the spanned document.
+fn spanned<'a, 'i>(table: &'a DeTable<'i>, key: &str) -> Option<&'a toml::Spanned<DeValue<'i>>> {
+    table
+        .iter()
+        .find(|(name, _)| name.get_ref().as_ref() == key)
+        .map(|(_, value)| value)
+}
+
+fn key_span(table: &DeTable, key: &str) -> Option<Range<usize>> {
+    table
+        .iter()
+        .find(|(name, _)| name.get_ref().as_ref() == key)
+        .map(|(name, _)| name.span())
+}
+
+/// Returns `true` if the value alone deserializes into the configuration at the given path, e.g.
+/// `default_mode` under `editor`.
+fn check_value(path: &[&str], value: &Value) -> Result<(), String> {
+    let value = path.iter().rev().fold(value.clone(), |value, key| {
+        Value::Table(Table::from_iter([(key.to_string(), value)]))
+    });
+
+    value
+        .try_into::<TomlConfig>()
+        .map(|_| ())
+        .map_err(|err| err.message().trim().to_string())
+}
+
+struct Validator<'t> {
+    text: &'t str,
+    problems: Vec<ConfigProblem>,
+}
+
+impl Validator<'_> {
+    fn line(&self, span: Option<Range<usize>>) -> Option<usize> {
+        span.map(|span| {
+            self.text[..span.start.min(self.text.len())]
+                .matches('\n')
+                .count()
+                + 1
+        })
+    }
+
+    fn report(&mut self, span: Option<Range<usize>>, message: String) {
+        let line = self.line(span);
+        self.problems.push(ConfigProblem {
+            line,
+            section: None,
+            message,
+        });
+    }
+
+    fn report_binding(&mut self, section: &str, span: Option<Range<usize>>, message: String) {
+        let line = self.line(span);
+        self.problems.push(ConfigProblem {
+            line,
+            section: Some(section.to_string()),
+            message,
+        });
+    }
+
+    /// Validates the key bindings of the section and removes the invalid ones.
+    fn key_bindings(
+        &mut self,
+        section: &str,
+        value: &mut Value,
+        spans: Option<&DeValue>,
+    ) -> Vec<Binding> {
+        let span_of = |index: usize| {
+            spans
+                .and_then(DeValue::as_array)
+                .and_then(|array| array.get(index))
+                .map(|entry| entry.span())
+        };
+
+        let Value::Array(entries) = value else {
+            self.report_binding(
+                section,
+                span_of(0),
+                format!("key_bindings in [{section}] must be a list"),
+            );
+            *value = Value::Array(vec![]);
+            return vec![];
+        };
+
+        let mut bindings = vec![];
+        let mut valid = vec![];
+
+        for (i, entry) in entries.iter_mut().enumerate() {
+            let span = span_of(i);
+
+            if let Some(Value::String(command)) = entry.get_mut("command") {
+                if let Err(err) = expand_command(command) {
+                    self.report_binding(
+                        section,
+                        span,
+                        format!("Command `{command}` in [{section}]: {err}"),
+                    );
+                    valid.push(false);
+                    continue;
+                }
+            }
+
+            if let Some(table) = entry.as_table() {
+                for field in table
+                    .keys()
+                    .filter(|field| !["key", "command"].contains(&field.as_str()))
+                {
+                    self.report_binding(
+                        section,
+                        span.clone(),
+                        format!("Unknown field `{field}` in key binding of [{section}]"),
+                    );
+                }
+            }
+
+            match entry.clone().try_into::<KeyBinding>() {
+                Ok(binding) => {
+                    bindings.push(Binding {
+                        key: binding.key.to_string(),
+                        name: entry
+                            .get("key")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        command: entry
+                            .get("command")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        line: self.line(span),
+                    });
+                    valid.push(true);
+                }
+                Err(err) => {
+                    let key = entry.get("key").and_then(Value::as_str).unwrap_or_default();
+                    self.report_binding(
+                        section,
+                        span,
+                        format!(
+                            "Invalid key binding `{key}` in [{section}]: {}",
+                            err.message().trim()
+                        ),
+                    );
+                    valid.push(false);
+                }
+            }
+        }
+
+        let mut valid = valid.into_iter();
+        entries.retain(|_| valid.next().unwrap_or_default());
+
+        bindings
+    }
+
+    /// Reports keys bound more than once in the same section, and bindings that are never used
+    /// because a global binding takes precedence. Only bindings of the user configuration are
+    /// reported.
+    fn conflicts(
+        &mut self,
+        base: &BTreeMap<String, Vec<Binding>>,
+        user: &BTreeMap<String, Vec<Binding>>,
+    ) {
+        let mut effective: BTreeMap<&str, BTreeMap<String, Binding>> = BTreeMap::new();
+
+        for (section, bindings) in base.iter().chain(user) {
+            let section_bindings = effective.entry(section).or_default();
+            for binding in bindings {
+                section_bindings.insert(binding.key.clone(), binding.clone());
+            }
+        }
+
+        for (section, bindings) in user {
+            let mut seen: BTreeMap<&str, &Binding> = BTreeMap::new();
+            for binding in bindings {
+                if let Some(previous) = seen.insert(&binding.key, binding) {
+                    self.problems.push(ConfigProblem {
+                        line: previous.line,
+                        section: Some(section.clone()),
+                        message: format!(
+                            "`{}` is bound again in [{section}] on line {}, which is used instead",
+                            binding.name,
+                            binding.line.unwrap_or_default()
+                        ),
+                    });
+                }
+            }
+        }
+
+        let Some(global) = effective.get("global") else {
+            return;
+        };
+
+        for (section, bindings) in effective
+            .iter()
+            .filter(|(section, _)| **section != "global")
+        {
+            for (key, binding) in bindings {
+                let Some(global_binding) = global.get(key) else {
+                    continue;
+                };
+
+                if global_binding.command == binding.command {
+                    continue;
+                }
+
+                let (line, message) = match (binding.line, global_binding.line) {
+                    (Some(line), _) => (
+                        line,
+                        format!(
+                            "`{}` in [{section}] is never used, [global] binds it to {}",
+                            binding.name, global_binding.command
+                        ),
+                    ),
+                    (None, Some(line)) => (
+                        line,
+                        format!(
+                            "`{}` in [global] hides {} in [{section}]",
+                            global_binding.name, binding.command
+                        ),
+                    ),
+                    (None, None) => continue,
+                };
+
+                self.problems.push(ConfigProblem {
+                    line: Some(line),
+                    section: Some(section.to_string()),
+                    message,
+                });
+            }
+        }
+    }
+}
+
+/// Collects the key bindings of the sections in the configuration, which is expected to be valid.
+fn collect_bindings(table: &Table) -> BTreeMap<String, Vec<Binding>> {
+    SECTIONS
+        .iter()
+        .filter_map(|section| {
+            let entries = table.get(*section)?.get(KEY_BINDINGS)?.as_array()?;
+            let bindings = entries
+                .iter()
+                .filter_map(|entry| {
+                    let binding = entry.clone().try_into::<KeyBinding>().ok()?;
+                    Some(Binding {
+                        key: binding.key.to_string(),
+                        name: entry.get("key")?.as_str()?.to_string(),
+                        command: entry.get("command")?.as_str()?.to_string(),
+                        line: None,
+                    })
+                })
+                .collect();
+
+            Some((section.to_string(), bindings))
+        })
+        .collect()
+}
+
+/// Validates the user configuration. Returns the user configuration without the settings and key
+/// bindings that have problems, and the problems. The configuration is empty when it is not valid
+/// TOML.
+///
+/// The base configuration is used to find user key bindings that conflict with the default key
+/// bindings.
+pub(crate) fn validate(text: &str, base: &Table) -> (Table, Vec<ConfigProblem>) {
+    let mut validator = Validator {
+        text,
+        problems: vec![],
+    };
+
+    let (document, errors) = DeTable::parse_recoverable(text);
+    let mut table = match toml::from_str::<Table>(text) {
+        Ok(table) if errors.is_empty() => table,
+        result => {
+            for err in errors {
+                validator.report(err.span(), err.message().trim().to_string());
+            }
+
+            if let Err(err) = result {
+                if validator.problems.is_empty() {
+                    validator.report(err.span(), err.message().trim().to_string());
+                }
+            }
+
+            return (Table::new(), validator.problems);
+        }
+    };
+
+    let document = document.get_ref();
+    let mut user_bindings = BTreeMap::new();
+
+    for key in table.keys().cloned().collect::<Vec<_>>() {
+        let span = key_span(document, &key);
+
+        if key == INCLUDE {
+            if table[&key].clone().try_into::<Vec<String>>().is_err() {
+                validator.report(span, format!("{key}: expected a list of file paths"));
+            }
+            table.remove(&key);
+            continue;
+        }
+
+        if ROOT_SETTINGS.contains(&key.as_str()) {
+            if let Err(err) = check_value(
+                &[],
+                &Value::Table(Table::from_iter([(key.clone(), table[&key].clone())])),
+            ) {
+                validator.report(span, format!("{key}: {err}"));
+                table.remove(&key);
+            }
+            continue;
+        }
+
+        if let Some((_, entry)) = NAMED_TABLES.iter().find(|(name, _)| *name == key) {
+            let Some(entries) = table.get_mut(&key).and_then(Value::as_table_mut) else {
+                validator.report(span, format!("[{key}] must be a table"));
+                table.remove(&key);
+                continue;
+            };
+
+            let entry_spans = spanned(document, &key).and_then(|value| value.get_ref().as_table());
+
+            for name in entries.keys().cloned().collect::<Vec<_>>() {
+                let span = entry_spans.and_then(|spans| key_span(spans, &name));
+
+                if let Err(err) = check_value(&[&key, &name], &entries[&name]) {
+                    validator.report(span, format!("{entry} {name}: {err}"));
+                    entries.remove(&name);
+                    continue;
+                }
+
+                let problem = match (key.as_str(), entries.get_mut(&name)) {
+                    ("vault_startup", Some(Value::Array(commands))) => commands
+                        .iter_mut()
+                        .filter_map(|command| match command {
+                            Value::String(command) => expand_command(command).err(),
+                            _ => None,
+                        })
+                        .map(|err| err.to_string())
+                        .next(),
+                    ("open_with", Some(Value::String(command))) => {
+                        match OPEN_WITH_PREFIXES
+                            .iter()
+                            .any(|prefix| command.starts_with(prefix))
+                        {
+                            true => expand_command(command).err().map(|err| err.to_string()),
+                            false => Some(format!("`{command}` must start with exec: or spawn:")),
+                        }
+                    }
+                    _ => None,
+                };
+
+                if let Some(problem) = problem {
+                    validator.report(span, format!("{entry} {name}: {problem}"));
+                    entries.remove(&name);
+                }
+            }
+            continue;
+        }
+
+        let settings = SETTINGS
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, settings)| *settings);
+        let has_bindings = SECTIONS.contains(&key.as_str());
+
+        if settings.is_none() && !has_bindings {
+            let message = match table[&key].is_table() {
+                true => format!("Unknown table [{key}]"),
+                false => format!("Unknown setting `{key}`"),
+            };
+            validator.report(span, message);
+            table.remove(&key);
+            continue;
+        }
+
+        let Some(section) = table.get_mut(&key).and_then(Value::as_table_mut) else {
+            validator.report(span, format!("[{key}] must be a table"));
+            table.remove(&key);
+            continue;
+        };
+
+        let section_spans = spanned(document, &key).and_then(|value| value.get_ref().as_table());
+
+        for field in section.keys().cloned().collect::<Vec<_>>() {
+            let field_span = section_spans.and_then(|spans| key_span(spans, &field));
+
+            if field == KEY_BINDINGS && has_bindings {
+                let spans = section_spans
+                    .and_then(|spans| spanned(spans, KEY_BINDINGS))
+                    .map(|value| value.get_ref());
+                let bindings = validator.key_bindings(&key, &mut section[&field], spans);
+                user_bindings.insert(key.clone(), bindings);
+                continue;
+            }
+
+            let is_chrome = (key == "ui" || has_bindings && key != "global")
+                && CHROME_SETTINGS.contains(&field.as_str());
+
+            if !is_chrome && !settings.is_some_and(|settings| settings.contains(&field.as_str())) {
+                validator.report(field_span, format!("Unknown setting `{field}` in [{key}]"));
+                section.remove(&field);
+                continue;
+            }
+
+            if let Err(err) = check_value(&[&key, &field], &section[&field]) {
+                validator.report(field_span, format!("{field} in [{key}]: {err}"));
+                section.remove(&field);
+                continue;
+            }
+
+            if EXPANDED_SETTINGS.contains(&(key.as_str(), field.as_str())) {
+                match section[&field].as_str().map(expand) {
+                    Some(Ok(value)) => section[&field] = Value::String(value),
+                    Some(Err(err)) => {
+                        validator.report(field_span, format!("{field} in [{key}]: {err}"));
+                        section.remove(&field);
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    let mut base_bindings = collect_bindings(base);
+    base_bindings
+        .entry("global".into())
+        .or_default()
+        .push(Binding {
+            key: Key::CTRL_C.to_string(),
+            name: "ctrl+c".into(),
+            command: "quit".into(),
+            line: None,
+        });
+
+    validator.conflicts(&base_bindings, &user_bindings);
+
+    validator
+        .problems
+        .sort_by_key(|problem| problem.line.unwrap_or_default());
+
+    (table, validator.problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::{chrome::Border, config::BASE_CONFIGURATION_STR};
+
+    fn base() -> Table {
+        toml::from_str(BASE_CONFIGURATION_STR).unwrap()
+    }
+
+    #[test]
+    fn test_validate_base_config() {
+        let (_, problems) = validate(BASE_CONFIGURATION_STR, &base());
+        assert_eq!(problems, vec![]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let text = indoc! {r#"
+            experimental_editor = "yes"
+            archive_folder = "Old"
+
+            [editor]
+            default_mode = "insert"
+            line_numbers = "absolute"
+            fancy = true
+
+            [explorer]
+            key_bindings = [
+             { key = "ctrl+shift+ö", command = "explorer_up" },
+             { key = "hyperactive+x", command = "explorer_up" },
+             { key = "x", command = "explorer_jump" },
+             { key = "k", command = "explorer_down" },
+             { key = "k", command = "explorer_up" },
+             { key = "q", command = "explorer_up" },
+            ]
+
+            [explorr]
+            width = 10
+        "#};
+
+        let (table, problems) = validate(text, &base());
+
+        let lines: Vec<_> = problems
+            .iter()
+            .map(|problem| problem.line.unwrap_or_default())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![1, 5, 7, 11, 12, 13, 14, 16, 19],
+            "{problems:#?}"
+        );
+
+        let config = Value::Table(table).try_into::<TomlConfig>().unwrap();
+        assert_eq!(config.archive_folder, "Old");
+        assert_eq!(
+            config.editor.line_numbers,
+            crate::note_editor::LineNumbers::Absolute
+        );
+        assert_eq!(config.explorer.section.key_bindings.as_ref().len(), 3);
+    }
+
+    #[test]
+    fn test_validate_chrome() {
+        let text = indoc! {r#"
+            [ui]
+            border = "plain"
+
+            [explorer]
+            border = "double"
+            padding = "wide"
+
+            [global]
+            border = "none"
+        "#};
+
+        let (table, problems) = validate(text, &base());
+
+        let lines: Vec<_> = problems
+            .iter()
+            .map(|problem| problem.line.unwrap_or_default())
+            .collect();
+        assert_eq!(lines, vec![6, 9], "{problems:#?}");
+
+        let config = Value::Table(table).try_into::<TomlConfig>().unwrap();
+        assert_eq!(config.ui.chrome.border, Some(Border::Plain));
+        assert_eq!(config.explorer.section.chrome.border, Some(Border::Double));
+    }
+
+    #[test]
+    fn test_validate_syntax_error() {
+        let (table, problems) = validate("[editor]\nline_numbers = ", &base());
+
+        assert!(table.is_empty());
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_validate_expand() {
+        let text = indoc! {r#"
+            [publish]
+            output_dir = "~/site"
+            template = "$BASALT_TESTING_UNSET/page.html"
+
+            [global]
+            key_bindings = [
+             { key = "ctrl+e", command = "exec:vi ${HOME}/todo.md" },
+             { key = "ctrl+o", command = "spawn:open $BASALT_TESTING_UNSET" },
+            ]
+
+            [vault_startup]
+            Notes = ["spawn:git -C ~/notes pull"]
+        "#};
+
+        let (table, problems) = validate(text, &base());
+
+        let problems: Vec<_> = problems
+            .iter()
+            .map(|problem| (problem.line.unwrap_or_default(), problem.message.as_str()))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    3,
+                    "template in [publish]: $BASALT_TESTING_UNSET is not set"
+                ),
+                (
+                    8,
+                    "Command `spawn:open $BASALT_TESTING_UNSET` in [global]: $BASALT_TESTING_UNSET is not set"
+                ),
+            ]
+        );
+
+        let home = expand("~").unwrap();
+        assert_eq!(
+            table["publish"]["output_dir"].as_str(),
+            Some(format!("{home}/site").as_str())
+        );
+        assert!(table["publish"].get("template").is_none());
+        assert_eq!(
+            table["global"]["key_bindings"][0]["command"].as_str(),
+            Some(format!("exec:vi {home}/todo.md").as_str())
+        );
+        assert_eq!(
+            table["global"]["key_bindings"].as_array().map(Vec::len),
+            Some(1)
+        );
+        assert_eq!(
+            table["vault_startup"]["Notes"][0].as_str(),
+            Some(format!("spawn:git -C {home}/notes pull").as_str())
+        );
+    }
+
+    #[test]
+    fn test_validate_open_with() {
+        let text = indoc! {r#"
+            [open_with]
+            "VS Code" = "spawn:code ~/notes %note_path"
+            Helix = "exec:hx %note_path"
+            Quit = "quit"
+            Pager = ["exec:less", "%note_path"]
+        "#};
+
+        let (table, problems) = validate(text, &base());
+
+        let problems: Vec<_> = problems
+            .iter()
+            .map(|problem| (problem.line.unwrap_or_default(), problem.message.as_str()))
+            .collect();
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        assert_eq!(
+            problems[0],
+            (4, "Program Quit: `quit` must start with exec: or spawn:")
+        );
+        assert_eq!(problems[1].0, 5);
+
+        let home = expand("~").unwrap();
+        assert_eq!(
+            table["open_with"]["VS Code"].as_str(),
+            Some(format!("spawn:code {home}/notes %note_path").as_str())
+        );
+        assert!(table["open_with"].get("Helix").is_some());
+        assert!(table["open_with"].get("Quit").is_none());
+    }
+}
@@ -0,0 +1,141 @@
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Clear, Padding, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
+        Widget, Wrap,
+    },
+};
+
+use crate::{app::Message as AppMessage, chrome::Chrome, config::ConfigReport, responsive};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Close,
+    ScrollUp,
+    ScrollDown,
+}
+
+pub fn update<'a>(message: &Message, state: &mut ConfigReportModalState) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Close => state.visible = false,
+        Message::ScrollUp => state.scroll = state.scroll.saturating_sub(1),
+        Message::ScrollDown => {
+            state.scroll = state
+                .scroll
+                .saturating_add(1)
+                .min(state.report.problems.len().saturating_sub(1))
+        }
+    };
+
+    None
+}
+
+/// Config report modal key mappings cannot be modified through configuration, as the report is
+/// shown when the configuration has problems.
+pub fn handle_event(key: &KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => Some(Message::Close),
+        KeyCode::Char('k') | KeyCode::Up => Some(Message::ScrollUp),
+        KeyCode::Char('j') | KeyCode::Down => Some(Message::ScrollDown),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ConfigReportModalState {
+    pub(crate) report: ConfigReport,
+    pub(crate) scroll: usize,
+    pub(crate) visible: bool,
+}
+
+impl ConfigReportModalState {
+    /// Shows the report of the problems in the user configuration.
+    pub fn new(report: ConfigReport) -> Self {
+        Self {
+            report,
+            scroll: 0,
+            visible: true,
+        }
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Length(83), Constraint::Percentage(50))
+}
+
+#[derive(Default)]
+pub struct ConfigReportModal {
+    chrome: Chrome,
+}
+
+impl ConfigReportModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for ConfigReportModal {
+    type State = ConfigReportModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let problems = &state.report.problems;
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::uniform(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(" {} ", state.report.path.display()))
+            .title(
+                Line::from(match problems.len() {
+                    1 => " 1 problem ".to_string(),
+                    count => format!(" {count} problems "),
+                })
+                .alignment(Alignment::Right),
+            )
+            .title_bottom(Line::from(" (j/k) scroll · (esc) close ").alignment(Alignment::Right));
+
+        let mut lines = vec![
+            Line::from("The parts of the configuration with problems are ignored.").italic(),
+            Line::default(),
+        ];
+
+        lines.extend(problems.iter().skip(state.scroll).map(|problem| {
+            let line = match problem.line {
+                Some(line) => format!("Line {line:<5}"),
+                None => format!("{:<10}", ""),
+            };
+
+            Line::from(vec![
+                Span::from(line).dark_gray(),
+                Span::from(problem.message.clone()).fg(Color::default()),
+            ])
+        }));
+
+        let area = modal_area(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(
+            Paragraph::new(lines)
+                .wrap(Wrap::default())
+                .block(block)
+                .fg(Color::default()),
+            area,
+            buf,
+        );
+
+        StatefulWidget::render(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area,
+            buf,
+            &mut ScrollbarState::new(problems.len()).position(state.scroll),
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Crash reports. The panic hook restores the terminal before anything is printed, so a panic does
+//! not leave the terminal in raw mode or on the alternate screen, and writes a report with the
+//! backtrace and the last handled messages to the cache directory. Panics of background threads
+//! are left to the threads, which report them as errors while the interface keeps running.
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    collections::VecDeque,
+    fmt::{self, Debug, Display, Write},
+    fs,
+    io::{self, stdout},
+    panic,
+    path::PathBuf,
+    sync::Mutex,
+    thread,
+};
+
+use ratatui::crossterm::{cursor::Show, event::DisableBracketedPaste, execute};
+
+use crate::dirs;
+
+/// Number of the last handled messages kept for the crash report.
+const RECENT_MESSAGES: usize = 50;
+
+/// Messages longer than this, e.g. ones carrying the note content, are cut in the crash report.
+const MAX_MESSAGE_LEN: usize = 200;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Writer that stops formatting once the limit is reached, so large messages are not formatted
+/// in full only to be cut.
+struct Truncated {
+    text: String,
+    len: usize,
+    limit: usize,
+}
+
+impl Write for Truncated {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.len == self.limit {
+                self.text.push('…');
+                return Err(fmt::Error);
+            }
+            self.text.push(c);
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the debug representation of the message, cut after `limit` characters.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::crash;
+///
+/// assert_eq!(crash::truncated_debug(&Some("note"), 20), "Some(\"note\")");
+/// assert_eq!(crash::truncated_debug(&Some("note"), 6), "Some(\"…");
+/// ```
+pub fn truncated_debug(message: &impl Debug, limit: usize) -> String {
+    let mut writer = Truncated {
+        text: String::new(),
+        len: 0,
+        limit,
+    };
+    _ = write!(writer, "{message:?}");
+    writer.text
+}
+
+/// Records the handled message for the crash report. Only the last [`RECENT_MESSAGES`] messages
+/// are kept.
+pub fn record(message: &impl Debug) {
+    let Ok(mut recent) = RECENT.try_lock() else {
+        return;
+    };
+
+    if recent.len() == RECENT_MESSAGES {
+        recent.pop_front();
+    }
+    recent.push_back(truncated_debug(message, MAX_MESSAGE_LEN));
+}
+
+/// Returns the crash report of the panic, with the backtrace and the last handled messages, the
+/// oldest first.
+pub fn report(panic: &dyn Display, backtrace: &dyn Display, messages: &[String]) -> String {
+    let messages = match messages {
+        [] => "  (none)".to_string(),
+        messages => messages
+            .iter()
+            .map(|message| format!("  {message}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+
+    format!(
+        "basalt {} crashed\n\n{panic}\n\nBacktrace:\n{backtrace}\n\nRecent messages:\n{messages}\n",
+        env!("CARGO_PKG_VERSION"),
+    )
+}
+
+fn report_path() -> Option<PathBuf> {
+    let time = chrono::Local::now().format("%Y%m%d-%H%M%S");
+
+    dirs::cache_dir().map(|dir| dir.join(format!("crash/crash-{time}.txt")))
+}
+
+fn write_report(report: &str) -> io::Result<PathBuf> {
+    let path = report_path().ok_or(io::Error::from(io::ErrorKind::NotFound))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+/// Leaves the alternate screen, disables raw mode and bracketed paste, and shows the cursor.
+fn restore_terminal() {
+    _ = execute!(stdout(), DisableBracketedPaste, Show);
+    ratatui::restore();
+}
+
+/// Returns the message the panic was raised with, as given to [`panic::catch_unwind`].
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::crash;
+///
+/// let payload = std::panic::catch_unwind(|| panic!("index out of bounds")).unwrap_err();
+/// assert_eq!(crash::panic_message(payload.as_ref()), "index out of bounds");
+/// ```
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Installs a panic hook that restores the terminal, prints the panic with the previous hook and
+/// writes the crash report, printing its path. Only panics of the thread that installs the hook
+/// are handled, panics of other threads are not printed over the interface.
+pub fn install_panic_hook() {
+    let hook = panic::take_hook();
+    let main = thread::current().id();
+
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().id() != main {
+            return;
+        }
+
+        restore_terminal();
+        hook(info);
+
+        let messages: Vec<String> = RECENT
+            .try_lock()
+            .map(|recent| recent.iter().cloned().collect())
+            .unwrap_or_default();
+
+        let report = report(info, &Backtrace::force_capture(), &messages);
+
+        match write_report(&report) {
+            Ok(path) => eprintln!("A crash report was written to {}", path.display()),
+            Err(err) => eprintln!("Could not write a crash report: {err}"),
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let report = report(
+            &"panicked at src/app.rs:1:1:\nindex out of bounds",
+            &"   0: basalt_tui::app::App::update",
+            &["Quit".to_string()],
+        );
+
+        assert_eq!(
+            report.replace(env!("CARGO_PKG_VERSION"), "x.y.z"),
+            indoc! {"
+                basalt x.y.z crashed
+
+                panicked at src/app.rs:1:1:
+                index out of bounds
+
+                Backtrace:
+                   0: basalt_tui::app::App::update
+
+                Recent messages:
+                  Quit
+            "}
+        );
+    }
+}
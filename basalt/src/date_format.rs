@@ -0,0 +1,175 @@
+//! Date formatting for the dates that are written to notes and shown in the interface. The
+//! features that work with dates format them here, so that the `date_format` setting applies to
+//! all of them.
+use std::{env, fmt::Write};
+
+use chrono::{format::StrftimeItems, DateTime, Local, TimeZone};
+use serde::Deserialize;
+
+/// The ISO 8601 date format, e.g. `2025-03-07`.
+const ISO: &str = "%Y-%m-%d";
+
+/// Format of the timestamps in file and folder names. The format is fixed, so that the names sort
+/// in chronological order and contain no path separators.
+const TIMESTAMP: &str = "%Y%m%d-%H%M%S";
+
+/// Expands the date format specifiers in the text, e.g. `Archive/%Y` to `Archive/2025`. The text
+/// is returned as is if it contains invalid specifiers.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::date_format;
+/// use chrono::{TimeZone, Utc};
+///
+/// let date = Utc.with_ymd_and_hms(2025, 3, 7, 9, 30, 0).unwrap();
+///
+/// assert_eq!(date_format::expand("Archive/%Y", &date), "Archive/2025");
+/// assert_eq!(date_format::expand("Archive/%Q", &date), "Archive/%Q");
+/// ```
+pub fn expand<Tz: TimeZone>(text: &str, datetime: &DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let mut expanded = String::new();
+    match write!(expanded, "{}", datetime.format(text)) {
+        Ok(_) => expanded,
+        Err(_) => text.to_string(),
+    }
+}
+
+/// Returns the current local time as a timestamp for file and folder names, e.g.
+/// `20250307-093000`.
+pub fn timestamp() -> String {
+    expand(TIMESTAMP, &Local::now())
+}
+
+/// Returns the locale of the dates from the environment, following the precedence of `LC_ALL`,
+/// `LC_TIME` and `LANG`.
+fn locale() -> Option<String> {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Returns the conventional numeric date format of the locale, e.g. `en_US.UTF-8` or `de_DE`.
+/// Locales that are not known, and the `C` and `POSIX` locales, use the ISO 8601 format.
+fn locale_format(locale: &str) -> &'static str {
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let (language, territory) = locale.split_once(['_', '-']).unwrap_or((locale, ""));
+
+    match (language, territory) {
+        ("en", "US" | "PH") => "%m/%d/%Y",
+        ("en", "CA" | "ZA") => ISO,
+        ("en" | "fr" | "es" | "it" | "pt" | "el" | "ga" | "ca" | "vi" | "id", _) => "%d/%m/%Y",
+        ("de" | "ru" | "pl" | "cs" | "sk" | "fi" | "nb" | "nn" | "da" | "tr" | "uk" | "ro", _) => {
+            "%d.%m.%Y"
+        }
+        ("nl", _) => "%d-%m-%Y",
+        ("ja" | "zh", _) => "%Y/%m/%d",
+        ("ko" | "hu", _) => "%Y. %m. %d.",
+        _ => ISO,
+    }
+}
+
+/// Format of the dates, given as date format specifiers like `%d.%m.%Y`, `"iso"` for
+/// `%Y-%m-%d` or `"locale"` for the numeric date format of the locale of the environment.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct DateFormat(String);
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        Self(ISO.to_string())
+    }
+}
+
+impl TryFrom<String> for DateFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "iso" => Ok(Self::default()),
+            "locale" => Ok(Self(
+                locale()
+                    .map_or(ISO, |locale| locale_format(&locale))
+                    .to_string(),
+            )),
+            _ if StrftimeItems::new(&value).parse().is_err() => {
+                Err(format!("invalid date format `{value}`"))
+            }
+            _ => Ok(Self(value)),
+        }
+    }
+}
+
+impl DateFormat {
+    /// Returns the date format specifiers of the format.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Formats the date.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::date_format::DateFormat;
+    /// use chrono::{TimeZone, Utc};
+    ///
+    /// let date = Utc.with_ymd_and_hms(2025, 3, 7, 9, 30, 0).unwrap();
+    /// let format = DateFormat::try_from("%d.%m.%Y".to_string()).unwrap();
+    ///
+    /// assert_eq!(DateFormat::default().format(&date), "2025-03-07");
+    /// assert_eq!(format.format(&date), "07.03.2025");
+    /// ```
+    pub fn format<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        expand(&self.0, datetime)
+    }
+
+    /// Formats the current local date.
+    pub fn today(&self) -> String {
+        self.format(&Local::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_format() {
+        let tests = [
+            ("en_US.UTF-8", "%m/%d/%Y"),
+            ("en_GB.UTF-8", "%d/%m/%Y"),
+            ("en_CA", "%Y-%m-%d"),
+            ("de_DE@euro", "%d.%m.%Y"),
+            ("fi-FI", "%d.%m.%Y"),
+            ("ja_JP.UTF-8", "%Y/%m/%d"),
+            ("sv_SE", "%Y-%m-%d"),
+            ("C", "%Y-%m-%d"),
+            ("POSIX", "%Y-%m-%d"),
+        ];
+
+        tests.into_iter().for_each(|(locale, expected)| {
+            assert_eq!(locale_format(locale), expected, "With locale {locale}");
+        });
+    }
+
+    #[test]
+    fn test_date_format() {
+        assert_eq!(
+            DateFormat::try_from("iso".to_string()),
+            Ok(DateFormat::default())
+        );
+        assert_eq!(
+            DateFormat::try_from("%A, %B %-d".to_string()).map(|format| format.0),
+            Ok("%A, %B %-d".to_string())
+        );
+        assert!(DateFormat::try_from("%Y-%Q".to_string()).is_err());
+    }
+}
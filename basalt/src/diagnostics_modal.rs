@@ -0,0 +1,325 @@
+use std::path::{Path, PathBuf};
+
+use basalt_core::obsidian::{Link, LinkIndex, LinkKind, Note, Vault};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{app::Message as AppMessage, background::Job, chrome::Chrome, responsive};
+
+/// A problem found in the links of a vault.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Diagnostic {
+    /// A link in `note` whose target does not exist. `new_file` is the path where the missing
+    /// note is created, or `None` if the target is outside the vault.
+    BrokenLink {
+        note: Note,
+        link: Link,
+        new_file: Option<PathBuf>,
+    },
+    /// A note without any incoming or outgoing links.
+    Orphan(Note),
+}
+
+impl Diagnostic {
+    /// Collects the broken links and orphan notes of the index, broken links first.
+    pub fn collect(index: &LinkIndex) -> Vec<Diagnostic> {
+        let broken_links =
+            index
+                .broken_links()
+                .into_iter()
+                .map(|(note, link)| Diagnostic::BrokenLink {
+                    note: Note::from(note),
+                    link: link.clone(),
+                    new_file: index.new_file_path(note, link),
+                });
+
+        let orphans = index
+            .orphans()
+            .into_iter()
+            .map(|note| Diagnostic::Orphan(Note::from(note)));
+
+        broken_links.chain(orphans).collect()
+    }
+
+    fn note(&self) -> &Note {
+        match self {
+            Diagnostic::BrokenLink { note, .. } | Diagnostic::Orphan(note) => note,
+        }
+    }
+
+    /// Byte offset of the offending link in the note.
+    fn offset(&self) -> usize {
+        match self {
+            Diagnostic::BrokenLink { link, .. } => link.range.start,
+            Diagnostic::Orphan(_) => 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    Close,
+    Up,
+    Down,
+    Open,
+    CreateNote,
+    Refresh,
+    Progress(usize, usize),
+    Loaded(Vec<Diagnostic>),
+    Failed(String),
+}
+
+pub fn update<'a>(
+    message: &Message,
+    vault: Option<&Vault>,
+    state: &mut DiagnosticsModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Toggle if state.visible => state.hide(),
+        Message::Toggle => {
+            state.visible = true;
+            return update(&Message::Refresh, vault, state);
+        }
+        Message::Close => state.hide(),
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::Refresh => {
+            let vault = vault?;
+            state.vault_path = vault.path.clone();
+
+            if state.progress.is_none() {
+                state.progress = Some((0, 0));
+                return Some(AppMessage::RunJob(Job::Diagnostics(vault.clone())));
+            }
+        }
+        Message::Open => {
+            let diagnostic = state.selected_item()?;
+            let message = AppMessage::OpenNoteAt(diagnostic.note().clone(), diagnostic.offset());
+            state.hide();
+            return Some(message);
+        }
+        Message::CreateNote => {
+            if let Some(Diagnostic::BrokenLink { new_file, .. }) = state.selected_item() {
+                return Some(match new_file {
+                    Some(new_file) => AppMessage::CreateNote(new_file.clone()),
+                    None => AppMessage::SetStatus("The link points outside the vault".into()),
+                });
+            }
+        }
+        Message::Progress(done, total) => state.progress = Some((*done, *total)),
+        Message::Loaded(items) => {
+            state.progress = None;
+            state.error = None;
+            state.items = items.clone();
+            state.list_state.select(match items.is_empty() {
+                true => None,
+                false => Some(
+                    state
+                        .list_state
+                        .selected()
+                        .unwrap_or_default()
+                        .min(items.len() - 1),
+                ),
+            });
+        }
+        Message::Failed(error) => {
+            state.progress = None;
+            state.error = Some(error.clone());
+            state.items.clear();
+            state.list_state.select(None);
+        }
+    };
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DiagnosticsModalState {
+    pub(crate) items: Vec<Diagnostic>,
+    pub(crate) list_state: ListState,
+    /// Number of scanned notes and total number of notes while the scan is running.
+    pub(crate) progress: Option<(usize, usize)>,
+    pub(crate) error: Option<String>,
+    pub(crate) vault_path: PathBuf,
+    pub(crate) visible: bool,
+}
+
+impl DiagnosticsModalState {
+    pub fn selected_item(&self) -> Option<&Diagnostic> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.items.get(index))
+    }
+
+    pub fn next(&mut self) {
+        if !self.items.is_empty() {
+            let index = self.list_state.selected().map_or(0, |index| index + 1);
+            self.list_state
+                .select(Some(index.min(self.items.len() - 1)));
+        }
+    }
+
+    pub fn previous(&mut self) {
+        let index = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some(index.saturating_sub(1)));
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.vault_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn list_item(&self, diagnostic: &Diagnostic) -> ListItem<'static> {
+        match diagnostic {
+            Diagnostic::BrokenLink { note, link, .. } => {
+                let target = match link.kind {
+                    LinkKind::Wiki => format!("[[{}]]", link.target),
+                    LinkKind::Markdown => format!("({})", link.target),
+                };
+
+                ListItem::new(Line::from(vec![
+                    Span::from("✗ ").red(),
+                    Span::from(self.relative(&note.path)),
+                    Span::from(" → ").dark_gray(),
+                    Span::from(target).red(),
+                ]))
+            }
+            Diagnostic::Orphan(note) => ListItem::new(Line::from(vec![
+                Span::from("◦ ").dark_gray(),
+                Span::from(self.relative(&note.path)),
+                Span::from(" orphan").dark_gray().italic(),
+            ])),
+        }
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(70), Constraint::Percentage(60))
+}
+
+#[derive(Default)]
+pub struct DiagnosticsModal {
+    chrome: Chrome,
+}
+
+impl DiagnosticsModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for DiagnosticsModal {
+    type State = DiagnosticsModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let broken_links = state
+            .items
+            .iter()
+            .filter(|item| matches!(item, Diagnostic::BrokenLink { .. }))
+            .count();
+        let orphans = state.items.len() - broken_links;
+
+        let summary = match state.progress {
+            Some((done, total)) => format!(" Scanning {done}/{total} "),
+            None => format!(" {broken_links} broken links · {orphans} orphans "),
+        };
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(" Diagnostics ")
+            .title(Line::from(summary).alignment(Alignment::Right))
+            .title_bottom(
+                Line::from(" (enter) jump · (c) create note · (esc) close ")
+                    .alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if let Some(error) = &state.error {
+            Paragraph::new(error.as_str()).red().render(inner_area, buf);
+            return;
+        }
+
+        if state.progress.is_none() && state.items.is_empty() {
+            Paragraph::new("No broken links or orphan notes")
+                .dark_gray()
+                .italic()
+                .render(inner_area, buf);
+            return;
+        }
+
+        let items: Vec<_> = state
+            .items
+            .iter()
+            .map(|diagnostic| state.list_item(diagnostic))
+            .collect();
+
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_style(Style::default().reversed().dark_gray())
+                .highlight_symbol(""),
+            inner_area,
+            buf,
+            &mut state.list_state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect() {
+        let mut index = LinkIndex::new(
+            Path::new("vault"),
+            vec!["vault/Index.md".into(), "vault/Orphan.md".into()],
+        );
+
+        index.insert(
+            &Note::from(Path::new("vault/Index.md")),
+            "[[Missing]] [[Index]] [[../../Escape]]",
+        );
+        index.insert(&Note::from(Path::new("vault/Orphan.md")), "");
+
+        let diagnostics = Diagnostic::collect(&index);
+
+        assert_eq!(diagnostics.len(), 3);
+        assert!(matches!(
+            &diagnostics[0],
+            Diagnostic::BrokenLink { new_file: Some(new_file), .. }
+                if new_file == Path::new("vault/Missing.md")
+        ));
+        // Links that point outside the vault get no file.
+        assert!(matches!(
+            &diagnostics[1],
+            Diagnostic::BrokenLink { new_file: None, .. }
+        ));
+        assert_eq!(
+            diagnostics[2],
+            Diagnostic::Orphan(Note::from(Path::new("vault/Orphan.md")))
+        );
+    }
+}
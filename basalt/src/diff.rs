@@ -0,0 +1,322 @@
+//! Line based diff of two texts, computed with the linear space variant of Myers' algorithm,
+//! which takes time proportional to the number of lines times the number of differences.
+use std::ops::{Index, IndexMut};
+
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffLine<'a> {
+    Equal(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Furthest reaching x positions of the edit paths by diagonal `k = x - y`, from `-max` to `max`.
+struct Diagonals {
+    max: usize,
+    x: Vec<usize>,
+}
+
+impl Diagonals {
+    fn new(max: usize) -> Self {
+        Self {
+            max,
+            x: vec![0; 2 * max + 1],
+        }
+    }
+}
+
+impl Index<isize> for Diagonals {
+    type Output = usize;
+
+    fn index(&self, k: isize) -> &usize {
+        &self.x[self.max.wrapping_add_signed(k)]
+    }
+}
+
+impl IndexMut<isize> for Diagonals {
+    fn index_mut(&mut self, k: isize) -> &mut usize {
+        &mut self.x[self.max.wrapping_add_signed(k)]
+    }
+}
+
+fn common_prefix(old: &[&str], new: &[&str]) -> usize {
+    old.iter().zip(new).take_while(|(a, b)| a == b).count()
+}
+
+fn common_suffix(old: &[&str], new: &[&str]) -> usize {
+    old.iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// Finds the middle snake of the shortest edit path by running the search from both ends until
+/// the paths overlap, returning the positions in `old` and `new` where the snake starts.
+fn middle_snake(
+    old: &[&str],
+    new: &[&str],
+    forward: &mut Diagonals,
+    backward: &mut Diagonals,
+) -> Option<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    let delta = n as isize - m as isize;
+    let odd = delta % 2 != 0;
+
+    forward[1] = 0;
+    backward[1] = 0;
+
+    for d in 0..=(n + m).div_ceil(2) as isize {
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = match k == -d || (k != d && forward[k - 1] < forward[k + 1]) {
+                true => forward[k + 1],
+                false => forward[k - 1] + 1,
+            };
+            let y = x.wrapping_add_signed(-k);
+            let start = (x, y);
+
+            if x < n && y < m {
+                x += common_prefix(&old[x..], &new[y..]);
+            }
+            forward[k] = x;
+
+            if odd && (k - delta).abs() < d && forward[k] + backward[delta - k] >= n {
+                return Some(start);
+            }
+        }
+
+        // Backward positions are counted from the ends of the texts.
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = match k == -d || (k != d && backward[k - 1] < backward[k + 1]) {
+                true => backward[k + 1],
+                false => backward[k - 1] + 1,
+            };
+            let mut y = x.wrapping_add_signed(-k);
+
+            if x < n && y < m {
+                let common = common_suffix(&old[..n - x], &new[..m - y]);
+                x += common;
+                y += common;
+            }
+            backward[k] = x;
+
+            if !odd && (k - delta).abs() <= d && backward[k] + forward[delta - k] >= n {
+                return Some((n - x, m - y));
+            }
+        }
+    }
+
+    None
+}
+
+/// Diffs the texts by splitting them at the middle snake and diffing both halves.
+fn conquer<'a>(
+    old: &[&'a str],
+    new: &[&'a str],
+    forward: &mut Diagonals,
+    backward: &mut Diagonals,
+    diff: &mut Vec<DiffLine<'a>>,
+) {
+    let prefix = common_prefix(old, new);
+    diff.extend(old[..prefix].iter().map(|l| DiffLine::Equal(l)));
+
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = common_suffix(old, new);
+    let (old_changed, new_changed) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+
+    let snake = match old_changed.is_empty() || new_changed.is_empty() {
+        true => None,
+        false => middle_snake(old_changed, new_changed, forward, backward),
+    };
+
+    match snake {
+        Some((x, y)) => {
+            conquer(
+                &old_changed[..x],
+                &new_changed[..y],
+                forward,
+                backward,
+                diff,
+            );
+            conquer(
+                &old_changed[x..],
+                &new_changed[y..],
+                forward,
+                backward,
+                diff,
+            );
+        }
+        None => {
+            diff.extend(old_changed.iter().map(|l| DiffLine::Removed(l)));
+            diff.extend(new_changed.iter().map(|l| DiffLine::Added(l)));
+        }
+    }
+
+    diff.extend(old[old.len() - suffix..].iter().map(|l| DiffLine::Equal(l)));
+}
+
+/// Returns the lines of `old` and `new` in order, marking the lines that only exist in one of
+/// them as removed or added. Within a run of changed lines the removed lines come first.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let max = (old.len() + new.len()).div_ceil(2) + 1;
+    let (mut forward, mut backward) = (Diagonals::new(max), Diagonals::new(max));
+
+    let mut diff = Vec::with_capacity(old.len().max(new.len()));
+    conquer(&old, &new, &mut forward, &mut backward, &mut diff);
+
+    diff.chunk_by_mut(|a, b| matches!(a, DiffLine::Equal(_)) == matches!(b, DiffLine::Equal(_)))
+        .for_each(|run| run.sort_by_key(|line| matches!(line, DiffLine::Added(_))));
+
+    diff
+}
+
+/// Returns how similar the texts are in percents, based on the number of shared lines.
+pub fn similarity(old: &str, new: &str) -> usize {
+    let diff = diff_lines(old, new);
+    let equal = diff
+        .iter()
+        .filter(|line| matches!(line, DiffLine::Equal(_)))
+        .count();
+
+    match diff.len() {
+        0 => 100,
+        len => equal * 100 / len,
+    }
+}
+
+/// Combines both texts by keeping every line of either one, in diff order.
+pub fn merge(old: &str, new: &str) -> String {
+    let mut merged = diff_lines(old, new)
+        .into_iter()
+        .map(|line| match line {
+            DiffLine::Equal(line) | DiffLine::Removed(line) | DiffLine::Added(line) => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if old.ends_with('\n') || new.ends_with('\n') {
+        merged.push('\n');
+    }
+
+    merged
+}
+
+/// Renders the diff with `+`/`-` markers, added lines in green and removed lines in red.
+pub fn render_lines(diff: &[DiffLine]) -> Vec<Line<'static>> {
+    diff.iter()
+        .map(|line| match line {
+            DiffLine::Equal(line) => Line::from(vec![
+                Span::from("  ").dark_gray(),
+                Span::from(line.to_string()),
+            ]),
+            DiffLine::Removed(line) => Line::from(format!("- {line}")).red(),
+            DiffLine::Added(line) => Line::from(format!("+ {line}")).green(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn test_diff_lines() {
+        let old = indoc! {"
+            # Note
+            first
+            second
+            last
+        "};
+
+        let new = indoc! {"
+            # Note
+            first
+            changed
+            last
+            added
+        "};
+
+        assert_eq!(
+            diff_lines(old, new),
+            vec![
+                DiffLine::Equal("# Note"),
+                DiffLine::Equal("first"),
+                DiffLine::Removed("second"),
+                DiffLine::Added("changed"),
+                DiffLine::Equal("last"),
+                DiffLine::Added("added"),
+            ]
+        );
+
+        assert_eq!(similarity(old, new), 50);
+        assert_eq!(similarity(old, old), 100);
+        assert_eq!(
+            merge(old, new),
+            "# Note\nfirst\nsecond\nchanged\nlast\nadded\n"
+        );
+    }
+
+    /// Length of the longest common subsequence of lines, computed with the quadratic table.
+    fn lcs_len(old: &[&str], new: &[&str]) -> usize {
+        let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lengths[i][j] = match old[i] == new[j] {
+                    true => lengths[i + 1][j + 1] + 1,
+                    false => lengths[i + 1][j].max(lengths[i][j + 1]),
+                };
+            }
+        }
+        lengths[0][0]
+    }
+
+    #[test]
+    fn test_diff_lines_shortest() {
+        // Pseudo random texts over a small alphabet, so that lines repeat and match in many ways.
+        let mut next = testing::xorshift(42);
+        let mut text = |len: usize| {
+            let lines = next() % len;
+            (0..lines)
+                .map(|_| ["a", "b", "c", "d"][next() % 4])
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        for _ in 0..500 {
+            let (old, new) = (text(30), text(30));
+            let diff = diff_lines(&old, &new);
+
+            let lines = |keep: fn(&DiffLine) -> bool| {
+                diff.iter()
+                    .filter(|line| keep(line))
+                    .map(|line| match line {
+                        DiffLine::Equal(l) | DiffLine::Removed(l) | DiffLine::Added(l) => *l,
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let (old_lines, new_lines): (Vec<_>, Vec<_>) =
+                (old.lines().collect(), new.lines().collect());
+            assert_eq!(lines(|line| !matches!(line, DiffLine::Added(_))), old_lines);
+            assert_eq!(
+                lines(|line| !matches!(line, DiffLine::Removed(_))),
+                new_lines
+            );
+            assert_eq!(
+                lines(|line| matches!(line, DiffLine::Equal(_))).len(),
+                lcs_len(&old_lines, &new_lines),
+                "{old:?} {new:?}"
+            );
+        }
+    }
+}
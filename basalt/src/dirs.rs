@@ -0,0 +1,125 @@
+//! Directories Basalt keeps its files in, following the conventions of the platform: settings in
+//! the config directory, data kept between application runs, like the session and the writing
+//! statistics, in the state directory, and data that can be built again, like the vault indexes
+//! and crash reports, in the cache directory.
+//!
+//! On Linux and macOS these are `$XDG_CONFIG_HOME/basalt`, `$XDG_STATE_HOME/basalt` and
+//! `$XDG_CACHE_HOME/basalt`. Platforms without a state directory keep the state in the data
+//! directory.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use etcetera::{choose_base_strategy, BaseStrategy};
+
+/// Files of the state directory that earlier versions kept in the data directory.
+const LEGACY_STATE_FILES: [&str; 1] = ["session.toml"];
+
+/// Returns the directory of the settings.
+pub fn config_dir() -> Option<PathBuf> {
+    choose_base_strategy()
+        .ok()
+        .map(|strategy| strategy.config_dir().join("basalt"))
+}
+
+/// Returns the directory of the data kept between application runs.
+pub fn state_dir() -> Option<PathBuf> {
+    choose_base_strategy().ok().map(|strategy| {
+        strategy
+            .state_dir()
+            .unwrap_or_else(|| strategy.data_dir())
+            .join("basalt")
+    })
+}
+
+/// Returns the directory of the data that can be built again.
+pub fn cache_dir() -> Option<PathBuf> {
+    choose_base_strategy()
+        .ok()
+        .map(|strategy| strategy.cache_dir().join("basalt"))
+}
+
+/// Moves the files that earlier versions kept in the data directory to the state directory.
+/// Files that already exist in the state directory are left alone. Returns the moved files.
+pub fn migrate() -> io::Result<Vec<PathBuf>> {
+    let legacy_dir = choose_base_strategy()
+        .ok()
+        .map(|strategy| strategy.data_dir().join("basalt"));
+
+    match (legacy_dir, state_dir()) {
+        (Some(from), Some(to)) => move_files(&from, &to, &LEGACY_STATE_FILES),
+        _ => Ok(vec![]),
+    }
+}
+
+/// Moves the files from one directory to the other, removing the former directory when it is
+/// left empty.
+fn move_files(from: &Path, to: &Path, files: &[&str]) -> io::Result<Vec<PathBuf>> {
+    if from == to {
+        return Ok(vec![]);
+    }
+
+    let mut moved = vec![];
+
+    for file in files {
+        let (old, new) = (from.join(file), to.join(file));
+        if !old.is_file() || new.exists() {
+            continue;
+        }
+
+        fs::create_dir_all(to)?;
+        // Renaming fails across file systems, where the file is copied instead.
+        if fs::rename(&old, &new).is_err() {
+            fs::copy(&old, &new)?;
+            fs::remove_file(&old)?;
+        }
+        moved.push(new);
+    }
+
+    _ = fs::remove_dir(from);
+
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_files() {
+        let dir = std::env::temp_dir().join("basalt-dirs-move-files");
+        _ = fs::remove_dir_all(&dir);
+        let (from, to) = (dir.join("data"), dir.join("state"));
+
+        fs::create_dir_all(&from).unwrap();
+        fs::write(from.join("session.toml"), "last_vault = \"Notes\"\n").unwrap();
+        fs::write(from.join("other.toml"), "").unwrap();
+
+        assert_eq!(
+            move_files(&from, &to, &["session.toml", "missing.toml"]).unwrap(),
+            vec![to.join("session.toml")]
+        );
+        assert_eq!(
+            fs::read_to_string(to.join("session.toml")).unwrap(),
+            "last_vault = \"Notes\"\n"
+        );
+        assert!(!from.join("session.toml").exists() && from.join("other.toml").exists());
+
+        // A file that exists in both directories is not overwritten.
+        fs::write(from.join("session.toml"), "").unwrap();
+        assert!(move_files(&from, &to, &["session.toml"])
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            fs::read_to_string(to.join("session.toml")).unwrap(),
+            "last_vault = \"Notes\"\n"
+        );
+
+        // The former directory is removed once it is empty.
+        fs::remove_file(from.join("other.toml")).unwrap();
+        fs::remove_file(from.join("session.toml")).unwrap();
+        move_files(&from, &to, &["session.toml"]).unwrap();
+        assert!(!from.exists());
+    }
+}
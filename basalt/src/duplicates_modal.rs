@@ -0,0 +1,554 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use basalt_core::obsidian::{Note, Vault};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Flex, Layout, Rect, Size},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, Gauge, List, ListItem, ListState, Padding, Paragraph,
+        StatefulWidget, Widget,
+    },
+};
+
+use crate::{
+    app::{calc_scroll_amount, Message as AppMessage, ScrollAmount},
+    background::Job,
+    chrome::Chrome,
+    diff,
+    input_modal::{self, InputAction},
+    responsive,
+    text_counts::strip_frontmatter,
+};
+
+/// Notes that only share the first line are reported when their contents are at least this
+/// similar.
+const SIMILARITY_THRESHOLD: usize = 90;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reason {
+    Title,
+    Content,
+    Conflict,
+}
+
+/// A note that duplicates another note. The note with the shorter name is considered the
+/// original, e.g. `Note` for `Note (conflicted copy)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Duplicate {
+    pub original: Note,
+    pub duplicate: Note,
+    pub reason: Reason,
+    /// Similarity of the contents in percents.
+    pub similarity: usize,
+}
+
+impl Duplicate {
+    /// Creates the sync conflict pair of the original note and its conflict copy.
+    pub fn conflict(original: Note, conflict: Note) -> Self {
+        let similarity = diff::similarity(
+            &Note::read_to_string(&original).unwrap_or_default(),
+            &Note::read_to_string(&conflict).unwrap_or_default(),
+        );
+
+        Self {
+            original,
+            duplicate: conflict,
+            reason: Reason::Conflict,
+            similarity,
+        }
+    }
+}
+
+/// Returns the note name without copy and sync conflict markers in lowercase, e.g. `note` for
+/// `Note (conflicted copy 2025-01-01)`, `Note.sync-conflict-20250101-120000-ABCDEF` or
+/// `Note copy`.
+pub fn base_title(name: &str) -> String {
+    let name = name
+        .split_once(".sync-conflict-")
+        .map_or(name, |(name, _)| name);
+
+    let mut title = String::new();
+    let mut depth = 0usize;
+
+    for c in name.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 => title.push(c),
+            _ => {}
+        }
+    }
+
+    let title = title.trim();
+    let title = title.strip_suffix(" copy").unwrap_or(title);
+
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn content_hash(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.trim().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Finds notes with the same title after removing copy markers, notes with identical contents
+/// and notes with near-identical contents. Most similar duplicates are returned first.
+pub fn find_duplicates(notes: &[(Note, String)]) -> Vec<Duplicate> {
+    #[derive(Hash, PartialEq, Eq)]
+    enum Key {
+        Title(String),
+        Content(u64),
+        FirstLine(String),
+    }
+
+    let mut groups: HashMap<Key, Vec<usize>> = HashMap::new();
+
+    for (i, (note, contents)) in notes.iter().enumerate() {
+        groups
+            .entry(Key::Title(base_title(&note.name)))
+            .or_default()
+            .push(i);
+
+        if contents.trim().is_empty() {
+            continue;
+        }
+
+        groups
+            .entry(Key::Content(content_hash(contents)))
+            .or_default()
+            .push(i);
+
+        // Frontmatter is skipped, it starts most notes of a vault the same way.
+        let first_line = strip_frontmatter(contents)
+            .lines()
+            .find(|line| !line.trim().is_empty());
+        if let Some(line) = first_line {
+            groups
+                .entry(Key::FirstLine(line.trim().to_string()))
+                .or_default()
+                .push(i);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut duplicates = vec![];
+
+    for (key, mut group) in groups.into_iter().filter(|(_, group)| group.len() > 1) {
+        group.sort_by_key(|i| (notes[*i].0.name.len(), notes[*i].0.path.clone()));
+
+        let (original, others) = group.split_first().unwrap();
+        let (original_note, original_contents) = &notes[*original];
+
+        for other in others {
+            if !seen.insert((*original, *other)) {
+                continue;
+            }
+
+            let (note, contents) = &notes[*other];
+            let similarity = diff::similarity(original_contents, contents);
+
+            // A pair can be in several groups, identical contents take precedence.
+            let reason = match key {
+                _ if note.conflict_of() == Some(&original_note.name) => Reason::Conflict,
+                _ if original_contents.trim() == contents.trim() => Reason::Content,
+                Key::Title(_) => Reason::Title,
+                Key::Content(_) => Reason::Content,
+                Key::FirstLine(_) if similarity >= SIMILARITY_THRESHOLD => Reason::Content,
+                Key::FirstLine(_) => continue,
+            };
+
+            duplicates.push(Duplicate {
+                original: original_note.clone(),
+                duplicate: note.clone(),
+                reason,
+                similarity,
+            });
+        }
+    }
+
+    duplicates.sort_by(|a, b| {
+        b.similarity
+            .cmp(&a.similarity)
+            .then(a.original.path.cmp(&b.original.path))
+            .then(a.duplicate.path.cmp(&b.duplicate.path))
+    });
+
+    duplicates
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    /// Opens the modal with the given duplicates without scanning the vault.
+    Open(Vec<Duplicate>),
+    Close,
+    Up,
+    Down,
+    ScrollUp(ScrollAmount),
+    ScrollDown(ScrollAmount),
+    Swap,
+    Delete,
+    Replace,
+    Merge,
+    Refresh,
+    Progress(usize, usize),
+    Loaded(Vec<Duplicate>),
+}
+
+pub fn update<'a>(
+    message: &Message,
+    screen_size: Size,
+    vault: Option<&Vault>,
+    state: &mut DuplicatesModalState,
+) -> Option<AppMessage<'a>> {
+    let diff_height = diff_area(Rect::new(0, 0, screen_size.width, screen_size.height)).height;
+
+    match message {
+        Message::Toggle if state.visible => state.hide(),
+        Message::Toggle => {
+            state.visible = true;
+            state.scan = true;
+            return update(&Message::Refresh, screen_size, vault, state);
+        }
+        Message::Open(items) => {
+            state.visible = true;
+            state.scan = false;
+            state.items = items.clone();
+            if let Some(vault) = vault {
+                state.vault_path = vault.path.clone();
+            }
+            state.select(0);
+        }
+        Message::Close => state.hide(),
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::ScrollUp(scroll_amount) => {
+            state.diff_scroll = state
+                .diff_scroll
+                .saturating_sub(calc_scroll_amount(scroll_amount, diff_height.into()));
+        }
+        Message::ScrollDown(scroll_amount) => {
+            state.diff_scroll = state
+                .diff_scroll
+                .saturating_add(calc_scroll_amount(scroll_amount, diff_height.into()))
+                .min(state.diff.len().saturating_sub(1));
+        }
+        Message::Swap => {
+            let index = state.list_state.selected()?;
+            let item = state.items.get_mut(index)?;
+            std::mem::swap(&mut item.original, &mut item.duplicate);
+            state.load_diff();
+        }
+        Message::Delete => {
+            let item = state.selected_item()?;
+            return Some(AppMessage::InputModal(input_modal::Message::Confirm {
+                title: "Delete duplicate".into(),
+                question: format!("Delete {}?", item.duplicate.name),
+                action: InputAction::DeleteNote(item.duplicate.clone()),
+            }));
+        }
+        Message::Replace => {
+            let item = state.selected_item()?;
+            return Some(AppMessage::InputModal(input_modal::Message::Confirm {
+                title: "Replace with duplicate".into(),
+                question: format!(
+                    "Replace {} with {} and delete {}?",
+                    item.original.name, item.duplicate.name, item.duplicate.name
+                ),
+                action: InputAction::ReplaceNote(item.original.clone(), item.duplicate.clone()),
+            }));
+        }
+        Message::Merge => {
+            let item = state.selected_item()?;
+            return Some(AppMessage::InputModal(input_modal::Message::Confirm {
+                title: "Merge duplicate".into(),
+                question: format!(
+                    "Merge {} into {} and delete {}?",
+                    item.duplicate.name, item.original.name, item.duplicate.name
+                ),
+                action: InputAction::MergeNotes(item.original.clone(), item.duplicate.clone()),
+            }));
+        }
+        Message::Refresh if !state.scan => {
+            state
+                .items
+                .retain(|item| item.original.path.exists() && item.duplicate.path.exists());
+            state.select(state.list_state.selected().unwrap_or_default());
+        }
+        Message::Refresh => {
+            let vault = vault?;
+            state.vault_path = vault.path.clone();
+
+            if state.progress.is_none() {
+                state.progress = Some((0, 0));
+                return Some(AppMessage::RunJob(Job::Duplicates(vault.clone())));
+            }
+        }
+        Message::Progress(done, total) => state.progress = Some((*done, *total)),
+        Message::Loaded(items) => {
+            state.progress = None;
+            state.items = items.clone();
+            state.select(state.list_state.selected().unwrap_or_default());
+        }
+    };
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DuplicatesModalState {
+    pub(crate) items: Vec<Duplicate>,
+    pub(crate) list_state: ListState,
+    /// Rendered diff of the selected duplicate against the original.
+    pub(crate) diff: Vec<Line<'static>>,
+    pub(crate) diff_scroll: usize,
+    /// Number of scanned notes and total number of notes while the scan is running.
+    pub(crate) progress: Option<(usize, usize)>,
+    pub(crate) vault_path: PathBuf,
+    /// Whether the duplicates were found by scanning the vault, or given when opening the modal.
+    pub(crate) scan: bool,
+    pub(crate) visible: bool,
+}
+
+impl DuplicatesModalState {
+    pub fn selected_item(&self) -> Option<&Duplicate> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.items.get(index))
+    }
+
+    fn select(&mut self, index: usize) {
+        self.list_state.select(match self.items.is_empty() {
+            true => None,
+            false => Some(index.min(self.items.len() - 1)),
+        });
+        self.load_diff();
+    }
+
+    fn load_diff(&mut self) {
+        self.diff_scroll = 0;
+        self.diff = self
+            .selected_item()
+            .map(|item| {
+                let original = Note::read_to_string(&item.original).unwrap_or_default();
+                let duplicate = Note::read_to_string(&item.duplicate).unwrap_or_default();
+                diff::render_lines(&diff::diff_lines(&original, &duplicate))
+            })
+            .unwrap_or_default();
+    }
+
+    pub fn next(&mut self) {
+        let index = self.list_state.selected().map_or(0, |index| index + 1);
+        self.select(index);
+    }
+
+    pub fn previous(&mut self) {
+        let index = self.list_state.selected().unwrap_or_default();
+        self.select(index.saturating_sub(1));
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.vault_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn list_item(&self, item: &Duplicate) -> ListItem<'static> {
+        let reason = match item.reason {
+            Reason::Title => "same title",
+            Reason::Content => "same content",
+            Reason::Conflict => "sync conflict",
+        };
+
+        ListItem::new(Line::from(vec![
+            Span::from(format!("{:>3}% ", item.similarity)).dark_gray(),
+            Span::from(self.relative(&item.original.path)),
+            Span::from(" ⇄ ").dark_gray(),
+            Span::from(self.relative(&item.duplicate.path)).yellow(),
+            Span::from(format!(" {reason}")).dark_gray().italic(),
+        ]))
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(80), Constraint::Percentage(80))
+}
+
+fn layout(area: Rect) -> [Rect; 2] {
+    // Borders and padding of the modal
+    let inner = Rect {
+        x: area.x + 2,
+        y: area.y + 1,
+        width: area.width.saturating_sub(4),
+        height: area.height.saturating_sub(2),
+    };
+
+    Layout::vertical([Constraint::Percentage(35), Constraint::Fill(1)]).areas(inner)
+}
+
+fn diff_area(area: Rect) -> Rect {
+    let [_, diff] = layout(modal_area(area));
+    diff
+}
+
+#[derive(Default)]
+pub struct DuplicatesModal {
+    chrome: Chrome,
+}
+
+impl DuplicatesModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for DuplicatesModal {
+    type State = DuplicatesModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(" Duplicate Notes ")
+            .title(Line::from(format!(" {} found ", state.items.len())).alignment(Alignment::Right))
+            .title_bottom(
+                Line::from(
+                    " (s) swap · (d) keep original · (r) keep duplicate · (m) merge · (esc) close ",
+                )
+                .alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let [list_area, diff_area] = layout(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if let Some((done, total)) = state.progress {
+            let [gauge] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(list_area);
+
+            Gauge::default()
+                .gauge_style(Style::default().magenta())
+                .ratio(if total == 0 {
+                    0.0
+                } else {
+                    done as f64 / total as f64
+                })
+                .label(format!("Scanning notes {done}/{total}"))
+                .render(gauge, buf);
+        } else if state.items.is_empty() {
+            Paragraph::new(match state.scan {
+                true => "No duplicate notes",
+                false => "Resolved",
+            })
+            .dark_gray()
+            .italic()
+            .render(list_area, buf);
+            return;
+        }
+
+        let items: Vec<_> = state
+            .items
+            .iter()
+            .map(|item| state.list_item(item))
+            .collect();
+
+        if state.progress.is_none() {
+            StatefulWidget::render(
+                List::new(items).highlight_style(Style::default().reversed().dark_gray()),
+                list_area,
+                buf,
+                &mut state.list_state,
+            );
+        }
+
+        Paragraph::new(state.diff.clone())
+            .block(Block::new().borders(Borders::TOP).dark_gray())
+            .scroll((state.diff_scroll as u16, 0))
+            .render(diff_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(name: &str, contents: &str) -> (Note, String) {
+        (
+            Note {
+                name: name.into(),
+                path: PathBuf::from(format!("vault/{name}.md")),
+            },
+            contents.into(),
+        )
+    }
+
+    #[test]
+    fn test_base_title() {
+        assert_eq!(base_title("Note (conflicted copy 2025-01-01)"), "note");
+        assert_eq!(
+            base_title("Note.sync-conflict-20250101-120000-ABCDEF"),
+            "note"
+        );
+        assert_eq!(base_title("Note copy"), "note");
+        assert_eq!(base_title("Chapter 2"), "chapter 2");
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        // Notes from the same template share most lines, but not the first line of the body.
+        let frontmatter: String = (0..20).map(|i| format!("field{i}: value\n")).collect();
+        let daily = format!("---\n{frontmatter}---\n\n# Monday");
+        let weekly = format!("---\n{frontmatter}---\n\n# Week");
+
+        let notes = [
+            note("Note", "# Note\n\nfirst\nsecond"),
+            note("Note (conflicted copy)", "# Note\n\nfirst\nchanged"),
+            note("Other (1)", "# Other"),
+            note("Copy", "# Note\n\nfirst\nsecond"),
+            note("Other", "# Other"),
+            note("Daily", &daily),
+            note("Weekly", &weekly),
+        ];
+
+        let duplicates: Vec<_> = find_duplicates(&notes)
+            .into_iter()
+            .map(|d| (d.original.name, d.duplicate.name, d.reason))
+            .collect();
+
+        assert_eq!(
+            duplicates,
+            vec![
+                ("Copy".into(), "Note".into(), Reason::Content),
+                ("Other".into(), "Other (1)".into(), Reason::Content),
+                (
+                    "Note".into(),
+                    "Note (conflicted copy)".into(),
+                    Reason::Conflict
+                ),
+            ]
+        );
+    }
+}
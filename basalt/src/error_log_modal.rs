@@ -0,0 +1,179 @@
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Clear, Padding, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
+        Widget, Wrap,
+    },
+};
+
+use crate::{app::Message as AppMessage, chrome::Chrome, responsive};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    Close,
+    ScrollUp,
+    ScrollDown,
+}
+
+pub fn update<'a>(message: &Message, state: &mut ErrorLogModalState) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Toggle => state.visible = !state.visible,
+        Message::Close => state.visible = false,
+        Message::ScrollUp => state.scroll = state.scroll.saturating_sub(1),
+        Message::ScrollDown => {
+            state.scroll = state
+                .scroll
+                .saturating_add(1)
+                .min(state.errors.len().saturating_sub(1))
+        }
+    };
+
+    None
+}
+
+/// Error log modal key mappings cannot be modified through configuration, like the configuration
+/// report that lists the configuration errors.
+pub fn handle_event(key: &KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => Some(Message::Close),
+        KeyCode::Char('k') | KeyCode::Up => Some(Message::ScrollUp),
+        KeyCode::Char('j') | KeyCode::Down => Some(Message::ScrollDown),
+        _ => None,
+    }
+}
+
+/// An error of the session and the time it happened at, as `HH:MM:SS`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoggedError {
+    pub time: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ErrorLogModalState {
+    /// Errors of the session, the latest first.
+    pub(crate) errors: Vec<LoggedError>,
+    pub(crate) scroll: usize,
+    pub(crate) visible: bool,
+}
+
+impl ErrorLogModalState {
+    /// Adds the error to the top of the log.
+    pub fn push(&mut self, time: &str, message: &str) {
+        self.errors.insert(
+            0,
+            LoggedError {
+                time: time.to_string(),
+                message: message.to_string(),
+            },
+        );
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Length(83), Constraint::Percentage(50))
+}
+
+#[derive(Default)]
+pub struct ErrorLogModal {
+    chrome: Chrome,
+}
+
+impl ErrorLogModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for ErrorLogModal {
+    type State = ErrorLogModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let errors = &state.errors;
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::uniform(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(" Errors ")
+            .title(
+                Line::from(match errors.len() {
+                    1 => " 1 error ".to_string(),
+                    count => format!(" {count} errors "),
+                })
+                .alignment(Alignment::Right),
+            )
+            .title_bottom(Line::from(" (j/k) scroll · (esc) close ").alignment(Alignment::Right));
+
+        let lines: Vec<Line> = if errors.is_empty() {
+            vec![Line::from("No errors in this session.").italic()]
+        } else {
+            errors
+                .iter()
+                .skip(state.scroll)
+                .map(|error| {
+                    Line::from(vec![
+                        Span::from(format!("{:<10}", error.time)).dark_gray(),
+                        Span::from(error.message.clone()).fg(Color::default()),
+                    ])
+                })
+                .collect()
+        };
+
+        let area = modal_area(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(
+            Paragraph::new(lines)
+                .wrap(Wrap::default())
+                .block(block)
+                .fg(Color::default()),
+            area,
+            buf,
+        );
+
+        StatefulWidget::render(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area,
+            buf,
+            &mut ScrollbarState::new(errors.len()).position(state.scroll),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn test_render_error_log() {
+        let mut state = ErrorLogModalState {
+            visible: true,
+            ..Default::default()
+        };
+        state.push(
+            "09:12:01",
+            "Could not write Inbox: Permission denied (os error 13)",
+        );
+        state.push("09:15:44", "Command `vi` failed: exit status: 1");
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal
+            .draw(|frame| {
+                ErrorLogModal::default().render(frame.area(), frame.buffer_mut(), &mut state)
+            })
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+    }
+}
@@ -3,11 +3,12 @@ mod state;
 
 pub use item::Item;
 use ratatui::layout::Size;
-use ratatui::widgets::Borders;
+use ratatui::widgets::{Borders, Padding};
 pub use state::ExplorerState;
 pub use state::Sort;
 
 use std::{marker::PhantomData, path::PathBuf};
+use unicode_width::UnicodeWidthStr;
 
 use basalt_core::obsidian::Note;
 use ratatui::{
@@ -15,13 +16,18 @@ use ratatui::{
     layout::{Alignment, Constraint, Layout, Rect},
     style::{Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, BorderType, List, ListItem, StatefulWidget},
+    widgets::{List, ListItem, StatefulWidget},
 };
 
 use crate::app::{
     calc_scroll_amount, ActivePane, Message as AppMessage, ScrollAmount, SelectedNote,
 };
+use crate::canvas_modal;
+use crate::chrome::Chrome;
+use crate::duplicates_modal::{self, Duplicate};
+use crate::input_modal::{self, InputAction};
 use crate::outline;
+use crate::truncate;
 
 const SORT_SYMBOL_ASC: &str = "↑𝌆";
 const SORT_SYMBOL_DESC: &str = "↓𝌆";
@@ -34,10 +40,28 @@ pub enum Message {
     Sort,
     Toggle,
     ToggleOutline,
+    DuplicateNote,
+    SaveNoteAsTemplate,
+    ResolveConflict,
     SwitchPaneNext,
     SwitchPanePrevious,
     ScrollUp(ScrollAmount),
     ScrollDown(ScrollAmount),
+    /// Selects the first item.
+    GotoTop,
+    /// Selects the last item.
+    GotoBottom,
+    /// Expands every folder of the tree.
+    ExpandAll,
+    /// Collapses every folder and saved search of the tree.
+    CollapseAll,
+    /// Expands the folder under the selector and the folders in it.
+    ExpandSubtree,
+    /// Collapses the folder under the selector and the folders in it.
+    CollapseSubtree,
+    /// Shows the path of the item under the selector in the status bar, for names that do not
+    /// fit the pane.
+    ShowPath,
 }
 
 pub fn update<'a>(
@@ -48,6 +72,12 @@ pub fn update<'a>(
     match message {
         Message::Up => state.previous(1),
         Message::Down => state.next(1),
+        Message::GotoTop => state.first(),
+        Message::GotoBottom => state.last(),
+        Message::ExpandAll => state.set_all_expanded(true, false),
+        Message::CollapseAll => state.set_all_expanded(false, false),
+        Message::ExpandSubtree => state.set_all_expanded(true, true),
+        Message::CollapseSubtree => state.set_all_expanded(false, true),
         Message::Sort => state.sort(),
         Message::Toggle => {
             state.toggle();
@@ -56,14 +86,21 @@ pub fn update<'a>(
                 return Some(AppMessage::SetActivePane(ActivePane::NoteEditor));
             }
         }
-        Message::SwitchPaneNext => {
-            state.set_active(false);
-            return Some(AppMessage::SetActivePane(ActivePane::NoteEditor));
-        }
-        Message::SwitchPanePrevious => {
-            state.set_active(false);
-            return Some(AppMessage::SetActivePane(ActivePane::Outline));
+        Message::ShowPath => {
+            let path = match state.highlighted_item()? {
+                Item::Search { name, .. } => name.clone(),
+                item => item
+                    .path()
+                    .strip_prefix(&state.root)
+                    .unwrap_or(item.path())
+                    .to_string_lossy()
+                    .to_string(),
+            };
+
+            return Some(AppMessage::SetStatus(path));
         }
+        Message::SwitchPaneNext => return Some(AppMessage::SwitchPaneNext),
+        Message::SwitchPanePrevious => return Some(AppMessage::SwitchPanePrevious),
         Message::ScrollUp(scroll_amount) => {
             state.previous(calc_scroll_amount(scroll_amount, screen_size.height.into()));
         }
@@ -73,7 +110,52 @@ pub fn update<'a>(
         Message::ToggleOutline => {
             return Some(AppMessage::Outline(outline::Message::Toggle));
         }
+        Message::DuplicateNote => {
+            let note = state.highlighted_note()?.clone();
+            let dir = note.path.parent()?;
+
+            return Some(AppMessage::InputModal(input_modal::Message::Open {
+                title: "Duplicate note".into(),
+                value: Note::available_name(dir, &format!("{} copy", note.name)),
+                action: InputAction::DuplicateNote(note),
+            }));
+        }
+        Message::SaveNoteAsTemplate => {
+            let note = state.highlighted_note()?.clone();
+
+            return Some(AppMessage::InputModal(input_modal::Message::Open {
+                title: "Save as template".into(),
+                value: note.name.clone(),
+                action: InputAction::SaveNoteAsTemplate(note),
+            }));
+        }
+        Message::ResolveConflict => {
+            let conflict = state.highlighted_note()?.clone();
+            let name = conflict.conflict_of()?;
+            let original = Note::from(conflict.path.with_file_name(format!("{name}.md")).as_path());
+
+            if !original.path.exists() {
+                return None;
+            }
+
+            return Some(AppMessage::DuplicatesModal(
+                duplicates_modal::Message::Open(vec![Duplicate::conflict(original, conflict)]),
+            ));
+        }
         Message::Open => {
+            if let Some(file) = state
+                .highlighted_note()
+                .filter(|note| note.path.extension().is_none_or(|ext| ext != "md"))
+                .cloned()
+            {
+                return Some(
+                    match file.path.extension().is_some_and(|ext| ext == "canvas") {
+                        true => AppMessage::CanvasModal(canvas_modal::Message::Open(file)),
+                        false => AppMessage::OpenAttachment(file.path),
+                    },
+                );
+            }
+
             state.select();
             let note = state.selected_note.as_ref()?;
             return Some(AppMessage::SelectNote(SelectedNote::from(note)));
@@ -85,19 +167,29 @@ pub fn update<'a>(
 
 #[derive(Default)]
 pub struct Explorer<'a> {
+    chrome: Chrome,
     _lifetime: PhantomData<&'a ()>,
 }
 
 impl Explorer<'_> {
     pub fn new() -> Self {
         Self {
+            chrome: Chrome::default(),
             _lifetime: PhantomData::<&()>,
         }
     }
 
+    /// Sets the borders, padding and title alignment of the pane.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome, ..self }
+    }
+
+    /// Returns the list item of the tree item. Names longer than the width are shortened in the
+    /// middle.
     fn list_item<'a>(
         selected_path: Option<PathBuf>,
         is_open: bool,
+        width: usize,
     ) -> impl Fn(&'a (Item, usize)) -> ListItem<'a> {
         move |(item, depth)| {
             let indentation = if *depth > 0 {
@@ -105,19 +197,48 @@ impl Explorer<'_> {
             } else {
                 Span::raw("  ".repeat(*depth)).black()
             };
+            // The indentation and the symbol before the name.
+            let prefix_width = 2 * depth + 2;
+
             match item {
-                Item::File(Note { path, name }) => {
+                Item::File(note @ Note { path, name }) => {
                     let is_selected = selected_path
                         .as_ref()
                         .is_some_and(|selected| selected == path);
-                    ListItem::new(Line::from(match (is_open, is_selected) {
+
+                    // Sync conflict copies are badged so they stand out in the tree.
+                    let badge = (is_open && note.conflict_of().is_some()).then_some(" ⚠");
+
+                    // Canvases and attachments show their extension, as names leave it out.
+                    let ext = path
+                        .extension()
+                        .filter(|ext| is_open && *ext != "md")
+                        .map(|ext| format!(" {}", ext.to_string_lossy()));
+
+                    let suffix_width = badge.map_or(0, UnicodeWidthStr::width)
+                        + ext.as_deref().map_or(0, UnicodeWidthStr::width);
+                    let name =
+                        truncate::middle(name, width.saturating_sub(prefix_width + suffix_width));
+
+                    let mut line = Line::from(match (is_open, is_selected) {
                         (true, true) => [indentation, "◆ ".into(), name.into()].to_vec(),
                         (true, false) => [indentation, "  ".into(), name.into()].to_vec(),
                         (false, true) => ["◆".into()].to_vec(),
                         (false, false) => ["◦".dark_gray()].to_vec(),
-                    }))
+                    });
+
+                    if let Some(badge) = badge {
+                        line.push_span(badge.yellow());
+                    }
+
+                    if let Some(ext) = ext {
+                        line.push_span(ext.dark_gray());
+                    }
+
+                    ListItem::new(line)
                 }
                 Item::Directory { expanded, name, .. } => {
+                    let name = truncate::middle(name, width.saturating_sub(prefix_width));
                     ListItem::new(Line::from(match (is_open, expanded) {
                         (true, true) => [indentation, "▾ ".dark_gray(), name.into()].to_vec(),
                         (true, false) => [indentation, "▸ ".dark_gray(), name.into()].to_vec(),
@@ -125,6 +246,28 @@ impl Explorer<'_> {
                         (false, false) => ["▫".dark_gray()].to_vec(),
                     }))
                 }
+                Item::Search {
+                    expanded,
+                    name,
+                    items,
+                    ..
+                } => {
+                    let count = format!(" {}", items.len());
+                    let count_width = if *expanded { count.width() } else { 0 };
+                    let name = Span::raw(truncate::middle(
+                        name,
+                        width.saturating_sub(prefix_width + count_width),
+                    ))
+                    .italic();
+                    ListItem::new(Line::from(match (is_open, expanded) {
+                        (true, true) => {
+                            [indentation, "▾ ".dark_gray(), name, count.dark_gray()].to_vec()
+                        }
+                        (true, false) => [indentation, "▸ ".dark_gray(), name].to_vec(),
+                        (false, true) => ["▪".dark_gray()].to_vec(),
+                        (false, false) => ["▫".dark_gray()].to_vec(),
+                    }))
+                }
             }
         }
     }
@@ -134,15 +277,14 @@ impl<'a> StatefulWidget for Explorer<'a> {
     type State = ExplorerState<'a>;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let block = Block::bordered()
-            .border_type(if state.active {
-                BorderType::Thick
-            } else {
-                BorderType::Rounded
-            })
+        let block = self
+            .chrome
+            .block(state.active)
+            .padding(self.chrome.padding(Padding::ZERO))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
             .title_style(Style::default().italic().bold());
 
-        let Rect { height, .. } = block.inner(area);
+        let Rect { height, width, .. } = block.inner(area);
         state.update_offset_mut(height.into());
 
         let sort_symbol = match state.sort {
@@ -153,7 +295,12 @@ impl<'a> StatefulWidget for Explorer<'a> {
         let items: Vec<ListItem> = state
             .flat_items
             .iter()
-            .map(Explorer::list_item(state.selected_path(), state.is_open()))
+            .map(Explorer::list_item(
+                state.selected_path(),
+                state.is_open(),
+                // The highlight symbol is drawn before the items.
+                usize::from(width).saturating_sub(1),
+            ))
             .collect();
 
         if state.open {
@@ -172,9 +319,10 @@ impl<'a> StatefulWidget for Explorer<'a> {
 
             List::new(items)
                 .block(
-                    block
-                        .title(" ▶ ")
-                        .borders(Borders::LEFT | Borders::TOP | Borders::BOTTOM),
+                    block.title(" ▶ ").borders(
+                        self.chrome
+                            .borders(Borders::LEFT | Borders::TOP | Borders::BOTTOM),
+                    ),
                 )
                 .highlight_style(Style::new().reversed().dark_gray())
                 .highlight_symbol(" ")
@@ -189,6 +337,7 @@ mod tests {
     use basalt_core::obsidian::VaultEntry;
     use insta::assert_snapshot;
     use ratatui::{backend::TestBackend, Terminal};
+    use std::{collections::BTreeMap, fs};
 
     #[test]
     fn test_render_entries() {
@@ -256,4 +405,232 @@ mod tests {
             assert_snapshot!(terminal.backend());
         });
     }
+
+    #[test]
+    fn test_with_attachments() {
+        let file = |path: &str| {
+            VaultEntry::File(Note {
+                name: path.split('.').next().unwrap_or_default().into(),
+                path: path.into(),
+            })
+        };
+        let entries = vec![
+            file("Note.md"),
+            file("Board.canvas"),
+            file("Report.PDF"),
+            file("Song.mp3"),
+            VaultEntry::Directory {
+                name: "Assets".into(),
+                path: "Assets".into(),
+                entries: vec![file("Assets/Logo.png"), file("Assets/Data.csv")],
+            },
+        ];
+
+        let mut state = ExplorerState::new("Test", entries)
+            .with_attachments(&["pdf".to_string(), "png".to_string()]);
+        state.list_state.select(Some(0));
+        state.select();
+
+        let paths: Vec<_> = state
+            .flat_items
+            .iter()
+            .map(|(item, _)| item.path().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "Assets",
+                "Assets/Logo.png",
+                "Board.canvas",
+                "Note.md",
+                "Report.PDF"
+            ]
+        );
+
+        state.list_state.select(Some(4));
+        assert_eq!(
+            update(&Message::Open, Size::default(), &mut state),
+            Some(AppMessage::OpenAttachment("Report.PDF".into()))
+        );
+    }
+
+    #[test]
+    fn test_saved_searches() {
+        let vault = crate::testing::vault(
+            "basalt-explorer-saved-searches",
+            &[
+                ("Books/Dune.md", "#book\n\n- [ ] Read\n"),
+                ("Books/Emma.md", "#book\n\n- [x] Read\n"),
+                ("Inbox.md", "Buy a #book\n"),
+            ],
+        );
+        let searches = BTreeMap::from([("Reading".to_string(), "tag:book task:open".to_string())]);
+
+        let mut state =
+            ExplorerState::new("Test", vault.entries()).with_saved_searches(&vault.path, &searches);
+        let names = |state: &ExplorerState| -> Vec<String> {
+            state
+                .flat_items
+                .iter()
+                .map(|(item, _)| item.name().to_string())
+                .collect()
+        };
+        assert_eq!(names(&state), ["Reading", "Books", "Inbox"]);
+
+        state.select();
+        assert_eq!(names(&state), ["Reading", "Dune", "Books", "Inbox"]);
+
+        fs::write(vault.path.join("Books/Emma.md"), "#book\n\n- [ ] Reread\n").unwrap();
+        state.refresh(vault.entries());
+        assert_eq!(names(&state), ["Reading", "Dune", "Emma", "Books", "Inbox"]);
+
+        state.list_state.select(Some(2));
+        update(&Message::Open, Size::default(), &mut state);
+        assert_eq!(
+            state.selected_path(),
+            Some(vault.path.join("Books/Emma.md"))
+        );
+
+        state.list_state.select(Some(0));
+        state.select();
+        assert_eq!(names(&state), ["Reading", "Books", "Inbox"]);
+    }
+
+    #[test]
+    fn test_expand_and_collapse_all() {
+        let file = |path: &str| {
+            VaultEntry::File(Note {
+                name: path.rsplit('/').next().unwrap_or_default().into(),
+                path: path.into(),
+            })
+        };
+        let entries = vec![
+            VaultEntry::Directory {
+                name: "Garden".into(),
+                path: "Garden".into(),
+                entries: vec![
+                    VaultEntry::Directory {
+                        name: "Pond".into(),
+                        path: "Garden/Pond".into(),
+                        entries: vec![file("Garden/Pond/Fish")],
+                    },
+                    file("Garden/Shed"),
+                ],
+            },
+            VaultEntry::Directory {
+                name: "House".into(),
+                path: "House".into(),
+                entries: vec![file("House/Roof")],
+            },
+        ];
+
+        let mut state = ExplorerState::new("Test", entries.clone());
+        let names = |state: &ExplorerState| -> Vec<String> {
+            state
+                .flat_items
+                .iter()
+                .map(|(item, _)| item.name().to_string())
+                .collect()
+        };
+
+        update(&Message::ExpandAll, Size::default(), &mut state);
+        assert_eq!(
+            names(&state),
+            ["Garden", "Pond", "Fish", "Shed", "House", "Roof"]
+        );
+
+        state.list_state.select(Some(2));
+        update(&Message::CollapseSubtree, Size::default(), &mut state);
+        assert_eq!(names(&state), ["Garden", "Pond", "Shed", "House", "Roof"]);
+        assert_eq!(state.highlighted_item().map(Item::name), Some("Pond"));
+
+        update(&Message::CollapseAll, Size::default(), &mut state);
+        assert_eq!(names(&state), ["Garden", "House"]);
+        assert_eq!(state.highlighted_item().map(Item::name), Some("Garden"));
+
+        update(&Message::ExpandSubtree, Size::default(), &mut state);
+        assert_eq!(names(&state), ["Garden", "Pond", "Fish", "Shed", "House"]);
+        assert_eq!(
+            state.expanded_folders(),
+            [PathBuf::from("Garden"), PathBuf::from("Garden/Pond")]
+        );
+
+        let state = ExplorerState::new("Test", entries).with_expanded_folders(&["House".into()]);
+        assert_eq!(names(&state), ["Garden", "House", "Roof"]);
+    }
+
+    #[test]
+    fn test_long_names() {
+        let entries = vec![VaultEntry::Directory {
+            name: "Meetings".into(),
+            path: "Meetings".into(),
+            entries: vec![VaultEntry::File(Note {
+                name: "2024-05-01 Roadmap review".into(),
+                path: "Meetings/2024-05-01 Roadmap review.md".into(),
+            })],
+        }];
+
+        let mut state = ExplorerState::new("Test", entries);
+        state.select();
+        state.next(1);
+
+        let mut terminal = Terminal::new(TestBackend::new(24, 4)).unwrap();
+        terminal
+            .draw(|frame| Explorer::default().render(frame.area(), frame.buffer_mut(), &mut state))
+            .unwrap();
+
+        let row: String = (0..24)
+            .map(|x| terminal.backend().buffer()[(x, 2)].symbol())
+            .collect();
+        assert!(row.contains("│   2024-05-…p review┃"), "{row}");
+
+        assert_eq!(
+            update(&Message::ShowPath, Size::default(), &mut state),
+            Some(AppMessage::SetStatus(
+                "Meetings/2024-05-01 Roadmap review.md".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_toggle_keeps_state() {
+        let file = |path: String| {
+            VaultEntry::File(Note {
+                name: path.trim_end_matches(".md").into(),
+                path: path.into(),
+            })
+        };
+
+        let entries = vec![
+            VaultEntry::Directory {
+                name: "Archive".into(),
+                path: "Archive".into(),
+                entries: (0..20)
+                    .map(|i| file(format!("Archive/{i:02}.md")))
+                    .collect(),
+            },
+            file("Inbox.md".into()),
+        ];
+
+        let mut state = ExplorerState::new("Test", entries);
+        state.select();
+        update(&Message::Down, Size::default(), &mut state);
+        state.next(14);
+
+        // The offset follows the listed notes of expanded folders, not only the top-level items.
+        state.update_offset_mut(10);
+        let offset = state.list_state.offset();
+        assert_eq!(offset, 10);
+
+        update(&Message::Toggle, Size::default(), &mut state);
+        update(&Message::Toggle, Size::default(), &mut state);
+        state.update_offset_mut(10);
+
+        assert_eq!(state.list_state.selected(), Some(15));
+        assert_eq!(state.list_state.offset(), offset);
+        assert_eq!(
+            state.highlighted_note().map(|note| note.name.as_str()),
+            Some("Archive/14")
+        );
+    }
 }
@@ -1,6 +1,6 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use basalt_core::obsidian::{Note, VaultEntry};
+use basalt_core::obsidian::{Note, Query, VaultEntry};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Item {
@@ -11,17 +11,29 @@ pub enum Item {
         expanded: bool,
         items: Vec<Item>,
     },
+    /// A saved search, which lists the notes that match its query while it is expanded.
+    Search {
+        name: String,
+        query: Query,
+        expanded: bool,
+        items: Vec<Item>,
+    },
 }
 
 impl Item {
     pub(crate) fn name(&self) -> &str {
         match self {
-            Self::Directory { name, .. } | Self::File(Note { name, .. }) => name.as_str(),
+            Self::Directory { name, .. }
+            | Self::Search { name, .. }
+            | Self::File(Note { name, .. }) => name.as_str(),
         }
     }
 
-    pub(crate) fn is_dir(&self) -> bool {
-        matches!(self, Self::Directory { .. })
+    pub(crate) fn path(&self) -> &Path {
+        match self {
+            Self::Directory { path, .. } | Self::File(Note { path, .. }) => path.as_path(),
+            Self::Search { .. } => Path::new(""),
+        }
     }
 }
 
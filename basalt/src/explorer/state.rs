@@ -1,12 +1,14 @@
 use std::{
     cmp::Ordering,
+    collections::BTreeMap,
     path::{Path, PathBuf},
 };
 
-use basalt_core::obsidian::{Note, VaultEntry};
+use basalt_core::obsidian::{Note, Query, VaultEntry};
 use ratatui::widgets::ListState;
 
 use super::Item;
+use crate::type_ahead;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub enum Sort {
@@ -27,6 +29,70 @@ pub struct ExplorerState<'a> {
     pub(crate) sort: Sort,
     pub(crate) list_state: ListState,
     pub(crate) active: bool,
+    /// Extensions of the attachments listed besides notes and canvases, or `None` to list all
+    /// files.
+    pub(crate) attachments: Option<Vec<String>>,
+    /// Root of the vault, which the paths matched by saved searches are relative to.
+    pub(crate) root: PathBuf,
+}
+
+/// Returns `true` if the explorer lists the item: folders, notes, canvases and attachments with
+/// one of the extensions.
+fn is_listed(item: &Item, attachments: Option<&[String]>) -> bool {
+    let (Item::File(Note { path, .. }), Some(extensions)) = (item, attachments) else {
+        return true;
+    };
+
+    path.extension().is_some_and(|ext| {
+        let ext = ext.to_string_lossy();
+        ext == "md"
+            || ext == "canvas"
+            || extensions
+                .iter()
+                .any(|extension| extension.eq_ignore_ascii_case(&ext))
+    })
+}
+
+/// Removes the files the explorer does not list from the items and the items of their folders.
+fn listed_items(items: Vec<Item>, attachments: Option<&[String]>) -> Vec<Item> {
+    items
+        .into_iter()
+        .filter(|item| is_listed(item, attachments))
+        .map(|item| match item {
+            Item::Directory {
+                name,
+                path,
+                expanded,
+                items,
+            } => Item::Directory {
+                name,
+                path,
+                expanded,
+                items: listed_items(items, attachments),
+            },
+            item => item,
+        })
+        .collect()
+}
+
+/// Returns the notes among the items and the items of their folders that match the query.
+fn search(root: &Path, items: &[Item], query: &Query) -> Vec<Item> {
+    items
+        .iter()
+        .flat_map(|item| match item {
+            Item::File(note) if note.path.extension().is_some_and(|ext| ext == "md") => {
+                let contents = Note::read_to_string(note).unwrap_or_default();
+                let path = note.path.strip_prefix(root).unwrap_or(&note.path);
+
+                match query.matches(path, &contents) {
+                    true => vec![item.clone()],
+                    false => vec![],
+                }
+            }
+            Item::Directory { items, .. } => search(root, items, query),
+            _ => vec![],
+        })
+        .collect()
 }
 
 /// Calculates the vertical offset of list items in rows.
@@ -72,6 +138,11 @@ pub fn flatten(sort: Sort, depth: usize) -> impl Fn(&Item) -> Vec<(Item, usize)>
             expanded: true,
             items,
             ..
+        }
+        | Item::Search {
+            expanded: true,
+            items,
+            ..
         } => [(item.clone(), depth)]
             .into_iter()
             .chain({
@@ -85,22 +156,32 @@ pub fn flatten(sort: Sort, depth: usize) -> impl Fn(&Item) -> Vec<(Item, usize)>
             .collect(),
         Item::Directory {
             expanded: false, ..
+        }
+        | Item::Search {
+            expanded: false, ..
         } => [(item.clone(), depth)].to_vec(),
     }
 }
 
+/// Saved searches are listed first, then folders and then files.
+fn rank(item: &Item) -> u8 {
+    match item {
+        Item::Search { .. } => 0,
+        Item::Directory { .. } => 1,
+        Item::File(..) => 2,
+    }
+}
+
 fn sort_items_by(sort: Sort) -> impl Fn(&Item, &Item) -> Ordering {
-    move |a, b| match (a.is_dir(), b.is_dir()) {
-        (true, false) => Ordering::Less,
-        (false, true) => Ordering::Greater,
-        _ => {
+    move |a, b| {
+        rank(a).cmp(&rank(b)).then_with(|| {
             let a = a.name().to_lowercase();
             let b = b.name().to_lowercase();
             match sort {
                 Sort::Asc => a.cmp(&b),
                 Sort::Desc => b.cmp(&a),
             }
-        }
+        })
     }
 }
 
@@ -125,6 +206,35 @@ impl<'a> ExplorerState<'a> {
         state
     }
 
+    /// Lists only notes, canvases and the attachments with the given extensions, and hides the
+    /// other files of the vault.
+    pub fn with_attachments(mut self, extensions: &[String]) -> Self {
+        self.attachments = Some(extensions.to_vec());
+        let items = listed_items(self.items.clone(), self.attachments.as_deref());
+        self.flatten_with_items(&items);
+        self
+    }
+
+    /// Lists the saved searches by their names above the folders and notes of the vault at
+    /// `root`. The query of a search is evaluated every time the search is expanded.
+    pub fn with_saved_searches(mut self, root: &Path, searches: &BTreeMap<String, String>) -> Self {
+        self.root = root.to_path_buf();
+
+        let items: Vec<Item> = searches
+            .iter()
+            .map(|(name, query)| Item::Search {
+                name: name.clone(),
+                query: Query::from(query.as_str()),
+                expanded: false,
+                items: vec![],
+            })
+            .chain(self.items.clone())
+            .collect();
+
+        self.flatten_with_items(&items);
+        self
+    }
+
     pub fn set_active(&mut self, active: bool) {
         self.active = active;
     }
@@ -160,9 +270,9 @@ impl<'a> ExplorerState<'a> {
     }
 
     pub fn update_offset_mut(&mut self, window_height: usize) -> &Self {
-        if !self.items.is_empty() {
+        if !self.flat_items.is_empty() {
             let idx = self.list_state.selected().unwrap_or_default();
-            let items_count = self.items.len();
+            let items_count = self.flat_items.len();
 
             let offset = calculate_offset(idx, items_count, window_height);
 
@@ -223,6 +333,31 @@ impl<'a> ExplorerState<'a> {
 
                 self.flatten_with_items(&items)
             }
+            (Item::Search { name, .. }, _) => {
+                let items: Vec<Item> = self
+                    .items
+                    .iter()
+                    .map(|item| match item {
+                        Item::Search {
+                            name: search_name,
+                            query,
+                            expanded,
+                            ..
+                        } if search_name == name => Item::Search {
+                            name: search_name.clone(),
+                            query: query.clone(),
+                            expanded: !expanded,
+                            items: match expanded {
+                                true => vec![],
+                                false => search(&self.root, &self.items, query),
+                            },
+                        },
+                        item => item.clone(),
+                    })
+                    .collect();
+
+                self.flatten_with_items(&items)
+            }
             (Item::File(note), _) => {
                 self.selected_note = Some(note.clone());
                 self.selected_item_index = Some(selected_item_index);
@@ -231,6 +366,242 @@ impl<'a> ExplorerState<'a> {
         }
     }
 
+    /// Returns the note or folder under the selector.
+    pub fn highlighted_item(&self) -> Option<&Item> {
+        self.flat_items
+            .get(self.list_state.selected()?)
+            .map(|(item, _)| item)
+    }
+
+    /// Returns the note under the selector, if the selector is on a note instead of a folder.
+    pub fn highlighted_note(&self) -> Option<&Note> {
+        match self.flat_items.get(self.list_state.selected()?) {
+            Some((Item::File(note), _)) => Some(note),
+            _ => None,
+        }
+    }
+
+    fn expanded_paths(items: &[Item]) -> Vec<PathBuf> {
+        items
+            .iter()
+            .flat_map(|item| match item {
+                Item::Directory {
+                    path,
+                    expanded,
+                    items,
+                    ..
+                } => expanded
+                    .then(|| path.clone())
+                    .into_iter()
+                    .chain(Self::expanded_paths(items))
+                    .collect(),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    fn expand_paths(item: Item, paths: &[PathBuf]) -> Item {
+        match item {
+            Item::Directory {
+                name,
+                path,
+                expanded,
+                items,
+            } => Item::Directory {
+                expanded: expanded || paths.contains(&path),
+                items: items
+                    .into_iter()
+                    .map(|item| Self::expand_paths(item, paths))
+                    .collect(),
+                name,
+                path,
+            },
+            item => item,
+        }
+    }
+
+    /// Expands or collapses the folders under `root`, or all folders and saved searches if `root`
+    /// is `None`. Saved searches are only collapsed, as expanding them evaluates their query.
+    fn set_expanded(item: Item, expanded: bool, root: Option<&Path>) -> Item {
+        match item {
+            Item::Directory {
+                name,
+                path,
+                expanded: was_expanded,
+                items,
+            } => Item::Directory {
+                expanded: match root {
+                    Some(root) if !path.starts_with(root) => was_expanded,
+                    _ => expanded,
+                },
+                items: items
+                    .into_iter()
+                    .map(|item| Self::set_expanded(item, expanded, root))
+                    .collect(),
+                name,
+                path,
+            },
+            Item::Search { name, query, .. } if root.is_none() && !expanded => Item::Search {
+                name,
+                query,
+                expanded: false,
+                items: vec![],
+            },
+            item => item,
+        }
+    }
+
+    /// Returns the folder under the selector, or the folder of the note under the selector.
+    fn highlighted_folder(&self) -> Option<PathBuf> {
+        match self.highlighted_item()? {
+            Item::Directory { path, .. } => Some(path.clone()),
+            Item::File(note) => {
+                let parent = note.path.parent()?;
+                self.flat_items
+                    .iter()
+                    .any(|(item, _)| matches!(item, Item::Directory { path, .. } if path == parent))
+                    .then(|| parent.to_path_buf())
+            }
+            Item::Search { .. } => None,
+        }
+    }
+
+    /// Expands or collapses every folder of the tree, or with `subtree` only the folder under the
+    /// selector and the folders in it. The selector stays on the same item, or moves to the
+    /// folder the item was collapsed into.
+    pub fn set_all_expanded(&mut self, expanded: bool, subtree: bool) {
+        let root = match subtree {
+            true => match self.highlighted_folder() {
+                Some(folder) => Some(folder),
+                None => return,
+            },
+            false => None,
+        };
+        let highlighted = self
+            .highlighted_item()
+            .map(|item| item.path().to_path_buf());
+
+        let items: Vec<Item> = self
+            .items
+            .clone()
+            .into_iter()
+            .map(|item| Self::set_expanded(item, expanded, root.as_deref()))
+            .collect();
+        self.flatten_with_items(&items);
+
+        if let Some(index) = highlighted.and_then(|highlighted| {
+            highlighted.ancestors().find_map(|path| {
+                self.flat_items
+                    .iter()
+                    .position(|(item, _)| !path.as_os_str().is_empty() && item.path() == path)
+            })
+        }) {
+            self.list_state.select(Some(index));
+        }
+    }
+
+    /// Returns the paths of the listed files other than notes, i.e. the canvases and attachments.
+    pub fn attachments(&self) -> Vec<&Path> {
+        fn files(items: &[Item]) -> Vec<&Path> {
+            items
+                .iter()
+                .flat_map(|item| match item {
+                    Item::File(note) if note.path.extension().is_some_and(|ext| ext != "md") => {
+                        vec![note.path.as_path()]
+                    }
+                    Item::Directory { items, .. } => files(items),
+                    _ => vec![],
+                })
+                .collect()
+        }
+
+        files(&self.items)
+    }
+
+    /// Returns the paths of the expanded folders.
+    pub fn expanded_folders(&self) -> Vec<PathBuf> {
+        Self::expanded_paths(&self.items)
+    }
+
+    /// Expands the folders with the given paths, e.g. the folders that were expanded when the
+    /// vault was open before.
+    pub fn with_expanded_folders(mut self, paths: &[PathBuf]) -> Self {
+        let items: Vec<Item> = self
+            .items
+            .clone()
+            .into_iter()
+            .map(|item| Self::expand_paths(item, paths))
+            .collect();
+
+        self.flatten_with_items(&items);
+        self
+    }
+
+    /// Replaces the items with the given entries, e.g. after files have been added to the vault.
+    ///
+    /// Expanded folders stay expanded, expanded saved searches are evaluated again, and the
+    /// selector is kept in the same position when possible.
+    pub fn refresh(&mut self, entries: Vec<VaultEntry>) {
+        let expanded = Self::expanded_paths(&self.items);
+        let items: Vec<Item> = entries
+            .into_iter()
+            .map(|entry| Self::expand_paths(entry.into(), &expanded))
+            .collect();
+        let items = listed_items(items, self.attachments.as_deref());
+
+        let searches: Vec<Item> = self
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Search {
+                    name,
+                    query,
+                    expanded,
+                    ..
+                } => Some(Item::Search {
+                    name: name.clone(),
+                    query: query.clone(),
+                    expanded: *expanded,
+                    items: match expanded {
+                        true => search(&self.root, &items, query),
+                        false => vec![],
+                    },
+                }),
+                _ => None,
+            })
+            .collect();
+
+        self.flatten_with_items(&[searches, items].concat());
+
+        let index = self
+            .list_state
+            .selected()
+            .map(|index| index.min(self.flat_items.len().saturating_sub(1)));
+        self.list_state.select(index);
+    }
+
+    /// Moves the selector to the item with the given path. Parent folders of the item are
+    /// expanded, so that the item becomes visible.
+    pub fn select_path(&mut self, path: &Path) {
+        let ancestors: Vec<PathBuf> = path.ancestors().map(Path::to_path_buf).collect();
+        let items: Vec<Item> = self
+            .items
+            .clone()
+            .into_iter()
+            .map(|item| Self::expand_paths(item, &ancestors))
+            .collect();
+
+        self.flatten_with_items(&items);
+
+        if let Some(index) = self
+            .flat_items
+            .iter()
+            .position(|(item, _)| item.path() == path)
+        {
+            self.list_state.select(Some(index));
+        }
+    }
+
     pub fn selected_path(&self) -> Option<PathBuf> {
         self.selected_item_path.clone()
     }
@@ -253,4 +624,29 @@ impl<'a> ExplorerState<'a> {
 
         self.list_state.select(index);
     }
+
+    /// Moves the selector to the first item whose name starts with the typed query. Returns
+    /// `false` if no item matches.
+    pub fn type_ahead(&mut self, query: &str) -> bool {
+        let names = self.flat_items.iter().map(|(item, _)| item.name());
+        let start = self.list_state.selected().unwrap_or_default();
+        let index = type_ahead::find(names, start, query);
+
+        if index.is_some() {
+            self.list_state.select(index);
+        }
+        index.is_some()
+    }
+
+    pub fn first(&mut self) {
+        if !self.flat_items.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    pub fn last(&mut self) {
+        if !self.flat_items.is_empty() {
+            self.list_state.select(Some(self.flat_items.len() - 1));
+        }
+    }
 }
@@ -0,0 +1,243 @@
+//! Extracting a block or the selected text of a note into another note. The target is given as a
+//! note name relative to the vault root, optionally followed by a heading, e.g. `Projects#Ideas`.
+use std::path::{Path, PathBuf};
+
+/// Splits the target into the note name and the heading, if any.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::extract;
+///
+/// assert_eq!(extract::split_target("Projects#Ideas"), ("Projects", Some("Ideas")));
+/// assert_eq!(extract::split_target("Projects.md"), ("Projects", None));
+/// ```
+pub fn split_target(target: &str) -> (&str, Option<&str>) {
+    let (name, heading) = match target.split_once('#') {
+        Some((name, heading)) => (name, Some(heading.trim()).filter(|h| !h.is_empty())),
+        None => (target, None),
+    };
+
+    let name = name.trim();
+    (name.strip_suffix(".md").unwrap_or(name), heading)
+}
+
+/// Returns the path of the target note in the vault, with the `.md` extension.
+pub fn note_path(vault_path: &Path, name: &str) -> PathBuf {
+    vault_path.join(format!("{name}.md"))
+}
+
+/// Returns the wikilink to the target note, or to the heading of the target note.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::extract;
+///
+/// assert_eq!(extract::link("Projects", Some("Ideas")), "[[Projects#Ideas]]");
+/// assert_eq!(extract::link("Projects", None), "[[Projects]]");
+/// ```
+pub fn link(name: &str, heading: Option<&str>) -> String {
+    match heading {
+        Some(heading) => format!("[[{name}#{heading}]]"),
+        None => format!("[[{name}]]"),
+    }
+}
+
+/// Returns the heading as written in the anchor of a wikilink. Characters that end the anchor or
+/// the link, e.g. `#`, `|` and `]`, are left out, as Obsidian does, so that `Ideas: Q1 | Q2`
+/// becomes `Ideas Q1 Q2`.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::extract;
+///
+/// assert_eq!(extract::heading_anchor("Ideas"), "Ideas");
+/// assert_eq!(extract::heading_anchor("Ideas: Q1 | Q2"), "Ideas Q1 Q2");
+/// assert_eq!(extract::heading_anchor("[[Pond]] #garden"), "Pond garden");
+/// ```
+pub fn heading_anchor(heading: &str) -> String {
+    heading
+        .split(|c| "#^[]|:".contains(c))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the level of the ATX heading on the line, e.g. `2` for `## Ideas`, and its text.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = &line[level..];
+
+    ((1..=6).contains(&level) && (text.is_empty() || text.starts_with([' ', '\t'])))
+        .then(|| (level, text.trim().trim_end_matches('#').trim_end()))
+}
+
+/// Returns the byte offset where the text is appended: the end of the section under the heading,
+/// or the end of the contents without a heading. Returns `None` if the heading is missing.
+/// Headings in fenced code blocks are skipped.
+fn insert_offset(contents: &str, heading_text: Option<&str>) -> Option<usize> {
+    let Some(heading_text) = heading_text else {
+        return Some(contents.len());
+    };
+
+    let mut in_fence = false;
+    let mut section_level = None;
+    let mut offset = 0;
+
+    for line in contents.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+
+        let Some((level, text)) = heading(line).filter(|_| !in_fence) else {
+            continue;
+        };
+
+        match section_level {
+            Some(section_level) if level <= section_level => return Some(start),
+            None if text == heading_text => section_level = Some(level),
+            _ => {}
+        }
+    }
+
+    section_level.map(|_| contents.len())
+}
+
+/// Returns the contents with the text appended as a block to the end of the section under the
+/// heading, or to the end of the contents without a heading. A missing heading is added as a
+/// second level heading at the end of the contents.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::extract;
+///
+/// assert_eq!(
+///     extract::append("# Projects\n\n## Ideas\n\nGarden\n\n## Done\n", Some("Ideas"), "Shed"),
+///     "# Projects\n\n## Ideas\n\nGarden\n\nShed\n\n## Done\n"
+/// );
+/// ```
+pub fn append(contents: &str, heading: Option<&str>, text: &str) -> String {
+    let text = text.trim_matches('\n');
+
+    let (offset, text) = match insert_offset(contents, heading) {
+        Some(offset) => (offset, text.to_string()),
+        None => (
+            contents.len(),
+            format!("## {}\n\n{text}", heading.unwrap_or_default()),
+        ),
+    };
+
+    let before = contents[..offset].trim_end_matches('\n');
+    let after = &contents[offset..];
+
+    let mut appended = String::new();
+    if !before.is_empty() {
+        appended.push_str(before);
+        appended.push_str("\n\n");
+    }
+    appended.push_str(&text);
+    appended.push('\n');
+    if !after.is_empty() {
+        appended.push('\n');
+        appended.push_str(after);
+    }
+
+    appended
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_append() {
+        let contents = indoc! {"
+            # Projects
+
+            ## Ideas
+
+            ```sh
+            # Not a heading
+            ```
+
+            ### Garden
+
+            Shed
+
+            ## Done
+        "};
+
+        let tests = [
+            ("", None, "- Item", "- Item\n"),
+            ("# Inbox", None, "Text\n", "# Inbox\n\nText\n"),
+            (
+                "# Inbox\n",
+                Some("Later"),
+                "Text",
+                "# Inbox\n\n## Later\n\nText\n",
+            ),
+            (
+                contents,
+                Some("Ideas"),
+                "Pond",
+                indoc! {"
+                    # Projects
+
+                    ## Ideas
+
+                    ```sh
+                    # Not a heading
+                    ```
+
+                    ### Garden
+
+                    Shed
+
+                    Pond
+
+                    ## Done
+                "},
+            ),
+            (
+                contents,
+                Some("Done"),
+                "Fence",
+                &format!("{contents}\nFence\n"),
+            ),
+            (
+                contents,
+                Some("Not a heading"),
+                "Text",
+                &format!("{contents}\n## Not a heading\n\nText\n"),
+            ),
+        ];
+
+        tests
+            .into_iter()
+            .for_each(|(contents, heading, text, expected)| {
+                assert_eq!(
+                    append(contents, heading, text),
+                    expected,
+                    "With {heading:?}"
+                );
+            });
+    }
+
+    #[test]
+    fn test_split_target() {
+        assert_eq!(split_target(" Inbox # "), ("Inbox", None));
+        assert_eq!(
+            split_target("Work/Projects.md#Ideas"),
+            ("Work/Projects", Some("Ideas"))
+        );
+    }
+}
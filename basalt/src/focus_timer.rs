@@ -0,0 +1,129 @@
+//! Focus timer for writing sessions, e.g. 25 minutes of writing as in the Pomodoro technique. The
+//! time left is shown in the status bar, and the `on_end` command of the `[timer]` section runs
+//! when a session ends, e.g. to send a notification.
+use std::time::{Duration, Instant};
+
+use crate::{app::Message as AppMessage, config::TimerConfig};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Starts a session of the configured length, starting over if one is running.
+    Start,
+    Stop,
+    /// Redraws the time left, sent when it changed by a second.
+    Tick,
+    /// Ends the session when its time is up, running the `on_end` command.
+    Ended,
+}
+
+pub fn update<'a>(
+    message: &Message,
+    config: &TimerConfig,
+    state: &mut FocusTimer,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Start => {
+            state.start(Duration::from_secs(config.minutes * 60), Instant::now());
+            Some(AppMessage::SetStatus(format!(
+                "Focus session of {} minutes started",
+                config.minutes
+            )))
+        }
+        Message::Stop => Some(AppMessage::SetStatus(match state.stop(Instant::now()) {
+            Some(left) => format!("Focus session stopped with {} left", format_time(left)),
+            None => "No focus session running".into(),
+        })),
+        Message::Tick => None,
+        Message::Ended => {
+            state.stop(Instant::now());
+            let command = config.on_end.trim();
+            (!command.is_empty()).then(|| AppMessage::Spawn(command.to_string()))
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FocusTimer {
+    ends_at: Option<Instant>,
+    /// Seconds left at the last tick, to tick once a second.
+    ticked: Option<u64>,
+}
+
+impl FocusTimer {
+    pub fn start(&mut self, duration: Duration, now: Instant) {
+        self.ends_at = Some(now + duration);
+        self.ticked = None;
+    }
+
+    /// Stops the session and returns the time that was left, or `None` if none was running.
+    pub fn stop(&mut self, now: Instant) -> Option<Duration> {
+        let left = self.left(now);
+        self.ends_at = None;
+        left
+    }
+
+    /// Returns the time left of the running session.
+    pub fn left(&self, now: Instant) -> Option<Duration> {
+        self.ends_at
+            .map(|ends_at| ends_at.saturating_duration_since(now))
+    }
+
+    /// Returns [`Message::Tick`] when the seconds left changed since the last tick, and
+    /// [`Message::Ended`] when the time is up.
+    pub fn tick(&mut self, now: Instant) -> Option<Message> {
+        let left = self.left(now)?;
+        if left.is_zero() {
+            return Some(Message::Ended);
+        }
+
+        let seconds = left.as_millis().div_ceil(1000) as u64;
+        (self.ticked.replace(seconds) != Some(seconds)).then_some(Message::Tick)
+    }
+}
+
+/// Formats the time as minutes and seconds, rounding up to the next second, so that a session
+/// shows its full length when it starts and `00:00` only when it ended.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::focus_timer::format_time;
+/// use std::time::Duration;
+///
+/// assert_eq!(format_time(Duration::from_secs(25 * 60)), "25:00");
+/// assert_eq!(format_time(Duration::from_millis(61_500)), "01:02");
+/// ```
+pub fn format_time(time: Duration) -> String {
+    let seconds = time.as_millis().div_ceil(1000);
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick() {
+        let start = Instant::now();
+        let mut timer = FocusTimer::default();
+        assert_eq!(timer.tick(start), None);
+
+        timer.start(Duration::from_secs(3), start);
+        assert_eq!(timer.tick(start), Some(Message::Tick));
+        assert_eq!(timer.tick(start + Duration::from_millis(500)), None);
+        assert_eq!(
+            timer.tick(start + Duration::from_secs(1)),
+            Some(Message::Tick)
+        );
+        assert_eq!(
+            timer.tick(start + Duration::from_secs(3)),
+            Some(Message::Ended)
+        );
+
+        assert_eq!(
+            timer.stop(start + Duration::from_secs(3)),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(timer.tick(start + Duration::from_secs(4)), None);
+    }
+}
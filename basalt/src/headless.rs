@@ -0,0 +1,312 @@
+//! Headless mode. Runs the application on a virtual terminal instead of the real one and feeds it
+//! a script of commands, then dumps the screen and the state as text. Used by the rendering tests
+//! and for automating note workflows, e.g. `basalt --headless script.txt`.
+//!
+//! A script has one command per line, with the same names as in the key bindings of the
+//! configuration, e.g. `explorer_down` or `exec:vi %note_path`. Commands run regardless of the
+//! focused pane. Empty lines and lines starting with `#` are skipped, and a few lines drive the
+//! application like a user would:
+//!
+//! - `vault <name>` opens the vault with the name
+//! - `keys <keys>` presses the keys, written as in the configuration, e.g. `keys j j enter`
+//! - `type <text>` types the text one character at a time
+//!
+//! The script stops at the `quit` command.
+use std::fmt;
+
+use basalt_core::obsidian::Vault;
+use ratatui::{
+    backend::TestBackend,
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::Size,
+    Terminal,
+};
+
+use crate::{
+    app::{App, AppState, Message},
+    command::Command,
+    config::{Config, Key},
+};
+
+/// Error of a script line, with the line number starting from 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+pub struct Headless<'a> {
+    app: App<'a>,
+    config: &'a Config<'a>,
+    state: AppState<'a>,
+    terminal: Terminal<TestBackend>,
+    vaults: Vec<&'a Vault>,
+}
+
+impl<'a> Headless<'a> {
+    /// Starts the application on the splash screen with the vaults, like [`App::start`] does.
+    pub fn new(
+        config: &'a Config<'a>,
+        version: &'a str,
+        vaults: Vec<&'a Vault>,
+        width: u16,
+        height: u16,
+    ) -> Self {
+        let state = App::initial_state(version, Size::new(width, height), vaults.clone());
+        let mut app = App::with_config(state, config.clone());
+        let state = std::mem::take(&mut app.state).set_running(true);
+
+        let mut headless = Self {
+            app,
+            config,
+            state,
+            terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+            vaults,
+        };
+
+        headless.state.fit_panes();
+        let message = App::startup_message(config, &mut headless.state);
+        headless.update(message);
+        headless
+    }
+
+    /// Returns `true` until the application quits.
+    pub fn is_running(&self) -> bool {
+        self.state.is_running
+    }
+
+    /// Handles the message and the messages it results in, e.g. to get to a state that has no key
+    /// binding.
+    pub fn send(&mut self, message: Message<'a>) -> &mut Self {
+        self.update(Some(message));
+        self
+    }
+
+    fn update(&mut self, mut message: Option<Message<'a>>) {
+        while message.is_some() {
+            message = App::update(&mut self.terminal, self.config, &mut self.state, message);
+        }
+    }
+
+    /// Presses the keys, given as in the configuration and separated by whitespace, e.g.
+    /// `"j j ctrl+o enter"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key cannot be parsed.
+    pub fn keys(&mut self, keys: &str) -> &mut Self {
+        if let Err(err) = self.try_keys(keys) {
+            panic!("{err}");
+        }
+        self
+    }
+
+    fn try_keys(&mut self, keys: &str) -> Result<(), String> {
+        keys.split_whitespace().try_for_each(|key| {
+            let key: Key = key
+                .parse()
+                .map_err(|err| format!("invalid key {key}: {err}"))?;
+            self.press(KeyEvent::new(key.code, key.modifiers));
+            Ok(())
+        })
+    }
+
+    /// Types the text one character at a time.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        text.chars().for_each(|c| {
+            let modifiers = match c.is_uppercase() {
+                true => KeyModifiers::SHIFT,
+                false => KeyModifiers::NONE,
+            };
+            self.press(KeyEvent::new(KeyCode::Char(c), modifiers));
+        });
+        self
+    }
+
+    fn press(&mut self, key: KeyEvent) {
+        let message = App::handle_key_event(self.config, &self.state, &key);
+        self.update(message);
+    }
+
+    /// Runs the command like its key binding would, regardless of the focused pane.
+    pub(crate) fn command(&mut self, command: Command) -> &mut Self {
+        self.send(command.into())
+    }
+
+    /// Runs the script line by line until the end or the `quit` command. Stops at the first line
+    /// that cannot be run.
+    pub fn run_script(&mut self, script: &str) -> Result<(), ScriptError> {
+        for (index, line) in script.lines().enumerate() {
+            if !self.is_running() {
+                break;
+            }
+
+            self.run_line(line.trim()).map_err(|message| ScriptError {
+                line: index + 1,
+                message,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn run_line(&mut self, line: &str) -> Result<(), String> {
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+
+        match line.split_once(' ') {
+            Some(("vault", name)) => {
+                let vault = self
+                    .vaults
+                    .iter()
+                    .find(|vault| vault.name == name.trim())
+                    .copied()
+                    .ok_or(format!("no vault named {name}"))?;
+                self.state.splash_modal.hide();
+                self.send(Message::OpenVault(vault));
+            }
+            Some(("keys", keys)) => self.try_keys(keys)?,
+            Some(("type", text)) => _ = self.type_text(text),
+            _ => _ = self.command(line.parse()?),
+        }
+
+        Ok(())
+    }
+
+    /// Draws the application and returns the backend holding the rendered screen.
+    pub fn render(&mut self) -> &TestBackend {
+        self.app.draw(&mut self.terminal, &mut self.state).unwrap();
+        self.terminal.backend()
+    }
+
+    /// Draws the application and returns the screen as text, without trailing whitespace.
+    pub fn screen(&mut self) -> String {
+        let buffer = self.render().buffer();
+
+        buffer
+            .content
+            .chunks(buffer.area.width.into())
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns the state as `name: value` lines: the focused pane, the open vault and note, the
+    /// notes with unsaved changes and the last error.
+    pub fn state(&self) -> String {
+        let state = &self.state;
+        let pane: &str = state.active_component().into();
+
+        [
+            ("pane", pane.to_string()),
+            (
+                "vault",
+                state
+                    .vault
+                    .map(|vault| vault.name.clone())
+                    .unwrap_or_default(),
+            ),
+            (
+                "note",
+                state
+                    .selected_note
+                    .as_ref()
+                    .map(|note| note.name.clone())
+                    .unwrap_or_default(),
+            ),
+            ("unsaved", state.unsaved_notes().join(", ")),
+            ("error", state.last_error.clone().unwrap_or_default()),
+        ]
+        .into_iter()
+        .map(|(name, value)| format!("{name}: {value}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use insta::assert_snapshot;
+
+    use crate::testing;
+
+    #[test]
+    fn test_run_script() {
+        let config = testing::config("");
+        let vault = testing::vault(
+            "basalt-headless-script",
+            &[
+                ("Inbox.md", "# Inbox\n\n- Milk\n"),
+                ("Projects.md", "# Projects\n"),
+            ],
+        );
+        let mut app = testing::app(&config, vec![&vault], 60, 10);
+
+        let script = indoc! {"
+            # Open the inbox
+            vault basalt-headless-script
+            explorer_open
+            keys tab
+
+            error_log_modal_toggle
+            error_log_modal_toggle
+        "};
+
+        assert_eq!(app.run_script(script), Ok(()));
+        assert_eq!(
+            app.state(),
+            indoc! {"
+                pane: Note Editor
+                vault: basalt-headless-script
+                note: Inbox
+                unsaved: 
+                error: "}
+        );
+        assert_snapshot!(app.screen());
+    }
+
+    #[test]
+    fn test_run_script_error() {
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![], 60, 10);
+
+        let tests = [
+            ("vault Missing", "line 1: no vault named Missing"),
+            ("\nkeys ctrl+nope", "line 2: invalid key ctrl+nope: "),
+            (
+                "splash_down\nnot_a_command",
+                "line 2: not_a_command is not a valid command",
+            ),
+        ];
+
+        tests.into_iter().for_each(|(script, expected)| {
+            let err = app.run_script(script).unwrap_err().to_string();
+            assert!(err.starts_with(expected), "{err} for {script:?}");
+        });
+    }
+
+    #[test]
+    fn test_quit_stops_script() {
+        let config = testing::config("");
+        let mut app = testing::app(&config, vec![], 60, 10);
+
+        assert_eq!(app.run_script("quit\nnot_a_command"), Ok(()));
+        assert!(!app.is_running());
+    }
+}
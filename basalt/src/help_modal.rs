@@ -1,20 +1,23 @@
 use ratatui::{
     buffer::Buffer,
-    layout::{Alignment, Constraint, Flex, Layout, Rect, Size},
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::{Alignment, Constraint, Rect, Size},
     style::{Color, Style, Stylize},
-    text::Line,
+    text::{Line, Span},
     widgets::{
-        Block, BorderType, Clear, Padding, Paragraph, Scrollbar, ScrollbarOrientation,
-        ScrollbarState, StatefulWidget, Widget, Wrap,
+        Clear, Padding, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
+        Widget, Wrap,
     },
 };
 
 use crate::app::{calc_scroll_amount, Message as AppMessage, ScrollAmount};
+use crate::chrome::Chrome;
+use crate::responsive;
 
 fn modal_area_height(size: Size) -> usize {
-    let vertical = Layout::vertical([Constraint::Percentage(50)]).flex(Flex::Center);
-    let [area] = vertical.areas(Rect::new(0, 0, size.width, size.height.saturating_sub(3)));
-    area.height.into()
+    modal_area(Rect::new(0, 0, size.width, size.height.saturating_sub(3)))
+        .height
+        .into()
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -23,6 +26,33 @@ pub enum Message {
     Close,
     ScrollUp(ScrollAmount),
     ScrollDown(ScrollAmount),
+    /// Starts typing a search query.
+    Search,
+    SearchInsert(char),
+    SearchDelete,
+    /// Stops typing the query and keeps the matches highlighted.
+    SearchSubmit,
+    /// Stops typing the query and clears it.
+    SearchCancel,
+    SearchNext,
+    SearchPrevious,
+}
+
+/// Handles the keys while the search query is typed.
+pub fn handle_search_event(key: &KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Esc => Some(Message::SearchCancel),
+        KeyCode::Enter => Some(Message::SearchSubmit),
+        KeyCode::Backspace => Some(Message::SearchDelete),
+        KeyCode::Char(c)
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            Some(Message::SearchInsert(c))
+        }
+        _ => None,
+    }
 }
 
 pub fn update<'a>(
@@ -45,6 +75,23 @@ pub fn update<'a>(
                 modal_area_height(screen_size),
             ));
         }
+        Message::Search => state.start_search(),
+        Message::SearchInsert(c) => {
+            state.query.push(*c);
+            state.update_matches();
+        }
+        Message::SearchDelete => {
+            state.query.pop();
+            state.update_matches();
+        }
+        Message::SearchSubmit => state.searching = false,
+        Message::SearchCancel => {
+            state.searching = false;
+            state.query.clear();
+            state.update_matches();
+        }
+        Message::SearchNext => state.select_match(1),
+        Message::SearchPrevious => state.select_match(-1),
     };
 
     None
@@ -56,6 +103,15 @@ pub struct HelpModalState {
     pub scrollbar_position: usize,
     pub text: String,
     pub visible: bool,
+    /// Whether the search query is being typed.
+    pub searching: bool,
+    pub query: String,
+    /// Indices of the lines that contain the query.
+    matches: Vec<usize>,
+    /// Index of the current match in `matches`.
+    match_index: usize,
+    /// Scroll position when the search started, the first match is searched from here.
+    search_origin: usize,
 }
 
 impl HelpModalState {
@@ -75,6 +131,65 @@ impl HelpModalState {
         self.visible = false;
     }
 
+    fn start_search(&mut self) {
+        self.searching = true;
+        self.query.clear();
+        self.search_origin = self.scrollbar_position;
+        self.update_matches();
+    }
+
+    /// Finds the lines that contain the query, ignoring ASCII case, and scrolls to the first
+    /// match from where the search started.
+    fn update_matches(&mut self) {
+        let query = self.query.to_ascii_lowercase();
+
+        self.matches = if query.is_empty() {
+            vec![]
+        } else {
+            self.text
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| line.to_ascii_lowercase().contains(&query))
+                .map(|(index, _)| index)
+                .collect()
+        };
+
+        self.match_index = self
+            .matches
+            .iter()
+            .position(|line| *line >= self.search_origin)
+            .unwrap_or_default();
+
+        self.scroll_to_match();
+    }
+
+    /// Selects the next match, or the previous match with a negative step, wrapping around the
+    /// text.
+    fn select_match(&mut self, step: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+
+        self.match_index = self
+            .match_index
+            .saturating_add_signed(step + self.matches.len() as isize)
+            % self.matches.len();
+
+        self.scroll_to_match();
+    }
+
+    fn scroll_to_match(&mut self) {
+        if let Some(line) = self.current_match() {
+            self.scrollbar_position = line;
+            self.scrollbar_state = self.scrollbar_state.position(line);
+        }
+    }
+
+    /// Returns the line of the current match.
+    pub fn current_match(&self) -> Option<usize> {
+        self.matches.get(self.match_index).copied()
+    }
+
     pub fn scroll_up(&mut self, amount: usize) {
         let scrollbar_position = self.scrollbar_position.saturating_sub(amount);
         let scrollbar_state = self.scrollbar_state.position(scrollbar_position);
@@ -97,14 +212,47 @@ impl HelpModalState {
 }
 
 fn modal_area(area: Rect) -> Rect {
-    let vertical = Layout::vertical([Constraint::Percentage(50)]).flex(Flex::Center);
-    let horizontal = Layout::horizontal([Constraint::Length(83)]).flex(Flex::Center);
-    let [area] = vertical.areas(area);
-    let [area] = horizontal.areas(area);
-    area
+    responsive::modal_area(area, Constraint::Length(83), Constraint::Percentage(50))
+}
+
+/// Returns the line with the matches of the query highlighted, ignoring ASCII case.
+fn highlight<'a>(line: &'a str, query: &str, current: bool) -> Line<'a> {
+    let query = query.to_ascii_lowercase();
+    let lowercase = line.to_ascii_lowercase();
+
+    if query.is_empty() || !lowercase.contains(&query) {
+        return Line::from(line);
+    }
+
+    let style = if current {
+        Style::default().black().on_yellow()
+    } else {
+        Style::default().reversed()
+    };
+
+    let mut spans = vec![];
+    let mut start = 0;
+    for (index, _) in lowercase.match_indices(&query) {
+        spans.push(Span::raw(&line[start..index]));
+        spans.push(Span::styled(&line[index..index + query.len()], style));
+        start = index + query.len();
+    }
+    spans.push(Span::raw(&line[start..]));
+
+    Line::from(spans)
+}
+
+#[derive(Default)]
+pub struct HelpModal {
+    chrome: Chrome,
 }
 
-pub struct HelpModal;
+impl HelpModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
 
 impl StatefulWidget for HelpModal {
     type State = HelpModalState;
@@ -113,19 +261,41 @@ impl StatefulWidget for HelpModal {
     where
         Self: Sized,
     {
-        let block = Block::bordered()
+        let mut block = self
+            .chrome
+            .block(false)
             .dark_gray()
-            .border_type(BorderType::Rounded)
-            .padding(Padding::uniform(1))
+            .padding(self.chrome.padding(Padding::uniform(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
             .title_style(Style::default().italic().bold())
             .title(" Help ")
             .title(Line::from(" (?) ").alignment(Alignment::Right));
 
+        if state.searching || !state.query.is_empty() {
+            let cursor = if state.searching { "█" } else { "" };
+            let count = match state.matches.len() {
+                0 => "no matches".to_string(),
+                len => format!("{}/{len}", state.match_index + 1),
+            };
+
+            block = block.title_bottom(
+                Line::from(format!(" /{}{cursor} ({count}) ", state.query)).fg(Color::default()),
+            );
+        }
+
+        let current_match = state.current_match();
+        let lines: Vec<Line> = state
+            .text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| highlight(line, &state.query, current_match == Some(index)))
+            .collect();
+
         let area = modal_area(area);
 
         Widget::render(Clear, area, buf);
         Widget::render(
-            Paragraph::new(state.text.clone())
+            Paragraph::new(lines)
                 .wrap(Wrap::default())
                 .scroll((state.scrollbar_position as u16, 0))
                 .block(block)
@@ -142,3 +312,58 @@ impl StatefulWidget for HelpModal {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let mut state =
+            HelpModalState::new("Explorer\nOutline\nexplorer_up\nEditor\nExplorer pane");
+        state.scroll_down(1);
+
+        update(&Message::Search, Size::default(), &mut state);
+        assert!(state.searching);
+
+        "EXPL".chars().for_each(|c| {
+            update(&Message::SearchInsert(c), Size::default(), &mut state);
+        });
+        assert_eq!(state.matches, [0, 2, 4]);
+        assert_eq!(state.current_match(), Some(2));
+        assert_eq!(state.scrollbar_position, 2);
+
+        update(&Message::SearchSubmit, Size::default(), &mut state);
+        assert!(!state.searching);
+
+        let tests = [
+            (Message::SearchNext, 4),
+            (Message::SearchNext, 0),
+            (Message::SearchPrevious, 4),
+            (Message::SearchPrevious, 2),
+        ];
+
+        tests.into_iter().for_each(|(message, expected)| {
+            update(&message, Size::default(), &mut state);
+            assert_eq!(state.current_match(), Some(expected), "{message:?}");
+        });
+
+        update(&Message::SearchCancel, Size::default(), &mut state);
+        assert_eq!(state.current_match(), None);
+    }
+
+    #[test]
+    fn test_highlight() {
+        assert_eq!(highlight("Help", "x", false), Line::from("Help"));
+        assert_eq!(
+            highlight("Explorer explorer", "explorer", false).spans,
+            [
+                Span::raw(""),
+                Span::styled("Explorer", Style::default().reversed()),
+                Span::raw(" "),
+                Span::styled("explorer", Style::default().reversed()),
+                Span::raw(""),
+            ]
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Navigation history of the opened notes, which is gone back and forward through like in a
+//! browser. The previous and next notes are shown around the open note in the status bar, e.g.
+//! `Ideas ← Inbox → Projects`.
+use std::path::{Path, PathBuf};
+
+/// Most notes kept in the history before the oldest ones are dropped.
+const MAX_LEN: usize = 100;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct History {
+    back: Vec<PathBuf>,
+    current: Option<PathBuf>,
+    forward: Vec<PathBuf>,
+}
+
+/// Returns the note name of the path, i.e. the file name without the extension.
+fn name(path: &Path) -> String {
+    path.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+impl History {
+    /// Records opening the note. Opening a note other than the current one clears the notes to
+    /// go forward to.
+    pub fn visit(&mut self, path: &Path) {
+        if self.current.as_deref() == Some(path) {
+            return;
+        }
+
+        self.back.extend(self.current.take());
+        self.forward.clear();
+
+        if self.back.len() > MAX_LEN {
+            self.back.remove(0);
+        }
+        self.current = Some(path.to_path_buf());
+    }
+
+    /// Goes back to the previous note and returns it, or `None` if there is none.
+    pub fn back(&mut self) -> Option<&Path> {
+        let previous = self.back.pop()?;
+        self.forward.extend(self.current.replace(previous));
+        self.current.as_deref()
+    }
+
+    /// Goes forward to the next note and returns it, or `None` if there is none.
+    pub fn forward(&mut self) -> Option<&Path> {
+        let next = self.forward.pop()?;
+        self.back.extend(self.current.replace(next));
+        self.current.as_deref()
+    }
+
+    /// Returns the note before the current one.
+    pub fn previous(&self) -> Option<&Path> {
+        self.back.last().map(PathBuf::as_path)
+    }
+
+    /// Returns the note after the current one, if the history was gone back through.
+    pub fn next(&self) -> Option<&Path> {
+        self.forward.last().map(PathBuf::as_path)
+    }
+
+    /// Removes the note, e.g. after it was deleted, from the history.
+    pub fn remove(&mut self, path: &Path) {
+        self.back.retain(|previous| previous != path);
+        self.forward.retain(|next| next != path);
+        if self.current.as_deref() == Some(path) {
+            self.current = None;
+        }
+    }
+
+    /// Returns the names of the previous, current and next notes, e.g.
+    /// `Ideas ← Inbox → Projects`, or `None` if there is nothing to go back or forward to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::history::History;
+    /// use std::path::Path;
+    ///
+    /// let mut history = History::default();
+    /// history.visit(Path::new("vault/Ideas.md"));
+    /// assert_eq!(history.breadcrumbs(), None);
+    ///
+    /// history.visit(Path::new("vault/Inbox.md"));
+    /// assert_eq!(history.breadcrumbs(), Some("Ideas ← Inbox".into()));
+    /// ```
+    pub fn breadcrumbs(&self) -> Option<String> {
+        if self.back.is_empty() && self.forward.is_empty() {
+            return None;
+        }
+
+        let current = self.current.as_deref().map(name).unwrap_or_default();
+        let previous = self.previous().map(|path| format!("{} ← ", name(path)));
+        let next = self.next().map(|path| format!(" → {}", name(path)));
+
+        Some(format!(
+            "{}{current}{}",
+            previous.unwrap_or_default(),
+            next.unwrap_or_default()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history() {
+        let mut history = History::default();
+        ["Ideas.md", "Inbox.md", "Projects.md"]
+            .into_iter()
+            .for_each(|path| history.visit(Path::new(path)));
+
+        assert_eq!(history.back(), Some(Path::new("Inbox.md")));
+        assert_eq!(
+            history.breadcrumbs().as_deref(),
+            Some("Ideas ← Inbox → Projects")
+        );
+
+        history.back();
+        assert_eq!(history.back(), None);
+        assert_eq!(history.breadcrumbs().as_deref(), Some("Ideas → Inbox"));
+
+        history.forward();
+        history.visit(Path::new("Inbox.md"));
+        history.visit(Path::new("Shed.md"));
+        assert_eq!(history.next(), None);
+        assert_eq!(history.breadcrumbs().as_deref(), Some("Inbox ← Shed"));
+
+        history.remove(Path::new("Inbox.md"));
+        assert_eq!(history.previous(), Some(Path::new("Ideas.md")));
+    }
+}
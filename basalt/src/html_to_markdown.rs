@@ -0,0 +1,594 @@
+//! Converts HTML, e.g. rich text copied from a browser, to markdown. Only the structure that has
+//! a markdown equivalent is kept: headings, paragraphs, emphasis, links, images, lists, quotes,
+//! code and tables. Other tags are dropped and their text kept, while the contents of scripts,
+//! styles and the document head are dropped as a whole.
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token<'a> {
+    Open {
+        name: String,
+        attrs: Vec<(String, String)>,
+    },
+    Close(String),
+    Text(&'a str),
+}
+
+/// Tags whose contents are never part of the converted text.
+const SKIPPED_TAGS: [&str; 8] = [
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "title",
+];
+
+/// Page chrome around the article, dropped when converting only the readable content.
+const CHROME_TAGS: [&str; 7] = [
+    "nav", "header", "footer", "aside", "form", "button", "dialog",
+];
+
+/// Tags that never have contents or a closing tag.
+const VOID_TAGS: [&str; 8] = ["br", "hr", "img", "input", "meta", "link", "wbr", "source"];
+
+fn parse_attrs(source: &str) -> Vec<(String, String)> {
+    let mut attrs = vec![];
+    let mut rest = source.trim();
+
+    while !rest.is_empty() {
+        let name_len = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let name = rest[..name_len].to_lowercase();
+        rest = rest[name_len..].trim_start();
+
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                let (value, remaining) = match value.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = value[1..].find(quote).map_or(value.len(), |i| i + 1);
+                        (&value[1..end], value.get(end + 1..).unwrap_or_default())
+                    }
+                    _ => {
+                        let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                        (&value[..end], &value[end..])
+                    }
+                };
+                rest = remaining.trim_start();
+                decode_entities(value)
+            }
+            None => String::new(),
+        };
+
+        if !name.is_empty() {
+            attrs.push((name, value));
+        }
+    }
+
+    attrs
+}
+
+fn tokenize<'a>(html: &'a str, skipped: &[&str]) -> Vec<Token<'a>> {
+    let mut tokens = vec![];
+    let mut index = 0;
+
+    while index < html.len() {
+        let Some(start) = html[index..].find('<').map(|i| i + index) else {
+            tokens.push(Token::Text(&html[index..]));
+            break;
+        };
+
+        if start > index {
+            tokens.push(Token::Text(&html[index..start]));
+        }
+
+        if html[start..].starts_with("<!--") {
+            index = html[start..]
+                .find("-->")
+                .map_or(html.len(), |i| start + i + 3);
+            continue;
+        }
+
+        let Some(end) = html[start..].find('>').map(|i| i + start) else {
+            tokens.push(Token::Text(&html[start..]));
+            break;
+        };
+
+        let tag = &html[start + 1..end];
+        index = end + 1;
+
+        if let Some(name) = tag.strip_prefix('/') {
+            tokens.push(Token::Close(name.trim().to_lowercase()));
+        } else if !tag.starts_with(['!', '?']) {
+            let tag = tag.trim_end_matches('/');
+            let name_len = tag.find(char::is_whitespace).unwrap_or(tag.len());
+            let name = tag[..name_len].to_lowercase();
+
+            // Contents of skipped tags might contain `<`, e.g. in scripts, so they are not
+            // tokenized at all.
+            if skipped.contains(&name.as_str()) {
+                let close = format!("</{name}");
+                index = html[index..]
+                    .to_lowercase()
+                    .find(&close)
+                    .and_then(|i| html[index + i..].find('>').map(|j| index + i + j + 1))
+                    .unwrap_or(html.len());
+                continue;
+            }
+
+            tokens.push(Token::Open {
+                attrs: parse_attrs(&tag[name_len..]),
+                name,
+            });
+        }
+    }
+
+    tokens
+}
+
+/// Decodes the common named character references and all numeric ones.
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "ndash" => Some('–'),
+            "mdash" => Some('—'),
+            "hellip" => Some('…'),
+            "lsquo" => Some('‘'),
+            "rsquo" => Some('’'),
+            "ldquo" => Some('“'),
+            "rdquo" => Some('”'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        });
+
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Resolves the link target against the URL of the page, e.g. `/post` on
+/// `https://example.com/blog/` to `https://example.com/post`.
+fn resolve_url(base: &str, url: &str) -> String {
+    let Some((scheme, rest)) = base.split_once("://") else {
+        return url.to_string();
+    };
+
+    let has_scheme = url.split_once(':').is_some_and(|(scheme, _)| {
+        !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+
+    if url.is_empty() || has_scheme || url.starts_with('#') {
+        return url.to_string();
+    }
+
+    if let Some(url) = url.strip_prefix("//") {
+        return format!("{scheme}://{url}");
+    }
+
+    let origin_len = rest.find('/').unwrap_or(rest.len());
+    let origin = &base[..scheme.len() + 3 + origin_len];
+
+    match url.starts_with('/') {
+        true => format!("{origin}{url}"),
+        false => {
+            let path = base[origin.len()..]
+                .split(['?', '#'])
+                .next()
+                .unwrap_or_default();
+            let dir = path.rfind('/').map_or("", |i| &path[..i]);
+            format!("{origin}{dir}/{url}")
+        }
+    }
+}
+
+#[derive(Default)]
+struct Converter {
+    /// URL of the page that relative links are resolved against.
+    base_url: String,
+    output: String,
+    /// Counter of each open list, `None` for unordered lists.
+    lists: Vec<Option<usize>>,
+    /// Output offsets where the open blockquotes start.
+    quotes: Vec<usize>,
+    /// Link targets of the open links.
+    links: Vec<String>,
+    /// Number of cells in the current table row and whether the row is a header.
+    row: Option<(usize, bool)>,
+    pre: bool,
+}
+
+impl Converter {
+    fn at_line_start(&self) -> bool {
+        self.output.is_empty() || self.output.ends_with('\n')
+    }
+
+    fn line_break(&mut self) {
+        let trimmed = self.output.trim_end_matches([' ', '\t']).len();
+        self.output.truncate(trimmed);
+
+        if !self.at_line_start() {
+            self.output.push('\n');
+        }
+    }
+
+    fn block_break(&mut self) {
+        // Blocks in list items stay in the item, as markdown needs indentation for paragraphs
+        // in lists.
+        if !self.lists.is_empty() {
+            return self.line_break();
+        }
+
+        self.line_break();
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            self.output.push('\n');
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let text = decode_entities(text);
+
+        if self.pre {
+            return self.output.push_str(&text);
+        }
+
+        let mut collapsed = String::new();
+        for (i, word) in text.split_whitespace().enumerate() {
+            if i > 0 {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+
+        if text.starts_with(char::is_whitespace) && !self.at_line_start() {
+            collapsed.insert(0, ' ');
+        }
+        if text.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+
+        if text.trim().is_empty() && self.output.ends_with(' ') {
+            return;
+        }
+
+        self.output.push_str(&collapsed);
+    }
+
+    fn open(&mut self, name: &str, attrs: &[(String, String)]) {
+        let attr = |key: &str| {
+            attrs
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.as_str())
+                .unwrap_or_default()
+        };
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block_break();
+                let level = name[1..].parse().unwrap_or(1);
+                self.output.push_str(&format!("{} ", "#".repeat(level)));
+            }
+            "p" | "div" | "section" | "article" | "header" | "footer" | "figure" | "table" => {
+                self.block_break()
+            }
+            // Obsidian shows single line breaks as is, so no hard break syntax is needed.
+            "br" => self.line_break(),
+            "hr" => {
+                self.block_break();
+                self.output.push_str("---");
+                self.block_break();
+            }
+            "strong" | "b" => self.output.push_str("**"),
+            "em" | "i" => self.output.push('*'),
+            "del" | "s" | "strike" => self.output.push_str("~~"),
+            "code" if !self.pre => self.output.push('`'),
+            "pre" => {
+                self.block_break();
+                self.output.push_str("```\n");
+                self.pre = true;
+            }
+            "blockquote" => {
+                self.block_break();
+                self.quotes.push(self.output.len());
+            }
+            "ul" | "ol" => {
+                self.line_break();
+                self.lists.push((name == "ol").then_some(0));
+            }
+            "li" => {
+                self.line_break();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(count)) => {
+                        *count += 1;
+                        format!("{count}. ")
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.output.push_str(&format!("{indent}{marker}"));
+            }
+            "a" => {
+                self.links.push(resolve_url(&self.base_url, attr("href")));
+                self.output.push('[');
+            }
+            "img" => {
+                let src = resolve_url(&self.base_url, attr("src"));
+                if !src.is_empty() {
+                    self.output.push_str(&format!("![{}]({src})", attr("alt")));
+                }
+            }
+            "tr" => {
+                self.line_break();
+                self.output.push('|');
+                self.row = Some((0, false));
+            }
+            "th" | "td" => {
+                if let Some((cells, header)) = self.row.as_mut() {
+                    *cells += 1;
+                    *header |= name == "th";
+                }
+                self.output.push(' ');
+            }
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "div" | "section" | "article"
+            | "header" | "footer" | "figure" | "table" => self.block_break(),
+            "strong" | "b" => self.output.push_str("**"),
+            "em" | "i" => self.output.push('*'),
+            "del" | "s" | "strike" => self.output.push_str("~~"),
+            "code" if !self.pre => self.output.push('`'),
+            "pre" => {
+                self.pre = false;
+                self.line_break();
+                self.output.push_str("```");
+                self.block_break();
+            }
+            "blockquote" => {
+                let Some(start) = self.quotes.pop() else {
+                    return;
+                };
+
+                let quoted = self.output.split_off(start);
+                let quoted = quoted
+                    .trim_end()
+                    .lines()
+                    .map(|line| match line.is_empty() {
+                        true => ">".to_string(),
+                        false => format!("> {line}"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                self.output.push_str(&quoted);
+                self.block_break();
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.block_break();
+            }
+            "li" => self.line_break(),
+            "a" => {
+                let href = self.links.pop().unwrap_or_default();
+                match href.is_empty() || href.starts_with("javascript:") {
+                    true => self.output.push(']'),
+                    false => self.output.push_str(&format!("]({href})")),
+                }
+            }
+            "th" | "td" => {
+                let trimmed = self.output.trim_end().len();
+                self.output.truncate(trimmed);
+                self.output.push_str(" |");
+            }
+            "tr" => {
+                if let Some((cells, true)) = self.row.take() {
+                    self.output.push('\n');
+                    self.output.push('|');
+                    self.output.push_str(&" --- |".repeat(cells));
+                }
+                self.line_break();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the contents of the first element with the tag, e.g. `<article>`, from its opening to
+/// the last closing tag.
+fn element<'a>(html: &'a str, lowercase: &str, tag: &str) -> Option<&'a str> {
+    let start = lowercase
+        .match_indices(&format!("<{tag}"))
+        .map(|(index, _)| index)
+        .find(|index| {
+            lowercase[index + tag.len() + 1..].starts_with(|c: char| c == '>' || c.is_whitespace())
+        })?;
+    let start = start + lowercase[start..].find('>')? + 1;
+    let end = lowercase
+        .rfind(&format!("</{tag}"))
+        .filter(|end| *end >= start)?;
+
+    Some(&html[start..end])
+}
+
+/// Returns the title of the page, from the `og:title` property or the `<title>` tag.
+pub fn title(html: &str) -> Option<String> {
+    let og_title = tokenize(html, &[])
+        .into_iter()
+        .find_map(|token| match token {
+            Token::Open { name, attrs } if name == "meta" => {
+                let attr = |key: &str| attrs.iter().find(|(name, _)| name == key);
+                attr("property")
+                    .filter(|(_, value)| value == "og:title")
+                    .and(attr("content"))
+                    .map(|(_, value)| value.clone())
+            }
+            _ => None,
+        });
+
+    og_title
+        .or_else(|| element(html, &html.to_lowercase(), "title").map(decode_entities))
+        .map(|title| title.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|title| !title.is_empty())
+}
+
+/// Converts only the readable content of a web page to markdown: the `<article>`, or the
+/// `<main>` part of the page if there is no article, without navigation, headers, footers and
+/// other page chrome. Relative links are resolved against the URL of the page.
+pub fn convert_readable(html: &str, url: &str) -> String {
+    let lowercase = html.to_lowercase();
+    let content = ["article", "main", "body"]
+        .into_iter()
+        .find_map(|tag| element(html, &lowercase, tag))
+        .unwrap_or(html);
+
+    let skipped: Vec<_> = SKIPPED_TAGS.into_iter().chain(CHROME_TAGS).collect();
+    convert_tokens(tokenize(content, &skipped), url)
+}
+
+/// Converts the HTML to markdown.
+pub fn convert(html: &str) -> String {
+    convert_tokens(tokenize(html, &SKIPPED_TAGS), "")
+}
+
+fn convert_tokens(tokens: Vec<Token>, base_url: &str) -> String {
+    let mut converter = Converter {
+        base_url: base_url.to_string(),
+        ..Default::default()
+    };
+
+    for token in tokens {
+        match token {
+            Token::Open { name, attrs } => {
+                converter.open(&name, &attrs);
+                if VOID_TAGS.contains(&name.as_str()) {
+                    converter.close(&name);
+                }
+            }
+            Token::Close(name) => converter.close(&name),
+            Token::Text(text) => converter.text(text),
+        }
+    }
+
+    let mut markdown = String::new();
+    for line in converter.output.trim().lines() {
+        if line.trim().is_empty() && markdown.ends_with("\n\n") {
+            continue;
+        }
+        markdown.push_str(line.trim_end_matches('\t'));
+        markdown.push('\n');
+    }
+
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let html = indoc! {r#"
+            <html><head><title>Ignored</title><style>p { color: red; }</style></head>
+            <body>
+              <h1>Title</h1>
+              <p>Some <b>bold</b>, <em>italic</em> and <code>code</code> with a
+                 <a href="https://example.com">link</a> &amp; an image <img src="a.png" alt="A">.</p>
+              <ul>
+                <li>First</li>
+                <li>Second
+                  <ol><li>Nested</li></ol>
+                </li>
+              </ul>
+              <blockquote><p>Quote</p></blockquote>
+              <pre><code>let x = 1 &lt; 2;
+            </code></pre>
+              <table><tr><th>A</th><th>B</th></tr><tr><td>1</td><td>2</td></tr></table>
+              <script>if (a < b) {}</script>
+            </body></html>
+        "#};
+
+        assert_eq!(
+            convert(html),
+            indoc! {"
+                # Title
+
+                Some **bold**, *italic* and `code` with a [link](https://example.com) & an image ![A](a.png).
+
+                - First
+                - Second
+                  1. Nested
+
+                > Quote
+
+                ```
+                let x = 1 < 2;
+                ```
+
+                | A | B |
+                | --- | --- |
+                | 1 | 2 |
+            "}
+        );
+    }
+
+    #[test]
+    fn test_convert_readable() {
+        let html = indoc! {r#"
+            <html><head><title>Page &amp; title</title></head>
+            <body>
+              <nav><a href="/">Home</a></nav>
+              <article>
+                <header><p>By someone</p></header>
+                <h2>Heading</h2>
+                <p>Article <a href="other">text</a>.</p>
+                <aside>Related</aside>
+              </article>
+              <footer>Footer</footer>
+            </body></html>
+        "#};
+
+        assert_eq!(title(html), Some("Page & title".into()));
+        assert_eq!(
+            convert_readable(html, "https://example.com/blog/post?page=1"),
+            "## Heading\n\nArticle [text](https://example.com/blog/other).\n"
+        );
+        assert_eq!(
+            resolve_url("https://example.com/blog/post", "/about"),
+            "https://example.com/about"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Vault indexes that are persisted between application runs in the cache directory, so that
+//! only the notes modified since the last run are read when a vault is opened.
+use std::path::PathBuf;
+
+use basalt_core::obsidian::{Vault, VaultIndex};
+
+use crate::dirs;
+
+/// Returns a hash of the text that stays the same between builds, unlike the hashers of the
+/// standard library.
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Returns the path of the cached index of the vault. Vaults with the same name are told apart by
+/// the hash of the vault path.
+fn index_path(vault: &Vault) -> Option<PathBuf> {
+    let hash = stable_hash(&vault.path.to_string_lossy());
+
+    dirs::cache_dir().map(|dir| dir.join(format!("index/{}-{hash:016x}.json", vault.name)))
+}
+
+/// Loads the cached index of the vault and brings it up to date with the notes. The index is
+/// written back to the cache when it changed. A missing or unreadable cache results in indexing
+/// every note.
+pub fn load(vault: &Vault) -> VaultIndex {
+    let path = index_path(vault);
+
+    let mut index = path
+        .as_deref()
+        .and_then(|path| VaultIndex::load(path).ok())
+        .unwrap_or_default();
+
+    let count = index.notes().count();
+    let updated = index.update(vault, |_, _| {});
+
+    if let Some(path) = path.filter(|_| updated > 0 || index.notes().count() != count) {
+        _ = index.save(&path);
+    }
+
+    index
+}
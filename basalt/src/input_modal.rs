@@ -0,0 +1,607 @@
+use std::path::PathBuf;
+
+use basalt_core::obsidian::{Link, Note, Tag};
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{
+    app::Message as AppMessage,
+    chrome::Chrome,
+    command, link_fix,
+    note_editor::{self, Registers},
+    replace_modal::{self, Scope},
+    responsive, vault_replace_modal,
+};
+
+/// The operation that is run with the submitted input value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputAction {
+    DuplicateNote(Note),
+    SaveNoteAsTemplate(Note),
+    Publish,
+    InsertFromUrl,
+    SearchPattern(Scope),
+    ReplaceWith(Scope, String),
+    /// Moves the attachments to the folder, relative to the vault root.
+    MoveAttachments(Vec<PathBuf>),
+    QuickCapture,
+    /// Appends the text to the target note, given as `Note#Heading`.
+    ExtractTo(String),
+    DeleteNote(Note),
+    /// Writes the contents of the second note over the first note, then deletes the second note.
+    ReplaceNote(Note, Note),
+    /// Merges the second note into the first note, then deletes the second note.
+    MergeNotes(Note, Note),
+    DeleteAttachments(Vec<PathBuf>),
+    /// Opens the note with the selected label, given as the labels and paths of the notes.
+    OpenNote(Vec<(String, PathBuf)>),
+    KeysExport,
+    /// Selects the register of the note editor, given as `"a` or `a`.
+    SelectRegister,
+    /// Asks for the new name of the tag.
+    TagRename,
+    /// Renames the tag across the vault.
+    TagRenameTo(String),
+    /// Renames the heading of the note at the path and the links to it across the vault.
+    HeadingRename {
+        path: PathBuf,
+        from: String,
+    },
+    /// Updates the links to the renamed notes, given as their old and new paths.
+    FixRenamedLinks(Vec<(PathBuf, PathBuf)>),
+    /// Points the broken link to the note with the selected label, given as the link, its source
+    /// text and the labels and paths of the notes.
+    FixLink {
+        link: Link,
+        source: String,
+        notes: Vec<(String, PathBuf)>,
+    },
+    /// Runs the command with the arguments, filled in as by [`command::fill_arguments`].
+    ExecPrompt(String),
+    /// Runs the program with the selected name, given as the names of the programs.
+    OpenWith(Vec<String>),
+}
+
+impl InputAction {
+    fn into_message<'a>(self, value: String) -> AppMessage<'a> {
+        match self {
+            InputAction::DuplicateNote(note) => AppMessage::DuplicateNote(note, value),
+            InputAction::SaveNoteAsTemplate(note) => AppMessage::SaveNoteAsTemplate(note, value),
+            InputAction::Publish => AppMessage::PublishNotes(value),
+            InputAction::InsertFromUrl => AppMessage::ClipUrl(value),
+            InputAction::SearchPattern(scope) => AppMessage::SearchPattern(scope, value),
+            InputAction::ReplaceWith(Scope::Note, pattern) => {
+                AppMessage::ReplaceModal(replace_modal::Message::Open {
+                    pattern,
+                    replacement: value,
+                })
+            }
+            InputAction::ReplaceWith(Scope::Vault, pattern) => {
+                AppMessage::VaultReplaceModal(vault_replace_modal::Message::Open {
+                    pattern,
+                    replacement: value,
+                })
+            }
+            InputAction::MoveAttachments(paths) => AppMessage::MoveAttachments(paths, value),
+            InputAction::QuickCapture => AppMessage::Capture(value),
+            InputAction::ExtractTo(text) => AppMessage::ExtractTo(text, value),
+            InputAction::DeleteNote(note) => AppMessage::DeleteNote(note),
+            InputAction::ReplaceNote(note, with) => AppMessage::ReplaceNote(note, with),
+            InputAction::MergeNotes(into, from) => AppMessage::MergeNotes(into, from),
+            InputAction::DeleteAttachments(paths) => AppMessage::DeleteAttachments(paths),
+            InputAction::KeysExport => AppMessage::ExportKeys(value),
+            InputAction::SelectRegister => AppMessage::NoteEditor(
+                note_editor::Message::SelectRegister(value.chars().next().unwrap_or_default()),
+            ),
+            InputAction::TagRename => AppMessage::InputModal(Message::Open {
+                title: format!("Rename #{value} to"),
+                value: value.clone(),
+                action: InputAction::TagRenameTo(value),
+            }),
+            InputAction::TagRenameTo(from) => {
+                AppMessage::VaultReplaceModal(vault_replace_modal::Message::RenameTag {
+                    from,
+                    to: value,
+                })
+            }
+            InputAction::HeadingRename { path, from } => {
+                AppMessage::VaultReplaceModal(vault_replace_modal::Message::RenameHeading {
+                    path,
+                    from,
+                    to: value,
+                })
+            }
+            InputAction::FixRenamedLinks(renames) => AppMessage::FixRenamedLinks(renames),
+            InputAction::ExecPrompt(command) => {
+                AppMessage::Exec(command::fill_arguments(&command, &value))
+            }
+            InputAction::FixLink { link, source, .. } => AppMessage::ReplaceLinkTarget(
+                link.range,
+                link_fix::target(link.kind, &source, &value),
+                source,
+            ),
+            InputAction::OpenWith(_) => AppMessage::RunOpenWith(value),
+            InputAction::OpenNote(notes) => AppMessage::SwitchToNote(
+                notes
+                    .into_iter()
+                    .find(|(label, _)| *label == value)
+                    .map(|(_, path)| path)
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+
+    /// Returns the submitted value, or `None` if nothing should be run. Search patterns and
+    /// replacements are used as typed, and an empty replacement deletes the matches. Only listed
+    /// notes can be opened or linked to, and tags are renamed to valid tag names.
+    fn value(&self, input: &str) -> Option<String> {
+        match self {
+            InputAction::SearchPattern(_) => (!input.is_empty()).then(|| input.to_string()),
+            InputAction::ReplaceWith(..) => Some(input.to_string()),
+            // Commands may run without arguments.
+            InputAction::ExecPrompt(_) => Some(input.trim().to_string()),
+            InputAction::DeleteNote(_)
+            | InputAction::ReplaceNote(..)
+            | InputAction::MergeNotes(..)
+            | InputAction::DeleteAttachments(_)
+            | InputAction::FixRenamedLinks(_) => Some(String::new()),
+            InputAction::OpenNote(notes) | InputAction::FixLink { notes, .. } => notes
+                .iter()
+                .any(|(label, _)| label == input)
+                .then(|| input.to_string()),
+            InputAction::OpenWith(programs) => programs
+                .iter()
+                .any(|name| name == input)
+                .then(|| input.to_string()),
+            InputAction::SelectRegister => input
+                .trim_start()
+                .trim_start_matches('"')
+                .chars()
+                .next()
+                .filter(|name| Registers::is_register(*name))
+                .map(String::from),
+            InputAction::TagRename | InputAction::TagRenameTo(_) => {
+                let tag = input.trim().trim_start_matches('#').trim_end_matches('/');
+                let renamed = matches!(self, InputAction::TagRenameTo(from) if from == tag);
+                (Tag::is_valid_name(tag) && !renamed).then(|| tag.to_string())
+            }
+            // Link syntax in the heading would break the links to it.
+            InputAction::HeadingRename { from, .. } => Some(input.trim())
+                .filter(|heading| {
+                    !heading.is_empty()
+                        && heading != from
+                        && !heading.contains(['#', '|', '[', ']', '^'])
+                })
+                .map(String::from),
+            _ => Some(input.trim().to_string()).filter(|value| !value.is_empty()),
+        }
+    }
+}
+
+/// What the modal asks for before the action runs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Prompt {
+    /// A single line of text.
+    #[default]
+    Input,
+    /// Yes or no, the action only runs on yes. The text is the question.
+    Confirm(String),
+    /// One of the options, narrowed down by the typed text. The typed text is submitted when no
+    /// option contains it.
+    Select(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Open {
+        title: String,
+        value: String,
+        action: InputAction,
+    },
+    Confirm {
+        title: String,
+        question: String,
+        action: InputAction,
+    },
+    Select {
+        title: String,
+        value: String,
+        options: Vec<String>,
+        action: InputAction,
+    },
+    Up,
+    Down,
+    Insert(char),
+    Paste(String),
+    Delete,
+    DeleteForward,
+    CursorLeft,
+    CursorRight,
+    CursorStart,
+    CursorEnd,
+    Submit,
+    Cancel,
+}
+
+pub fn update<'a>(message: &Message, state: &mut InputModalState) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Open {
+            title,
+            value,
+            action,
+        } => state.open(title, value, action.clone()),
+        Message::Confirm {
+            title,
+            question,
+            action,
+        } => {
+            state.open(title, "", action.clone());
+            state.prompt = Prompt::Confirm(question.clone());
+        }
+        Message::Select {
+            title,
+            value,
+            options,
+            action,
+        } => {
+            state.open(title, value, action.clone());
+            state.prompt = Prompt::Select(options.clone());
+        }
+        Message::Up => state.selected = state.selected.saturating_sub(1),
+        Message::Down => {
+            state.selected = (state.selected + 1).min(state.options().len().saturating_sub(1));
+        }
+        Message::Insert(c) => state.insert(*c),
+        // The input is a single line, so pasted lines are joined.
+        Message::Paste(text) => text
+            .chars()
+            .filter(|c| *c != '\r')
+            .for_each(|c| state.insert(if c == '\n' { ' ' } else { c })),
+        Message::Delete => state.delete(),
+        Message::DeleteForward => state.delete_forward(),
+        Message::CursorLeft => state.cursor = state.cursor.saturating_sub(1),
+        Message::CursorRight => state.cursor = (state.cursor + 1).min(state.len()),
+        Message::CursorStart => state.cursor = 0,
+        Message::CursorEnd => state.cursor = state.len(),
+        Message::Cancel => state.hide(),
+        Message::Submit => {
+            let value = state
+                .options()
+                .get(state.selected)
+                .map_or(state.value.clone(), |option| option.to_string());
+            let action = state.action.take()?;
+            let value = action.value(&value);
+            state.hide();
+
+            return value.map(|value| action.into_message(value));
+        }
+    };
+
+    None
+}
+
+/// Input modal key mappings cannot be modified through configuration, similarly to the edit mode
+/// of the note editor.
+pub fn handle_event(key: &KeyEvent, state: &InputModalState) -> Option<Message> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    match (&state.prompt, key.code) {
+        (Prompt::Confirm(_), KeyCode::Char('y') | KeyCode::Enter) => Some(Message::Submit),
+        (Prompt::Confirm(_), KeyCode::Char('n' | 'q') | KeyCode::Esc) => Some(Message::Cancel),
+        (Prompt::Confirm(_), _) => None,
+        (Prompt::Select(_), KeyCode::Up) => Some(Message::Up),
+        (Prompt::Select(_), KeyCode::Char('p')) if ctrl => Some(Message::Up),
+        (Prompt::Select(_), KeyCode::Down) => Some(Message::Down),
+        (Prompt::Select(_), KeyCode::Char('n')) if ctrl => Some(Message::Down),
+        _ => handle_input_event(key),
+    }
+}
+
+fn handle_input_event(key: &KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Esc => Some(Message::Cancel),
+        KeyCode::Enter => Some(Message::Submit),
+        KeyCode::Backspace => Some(Message::Delete),
+        KeyCode::Delete => Some(Message::DeleteForward),
+        KeyCode::Left => Some(Message::CursorLeft),
+        KeyCode::Right => Some(Message::CursorRight),
+        KeyCode::Home => Some(Message::CursorStart),
+        KeyCode::End => Some(Message::CursorEnd),
+        KeyCode::Char(c)
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            Some(Message::Insert(c))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InputModalState {
+    pub(crate) title: String,
+    pub(crate) value: String,
+    /// Cursor position as a character index.
+    pub(crate) cursor: usize,
+    pub(crate) action: Option<InputAction>,
+    pub(crate) prompt: Prompt,
+    /// Index of the highlighted option in the options that contain the typed text.
+    pub(crate) selected: usize,
+    pub(crate) visible: bool,
+}
+
+impl InputModalState {
+    pub fn open(&mut self, title: &str, value: &str, action: InputAction) {
+        *self = Self {
+            title: title.to_string(),
+            value: value.to_string(),
+            cursor: value.chars().count(),
+            action: Some(action),
+            visible: true,
+            ..Default::default()
+        };
+    }
+
+    /// Returns the options that contain the typed text, ignoring case, with an exact match first.
+    pub fn options(&self) -> Vec<&str> {
+        let Prompt::Select(options) = &self.prompt else {
+            return vec![];
+        };
+
+        let value = self.value.to_lowercase();
+        let mut options: Vec<&str> = options
+            .iter()
+            .map(String::as_str)
+            .filter(|option| option.to_lowercase().contains(&value))
+            .collect();
+        options.sort_by_key(|option| *option != self.value);
+        options
+    }
+
+    pub fn hide(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map(|(index, _)| index)
+            .unwrap_or(self.value.len())
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let index = self.byte_index(self.cursor);
+        self.value.insert(index, c);
+        self.cursor += 1;
+        self.selected = 0;
+    }
+
+    pub fn delete(&mut self) {
+        self.selected = 0;
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let index = self.byte_index(self.cursor);
+            self.value.remove(index);
+        }
+    }
+
+    pub fn delete_forward(&mut self) {
+        self.selected = 0;
+        if self.cursor < self.len() {
+            let index = self.byte_index(self.cursor);
+            self.value.remove(index);
+        }
+    }
+}
+
+/// Options listed below the input of a select prompt at once.
+const MAX_OPTIONS: usize = 8;
+
+fn modal_area(area: Rect, height: u16) -> Rect {
+    responsive::modal_area(area, Constraint::Length(60), Constraint::Length(height))
+}
+
+#[derive(Default)]
+pub struct InputModal {
+    chrome: Chrome,
+}
+
+impl InputModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for InputModal {
+    type State = InputModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(" {} ", state.title));
+
+        if let Prompt::Confirm(question) = &state.prompt {
+            let area = modal_area(area, 3);
+            Widget::render(Clear, area, buf);
+            Paragraph::new(Line::from(vec![
+                Span::from(question.as_str()),
+                Span::from(" (y/n)").dark_gray(),
+            ]))
+            .block(block)
+            .fg(Color::default())
+            .render(area, buf);
+            return;
+        }
+
+        let options = state.options();
+        let option_rows = match state.prompt {
+            Prompt::Select(_) => options.len().clamp(1, MAX_OPTIONS) as u16,
+            _ => 0,
+        };
+
+        let area = modal_area(area, 3 + option_rows);
+        let width = block.inner(area).width as usize;
+
+        // Scroll the input horizontally so that the cursor always stays visible.
+        let offset = state.cursor.saturating_sub(width.saturating_sub(1));
+
+        let chars: Vec<char> = state.value.chars().collect();
+        let before: String = chars[offset..state.cursor].iter().collect();
+        let cursor: String = chars.get(state.cursor).copied().unwrap_or(' ').into();
+        let after: String = chars.iter().skip(state.cursor + 1).collect();
+
+        // Options scroll so that the highlighted option stays visible.
+        let option_offset = state.selected.saturating_sub(MAX_OPTIONS - 1);
+        let option_lines = options
+            .iter()
+            .enumerate()
+            .skip(option_offset)
+            .take(option_rows.into())
+            .map(|(index, option)| match index == state.selected {
+                true => Line::from(format!("▸ {option}")).reversed(),
+                false => Line::from(format!("  {option}")).dark_gray(),
+            });
+
+        let no_options = (option_rows > 0 && options.is_empty()).then(|| {
+            let text = match state.action {
+                Some(InputAction::OpenNote(_) | InputAction::FixLink { .. }) => {
+                    "  No matching notes"
+                }
+                _ => "  New value",
+            };
+            Line::from(text).dark_gray().italic()
+        });
+
+        let lines: Vec<Line> = [Line::from(vec![
+            Span::from(before),
+            Span::from(cursor).reversed(),
+            Span::from(after),
+        ])]
+        .into_iter()
+        .chain(option_lines)
+        .chain(no_options)
+        .collect();
+
+        Widget::render(Clear, area, buf);
+        Paragraph::new(lines)
+            .block(block)
+            .fg(Color::default())
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_editing() {
+        let mut state = InputModalState::default();
+        state.open(
+            "Duplicate note",
+            "Nöte",
+            InputAction::DuplicateNote(Note::default()),
+        );
+
+        update(&Message::Delete, &mut state);
+        update(&Message::CursorLeft, &mut state);
+        update(&Message::Delete, &mut state);
+        update(&Message::Insert('o'), &mut state);
+        update(&Message::CursorEnd, &mut state);
+        update(&Message::Insert('e'), &mut state);
+        update(&Message::Insert('s'), &mut state);
+        assert_eq!(state.value(), "Notes");
+
+        update(&Message::CursorStart, &mut state);
+        update(&Message::DeleteForward, &mut state);
+        assert_eq!(state.value(), "otes");
+
+        assert_eq!(
+            update(&Message::Submit, &mut state),
+            Some(AppMessage::DuplicateNote(Note::default(), "otes".into()))
+        );
+        assert!(!state.visible);
+    }
+
+    #[test]
+    fn test_confirm() {
+        let mut state = InputModalState::default();
+        let note = Note::default();
+        update(
+            &Message::Confirm {
+                title: "Delete note".into(),
+                question: "Delete Note?".into(),
+                action: InputAction::DeleteNote(note.clone()),
+            },
+            &mut state,
+        );
+
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(handle_event(&key(KeyCode::Char('x')), &state), None);
+        assert_eq!(
+            handle_event(&key(KeyCode::Char('n')), &state),
+            Some(Message::Cancel)
+        );
+        assert_eq!(
+            handle_event(&key(KeyCode::Char('y')), &state),
+            Some(Message::Submit)
+        );
+        assert_eq!(
+            update(&Message::Submit, &mut state),
+            Some(AppMessage::DeleteNote(note))
+        );
+        assert!(!state.visible);
+    }
+
+    #[test]
+    fn test_select() {
+        let select = |value: &str| Message::Select {
+            title: "Move attachments to folder".into(),
+            value: value.into(),
+            options: vec!["Assets".into(), "Assets/Images".into(), "Notes".into()],
+            action: InputAction::MoveAttachments(vec![]),
+        };
+
+        let mut state = InputModalState::default();
+        update(&select("images"), &mut state);
+        assert_eq!(state.options(), ["Assets/Images"]);
+
+        update(&select("Assets"), &mut state);
+        assert_eq!(state.options(), ["Assets", "Assets/Images"]);
+        update(&Message::Down, &mut state);
+        update(&Message::Down, &mut state);
+        assert_eq!(
+            update(&Message::Submit, &mut state),
+            Some(AppMessage::MoveAttachments(vec![], "Assets/Images".into()))
+        );
+
+        // The typed text is submitted when no option contains it.
+        update(&select("Archive"), &mut state);
+        assert_eq!(
+            update(&Message::Submit, &mut state),
+            Some(AppMessage::MoveAttachments(vec![], "Archive".into()))
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Processes started by `spawn:` commands and by the `on_end` command of the focus timer, listed
+//! with their process id, command, status and runtime. Running processes are checked on every
+//! turn of the main loop: the status bar tells when one finished and the error log when one
+//! failed. The selected process can be killed.
+use std::{
+    fmt,
+    process::Child,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{app::Message as AppMessage, chrome::Chrome, focus_timer::format_time, responsive};
+
+/// Number of finished processes kept in the list, the oldest are dropped first.
+const KEPT_FINISHED: usize = 50;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Status {
+    Running,
+    Finished,
+    /// Exited with a failure, with the exit status.
+    Failed(String),
+    Killed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Running => write!(f, "running"),
+            Status::Finished => write!(f, "finished"),
+            Status::Failed(status) => write!(f, "failed ({status})"),
+            Status::Killed => write!(f, "killed"),
+        }
+    }
+}
+
+/// A spawned process. The process is shared so that the job can be passed around in messages.
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub pid: u32,
+    pub command: String,
+    pub status: Status,
+    started: Instant,
+    ended: Option<Instant>,
+    child: Arc<Mutex<Child>>,
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.pid == other.pid && self.command == other.command && self.status == other.status
+    }
+}
+
+impl Job {
+    pub fn new(child: Child, command: String, started: Instant) -> Self {
+        Self {
+            pid: child.id(),
+            command,
+            status: Status::Running,
+            started,
+            ended: None,
+            child: Arc::new(Mutex::new(child)),
+        }
+    }
+
+    /// Time the process ran for, or has been running for.
+    pub fn runtime(&self, now: Instant) -> Duration {
+        self.ended
+            .unwrap_or(now)
+            .saturating_duration_since(self.started)
+    }
+
+    /// Checks whether the process exited, returning the message to show if it did.
+    fn poll<'a>(&mut self, now: Instant) -> Option<AppMessage<'a>> {
+        if self.status != Status::Running {
+            return None;
+        }
+
+        let status = self.child.lock().ok()?.try_wait();
+        let message = match status {
+            Ok(None) => return None,
+            Ok(Some(status)) if status.success() => {
+                self.status = Status::Finished;
+                AppMessage::SetStatus(format!(
+                    "`{}` finished in {}",
+                    self.command,
+                    format_time(now.saturating_duration_since(self.started))
+                ))
+            }
+            Ok(Some(status)) => {
+                self.status = Status::Failed(status.to_string());
+                AppMessage::Error(format!("`{}` failed: {status}", self.command))
+            }
+            Err(err) => {
+                self.status = Status::Failed(err.to_string());
+                AppMessage::Error(format!("`{}` failed: {err}", self.command))
+            }
+        };
+
+        self.ended = Some(now);
+        Some(message)
+    }
+
+    fn kill(&mut self, now: Instant) -> Result<(), String> {
+        let mut child = self.child.lock().map_err(|err| err.to_string())?;
+        child.kill().map_err(|err| err.to_string())?;
+        // Waiting reaps the killed process.
+        _ = child.wait();
+
+        self.status = Status::Killed;
+        self.ended = Some(now);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Adds the spawned process to the list.
+    Spawned(Job),
+    Toggle,
+    Close,
+    Up,
+    Down,
+    /// Kills the selected process.
+    Kill,
+    /// Redraws the runtimes, sent once a second while the modal shows running processes.
+    Tick,
+}
+
+pub fn update<'a>(message: &Message, state: &mut JobsModalState) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Spawned(job) => state.add(job.clone()),
+        Message::Toggle => {
+            state.visible = !state.visible;
+            if state.visible {
+                state
+                    .list_state
+                    .select((!state.jobs.is_empty()).then_some(0));
+            }
+        }
+        Message::Close => state.visible = false,
+        Message::Up => state.list_state.select_previous(),
+        Message::Down => {
+            if let Some(index) = state.list_state.selected() {
+                state
+                    .list_state
+                    .select(Some((index + 1).min(state.jobs.len().saturating_sub(1))));
+            }
+        }
+        Message::Kill => {
+            let job = state
+                .list_state
+                .selected()
+                .and_then(|index| state.jobs.get_mut(index))?;
+
+            if job.status != Status::Running {
+                return Some(AppMessage::SetStatus(format!(
+                    "`{}` is not running",
+                    job.command
+                )));
+            }
+
+            return Some(match job.kill(Instant::now()) {
+                Ok(()) => AppMessage::SetStatus(format!("Killed `{}`", job.command)),
+                Err(err) => AppMessage::Error(format!("Could not kill `{}`: {err}", job.command)),
+            });
+        }
+        Message::Tick => {}
+    };
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct JobsModalState {
+    /// Spawned processes, the most recent first.
+    pub(crate) jobs: Vec<Job>,
+    pub(crate) list_state: ListState,
+    pub(crate) visible: bool,
+    /// Seconds of the newest running process at the last tick, to tick once a second.
+    ticked: Option<u64>,
+}
+
+impl JobsModalState {
+    fn add(&mut self, job: Job) {
+        self.jobs.insert(0, job);
+        if let Some(index) = self.list_state.selected() {
+            self.list_state.select(Some(index + 1));
+        }
+
+        let mut finished = 0;
+        self.jobs.retain(|job| {
+            finished += usize::from(job.status != Status::Running);
+            job.status == Status::Running || finished <= KEPT_FINISHED
+        });
+        if let Some(index) = self.list_state.selected() {
+            self.list_state
+                .select(Some(index.min(self.jobs.len().saturating_sub(1))));
+        }
+    }
+
+    /// Returns the number of processes that are still running.
+    pub fn running(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == Status::Running)
+            .count()
+    }
+
+    /// Checks the running processes, returning the messages of the processes that exited, and a
+    /// tick to redraw the runtimes once a second while the modal is visible.
+    pub fn tick<'a>(&mut self, now: Instant) -> Vec<AppMessage<'a>> {
+        let mut messages: Vec<_> = self
+            .jobs
+            .iter_mut()
+            .filter_map(|job| job.poll(now))
+            .collect();
+
+        let seconds = self
+            .jobs
+            .iter()
+            .find(|job| job.status == Status::Running)
+            .map(|job| job.runtime(now).as_secs());
+
+        if self.visible && seconds.is_some() && self.ticked != seconds {
+            messages.push(AppMessage::JobsModal(Message::Tick));
+        }
+        self.ticked = seconds;
+
+        messages
+    }
+
+    fn list_item(job: &Job, now: Instant) -> ListItem<'static> {
+        let status = match &job.status {
+            Status::Running => Span::from(job.status.to_string()).green(),
+            Status::Finished => Span::from(job.status.to_string()).dark_gray(),
+            Status::Failed(_) | Status::Killed => Span::from(job.status.to_string()).red(),
+        };
+
+        ListItem::new(Line::from(vec![
+            Span::from(format!("{:>7} ", job.pid)).dark_gray(),
+            Span::from(format!("{} ", format_time(job.runtime(now)))).dark_gray(),
+            Span::from(job.command.clone()),
+            Span::from(" "),
+            status,
+        ]))
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(60), Constraint::Percentage(50))
+}
+
+#[derive(Default)]
+pub struct JobsModal {
+    chrome: Chrome,
+}
+
+impl JobsModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+
+    /// Renders the modal with the runtimes at the given time.
+    fn render_at(self, now: Instant, area: Rect, buf: &mut Buffer, state: &mut JobsModalState) {
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(" Jobs ")
+            .title(Line::from(format!(" {} running ", state.running())).alignment(Alignment::Right))
+            .title_bottom(Line::from(" (x) kill · (esc) close ").alignment(Alignment::Right));
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if state.jobs.is_empty() {
+            Paragraph::new("No processes spawned yet")
+                .dark_gray()
+                .italic()
+                .render(inner_area, buf);
+            return;
+        }
+
+        let items: Vec<_> = state
+            .jobs
+            .iter()
+            .map(|job| JobsModalState::list_item(job, now))
+            .collect();
+
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_style(Style::default().reversed().dark_gray())
+                .highlight_symbol(""),
+            inner_area,
+            buf,
+            &mut state.list_state,
+        );
+    }
+}
+
+impl StatefulWidget for JobsModal {
+    type State = JobsModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        self.render_at(Instant::now(), area, buf, state);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{process::Command, thread};
+
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    fn spawn(command: &str, args: &[&str], started: Instant) -> Job {
+        let child = Command::new(command).args(args).spawn().unwrap();
+        let command = format!("{command} {}", args.join(" "));
+        Job::new(child, command.trim_end().to_string(), started)
+    }
+
+    /// Ticks until no process is running, returning the messages.
+    fn wait(state: &mut JobsModalState) -> Vec<AppMessage<'static>> {
+        let mut messages = vec![];
+        while state.running() > 0 {
+            thread::sleep(Duration::from_millis(10));
+            messages.extend(state.tick(Instant::now()));
+        }
+        messages
+    }
+
+    #[test]
+    fn test_jobs() {
+        let mut state = JobsModalState::default();
+        let started = Instant::now();
+
+        update(&Message::Spawned(spawn("true", &[], started)), &mut state);
+        update(&Message::Spawned(spawn("false", &[], started)), &mut state);
+        assert_eq!(state.running(), 2);
+
+        let messages = wait(&mut state);
+        assert_eq!(messages.len(), 2);
+        assert!(messages.contains(&AppMessage::Error("`false` failed: exit status: 1".into())));
+        assert!(
+            matches!(&messages[..], [AppMessage::SetStatus(status), _] | [_, AppMessage::SetStatus(status)]
+            if status.starts_with("`true` finished in"))
+        );
+
+        update(
+            &Message::Spawned(spawn("sleep", &["5"], started)),
+            &mut state,
+        );
+        update(&Message::Toggle, &mut state);
+        assert_eq!(
+            update(&Message::Kill, &mut state),
+            Some(AppMessage::SetStatus("Killed `sleep 5`".into()))
+        );
+        assert_eq!(state.jobs[0].status, Status::Killed);
+        assert_eq!(
+            update(&Message::Kill, &mut state),
+            Some(AppMessage::SetStatus("`sleep 5` is not running".into()))
+        );
+
+        // Processes are listed from the most recent, with pids left out of the snapshot.
+        state.jobs.iter_mut().for_each(|job| {
+            job.pid = 42;
+            job.ended = Some(started + Duration::from_secs(3));
+        });
+        let mut terminal = Terminal::new(TestBackend::new(70, 12)).unwrap();
+        terminal
+            .draw(|frame| {
+                JobsModal::default().render_at(
+                    started,
+                    frame.area(),
+                    frame.buffer_mut(),
+                    &mut state,
+                )
+            })
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+    }
+}
@@ -0,0 +1,386 @@
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Clear, Padding, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
+        Widget,
+    },
+};
+
+use crate::{
+    app::{ActivePane, Message as AppMessage},
+    chrome::Chrome,
+    config::{Config, ConfigProblem, ConfigReport, ConfigSection, Key},
+    responsive,
+};
+
+/// Returns the configuration table and key bindings of the pane. Panes with key mappings that
+/// cannot be modified through configuration have no key bindings.
+fn pane_section<'a>(
+    config: &'a Config,
+    pane: ActivePane,
+) -> Option<(&'static str, &'a ConfigSection<'a>)> {
+    match pane {
+        ActivePane::Splash => Some(("splash", &config.splash)),
+        ActivePane::Explorer => Some(("explorer", &config.explorer)),
+        ActivePane::NoteEditor => Some(("note_editor", &config.note_editor)),
+        ActivePane::Outline => Some(("outline", &config.outline)),
+        ActivePane::HelpModal => Some(("help_modal", &config.help_modal)),
+        ActivePane::VaultSelectorModal => {
+            Some(("vault_selector_modal", &config.vault_selector_modal))
+        }
+        ActivePane::VaultStatsModal => Some(("vault_stats_modal", &config.vault_stats_modal)),
+        ActivePane::DiagnosticsModal => Some(("diagnostics_modal", &config.diagnostics_modal)),
+        ActivePane::AttachmentsModal => Some(("attachments_modal", &config.attachments_modal)),
+        ActivePane::DuplicatesModal => Some(("duplicates_modal", &config.duplicates_modal)),
+        ActivePane::NoteDiffModal => Some(("note_diff_modal", &config.note_diff_modal)),
+        ActivePane::NoteHistoryModal => Some(("note_history_modal", &config.note_history_modal)),
+        ActivePane::VaultReplaceModal => Some(("vault_replace_modal", &config.vault_replace_modal)),
+        ActivePane::CanvasModal => Some(("canvas_modal", &config.canvas_modal)),
+        ActivePane::TagBrowserModal => Some(("tag_browser_modal", &config.tag_browser_modal)),
+        ActivePane::OutgoingLinksModal => {
+            Some(("outgoing_links_modal", &config.outgoing_links_modal))
+        }
+        ActivePane::JobsModal => Some(("jobs_modal", &config.jobs_modal)),
+        ActivePane::InputModal
+        | ActivePane::ReplaceModal
+        | ActivePane::ConfigReportModal
+        | ActivePane::ErrorLogModal
+        | ActivePane::VaultLoadingModal
+        | ActivePane::KeysDebugModal
+        | ActivePane::QuitModal
+        | ActivePane::SaveConflictModal => None,
+    }
+}
+
+/// A key binding of the resolved keymap of a pane.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding {
+    pub key: String,
+    pub message: String,
+    /// Table the binding is read from, `global` or the table of the pane.
+    pub section: &'static str,
+    /// Whether a global binding of the same key takes precedence, so the binding is never used.
+    pub hidden: bool,
+}
+
+/// Resolves the keymap of the pane: the global bindings followed by the bindings of the pane.
+/// Pane bindings hidden by a global binding are flagged.
+pub fn resolve_keymap(config: &Config, pane: ActivePane) -> Vec<Binding> {
+    let global = config
+        .global
+        .key_bindings
+        .iter()
+        .map(|(key, message)| Binding {
+            key: key.clone(),
+            message: format!("{message:?}"),
+            section: "global",
+            hidden: false,
+        });
+
+    let pane_bindings =
+        pane_section(config, pane)
+            .into_iter()
+            .flat_map(|(section, pane_section)| {
+                pane_section
+                    .key_bindings
+                    .iter()
+                    .map(move |(key, message)| Binding {
+                        key: key.clone(),
+                        message: format!("{message:?}"),
+                        section,
+                        hidden: config
+                            .global
+                            .key_bindings
+                            .get(key)
+                            .is_some_and(|global| global != message),
+                    })
+            });
+
+    global.chain(pane_bindings).collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Open(ActivePane),
+    Inspect(Key),
+    ScrollUp,
+    ScrollDown,
+    Close,
+}
+
+pub fn update<'a>(
+    message: &Message,
+    config: &Config,
+    report: &ConfigReport,
+    state: &mut KeysDebugModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Open(pane) => {
+            let section = pane_section(config, *pane).map(|(section, _)| section);
+
+            *state = KeysDebugModalState {
+                pane: *pane,
+                bindings: resolve_keymap(config, *pane),
+                problems: report
+                    .problems
+                    .iter()
+                    .filter(|problem| {
+                        problem.section.as_deref().is_some_and(|problem_section| {
+                            problem_section == "global" || Some(problem_section) == section
+                        })
+                    })
+                    .cloned()
+                    .collect(),
+                visible: true,
+                ..Default::default()
+            };
+        }
+        Message::Inspect(key) => {
+            state.pressed = Some(key.to_string());
+        }
+        Message::ScrollUp => state.scroll = state.scroll.saturating_sub(1),
+        Message::ScrollDown => {
+            state.scroll = state
+                .scroll
+                .saturating_add(1)
+                .min(state.bindings.len().saturating_sub(1))
+        }
+        Message::Close => state.visible = false,
+    };
+
+    None
+}
+
+/// Keys debug modal key mappings cannot be modified through configuration, as every other key
+/// is inspected.
+pub fn handle_event(key: &KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Esc => Some(Message::Close),
+        KeyCode::PageUp => Some(Message::ScrollUp),
+        KeyCode::PageDown => Some(Message::ScrollDown),
+        _ => Some(Message::Inspect(key.into())),
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KeysDebugModalState {
+    /// Pane the keymap is resolved for, the active pane when the modal was opened.
+    pub(crate) pane: ActivePane,
+    pub(crate) bindings: Vec<Binding>,
+    /// Problems of the user configuration in the tables of the keymap.
+    pub(crate) problems: Vec<ConfigProblem>,
+    /// The last pressed key.
+    pub(crate) pressed: Option<String>,
+    pub(crate) scroll: usize,
+    pub(crate) visible: bool,
+}
+
+impl KeysDebugModalState {
+    /// Returns the binding that the pressed key triggers in the pane.
+    pub fn pressed_binding(&self) -> Option<&Binding> {
+        let key = self.pressed.as_ref()?;
+        self.bindings
+            .iter()
+            .find(|binding| &binding.key == key && !binding.hidden)
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Length(83), Constraint::Percentage(70))
+}
+
+#[derive(Default)]
+pub struct KeysDebugModal {
+    chrome: Chrome,
+}
+
+impl KeysDebugModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for KeysDebugModal {
+    type State = KeysDebugModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let pane: &str = state.pane.into();
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(" Keys · {pane} "))
+            .title(
+                Line::from(format!(" {} bindings ", state.bindings.len()))
+                    .alignment(Alignment::Right),
+            )
+            .title_bottom(
+                Line::from(" press a key to inspect · (pgup/pgdn) scroll · (esc) close ")
+                    .alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        let [pressed_area, problems_area, bindings_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(state.problems.len().min(5) as u16 + 1),
+            Constraint::Fill(1),
+        ])
+        .areas(inner_area);
+
+        let pressed = match (&state.pressed, state.pressed_binding()) {
+            (None, _) => Line::from("Press a key to see the command it triggers").italic(),
+            (Some(key), Some(binding)) => Line::from(vec![
+                Span::from(key.clone()).bold(),
+                Span::from(" → "),
+                Span::from(binding.message.clone()).green(),
+                Span::from(format!(" from [{}]", binding.section)).dark_gray(),
+            ]),
+            (Some(key), None) => Line::from(vec![
+                Span::from(key.clone()).bold(),
+                Span::from(" is not bound").dark_gray(),
+            ]),
+        };
+
+        Paragraph::new(pressed)
+            .fg(Color::default())
+            .render(pressed_area, buf);
+
+        Paragraph::new(
+            state
+                .problems
+                .iter()
+                .map(|problem| Line::from(format!("⚠ {problem}")).yellow())
+                .collect::<Vec<_>>(),
+        )
+        .render(problems_area, buf);
+
+        let key_width = state
+            .bindings
+            .iter()
+            .map(|binding| binding.key.chars().count())
+            .max()
+            .unwrap_or_default()
+            + 2;
+
+        let lines: Vec<_> = state
+            .bindings
+            .iter()
+            .skip(state.scroll)
+            .map(|binding| {
+                let key = Span::from(format!("{:<key_width$}", binding.key)).bold();
+                let message = Span::from(binding.message.clone());
+                let section = Span::from(format!(" [{}]", binding.section)).dark_gray();
+
+                match binding.hidden {
+                    true => Line::from(vec![
+                        key.dark_gray().crossed_out(),
+                        message.dark_gray().crossed_out(),
+                        section,
+                        Span::from(" hidden by [global]").yellow(),
+                    ]),
+                    false => Line::from(vec![key, message, section]).fg(Color::default()),
+                }
+            })
+            .collect();
+
+        Paragraph::new(lines).render(bindings_area, buf);
+
+        StatefulWidget::render(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area,
+            buf,
+            &mut ScrollbarState::new(state.bindings.len()).position(state.scroll),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_keymap() {
+        let mut config = Config::default();
+        config
+            .global
+            .key_bindings
+            .insert("q".into(), AppMessage::Quit);
+        config
+            .explorer
+            .key_bindings
+            .insert("q".into(), AppMessage::Publish);
+        config
+            .explorer
+            .key_bindings
+            .insert("j".into(), AppMessage::ArchiveNote);
+
+        let report = ConfigReport {
+            problems: vec![
+                ConfigProblem {
+                    line: Some(3),
+                    section: Some("explorer".into()),
+                    message: "`j` is bound again".into(),
+                },
+                ConfigProblem {
+                    line: Some(9),
+                    section: Some("outline".into()),
+                    message: "`k` is bound again".into(),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let mut state = KeysDebugModalState::default();
+        update(
+            &Message::Open(ActivePane::Explorer),
+            &config,
+            &report,
+            &mut state,
+        );
+
+        assert_eq!(state.problems, report.problems[..1]);
+
+        let hidden: Vec<_> = state
+            .bindings
+            .iter()
+            .filter(|binding| binding.hidden)
+            .map(|binding| binding.key.as_str())
+            .collect();
+        assert_eq!(hidden, vec!["q"]);
+
+        update(
+            &Message::Inspect(Key::new(KeyCode::Char('q'), KeyModifiers::NONE)),
+            &config,
+            &report,
+            &mut state,
+        );
+        assert_eq!(
+            state.pressed_binding().map(|binding| binding.section),
+            Some("global")
+        );
+
+        update(
+            &Message::Inspect(Key::new(KeyCode::Char('x'), KeyModifiers::NONE)),
+            &config,
+            &report,
+            &mut state,
+        );
+        assert_eq!(state.pressed_binding(), None);
+    }
+}
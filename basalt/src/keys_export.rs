@@ -0,0 +1,106 @@
+//! Export of the resolved keymap as markdown, for documenting a personal setup. The key bindings
+//! are listed per table, with the keys and commands as written in the configuration, including
+//! the `exec:` and `spawn:` bindings of the user.
+use crate::config::Config;
+
+/// Returns the key bindings of the configuration as markdown, a table per pane. Pane bindings
+/// that are never used because a global binding of the same key takes precedence are marked.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::{config::Config, keys_export};
+///
+/// let markdown = keys_export::markdown(&Config::default());
+///
+/// assert!(markdown.starts_with("# Key bindings\n"));
+/// ```
+pub fn markdown(config: &Config) -> String {
+    let sections = config
+        .sections()
+        .into_iter()
+        .filter(|(_, section)| !section.key_bindings.is_empty())
+        .map(|(name, section)| {
+            let rows = section.key_bindings.iter().map(|(key, message)| {
+                let hidden = config
+                    .global
+                    .key_bindings
+                    .get(key)
+                    .is_some_and(|global| name != "global" && global != message);
+
+                let hidden = match hidden {
+                    true => " *(hidden by a global binding)*",
+                    false => "",
+                };
+
+                let (key, command) = match section.bindings.get(key) {
+                    Some(binding) => (binding.key.to_config_string(), binding.command.to_string()),
+                    None => (key.clone(), format!("{message:?}")),
+                };
+
+                // Pipes would end the table cell.
+                format!(
+                    "| `{}` | `{}`{hidden} |",
+                    key.replace('|', "\\|"),
+                    command.replace('|', "\\|")
+                )
+            });
+
+            [
+                format!("## {name}"),
+                String::new(),
+                "| Key | Command |".to_string(),
+                "| --- | --- |".to_string(),
+            ]
+            .into_iter()
+            .chain(rows)
+            .collect::<Vec<_>>()
+            .join("\n")
+        });
+
+    ["# Key bindings".to_string()]
+        .into_iter()
+        .chain(sections)
+        .collect::<Vec<_>>()
+        .join("\n\n")
+        + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::{app::Message, config::Key, testing};
+
+    #[test]
+    fn test_markdown() {
+        let mut config = testing::config(indoc! {r#"
+            [global]
+            key_bindings = [
+             { key = "ctrl+alt+e", command = "exec:vi %note_path | cat" },
+            ]
+
+            [explorer]
+            key_bindings = [
+             { key = "x", command = "explorer_open" },
+             { key = "space", command = "explorer_open" },
+             { key = "page_down", command = "explorer_scroll_down_half_page" },
+            ]
+        "#});
+
+        // Validation leaves out conflicting user bindings, so the conflict is added afterwards.
+        config
+            .global
+            .key_bindings
+            .insert(Key::from('x').to_string(), Message::Quit);
+
+        let markdown = markdown(&config);
+
+        assert!(markdown.contains("| `ctrl+alt+e` | `exec:vi %note_path \\| cat` |\n"));
+        assert!(markdown.contains("| `space` | `explorer_open` |\n"));
+        assert!(markdown.contains("| `page_down` | `explorer_scroll_down_half_page` |\n"));
+        assert!(markdown.contains("| `x` | `explorer_open` *(hidden by a global binding)* |\n"));
+        assert!(markdown.contains("\n\n## note_editor\n\n| Key | Command |\n| --- | --- |\n"));
+    }
+}
@@ -1,13 +1,63 @@
+pub mod accessibility;
 pub mod app;
+pub mod attachments_modal;
+pub mod background;
+pub mod canvas_modal;
+pub mod capture;
+pub mod chrome;
+pub mod cli;
+pub mod clipboard;
+pub mod color_support;
 pub mod command;
 pub mod config;
+pub mod config_report_modal;
+pub mod crash;
+pub mod date_format;
+pub mod diagnostics_modal;
+pub mod diff;
+pub mod dirs;
+pub mod duplicates_modal;
+pub mod error_log_modal;
 pub mod explorer;
+pub mod extract;
+pub mod focus_timer;
+pub mod headless;
 pub mod help_modal;
+pub mod history;
+pub mod html_to_markdown;
+pub mod index_cache;
+pub mod input_modal;
+pub mod jobs_modal;
+pub mod keys_debug_modal;
+pub mod keys_export;
+pub mod link_fix;
+pub mod note_diff_modal;
 pub mod note_editor;
+pub mod note_history_modal;
+pub mod outgoing_links_modal;
 pub mod outline;
+pub mod print;
+pub mod quick_switcher;
+pub mod quit_modal;
+pub mod replace_modal;
+pub mod responsive;
+pub mod save_conflict_modal;
+pub mod screen_reader;
+pub mod session;
 pub mod splash_modal;
 pub mod statusbar;
 pub mod stylized_text;
+pub mod tag_browser_modal;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod text_counts;
+pub mod truncate;
+pub mod type_ahead;
+pub mod vault_loading_modal;
+pub mod vault_replace_modal;
 pub mod vault_selector;
 pub mod vault_selector_modal;
+pub mod vault_stats_modal;
+pub mod watcher;
+pub mod web_clip;
+pub mod writing_stats;
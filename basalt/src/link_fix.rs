@@ -0,0 +1,280 @@
+//! Fixing of broken links. Notes that were renamed or moved outside Basalt are paired with their
+//! new paths, so the links to them can be updated, and a single broken link can be pointed to one
+//! of the notes that match its target best.
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use basalt_core::obsidian::{IndexedNote, Link, LinkIndex, LinkKind, Note, Vault, VaultIndex};
+
+use crate::watcher::Changes;
+
+/// Most suggestions listed for a broken link.
+const MAX_SUGGESTIONS: usize = 20;
+
+fn is_note(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "md")
+}
+
+/// Returns `true` if the indexed contents of the notes are the same, apart from the title.
+fn same_contents(removed: &IndexedNote, added: &IndexedNote) -> bool {
+    let is_empty =
+        removed.headings.is_empty() && removed.tags.is_empty() && removed.links.is_empty();
+
+    !is_empty
+        && removed.headings == added.headings
+        && removed.tags == added.tags
+        && removed.links == added.links
+}
+
+/// Pairs the removed notes with the added notes they were renamed or moved to. A removed note is
+/// paired with an added note of the same name, which was moved, or with an added note with the same
+/// indexed contents, which was renamed. A single removed and added note are always paired.
+pub fn renames(changes: &Changes, index: &VaultIndex) -> Vec<(PathBuf, PathBuf)> {
+    let removed: Vec<_> = changes
+        .removed
+        .iter()
+        .filter(|path| is_note(path))
+        .collect();
+    let added: Vec<_> = changes.added.iter().filter(|path| is_note(path)).collect();
+
+    if let ([from], [to]) = (removed.as_slice(), added.as_slice()) {
+        return vec![((*from).clone(), (*to).clone())];
+    }
+
+    let indexed: Vec<_> = added
+        .iter()
+        .map(|path| {
+            let contents = fs::read_to_string(path).unwrap_or_default();
+            IndexedNote::new(&Note::from(path.as_path()), &contents, 0)
+        })
+        .collect();
+
+    let mut paired = HashSet::new();
+
+    removed
+        .into_iter()
+        .filter_map(|from| {
+            let i = (0..added.len())
+                .filter(|i| !paired.contains(i))
+                .find(|i| added[*i].file_name() == from.file_name())
+                .or_else(|| {
+                    let removed = index.get(from)?;
+                    (0..added.len())
+                        .filter(|i| !paired.contains(i))
+                        .find(|i| same_contents(removed, &indexed[*i]))
+                })?;
+
+            paired.insert(i);
+            Some((from.clone(), added[i].clone()))
+        })
+        .collect()
+}
+
+/// Returns the notes with links that pointed to the renamed notes before the changes and are
+/// broken now. Links that still resolve, e.g. by the note name after a move, are left out.
+pub fn broken_links(
+    vault: &Vault,
+    changes: &Changes,
+    renames: &[(PathBuf, PathBuf)],
+) -> BTreeSet<PathBuf> {
+    let files = vault.files();
+    let mut files_before = files.clone();
+    files_before.retain(|path| !changes.added.contains(path));
+    files_before.extend(changes.removed.iter().cloned());
+
+    let before = LinkIndex::new(&vault.path, files_before);
+    let after = LinkIndex::new(&vault.path, files);
+    let renamed = |target: PathBuf| renames.iter().any(|(from, _)| *from == target);
+
+    vault
+        .notes()
+        .into_iter()
+        .filter(|note| {
+            let contents = Note::read_to_string(note).unwrap_or_default();
+            Link::parse(&contents).iter().any(|link| {
+                before.resolve(&note.path, link).is_some_and(renamed)
+                    && after.resolve(&note.path, link).is_none()
+            })
+        })
+        .map(|note| note.path)
+        .collect()
+}
+
+/// Returns the pairs of adjacent characters of the text, ignoring case.
+fn bigrams(text: &str) -> Vec<(char, char)> {
+    let chars: Vec<_> = text.to_lowercase().chars().collect();
+    chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// Returns how closely the name matches the target, from 0 for nothing in common. Names that
+/// contain the target, or are contained in it, score highest, then names that share the most
+/// pairs of adjacent characters.
+fn score(target: &str, name: &str) -> usize {
+    let (target_lower, name_lower) = (target.to_lowercase(), name.to_lowercase());
+    let contains = name_lower.contains(&target_lower) || target_lower.contains(&name_lower);
+
+    let target_bigrams = bigrams(target);
+    let mut name_bigrams = bigrams(name);
+    let total = target_bigrams.len() + name_bigrams.len();
+
+    let shared = target_bigrams
+        .iter()
+        .filter(|bigram| {
+            let position = name_bigrams.iter().position(|other| other == *bigram);
+            position.map(|i| name_bigrams.swap_remove(i)).is_some()
+        })
+        .count();
+
+    let similarity = match total {
+        0 => 0,
+        total => 200 * shared / total,
+    };
+
+    similarity + if contains { 100 } else { 0 }
+}
+
+/// Returns the note labels that match the broken link target, best first. The labels are the
+/// paths of the notes relative to the vault without the `.md` extension, and are compared by the
+/// note name.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::link_fix;
+///
+/// let labels = ["Garden/Ideas".to_string(), "Inbox".into(), "Idea board".into()];
+///
+/// assert_eq!(link_fix::suggestions("Idaes", &labels), ["Garden/Ideas", "Idea board"]);
+/// ```
+pub fn suggestions(target: &str, labels: &[String]) -> Vec<String> {
+    let name = |path: &str| -> String {
+        let path = path.trim().trim_end_matches(".md");
+        path.rsplit('/').next().unwrap_or(path).to_string()
+    };
+    let target = name(target);
+
+    let mut scored: Vec<_> = labels
+        .iter()
+        .map(|label| (score(&target, &name(label)), label))
+        .filter(|(score, _)| *score > 0)
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, label)| label.clone())
+        .collect()
+}
+
+/// Returns the link target that points to the note with the label, written like the broken
+/// target: the note name for wikilinks that consist of the name, or the path relative to the vault
+/// otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::LinkKind;
+/// use basalt_tui::link_fix;
+///
+/// assert_eq!(link_fix::target(LinkKind::Wiki, "Idaes", "Garden/Ideas"), "Ideas");
+/// assert_eq!(link_fix::target(LinkKind::Wiki, "Notes/Idaes", "Garden/Ideas"), "Garden/Ideas");
+/// assert_eq!(
+///     link_fix::target(LinkKind::Markdown, "Idaes.md", "Garden/My ideas"),
+///     "Garden/My%20ideas.md"
+/// );
+/// ```
+pub fn target(kind: LinkKind, broken: &str, label: &str) -> String {
+    let extension = match broken.trim().ends_with(".md") {
+        true => ".md",
+        false => "",
+    };
+
+    match kind {
+        LinkKind::Wiki if !broken.contains('/') => {
+            let name = label.rsplit('/').next().unwrap_or(label);
+            format!("{name}{extension}")
+        }
+        LinkKind::Wiki => format!("{label}{extension}"),
+        LinkKind::Markdown => format!("{}.md", label.replace(' ', "%20")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn test_renames() {
+        let vault = testing::vault(
+            "basalt-link-fix-renames",
+            &[
+                ("Archive/Shed.md", "# Shed\n\n[[Pond]]\n"),
+                ("Garden plans.md", "# Pond\n\n#garden\n"),
+                ("Inbox.md", "[[Shed]] [[Ideas]] [[Inbox]]\n"),
+            ],
+        );
+
+        let mut index = VaultIndex::default();
+        index.insert(
+            &Note::from(vault.path.join("Shed.md").as_path()),
+            "# Shed\n\n[[Pond]]\n",
+        );
+        index.insert(
+            &Note::from(vault.path.join("Ideas.md").as_path()),
+            "# Pond\n\n#garden\n",
+        );
+        index.insert(
+            &Note::from(vault.path.join("Inbox.md").as_path()),
+            "[[Shed]] [[Ideas]] [[Inbox]]\n",
+        );
+
+        let changes = Changes {
+            removed: vec![vault.path.join("Ideas.md"), vault.path.join("Shed.md")],
+            added: vec![
+                vault.path.join("Archive/Shed.md"),
+                vault.path.join("Garden plans.md"),
+            ],
+            ..Default::default()
+        };
+
+        let renames = renames(&changes, &index);
+        assert_eq!(
+            renames,
+            [
+                (
+                    vault.path.join("Ideas.md"),
+                    vault.path.join("Garden plans.md")
+                ),
+                (
+                    vault.path.join("Shed.md"),
+                    vault.path.join("Archive/Shed.md")
+                ),
+            ]
+        );
+
+        assert_eq!(
+            broken_links(&vault, &changes, &renames),
+            BTreeSet::from([vault.path.join("Inbox.md")])
+        );
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let labels = [
+            "Projects".to_string(),
+            "Archive/Project ideas".into(),
+            "Pond".into(),
+        ];
+
+        assert_eq!(
+            suggestions("Archive/Projetcs.md", &labels),
+            ["Projects", "Archive/Project ideas"]
+        );
+        assert_eq!(suggestions("Shed", &labels), Vec::<String>::new());
+    }
+}
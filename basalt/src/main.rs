@@ -1,18 +1,184 @@
-use std::io;
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, stdout, Read, Write},
+    process::ExitCode,
+};
 
-use basalt_core::obsidian::ObsidianConfig;
-use basalt_tui::app::App;
+use basalt_core::obsidian::{ObsidianConfig, Vault};
+use basalt_tui::{
+    app::{self, App},
+    cli, config, crash, dirs,
+    headless::Headless,
+    keys_export,
+    print::PrintFormat,
+};
+use ratatui::crossterm::{
+    event::{DisableBracketedPaste, EnableBracketedPaste},
+    execute,
+};
+
+/// Size of the virtual terminal in headless mode, unless given with `--size`.
+const HEADLESS_SIZE: (u16, u16) = (80, 24);
+
+fn main() -> io::Result<ExitCode> {
+    let args = match cli::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n{}", cli::usage());
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    if args.has("--help") {
+        print!("{}", cli::help());
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.has("--version") {
+        println!("basalt {}", env!("CARGO_PKG_VERSION"));
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    match args.subcommand {
+        Some(("man", _)) => {
+            print!("{}", cli::man(env!("CARGO_PKG_VERSION")));
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(("completions", shell)) => {
+            // The shell is one of the choices of the subcommand.
+            print!(
+                "{}",
+                cli::completions(&shell.unwrap_or_default()).unwrap_or_default()
+            );
+            return Ok(ExitCode::SUCCESS);
+        }
+        _ => {}
+    }
+
+    if args.has("--keys-export") {
+        return keys_export();
+    }
+
+    // The format is one of the choices of the option.
+    let print = match (args.has("--print"), args.value("--print")) {
+        (false, _) => None,
+        (true, None) => Some(PrintFormat::Raw),
+        (true, Some(format)) => PrintFormat::parse(format),
+    };
+
+    if let Err(err) = dirs::migrate() {
+        eprintln!("Could not move the session to the state directory: {err}");
+    }
 
-fn main() -> io::Result<()> {
-    let mut terminal = ratatui::init();
     let obsidian_config = ObsidianConfig::load().unwrap();
     let vaults = obsidian_config.vaults();
 
+    if let Some(script) = args.value("--headless") {
+        return headless(script, args.value("--size"), vaults);
+    }
+
+    // With `--print` the note goes to the piped stdout, so the interface is drawn on the terminal.
+    let pipe = match print {
+        Some(_) => redirect_stdout_to_tty()?,
+        None => None,
+    };
+
+    let mut terminal = ratatui::init();
+    crash::install_panic_hook();
+
     terminal.show_cursor()?;
+    execute!(stdout(), EnableBracketedPaste)?;
 
-    App::start(terminal, vaults)?;
+    let note = App::start(terminal, vaults, print)?;
 
+    execute!(stdout(), DisableBracketedPaste)?;
     ratatui::restore();
 
-    Ok(())
+    if let Some(note) = note {
+        match pipe {
+            Some(mut pipe) => pipe.write_all(note.as_bytes())?,
+            None => stdout().write_all(note.as_bytes())?,
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Points stdout to the terminal when it is piped, returning the pipe to print to on quit.
+#[cfg(unix)]
+fn redirect_stdout_to_tty() -> io::Result<Option<File>> {
+    use std::{fs::OpenOptions, io::IsTerminal, os::fd::AsFd};
+
+    if stdout().is_terminal() {
+        return Ok(None);
+    }
+
+    let pipe = stdout().as_fd().try_clone_to_owned()?;
+    let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    rustix::stdio::dup2_stdout(&tty)?;
+
+    Ok(Some(File::from(pipe)))
+}
+
+#[cfg(not(unix))]
+fn redirect_stdout_to_tty() -> io::Result<Option<File>> {
+    Ok(None)
+}
+
+/// Prints the key bindings of the configuration as markdown.
+fn keys_export() -> io::Result<ExitCode> {
+    let (config, report) = config::load();
+    if let Some(report) = report {
+        report
+            .problems
+            .iter()
+            .for_each(|problem| eprintln!("{}: {problem}", report.path.display()));
+    }
+
+    print!("{}", keys_export::markdown(&config));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Runs the script on a virtual terminal and prints the screen and the state.
+fn headless(script: &str, size: Option<&str>, vaults: Vec<&Vault>) -> io::Result<ExitCode> {
+    let Some((width, height)) = size.map_or(Some(HEADLESS_SIZE), |size| {
+        let (width, height) = size.split_once('x')?;
+        Some((width.parse().ok()?, height.parse().ok()?))
+    }) else {
+        eprintln!("Invalid size {}, expected e.g. 80x24", size.unwrap());
+        return Ok(ExitCode::FAILURE);
+    };
+
+    let script = match script {
+        "-" => {
+            let mut script = String::new();
+            io::stdin().read_to_string(&mut script)?;
+            script
+        }
+        path => fs::read_to_string(path)?,
+    };
+
+    let (config, report) = config::load();
+    if let Some(report) = report {
+        report
+            .problems
+            .iter()
+            .for_each(|problem| eprintln!("{}: {problem}", report.path.display()));
+    }
+
+    let version = app::version();
+    let mut headless = Headless::new(&config, &version, vaults, width, height);
+    let result = headless.run_script(&script);
+
+    println!("{}\n\n{}", headless.screen(), headless.state());
+
+    match result {
+        Ok(()) => Ok(ExitCode::SUCCESS),
+        Err(err) => {
+            eprintln!("Script failed at {err}");
+            Ok(ExitCode::FAILURE)
+        }
+    }
 }
@@ -0,0 +1,227 @@
+use std::{path::Path, process};
+
+use basalt_core::obsidian::Note;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Rect, Size},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Clear, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{
+    app::{calc_scroll_amount, Message as AppMessage, ScrollAmount},
+    chrome::Chrome,
+    diff::{self, DiffLine},
+    note_editor::EditorState,
+    responsive,
+};
+
+/// Git revision the note is compared against when the git source is selected.
+const GIT_REVISION: &str = "HEAD";
+
+/// What the editor content is compared against.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Source {
+    #[default]
+    Disk,
+    Git,
+}
+
+/// Reads the note as committed in the git revision. Fails when the vault is not a git repository
+/// or the note is not committed.
+fn read_git_revision(path: &Path, revision: &str) -> Result<String, String> {
+    let dir = path.parent().ok_or("Note has no parent directory")?;
+    let file_name = path
+        .file_name()
+        .ok_or("Note has no file name")?
+        .to_string_lossy();
+
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("show")
+        .arg(format!("{revision}:./{file_name}"))
+        .output()
+        .map_err(|err| err.to_string())?;
+
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        false => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    ToggleSource,
+    Close,
+    ScrollUp(ScrollAmount),
+    ScrollDown(ScrollAmount),
+}
+
+pub fn update<'a>(
+    message: &Message,
+    screen_size: Size,
+    editor: &EditorState,
+    state: &mut NoteDiffModalState,
+) -> Option<AppMessage<'a>> {
+    let height = modal_area(Rect::new(0, 0, screen_size.width, screen_size.height)).height;
+
+    match message {
+        Message::Toggle if state.visible => state.hide(),
+        Message::Toggle => {
+            if editor.path().as_os_str().is_empty() {
+                return None;
+            }
+
+            state.visible = true;
+            state.source = Source::Disk;
+            state.load(editor);
+        }
+        Message::ToggleSource => {
+            state.source = match state.source {
+                Source::Disk => Source::Git,
+                Source::Git => Source::Disk,
+            };
+            state.load(editor);
+        }
+        Message::Close => state.hide(),
+        Message::ScrollUp(scroll_amount) => {
+            state.scroll = state
+                .scroll
+                .saturating_sub(calc_scroll_amount(scroll_amount, height.into()));
+        }
+        Message::ScrollDown(scroll_amount) => {
+            state.scroll = state
+                .scroll
+                .saturating_add(calc_scroll_amount(scroll_amount, height.into()))
+                .min(state.lines.len().saturating_sub(1));
+        }
+    };
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NoteDiffModalState {
+    pub(crate) source: Source,
+    pub(crate) title: String,
+    pub(crate) lines: Vec<Line<'static>>,
+    /// Number of added and removed lines.
+    pub(crate) changes: (usize, usize),
+    pub(crate) error: Option<String>,
+    pub(crate) scroll: usize,
+    pub(crate) visible: bool,
+}
+
+impl NoteDiffModalState {
+    /// Compares the editor content against the selected source.
+    fn load(&mut self, editor: &EditorState) {
+        let path = editor.path();
+
+        self.scroll = 0;
+        self.title = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let original = match self.source {
+            Source::Disk => Note::read_to_string(&Note::from(path)).map_err(|err| err.to_string()),
+            Source::Git => read_git_revision(path, GIT_REVISION),
+        };
+
+        match original {
+            Ok(original) => {
+                let diff = diff::diff_lines(&original, editor.content());
+
+                self.changes = diff
+                    .iter()
+                    .fold((0, 0), |(added, removed), line| match line {
+                        DiffLine::Added(_) => (added + 1, removed),
+                        DiffLine::Removed(_) => (added, removed + 1),
+                        DiffLine::Equal(_) => (added, removed),
+                    });
+                self.lines = diff::render_lines(&diff);
+                self.error = None;
+            }
+            Err(error) => {
+                self.changes = (0, 0);
+                self.lines.clear();
+                self.error = Some(error);
+            }
+        }
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(80), Constraint::Percentage(80))
+}
+
+#[derive(Default)]
+pub struct NoteDiffModal {
+    chrome: Chrome,
+}
+
+impl NoteDiffModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for NoteDiffModal {
+    type State = NoteDiffModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let source = match state.source {
+            Source::Disk => "on disk".to_string(),
+            Source::Git => format!("git {GIT_REVISION}"),
+        };
+
+        let (added, removed) = state.changes;
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(" Diff: {} ", state.title))
+            .title(
+                Line::from(format!(" +{added} -{removed} against {source} "))
+                    .alignment(Alignment::Right),
+            )
+            .title_bottom(
+                Line::from(" (g) toggle disk/git · (esc) close ").alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if let Some(error) = &state.error {
+            Paragraph::new(error.as_str()).red().render(inner_area, buf);
+            return;
+        }
+
+        if added == 0 && removed == 0 {
+            Paragraph::new("No changes")
+                .dark_gray()
+                .italic()
+                .render(inner_area, buf);
+            return;
+        }
+
+        Paragraph::new(state.lines.clone())
+            .scroll((state.scroll as u16, 0))
+            .render(inner_area, buf);
+    }
+}
@@ -1,4 +1,8 @@
+mod bidi;
+mod completion;
 mod editor;
+pub mod query_block;
+mod registers;
 mod state;
 mod text_buffer;
 
@@ -17,17 +21,25 @@ mod text_buffer;
 /// pub mod markdown;
 pub mod markdown_parser;
 
-pub use editor::Editor;
+pub use completion::{
+    embed_target, link_anchor, link_target, tag_prefix, AnchorKind, Completion, CompletionPopup,
+    LinkAnchor,
+};
+pub use editor::{Editor, LineNumbers};
 use ratatui::{
-    crossterm::event::{KeyCode, KeyEvent},
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
     layout::Size,
 };
+pub use registers::Registers;
 pub use state::{EditorState, Mode};
 pub use text_buffer::TextBuffer;
 
 use crate::{
-    app::{calc_scroll_amount, ActivePane, Message as AppMessage, ScrollAmount},
-    explorer, outline,
+    app::{calc_scroll_amount, Message as AppMessage, ScrollAmount},
+    clipboard::{self, Clipboard},
+    explorer, html_to_markdown,
+    input_modal::{self, InputAction},
+    outline, save_conflict_modal,
 };
 
 #[derive(Clone, Debug, PartialEq)]
@@ -47,10 +59,36 @@ pub enum Message {
     CursorWordForward,
     CursorWordBackward,
     CursorDown,
+    /// Moves the cursor to the start of the note.
+    GotoTop,
+    /// Moves the cursor to the end of the note.
+    GotoBottom,
     ScrollUp(ScrollAmount),
     ScrollDown(ScrollAmount),
     SetRow(usize),
     Delete,
+    PasteAsMarkdown,
+    Paste(String),
+    NextHeading,
+    PreviousHeading,
+    /// Moves the cursor to the block that was changed last, also in an earlier session.
+    GotoLastEdit,
+    MoveNodeUp,
+    MoveNodeDown,
+    YankNode,
+    DeleteNode,
+    PasteNode,
+    /// Asks for the register of the next yank, delete or paste.
+    Registers,
+    SelectRegister(char),
+    CompletionUp,
+    CompletionDown,
+    AcceptCompletion,
+    CloseCompletion,
+    /// Asks for the note to move the selection, or the current node, into.
+    Extract,
+    /// Asks for the note the first broken link of the current node points to instead.
+    FixLink,
 }
 
 pub fn update<'a>(
@@ -58,6 +96,26 @@ pub fn update<'a>(
     screen_size: Size,
     state: &mut EditorState,
 ) -> Option<AppMessage<'a>> {
+    let is_change = matches!(
+        message,
+        Message::EditMode
+            | Message::Delete
+            | Message::PasteAsMarkdown
+            | Message::Paste(_)
+            | Message::MoveNodeUp
+            | Message::MoveNodeDown
+            | Message::DeleteNode
+            | Message::PasteNode
+            | Message::Extract
+            | Message::FixLink
+    );
+
+    if let Some(reason) = state.read_only().filter(|_| is_change) {
+        return Some(AppMessage::SetStatus(format!(
+            "This note is read-only, as {reason}"
+        )));
+    }
+
     match message {
         Message::CursorLeft => state.cursor_left(),
         Message::CursorRight => state.cursor_right(),
@@ -66,12 +124,109 @@ pub fn update<'a>(
         Message::Delete => state.delete_char(),
         Message::SetRow(row) => state.set_row(*row),
 
+        Message::PasteAsMarkdown => {
+            let text = match clipboard::read()? {
+                Clipboard::Html(html) => html_to_markdown::convert(&html),
+                Clipboard::Text(text) => text,
+            };
+
+            state.insert(&text);
+
+            // Blocks are parsed again when leaving edit mode.
+            let nodes = (!state.is_editing()).then(|| state.nodes().to_vec());
+            return Some(AppMessage::UpdateSelectedNoteContent((
+                state.content().to_string(),
+                nodes,
+            )));
+        }
+
+        Message::Paste(text) => {
+            state.paste(text);
+            return Some(AppMessage::UpdateSelectedNoteContent((
+                state.content().to_string(),
+                None,
+            )));
+        }
+
         Message::CursorUp => {
             state.cursor_up();
             return Some(AppMessage::Outline(outline::Message::SelectAt(
                 state.current_row,
             )));
         }
+        Message::NextHeading if !state.is_editing() => {
+            state.next_heading();
+            return Some(AppMessage::Outline(outline::Message::SelectAt(
+                state.current_row,
+            )));
+        }
+        Message::PreviousHeading if !state.is_editing() => {
+            state.previous_heading();
+            return Some(AppMessage::Outline(outline::Message::SelectAt(
+                state.current_row,
+            )));
+        }
+        Message::GotoLastEdit if !state.is_editing() => {
+            if !state.goto_last_edit() {
+                return Some(AppMessage::SetStatus("No changes in the note yet".into()));
+            }
+            return Some(AppMessage::Outline(outline::Message::SelectAt(
+                state.current_row,
+            )));
+        }
+        Message::YankNode if !state.is_editing() => state.yank_node(),
+        Message::Registers if !state.is_editing() => {
+            // Registers are listed as `"a  First line`, and a register that is not listed is
+            // selected by typing its name.
+            let options = state
+                .registers
+                .list()
+                .into_iter()
+                .map(|(name, text)| {
+                    format!("\"{name}  {}", text.lines().next().unwrap_or_default())
+                })
+                .collect();
+
+            return Some(AppMessage::InputModal(input_modal::Message::Select {
+                title: "Register for the next yank, delete or paste, e.g. \"a".into(),
+                value: String::new(),
+                options,
+                action: InputAction::SelectRegister,
+            }));
+        }
+        Message::SelectRegister(name) => {
+            state.registers.select(*name);
+            return Some(AppMessage::SetStatus(format!("Register \"{name}")));
+        }
+        Message::Extract => {
+            let Some(text) = state.extract_text() else {
+                return Some(AppMessage::SetStatus("Select the text to extract".into()));
+            };
+
+            return Some(AppMessage::InputModal(input_modal::Message::Open {
+                title: "Extract to note, e.g. Projects#Ideas".into(),
+                value: String::new(),
+                action: InputAction::ExtractTo(text),
+            }));
+        }
+        Message::FixLink if !state.is_editing() => {
+            return Some(AppMessage::FixLink(state.node_links()));
+        }
+        Message::MoveNodeUp | Message::MoveNodeDown | Message::DeleteNode | Message::PasteNode
+            if !state.is_editing() =>
+        {
+            match message {
+                Message::MoveNodeUp => state.move_node(true),
+                Message::MoveNodeDown => state.move_node(false),
+                Message::DeleteNode => state.delete_node(),
+                _ => state.paste_node(),
+            }
+
+            return Some(AppMessage::UpdateSelectedNoteContent((
+                state.content().to_string(),
+                Some(state.nodes().to_vec()),
+            )));
+        }
         Message::CursorDown => {
             state.cursor_down();
             return Some(AppMessage::Outline(outline::Message::SelectAt(
@@ -85,6 +240,17 @@ pub fn update<'a>(
         Mode::Edit => match message {
             Message::ScrollUp(_) => state.cursor_up(),
             Message::ScrollDown(_) => state.cursor_down(),
+            Message::CompletionUp => state.completion_as_mut()?.up(),
+            Message::CompletionDown => state.completion_as_mut()?.down(),
+            Message::CloseCompletion => state.set_completion(None),
+            Message::AcceptCompletion => {
+                state.accept_completion();
+
+                return Some(AppMessage::UpdateSelectedNoteContent((
+                    state.content().to_string(),
+                    None,
+                )));
+            }
             Message::KeyEvent(key) => {
                 state.edit((*key).into());
 
@@ -109,6 +275,16 @@ pub fn update<'a>(
             Message::ReadMode => state.set_mode(Mode::Read),
             Message::ExitMode => state.set_mode(Mode::View),
             Message::SetRow(row) => state.set_row(*row),
+            Message::GotoTop | Message::GotoBottom => {
+                match message {
+                    Message::GotoTop => state.goto_top(),
+                    _ => state.goto_bottom(),
+                }
+
+                return Some(AppMessage::Outline(outline::Message::SelectAt(
+                    state.current_row,
+                )));
+            }
 
             Message::ScrollUp(scroll_amount) => {
                 state.scroll_up(calc_scroll_amount(scroll_amount, screen_size.height.into()));
@@ -122,15 +298,17 @@ pub fn update<'a>(
             Message::ToggleOutline => {
                 return Some(AppMessage::Outline(outline::Message::Toggle));
             }
-            Message::SwitchPaneNext => {
-                state.set_active(false);
-                return Some(AppMessage::SetActivePane(ActivePane::Outline));
-            }
-            Message::SwitchPanePrevious => {
-                state.set_active(false);
-                return Some(AppMessage::SetActivePane(ActivePane::Explorer));
-            }
+            Message::SwitchPaneNext => return Some(AppMessage::SwitchPaneNext),
+            Message::SwitchPanePrevious => return Some(AppMessage::SwitchPanePrevious),
+            // Changes made to the note file outside Basalt are not overwritten without asking.
             Message::Save => {
+                if state.modified && state.changed_on_disk() {
+                    let name = state.path().file_stem().unwrap_or_default();
+                    return Some(AppMessage::SaveConflictModal(
+                        save_conflict_modal::Message::Open(name.to_string_lossy().to_string()),
+                    ));
+                }
+
                 state.save();
                 return Some(AppMessage::UpdateSelectedNoteContent((
                     state.content().to_string(),
@@ -144,12 +322,57 @@ pub fn update<'a>(
     None
 }
 
-pub fn handle_editing_event(key: &KeyEvent) -> Option<Message> {
+/// While link anchors are completed, the arrow keys select the completion, ‹Tab› or ‹Enter›
+/// accepts it and ‹Esc› closes it.
+pub fn handle_editing_event(key: &KeyEvent, state: &EditorState) -> Option<Message> {
+    if state.completion().is_some() {
+        match key.code {
+            KeyCode::Up => return Some(Message::CompletionUp),
+            KeyCode::Down => return Some(Message::CompletionDown),
+            KeyCode::Tab | KeyCode::Enter => return Some(Message::AcceptCompletion),
+            KeyCode::Esc => return Some(Message::CloseCompletion),
+            _ => {}
+        }
+    }
+
     match key.code {
         KeyCode::Up => Some(Message::CursorUp),
         KeyCode::Down => Some(Message::CursorDown),
         KeyCode::Esc => Some(Message::ExitMode),
         KeyCode::Backspace => Some(Message::Delete),
+        KeyCode::Char('v') if key.modifiers == KeyModifiers::CONTROL | KeyModifiers::ALT => {
+            Some(Message::PasteAsMarkdown)
+        }
+        KeyCode::Char('x') if key.modifiers == KeyModifiers::CONTROL | KeyModifiers::ALT => {
+            Some(Message::Extract)
+        }
         _ => Some(Message::KeyEvent(*key)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use basalt_core::obsidian::ReadOnly;
+
+    use super::*;
+
+    #[test]
+    fn test_read_only() {
+        let mut state = EditorState::new("# Archive\n\nDone", "Archive.md".into());
+        state.set_read_only(Some(ReadOnly::Frontmatter));
+
+        assert_eq!(
+            update(&Message::EditMode, Size::default(), &mut state),
+            Some(AppMessage::SetStatus(
+                "This note is read-only, as its frontmatter sets readonly: true".into()
+            ))
+        );
+        assert!(!state.is_editing());
+        assert!(update(&Message::PasteNode, Size::default(), &mut state).is_some());
+        assert_eq!(state.content(), "# Archive\n\nDone");
+
+        state.set_read_only(None);
+        update(&Message::EditMode, Size::default(), &mut state);
+        assert!(state.is_editing());
+    }
+}
@@ -0,0 +1,233 @@
+//! Basic bidirectional text handling for rendering right-to-left scripts, like Hebrew and
+//! Arabic, in the order they are read.
+//!
+//! Terminals draw characters from left to right in the order they are stored. Lines that contain
+//! right-to-left text are reordered for display with a simplified version of the Unicode
+//! Bidirectional Algorithm: characters are strong left-to-right, strong right-to-left or
+//! neutral, neutrals between two characters of the same direction take that direction and other
+//! neutrals take the direction of the paragraph. Explicit direction marks and embeddings are not
+//! supported.
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+
+/// Writing direction of a character or a paragraph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Returns `true` for characters of right-to-left scripts: Hebrew, Arabic, Syriac, Thaana, NKo
+/// and their presentation forms.
+fn is_rtl_char(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}'
+    )
+}
+
+/// Returns the direction of a strong character, or `None` for neutral characters like spaces and
+/// punctuation. Digits are treated as left-to-right, so that numbers keep their order.
+fn strong_direction(c: char) -> Option<Direction> {
+    if is_rtl_char(c) {
+        Some(Direction::RightToLeft)
+    } else if c.is_alphanumeric() {
+        Some(Direction::LeftToRight)
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if the paragraph is written right-to-left, i.e. the first letter of the text is
+/// of a right-to-left script. Digits do not decide the direction of the paragraph.
+pub fn is_rtl(text: &str) -> bool {
+    text.chars()
+        .filter(|c| !c.is_numeric())
+        .find_map(strong_direction)
+        == Some(Direction::RightToLeft)
+}
+
+/// Returns the mirrored bracket, as brackets in right-to-left text point the opposite way.
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        _ => c,
+    }
+}
+
+/// Resolves the embedding levels of the characters: even levels are drawn left-to-right and odd
+/// levels right-to-left.
+fn levels(chars: &[char], paragraph: Direction) -> Vec<u8> {
+    let directions: Vec<_> = chars.iter().map(|c| strong_direction(*c)).collect();
+
+    (0..chars.len())
+        .map(|i| {
+            let direction = directions[i].unwrap_or_else(|| {
+                let before = directions[..i].iter().rev().find_map(|d| *d);
+                let after = directions[i + 1..].iter().find_map(|d| *d);
+                match (before, after) {
+                    (Some(before), Some(after)) if before == after => before,
+                    _ => paragraph,
+                }
+            });
+
+            match (paragraph, direction) {
+                (Direction::LeftToRight, Direction::LeftToRight) => 0,
+                (Direction::LeftToRight, Direction::RightToLeft) => 1,
+                (Direction::RightToLeft, Direction::RightToLeft) => 1,
+                (Direction::RightToLeft, Direction::LeftToRight) => 2,
+            }
+        })
+        .collect()
+}
+
+/// Returns the display order of the characters: from the highest level down to the lowest odd
+/// level, every run of characters at that level or higher is reversed.
+fn display_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let highest = levels.iter().copied().max().unwrap_or_default();
+    let lowest_odd = levels
+        .iter()
+        .copied()
+        .filter(|level| level % 2 == 1)
+        .min()
+        .unwrap_or(highest + 1);
+
+    for level in (lowest_odd..=highest).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level {
+                i += 1;
+                continue;
+            }
+
+            let start = i;
+            while i < order.len() && levels[order[i]] >= level {
+                i += 1;
+            }
+            order[start..i].reverse();
+        }
+    }
+
+    order
+}
+
+/// Reorders the line for display in a paragraph with the given direction. Styles stay with their
+/// characters, and lines of right-to-left paragraphs are padded to align them to the right of the
+/// width.
+fn reorder_line(line: Line<'static>, rtl: bool, width: usize) -> Line<'static> {
+    let chars: Vec<(char, Style)> = line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect();
+
+    let paragraph = match rtl {
+        true => Direction::RightToLeft,
+        false => Direction::LeftToRight,
+    };
+
+    if !rtl && !chars.iter().any(|(c, _)| is_rtl_char(*c)) {
+        return line;
+    }
+
+    let text: Vec<char> = chars.iter().map(|(c, _)| *c).collect();
+    let levels = levels(&text, paragraph);
+
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    display_order(&levels).into_iter().for_each(|i| {
+        let (c, style) = chars[i];
+        let c = match levels[i] % 2 {
+            1 => mirror(c),
+            _ => c,
+        };
+
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    });
+
+    let padding = width.saturating_sub(line.width());
+    if rtl && padding > 0 {
+        spans.insert(0, Span::raw(" ".repeat(padding)));
+    }
+
+    Line { spans, ..line }
+}
+
+/// Reorders the rendered lines of a node for display in the width. The direction of the node is
+/// detected from its first strong character.
+pub fn reorder(lines: Vec<Line<'static>>, width: u16) -> Vec<Line<'static>> {
+    let text: String = lines
+        .iter()
+        .flat_map(|line| line.spans.iter())
+        .map(|span| span.content.as_ref())
+        .collect();
+    let rtl = is_rtl(&text);
+
+    lines
+        .into_iter()
+        .map(|line| reorder_line(line, rtl, width.into()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Stylize;
+
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_is_rtl() {
+        assert!(is_rtl("שלום עולם"));
+        assert!(is_rtl("123 مرحبا"));
+        assert!(!is_rtl("Hello שלום"));
+        assert!(!is_rtl("..."));
+    }
+
+    #[test]
+    fn test_reorder() {
+        let tests = [
+            ("Hello world", "Hello world"),
+            ("אבג דהו", "             והד גבא"),
+            ("Read אבג now", "Read גבא now"),
+            ("אבג Basalt 2.0 דה", "   הד Basalt 2.0 גבא"),
+            ("אב (גד)", "             (דג) בא"),
+        ];
+
+        tests.into_iter().for_each(|(input, expected)| {
+            let lines = reorder(vec![Line::from(input)], 20);
+            assert_eq!(text(&lines[0]), expected, "With input {input}");
+        });
+    }
+
+    #[test]
+    fn test_reorder_keeps_styles() {
+        let lines = reorder(
+            vec![Line::from(vec![Span::from("אב "), Span::from("גד").bold()])],
+            5,
+        );
+
+        assert_eq!(
+            lines[0].spans,
+            vec![Span::from("דג").bold(), Span::from(" בא")]
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Completion of links, link anchors and tags in edit mode. Typing `[[` lists the notes of the
+//! vault and their aliases, `[[Note#` the headings of the linked note, `[[Note#^` its block IDs and
+//! `#` followed by a letter the tags of the vault, narrowed down while typing.
+use basalt_core::obsidian::Tag;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    widgets::{Block, BorderType, Clear, List, ListItem, ListState, StatefulWidget, Widget},
+};
+
+/// Most completion items shown at once.
+const MAX_VISIBLE_ITEMS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnchorKind {
+    Heading,
+    Block,
+}
+
+/// A link anchor that is being typed, e.g. `[[Projects#Sh`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkAnchor<'a> {
+    /// The link target, empty for anchors in the same note, e.g. `[[#Heading`.
+    pub target: &'a str,
+    pub kind: AnchorKind,
+    /// The typed part of the heading or block ID, without the `^` of block IDs.
+    pub partial: &'a str,
+}
+
+/// Returns the link anchor that ends at the cursor, given the text of the cursor line before the
+/// cursor.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::note_editor::{link_anchor, AnchorKind, LinkAnchor};
+///
+/// assert_eq!(
+///     link_anchor("See [[Projects#^ta"),
+///     Some(LinkAnchor { target: "Projects", kind: AnchorKind::Block, partial: "ta" })
+/// );
+/// assert_eq!(link_anchor("See [[Projects]] #tag"), None);
+/// ```
+pub fn link_anchor(before_cursor: &str) -> Option<LinkAnchor<'_>> {
+    let (_, link) = before_cursor.rsplit_once("[[")?;
+    if link.contains("]]") || link.contains('|') {
+        return None;
+    }
+
+    let (target, anchor) = link.split_once('#')?;
+
+    let (kind, partial) = match anchor.strip_prefix('^') {
+        Some(partial) => (AnchorKind::Block, partial),
+        None => (AnchorKind::Heading, anchor),
+    };
+
+    Some(LinkAnchor {
+        target,
+        kind,
+        partial,
+    })
+}
+
+/// Returns the partial link target that ends at the cursor, without the `[[`, given the text of
+/// the cursor line before the cursor.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::note_editor::link_target;
+///
+/// assert_eq!(link_target("See [[Proj"), Some("Proj"));
+/// assert_eq!(link_target("See [[Projects#Sh"), None);
+/// ```
+pub fn link_target(before_cursor: &str) -> Option<&str> {
+    let (_, target) = before_cursor.rsplit_once("[[")?;
+
+    (!target.contains("]]") && !target.contains(['|', '#'])).then_some(target)
+}
+
+/// Returns the partial embed target that ends at the cursor, without the `![[`, given the text of
+/// the cursor line before the cursor.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::note_editor::embed_target;
+///
+/// assert_eq!(embed_target("See ![[Sh"), Some("Sh"));
+/// assert_eq!(embed_target("See [[Sh"), None);
+/// ```
+pub fn embed_target(before_cursor: &str) -> Option<&str> {
+    let (before, _) = before_cursor.rsplit_once("[[")?;
+
+    before
+        .ends_with('!')
+        .then(|| link_target(before_cursor))
+        .flatten()
+}
+
+/// Returns the partial tag that ends at the cursor, without the `#`, given the text of the cursor
+/// line before the cursor.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::note_editor::tag_prefix;
+///
+/// assert_eq!(tag_prefix("See #project/ba"), Some("project/ba"));
+/// assert_eq!(tag_prefix("issue#1 and #"), None);
+/// ```
+pub fn tag_prefix(before_cursor: &str) -> Option<&str> {
+    let (before, partial) = before_cursor.rsplit_once('#')?;
+    let starts_tag = before.chars().next_back().is_none_or(char::is_whitespace);
+
+    (starts_tag && Tag::is_valid_name(partial)).then_some(partial)
+}
+
+/// The completion items for the typed text, and the selected item.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Completion {
+    items: Vec<String>,
+    selected: usize,
+    /// Number of typed characters that the selected item replaces.
+    typed_len: usize,
+    /// Text that closes the completed item, e.g. `]]` of links, inserted unless it follows the
+    /// cursor already.
+    closing: &'static str,
+}
+
+impl Completion {
+    /// Returns the completion of the typed text from the candidates that contain it, ignoring
+    /// case, or `None` if no candidate matches. Candidates that start with the typed text are
+    /// listed first.
+    pub fn new(typed: &str, candidates: &[String]) -> Option<Self> {
+        let typed_lower = typed.to_lowercase();
+
+        let mut items: Vec<_> = candidates
+            .iter()
+            .filter(|candidate| *candidate != typed)
+            .filter(|candidate| candidate.to_lowercase().contains(&typed_lower))
+            .cloned()
+            .collect();
+        items.sort_by_key(|item| !item.to_lowercase().starts_with(&typed_lower));
+        items.dedup();
+
+        (!items.is_empty()).then(|| Self {
+            items,
+            selected: 0,
+            typed_len: typed.chars().count(),
+            closing: "",
+        })
+    }
+
+    pub fn closing(self, closing: &'static str) -> Self {
+        Self { closing, ..self }
+    }
+
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    pub fn selected(&self) -> &str {
+        &self.items[self.selected]
+    }
+
+    pub fn typed_len(&self) -> usize {
+        self.typed_len
+    }
+
+    pub fn closing_text(&self) -> &'static str {
+        self.closing
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1).min(self.items.len() - 1);
+    }
+}
+
+/// List of the completion items, shown below the cursor or above it when there is no room
+/// below.
+pub struct CompletionPopup<'a> {
+    pub completion: &'a Completion,
+    /// Screen position of the cursor.
+    pub cursor: (u16, u16),
+}
+
+impl Widget for CompletionPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let items = self.completion.items();
+        let width = items
+            .iter()
+            .map(|item| item.chars().count() as u16)
+            .max()
+            .unwrap_or_default()
+            .saturating_add(4)
+            .min(area.width);
+        let height = (items.len().min(MAX_VISIBLE_ITEMS) as u16 + 2).min(area.height);
+
+        let (x, y) = self.cursor;
+        let x = x.min(area.right().saturating_sub(width)).max(area.x);
+        let y = match y + 1 + height <= area.bottom() {
+            true => y + 1,
+            false => y.saturating_sub(height).max(area.y),
+        };
+
+        let popup = Rect::new(x, y, width, height).intersection(area);
+
+        let items: Vec<_> = items
+            .iter()
+            .map(|item| ListItem::new(item.as_str()))
+            .collect();
+        let mut state = ListState::default().with_selected(Some(self.completion.selected));
+
+        Clear.render(popup, buf);
+        StatefulWidget::render(
+            List::new(items)
+                .block(
+                    Block::bordered()
+                        .border_type(BorderType::Rounded)
+                        .dark_gray(),
+                )
+                .style(Style::default().reset())
+                .highlight_style(Style::default().reversed()),
+            popup,
+            buf,
+            &mut state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_anchor() {
+        let heading = |target, partial| {
+            Some(LinkAnchor {
+                target,
+                kind: AnchorKind::Heading,
+                partial,
+            })
+        };
+
+        assert_eq!(link_anchor("[[Projects#"), heading("Projects", ""));
+        assert_eq!(link_anchor("[[a]] [[#Po"), heading("", "Po"));
+        assert_eq!(link_anchor("[[Projects"), None);
+        assert_eq!(link_anchor("[[Projects|Alias#"), None);
+        assert_eq!(link_anchor("[[Projects#Shed]] and #"), None);
+    }
+
+    #[test]
+    fn test_link_target() {
+        assert_eq!(link_target("[["), Some(""));
+        assert_eq!(link_target("[[a]] [[Garden/Sh"), Some("Garden/Sh"));
+        assert_eq!(link_target("[[Projects]] and"), None);
+        assert_eq!(link_target("[[Projects|Al"), None);
+        assert_eq!(link_target("[Projects"), None);
+    }
+
+    #[test]
+    fn test_embed_target() {
+        assert_eq!(embed_target("![["), Some(""));
+        assert_eq!(embed_target("![[a.png]] ![[Garden/Sh"), Some("Garden/Sh"));
+        assert_eq!(embed_target("![[a.png]] [[Sh"), None);
+        assert_eq!(embed_target("![[Shed.png|300"), None);
+    }
+
+    #[test]
+    fn test_tag_prefix() {
+        assert_eq!(tag_prefix("#pro"), Some("pro"));
+        assert_eq!(tag_prefix("Text #a and #b-c"), Some("b-c"));
+        assert_eq!(tag_prefix("# Heading"), None);
+        assert_eq!(tag_prefix("#2024"), None);
+        assert_eq!(tag_prefix("[[Note#Head"), None);
+    }
+
+    #[test]
+    fn test_completion() {
+        let candidates = ["Shed".to_string(), "Pond".into(), "Garden shed".into()];
+
+        let completion = Completion::new("sh", &candidates).unwrap();
+        assert_eq!(completion.items(), ["Shed", "Garden shed"]);
+        assert_eq!(completion.typed_len(), 2);
+
+        let mut completion = Completion::new("", &candidates).unwrap();
+        completion.down();
+        completion.down();
+        completion.down();
+        assert_eq!(completion.selected(), "Garden shed");
+
+        assert_eq!(Completion::new("Shed", &candidates[..1]), None);
+        assert_eq!(Completion::new("roof", &candidates), None);
+    }
+}
@@ -37,29 +37,299 @@
 //! ┃ society.
 //! ┃
 //! ┃ - Doug Engelbart, 1961
-use std::marker::PhantomData;
+use std::{iter, marker::PhantomData, ops::Range};
 
 use ratatui::{
     buffer::Buffer,
-    layout::{Offset, Rect},
+    layout::{Alignment, Offset, Rect},
     style::{Color, Modifier, Style, Stylize},
     text::{Line, Span},
-    widgets::{
-        self, Block, BorderType, Clear, Padding, Paragraph, ScrollbarOrientation, StatefulWidget,
-        Widget,
-    },
+    widgets::{self, Clear, Padding, Paragraph, ScrollbarOrientation, StatefulWidget, Widget},
 };
 
-use crate::stylized_text::{stylize, FontStyle};
+use serde::Deserialize;
 
-use super::{markdown_parser, state::Mode};
+use crate::{
+    chrome::Chrome,
+    stylized_text::{stylize, FontStyle},
+};
+
+use super::{bidi, markdown_parser, query_block, state::Mode, CompletionPopup};
+
+/// Columns of the minimap: a gap to the text, the viewport marker and the map.
+const MINIMAP_WIDTH: u16 = 3;
 
 use super::state::EditorState;
 
+/// Line number gutter shown on the left side of the editor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineNumbers {
+    #[default]
+    None,
+    /// Source line numbers of the note.
+    Absolute,
+    /// Distance to the cursor line in source lines. The cursor line shows the absolute number.
+    Relative,
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
-pub struct Editor<'text_buffer>(PhantomData<&'text_buffer ()>);
+pub struct Editor<'text_buffer> {
+    dim_inactive: bool,
+    line_numbers: LineNumbers,
+    line_numbers_in_read_mode: bool,
+    read_mode_max_width: Option<u16>,
+    detect_direction: bool,
+    sticky_header: bool,
+    minimap: bool,
+    scroll_off: Option<usize>,
+    chrome: Chrome,
+    _lifetime: PhantomData<&'text_buffer ()>,
+}
 
 impl Editor<'_> {
+    /// Sets the borders, padding and title alignment of the pane. In read mode with a maximum
+    /// width the padding grows to center the text.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome, ..self }
+    }
+
+    /// Dims all nodes except the node under the cursor.
+    pub fn dim_inactive(self, dim_inactive: bool) -> Self {
+        Self {
+            dim_inactive,
+            ..self
+        }
+    }
+
+    /// Shows line numbers in view and edit modes, and in read mode if `in_read_mode` is set.
+    pub fn line_numbers(self, line_numbers: LineNumbers, in_read_mode: bool) -> Self {
+        Self {
+            line_numbers,
+            line_numbers_in_read_mode: in_read_mode,
+            ..self
+        }
+    }
+
+    /// Caps the width of the note text in read mode and centers the text in the editor.
+    pub fn read_mode_max_width(self, max_width: Option<u16>) -> Self {
+        Self {
+            read_mode_max_width: max_width,
+            ..self
+        }
+    }
+
+    /// Detects the direction of each node in read mode, so that right-to-left text is shown in
+    /// reading order and aligned to the right.
+    pub fn detect_direction(self, detect_direction: bool) -> Self {
+        Self {
+            detect_direction,
+            ..self
+        }
+    }
+
+    /// Pins the nearest heading above the viewport to the top of the editor in read mode.
+    pub fn sticky_header(self, sticky_header: bool) -> Self {
+        Self {
+            sticky_header,
+            ..self
+        }
+    }
+
+    /// Shows a map of the headings and the text of the whole note on the right edge, with the
+    /// viewport marked, and how far the viewport is through the note in percents.
+    pub fn minimap(self, minimap: bool) -> Self {
+        Self { minimap, ..self }
+    }
+
+    /// Keeps the lines above and below the cursor visible when the cursor moves.
+    pub fn scroll_off(self, scroll_off: Option<usize>) -> Self {
+        Self { scroll_off, ..self }
+    }
+
+    /// Returns the lines of the whole note as rendered in read mode at the width.
+    pub fn note_lines(state: &mut EditorState, width: u16) -> Vec<Line<'static>> {
+        let area = Rect::new(0, 0, width, 0);
+        state.render_nodes(width, |node, result| match result {
+            Some(result) => query_block::render(result, width.into()),
+            None => Editor::render_markdown(node, area, Span::default()),
+        });
+
+        (0..state.nodes().len())
+            .flat_map(|index| state.rendered_lines(index).to_vec())
+            .collect()
+    }
+
+    /// Returns the scroll position that keeps `margin` lines visible above and below the cursor
+    /// line, or `None` if they are visible at the scroll position. The margin is at most half of
+    /// the viewport height.
+    fn scroll_off_position(
+        position: usize,
+        cursor_line: usize,
+        margin: usize,
+        height: usize,
+        total_height: usize,
+    ) -> Option<usize> {
+        let margin = margin.min(height.saturating_sub(1) / 2);
+
+        let top = cursor_line.saturating_sub(margin);
+        let below = (cursor_line + margin + 1).min(total_height.max(cursor_line + 1));
+        let bottom = below.saturating_sub(height);
+
+        if position > top {
+            Some(top)
+        } else if position < bottom {
+            Some(bottom)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the text of the nearest heading that starts above the `top` line offset.
+    fn sticky_heading(
+        nodes: &[markdown_parser::Node],
+        offsets: &[usize],
+        top: usize,
+    ) -> Option<String> {
+        nodes
+            .iter()
+            .zip(offsets)
+            .take_while(|(_, offset)| **offset < top)
+            .filter_map(|(node, _)| match &node.markdown_node {
+                markdown_parser::MarkdownNode::Heading { text, .. } => Some(text.into()),
+                _ => None,
+            })
+            .last()
+    }
+
+    /// Returns the rendered lines that the row of the minimap stands for, each of the `rows` an
+    /// equal share of the `total` lines.
+    fn minimap_lines(row: usize, rows: usize, total: usize) -> Range<usize> {
+        let start = (row * total / rows.max(1)).min(total);
+        let end = ((row + 1) * total / rows.max(1)).clamp(start + 1, total.max(start + 1));
+        start..end.min(total)
+    }
+
+    /// Returns the symbol of each of the `rows` of the minimap: `■` for rows with a heading, and
+    /// a shade of the share of non-empty lines otherwise. `filled` tells for every rendered line
+    /// whether it has text, and `headings` are the lines where headings start.
+    fn minimap_symbols(filled: &[bool], headings: &[usize], rows: usize) -> Vec<&'static str> {
+        (0..rows)
+            .map(|row| {
+                let lines = Editor::minimap_lines(row, rows, filled.len());
+                if headings.iter().any(|line| lines.contains(line)) {
+                    return "■";
+                }
+
+                let count = filled[lines.clone()]
+                    .iter()
+                    .filter(|filled| **filled)
+                    .count();
+                match (count * 3).div_ceil(lines.len().max(1)) {
+                    0 => " ",
+                    1 => "░",
+                    2 => "▒",
+                    _ => "▓",
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the line offset of each node in the rendered lines, followed by the total height.
+    fn node_offsets(heights: &[usize]) -> Vec<usize> {
+        iter::once(0)
+            .chain(heights.iter().scan(0, |offset, height| {
+                *offset += height;
+                Some(*offset)
+            }))
+            .collect()
+    }
+
+    /// Returns the range of nodes that have lines between the `top` and `bottom` line offsets.
+    fn visible_nodes(offsets: &[usize], top: usize, bottom: usize) -> Range<usize> {
+        let count = offsets.len().saturating_sub(1);
+        let start = offsets[1..].partition_point(|end| *end <= top);
+        let end = offsets[..count].partition_point(|start| *start < bottom);
+        start.min(end)..end
+    }
+
+    /// Returns the 1-based source line numbers where each node starts.
+    fn node_start_lines(content: &str, nodes: &[markdown_parser::Node]) -> Vec<usize> {
+        let mut line = 1;
+        let mut offset = 0;
+
+        nodes
+            .iter()
+            .map(|node| {
+                let start = node.source_range.start.clamp(offset, content.len());
+                line += content
+                    .get(offset..start)
+                    .map_or(0, |text| text.matches('\n').count());
+                offset = start;
+                line
+            })
+            .collect()
+    }
+
+    /// Prefixes the rendered lines with line numbers.
+    ///
+    /// The lines of the node that is being edited map one to one to source lines. Rendered
+    /// markdown does not, so only the first non-empty line of a rendered node is numbered.
+    /// Wrapped and decorative lines get an empty gutter, which keeps the text aligned.
+    fn with_line_numbers<'a>(
+        &self,
+        rendered_nodes: Vec<Vec<Line<'a>>>,
+        start_lines: &[usize],
+        edited: Option<(usize, usize)>,
+        cursor_line: usize,
+        gutter_width: usize,
+    ) -> Vec<Vec<Line<'a>>> {
+        let style = Style::default()
+            .fg(Color::DarkGray)
+            .bg(Color::Reset)
+            .remove_modifier(
+                Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED | Modifier::CROSSED_OUT,
+            );
+
+        let number = |line: usize| {
+            let number = match self.line_numbers {
+                LineNumbers::Relative if line != cursor_line => line.abs_diff(cursor_line),
+                _ => line,
+            };
+            Span::styled(format!("{number:>0$} ", gutter_width - 1), style)
+        };
+
+        let empty = Span::styled(" ".repeat(gutter_width), style);
+
+        rendered_nodes
+            .into_iter()
+            .enumerate()
+            .map(|(i, lines)| {
+                let start_line = start_lines.get(i).copied().unwrap_or_default();
+                let first_numbered = lines.iter().position(|line| line.width() > 0);
+                let edited_lines = edited.and_then(|(row, count)| (row == i).then_some(count));
+
+                lines
+                    .into_iter()
+                    .enumerate()
+                    .map(|(j, line)| {
+                        let gutter = match (edited_lines, first_numbered) {
+                            (Some(count), _) if j < count => number(start_line + j),
+                            (Some(_), _) => empty.clone(),
+                            (None, Some(first)) if first == j => number(start_line),
+                            _ => empty.clone(),
+                        };
+
+                        Line {
+                            spans: iter::once(gutter).chain(line.spans).collect(),
+                            ..line
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
     fn task<'a>(
         kind: markdown_parser::TaskListItemKind,
         content: Vec<Span<'a>>,
@@ -263,6 +533,17 @@ impl Editor<'_> {
                 })
                 .collect::<Vec<Line<'a>>>(),
 
+            // TODO: Render tables, HTML blocks and the like instead of the source text
+            markdown_parser::MarkdownNode::Raw { text } => String::from(text)
+                .lines()
+                .map(|line| Line::from(vec![prefix.clone(), Span::from(line.to_string())]))
+                .chain(if prefix.to_string().is_empty() {
+                    [Line::default()].to_vec()
+                } else {
+                    [].to_vec()
+                })
+                .collect::<Vec<_>>(),
+
             // TODO: Support callout block quote types
             markdown_parser::MarkdownNode::BlockQuote { nodes, .. } => nodes
                 .iter()
@@ -305,15 +586,28 @@ impl<'text_buffer> StatefulWidget for Editor<'text_buffer> {
             Mode::Edit => Color::Green,
             Mode::Read => Color::Red,
         };
-        let block = Block::bordered()
-            .border_type(if state.active() {
-                BorderType::Thick
-            } else {
-                BorderType::Rounded
-            })
+        // The text is centered by padding the block so that the text area is at most the
+        // maximum width.
+        let padding = self.chrome.padding(Padding::horizontal(1)).left;
+        let horizontal_padding = match self.read_mode_max_width {
+            Some(max_width) if state.mode == Mode::Read => {
+                let width = area.width.saturating_sub(2 + 2 * padding);
+                padding + width.saturating_sub(max_width) / 2
+            }
+            _ => padding,
+        };
+
+        let block = self
+            .chrome
+            .block(state.active())
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
             .title_bottom(
                 [
                     format!(" {}", state.mode).fg(mode_color).bold().italic(),
+                    match state.read_only() {
+                        Some(_) => " READ-ONLY".dark_gray().italic(),
+                        None => "".into(),
+                    },
                     if state.modified {
                         "* ".bold().italic()
                     } else {
@@ -322,16 +616,88 @@ impl<'text_buffer> StatefulWidget for Editor<'text_buffer> {
                 ]
                 .to_vec(),
             )
-            .padding(Padding::horizontal(1));
+            .padding(Padding::horizontal(horizontal_padding));
 
-        let inner_area = block.inner(area);
+        let show_line_numbers = self.line_numbers != LineNumbers::None
+            && (state.mode != Mode::Read || self.line_numbers_in_read_mode);
+
+        let gutter_width = if show_line_numbers {
+            state.content().lines().count().max(1).to_string().len() + 1
+        } else {
+            0
+        };
 
-        let nodes = state.nodes();
+        let block_inner_area = block.inner(area);
 
-        let rendered_nodes: Vec<_> = nodes
-            .iter()
-            .enumerate()
-            .map(|(i, node)| {
+        let minimap_width = match self.minimap {
+            true => MINIMAP_WIDTH.min(block_inner_area.width.saturating_sub(1)),
+            false => 0,
+        };
+
+        // Nodes are rendered next to the line number gutter, and left of the minimap.
+        let inner_area = Rect {
+            x: block_inner_area.x + gutter_width as u16,
+            width: block_inner_area
+                .width
+                .saturating_sub(gutter_width as u16 + minimap_width),
+            ..block_inner_area
+        };
+
+        let mut heights = state.render_nodes(inner_area.width, |node, result| match result {
+            Some(result) => query_block::render(result, inner_area.width.into()),
+            None => Editor::render_markdown(node, inner_area, Span::default()),
+        });
+
+        // Outside read mode the current node shows the lines of the text buffer, which can be
+        // more than the rendered lines.
+        if state.mode != Mode::Read {
+            let buffer_height = state.text_buffer().lines().len();
+            if let Some(height) = heights.get_mut(state.current_row) {
+                *height = (*height).max(buffer_height);
+            }
+        }
+
+        let offsets = Editor::node_offsets(&heights);
+        let total_height = offsets.last().copied().unwrap_or_default();
+
+        let offset_row = offsets.get(state.current_row).copied().unwrap_or_default();
+
+        let current_node_height = heights
+            .get(state.current_row)
+            .map_or(0, |height| *height as u16);
+
+        if state.take_scroll_to_current_row() {
+            state.set_scroll_position(offset_row);
+        }
+
+        if let (true, Some(margin)) = (state.take_cursor_moved(), self.scroll_off) {
+            let (row, _) = state.text_buffer().cursor();
+            let cursor_line =
+                offset_row + row.min(usize::from(current_node_height).saturating_sub(1));
+
+            if let Some(position) = Editor::scroll_off_position(
+                state.scrollbar().position,
+                cursor_line,
+                margin,
+                usize::from(inner_area.height),
+                total_height,
+            ) {
+                state.set_scroll_position(position);
+            }
+        }
+
+        // Only the nodes within a viewport height above and below the viewport are rendered.
+        let scroll_position = state.scrollbar().position;
+        let margin = usize::from(inner_area.height);
+        let visible = Editor::visible_nodes(
+            &offsets,
+            scroll_position.saturating_sub(margin),
+            scroll_position + 2 * margin,
+        );
+
+        let rendered_nodes: Vec<_> = visible
+            .clone()
+            .map(|i| {
                 // TODO: Figure out how to wrap the text while editing / viewing the markdown
                 // blocks.
                 //
@@ -355,18 +721,20 @@ impl<'text_buffer> StatefulWidget for Editor<'text_buffer> {
                 //     },
                 // ));
 
+                let lines = state.rendered_lines(i);
+
                 match (i == state.current_row, &state.mode) {
                     (true, Mode::Read) => {
                         let (row, _) = state.text_buffer().cursor();
-                        Editor::render_markdown(node, inner_area, Span::default())
-                            .into_iter()
+                        lines
+                            .iter()
+                            .cloned()
                             .enumerate()
                             .map(|(i, line)| if i == row { line.underlined() } else { line })
                             .collect()
                     }
                     (true, _) => {
-                        let expected_line_count =
-                            Editor::render_markdown(node, inner_area, Span::default()).len();
+                        let expected_line_count = lines.len();
 
                         let mut buffer_lines: Vec<Line> = state
                             .text_buffer()
@@ -381,23 +749,56 @@ impl<'text_buffer> StatefulWidget for Editor<'text_buffer> {
 
                         buffer_lines
                     }
-                    (false, _) => Editor::render_markdown(node, inner_area, Span::default()),
+                    (false, _) if self.dim_inactive => lines
+                        .iter()
+                        .cloned()
+                        .map(|line| line.add_modifier(Modifier::DIM))
+                        .collect(),
+                    (false, _) => lines.to_vec(),
                 }
             })
             .collect();
 
-        let offset_row = if !rendered_nodes.is_empty() {
-            rendered_nodes[..state.current_row]
-                .iter()
-                .map(|lines| lines.len())
-                .sum::<usize>()
-        } else {
-            0
+        // Right-to-left text is only reordered in read mode, as the other modes show the source
+        // of the current node in the order it is edited.
+        let rendered_nodes: Vec<_> = match self.detect_direction && state.mode == Mode::Read {
+            true => rendered_nodes
+                .into_iter()
+                .map(|lines| bidi::reorder(lines, inner_area.width))
+                .collect(),
+            false => rendered_nodes,
         };
 
-        let current_node_height = rendered_nodes
-            .get(state.current_row)
-            .map_or(0, |lines| lines.len() as u16);
+        // Changed nodes are marked down to their last non-empty line, leaving out the blank line
+        // between blocks.
+        let changed: Vec<_> = visible
+            .clone()
+            .zip(&rendered_nodes)
+            .filter(|(i, _)| state.is_node_changed(*i))
+            .map(|(i, lines)| {
+                let height = lines.iter().rposition(|line| line.width() > 0);
+                (i, height.map_or(1, |last| last + 1))
+            })
+            .collect();
+
+        let rendered_nodes = if show_line_numbers {
+            let start_lines = Editor::node_start_lines(state.content(), state.nodes());
+            let edited = state
+                .is_editing()
+                .then(|| (state.current_row, state.text_buffer().lines().len()));
+            let cursor_line = start_lines.get(state.current_row).copied().unwrap_or(1)
+                + edited.map_or(0, |_| state.text_buffer().cursor().0);
+
+            self.with_line_numbers(
+                rendered_nodes,
+                &start_lines[visible.clone()],
+                edited.and_then(|(row, count)| Some((row.checked_sub(visible.start)?, count))),
+                cursor_line,
+                gutter_width,
+            )
+        } else {
+            rendered_nodes
+        };
 
         fn calculate_clipped_rows(offset: i16, pos_y: u16, height: u16, max: u16) -> u16 {
             if offset < 0 {
@@ -438,16 +839,89 @@ impl<'text_buffer> StatefulWidget for Editor<'text_buffer> {
         })
         .clamp(inner_area);
 
-        let r = rendered_nodes.into_iter().flatten().collect::<Vec<_>>();
-        let r_len = r.len();
-        let mut scroll_state = scrollbar.state.content_length(r.len());
+        let lines = rendered_nodes.into_iter().flatten().collect::<Vec<_>>();
+        let mut scroll_state = scrollbar.state.content_length(total_height);
+
+        let overflows = total_height > usize::from(block_inner_area.height);
+
+        let block = match self.minimap && overflows {
+            true => {
+                let read =
+                    (scrollbar.position + usize::from(block_inner_area.height)).min(total_height);
+                block.title_bottom(
+                    Line::from(format!(" {}% ", read * 100 / total_height)).right_aligned(),
+                )
+            }
+            false => block,
+        };
 
-        let root_node = Paragraph::new(r)
+        let root_node = Paragraph::new(lines)
             .block(block)
-            .scroll((scrollbar.position as u16, 0));
+            .scroll(((scrollbar.position - offsets[visible.start]) as u16, 0));
 
         Widget::render(root_node, area, buf);
 
+        // The change bars are drawn in the left padding, so that the text stays in place.
+        if horizontal_padding > 0 {
+            let x = block_inner_area.x - 1;
+            let rows =
+                scrollbar.position..scrollbar.position + usize::from(block_inner_area.height);
+
+            for (i, height) in changed {
+                for line in offsets[i]..offsets[i] + height {
+                    if rows.contains(&line) {
+                        let y = block_inner_area.y + (line - scrollbar.position) as u16;
+                        buf[(x, y)].set_symbol("▎").set_fg(Color::Yellow);
+                    }
+                }
+            }
+        }
+
+        if let Some(heading) = Editor::sticky_heading(state.nodes(), &offsets, scrollbar.position)
+            .filter(|_| self.sticky_header && state.mode == Mode::Read)
+        {
+            let header = Rect {
+                height: block_inner_area.height.min(1),
+                ..block_inner_area
+            };
+            Clear.render(header, buf);
+            Line::from(heading).bold().underlined().render(header, buf);
+        }
+
+        if minimap_width == MINIMAP_WIDTH && overflows {
+            let (filled, headings) = state.nodes().iter().enumerate().fold(
+                (Vec::with_capacity(total_height), vec![]),
+                |(mut filled, mut headings), (i, node)| {
+                    if let markdown_parser::MarkdownNode::Heading { .. } = node.markdown_node {
+                        headings.push(offsets[i]);
+                    }
+
+                    let lines = state.rendered_lines(i);
+                    filled.extend(
+                        (0..heights[i]).map(|line| lines.get(line).is_some_and(|l| l.width() > 0)),
+                    );
+                    (filled, headings)
+                },
+            );
+
+            let rows = usize::from(block_inner_area.height);
+            let viewport = scrollbar.position..scrollbar.position + rows;
+            let x = block_inner_area.right() - 2;
+
+            for (row, symbol) in Editor::minimap_symbols(&filled, &headings, rows)
+                .into_iter()
+                .enumerate()
+            {
+                let y = block_inner_area.y + row as u16;
+                let lines = Editor::minimap_lines(row, rows, total_height);
+
+                if lines.start < viewport.end && viewport.start < lines.end {
+                    buf[(x, y)].set_symbol("┃").set_fg(Color::Cyan);
+                }
+                buf[(x + 1, y)].set_symbol(symbol).set_fg(Color::DarkGray);
+            }
+        }
+
         // TODO: Investigate why crash happens when complete node is rendered
         if rect.top() < max_height && state.mode != Mode::Read {
             // Nothing is visible, so we exit early
@@ -455,8 +929,9 @@ impl<'text_buffer> StatefulWidget for Editor<'text_buffer> {
                 return;
             }
 
-            let buffer = state.text_buffer_as_mut();
-            let textarea = buffer.textarea_as_mut();
+            // The clipped text area moves its cursor, so a copy is rendered to keep the cursor
+            // of the state where it is.
+            let mut textarea = state.text_buffer().textarea().clone();
 
             if vertical_offset > 0 && clipped_rows != 0 {
                 let (row, col) = textarea.cursor();
@@ -487,9 +962,19 @@ impl<'text_buffer> StatefulWidget for Editor<'text_buffer> {
 
             Clear.render(rect, buf);
             textarea.render(rect, buf);
+
+            if let Some(completion) = state.completion() {
+                let (row, col) = state.text_buffer().cursor();
+                let cursor = (
+                    rect.x + col as u16,
+                    rect.y + (row as u16).min(rect.height.saturating_sub(1)),
+                );
+
+                CompletionPopup { completion, cursor }.render(inner_area, buf);
+            }
         }
 
-        if r_len as u16 > inner_area.height {
+        if overflows {
             StatefulWidget::render(
                 widgets::Scrollbar::new(ScrollbarOrientation::VerticalRight),
                 area,
@@ -726,4 +1211,299 @@ mod tests {
             assert_snapshot!(name, terminal.backend());
         });
     }
+
+    #[test]
+    fn test_rendered_line_numbers() {
+        let content = indoc! { r#"## Line numbers
+
+            A paragraph that is long enough to be wrapped to the next line in the rendered view.
+
+            - First list item
+            - Second list item
+            "#};
+
+        let tests = [
+            ("line_numbers_absolute_edit_mode", LineNumbers::Absolute, {
+                let mut state = EditorState::default();
+                state.set_content(content);
+                state.cursor_down();
+                state.set_mode(Mode::Edit);
+                state
+            }),
+            ("line_numbers_relative_view_mode", LineNumbers::Relative, {
+                let mut state = EditorState::default();
+                state.set_content(content);
+                state.cursor_down();
+                state.cursor_down();
+                state.set_mode(Mode::View);
+                state
+            }),
+        ];
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 14)).unwrap();
+
+        tests
+            .into_iter()
+            .for_each(|(name, line_numbers, mut state)| {
+                _ = terminal.clear();
+                terminal
+                    .draw(|frame| {
+                        Editor::default().line_numbers(line_numbers, false).render(
+                            frame.area(),
+                            frame.buffer_mut(),
+                            &mut state,
+                        )
+                    })
+                    .unwrap();
+                assert_snapshot!(name, terminal.backend());
+            });
+    }
+
+    #[test]
+    fn test_rendered_read_mode_max_width() {
+        let mut state = EditorState::default();
+        state.set_content("A paragraph that is wrapped to the capped width and centered.");
+        state.set_mode(Mode::Read);
+
+        let mut terminal = Terminal::new(TestBackend::new(50, 8)).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Editor::default().read_mode_max_width(Some(20)).render(
+                    frame.area(),
+                    frame.buffer_mut(),
+                    &mut state,
+                )
+            })
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn test_rendered_sticky_header() {
+        let mut state = EditorState::default();
+        state.set_content(indoc! {"
+            ## Section
+
+            First paragraph of the section.
+
+            Second paragraph of the section.
+
+            Third paragraph of the section.
+        "});
+        state.set_mode(Mode::Read);
+        state.set_scroll_position(4);
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 6)).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Editor::default().sticky_header(true).render(
+                    frame.area(),
+                    frame.buffer_mut(),
+                    &mut state,
+                )
+            })
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn test_minimap_symbols() {
+        let filled = [true, true, false, false, true, false, false, false];
+
+        assert_eq!(
+            Editor::minimap_symbols(&filled, &[0], 4),
+            ["■", " ", "▒", " "]
+        );
+        assert_eq!(Editor::minimap_symbols(&filled, &[], 2), ["▒", "░"]);
+        assert_eq!(Editor::minimap_lines(2, 3, 8), 5..8);
+    }
+
+    #[test]
+    fn test_rendered_minimap() {
+        let mut state = EditorState::default();
+        state.set_content(indoc! {"
+            ## Pond
+
+            Frogs and toads.
+
+            Koi and carp.
+
+            ## Shed
+
+            Paint the door.
+
+            Fix the roof.
+        "});
+        state.set_mode(Mode::Read);
+        state.set_scroll_position(6);
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 8)).unwrap();
+
+        terminal
+            .draw(|frame| {
+                Editor::default()
+                    .minimap(true)
+                    .render(frame.area(), frame.buffer_mut(), &mut state)
+            })
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn test_rendered_virtualized_scroll() {
+        let content = (1..=40)
+            .map(|i| match i % 3 {
+                0 => format!("## Heading {i}"),
+                1 => format!("Paragraph {i} is long enough to be wrapped over a few lines."),
+                _ => format!("- Item {i}\n- Item {i} again"),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let mut state = EditorState::default();
+        state.set_content(&content);
+        state.set_mode(Mode::Read);
+
+        let rows = |terminal: &Terminal<TestBackend>, height: u16| -> Vec<String> {
+            let buffer = terminal.backend().buffer();
+            (1..height - 1)
+                .map(|y| {
+                    (1..buffer.area.width - 1)
+                        .map(|x| buffer[(x, y)].symbol())
+                        .collect()
+                })
+                .collect()
+        };
+
+        let draw = |terminal: &mut Terminal<TestBackend>, state: &mut EditorState| {
+            terminal
+                .draw(|frame| Editor::default().render(frame.area(), frame.buffer_mut(), state))
+                .unwrap();
+        };
+
+        // A viewport tall enough for the whole note renders every node.
+        let mut full = Terminal::new(TestBackend::new(30, 300)).unwrap();
+        draw(&mut full, &mut state.clone());
+        let full_rows = rows(&full, 300);
+        let total = full_rows
+            .iter()
+            .rposition(|row| !row.trim().is_empty())
+            .unwrap()
+            + 1;
+
+        let mut terminal = Terminal::new(TestBackend::new(30, 8)).unwrap();
+        draw(&mut terminal, &mut state);
+
+        // Every position crosses the node boundaries at the top and bottom of the viewport.
+        (0..=total).for_each(|position| {
+            state.set_scroll_position(position);
+            draw(&mut terminal, &mut state);
+
+            let expected: Vec<_> = (position..position + 6)
+                .map(|row| {
+                    full_rows
+                        .get(row)
+                        .cloned()
+                        .unwrap_or_else(|| " ".repeat(28))
+                })
+                .collect();
+            assert_eq!(rows(&terminal, 8), expected, "scroll position {position}");
+        });
+    }
+
+    #[test]
+    fn test_scroll_off_position() {
+        let tests = [
+            // position, cursor line, margin, height, total height, expected
+            (0, 0, 2, 6, 20, None),
+            (0, 3, 2, 6, 20, None),
+            (0, 4, 2, 6, 20, Some(1)),
+            (10, 11, 2, 6, 20, Some(9)),
+            (10, 12, 2, 6, 20, None),
+            (10, 19, 2, 6, 20, Some(14)),
+            (0, 5, 10, 6, 20, Some(2)),
+        ];
+
+        tests.into_iter().for_each(
+            |(position, cursor_line, margin, height, total_height, expected)| {
+                assert_eq!(
+                    Editor::scroll_off_position(
+                        position,
+                        cursor_line,
+                        margin,
+                        height,
+                        total_height
+                    ),
+                    expected,
+                    "cursor line {cursor_line} at {position}"
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_rendered_scroll_off() {
+        let content = (1..=20)
+            .map(|i| format!("Paragraph {i}"))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let draw = |scroll_off: Option<usize>| {
+            let mut state = EditorState::default();
+            state.set_content(&content);
+            state.set_mode(Mode::View);
+
+            let mut terminal = Terminal::new(TestBackend::new(30, 8)).unwrap();
+            (0..3).for_each(|_| {
+                state.cursor_down();
+                terminal
+                    .draw(|frame| {
+                        Editor::default().scroll_off(scroll_off).render(
+                            frame.area(),
+                            frame.buffer_mut(),
+                            &mut state,
+                        )
+                    })
+                    .unwrap();
+            });
+
+            state.scrollbar().position
+        };
+
+        assert_eq!(draw(None), 0);
+        assert!(draw(Some(2)) > 0);
+    }
+
+    #[test]
+    fn test_render_cache() {
+        let mut state = EditorState::default();
+        state.set_content("# Heading\n\nParagraph\n\n- Item");
+
+        let rendered = std::cell::Cell::new(0);
+        let render = |node: &markdown_parser::Node, _: Option<&query_block::QueryResult>| {
+            rendered.set(rendered.get() + 1);
+            Editor::render_markdown(node, Rect::new(0, 0, 20, 1), Span::default())
+        };
+
+        assert_eq!(state.render_nodes(20, render), vec![4, 2, 2]);
+        assert_eq!(rendered.get(), 3);
+
+        state.render_nodes(20, render);
+        assert_eq!(rendered.get(), 3);
+        assert!(state.rendered_lines(1)[0]
+            .to_string()
+            .starts_with("Paragraph"));
+
+        state.set_content("# Heading\n\nEdited paragraph\n\n- Item");
+        state.render_nodes(20, render);
+        assert_eq!(rendered.get(), 4);
+        assert!(state.rendered_lines(1)[0]
+            .to_string()
+            .starts_with("Edited paragraph"));
+
+        state.render_nodes(30, render);
+        assert_eq!(rendered.get(), 7);
+    }
 }
@@ -43,19 +43,26 @@
 //! with a new one. When an event indicates the end of that structure, the node is finalized
 //! and pushed into [`Parser::output`].
 //!
-//! Unrecognized events (such as [`InlineHtml`](pulldown_cmark::Event::InlineHtml)) are simply
-//! ignored for the time being.
+//! Block structures that have no node of their own yet, such as tables, HTML blocks, thematic
+//! breaks and front matter, become [`MarkdownNode::Raw`] nodes that keep the source text as is.
+//! Text that produces no events at all, e.g. link reference definitions, is covered with raw nodes
+//! too, so every non-whitespace character of the source belongs to a top-level node and malformed
+//! input can be edited and saved without losing anything. Unrecognized inline events (such as
+//! [`InlineHtml`](pulldown_cmark::Event::InlineHtml)) are ignored for the time being.
 //!
 //! ## Not yet implemented
 //!
-//! - Handling of inline HTML, math blocks, etc.
-//! - Tracking code block language (`lang`) properly (currently set to [`None`]).
-use std::{iter::Peekable, vec::IntoIter};
+//! - Rendering of tables, inline HTML, math blocks, etc.
+use std::{
+    hash::{Hash, Hasher},
+    iter::Peekable,
+    vec::IntoIter,
+};
 
-use pulldown_cmark::{Event, Options, Tag, TagEnd};
+use pulldown_cmark::{CodeBlockKind, Event, Options, Tag, TagEnd};
 
 /// A style that can be applied to [`TextNode`] (code, emphasis, strikethrough, strong).
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Style {
     /// Inline code style (e.g. `code`).
     Code,
@@ -70,7 +77,7 @@ pub enum Style {
 }
 
 /// Represents the variant of a list or task item (checked, unchecked, etc.).
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ItemKind {
     // An ordered list item (e.g., `1. item`), storing the numeric index.
     Ordered(u64),
@@ -79,7 +86,7 @@ pub enum ItemKind {
 }
 
 /// Represents the variant of a list or task item (checked, unchecked, etc.).
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TaskListItemKind {
     /// A checkbox item that is marked as done using `- [x]`.
     Checked,
@@ -90,7 +97,7 @@ pub enum TaskListItemKind {
     LooselyChecked,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 #[allow(missing_docs)]
 pub enum HeadingLevel {
     H1 = 1,
@@ -118,7 +125,7 @@ impl From<pulldown_cmark::HeadingLevel> for HeadingLevel {
 ///
 /// Currently, the underlying [`pulldown_cmark`] parser distinguishes these via syntax like `">
 /// [!NOTE] Some note"`.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
 pub enum BlockQuoteKind {
     Note,
@@ -141,7 +148,7 @@ impl From<pulldown_cmark::BlockQuoteKind> for BlockQuoteKind {
 }
 
 /// Denotes whether a list is ordered or unordered.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ListKind {
     /// An ordered list item (e.g., `1. item`), storing the numeric index.
     Ordered(u64),
@@ -155,7 +162,7 @@ pub enum ListKind {
 ///
 /// Usually styled text will be contained in a single [`TextNode`] with the given [`Style`]
 /// property.
-#[derive(Clone, Debug, PartialEq, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
 pub struct TextNode {
     /// The literal text content.
     pub content: String,
@@ -186,7 +193,7 @@ impl TextNode {
 }
 
 /// A wrapper type holding a list of [`TextNode`]s.
-#[derive(Clone, Debug, PartialEq, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
 pub struct Text(Vec<TextNode>);
 
 impl From<&Text> for String {
@@ -286,7 +293,7 @@ pub type Range<Idx> = std::ops::Range<Idx>;
 /// assert_eq!(node.markdown_node, MarkdownNode::Paragraph { text: Text::default() });
 /// assert_eq!(node.source_range, Range { start: 0, end: 10 });
 /// ```
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Node {
     /// The specific Markdown node represented by this node.
     pub markdown_node: MarkdownNode,
@@ -295,6 +302,14 @@ pub struct Node {
     pub source_range: Range<usize>,
 }
 
+/// The source range is not hashed, so nodes with the same content hash the same wherever they
+/// are in the source text.
+impl Hash for Node {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.markdown_node.hash(state);
+    }
+}
+
 impl Node {
     /// Creates a new `Node` from the provided [`MarkdownNode`] and source range.
     pub fn new(markdown_node: MarkdownNode, source_range: Range<usize>) -> Self {
@@ -316,6 +331,7 @@ impl Node {
             | MarkdownNode::CodeBlock { text, .. }
             | MarkdownNode::TaskListItem { text, .. }
             | MarkdownNode::Item { text, .. } => text.push(node),
+            MarkdownNode::Raw { .. } => {}
             MarkdownNode::List { nodes, .. } | MarkdownNode::BlockQuote { nodes, .. } => {
                 if let Some(last_node) = nodes.last_mut() {
                     last_node.push_text_node(node);
@@ -326,7 +342,7 @@ impl Node {
 }
 
 /// The Markdown AST node enumeration.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[allow(missing_docs)]
 pub enum MarkdownNode {
     /// A heading node that represents different heading levels.
@@ -378,6 +394,12 @@ pub enum MarkdownNode {
         kind: TaskListItemKind,
         text: Text,
     },
+
+    /// A block without a dedicated node, e.g. a table or an HTML block, with the source text kept
+    /// verbatim.
+    Raw {
+        text: Text,
+    },
 }
 
 /// Returns `true` if the [`Tag`] should be closed upon encountering the given [`TagEnd`].
@@ -452,15 +474,58 @@ pub fn from_str(text: &str) -> Vec<Node> {
 ///   },
 /// ])
 /// ```
-pub struct Parser<'a>(pulldown_cmark::TextMergeWithOffset<'a, pulldown_cmark::OffsetIter<'a>>);
+pub struct Parser<'a> {
+    source: &'a str,
+    events: pulldown_cmark::TextMergeWithOffset<'a, pulldown_cmark::OffsetIter<'a>>,
+}
 
 impl<'a> Iterator for Parser<'a> {
     type Item = (Event<'a>, Range<usize>);
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        self.events.next()
+    }
+}
+
+/// Returns a [`MarkdownNode::Raw`] node with the source text of the range, without the trailing
+/// line break.
+fn raw(source: &str, source_range: Range<usize>) -> Node {
+    let text = source
+        .get(source_range.clone())
+        .unwrap_or_default()
+        .trim_end_matches(['\n', '\r']);
+
+    Node::new(MarkdownNode::Raw { text: text.into() }, source_range)
+}
+
+/// Consumes the events up to and including the end of the tag that was just started.
+fn skip_tag<'a>(events: &mut impl Iterator<Item = (Event<'a>, Range<usize>)>) {
+    let mut depth = 0;
+
+    for (event, _) in events {
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) if depth == 0 => return,
+            Event::End(_) => depth -= 1,
+            _ => {}
+        }
     }
 }
 
+/// Returns the lines of the range that contain non-whitespace characters, from the start of the
+/// first line to the line break of the last one, or [`None`] if the range is blank.
+fn non_blank_lines(source: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let text = source.get(range.clone())?;
+    let first = text.find(|c: char| !c.is_whitespace())?;
+    let last = text.trim_end().len();
+
+    let start = text[..first].rfind('\n').map_or(0, |index| index + 1);
+    let end = text[last..]
+        .find('\n')
+        .map_or(text.len(), |index| last + index + 1);
+
+    Some(range.start + start..range.start + end)
+}
+
 impl<'a> Parser<'a> {
     /// Creates a new [`Parser`] from a Markdown input string.
     ///
@@ -473,10 +538,14 @@ impl<'a> Parser<'a> {
             pulldown_cmark::Parser::new_ext(text, Options::all()).into_offset_iter(),
         );
 
-        Self(parser)
+        Self {
+            source: text,
+            events: parser,
+        }
     }
 
     fn parse_tag(
+        source: &str,
         tag: Tag,
         events: &mut Peekable<Parser<'a>>,
         source_range: Range<usize>,
@@ -485,14 +554,14 @@ impl<'a> Parser<'a> {
             Tag::BlockQuote(kind) => Some(Node::new(
                 MarkdownNode::BlockQuote {
                     kind: kind.map(|kind| kind.into()),
-                    nodes: Parser::parse_events(events, Some(tag)),
+                    nodes: Parser::parse_events(source, events, Some(tag)),
                 },
                 source_range,
             )),
             Tag::List(start) => Some(Node::new(
                 MarkdownNode::List {
                     kind: start.map(ListKind::Ordered).unwrap_or(ListKind::Unordered),
-                    nodes: Parser::parse_events(events, Some(tag)),
+                    nodes: Parser::parse_events(source, events, Some(tag)),
                 },
                 source_range,
             )),
@@ -503,9 +572,14 @@ impl<'a> Parser<'a> {
                 },
                 source_range,
             )),
-            Tag::CodeBlock(_) => Some(Node::new(
+            Tag::CodeBlock(kind) => Some(Node::new(
                 MarkdownNode::CodeBlock {
-                    lang: None,
+                    lang: match kind {
+                        CodeBlockKind::Fenced(info) => {
+                            info.split_whitespace().next().map(|lang| lang.to_string())
+                        }
+                        CodeBlockKind::Indented => None,
+                    },
                     text: Text::default(),
                 },
                 source_range,
@@ -522,13 +596,20 @@ impl<'a> Parser<'a> {
                 },
                 source_range,
             )),
+            // Block tags without a node of their own. Their inner events would otherwise end up in
+            // the previous node, e.g. the cells of a table as its text.
+            Tag::HtmlBlock
+            | Tag::Table(_)
+            | Tag::FootnoteDefinition(_)
+            | Tag::MetadataBlock(_)
+            | Tag::DefinitionList => {
+                skip_tag(events);
+                Some(raw(source, source_range))
+            }
             // NOTE: After all tags have been implemented the Option wrapper can be removed.
             //
             // Missing tags:
             //
-            // | Tag::HtmlBlock
-            // | Tag::FootnoteDefinition(_)
-            // | Tag::Table(_)
             // | Tag::TableHead
             // | Tag::TableRow
             // | Tag::TableCell
@@ -537,8 +618,6 @@ impl<'a> Parser<'a> {
             // | Tag::Strikethrough
             // | Tag::Link { .. }
             // | Tag::Image { .. }
-            // | Tag::MetadataBlock(_)
-            // | Tag::DefinitionList
             // | Tag::DefinitionListTitle
             // | Tag::Subscript
             // | Tag::Superscript
@@ -547,14 +626,18 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_events(events: &mut Peekable<Parser<'a>>, current_tag: Option<Tag>) -> Vec<Node> {
+    fn parse_events(
+        source: &str,
+        events: &mut Peekable<Parser<'a>>,
+        current_tag: Option<Tag>,
+    ) -> Vec<Node> {
         let mut nodes = Vec::new();
 
         while let Some((event, range)) = events.peek().cloned() {
             events.next();
             match event {
                 Event::Start(tag) => {
-                    if let Some(node) = Parser::parse_tag(tag, events, range) {
+                    if let Some(node) = Parser::parse_tag(source, tag, events, range) {
                         nodes.push(node);
                     }
                 }
@@ -623,6 +706,7 @@ impl<'a> Parser<'a> {
                         }
                     }
                 }
+                Event::Rule => nodes.push(raw(source, range)),
                 // Missing events:
                 //
                 // | Event::InlineMath(_)
@@ -631,7 +715,6 @@ impl<'a> Parser<'a> {
                 // | Event::InlineHtml(_)
                 // | Event::SoftBreak
                 // | Event::HardBreak
-                // | Event::Rule
                 // | Event::FootnoteReference(_)
                 _ => {}
             }
@@ -661,7 +744,26 @@ impl<'a> Parser<'a> {
     /// ]);
     /// ```
     pub fn parse(self) -> Vec<Node> {
-        Parser::parse_events(&mut self.peekable(), None)
+        let source = self.source;
+        let nodes = Parser::parse_events(source, &mut self.peekable(), None);
+
+        // Text between the nodes that produced no events, e.g. a link reference definition.
+        let mut offset = 0;
+        let mut output = Vec::with_capacity(nodes.len());
+
+        for node in nodes {
+            if let Some(range) = non_blank_lines(source, offset..node.source_range.start) {
+                output.push(raw(source, range));
+            }
+            offset = offset.max(node.source_range.end);
+            output.push(node);
+        }
+
+        if let Some(range) = non_blank_lines(source, offset..source.len()) {
+            output.push(raw(source, range));
+        }
+
+        output
     }
 }
 
@@ -669,6 +771,8 @@ impl<'a> Parser<'a> {
 mod tests {
     use indoc::indoc;
 
+    use crate::testing;
+
     fn p(str: &str, range: Range<usize>) -> Node {
         Node::new(MarkdownNode::Paragraph { text: str.into() }, range)
     }
@@ -715,6 +819,10 @@ mod tests {
         )
     }
 
+    fn raw(str: &str, range: Range<usize>) -> Node {
+        Node::new(MarkdownNode::Raw { text: str.into() }, range)
+    }
+
     fn heading(level: HeadingLevel, str: &str, range: Range<usize>) -> Node {
         Node::new(
             MarkdownNode::Heading {
@@ -830,10 +938,117 @@ mod tests {
                     ),
                 ],
             ),
+            (
+                indoc! {r#"Text
+
+                | a | b |
+                |---|---|
+                | 1 | 2 |
+
+                ---
+
+                <div>
+                Hi
+                </div>
+
+                [link]: https://example.com
+                "#},
+                vec![
+                    p("Text", 0..5),
+                    raw("| a | b |\n|---|---|\n| 1 | 2 |", 6..36),
+                    raw("---", 37..41),
+                    raw("<div>\nHi\n</div>", 42..58),
+                    raw("[link]: https://example.com", 59..87),
+                ],
+            ),
+            (
+                "---\r\ntags: [a]\r\n---\r\n\r\n# Heading\r\n",
+                vec![
+                    raw("---\r\ntags: [a]\r\n---", 0..19),
+                    h1("Heading", 23..34),
+                ],
+            ),
         ];
 
         tests
             .iter()
             .for_each(|test| assert_eq!(from_str(test.0), test.1));
     }
+
+    /// Asserts that the ranges of the nodes are valid in the source and within the parent range.
+    fn assert_ranges(source: &str, nodes: &[Node], parent: &Range<usize>) {
+        nodes.iter().for_each(|node| {
+            let range = &node.source_range;
+            assert!(
+                source.get(range.clone()).is_some(),
+                "{range:?} is not valid in {source:?}"
+            );
+            assert!(
+                parent.start <= range.start && range.end <= parent.end,
+                "{range:?} is not within {parent:?} in {source:?}"
+            );
+
+            if let MarkdownNode::BlockQuote { nodes, .. } | MarkdownNode::List { nodes, .. } =
+                &node.markdown_node
+            {
+                assert_ranges(source, nodes, range);
+            }
+        });
+    }
+
+    #[test]
+    fn test_parse_fuzz() {
+        const FRAGMENTS: [&str; 24] = [
+            "#",
+            "## ",
+            "- ",
+            "1. ",
+            "[ ]",
+            "[x] ",
+            "> ",
+            "> [!NOTE]",
+            "```",
+            "~~~",
+            "$$",
+            "---",
+            "|a|b|\n|-|-|\n",
+            "<div>",
+            "</div>",
+            "[a]: x",
+            "[^1]: note",
+            "*",
+            "`",
+            "é",
+            "\t",
+            "\n",
+            "\r\n",
+            "word ",
+        ];
+
+        let mut next = testing::xorshift(0x2545_f491_4f6c_dd1d);
+
+        (0..2000).for_each(|_| {
+            let mut source = String::new();
+            (0..next() % 40).for_each(|_| match next() % 8 {
+                // Arbitrary bytes are read as notes are, with invalid UTF-8 replaced.
+                0 => source.push_str(&String::from_utf8_lossy(&next().to_le_bytes())),
+                _ => source.push_str(FRAGMENTS[next() % FRAGMENTS.len()]),
+            });
+
+            let nodes = from_str(&source);
+            assert_ranges(&source, &nodes, &(0..source.len()));
+
+            let mut covered = vec![false; source.len()];
+            nodes
+                .iter()
+                .for_each(|node| covered[node.source_range.clone()].fill(true));
+
+            source.char_indices().for_each(|(index, c)| {
+                assert!(
+                    c.is_whitespace() || covered[index],
+                    "{c:?} at {index} is not in a node in {source:?}"
+                );
+            });
+        });
+    }
 }
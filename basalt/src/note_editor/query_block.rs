@@ -0,0 +1,266 @@
+//! Query blocks, fenced code blocks in the `basalt-query` language, e.g.
+//!
+//! ````markdown
+//! ```basalt-query
+//! tag:#book status:reading
+//! ```
+//! ````
+//!
+//! are shown as the list of the notes that match the query. A `table:` term with property names,
+//! e.g. `table:author,rating`, shows the notes as a table with the values of the properties. The
+//! results are evaluated by the app and shown in place of the source of the block.
+use std::collections::BTreeMap;
+
+use basalt_core::obsidian::{Note, Query, Vault};
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span},
+};
+
+use super::markdown_parser::{MarkdownNode, Node};
+
+/// Language of the code blocks that are queries.
+pub const LANG: &str = "basalt-query";
+
+/// Notes that match the query of a block.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueryResult {
+    /// The query as written in the block.
+    pub query: String,
+    /// Property names of the table columns besides the note name, empty for a list.
+    pub columns: Vec<String>,
+    /// Names of the matching notes with the values of the columns, sorted by name.
+    pub rows: Vec<(String, Vec<String>)>,
+}
+
+/// Query results of the blocks of a note, by the text of the block.
+pub type QueryResults = BTreeMap<String, QueryResult>;
+
+/// Returns the text of the node if it is a query block.
+fn query_text(node: &Node) -> Option<String> {
+    match &node.markdown_node {
+        MarkdownNode::CodeBlock {
+            lang: Some(lang),
+            text,
+        } if lang == LANG => Some(String::from(text).trim().to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the result of the query block, or `None` if the node is not a query block or the query
+/// has not been evaluated yet.
+pub fn result<'a>(node: &Node, results: &'a QueryResults) -> Option<&'a QueryResult> {
+    results.get(&query_text(node)?)
+}
+
+/// Evaluates the query blocks among the nodes against the notes of the vault. The notes are only
+/// read if there are query blocks.
+pub fn evaluate(nodes: &[Node], vault: &Vault) -> QueryResults {
+    let queries: Vec<_> = nodes.iter().filter_map(query_text).collect();
+    if queries.is_empty() {
+        return QueryResults::new();
+    }
+
+    let notes: Vec<_> = vault
+        .notes()
+        .into_iter()
+        .map(|note| {
+            let contents = Note::read_to_string(&note).unwrap_or_default();
+            (note, contents)
+        })
+        .collect();
+
+    queries
+        .into_iter()
+        .map(|text| {
+            let (columns, terms): (Vec<_>, Vec<_>) = text
+                .split_whitespace()
+                .partition(|term| term.starts_with("table:"));
+            let columns: Vec<String> = columns
+                .iter()
+                .flat_map(|term| term["table:".len()..].split(','))
+                .filter(|column| !column.is_empty())
+                .map(String::from)
+                .collect();
+            let query = Query::from(terms.join(" ").as_str());
+
+            let mut rows: Vec<_> = notes
+                .iter()
+                .filter(|(note, contents)| {
+                    let path = note.path.strip_prefix(&vault.path).unwrap_or(&note.path);
+                    query.matches(path, contents)
+                })
+                .map(|(note, contents)| {
+                    let values = columns
+                        .iter()
+                        .map(|column| Note::property(contents, column).join(", "))
+                        .collect();
+                    (note.name.clone(), values)
+                })
+                .collect();
+            rows.sort_by_key(|(name, _)| name.to_lowercase());
+
+            let result = QueryResult {
+                query: terms.join(" "),
+                columns,
+                rows,
+            };
+            (text, result)
+        })
+        .collect()
+}
+
+/// Pads or truncates the text to the width.
+fn cell(text: &str, width: usize) -> String {
+    match text.chars().count() > width {
+        true => {
+            let text: String = text.chars().take(width.saturating_sub(1)).collect();
+            format!("{text}…")
+        }
+        false => format!("{text:width$}"),
+    }
+}
+
+/// Renders the result as a list or a table, followed by the query and the number of notes.
+pub fn render(result: &QueryResult, width: usize) -> Vec<Line<'static>> {
+    let rows: Vec<Line> = match result.columns.is_empty() {
+        true => result
+            .rows
+            .iter()
+            .map(|(name, _)| {
+                Line::from(vec![
+                    "• ".dark_gray(),
+                    Span::raw(cell(name, width.saturating_sub(2))),
+                ])
+            })
+            .collect(),
+        false => {
+            let headers: Vec<&str> = std::iter::once("Note")
+                .chain(result.columns.iter().map(String::as_str))
+                .collect();
+            let max_width = (width / headers.len()).saturating_sub(1).max(1);
+
+            let widths: Vec<usize> = headers
+                .iter()
+                .enumerate()
+                .map(|(i, header)| {
+                    result
+                        .rows
+                        .iter()
+                        .map(|(name, values)| match i {
+                            0 => name.chars().count(),
+                            i => values[i - 1].chars().count(),
+                        })
+                        .chain([header.chars().count()])
+                        .max()
+                        .unwrap_or_default()
+                        .min(max_width)
+                })
+                .collect();
+
+            let line = |cells: Vec<&str>| -> String {
+                cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(text, width)| cell(text, *width))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+
+            let separator = widths
+                .iter()
+                .map(|width| "─".repeat(*width))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            [
+                Line::from(line(headers.clone())).bold(),
+                Line::from(separator).dark_gray(),
+            ]
+            .into_iter()
+            .chain(result.rows.iter().map(|(name, values)| {
+                let cells = std::iter::once(name.as_str())
+                    .chain(values.iter().map(String::as_str))
+                    .collect();
+                Line::from(line(cells))
+            }))
+            .collect()
+        }
+    };
+
+    let count = match result.rows.len() {
+        1 => "1 note".to_string(),
+        count => format!("{count} notes"),
+    };
+
+    rows.into_iter()
+        .chain([
+            Line::from(cell(&format!("{} · {count}", result.query), width))
+                .dark_gray()
+                .italic(),
+            Line::default(),
+        ])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{note_editor::markdown_parser, testing};
+
+    #[test]
+    fn test_evaluate() {
+        let vault = testing::vault(
+            "basalt-query-block",
+            &[
+                (
+                    "Books/Dune.md",
+                    "---\nstatus: reading\nauthor: Herbert\n---\n#book\n",
+                ),
+                ("Books/Emma.md", "---\nstatus: done\n---\n#book\n"),
+                ("Inbox.md", "#book to buy\n"),
+            ],
+        );
+        let nodes = markdown_parser::from_str(
+            "```basalt-query\ntag:book -status:done\n```\n\n```basalt-query\nstatus:reading table:author,rating\n```\n\n```sh\nls\n```\n",
+        );
+
+        let results = evaluate(&nodes, &vault);
+        assert_eq!(results.len(), 2);
+
+        let list = result(&nodes[0], &results).unwrap();
+        let names: Vec<_> = list.rows.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["Dune", "Inbox"]);
+        assert_eq!(
+            render(list, 40)
+                .iter()
+                .map(|line| line.to_string().trim_end().to_string())
+                .collect::<Vec<_>>(),
+            ["• Dune", "• Inbox", "tag:book -status:done · 2 notes", ""]
+        );
+
+        let table = result(&nodes[1], &results).unwrap();
+        assert_eq!(
+            table.rows,
+            [(
+                "Dune".to_string(),
+                vec!["Herbert".to_string(), String::new()]
+            )]
+        );
+        assert_eq!(
+            render(table, 30)
+                .iter()
+                .map(|line| line.to_string().trim_end().to_string())
+                .collect::<Vec<_>>(),
+            [
+                "Note author  rating",
+                "──── ─────── ──────",
+                "Dune Herbert",
+                "status:reading · 1 note",
+                ""
+            ]
+        );
+
+        assert_eq!(result(&nodes[2], &results), None);
+    }
+}
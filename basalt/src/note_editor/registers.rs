@@ -0,0 +1,121 @@
+//! Registers for yanked and deleted blocks, similar to the registers of vim. Every yanked or
+//! deleted block goes to the kill ring, numbered from `0` for the newest to `9` for the oldest,
+//! and to the named register `a`–`z` selected before the yank or delete. A paste uses the selected
+//! register, or the newest block of the kill ring.
+use std::collections::{BTreeMap, VecDeque};
+
+/// Number of blocks kept in the kill ring.
+pub const KILL_RING_SIZE: usize = 10;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Registers {
+    named: BTreeMap<char, String>,
+    kill_ring: VecDeque<String>,
+    /// Register for the next yank, delete or paste.
+    selected: Option<char>,
+}
+
+impl Registers {
+    /// Returns `true` for the names of the registers, `a`–`z` and `0`–`9`.
+    pub fn is_register(name: char) -> bool {
+        name.is_ascii_lowercase() || name.is_ascii_digit()
+    }
+
+    /// Selects the register for the next yank, delete or paste.
+    pub fn select(&mut self, name: char) {
+        self.selected = Self::is_register(name).then_some(name);
+    }
+
+    pub fn selected(&self) -> Option<char> {
+        self.selected
+    }
+
+    /// Returns the text of the register.
+    pub fn get(&self, name: char) -> Option<&str> {
+        match name.to_digit(10) {
+            Some(index) => self.kill_ring.get(index as usize),
+            None => self.named.get(&name),
+        }
+        .map(String::as_str)
+    }
+
+    /// Stores the text as the newest block of the kill ring, and in the selected named register.
+    /// The selection is cleared.
+    pub fn store(&mut self, text: String) {
+        if let Some(name) = self.selected.take().filter(char::is_ascii_lowercase) {
+            self.named.insert(name, text.clone());
+        }
+
+        self.kill_ring.push_front(text);
+        self.kill_ring.truncate(KILL_RING_SIZE);
+    }
+
+    /// Returns the text to paste: the text of the selected register, or the newest block of the
+    /// kill ring. The selection is cleared.
+    pub fn take(&mut self) -> Option<String> {
+        let name = self.selected.take().unwrap_or('0');
+        self.get(name).map(str::to_string)
+    }
+
+    /// Returns the registers that hold text, the kill ring first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::note_editor::Registers;
+    ///
+    /// let mut registers = Registers::default();
+    /// registers.store("Paint the door".into());
+    /// registers.select('a');
+    /// registers.store("Fix the roof".into());
+    ///
+    /// assert_eq!(
+    ///     registers.list(),
+    ///     [('0', "Fix the roof"), ('1', "Paint the door"), ('a', "Fix the roof")]
+    /// );
+    /// ```
+    pub fn list(&self) -> Vec<(char, &str)> {
+        let kill_ring = self
+            .kill_ring
+            .iter()
+            .zip('0'..='9')
+            .map(|(text, name)| (name, text.as_str()));
+
+        let named = self.named.iter().map(|(name, text)| (*name, text.as_str()));
+
+        kill_ring.chain(named).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registers() {
+        let mut registers = Registers::default();
+        assert_eq!(registers.take(), None);
+
+        (0..12).for_each(|i| registers.store(format!("Block {i}")));
+        assert_eq!(registers.get('0'), Some("Block 11"));
+        assert_eq!(registers.get('9'), Some("Block 2"));
+        assert_eq!(registers.list().len(), KILL_RING_SIZE);
+
+        registers.select('q');
+        registers.store("Quote".into());
+        assert_eq!(registers.selected(), None);
+        assert_eq!(registers.get('q'), Some("Quote"));
+
+        // The named register is kept while the kill ring moves on.
+        registers.store("Block 12".into());
+        registers.select('q');
+        assert_eq!(registers.take(), Some("Quote".into()));
+        assert_eq!(registers.take(), Some("Block 12".into()));
+
+        registers.select('4');
+        assert_eq!(registers.take(), Some("Block 9".into()));
+
+        registers.select('"');
+        assert_eq!(registers.selected(), None);
+    }
+}
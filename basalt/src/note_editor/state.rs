@@ -1,17 +1,28 @@
 use core::fmt;
 
 use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
     fs::File,
+    hash::{DefaultHasher, Hash, Hasher},
     io::{self, Write},
     ops::RangeBounds,
-    path::PathBuf,
+    path::{Path, PathBuf},
     slice::SliceIndex,
+    time::SystemTime,
 };
 
-use ratatui::widgets::ScrollbarState;
+use basalt_core::obsidian::{Link, ReadOnly};
+use ratatui::{text::Line, widgets::ScrollbarState};
+use serde::Deserialize;
 use tui_textarea::Input;
 
-use super::{markdown_parser, text_buffer::CursorMove, TextBuffer};
+use super::{
+    markdown_parser,
+    query_block::{self, QueryResult, QueryResults},
+    text_buffer::CursorMove,
+    Completion, Registers, TextBuffer,
+};
 
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct Scrollbar {
@@ -19,7 +30,8 @@ pub struct Scrollbar {
     pub position: usize,
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Mode {
     #[default]
     Read,
@@ -60,7 +72,170 @@ pub struct EditorState<'text_buffer> {
     // error_message: Option<String>,
     active: bool,
     pub modified: bool,
+    /// Why the note cannot be edited, if it is read-only.
+    read_only: Option<ReadOnly>,
+    /// The note file as it was when the note was opened or last saved, to find changes made
+    /// outside Basalt, e.g. by Obsidian Sync, before they are overwritten.
+    disk_version: Option<DiskVersion>,
+    /// Hashes of the sources of the nodes as they were when the note was opened or last saved.
+    /// Nodes whose source is not among them are marked as changed.
+    saved_nodes: HashSet<u64>,
+    /// Byte offset of the node that was changed last.
+    last_edit: Option<usize>,
     dirty: bool,
+    /// Scrolls the current row to the top of the viewport on the next render, as the position
+    /// of the row in rendered lines depends on the editor width.
+    scroll_to_current_row: bool,
+    /// Keeps the scroll-off lines around the cursor visible on the next render, as the cursor line
+    /// in rendered lines depends on the editor width.
+    cursor_moved: bool,
+    /// Sources of the yanked and deleted nodes.
+    pub registers: Registers,
+    /// Completion of the link anchor at the cursor in edit mode.
+    completion: Option<Completion>,
+    /// Results of the query blocks of the note, shown in place of the blocks.
+    query_results: QueryResults,
+    render_cache: RenderCache,
+}
+
+/// Modification time, size and content hash of a note file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct DiskVersion {
+    metadata: Option<(SystemTime, u64)>,
+    hash: u64,
+}
+
+impl DiskVersion {
+    fn new(path: &Path, content: &str) -> Self {
+        Self {
+            metadata: file_metadata(path),
+            hash: text_hash(content),
+        }
+    }
+}
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the hashes of the sources of the nodes.
+fn node_hashes(content: &str, nodes: &[markdown_parser::Node]) -> HashSet<u64> {
+    nodes
+        .iter()
+        .filter_map(|node| content.get(node.source_range.clone()))
+        .map(text_hash)
+        .collect()
+}
+
+/// Reads the note file as the open note is read, with invalid UTF-8 replaced with U+FFFD.
+fn read_note(path: &Path) -> io::Result<String> {
+    std::fs::read(path).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn file_metadata(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Rendered lines of the nodes at the editor width, keyed by the hash of the node. Nodes that are
+/// unchanged between frames are not styled again.
+#[derive(Clone, Debug, Default)]
+struct RenderCache {
+    width: u16,
+    lines: HashMap<u64, Vec<Line<'static>>>,
+    /// Hashes of the nodes, in the order of the nodes.
+    keys: Vec<u64>,
+}
+
+/// Returns `true` if the text is a single URL, e.g. `https://example.com` or
+/// `mailto:user@example.com`.
+fn is_url(text: &str) -> bool {
+    if text.is_empty() || text.contains(char::is_whitespace) {
+        return false;
+    }
+
+    let is_scheme = |scheme: &str| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    };
+
+    match text.split_once("://") {
+        Some((scheme, rest)) => is_scheme(scheme) && !rest.is_empty(),
+        None => text.starts_with("mailto:"),
+    }
+}
+
+/// Returns the text to insert for pasted text, given the selection and the text of the cursor
+/// line around the cursor. A URL pasted over a selection on one line becomes a markdown link with
+/// the selection as its text, and a note name pasted right after `[[` is closed as a wikilink.
+fn paste_text(selection: Option<&str>, before: &str, after: &str, text: &str) -> String {
+    let pasted = text.trim();
+
+    match selection {
+        Some(selection) if !selection.is_empty() && !selection.contains('\n') && is_url(pasted) => {
+            format!("[{selection}]({pasted})")
+        }
+        None if before.ends_with("[[")
+            && !pasted.is_empty()
+            && !pasted.contains('\n')
+            && !is_url(pasted) =>
+        {
+            let name = pasted.strip_suffix(".md").unwrap_or(pasted);
+            match after.starts_with("]]") {
+                true => name.to_string(),
+                false => format!("{name}]]"),
+            }
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Returns the line ending used in the text, `\r\n` if there is any, or `\n` otherwise.
+fn line_ending(text: &str) -> &'static str {
+    match text.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    }
+}
+
+/// Returns the source of a node with its lines replaced by the edited lines. The line break at
+/// the end of the source is kept, as the text buffer has no line for it, and the lines are joined
+/// with the line ending of the source. Unchanged lines return the source as is, so mixed line
+/// endings and the whitespace the text buffer cannot represent are not normalized.
+fn replace_lines(source: &str, lines: &[String], default_line_ending: &str) -> String {
+    if source.lines().eq(lines.iter().map(String::as_str)) {
+        return source.to_string();
+    }
+
+    let line_ending = match source.contains('\n') {
+        true => line_ending(source),
+        false => default_line_ending,
+    };
+
+    let trailing = ["\r\n", "\n"]
+        .into_iter()
+        .find(|ending| source.ends_with(ending))
+        .unwrap_or_default();
+
+    lines.join(line_ending) + trailing
+}
+
+/// Moves the source range of the node and its child nodes by `delta` bytes.
+fn shift_node(node: &mut markdown_parser::Node, delta: isize) {
+    let shift = |offset: usize| offset.saturating_add_signed(delta);
+    node.source_range = shift(node.source_range.start)..shift(node.source_range.end);
+
+    match &mut node.markdown_node {
+        markdown_parser::MarkdownNode::BlockQuote { nodes, .. }
+        | markdown_parser::MarkdownNode::List { nodes, .. } => {
+            nodes.iter_mut().for_each(|node| shift_node(node, delta))
+        }
+        _ => {}
+    }
 }
 
 impl<'text_buffer> EditorState<'text_buffer> {
@@ -87,6 +262,64 @@ impl<'text_buffer> EditorState<'text_buffer> {
         self.nodes.as_slice()
     }
 
+    /// Sets the results of the query blocks. The query blocks are rendered again if the results
+    /// changed.
+    pub fn set_query_results(&mut self, results: QueryResults) {
+        if self.query_results != results {
+            self.query_results = results;
+            self.render_cache.lines.clear();
+        }
+    }
+
+    /// Renders the nodes that are not cached at the width with `render` and returns the heights
+    /// of the nodes. `render` is given the result of query blocks. The cache is cleared when the
+    /// width changes, and the lines of nodes that no longer exist are dropped.
+    pub fn render_nodes(
+        &mut self,
+        width: u16,
+        render: impl Fn(&markdown_parser::Node, Option<&QueryResult>) -> Vec<Line<'static>>,
+    ) -> Vec<usize> {
+        let cache = &mut self.render_cache;
+        if cache.width != width {
+            cache.width = width;
+            cache.lines.clear();
+        }
+
+        cache.keys = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let mut hasher = DefaultHasher::new();
+                node.markdown_node.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
+
+        let keys: HashSet<_> = cache.keys.iter().collect();
+        cache.lines.retain(|key, _| keys.contains(key));
+
+        self.nodes
+            .iter()
+            .zip(&cache.keys)
+            .map(|(node, key)| {
+                cache
+                    .lines
+                    .entry(*key)
+                    .or_insert_with(|| render(node, query_block::result(node, &self.query_results)))
+                    .len()
+            })
+            .collect()
+    }
+
+    /// Returns the cached lines of the node at the index, see [`EditorState::render_nodes`].
+    pub fn rendered_lines(&self, index: usize) -> &[Line<'static>] {
+        self.render_cache
+            .keys
+            .get(index)
+            .and_then(|key| self.render_cache.lines.get(key))
+            .map_or(&[], Vec::as_slice)
+    }
+
     pub fn nodes_as_mut(&mut self) -> &mut [markdown_parser::Node] {
         self.nodes.as_mut_slice()
     }
@@ -100,8 +333,11 @@ impl<'text_buffer> EditorState<'text_buffer> {
     }
 
     pub fn new(content: &str, path: PathBuf) -> Self {
+        let nodes = markdown_parser::from_str(content);
+
         Self {
-            nodes: markdown_parser::from_str(content),
+            saved_nodes: node_hashes(content, &nodes),
+            nodes,
             content_original: content.to_string(),
             content: content.to_string(),
             path,
@@ -109,13 +345,74 @@ impl<'text_buffer> EditorState<'text_buffer> {
         }
     }
 
+    /// Sets the content of the note as read from the note file.
     pub fn set_content(&mut self, content: &str) {
         self.nodes = markdown_parser::from_str(content);
         self.content_original = content.to_string();
         self.content = content.to_string();
+        self.disk_version = Some(DiskVersion::new(&self.path, content));
+        self.saved_nodes = node_hashes(content, &self.nodes);
         self.update_text_buffer();
     }
 
+    /// Returns `true` if the node at the index was changed since the note was opened or last
+    /// saved, including the unsaved edits of the text buffer of the current node.
+    pub fn is_node_changed(&self, index: usize) -> bool {
+        if index == self.current_row && self.text_buffer.is_modified() {
+            return true;
+        }
+
+        self.nodes
+            .get(index)
+            .and_then(|node| self.content.get(node.source_range.clone()))
+            .is_some_and(|source| !self.saved_nodes.contains(&text_hash(source)))
+    }
+
+    /// Reads the note file again, dropping the unsaved changes.
+    pub fn reload(&mut self) -> io::Result<()> {
+        let content = read_note(&self.path)?;
+        self.set_content(&content);
+        self.current_row = self.current_row.min(self.nodes.len().saturating_sub(1));
+        self.modified = false;
+        Ok(())
+    }
+
+    /// Returns `true` if the note file was changed outside Basalt since the note was opened or
+    /// last saved. Files that were touched without changing their content, or that were removed,
+    /// are not considered changed.
+    pub fn changed_on_disk(&self) -> bool {
+        let Some(version) = self.disk_version else {
+            return false;
+        };
+
+        if file_metadata(&self.path) == version.metadata {
+            return false;
+        }
+
+        read_note(&self.path)
+            .is_ok_and(|content| DiskVersion::new(&self.path, &content).hash != version.hash)
+    }
+
+    /// Replaces the content without saving it, e.g. after search and replace.
+    pub fn replace_content(&mut self, content: &str) {
+        self.nodes = markdown_parser::from_str(content);
+        self.content = content.to_string();
+        self.current_row = self.current_row.min(self.nodes.len().saturating_sub(1));
+        self.content_changed();
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn read_only(&self) -> Option<ReadOnly> {
+        self.read_only
+    }
+
+    pub fn set_read_only(&mut self, read_only: Option<ReadOnly>) {
+        self.read_only = read_only;
+    }
+
     pub fn set_path(&mut self, path: PathBuf) {
         self.path = path;
     }
@@ -124,21 +421,42 @@ impl<'text_buffer> EditorState<'text_buffer> {
         self.intermediate_save();
     }
 
-    fn intermediate_save(&mut self) {
-        if let Some(node) = self.nodes().get(self.current_row) {
-            let start = node.source_range.start;
-            let end = node.source_range.end;
+    /// Returns the content with the text buffer written back to the source range of the current
+    /// node, i.e. the content with the edits that are not written back yet. The rest of the
+    /// content is kept byte for byte, see [`replace_lines`].
+    pub fn edited_content(&self) -> Cow<'_, str> {
+        let Some(node) = self.nodes().get(self.current_row) else {
+            return Cow::Borrowed(&self.content);
+        };
 
-            let str_start = &self.content_slice(..start.saturating_sub(1));
-            let str_end = &self.content_slice(end..);
+        let start = node.source_range.start.min(self.content.len());
+        let end = node.source_range.end.clamp(start, self.content.len());
 
-            let modified_str = self.text_buffer().to_string();
+        let modified_str = replace_lines(
+            self.content_slice(start..end),
+            self.text_buffer.lines(),
+            line_ending(&self.content),
+        );
 
-            let complete_modified_content = [str_start, modified_str.as_str(), str_end].join("\n");
+        Cow::Owned(
+            [
+                self.content_slice(..start),
+                modified_str.as_str(),
+                self.content_slice(end..),
+            ]
+            .concat(),
+        )
+    }
+
+    /// Writes the text buffer back to the source range of the current node.
+    fn intermediate_save(&mut self) {
+        if self.nodes().get(self.current_row).is_some() {
+            let complete_modified_content = self.edited_content().into_owned();
 
             if self.content != complete_modified_content {
                 self.nodes = markdown_parser::from_str(&complete_modified_content);
                 self.content = complete_modified_content;
+                self.mark_edited();
                 self.update_text_buffer();
             }
 
@@ -187,7 +505,320 @@ impl<'text_buffer> EditorState<'text_buffer> {
         }
     }
 
+    fn content_changed(&mut self) {
+        self.modified = self.content != self.content_original;
+        self.mark_edited();
+        self.update_text_buffer();
+    }
+
+    /// Remembers the current node as the node that was changed last.
+    fn mark_edited(&mut self) {
+        self.last_edit = self
+            .nodes
+            .get(self.current_row)
+            .map(|node| node.source_range.start);
+    }
+
+    /// Returns the byte offset of the node that was changed last.
+    pub fn last_edit(&self) -> Option<usize> {
+        self.last_edit
+    }
+
+    /// Returns the byte offset of the current node.
+    pub fn cursor_offset(&self) -> usize {
+        self.nodes
+            .get(self.current_row)
+            .map_or(0, |node| node.source_range.start)
+    }
+
+    /// Moves the cursor to the node at the byte offset, and remembers the offset as the node that
+    /// was changed last, e.g. as they were when the note was closed before. Offsets past the end
+    /// of the content are ignored, as the note was changed outside Basalt since.
+    pub fn restore_position(&mut self, cursor: usize, last_edit: Option<usize>) {
+        let len = self.content.len();
+        self.last_edit = last_edit.filter(|offset| *offset < len);
+
+        if cursor > 0 && cursor < len {
+            self.jump_to_row(Some(self.row_at_offset(cursor)));
+        }
+    }
+
+    /// Moves the cursor to the node that was changed last and scrolls it to the top of the
+    /// viewport. Returns `false` if nothing was changed.
+    pub fn goto_last_edit(&mut self) -> bool {
+        let row = self.last_edit.map(|offset| self.row_at_offset(offset));
+        self.jump_to_row(row);
+        row.is_some()
+    }
+
+    /// Swaps the current node with the next node, or with the previous node if `up` is set. The
+    /// text between the nodes is kept in place, so only the source ranges of the two nodes change
+    /// and nothing is parsed again.
+    pub fn move_node(&mut self, up: bool) {
+        let (first, second) = match up {
+            true if self.current_row > 0 => (self.current_row - 1, self.current_row),
+            false if self.current_row + 1 < self.nodes.len() => {
+                (self.current_row, self.current_row + 1)
+            }
+            _ => return,
+        };
+
+        let a = self.nodes[first].source_range.clone();
+        let b = self.nodes[second].source_range.clone();
+        if a.end > b.start || b.end > self.content.len() {
+            return;
+        }
+
+        let mut a_text = self.content[a.clone()].to_string();
+        let mut b_text = self.content[b.clone()].to_string();
+        let separator = self.content[a.end..b.start].to_string();
+
+        // The last node might miss the trailing newline, which would join the nodes once the
+        // last node is moved up.
+        let ending = ["\r\n", "\n"]
+            .into_iter()
+            .find(|ending| a_text.ends_with(ending));
+        if let Some(ending) = ending.filter(|_| !b_text.ends_with('\n')) {
+            a_text.truncate(a_text.len() - ending.len());
+            b_text.push_str(ending);
+        }
+
+        self.content
+            .replace_range(a.start..b.end, &format!("{b_text}{separator}{a_text}"));
+
+        let second_start = a.start + b_text.len() + separator.len();
+
+        let mut first_node = self.nodes[first].clone();
+        let mut second_node = self.nodes[second].clone();
+        shift_node(&mut second_node, a.start as isize - b.start as isize);
+        shift_node(&mut first_node, second_start as isize - a.start as isize);
+        second_node.source_range.end = a.start + b_text.len();
+        first_node.source_range.end = second_start + a_text.len();
+
+        self.nodes[first] = second_node;
+        self.nodes[second] = first_node;
+        self.current_row = if up { first } else { second };
+        self.content_changed();
+    }
+
+    /// Copies the source of the current node to the registers.
+    pub fn yank_node(&mut self) {
+        if let Some(text) = self.extract_text().filter(|_| !self.is_editing()) {
+            self.registers.store(text);
+        }
+    }
+
+    /// Removes the current node, and copies its source to the registers.
+    pub fn delete_node(&mut self) {
+        if let Some(text) = self.extract_text().filter(|_| !self.is_editing()) {
+            self.registers.store(text);
+            self.replace_extracted(None);
+        }
+    }
+
+    /// Pastes the node of the selected register, or the last yanked or deleted node, after the
+    /// current node. Only the pasted text is parsed, and the source ranges of the following nodes
+    /// are moved.
+    pub fn paste_node(&mut self) {
+        let Some(yanked) = self.registers.take() else {
+            return;
+        };
+
+        let row = (self.current_row + 1).min(self.nodes.len());
+        let ending = line_ending(&self.content);
+
+        // Where the text is inserted, and where the pasted node starts.
+        let (insert_at, offset, text) = match self.nodes.get(row) {
+            Some(next) => {
+                let start = next.source_range.start;
+                (start, start, format!("{yanked}{ending}{ending}"))
+            }
+            None => {
+                let end = self.content.trim_end_matches(['\r', '\n']).len();
+                self.nodes.iter_mut().for_each(|node| {
+                    node.source_range.end = node.source_range.end.min(end);
+                });
+                self.content.truncate(end);
+
+                match end {
+                    0 => (0, 0, format!("{yanked}{ending}")),
+                    _ => (
+                        end,
+                        end + 2 * ending.len(),
+                        format!("{ending}{ending}{yanked}{ending}"),
+                    ),
+                }
+            }
+        };
+
+        self.content.insert_str(insert_at, &text);
+
+        let pasted: Vec<_> = markdown_parser::from_str(&format!("{yanked}{ending}"))
+            .into_iter()
+            .map(|mut node| {
+                shift_node(&mut node, offset as isize);
+                node
+            })
+            .collect();
+
+        self.nodes[row..]
+            .iter_mut()
+            .for_each(|node| shift_node(node, text.len() as isize));
+
+        let pasted_len = pasted.len();
+        self.nodes.splice(row..row, pasted);
+
+        if pasted_len > 0 {
+            self.current_row = row;
+        }
+        self.content_changed();
+    }
+
+    /// Returns the text to extract into another note: the selection in edit mode, or the source of
+    /// the current node otherwise.
+    pub fn extract_text(&self) -> Option<String> {
+        match self.is_editing() {
+            true => self
+                .text_buffer
+                .selected_text()
+                .filter(|text| !text.trim().is_empty()),
+            false => self
+                .nodes
+                .get(self.current_row)
+                .and_then(|node| self.content.get(node.source_range.clone()))
+                .map(|text| text.trim_end_matches(['\r', '\n']).to_string()),
+        }
+    }
+
+    /// Returns the selected text in edit mode, if any text is selected.
+    pub fn selected_text(&self) -> Option<String> {
+        self.text_buffer
+            .selected_text()
+            .filter(|text| self.is_editing() && !text.is_empty())
+    }
+
+    /// Returns the text of the current node, with the edits that are not written back yet.
+    pub fn current_node_text(&self) -> String {
+        self.text_buffer.lines().join("\n")
+    }
+
+    /// Returns the links of the current node, with their ranges in the content.
+    pub fn node_links(&self) -> Vec<Link> {
+        let Some(node) = self.nodes.get(self.current_row) else {
+            return vec![];
+        };
+        let start = node.source_range.start;
+
+        self.content
+            .get(node.source_range.clone())
+            .map(Link::parse)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|link| Link {
+                range: start + link.range.start..start + link.range.end,
+                ..link
+            })
+            .collect()
+    }
+
+    /// Replaces the text returned by [`EditorState::extract_text`] with the link, or removes the
+    /// text without a link.
+    pub fn replace_extracted(&mut self, link: Option<&str>) {
+        if self.is_editing() {
+            let textarea = self.text_buffer.textarea_as_mut();
+            match link {
+                Some(link) => textarea.insert_str(link),
+                None => textarea.cut(),
+            };
+            self.dirty = true;
+            return;
+        }
+
+        let Some(range) = self.nodes.get(self.current_row).map(|node| {
+            node.source_range.start.min(self.content.len())
+                ..node.source_range.end.min(self.content.len())
+        }) else {
+            return;
+        };
+
+        let ending = line_ending(&self.content);
+        let before = self.content[..range.start].trim_end_matches(['\r', '\n']);
+        let after = self.content[range.end..].trim_start_matches(['\r', '\n']);
+
+        let mut content = [
+            before,
+            link.unwrap_or_default(),
+            after.trim_end_matches(['\r', '\n']),
+        ]
+        .into_iter()
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(&ending.repeat(2));
+        if !content.is_empty() && self.content.ends_with('\n') {
+            content.push_str(ending);
+        }
+
+        self.replace_content(&content);
+    }
+
+    /// Pastes the text at the cursor in edit mode, replacing the selection. Links are created from
+    /// pasted URLs and note names as in Obsidian, see [`paste_text`].
+    pub fn paste(&mut self, text: &str) {
+        if !self.is_editing() {
+            return;
+        }
+
+        let selection = self.text_buffer.selected_text();
+        let (before, after) = self.text_buffer.cursor_line();
+        let text = paste_text(selection.as_deref(), before, after, text);
+
+        self.text_buffer.textarea_as_mut().insert_str(text);
+        self.dirty = true;
+    }
+
+    /// Inserts the text at the cursor when editing. Otherwise the text is inserted as a new
+    /// block after the current block.
+    pub fn insert(&mut self, text: &str) {
+        if self.is_editing() {
+            self.text_buffer.textarea_as_mut().insert_str(text);
+            self.dirty = true;
+            return;
+        }
+
+        let offset = self
+            .nodes
+            .get(self.current_row)
+            .map_or(self.content.len(), |node| {
+                node.source_range.end.min(self.content.len())
+            });
+
+        let ending = line_ending(&self.content);
+        let (before, after) = self.content.split_at(offset);
+        let before = before.trim_end_matches(['\r', '\n']);
+        let after = after.trim_start_matches(['\r', '\n']);
+
+        let mut content = String::new();
+        if !before.is_empty() {
+            content.push_str(before);
+            content.push_str(ending);
+            content.push_str(ending);
+        }
+        content.push_str(text.trim_end());
+        content.push_str(ending);
+        if !after.is_empty() {
+            content.push_str(ending);
+            content.push_str(after);
+        }
+
+        self.nodes = markdown_parser::from_str(&content);
+        self.last_edit = Some(before.len() + 2 * ending.len() * usize::from(!before.is_empty()));
+        self.content = content;
+        self.modified = self.content != self.content_original;
+        self.update_text_buffer();
+    }
+
     pub fn cursor_up(&mut self) {
+        self.cursor_moved = true;
         let (row, _) = self.text_buffer.cursor();
         if row == 0 {
             if self.dirty {
@@ -227,11 +858,107 @@ impl<'text_buffer> EditorState<'text_buffer> {
         self.text_buffer.cursor_move(CursorMove::WordBackward);
     }
 
+    /// Returns the cursor position in the note source as 1-based line and column.
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let (row, col) = self.text_buffer.cursor();
+        let node_start = self
+            .nodes
+            .get(self.current_row)
+            .map_or(0, |node| node.source_range.start.min(self.content.len()));
+
+        let line = self.content[..node_start].matches('\n').count() + row + 1;
+
+        (line, col + 1)
+    }
+
+    /// Returns how far the cursor line is through the note in percents.
+    pub fn progress(&self) -> usize {
+        let (line, _) = self.cursor_position();
+        let line_count = self.content.lines().count().max(1);
+
+        (line.min(line_count) * 100) / line_count
+    }
+
     pub fn set_row(&mut self, row: usize) {
         self.current_row = row;
     }
 
+    fn is_heading(&self, row: usize) -> bool {
+        self.nodes.get(row).is_some_and(|node| {
+            matches!(
+                node.markdown_node,
+                markdown_parser::MarkdownNode::Heading { .. }
+            )
+        })
+    }
+
+    fn jump_to_row(&mut self, row: Option<usize>) {
+        if let Some(row) = row {
+            self.current_row = row;
+            self.scroll_to_current_row = true;
+            self.update_text_buffer();
+            self.text_buffer.cursor_move(CursorMove::Top);
+        }
+    }
+
+    /// Moves the cursor to the next heading and scrolls it to the top of the viewport.
+    pub fn next_heading(&mut self) {
+        let row = (self.current_row + 1..self.nodes.len()).find(|row| self.is_heading(*row));
+        self.jump_to_row(row);
+    }
+
+    /// Moves the cursor to the previous heading and scrolls it to the top of the viewport.
+    pub fn previous_heading(&mut self) {
+        let row = (0..self.current_row)
+            .rev()
+            .find(|row| self.is_heading(*row));
+        self.jump_to_row(row);
+    }
+
+    /// Returns `true` once after the current row was requested to scroll to the top of the
+    /// viewport.
+    pub fn take_scroll_to_current_row(&mut self) -> bool {
+        std::mem::take(&mut self.scroll_to_current_row)
+    }
+
+    /// Returns `true` once after the cursor moved up or down.
+    pub fn take_cursor_moved(&mut self) -> bool {
+        std::mem::take(&mut self.cursor_moved)
+    }
+
+    pub fn set_scroll_position(&mut self, position: usize) {
+        self.scrollbar = Scrollbar {
+            state: self.scrollbar.state.position(position),
+            position,
+        };
+    }
+
+    /// Returns the index of the node that contains the byte offset of the note content.
+    pub fn row_at_offset(&self, offset: usize) -> usize {
+        self.nodes
+            .iter()
+            .rposition(|node| node.source_range.start <= offset)
+            .unwrap_or_default()
+    }
+
+    /// Moves the cursor to the first line of the note.
+    pub fn goto_top(&mut self) {
+        self.cursor_moved = true;
+        self.current_row = 0;
+        self.update_text_buffer();
+        self.text_buffer.cursor_move(CursorMove::Top);
+    }
+
+    /// Moves the cursor to the last line of the note.
+    pub fn goto_bottom(&mut self) {
+        self.cursor_moved = true;
+        self.current_row = self.nodes.len().saturating_sub(1);
+        self.update_text_buffer();
+        self.text_buffer.cursor_move(CursorMove::Bottom);
+    }
+
     pub fn cursor_down(&mut self) {
+        self.cursor_moved = true;
         let (row, _) = self.text_buffer.cursor();
         if row < self.text_buffer.lines().len().saturating_sub(1) {
             self.text_buffer.cursor_move(CursorMove::Down);
@@ -280,6 +1007,8 @@ impl<'text_buffer> EditorState<'text_buffer> {
         let mut file = File::create(&self.path)?;
         file.write_all(self.content.as_bytes())?;
         self.modified = false;
+        self.disk_version = Some(DiskVersion::new(&self.path, &self.content));
+        self.saved_nodes = node_hashes(&self.content, &self.nodes);
         Ok(())
     }
 
@@ -309,6 +1038,60 @@ impl<'text_buffer> EditorState<'text_buffer> {
 
     pub fn set_mode(&mut self, mode: Mode) {
         self.mode = mode;
+        self.completion = None;
+    }
+
+    /// Returns the text of the cursor line before the cursor in edit mode.
+    pub fn text_before_cursor(&self) -> Option<&str> {
+        self.is_editing().then(|| self.text_buffer.cursor_line().0)
+    }
+
+    /// Returns the text of the cursor line in edit mode.
+    pub fn cursor_line(&self) -> Option<String> {
+        self.is_editing().then(|| {
+            let (before, after) = self.text_buffer.cursor_line();
+            format!("{before}{after}")
+        })
+    }
+
+    pub fn completion(&self) -> Option<&Completion> {
+        self.completion.as_ref()
+    }
+
+    pub fn completion_as_mut(&mut self) -> Option<&mut Completion> {
+        self.completion.as_mut()
+    }
+
+    pub fn set_completion(&mut self, completion: Option<Completion>) {
+        self.completion = completion.filter(|_| self.is_editing());
+    }
+
+    /// Replaces the typed text with the selected completion item and closes it, e.g. the link,
+    /// moving the cursor after it.
+    pub fn accept_completion(&mut self) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+
+        let textarea = self.text_buffer.textarea_as_mut();
+        (0..completion.typed_len()).for_each(|_| {
+            textarea.delete_char();
+        });
+        textarea.insert_str(completion.selected());
+
+        let closing = completion.closing_text();
+        match self.text_buffer.cursor_line().1.starts_with(closing) {
+            true => closing.chars().for_each(|_| {
+                self.text_buffer
+                    .textarea_as_mut()
+                    .move_cursor(tui_textarea::CursorMove::Forward)
+            }),
+            false => {
+                self.text_buffer.textarea_as_mut().insert_str(closing);
+            }
+        }
+
+        self.dirty = true;
     }
 
     pub fn text_buffer(&self) -> &TextBuffer<'text_buffer> {
@@ -345,3 +1128,260 @@ impl<'text_buffer> EditorState<'text_buffer> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::testing;
+
+    fn node_texts(state: &EditorState) -> Vec<String> {
+        state
+            .nodes()
+            .iter()
+            .map(|node| state.content_slice(node.source_range.clone()).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_move_and_paste_node() {
+        let mut state = EditorState::default();
+        state.set_content("# Heading\n\nFirst\n\nLast");
+
+        state.set_row(2);
+        state.move_node(true);
+
+        assert_eq!(state.content(), "# Heading\n\nLast\n\nFirst");
+        assert_eq!(state.current_row, 1);
+        assert_eq!(
+            node_texts(&state),
+            markdown_parser::from_str(state.content())
+                .iter()
+                .map(|node| state.content_slice(node.source_range.clone()).to_string())
+                .collect::<Vec<_>>()
+        );
+
+        state.set_row(0);
+        state.yank_node();
+        state.set_row(2);
+        state.paste_node();
+
+        assert_eq!(
+            state.content(),
+            indoc! {"
+                # Heading
+
+                Last
+
+                First
+
+                # Heading
+            "}
+        );
+        assert_eq!(state.current_row, 3);
+        assert_eq!(
+            node_texts(&state).last().map(String::as_str),
+            Some("# Heading\n")
+        );
+
+        state.set_row(1);
+        state.paste_node();
+
+        assert_eq!(node_texts(&state)[2], "# Heading\n");
+        assert_eq!(node_texts(&state)[3], "First");
+        assert!(state.modified);
+    }
+
+    #[test]
+    fn test_block_edits_keep_crlf() {
+        let mut state = EditorState::default();
+        state.set_content("# Heading\r\n\r\nFirst\r\n\r\nLast");
+
+        state.set_row(2);
+        state.move_node(true);
+        assert_eq!(state.content(), "# Heading\r\n\r\nLast\r\n\r\nFirst");
+
+        state.set_row(0);
+        state.yank_node();
+        state.set_row(2);
+        state.paste_node();
+        assert_eq!(
+            state.content(),
+            "# Heading\r\n\r\nLast\r\n\r\nFirst\r\n\r\n# Heading\r\n"
+        );
+
+        state.set_row(1);
+        state.delete_node();
+        state.insert("Middle");
+        assert_eq!(
+            state.content(),
+            "# Heading\r\n\r\nFirst\r\n\r\nMiddle\r\n\r\n# Heading\r\n"
+        );
+        assert_eq!(
+            node_texts(&state),
+            markdown_parser::from_str(state.content())
+                .iter()
+                .map(|node| state.content_slice(node.source_range.clone()).to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_is_node_changed() {
+        let mut state = EditorState::default();
+        state.set_content("# Shed\n\nPaint the door\n\nFix the roof\n");
+        assert!((0..3).all(|index| !state.is_node_changed(index)));
+
+        state.replace_content("# Shed\n\nPaint the gate\n\nFix the roof\n");
+        assert_eq!(
+            (0..3)
+                .map(|index| state.is_node_changed(index))
+                .collect::<Vec<_>>(),
+            [false, true, false]
+        );
+
+        state.set_content("# Shed\n\nPaint the gate\n\nFix the roof\n");
+        assert!(!state.is_node_changed(1));
+    }
+
+    #[test]
+    fn test_delete_node_and_registers() {
+        let mut state = EditorState::default();
+        state.set_content("# Shed\n\nPaint the door\n\nFix the roof\n");
+
+        state.set_row(1);
+        state.registers.select('a');
+        state.yank_node();
+        state.set_row(2);
+        state.delete_node();
+
+        assert_eq!(state.content(), "# Shed\n\nPaint the door\n");
+        assert_eq!(state.current_row, 1);
+
+        // The last removed block is pasted, unless a register is selected.
+        state.set_row(0);
+        state.paste_node();
+        state.registers.select('a');
+        state.paste_node();
+
+        assert_eq!(
+            state.content(),
+            "# Shed\n\nFix the roof\n\nPaint the door\n\nPaint the door\n"
+        );
+    }
+
+    #[test]
+    fn test_extract() {
+        let mut state = EditorState::default();
+        state.set_content("# Heading\n\nIdea\n\nLast\n");
+
+        state.set_row(1);
+        assert_eq!(state.extract_text().as_deref(), Some("Idea"));
+
+        state.replace_extracted(Some("[[Ideas]]"));
+        assert_eq!(state.content(), "# Heading\n\n[[Ideas]]\n\nLast\n");
+
+        state.set_row(2);
+        state.replace_extracted(None);
+        assert_eq!(state.content(), "# Heading\n\n[[Ideas]]\n");
+        assert_eq!(state.current_row, 1);
+        assert!(state.modified);
+
+        state.set_mode(Mode::Edit);
+        assert_eq!(state.extract_text(), None);
+    }
+
+    #[test]
+    fn test_intermediate_save_round_trip() {
+        const LINES: [&str; 12] = [
+            "# Heading",
+            "Text",
+            "- Item",
+            "- [ ] Task",
+            "> Quote",
+            "```",
+            "|a|b|",
+            "|-|-|",
+            "[a]: x",
+            "   ",
+            "\tTabbed",
+            "",
+        ];
+
+        let mut next = testing::xorshift(0x9e37_79b9_7f4a_7c15);
+
+        (0..500).for_each(|_| {
+            let mut content = String::new();
+            (0..next() % 12).for_each(|_| {
+                content.push_str(LINES[next() % LINES.len()]);
+                content.push_str(["\n", "\r\n", "\n\n", "\r\n\r\n"][next() % 4]);
+            });
+            if next() % 2 == 0 {
+                content.truncate(content.trim_end().len());
+            }
+
+            let mut state = EditorState::default();
+            state.set_content(&content);
+
+            (0..state.nodes().len()).for_each(|row| {
+                state.set_row(row);
+                state.update_text_buffer();
+                state.exit_insert();
+
+                assert_eq!(state.content(), content, "row {row} of {content:?}");
+                assert!(!state.modified);
+            });
+        });
+    }
+
+    #[test]
+    fn test_intermediate_save_line_endings() {
+        let mut state = EditorState::default();
+        state.set_content("# Heading\r\n\r\n\r\nFirst\r\nline\r\n\r\nLast");
+
+        state.set_row(1);
+        state.update_text_buffer();
+        state
+            .text_buffer_as_mut()
+            .textarea_as_mut()
+            .insert_str("New ");
+        state.exit_insert();
+
+        assert_eq!(
+            state.content(),
+            "# Heading\r\n\r\n\r\nNew First\r\nline\r\n\r\nLast"
+        );
+        assert!(state.modified);
+
+        state.set_row(2);
+        state.update_text_buffer();
+        state
+            .text_buffer_as_mut()
+            .textarea_as_mut()
+            .insert_newline();
+        state.exit_insert();
+
+        assert_eq!(
+            state.content(),
+            "# Heading\r\n\r\n\r\nNew First\r\nline\r\n\r\nLast\r\n"
+        );
+    }
+
+    #[test]
+    fn test_paste_text() {
+        let url = "https://example.com";
+
+        assert_eq!(
+            paste_text(Some("example"), "", "", url),
+            "[example](https://example.com)"
+        );
+        assert_eq!(paste_text(None, "", "", url), url);
+        assert_eq!(paste_text(Some("two\nlines"), "", "", url), url);
+        assert_eq!(paste_text(Some("text"), "", "", "not a url"), "not a url");
+
+        assert_eq!(paste_text(None, "See [[", "", "Note.md"), "Note]]");
+        assert_eq!(paste_text(None, "See [[", "]] here", "Note"), "Note");
+        assert_eq!(paste_text(None, "See ", "", "Note"), "Note");
+    }
+}
@@ -1,6 +1,42 @@
 use core::fmt;
+use std::iter;
 
-use tui_textarea::{Input, TextArea};
+use tui_textarea::{Input, Key, TextArea};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Returns the character columns where the grapheme clusters of the line start, followed by the
+/// end of the line. The cursor column of the text area counts characters, so a cursor between
+/// these columns would split a cluster like an emoji with modifiers or a letter with combining
+/// marks.
+fn grapheme_columns(line: &str) -> Vec<usize> {
+    iter::once(0)
+        .chain(line.graphemes(true).scan(0, |col, grapheme| {
+            *col += grapheme.chars().count();
+            Some(*col)
+        }))
+        .collect()
+}
+
+/// Returns the display width of the first `col` characters of the line. Wide characters, e.g.
+/// CJK characters, take two columns.
+fn display_width(line: &str, col: usize) -> usize {
+    let index = line
+        .char_indices()
+        .nth(col)
+        .map_or(line.len(), |(index, _)| index);
+    line[..index].width()
+}
+
+/// Returns the character column of the last grapheme cluster boundary in the line that is at most
+/// `width` display columns from the start.
+fn column_at_width(line: &str, width: usize) -> usize {
+    grapheme_columns(line)
+        .into_iter()
+        .take_while(|col| display_width(line, *col) <= width)
+        .last()
+        .unwrap_or_default()
+}
 
 // TODO: Text wrapping according to the available width of the area
 #[derive(Clone, Debug, Default)]
@@ -82,6 +118,10 @@ impl<'a> TextBuffer<'a> {
         self
     }
 
+    pub fn textarea(&self) -> &TextArea<'a> {
+        &self.textarea
+    }
+
     pub fn textarea_as_mut(&mut self) -> &mut TextArea<'a> {
         &mut self.textarea
     }
@@ -90,22 +130,151 @@ impl<'a> TextBuffer<'a> {
         self.textarea.lines()
     }
 
+    /// Returns the selected text, if any text is selected.
+    pub fn selected_text(&self) -> Option<String> {
+        let ((start_row, start_col), (end_row, end_col)) = self.textarea.selection_range()?;
+        let lines = self.textarea.lines();
+
+        let selected = (start_row..=end_row)
+            .map(|row| {
+                let line = lines.get(row).map(String::as_str).unwrap_or_default();
+                let start = if row == start_row { start_col } else { 0 };
+                let end = if row == end_row {
+                    end_col
+                } else {
+                    line.chars().count()
+                };
+
+                line.chars()
+                    .skip(start)
+                    .take(end.saturating_sub(start))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Some(selected)
+    }
+
+    /// Returns the text of the cursor line before and after the cursor.
+    pub fn cursor_line(&self) -> (&str, &str) {
+        let (row, col) = self.textarea.cursor();
+        let line = self
+            .textarea
+            .lines()
+            .get(row)
+            .map(String::as_str)
+            .unwrap_or_default();
+
+        let index = line
+            .char_indices()
+            .nth(col)
+            .map_or(line.len(), |(index, _)| index);
+
+        line.split_at(index)
+    }
+
     pub fn modified(&self) -> bool {
         self.modified
     }
 
+    /// Returns the line of the cursor.
+    fn current_line(&self) -> &str {
+        let (row, _) = self.textarea.cursor();
+        self.textarea
+            .lines()
+            .get(row)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// Handles the input in the text area. Moving the cursor sideways and deleting characters
+    /// works on whole grapheme clusters.
     pub fn edit(&mut self, input: Input) {
-        self.modified = self.textarea.input(input);
+        let (_, col) = self.textarea.cursor();
+        let columns = grapheme_columns(self.current_line());
+        let previous = columns.iter().rev().find(|start| **start < col).copied();
+        let next = columns.iter().find(|start| **start > col).copied();
+
+        let plain = !input.ctrl && !input.alt && !input.shift;
+
+        self.modified = match (input.key, previous, next) {
+            (Key::Left | Key::Right, _, _) if plain => {
+                let cursor_move = match input.key {
+                    Key::Left => CursorMove::Left,
+                    _ => CursorMove::Right,
+                };
+                self.cursor_move(cursor_move);
+                false
+            }
+            (Key::Backspace, Some(previous), _) if plain && !self.has_selection() => {
+                self.textarea
+                    .move_cursor(tui_textarea::CursorMove::Jump(self.row(), previous as u16));
+                self.textarea.delete_str(col - previous)
+            }
+            (Key::Delete, _, Some(next)) if plain && !self.has_selection() => {
+                self.textarea.delete_str(next - col)
+            }
+            _ => self.textarea.input(input),
+        };
+    }
+
+    fn has_selection(&self) -> bool {
+        self.textarea.selection_range().is_some()
+    }
+
+    fn row(&self) -> u16 {
+        self.textarea.cursor().0 as u16
+    }
+
+    /// Moves the cursor to the row, keeping the display column of the cursor, so that the cursor
+    /// does not jump sideways between lines with wide characters.
+    fn move_to_row(&mut self, row: usize) {
+        let (_, col) = self.textarea.cursor();
+        let width = display_width(self.current_line(), col);
+
+        if let Some(line) = self.textarea.lines().get(row) {
+            let col = column_at_width(line, width);
+            self.textarea
+                .move_cursor(tui_textarea::CursorMove::Jump(row as u16, col as u16));
+        }
     }
 
     pub fn cursor_move(&mut self, cursor_move: CursorMove) {
         match cursor_move {
             CursorMove::Top => self.textarea.move_cursor(tui_textarea::CursorMove::Top),
             CursorMove::Bottom => self.textarea.move_cursor(tui_textarea::CursorMove::Bottom),
-            CursorMove::Up => self.textarea.move_cursor(tui_textarea::CursorMove::Up),
-            CursorMove::Down => self.textarea.move_cursor(tui_textarea::CursorMove::Down),
-            CursorMove::Left => self.textarea.move_cursor(tui_textarea::CursorMove::Back),
-            CursorMove::Right => self.textarea.move_cursor(tui_textarea::CursorMove::Forward),
+            CursorMove::Up => {
+                if let Some(row) = self.cursor().0.checked_sub(1) {
+                    self.move_to_row(row);
+                }
+            }
+            CursorMove::Down => self.move_to_row(self.cursor().0 + 1),
+            CursorMove::Left => {
+                let (row, col) = self.cursor();
+                match grapheme_columns(self.current_line())
+                    .into_iter()
+                    .rev()
+                    .find(|start| *start < col)
+                {
+                    Some(col) => self
+                        .textarea
+                        .move_cursor(tui_textarea::CursorMove::Jump(row as u16, col as u16)),
+                    None => self.textarea.move_cursor(tui_textarea::CursorMove::Back),
+                }
+            }
+            CursorMove::Right => {
+                let (row, col) = self.cursor();
+                match grapheme_columns(self.current_line())
+                    .into_iter()
+                    .find(|start| *start > col)
+                {
+                    Some(col) => self
+                        .textarea
+                        .move_cursor(tui_textarea::CursorMove::Jump(row as u16, col as u16)),
+                    None => self.textarea.move_cursor(tui_textarea::CursorMove::Forward),
+                }
+            }
             CursorMove::WordForward => self
                 .textarea
                 .move_cursor(tui_textarea::CursorMove::WordForward),
@@ -138,3 +307,64 @@ impl<'a> TextBuffer<'a> {
         self.textarea.cursor()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: Key) -> Input {
+        Input {
+            key,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_grapheme_cursor_moves() {
+        // The thumbs up with a skin tone modifier and the e with a combining acute accent are
+        // both two characters.
+        let mut buffer = TextBuffer::from("a👍🏽e\u{301}b");
+
+        let cols: Vec<_> = (0..5)
+            .map(|_| {
+                buffer.cursor_move(CursorMove::Right);
+                buffer.cursor().1
+            })
+            .collect();
+        assert_eq!(cols, vec![1, 3, 5, 6, 6]);
+
+        buffer.edit(key(Key::Left));
+        buffer.edit(key(Key::Left));
+        assert_eq!(buffer.cursor(), (0, 3));
+
+        buffer.edit(key(Key::Backspace));
+        assert_eq!(buffer.to_string(), "ae\u{301}b");
+        assert_eq!(buffer.cursor(), (0, 1));
+
+        buffer.edit(key(Key::Delete));
+        assert_eq!(buffer.to_string(), "ab");
+        assert!(buffer.modified());
+    }
+
+    #[test]
+    fn test_wide_character_cursor_moves() {
+        let mut buffer = TextBuffer::from("日本語\nabcdef\nx");
+
+        buffer.cursor_move(CursorMove::Jump(0, 2));
+        buffer.cursor_move(CursorMove::Down);
+        assert_eq!(buffer.cursor(), (1, 4));
+
+        buffer.cursor_move(CursorMove::Right);
+        buffer.cursor_move(CursorMove::Up);
+        assert_eq!(buffer.cursor(), (0, 2));
+
+        buffer.cursor_move(CursorMove::Jump(1, 5));
+        buffer.cursor_move(CursorMove::Up);
+        assert_eq!(buffer.cursor(), (0, 2));
+
+        buffer.cursor_move(CursorMove::Jump(0, 3));
+        buffer.cursor_move(CursorMove::Down);
+        buffer.cursor_move(CursorMove::Down);
+        assert_eq!(buffer.cursor(), (2, 1));
+    }
+}
@@ -0,0 +1,395 @@
+//! Git history of the open note: the commits that changed the note, newest first, with the
+//! changes of the selected commit. A version can be restored into the note editor, where it can be
+//! saved like any other change.
+use std::{
+    path::{Path, PathBuf},
+    process,
+};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout, Rect, Size},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Padding, Paragraph, StatefulWidget,
+        Widget,
+    },
+};
+
+use crate::{
+    app::{calc_scroll_amount, Message as AppMessage, ScrollAmount},
+    chrome::Chrome,
+    diff,
+    note_editor::EditorState,
+    responsive,
+};
+
+/// Separates the commits in the git log output.
+const RECORD_SEPARATOR: char = '\x1e';
+
+/// A commit that changed the note.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commit {
+    /// Abbreviated hash.
+    pub hash: String,
+    /// Author date, e.g. `2025-03-07`.
+    pub date: String,
+    pub subject: String,
+    /// Path of the note in the commit relative to the repository root, which differs from the
+    /// current path for commits before the note was renamed.
+    pub path: String,
+}
+
+/// Parses the output of `git log --name-only` with the format of [`read_log`].
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::note_history_modal::parse_log;
+///
+/// let log = "\x1ea1b2c3d\t2025-03-07\tRename\n\nnotes/Pond.md\n\x1ee4f5a6b\t2025-03-01\tAdd\n\nPond.md\n";
+/// let commits = parse_log(log);
+///
+/// assert_eq!(commits.len(), 2);
+/// assert_eq!((commits[1].hash.as_str(), commits[1].path.as_str()), ("e4f5a6b", "Pond.md"));
+/// ```
+pub fn parse_log(log: &str) -> Vec<Commit> {
+    log.split(RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let mut lines = record.lines();
+            let mut fields = lines.next()?.splitn(3, '\t');
+            let (hash, date, subject) = (fields.next()?, fields.next()?, fields.next()?);
+            let path = lines.map(str::trim).find(|line| !line.is_empty())?;
+
+            Some(Commit {
+                hash: hash.to_string(),
+                date: date.to_string(),
+                subject: subject.to_string(),
+                path: path.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Runs git in the folder of the note and returns its output. Fails when git is not installed or
+/// the folder is not in a git repository.
+fn git(note_path: &Path, args: &[&str]) -> Result<String, String> {
+    let dir = note_path.parent().ok_or("Note has no parent directory")?;
+
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|err| err.to_string())?;
+
+    match output.status.success() {
+        true => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        false => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+    }
+}
+
+/// Lists the commits that changed the note, following renames.
+fn read_log(path: &Path) -> Result<Vec<Commit>, String> {
+    let file_name = path
+        .file_name()
+        .ok_or("Note has no file name")?
+        .to_string_lossy();
+
+    git(
+        path,
+        &[
+            "log",
+            "--follow",
+            "--name-only",
+            &format!("--format={RECORD_SEPARATOR}%h%x09%as%x09%s"),
+            "--",
+            &file_name,
+        ],
+    )
+    .map(|log| parse_log(&log))
+}
+
+/// Reads the note as it was in the commit.
+fn read_version(note_path: &Path, commit: &Commit) -> Result<String, String> {
+    git(
+        note_path,
+        &["show", &format!("{}:{}", commit.hash, commit.path)],
+    )
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    Close,
+    Up,
+    Down,
+    ScrollUp(ScrollAmount),
+    ScrollDown(ScrollAmount),
+    /// Replaces the note editor content with the note as it was in the selected commit.
+    Restore,
+}
+
+pub fn update<'a>(
+    message: &Message,
+    screen_size: Size,
+    editor: &EditorState,
+    state: &mut NoteHistoryModalState,
+) -> Option<AppMessage<'a>> {
+    let diff_height = diff_area(Rect::new(0, 0, screen_size.width, screen_size.height)).height;
+
+    match message {
+        Message::Toggle if state.visible => state.hide(),
+        Message::Toggle => {
+            if editor.path().as_os_str().is_empty() {
+                return None;
+            }
+
+            state.visible = true;
+            state.load(editor.path());
+        }
+        Message::Close => state.hide(),
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::ScrollUp(scroll_amount) => {
+            state.diff_scroll = state
+                .diff_scroll
+                .saturating_sub(calc_scroll_amount(scroll_amount, diff_height.into()));
+        }
+        Message::ScrollDown(scroll_amount) => {
+            state.diff_scroll = state
+                .diff_scroll
+                .saturating_add(calc_scroll_amount(scroll_amount, diff_height.into()))
+                .min(state.diff.len().saturating_sub(1));
+        }
+        Message::Restore => {
+            let commit = state.selected_item()?;
+            let message = match read_version(&state.path, commit) {
+                Ok(content) => AppMessage::RestoreNoteVersion(content, commit.hash.clone()),
+                Err(err) => AppMessage::Error(format!("Could not read {}: {err}", commit.hash)),
+            };
+
+            state.hide();
+            return Some(message);
+        }
+    };
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NoteHistoryModalState {
+    pub(crate) commits: Vec<Commit>,
+    pub(crate) list_state: ListState,
+    /// Rendered changes of the selected commit.
+    pub(crate) diff: Vec<Line<'static>>,
+    pub(crate) diff_scroll: usize,
+    /// Path of the note whose history is listed.
+    pub(crate) path: PathBuf,
+    pub(crate) error: Option<String>,
+    pub(crate) visible: bool,
+}
+
+impl NoteHistoryModalState {
+    fn load(&mut self, path: &Path) {
+        self.path = path.to_path_buf();
+
+        match read_log(path) {
+            Ok(commits) => {
+                self.commits = commits;
+                self.error = None;
+            }
+            Err(error) => {
+                self.commits.clear();
+                self.error = Some(error);
+            }
+        }
+
+        self.select(0);
+    }
+
+    pub fn selected_item(&self) -> Option<&Commit> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.commits.get(index))
+    }
+
+    fn select(&mut self, index: usize) {
+        self.list_state.select(match self.commits.is_empty() {
+            true => None,
+            false => Some(index.min(self.commits.len() - 1)),
+        });
+        self.load_diff();
+    }
+
+    /// Compares the selected version with the version of the commit before it. Nothing changed
+    /// the note in between, as the commits are all the commits that changed the note.
+    fn load_diff(&mut self) {
+        self.diff_scroll = 0;
+
+        let Some(index) = self.list_state.selected() else {
+            self.diff.clear();
+            return;
+        };
+
+        let version = |index: usize| {
+            self.commits
+                .get(index)
+                .and_then(|commit| read_version(&self.path, commit).ok())
+                .unwrap_or_default()
+        };
+
+        let (old, new) = (version(index + 1), version(index));
+        self.diff = diff::render_lines(&diff::diff_lines(&old, &new));
+    }
+
+    pub fn next(&mut self) {
+        let index = self.list_state.selected().map_or(0, |index| index + 1);
+        self.select(index);
+    }
+
+    pub fn previous(&mut self) {
+        let index = self.list_state.selected().unwrap_or_default();
+        self.select(index.saturating_sub(1));
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn list_item(commit: &Commit) -> ListItem<'static> {
+        ListItem::new(Line::from(vec![
+            Span::from(format!("{} ", commit.hash)).yellow(),
+            Span::from(format!("{} ", commit.date)).dark_gray(),
+            Span::from(commit.subject.clone()),
+        ]))
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(80), Constraint::Percentage(80))
+}
+
+fn layout(area: Rect) -> [Rect; 2] {
+    // Borders and padding of the modal
+    let inner = Rect {
+        x: area.x + 2,
+        y: area.y + 1,
+        width: area.width.saturating_sub(4),
+        height: area.height.saturating_sub(2),
+    };
+
+    Layout::vertical([Constraint::Percentage(35), Constraint::Fill(1)]).areas(inner)
+}
+
+fn diff_area(area: Rect) -> Rect {
+    let [_, diff] = layout(modal_area(area));
+    diff
+}
+
+#[derive(Default)]
+pub struct NoteHistoryModal {
+    chrome: Chrome,
+}
+
+impl NoteHistoryModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for NoteHistoryModal {
+    type State = NoteHistoryModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let name = state.path.file_stem().unwrap_or_default().to_string_lossy();
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(" History: {name} "))
+            .title(
+                Line::from(format!(" {} commits ", state.commits.len()))
+                    .alignment(Alignment::Right),
+            )
+            .title_bottom(
+                Line::from(" (enter) restore this version · (esc) close ")
+                    .alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let [list_area, diff_area] = layout(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if let Some(error) = &state.error {
+            Paragraph::new(error.as_str())
+                .red()
+                .render(list_area.union(diff_area), buf);
+            return;
+        }
+
+        if state.commits.is_empty() {
+            Paragraph::new("No commits changed the note")
+                .dark_gray()
+                .italic()
+                .render(list_area, buf);
+            return;
+        }
+
+        let items: Vec<_> = state
+            .commits
+            .iter()
+            .map(NoteHistoryModalState::list_item)
+            .collect();
+
+        StatefulWidget::render(
+            List::new(items).highlight_style(Style::default().reversed().dark_gray()),
+            list_area,
+            buf,
+            &mut state.list_state,
+        );
+
+        Paragraph::new(state.diff.clone())
+            .block(Block::new().borders(Borders::TOP).dark_gray())
+            .scroll((state.diff_scroll as u16, 0))
+            .render(diff_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log() {
+        let log = "\x1ea1b2c3d\t2025-03-07\tMove pond: notes\tand tabs\n\nGarden/Pond.md\n\
+                   \x1ee4f5a6b\t2025-03-01\tAdd pond\n\nPond.md\n\
+                   \x1ef0f0f0f\t2025-02-01\tMerge without files\n";
+
+        assert_eq!(
+            parse_log(log),
+            [
+                Commit {
+                    hash: "a1b2c3d".into(),
+                    date: "2025-03-07".into(),
+                    subject: "Move pond: notes\tand tabs".into(),
+                    path: "Garden/Pond.md".into(),
+                },
+                Commit {
+                    hash: "e4f5a6b".into(),
+                    date: "2025-03-01".into(),
+                    subject: "Add pond".into(),
+                    path: "Pond.md".into(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Outgoing links of the open note: its wikilinks, markdown links and embeds in the order they
+//! appear, with the file each link points to. Broken links, which point to no file in the vault,
+//! are marked. A link can be opened, or jumped to in the note.
+use std::path::{Path, PathBuf};
+
+use basalt_core::obsidian::{Link, LinkIndex, LinkKind, Note, Vault, VaultIndex};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{app::Message as AppMessage, chrome::Chrome, responsive};
+
+/// A link of the open note.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutgoingLink {
+    pub link: Link,
+    /// `true` for embeds, e.g. `![[Image.png]]`.
+    pub embed: bool,
+    /// Path of the linked file, or `None` for external and broken links.
+    pub target: Option<PathBuf>,
+}
+
+impl OutgoingLink {
+    /// Returns `true` if the link points to no file in the vault.
+    pub fn is_broken(&self) -> bool {
+        self.target.is_none() && !self.link.is_external()
+    }
+
+    /// The link as written, without headings, aliases or link text, e.g. `![[Image.png]]`.
+    fn label(&self) -> String {
+        let embed = if self.embed { "!" } else { "" };
+        match self.link.kind {
+            LinkKind::Wiki => format!("{embed}[[{}]]", self.link.target),
+            LinkKind::Markdown => format!("{embed}({})", self.link.target),
+        }
+    }
+}
+
+/// Returns `true` if the link is preceded by `!`, i.e. `![[Target]]` or `![Text](Target)`.
+fn is_embed(contents: &str, link: &Link) -> bool {
+    let before = &contents[..link.range.start];
+    let opening = match link.kind {
+        LinkKind::Wiki => before.strip_suffix("[["),
+        LinkKind::Markdown => before
+            .strip_suffix("](")
+            .and_then(|before| before.rfind('[').map(|start| &before[..start])),
+    };
+
+    opening.is_some_and(|before| before.ends_with('!'))
+}
+
+/// Returns the links of the note at the path with the given contents, resolved with the index.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::LinkIndex;
+/// use basalt_tui::outgoing_links_modal::outgoing_links;
+/// use std::path::Path;
+///
+/// let index = LinkIndex::new(Path::new("vault"), vec!["vault/Shed.png".into()]);
+/// let links = outgoing_links("![[Shed.png]] [[Missing]]", Path::new("vault/Note.md"), &index);
+///
+/// assert!(links[0].embed && !links[0].is_broken());
+/// assert!(!links[1].embed && links[1].is_broken());
+/// ```
+pub fn outgoing_links(contents: &str, path: &Path, index: &LinkIndex) -> Vec<OutgoingLink> {
+    Link::parse(contents)
+        .into_iter()
+        .map(|link| OutgoingLink {
+            embed: is_embed(contents, &link),
+            target: index.resolve(path, &link),
+            link,
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    Close,
+    Up,
+    Down,
+    /// Opens the file the selected link points to, or an external link with the opener of the
+    /// `[attachments]` section.
+    Open,
+    /// Moves the cursor of the note editor to the selected link.
+    Jump,
+}
+
+pub fn update<'a>(
+    message: &Message,
+    vault: Option<&Vault>,
+    index: &VaultIndex,
+    note: Option<(&Path, &str)>,
+    state: &mut OutgoingLinksModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Toggle if state.visible => state.hide(),
+        Message::Toggle => {
+            let vault = vault?;
+            let (path, contents) = note?;
+
+            state.vault_path = vault.path.clone();
+            state.note = path.to_path_buf();
+            state.links = outgoing_links(contents, path, &index.link_index(vault));
+            state
+                .list_state
+                .select((!state.links.is_empty()).then_some(0));
+            state.visible = true;
+        }
+        Message::Close => state.hide(),
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::Open => {
+            let link = state.selected_item()?;
+            let message = match &link.target {
+                Some(path) if path.extension().is_some_and(|ext| ext == "md") => {
+                    AppMessage::OpenNote(Note::from(path.as_path()))
+                }
+                Some(path) => AppMessage::OpenAttachment(path.clone()),
+                None if link.link.is_external() => {
+                    AppMessage::OpenAttachment(PathBuf::from(&link.link.target))
+                }
+                None => {
+                    return Some(AppMessage::SetStatus(format!(
+                        "No note or file for {}",
+                        link.link.target
+                    )))
+                }
+            };
+
+            state.hide();
+            return Some(message);
+        }
+        Message::Jump => {
+            let offset = state.selected_item()?.link.range.start;
+            state.hide();
+            return Some(AppMessage::JumpToOffset(offset));
+        }
+    };
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OutgoingLinksModalState {
+    pub(crate) links: Vec<OutgoingLink>,
+    pub(crate) list_state: ListState,
+    /// Path of the note whose links are listed.
+    pub(crate) note: PathBuf,
+    pub(crate) vault_path: PathBuf,
+    pub(crate) visible: bool,
+}
+
+impl OutgoingLinksModalState {
+    pub fn selected_item(&self) -> Option<&OutgoingLink> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.links.get(index))
+    }
+
+    pub fn next(&mut self) {
+        if !self.links.is_empty() {
+            let index = self.list_state.selected().map_or(0, |index| index + 1);
+            self.list_state
+                .select(Some(index.min(self.links.len() - 1)));
+        }
+    }
+
+    pub fn previous(&mut self) {
+        let index = self.list_state.selected().unwrap_or_default();
+        self.list_state.select(Some(index.saturating_sub(1)));
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn list_item(&self, link: &OutgoingLink) -> ListItem<'static> {
+        let target = match &link.target {
+            Some(path) => Span::from(format!(
+                " → {}",
+                path.strip_prefix(&self.vault_path)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+            ))
+            .dark_gray(),
+            None if link.link.is_external() => Span::from(" external").dark_gray(),
+            None => Span::from(" broken").red(),
+        };
+
+        let marker = match link.is_broken() {
+            true => Span::from("✗ ").red(),
+            false => Span::from("  "),
+        };
+
+        ListItem::new(Line::from(vec![marker, Span::from(link.label()), target]))
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(60), Constraint::Percentage(60))
+}
+
+#[derive(Default)]
+pub struct OutgoingLinksModal {
+    chrome: Chrome,
+}
+
+impl OutgoingLinksModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for OutgoingLinksModal {
+    type State = OutgoingLinksModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let name = state.note.file_stem().unwrap_or_default().to_string_lossy();
+        let broken = state.links.iter().filter(|link| link.is_broken()).count();
+        let summary = match broken {
+            0 => format!(" {} links ", state.links.len()),
+            broken => format!(" {} links, {broken} broken ", state.links.len()),
+        };
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(" Links of {name} "))
+            .title(Line::from(summary).alignment(Alignment::Right))
+            .title_bottom(
+                Line::from(" (enter) open · (g) jump to link · (esc) close ")
+                    .alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if state.links.is_empty() {
+            Paragraph::new("No links in the note")
+                .dark_gray()
+                .italic()
+                .render(inner_area, buf);
+            return;
+        }
+
+        let items: Vec<_> = state
+            .links
+            .iter()
+            .map(|link| state.list_item(link))
+            .collect();
+
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_style(Style::default().reversed().dark_gray())
+                .highlight_symbol(""),
+            inner_area,
+            buf,
+            &mut state.list_state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outgoing_links() {
+        let index = LinkIndex::new(
+            Path::new("vault"),
+            vec!["vault/Shed.png".into(), "vault/Garden/Pond.md".into()],
+        );
+        let contents =
+            "![[Shed.png]] [[Pond#Fish]] ![plan](Garden/Pond.md) [site](https://a.b) [[Roof]]";
+
+        let links = outgoing_links(contents, Path::new("vault/Note.md"), &index);
+
+        let labels: Vec<_> = links
+            .iter()
+            .map(|link| (link.label(), link.embed, link.is_broken()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                ("![[Shed.png]]".to_string(), true, false),
+                ("[[Pond]]".into(), false, false),
+                ("!(Garden/Pond.md)".into(), true, false),
+                ("(https://a.b)".into(), false, false),
+                ("[[Roof]]".into(), false, true),
+            ]
+        );
+
+        let targets: Vec<_> = links.into_iter().map(|link| link.target).collect();
+        assert_eq!(
+            targets,
+            [
+                Some("vault/Shed.png".into()),
+                Some("vault/Garden/Pond.md".into()),
+                Some("vault/Garden/Pond.md".into()),
+                None,
+                None,
+            ]
+        );
+    }
+}
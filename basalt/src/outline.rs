@@ -1,4 +1,4 @@
-use item::{Flatten, Item};
+use item::{BlockKind, Flatten, Item};
 pub use state::OutlineState;
 
 mod item;
@@ -9,45 +9,61 @@ use ratatui::{
     layout::{Alignment, Rect},
     style::{Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, List, ListItem, Padding, StatefulWidget},
+    widgets::{Borders, List, ListItem, Padding, StatefulWidget},
 };
 
 use crate::{
     app::{ActivePane, Message as AppMessage},
+    chrome::Chrome,
     explorer,
     note_editor::{self, markdown_parser::Node},
+    truncate,
 };
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Message {
     Up,
     Down,
+    /// Selects the first item.
+    GotoTop,
+    /// Selects the last item.
+    GotoBottom,
     Select,
     SelectAt(usize),
     SetNodes(Vec<Node>),
     Expand,
+    CycleDepth,
     Toggle,
     ToggleExplorer,
     SwitchPaneNext,
     SwitchPanePrevious,
+    /// Shows the whole heading or block under the selector in the status bar, for contents that
+    /// do not fit the pane.
+    ShowHeading,
+    /// Renames the heading under the selector and the links to it across the vault.
+    RenameHeading,
 }
 
 pub fn update<'a>(message: &Message, state: &mut OutlineState) -> Option<AppMessage<'a>> {
     match message {
         Message::Up => state.previous(1),
         Message::Down => state.next(1),
+        Message::GotoTop => state.first(),
+        Message::GotoBottom => state.last(),
         Message::Expand => state.toggle_item(),
         Message::SelectAt(index) => state.select_at(*index),
         Message::SetNodes(nodes) => state.set_nodes(nodes),
-
-        Message::SwitchPaneNext => {
-            state.set_active(false);
-            return Some(AppMessage::SetActivePane(ActivePane::Explorer));
-        }
-        Message::SwitchPanePrevious => {
-            state.set_active(false);
-            return Some(AppMessage::SetActivePane(ActivePane::NoteEditor));
+        Message::CycleDepth => {
+            state.cycle_depth();
+            return Some(AppMessage::SetStatus(match state.config.max_depth {
+                Some(1) => "Outline shows H1 headings".into(),
+                Some(depth) => format!("Outline shows H1–H{depth} headings"),
+                None => "Outline shows all headings".into(),
+            }));
         }
+
+        Message::SwitchPaneNext => return Some(AppMessage::SwitchPaneNext),
+        Message::SwitchPanePrevious => return Some(AppMessage::SwitchPanePrevious),
         Message::Toggle => {
             state.toggle();
             if !state.is_open() {
@@ -62,6 +78,16 @@ pub fn update<'a>(message: &Message, state: &mut OutlineState) -> Option<AppMess
                 )));
             }
         }
+        Message::ShowHeading => {
+            let item = state.selected()?;
+            return Some(AppMessage::SetStatus(item.content().to_string()));
+        }
+        Message::RenameHeading => {
+            return Some(match state.selected()? {
+                Item::Block { .. } => AppMessage::SetStatus("Only headings can be renamed".into()),
+                item => AppMessage::HeadingRename(item.content().to_string()),
+            });
+        }
         Message::ToggleExplorer => {
             return Some(AppMessage::Explorer(explorer::Message::Toggle));
         }
@@ -71,10 +97,21 @@ pub fn update<'a>(message: &Message, state: &mut OutlineState) -> Option<AppMess
 }
 
 #[derive(Default)]
-pub struct Outline;
+pub struct Outline {
+    chrome: Chrome,
+}
+
+impl Outline {
+    /// Sets the borders, padding and title alignment of the pane.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
 
 trait AsListItems {
-    fn to_list_items(&self) -> Vec<ListItem<'_>>;
+    /// Returns the list items of the headings and blocks. Contents longer than the width are
+    /// shortened in the middle.
+    fn to_list_items(&self, width: usize) -> Vec<ListItem<'_>>;
     fn to_collapsed_items(&self) -> Vec<ListItem<'_>>;
 }
 
@@ -83,7 +120,9 @@ impl AsListItems for Vec<Item> {
         self.flatten()
             .iter()
             .map(|item| match item {
-                Item::Heading { .. } => ListItem::new(Line::from("·")).dark_gray().dim(),
+                Item::Heading { .. } | Item::Block { .. } => {
+                    ListItem::new(Line::from("·")).dark_gray().dim()
+                }
                 Item::HeadingEntry { expanded: true, .. } => {
                     ListItem::new(Line::from("✺")).red().dim()
                 }
@@ -94,23 +133,40 @@ impl AsListItems for Vec<Item> {
             .collect()
     }
 
-    fn to_list_items(&self) -> Vec<ListItem<'_>> {
-        fn list_item<'a>(indentation: Span<'a>, symbol: &'a str, content: &'a str) -> ListItem<'a> {
-            ListItem::new(Line::from(
-                [indentation, symbol.into(), content.into()].to_vec(),
-            ))
-        }
-
-        fn to_list_items(depth: usize) -> impl Fn(&Item) -> Vec<ListItem> {
+    fn to_list_items(&self, width: usize) -> Vec<ListItem<'_>> {
+        fn to_list_items(depth: usize, width: usize) -> impl Fn(&Item) -> Vec<ListItem> {
             let indentation = if depth > 0 {
                 Span::raw("│ ".repeat(depth)).black()
             } else {
                 Span::raw("  ".repeat(depth)).black()
             };
+            // The indentation and the symbol before the content.
+            let content_width = width.saturating_sub(2 * depth + 2);
+            let list_item =
+                move |indentation: Span<'static>, symbol: &'static str, content: &str| {
+                    ListItem::new(Line::from(
+                        [
+                            indentation,
+                            symbol.into(),
+                            truncate::middle(content, content_width).into(),
+                        ]
+                        .to_vec(),
+                    ))
+                };
+
             move |item| match item {
                 Item::Heading { content, .. } => {
                     vec![list_item(indentation.clone(), "  ", content)]
                 }
+                Item::Block { content, kind, .. } => {
+                    let symbol = match kind {
+                        BlockKind::ListItem => "• ",
+                        BlockKind::Task { done: false } => "☐ ",
+                        BlockKind::Task { done: true } => "☑ ",
+                        BlockKind::CodeBlock => "λ ",
+                    };
+                    vec![list_item(indentation.clone(), symbol, content).dark_gray()]
+                }
                 Item::HeadingEntry {
                     expanded: true,
                     children,
@@ -118,7 +174,7 @@ impl AsListItems for Vec<Item> {
                     ..
                 } => {
                     let mut items = vec![list_item(indentation.clone(), "▾ ", content)];
-                    items.extend(children.iter().flat_map(to_list_items(depth + 1)));
+                    items.extend(children.iter().flat_map(to_list_items(depth + 1, width)));
                     items
                 }
                 Item::HeadingEntry {
@@ -129,7 +185,7 @@ impl AsListItems for Vec<Item> {
             }
         }
 
-        self.iter().flat_map(to_list_items(0)).collect()
+        self.iter().flat_map(to_list_items(0, width)).collect()
     }
 }
 
@@ -137,23 +193,20 @@ impl StatefulWidget for Outline {
     type State = OutlineState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let block = Block::bordered()
-            .border_type(if state.active {
-                BorderType::Thick
-            } else {
-                BorderType::Rounded
-            })
+        let block = self
+            .chrome
+            .block(state.active)
             .title(if state.is_open() {
                 " ▶ Outline "
             } else {
                 " ◀ "
             })
-            .title_alignment(Alignment::Right)
-            .padding(Padding::horizontal(1))
+            .title_alignment(self.chrome.title_alignment(Alignment::Right))
+            .padding(self.chrome.padding(Padding::horizontal(1)))
             .title_style(Style::default().italic().bold());
 
         let items = if state.is_open() {
-            state.items.to_list_items()
+            state.items.to_list_items(block.inner(area).width.into())
         } else {
             state.items.to_collapsed_items()
         };
@@ -162,7 +215,10 @@ impl StatefulWidget for Outline {
             .block(if state.is_open() {
                 block
             } else {
-                block.borders(Borders::RIGHT | Borders::TOP | Borders::BOTTOM)
+                block.borders(
+                    self.chrome
+                        .borders(Borders::RIGHT | Borders::TOP | Borders::BOTTOM),
+                )
             })
             .highlight_style(Style::default().reversed().dark_gray())
             .highlight_symbol("")
@@ -172,7 +228,7 @@ impl StatefulWidget for Outline {
 
 #[cfg(test)]
 mod tests {
-    use crate::note_editor::markdown_parser;
+    use crate::{config::OutlineConfig, note_editor::markdown_parser};
 
     use super::*;
     use indoc::indoc;
@@ -327,12 +383,153 @@ mod tests {
 
         tests.into_iter().for_each(|(name, nodes)| {
             _ = terminal.clear();
-            let mut state = OutlineState::new(&nodes, 0, true);
+            let mut state = OutlineState::new(&nodes, 0, true, OutlineConfig::default());
             state.expand_all();
             terminal
-                .draw(|frame| Outline.render(frame.area(), frame.buffer_mut(), &mut state))
+                .draw(|frame| {
+                    Outline::default().render(frame.area(), frame.buffer_mut(), &mut state)
+                })
                 .unwrap();
             assert_snapshot!(name, terminal.backend());
         });
     }
+
+    #[test]
+    fn test_outline_render_blocks() {
+        let nodes = markdown_parser::from_str(indoc! {r#"
+            # Groceries
+            - Milk
+            - Eggs
+
+            ## Chores
+            - [ ] Laundry
+            - [x] Dishes
+
+            ```sh
+            make clean
+            ```
+
+            ```
+            cargo test
+            ```
+        "#});
+
+        let config = OutlineConfig {
+            list_items: true,
+            tasks: true,
+            code_blocks: true,
+            ..Default::default()
+        };
+
+        let mut terminal = Terminal::new(TestBackend::new(30, 12)).unwrap();
+        let mut state = OutlineState::new(&nodes, 0, true, config);
+        terminal
+            .draw(|frame| Outline::default().render(frame.area(), frame.buffer_mut(), &mut state))
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+
+        state.config.tasks = false;
+        state.set_nodes(&nodes);
+        let selected_at = |state: &mut OutlineState, row| {
+            state.select_at(row);
+            state.selected().map(|item| item.content().to_string())
+        };
+        assert_eq!(selected_at(&mut state, 3), Some("Chores".into()));
+        assert_eq!(selected_at(&mut state, 4), Some("sh".into()));
+    }
+
+    #[test]
+    fn test_cycle_depth() {
+        let nodes = markdown_parser::from_str(indoc! {r#"
+            # Heading 1
+            ## Heading 1.1
+            ### Heading 1.1.1
+            # Heading 2
+        "#});
+
+        let contents = |state: &OutlineState| {
+            state
+                .items
+                .flatten()
+                .iter()
+                .map(|item| item.content().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let config = OutlineConfig {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let mut state = OutlineState::new(&nodes, 0, true, config);
+        assert_eq!(contents(&state), ["Heading 1", "Heading 1.1", "Heading 2"]);
+
+        state.select_at(2);
+        assert_eq!(
+            state.selected().map(|item| item.content().to_string()),
+            Some("Heading 1.1".into())
+        );
+
+        state.cycle_depth();
+        assert_eq!(contents(&state).len(), 4);
+
+        state.cycle_depth();
+        assert_eq!(contents(&state), ["Heading 1", "Heading 2"]);
+        assert_eq!(
+            state.selected().map(|item| item.content().to_string()),
+            Some("Heading 1".into())
+        );
+    }
+
+    #[test]
+    fn test_set_nodes_keeps_state() {
+        let nodes = markdown_parser::from_str(indoc! {r#"
+            # Heading 1
+            ## Heading 1.1
+            # Heading 2
+            ## Heading 2.1
+            # Heading 3
+        "#});
+
+        let selected =
+            |state: &OutlineState| state.selected().map(|item| item.content().to_string());
+
+        let mut state = OutlineState::new(&nodes, 0, true, OutlineConfig::default());
+        update(&Message::Toggle, &mut state);
+        update(&Message::Toggle, &mut state);
+        assert!(state.is_open());
+        assert_eq!(selected(&state), Some("Heading 1".into()));
+
+        state.toggle_item();
+        update(&Message::Down, &mut state);
+        update(&Message::Down, &mut state);
+        assert_eq!(selected(&state), Some("Heading 2.1".into()));
+
+        // A heading added above the selection moves the selected heading down.
+        let nodes = markdown_parser::from_str(indoc! {r#"
+            # Heading 0
+            # Heading 1
+            ## Heading 1.1
+            # Heading 2
+            ## Heading 2.1
+            # Heading 3
+        "#});
+        state.set_nodes(&nodes);
+
+        assert_eq!(selected(&state), Some("Heading 2.1".into()));
+        assert_eq!(
+            state
+                .items
+                .flatten()
+                .iter()
+                .map(|item| item.content())
+                .collect::<Vec<_>>(),
+            [
+                "Heading 0",
+                "Heading 1",
+                "Heading 2",
+                "Heading 2.1",
+                "Heading 3"
+            ]
+        );
+    }
 }
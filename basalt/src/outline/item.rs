@@ -1,5 +1,13 @@
 use std::ops::Range;
 
+/// Kind of a block that is listed in the outline next to the headings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockKind {
+    ListItem,
+    Task { done: bool },
+    CodeBlock,
+}
+
 // TODO: More generic naming to use this pattern in explorer too
 #[derive(Debug, Clone, PartialEq)]
 pub enum Item {
@@ -13,12 +21,27 @@ pub enum Item {
         children: Vec<Item>,
         expanded: bool,
     },
+    /// A top-level list item, task or code block.
+    Block {
+        range: Range<usize>,
+        content: String,
+        kind: BlockKind,
+    },
 }
 
 impl Item {
     pub fn get_range(&self) -> &Range<usize> {
         match self {
-            Item::Heading { range, .. } | Item::HeadingEntry { range, .. } => range,
+            Item::Heading { range, .. }
+            | Item::HeadingEntry { range, .. }
+            | Item::Block { range, .. } => range,
+        }
+    }
+    pub fn content(&self) -> &str {
+        match self {
+            Item::Heading { content, .. }
+            | Item::HeadingEntry { content, .. }
+            | Item::Block { content, .. } => content,
         }
     }
     fn contains_index(&self, index: usize) -> bool {
@@ -29,6 +52,7 @@ impl Item {
 fn flatten(item: &Item) -> Vec<Item> {
     match item {
         Item::Heading { .. }
+        | Item::Block { .. }
         | Item::HeadingEntry {
             expanded: false, ..
         } => {
@@ -2,9 +2,13 @@ use std::{iter::Peekable, ops::Range, slice::Iter};
 
 use ratatui::widgets::ListState;
 
-use crate::note_editor::markdown_parser::{HeadingLevel, MarkdownNode, Node};
+use crate::{
+    config::OutlineConfig,
+    note_editor::markdown_parser::{MarkdownNode, Node, TaskListItemKind},
+    type_ahead,
+};
 
-use super::item::{FindItem, Flatten, Item};
+use super::item::{BlockKind, FindItem, Flatten, Item};
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct OutlineState {
@@ -14,37 +18,51 @@ pub struct OutlineState {
     pub(crate) open: bool,
     pub(crate) list_state: ListState,
     pub(crate) active: bool,
+    /// Blocks listed next to the headings, and the deepest heading level listed.
+    pub(crate) config: OutlineConfig,
+    /// Nodes of the note, to list the headings again when the depth changes.
+    pub(crate) nodes: Vec<Node>,
 }
 
+/// Depth of the blocks in the outline, below the deepest heading level.
+const BLOCK_DEPTH: usize = 7;
+
+/// A heading, or a block listed in the outline. Headings have the depth of their level, and
+/// blocks are nested below the heading before them.
 #[derive(Debug, Clone, PartialEq)]
 struct Heading {
     index: usize,
-    level: HeadingLevel,
+    depth: usize,
     content: String,
+    block: Option<BlockKind>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 struct HeadingEntry {
     range: Range<usize>,
-    level: HeadingLevel,
     content: String,
+    block: Option<BlockKind>,
     children: Vec<HeadingEntry>,
 }
 
 impl From<HeadingEntry> for Item {
     fn from(value: HeadingEntry) -> Self {
-        if value.children.is_empty() {
-            Item::Heading {
+        match value.block {
+            Some(kind) => Item::Block {
                 range: value.range,
                 content: value.content,
-            }
-        } else {
-            Item::HeadingEntry {
+                kind,
+            },
+            None if value.children.is_empty() => Item::Heading {
+                range: value.range,
+                content: value.content,
+            },
+            None => Item::HeadingEntry {
                 range: value.range,
                 content: value.content,
                 children: value.children.into_iter().map(Item::from).collect(),
                 expanded: false,
-            }
+            },
         }
     }
 }
@@ -52,34 +70,37 @@ impl From<HeadingEntry> for Item {
 fn build_outline_tree(headings: &[Heading], max_end: usize) -> Vec<HeadingEntry> {
     fn build_outline_tree_rec(
         headings: &mut Peekable<Iter<Heading>>,
-        parent_level: Option<HeadingLevel>,
+        parent_depth: Option<usize>,
         max_end: usize,
     ) -> Vec<HeadingEntry> {
         let mut result: Vec<HeadingEntry> = vec![];
 
         while let Some(next_heading) = headings.peek() {
-            if parent_level.is_some_and(|parent_level| next_heading.level <= parent_level) {
+            if parent_depth.is_some_and(|parent_depth| next_heading.depth <= parent_depth) {
                 break;
             }
 
             if let Some(heading) = headings.next() {
-                let next_heading = headings.peek();
+                // Items of the same list share the index of the list node, so the range ends at
+                // the next entry of another node.
                 let range_start = heading.index;
-                let range_end = next_heading
+                let range_end = headings
+                    .clone()
+                    .find(|next_heading| next_heading.index > heading.index)
                     .map(|next_heading| next_heading.index)
                     .unwrap_or(max_end);
 
-                let children = match next_heading {
-                    Some(next_heading) if next_heading.level > heading.level => {
-                        build_outline_tree_rec(headings, Some(heading.level), max_end)
+                let children = match headings.peek() {
+                    Some(next_heading) if next_heading.depth > heading.depth => {
+                        build_outline_tree_rec(headings, Some(heading.depth), max_end)
                     }
                     _ => vec![],
                 };
 
                 result.push(HeadingEntry {
                     range: range_start..range_end,
-                    level: heading.level,
                     content: heading.content.clone(),
+                    block: heading.block,
                     children,
                 });
             }
@@ -91,25 +112,58 @@ fn build_outline_tree(headings: &[Heading], max_end: usize) -> Vec<HeadingEntry>
     build_outline_tree_rec(&mut headings.iter().peekable(), None, max_end)
 }
 
+/// Returns the first line of the text.
+fn first_line(text: impl Into<String>) -> String {
+    let text: String = text.into();
+    text.lines().next().unwrap_or_default().trim().to_string()
+}
+
 trait NodesAsHeadings {
-    fn to_headings(&self) -> Vec<Heading>;
+    fn to_headings(&self, config: &OutlineConfig) -> Vec<Heading>;
 }
 
 impl NodesAsHeadings for &[Node] {
-    fn to_headings(&self) -> Vec<Heading> {
+    fn to_headings(&self, config: &OutlineConfig) -> Vec<Heading> {
+        let block = |index: usize, kind: BlockKind, content: String| Heading {
+            index,
+            depth: BLOCK_DEPTH,
+            content,
+            block: Some(kind),
+        };
+
+        // Blocks are deeper than any heading, so a depth limit hides them too.
+        let within_depth =
+            |heading: &Heading| config.max_depth.is_none_or(|depth| heading.depth <= depth);
+
         self.iter()
             .enumerate()
-            .filter_map(|(index, node)| {
-                if let MarkdownNode::Heading { level, text } = &node.markdown_node {
-                    Some(Heading {
-                        index,
-                        level: *level,
-                        content: text.into(),
-                    })
-                } else {
-                    None
+            .flat_map(|(index, node)| match &node.markdown_node {
+                MarkdownNode::Heading { level, text } => vec![Heading {
+                    index,
+                    depth: *level as usize,
+                    content: text.into(),
+                    block: None,
+                }],
+                MarkdownNode::CodeBlock { lang, text } if config.code_blocks => {
+                    let caption = lang.clone().unwrap_or_else(|| first_line(text));
+                    vec![block(index, BlockKind::CodeBlock, caption)]
                 }
+                MarkdownNode::List { nodes, .. } => nodes
+                    .iter()
+                    .filter_map(|item| match &item.markdown_node {
+                        MarkdownNode::Item { text } if config.list_items => {
+                            Some(block(index, BlockKind::ListItem, first_line(text)))
+                        }
+                        MarkdownNode::TaskListItem { kind, text } if config.tasks => {
+                            let done = *kind != TaskListItemKind::Unchecked;
+                            Some(block(index, BlockKind::Task { done }, first_line(text)))
+                        }
+                        _ => None,
+                    })
+                    .collect(),
+                _ => vec![],
             })
+            .filter(within_depth)
             .collect()
     }
 }
@@ -128,8 +182,8 @@ impl HeadingsAsItems for Vec<Heading> {
 }
 
 impl OutlineState {
-    pub fn new(nodes: &[Node], index: usize, open: bool) -> Self {
-        let headings = nodes.to_headings();
+    pub fn new(nodes: &[Node], index: usize, open: bool, config: OutlineConfig) -> Self {
+        let headings = nodes.to_headings(&config);
         let max_heading_count = headings.len();
 
         let mut state = OutlineState {
@@ -138,19 +192,96 @@ impl OutlineState {
             selected_item_index: None,
             items: headings.to_items(nodes.len()),
             list_state: ListState::default(),
+            config,
+            nodes: nodes.to_vec(),
             ..Default::default()
         };
-        state.select_at(index);
         state.expand_all();
+        state.select_at(index);
         state
     }
 
+    /// Replaces the headings after the note changed. Collapsed headings stay collapsed and the
+    /// selection stays on the same heading, or in the same position if the heading is gone.
     pub fn set_nodes(&mut self, nodes: &[Node]) {
-        let headings = nodes.to_headings();
+        let collapsed = Self::collapsed_contents(&self.items);
+        let selected = self.selected();
+
+        let headings = nodes.to_headings(&self.config);
         let max_heading_count = headings.len();
         self.max_heading_count = max_heading_count;
         self.items = headings.to_items(nodes.len());
+        self.nodes = nodes.to_vec();
         self.expand_all();
+        self.items = Self::collapse_contents(&self.items, &collapsed);
+
+        let items = self.items.flatten();
+        let index = selected.and_then(|selected| {
+            items
+                .iter()
+                .position(|item| item.content() == selected.content())
+                .or(self.list_state.selected())
+                .map(|index| index.min(items.len().saturating_sub(1)))
+        });
+        self.selected_item_index = index;
+        self.list_state.select(index.filter(|_| !items.is_empty()));
+    }
+
+    fn collapsed_contents(items: &[Item]) -> Vec<String> {
+        items
+            .iter()
+            .flat_map(|item| match item {
+                Item::HeadingEntry {
+                    content,
+                    expanded,
+                    children,
+                    ..
+                } => (!expanded)
+                    .then(|| content.clone())
+                    .into_iter()
+                    .chain(Self::collapsed_contents(children))
+                    .collect(),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    fn collapse_contents(items: &[Item], contents: &[String]) -> Vec<Item> {
+        items
+            .iter()
+            .map(|item| match item {
+                Item::HeadingEntry {
+                    range,
+                    content,
+                    children,
+                    expanded,
+                } => Item::HeadingEntry {
+                    range: range.clone(),
+                    content: content.clone(),
+                    children: Self::collapse_contents(children, contents),
+                    expanded: *expanded && !contents.contains(content),
+                },
+                item => item.clone(),
+            })
+            .collect()
+    }
+
+    /// Cycles the listed headings from H1 only to H1 and H2, and to all headings. The selection
+    /// moves to the closest listed heading.
+    pub fn cycle_depth(&mut self) {
+        self.config.max_depth = match self.config.max_depth {
+            Some(1) => Some(2),
+            Some(_) => None,
+            None => Some(1),
+        };
+
+        let row = self.selected().map(|item| item.get_range().start);
+        let nodes = std::mem::take(&mut self.nodes);
+        self.set_nodes(&nodes);
+
+        if let Some(row) = row {
+            self.select_at(row);
+        }
     }
 
     pub fn selected(&self) -> Option<Item> {
@@ -161,6 +292,14 @@ impl OutlineState {
         }
     }
 
+    /// Returns the text of the selected heading, or `None` when a block or nothing is selected.
+    pub fn selected_heading(&self) -> Option<String> {
+        match self.selected()? {
+            Item::Block { .. } => None,
+            item => Some(item.content().to_string()),
+        }
+    }
+
     pub fn set_active(&mut self, active: bool) {
         self.active = active;
     }
@@ -275,4 +414,26 @@ impl OutlineState {
         let index = self.list_state.selected().map(|i| i.saturating_sub(amount));
         self.list_state.select(index);
     }
+
+    /// Moves the selector to the first heading or block that starts with the typed query. Returns
+    /// `false` if no item matches.
+    pub fn type_ahead(&mut self, query: &str) -> bool {
+        let items = self.items.flatten();
+        let start = self.list_state.selected().unwrap_or_default();
+        let index = type_ahead::find(items.iter().map(Item::content), start, query);
+
+        if index.is_some() {
+            self.list_state.select(index);
+        }
+        index.is_some()
+    }
+
+    pub fn first(&mut self) {
+        self.list_state.select(Some(0));
+    }
+
+    pub fn last(&mut self) {
+        self.list_state
+            .select(Some(self.max_heading_count.saturating_sub(1)));
+    }
 }
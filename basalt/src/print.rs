@@ -0,0 +1,145 @@
+//! Prints the open note to stdout when Basalt quits, e.g. `basalt --print | wl-copy`, either as
+//! written or as rendered in read mode with ANSI colors.
+use std::io::Write;
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    crossterm::{
+        queue,
+        style::{Attribute, Colors, ResetColor, SetAttribute, SetColors},
+    },
+    layout::Rect,
+    style::Modifier,
+    text::Line,
+    widgets::{Paragraph, Widget},
+};
+
+use crate::note_editor::{Editor, EditorState};
+
+/// How the note is printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PrintFormat {
+    /// The note as written.
+    #[default]
+    Raw,
+    /// The note as rendered in read mode, with ANSI escape codes for colors and styles.
+    Ansi,
+}
+
+impl PrintFormat {
+    /// Parses the format given to `--print`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::print::PrintFormat;
+    ///
+    /// assert_eq!(PrintFormat::parse("ansi"), Some(PrintFormat::Ansi));
+    /// assert_eq!(PrintFormat::parse("html"), None);
+    /// ```
+    pub fn parse(format: &str) -> Option<Self> {
+        match format {
+            "raw" => Some(PrintFormat::Raw),
+            "ansi" => Some(PrintFormat::Ansi),
+            _ => None,
+        }
+    }
+}
+
+/// Attributes of the modifiers, in the order they are set.
+const ATTRIBUTES: [(Modifier, Attribute); 6] = [
+    (Modifier::BOLD, Attribute::Bold),
+    (Modifier::DIM, Attribute::Dim),
+    (Modifier::ITALIC, Attribute::Italic),
+    (Modifier::UNDERLINED, Attribute::Underlined),
+    (Modifier::REVERSED, Attribute::Reverse),
+    (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+];
+
+/// Writes the cells with the escape codes of their styles. Every style change resets the previous
+/// style, so that each line can be printed on its own.
+fn write_cells(cells: &[Cell], out: &mut Vec<u8>) -> std::io::Result<()> {
+    let mut style = None;
+
+    for cell in cells {
+        if style != Some((cell.fg, cell.bg, cell.modifier)) {
+            queue!(
+                out,
+                SetAttribute(Attribute::Reset),
+                SetColors(Colors::new(cell.fg.into(), cell.bg.into()))
+            )?;
+
+            for (modifier, attribute) in ATTRIBUTES {
+                if cell.modifier.contains(modifier) {
+                    queue!(out, SetAttribute(attribute))?;
+                }
+            }
+
+            style = Some((cell.fg, cell.bg, cell.modifier));
+        }
+
+        out.write_all(cell.symbol().as_bytes())?;
+    }
+
+    queue!(out, SetAttribute(Attribute::Reset), ResetColor)
+}
+
+/// Returns the buffer as lines of text with ANSI escape codes. Trailing blank cells without a
+/// style are left out.
+pub fn ansi(buf: &Buffer) -> String {
+    let width = usize::from(buf.area.width).max(1);
+    let mut out = Vec::new();
+
+    buf.content.chunks(width).for_each(|row| {
+        let end = row
+            .iter()
+            .rposition(|cell| *cell != Cell::EMPTY)
+            .map_or(0, |last| last + 1);
+
+        if end > 0 {
+            _ = write_cells(&row[..end], &mut out);
+        }
+        out.push(b'\n');
+    });
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Returns the note of the editor in the format, rendered at the width for ANSI output.
+pub fn note(editor: &mut EditorState, format: PrintFormat, width: u16) -> String {
+    match format {
+        PrintFormat::Raw => editor.edited_content().into_owned(),
+        PrintFormat::Ansi => {
+            let lines: Vec<Line> = Editor::note_lines(editor, width);
+            let area = Rect::new(0, 0, width, lines.len().try_into().unwrap_or(u16::MAX));
+
+            let mut buf = Buffer::empty(area);
+            Paragraph::new(lines).render(area, &mut buf);
+            ansi(&buf)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Stylize;
+
+    use super::*;
+
+    #[test]
+    fn test_ansi() {
+        let mut editor = EditorState::default();
+        editor.set_content("# Pond\n\nFrogs\n");
+
+        assert_eq!(note(&mut editor, PrintFormat::Raw, 20), "# Pond\n\nFrogs\n");
+        assert!(note(&mut editor, PrintFormat::Ansi, 20).contains("Frogs"));
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+        Line::from(vec!["Koi".bold(), " ".into(), "carp".red()]).render(buf.area, &mut buf);
+
+        assert_eq!(
+            ansi(&buf),
+            "\x1b[0m\x1b[39;49m\x1b[1mKoi\x1b[0m\x1b[39;49m \x1b[0m\x1b[38;5;1;49mcarp\x1b[0m\x1b[0m\n\n"
+        );
+    }
+}
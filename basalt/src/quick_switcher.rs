@@ -0,0 +1,136 @@
+//! Quick switcher, which opens a note by typing a part of its path. The notes are listed in the
+//! [input modal](crate::input_modal) and narrowed down while typing. With `all_vaults` under the
+//! `[quick_switcher]` section the notes of all vaults are listed, and opening a note of another
+//! vault opens that vault first. The aliases of the notes of the open vault are listed as well,
+//! next to the note they point to.
+use std::path::{Path, PathBuf};
+
+use basalt_core::obsidian::{Vault, VaultIndex};
+
+/// Returns the path of the note relative to the vault without the `.md` extension.
+fn relative_path(vault: &Vault, path: &Path) -> String {
+    path.strip_prefix(&vault.path)
+        .unwrap_or(path)
+        .with_extension("")
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Returns the notes of the vaults as the label listed in the switcher and the path of the note.
+/// The label is the path of the note relative to its vault without the `.md` extension, prefixed
+/// with the name of the vault when there are several vaults. The notes of the open vault are
+/// listed first.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::Vault;
+/// use basalt_tui::quick_switcher;
+///
+/// let vault = Vault {
+///     name: "Notes".into(),
+///     path: "path/to/notes".into(),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(quick_switcher::notes(&vault, &[&vault]), vec![]);
+/// ```
+pub fn notes(open_vault: &Vault, vaults: &[&Vault]) -> Vec<(String, PathBuf)> {
+    let mut vaults = vaults.to_vec();
+    vaults.sort_by_key(|vault| vault.path != open_vault.path);
+
+    let prefix = vaults.len() > 1;
+
+    vaults
+        .iter()
+        .flat_map(|vault| {
+            vault.notes().into_iter().map(move |note| {
+                let path = relative_path(vault, &note.path);
+
+                let label = match prefix {
+                    true => format!("{}: {path}", vault.name),
+                    false => path,
+                };
+
+                (label, note.path)
+            })
+        })
+        .collect()
+}
+
+/// Returns the aliases of the indexed notes of the vault as the label listed in the switcher and
+/// the path of the note, sorted by the alias. The label is the alias followed by the path of the
+/// note it points to, e.g. `Plans → Projects/Ideas`.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_core::obsidian::{Note, Vault, VaultIndex};
+/// use basalt_tui::quick_switcher;
+///
+/// let vault = Vault {
+///     name: "Notes".into(),
+///     path: "notes".into(),
+///     ..Default::default()
+/// };
+///
+/// let note = Note {
+///     name: "Ideas".into(),
+///     path: "notes/Projects/Ideas.md".into(),
+/// };
+/// let mut index = VaultIndex::default();
+/// index.insert(&note, "---\naliases: [Plans]\n---\n");
+///
+/// assert_eq!(
+///     quick_switcher::aliases(&vault, &index),
+///     vec![("Plans → Projects/Ideas".into(), note.path)]
+/// );
+/// ```
+pub fn aliases(vault: &Vault, index: &VaultIndex) -> Vec<(String, PathBuf)> {
+    let mut aliases: Vec<_> = index
+        .notes()
+        .flat_map(|(path, note)| {
+            note.aliases.iter().map(move |alias| {
+                let label = format!("{alias} → {}", relative_path(vault, path));
+                (label, path.to_path_buf())
+            })
+        })
+        .collect();
+    aliases.sort_by_key(|(label, _)| label.to_lowercase());
+    aliases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn test_notes() {
+        let work = testing::vault(
+            "basalt-quick-switcher-work",
+            &[("Projects/Garden.md", "# Garden\n"), ("Plan.canvas", "{}")],
+        );
+        let personal = testing::vault("basalt-quick-switcher-personal", &[("Ideas.md", "")]);
+
+        let labels = |vaults: &[&Vault]| -> Vec<String> {
+            notes(&personal, vaults)
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect()
+        };
+
+        assert_eq!(labels(&[&personal]), ["Ideas"]);
+        assert_eq!(
+            labels(&[&work, &personal]),
+            [
+                "basalt-quick-switcher-personal: Ideas",
+                "basalt-quick-switcher-work: Projects/Garden",
+            ]
+        );
+        assert_eq!(
+            notes(&work, &[&work])[0].1,
+            work.path.join("Projects/Garden.md")
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Clear, Padding, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::{app::Message as AppMessage, chrome::Chrome, responsive};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Asks for confirmation before quitting with the given unsaved notes.
+    Open(Vec<String>),
+    /// Saves all notes and quits.
+    Save,
+    /// Quits without saving.
+    Discard,
+    Close,
+}
+
+/// Quits once the modal is open, as the open modal confirms the quit.
+pub fn update<'a>(message: &Message, state: &mut QuitModalState) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Open(notes) => {
+            state.notes = notes.clone();
+            state.visible = true;
+        }
+        Message::Save => return Some(AppMessage::SaveAll),
+        Message::Discard => return Some(AppMessage::Quit),
+        Message::Close => state.visible = false,
+    };
+
+    None
+}
+
+/// Quit modal key mappings cannot be modified through configuration, like the confirmation of
+/// replacements.
+pub fn handle_event(key: &KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Char('s') | KeyCode::Char('y') | KeyCode::Enter => Some(Message::Save),
+        KeyCode::Char('d') => Some(Message::Discard),
+        KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => Some(Message::Close),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct QuitModalState {
+    /// Names of the notes with unsaved changes.
+    pub(crate) notes: Vec<String>,
+    pub(crate) visible: bool,
+}
+
+fn modal_area(area: Rect, notes: usize) -> Rect {
+    let height = u16::try_from(notes).unwrap_or(u16::MAX).saturating_add(5);
+    responsive::modal_area(area, Constraint::Length(60), Constraint::Length(height))
+}
+
+#[derive(Default)]
+pub struct QuitModal {
+    chrome: Chrome,
+}
+
+impl QuitModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for QuitModal {
+    type State = QuitModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::uniform(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(" Unsaved changes ")
+            .title_bottom(
+                Line::from(" (s) save all and quit · (d) discard and quit · (esc) cancel ")
+                    .alignment(Alignment::Right),
+            );
+
+        let lines: Vec<_> = [Line::from("Quit with unsaved changes in:").italic()]
+            .into_iter()
+            .chain(
+                state
+                    .notes
+                    .iter()
+                    .map(|note| Line::from(format!("  ● {note}")).yellow()),
+            )
+            .collect();
+
+        let area = modal_area(area, state.notes.len());
+
+        Widget::render(Clear, area, buf);
+        Widget::render(
+            Paragraph::new(lines)
+                .wrap(Wrap::default())
+                .block(block)
+                .fg(Color::default()),
+            area,
+            buf,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    #[test]
+    fn test_quit_modal() {
+        let mut state = QuitModalState::default();
+        update(
+            &Message::Open(vec!["Daily".into(), "Ideas".into()]),
+            &mut state,
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(70, 12)).unwrap();
+        terminal
+            .draw(|frame| QuitModal::default().render(frame.area(), frame.buffer_mut(), &mut state))
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+
+        assert_eq!(
+            update(&Message::Save, &mut state),
+            Some(AppMessage::SaveAll)
+        );
+        assert_eq!(
+            update(&Message::Discard, &mut state),
+            Some(AppMessage::Quit)
+        );
+
+        update(&Message::Close, &mut state);
+        assert!(!state.visible);
+    }
+}
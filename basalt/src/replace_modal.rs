@@ -0,0 +1,342 @@
+use std::ops::Range;
+
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Alignment, Constraint, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, Padding, Paragraph, StatefulWidget, Widget, Wrap},
+};
+use regex::{Regex, RegexBuilder};
+
+use crate::{app::Message as AppMessage, chrome::Chrome, note_editor::EditorState, responsive};
+
+/// Where search and replace looks for matches.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scope {
+    Note,
+    Vault,
+}
+
+/// Parses the search pattern. Patterns written as `/pattern/` are regular expressions, where `^`
+/// and `$` match at the start and end of every line. Other patterns are matched literally.
+pub fn parse_pattern(pattern: &str) -> Result<Regex, String> {
+    let regex = match pattern
+        .strip_prefix('/')
+        .and_then(|pattern| pattern.strip_suffix('/'))
+    {
+        Some(regex) => regex.to_string(),
+        None => regex::escape(pattern),
+    };
+
+    RegexBuilder::new(&regex)
+        .multi_line(true)
+        .build()
+        .map_err(|err| format!("Invalid pattern: {err}"))
+}
+
+fn is_regex(pattern: &str) -> bool {
+    pattern.len() > 1 && pattern.starts_with('/') && pattern.ends_with('/')
+}
+
+/// A match of the search pattern and the text it is replaced with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// Finds the matches of the pattern in the content. Capture groups, e.g. `$1`, are expanded in
+/// the replacement of a regular expression.
+pub fn find_matches(content: &str, pattern: &str, replacement: &str) -> Result<Vec<Match>, String> {
+    let regex = parse_pattern(pattern)?;
+
+    Ok(regex
+        .captures_iter(content)
+        .filter_map(|captures| {
+            let range = captures.get(0)?.range();
+            let replacement = match is_regex(pattern) {
+                true => {
+                    let mut expanded = String::new();
+                    captures.expand(replacement, &mut expanded);
+                    expanded
+                }
+                false => replacement.to_string(),
+            };
+
+            Some(Match { range, replacement })
+        })
+        .collect())
+}
+
+/// Replaces the accepted matches in the content.
+pub fn replace(content: &str, matches: &[Match], accepted: &[bool]) -> String {
+    let mut replaced = String::with_capacity(content.len());
+    let mut offset = 0;
+
+    for (m, _) in matches
+        .iter()
+        .zip(accepted)
+        .filter(|(_, accepted)| **accepted)
+    {
+        replaced.push_str(&content[offset..m.range.start]);
+        replaced.push_str(&m.replacement);
+        offset = m.range.end;
+    }
+
+    replaced.push_str(&content[offset..]);
+    replaced
+}
+
+/// Returns the line number of the match and its line, with the match and its replacement
+/// highlighted.
+pub fn preview_line(content: &str, m: &Match) -> (usize, Line<'static>) {
+    let start = content[..m.range.start]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let end = content[m.range.end..]
+        .find('\n')
+        .map_or(content.len(), |index| m.range.end + index);
+    let line_number = content[..start].matches('\n').count() + 1;
+
+    // Newlines in multi-line matches are shown as symbols to keep the match on one line.
+    let show = |text: &str| text.replace('\n', "↵");
+
+    (
+        line_number,
+        Line::from(vec![
+            Span::from(content[start..m.range.start].to_string()),
+            Span::from(show(&content[m.range.clone()]))
+                .red()
+                .crossed_out(),
+            Span::from(show(&m.replacement)).green(),
+            Span::from(content[m.range.end..end].to_string()),
+        ]),
+    )
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Open {
+        pattern: String,
+        replacement: String,
+    },
+    Replace,
+    Skip,
+    ReplaceAll,
+    Close,
+}
+
+pub fn update<'a>(
+    message: &Message,
+    editor: &EditorState,
+    state: &mut ReplaceModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Open {
+            pattern,
+            replacement,
+        } => {
+            let matches = match find_matches(editor.content(), pattern, replacement) {
+                Ok(matches) => matches,
+                Err(err) => return Some(AppMessage::SetStatus(err)),
+            };
+
+            if matches.is_empty() {
+                return Some(AppMessage::SetStatus(format!("No matches for {pattern}")));
+            }
+
+            *state = ReplaceModalState {
+                pattern: pattern.clone(),
+                content: editor.content().to_string(),
+                accepted: vec![false; matches.len()],
+                matches,
+                index: 0,
+                visible: true,
+            };
+        }
+        Message::Replace => {
+            *state.accepted.get_mut(state.index)? = true;
+            state.index += 1;
+        }
+        Message::Skip => state.index += 1,
+        Message::ReplaceAll => {
+            state.accepted[state.index..].fill(true);
+            state.index = state.matches.len();
+        }
+        Message::Close => state.index = state.matches.len(),
+    };
+
+    if state.visible && state.index >= state.matches.len() {
+        return Some(state.finish());
+    }
+
+    None
+}
+
+/// Replace modal key mappings cannot be modified through configuration, similarly to the input
+/// modal.
+pub fn handle_event(key: &KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Enter => Some(Message::Replace),
+        KeyCode::Char('n') => Some(Message::Skip),
+        KeyCode::Char('a') => Some(Message::ReplaceAll),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Message::Close),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReplaceModalState {
+    pub(crate) pattern: String,
+    /// Note content the matches were found in.
+    pub(crate) content: String,
+    pub(crate) matches: Vec<Match>,
+    /// Whether each match is replaced, decided one match at a time.
+    pub(crate) accepted: Vec<bool>,
+    /// Index of the match waiting for confirmation.
+    pub(crate) index: usize,
+    pub(crate) visible: bool,
+}
+
+impl ReplaceModalState {
+    fn replaced(&self) -> usize {
+        self.accepted.iter().filter(|accepted| **accepted).count()
+    }
+
+    /// Hides the modal and returns the message that applies the accepted replacements.
+    fn finish<'a>(&mut self) -> AppMessage<'a> {
+        let replaced = self.replaced();
+        let content = replace(&self.content, &self.matches, &self.accepted);
+        *self = Self::default();
+
+        AppMessage::ReplacedMatches(content, replaced)
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(60), Constraint::Length(8))
+}
+
+#[derive(Default)]
+pub struct ReplaceModal {
+    chrome: Chrome,
+}
+
+impl ReplaceModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for ReplaceModal {
+    type State = ReplaceModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(
+                " Replace {} ({} of {}) ",
+                state.pattern,
+                state.index + 1,
+                state.matches.len()
+            ))
+            .title(
+                Line::from(format!(" {} replaced ", state.replaced())).alignment(Alignment::Right),
+            )
+            .title_bottom(
+                Line::from(" (y) replace · (n) skip · (a) replace all · (q) quit ")
+                    .alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        let Some(m) = state.matches.get(state.index) else {
+            return;
+        };
+
+        let (line_number, line) = preview_line(&state.content, m);
+
+        Paragraph::new(vec![
+            Line::from(format!("Line {line_number}"))
+                .dark_gray()
+                .italic(),
+            line,
+        ])
+        .wrap(Wrap { trim: false })
+        .render(inner_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_matches() {
+        let content = "Tasks: 2025-01-02, 2025-03-04\ntask";
+
+        let matches = find_matches(content, "task", "todo").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].range, 30..34);
+
+        let matches = find_matches(content, "/(\\d+)-(\\d+)-(\\d+)/", "$3.$2.$1").unwrap();
+        assert_eq!(
+            replace(content, &matches, &[false, true]),
+            "Tasks: 2025-01-02, 04.03.2025\ntask"
+        );
+
+        let matches = find_matches(content, "/^t/", "T").unwrap();
+        assert_eq!(
+            replace(content, &matches, &[true]),
+            "Tasks: 2025-01-02, 2025-03-04\nTask"
+        );
+
+        assert_eq!(
+            find_matches(content, "$1", "").map(|matches| matches.len()),
+            Ok(0)
+        );
+        assert!(find_matches(content, "/(/", "").is_err());
+    }
+
+    #[test]
+    fn test_confirm_matches() {
+        let mut editor = EditorState::default();
+        editor.set_content("a b a b a");
+
+        let mut state = ReplaceModalState::default();
+        let open = Message::Open {
+            pattern: "a".into(),
+            replacement: "c".into(),
+        };
+
+        assert_eq!(update(&open, &editor, &mut state), None);
+        assert!(state.visible);
+
+        assert_eq!(update(&Message::Skip, &editor, &mut state), None);
+        assert_eq!(update(&Message::Replace, &editor, &mut state), None);
+        assert_eq!(
+            update(&Message::Close, &editor, &mut state),
+            Some(AppMessage::ReplacedMatches("a b c b a".into(), 1))
+        );
+        assert!(!state.visible);
+
+        update(&open, &editor, &mut state);
+        update(&Message::Replace, &editor, &mut state);
+        assert_eq!(
+            update(&Message::ReplaceAll, &editor, &mut state),
+            Some(AppMessage::ReplacedMatches("c b c b c".into(), 3))
+        );
+    }
+}
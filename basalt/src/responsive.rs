@@ -0,0 +1,88 @@
+//! Size breakpoints of the terminal, for layouts that degrade gracefully on small terminals.
+//!
+//! The layout is chosen from the [`Breakpoint`] of the terminal size: on compact terminals the
+//! outline is collapsed and modals fill the screen, and on tiny terminals the explorer is
+//! collapsed too.
+use ratatui::layout::{Constraint, Flex, Layout, Rect, Size};
+
+/// Size class of the terminal, ordered from the smallest to the largest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Breakpoint {
+    /// Narrower than 60 columns or shorter than 16 rows.
+    Tiny,
+    /// Narrower than 80 columns or shorter than 24 rows.
+    Compact,
+    #[default]
+    Regular,
+}
+
+impl From<Size> for Breakpoint {
+    fn from(Size { width, height }: Size) -> Self {
+        match (width, height) {
+            (..60, _) | (_, ..16) => Breakpoint::Tiny,
+            (..80, _) | (_, ..24) => Breakpoint::Compact,
+            _ => Breakpoint::Regular,
+        }
+    }
+}
+
+/// Returns the area of a modal with the given width and height centered in the area. Below the
+/// regular breakpoint the modal fills the width of the area, and modals with a relative height
+/// fill the height as well.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::responsive::modal_area;
+/// use ratatui::layout::{Constraint, Rect};
+///
+/// let modal = |area| modal_area(area, Constraint::Length(60), Constraint::Percentage(50));
+///
+/// assert_eq!(modal(Rect::new(0, 0, 100, 40)), Rect::new(20, 10, 60, 20));
+/// assert_eq!(modal(Rect::new(0, 0, 70, 20)), Rect::new(0, 0, 70, 20));
+/// ```
+pub fn modal_area(area: Rect, width: Constraint, height: Constraint) -> Rect {
+    let (width, height) = match Breakpoint::from(area.as_size()) {
+        Breakpoint::Regular => (width, height),
+        _ => (
+            Constraint::Fill(1),
+            match height {
+                Constraint::Length(_) => height,
+                _ => Constraint::Fill(1),
+            },
+        ),
+    };
+
+    let [area] = Layout::vertical([height]).flex(Flex::Center).areas(area);
+    let [area] = Layout::horizontal([width]).flex(Flex::Center).areas(area);
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breakpoint() {
+        let tests = [
+            (Size::new(120, 40), Breakpoint::Regular),
+            (Size::new(80, 24), Breakpoint::Regular),
+            (Size::new(79, 40), Breakpoint::Compact),
+            (Size::new(120, 23), Breakpoint::Compact),
+            (Size::new(59, 40), Breakpoint::Tiny),
+            (Size::new(70, 15), Breakpoint::Tiny),
+        ];
+
+        tests.into_iter().for_each(|(size, expected)| {
+            assert_eq!(Breakpoint::from(size), expected, "With size {size:?}");
+        });
+    }
+
+    #[test]
+    fn test_modal_area() {
+        let input = |area| modal_area(area, Constraint::Length(60), Constraint::Length(3));
+
+        assert_eq!(input(Rect::new(0, 0, 100, 40)), Rect::new(20, 19, 60, 3));
+        assert_eq!(input(Rect::new(0, 0, 50, 20)), Rect::new(0, 9, 50, 3));
+    }
+}
@@ -0,0 +1,195 @@
+//! Asks what to do when the open note is saved after its file was changed outside Basalt, e.g.
+//! by Obsidian Sync, instead of overwriting the changes made elsewhere.
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Clear, Padding, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::{
+    app::Message as AppMessage, chrome::Chrome, note_diff_modal, note_editor::EditorState,
+    responsive,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Asks how to save the note with the given name.
+    Open(String),
+    /// Saves the editor content over the changed file.
+    Overwrite,
+    /// Reads the changed file into the editor, dropping the unsaved changes.
+    Reload,
+    /// Shows the changes of the editor content against the changed file.
+    ViewDiff,
+    Close,
+}
+
+pub fn update<'a>(
+    message: &Message,
+    editor: &mut EditorState,
+    state: &mut SaveConflictModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Open(note) => {
+            state.note = note.clone();
+            state.visible = true;
+        }
+        Message::Overwrite => {
+            state.visible = false;
+            editor.save();
+            return Some(AppMessage::UpdateSelectedNoteContent((
+                editor.content().to_string(),
+                None,
+            )));
+        }
+        Message::Reload => {
+            state.visible = false;
+            if let Err(err) = editor.reload() {
+                return Some(AppMessage::Error(format!(
+                    "Could not reload {}: {err}",
+                    state.note
+                )));
+            }
+
+            return Some(AppMessage::UpdateSelectedNoteContent((
+                editor.content().to_string(),
+                Some(editor.nodes().to_vec()),
+            )));
+        }
+        Message::ViewDiff => {
+            state.visible = false;
+            return Some(AppMessage::NoteDiffModal(note_diff_modal::Message::Toggle));
+        }
+        Message::Close => state.visible = false,
+    };
+
+    None
+}
+
+/// Save conflict modal key mappings cannot be modified through configuration, like the quit
+/// modal.
+pub fn handle_event(key: &KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Char('o') => Some(Message::Overwrite),
+        KeyCode::Char('r') => Some(Message::Reload),
+        KeyCode::Char('d') => Some(Message::ViewDiff),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Message::Close),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SaveConflictModalState {
+    /// Name of the note that was changed outside Basalt.
+    pub(crate) note: String,
+    pub(crate) visible: bool,
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Length(60), Constraint::Length(7))
+}
+
+#[derive(Default)]
+pub struct SaveConflictModal {
+    chrome: Chrome,
+}
+
+impl SaveConflictModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for SaveConflictModal {
+    type State = SaveConflictModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::uniform(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(" Changed on disk ")
+            .title_bottom(
+                Line::from(" (o) overwrite · (r) reload · (d) view diff · (esc) cancel ")
+                    .alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(
+            Paragraph::new(format!(
+                "{} was changed outside Basalt since it was opened.",
+                state.note
+            ))
+            .wrap(Wrap::default())
+            .block(block)
+            .fg(Color::default()),
+            area,
+            buf,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    #[test]
+    fn test_save_conflict_modal() {
+        let dir = std::env::temp_dir().join("basalt-save-conflict-modal");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Pond.md");
+        fs::write(&path, "# Pond\n").unwrap();
+
+        let mut editor = EditorState::default();
+        editor.set_path(path.clone());
+        editor.set_content("# Pond\n");
+        assert!(!editor.changed_on_disk());
+
+        fs::write(&path, "# Pond\n\nFrogs\n").unwrap();
+        assert!(editor.changed_on_disk());
+
+        let mut state = SaveConflictModalState::default();
+        update(&Message::Open("Pond".into()), &mut editor, &mut state);
+
+        let mut terminal = Terminal::new(TestBackend::new(70, 9)).unwrap();
+        terminal
+            .draw(|frame| {
+                SaveConflictModal::default().render(frame.area(), frame.buffer_mut(), &mut state)
+            })
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+
+        editor.replace_content("# Pond\n\nKoi\n");
+        update(&Message::Reload, &mut editor, &mut state);
+        assert_eq!(editor.content(), "# Pond\n\nFrogs\n");
+        assert!(!editor.modified && !editor.changed_on_disk());
+
+        fs::write(&path, "# Pond\n\nToads\n").unwrap();
+        editor.replace_content("# Pond\n\nKoi\n");
+        update(&Message::Overwrite, &mut editor, &mut state);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Pond\n\nKoi\n");
+        assert!(!editor.changed_on_disk() && !state.visible);
+
+        // Notes that are not valid UTF-8 are compared as they were read.
+        fs::write(&path, b"# Caf\xe9\n").unwrap();
+        editor.set_content("# Caf\u{FFFD}\n");
+        assert!(!editor.changed_on_disk());
+
+        fs::write(&path, b"# Caf\xe9\n\nFrogs\n").unwrap();
+        assert!(editor.changed_on_disk());
+    }
+}
@@ -0,0 +1,150 @@
+//! Announcements of focus and selection changes for terminal screen readers.
+//!
+//! Screen readers follow the text written to the terminal, which in a full screen application is
+//! mostly redrawn panes. When announcements are enabled, the focus is compared after every update
+//! and a short text describing the change, e.g. `Outline, Heading 2`, is either shown on a
+//! dedicated line above the status bar or sent as an OSC 9 notification to the terminal.
+use serde::Deserialize;
+
+use crate::note_editor::Mode;
+
+/// Where focus and selection changes are announced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Announcements {
+    /// Changes are not announced.
+    #[default]
+    Off,
+    /// The last change is shown on a line above the status bar.
+    Line,
+    /// Changes are sent to the terminal as OSC 9 notifications.
+    Osc,
+}
+
+/// The focused pane and the selections in it, compared between updates to find the changes to
+/// announce.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Focus {
+    pub pane: &'static str,
+    pub note: Option<String>,
+    pub mode: Mode,
+    /// The selected item of the focused pane, e.g. a note in the explorer or a heading in the
+    /// outline.
+    pub item: Option<String>,
+}
+
+impl Focus {
+    /// Returns the announcement of the changes since the previous focus, or `None` if nothing
+    /// changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::screen_reader::Focus;
+    ///
+    /// let explorer = Focus {
+    ///     pane: "Explorer",
+    ///     item: Some("Ideas".into()),
+    ///     ..Default::default()
+    /// };
+    /// let editor = Focus {
+    ///     pane: "Note Editor",
+    ///     note: Some("Ideas".into()),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(editor.announcement(&explorer), Some("Note Editor, opened Ideas".into()));
+    /// assert_eq!(editor.announcement(&editor), None);
+    /// ```
+    pub fn announcement(&self, previous: &Focus) -> Option<String> {
+        let mut changes = Vec::new();
+
+        if self.pane != previous.pane {
+            changes.push(self.pane.to_string());
+        }
+
+        if let Some(note) = self.note.as_ref().filter(|_| self.note != previous.note) {
+            changes.push(format!("opened {note}"));
+        }
+
+        if self.mode != previous.mode && self.pane == "Note Editor" {
+            changes.push(format!("{} mode", self.mode.to_string().to_lowercase()));
+        }
+
+        if let Some(item) = self.item.as_ref().filter(|_| self.item != previous.item) {
+            changes.push(item.clone());
+        }
+
+        (!changes.is_empty()).then(|| changes.join(", "))
+    }
+}
+
+/// Returns the OSC 9 escape sequence that sends the text to the terminal as a notification.
+/// Control characters are left out, so that the text cannot end the sequence early.
+pub fn osc_notification(text: &str) -> String {
+    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+    format!("\x1b]9;{text}\x07")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_announcement() {
+        let editor = Focus {
+            pane: "Note Editor",
+            note: Some("Ideas".into()),
+            ..Default::default()
+        };
+
+        let tests = [
+            (
+                Focus {
+                    mode: Mode::Edit,
+                    ..editor.clone()
+                },
+                Some("edit mode"),
+            ),
+            (
+                Focus {
+                    pane: "Outline",
+                    item: Some("Garden".into()),
+                    ..editor.clone()
+                },
+                Some("Outline, Garden"),
+            ),
+            (
+                Focus {
+                    note: None,
+                    ..editor.clone()
+                },
+                None,
+            ),
+            (
+                Focus {
+                    pane: "Explorer",
+                    mode: Mode::View,
+                    ..editor.clone()
+                },
+                Some("Explorer"),
+            ),
+        ];
+
+        tests.into_iter().for_each(|(focus, expected)| {
+            assert_eq!(
+                focus.announcement(&editor).as_deref(),
+                expected,
+                "With focus {focus:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_osc_notification() {
+        assert_eq!(
+            osc_notification("Opened\x07 Ideas"),
+            "\x1b]9;Opened Ideas\x07"
+        );
+    }
+}
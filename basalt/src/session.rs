@@ -0,0 +1,295 @@
+//! Session data that is persisted between application runs in the state directory, e.g. the last
+//! opened vault.
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use basalt_core::obsidian::Vault;
+use serde::{Deserialize, Serialize};
+
+use crate::dirs;
+
+/// Number of recently opened vaults that are remembered.
+const RECENT_VAULTS: usize = 10;
+
+/// Number of recently closed notes whose positions are remembered.
+const NOTE_POSITIONS: usize = 200;
+
+/// A vault that was opened, with the time it was opened at as a Unix timestamp.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RecentVault {
+    pub name: String,
+    pub opened_at: i64,
+}
+
+/// Where the cursor was in a note when it was closed, and where the note was last changed, as
+/// byte offsets of the note content.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NotePosition {
+    pub path: PathBuf,
+    pub cursor: usize,
+    pub last_edit: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Session {
+    /// Name of the vault that was opened last.
+    pub last_vault: Option<String>,
+    /// Vaults that were opened, the most recent first.
+    pub recent_vaults: Vec<RecentVault>,
+    /// Vaults listed first in the vault selector, in the order they were pinned.
+    pub pinned_vaults: Vec<String>,
+    /// Vaults left out of the vault selector.
+    pub hidden_vaults: Vec<String>,
+    /// Folders expanded in the explorer by vault name, relative to the vault root.
+    pub expanded_folders: BTreeMap<String, Vec<PathBuf>>,
+    /// Positions in the notes that were closed, the most recent first.
+    pub note_positions: Vec<NotePosition>,
+}
+
+fn session_path() -> Option<PathBuf> {
+    dirs::state_dir().map(|dir| dir.join("session.toml"))
+}
+
+impl Session {
+    /// Reads the session from disk. A missing or unreadable session file results in an empty
+    /// session.
+    pub fn load() -> Self {
+        session_path()
+            .map(|path| Self::load_from(&path))
+            .unwrap_or_default()
+    }
+
+    fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Records that the vault was opened at the time, given as a Unix timestamp.
+    pub fn opened(&mut self, name: &str, opened_at: i64) {
+        self.last_vault = Some(name.to_string());
+        self.recent_vaults.retain(|vault| vault.name != name);
+        self.recent_vaults.insert(
+            0,
+            RecentVault {
+                name: name.to_string(),
+                opened_at,
+            },
+        );
+        self.recent_vaults.truncate(RECENT_VAULTS);
+    }
+
+    /// Remembers the folders expanded in the explorer of the vault.
+    pub fn set_expanded_folders(&mut self, name: &str, folders: Vec<PathBuf>) {
+        match folders.is_empty() {
+            true => _ = self.expanded_folders.remove(name),
+            false => _ = self.expanded_folders.insert(name.to_string(), folders),
+        }
+    }
+
+    /// Remembers the position in the note, replacing the one remembered before.
+    pub fn set_note_position(&mut self, position: NotePosition) {
+        self.note_positions
+            .retain(|existing| existing.path != position.path);
+        self.note_positions.insert(0, position);
+        self.note_positions.truncate(NOTE_POSITIONS);
+    }
+
+    pub fn note_position(&self, path: &Path) -> Option<&NotePosition> {
+        self.note_positions
+            .iter()
+            .find(|position| position.path == path)
+    }
+
+    pub fn is_pinned(&self, name: &str) -> bool {
+        self.pinned_vaults.iter().any(|pinned| pinned == name)
+    }
+
+    pub fn is_hidden(&self, name: &str) -> bool {
+        self.hidden_vaults.iter().any(|hidden| hidden == name)
+    }
+
+    /// Pins the vault, or unpins it if it is pinned.
+    pub fn toggle_pinned(&mut self, name: &str) {
+        toggle(&mut self.pinned_vaults, name);
+    }
+
+    /// Hides the vault, or shows it again if it is hidden.
+    pub fn toggle_hidden(&mut self, name: &str) {
+        toggle(&mut self.hidden_vaults, name);
+    }
+
+    /// Returns the vaults in the order of the vault selector: the pinned vaults first, then the
+    /// recently opened vaults, the most recent first, then the rest in the given order. Hidden
+    /// vaults are left out, unless `show_hidden` is set.
+    pub fn arrange<'a>(&self, vaults: &[&'a Vault], show_hidden: bool) -> Vec<&'a Vault> {
+        let mut vaults: Vec<&'a Vault> = vaults
+            .iter()
+            .filter(|vault| show_hidden || !self.is_hidden(&vault.name))
+            .copied()
+            .collect();
+
+        vaults.sort_by_key(|vault| {
+            let pinned = self
+                .pinned_vaults
+                .iter()
+                .position(|pinned| *pinned == vault.name);
+            let opened_at = self
+                .recent_vaults
+                .iter()
+                .find(|recent| recent.name == vault.name)
+                .map(|recent| recent.opened_at);
+
+            (pinned.unwrap_or(usize::MAX), Reverse(opened_at))
+        });
+
+        vaults
+    }
+
+    /// Writes the session to disk.
+    pub fn save(&self) -> std::io::Result<()> {
+        self.save_to(&session_path().ok_or(std::io::ErrorKind::NotFound)?)
+    }
+
+    fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(
+            path,
+            toml::to_string(self).map_err(|err| std::io::Error::other(err.to_string()))?,
+        )
+    }
+}
+
+fn toggle(names: &mut Vec<String>, name: &str) {
+    match names.iter().position(|existing| existing == name) {
+        Some(index) => _ = names.remove(index),
+        None => names.push(name.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrange() {
+        let vaults: Vec<Vault> = ["Archive", "Personal", "Work", "Old"]
+            .into_iter()
+            .map(|name| Vault {
+                name: name.into(),
+                ..Default::default()
+            })
+            .collect();
+        let vaults: Vec<&Vault> = vaults.iter().collect();
+
+        let names = |vaults: Vec<&Vault>| -> Vec<String> {
+            vaults.iter().map(|vault| vault.name.clone()).collect()
+        };
+
+        let mut session = Session::default();
+        assert_eq!(
+            names(session.arrange(&vaults, false)),
+            ["Archive", "Personal", "Work", "Old"]
+        );
+
+        session.opened("Personal", 100);
+        session.opened("Work", 200);
+        session.opened("Personal", 300);
+        session.toggle_pinned("Old");
+        session.toggle_hidden("Archive");
+
+        assert_eq!(session.last_vault.as_deref(), Some("Personal"));
+        assert_eq!(
+            names(session.arrange(&vaults, false)),
+            ["Old", "Personal", "Work"]
+        );
+        assert_eq!(
+            names(session.arrange(&vaults, true)),
+            ["Old", "Personal", "Work", "Archive"]
+        );
+
+        session.toggle_pinned("Old");
+        session.toggle_hidden("Archive");
+        assert_eq!(
+            names(session.arrange(&vaults, false)),
+            ["Personal", "Work", "Archive", "Old"]
+        );
+    }
+
+    #[test]
+    fn test_note_positions() {
+        let mut session = Session::default();
+        let position = |path: &str, cursor| NotePosition {
+            path: path.into(),
+            cursor,
+            last_edit: None,
+        };
+
+        session.set_note_position(position("Pond.md", 10));
+        session.set_note_position(position("Shed.md", 20));
+        session.set_note_position(position("Pond.md", 30));
+
+        assert_eq!(
+            session.note_position(Path::new("Pond.md")),
+            Some(&position("Pond.md", 30))
+        );
+        assert_eq!(session.note_positions[1], position("Shed.md", 20));
+        assert_eq!(session.note_position(Path::new("Garden.md")), None);
+    }
+
+    #[test]
+    fn test_load_from() {
+        let dir = std::env::temp_dir().join("basalt-session-load-from");
+        _ = fs::remove_dir_all(&dir);
+        let path = dir.join("session.toml");
+
+        assert_eq!(Session::load_from(&path), Session::default());
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "last_vault = [\"Work\"\n[[recent_vaults").unwrap();
+        assert_eq!(Session::load_from(&path), Session::default());
+
+        // A corrupt session is replaced on the next save.
+        let mut session = Session::load_from(&path);
+        session.opened("Work", 100);
+        session.save_to(&path).unwrap();
+        assert_eq!(Session::load_from(&path), session);
+
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_session() {
+        let session: Session = toml::from_str(
+            r#"
+            last_vault = "Work"
+            pinned_vaults = ["Work"]
+
+            [[recent_vaults]]
+            name = "Work"
+            opened_at = 1700000000
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(session.recent_vaults[0].opened_at, 1700000000);
+        assert_eq!(
+            toml::from_str::<Session>("last_vault = \"Work\"").unwrap(),
+            Session {
+                last_vault: Some("Work".into()),
+                ..Default::default()
+            }
+        );
+    }
+}
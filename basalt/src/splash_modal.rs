@@ -11,6 +11,8 @@ use ratatui::{
 
 use crate::{
     app::Message as AppMessage,
+    chrome::Chrome,
+    session::Session,
     vault_selector::{VaultSelector, VaultSelectorState},
 };
 
@@ -19,12 +21,18 @@ pub enum Message {
     Up,
     Down,
     Open,
+    Pin,
+    Hide,
+    ShowHidden,
 }
 
 pub fn update<'a>(message: &Message, state: &mut SplashModalState<'a>) -> Option<AppMessage<'a>> {
     match message {
         Message::Up => state.previous(),
         Message::Down => state.next(),
+        Message::Pin => state.vault_selector_state.toggle_pinned(),
+        Message::Hide => state.vault_selector_state.toggle_hidden(),
+        Message::ShowHidden => state.vault_selector_state.toggle_show_hidden(),
         Message::Open => {
             state.select();
             if let Some(vault) = state.selected_item() {
@@ -39,6 +47,10 @@ pub fn update<'a>(message: &Message, state: &mut SplashModalState<'a>) -> Option
 
 const TITLE: &str = "⋅𝕭𝖆𝖘𝖆𝖑𝖙⋅";
 
+/// Height of the splash screen with the logo: the logo and title, the vault list, the help line
+/// and the margins.
+const SPLASH_MIN_HEIGHT: usize = 28 + 6 + 1 + 2;
+
 pub const LOGO: [&str; 25] = [
     "           ▒███▓░          ",
     "          ▒█████▒░         ",
@@ -71,6 +83,7 @@ pub const LOGO: [&str; 25] = [
 pub struct SplashModalState<'a> {
     pub(crate) vault_selector_state: VaultSelectorState<'a>,
     pub(crate) version: &'a str,
+    pub(crate) logo: Option<String>,
     pub(crate) visible: bool,
 }
 
@@ -81,14 +94,25 @@ impl<'a> SplashModalState<'a> {
         SplashModalState {
             version,
             vault_selector_state,
+            logo: None,
             visible,
         }
     }
 
+    /// Replaces the default logo with custom ASCII art.
+    pub fn set_logo(&mut self, logo: String) {
+        self.logo = Some(logo);
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
     }
 
+    pub fn show(&mut self) {
+        self.vault_selector_state.arrange(Session::load());
+        self.visible = true;
+    }
+
     pub fn select(&mut self) {
         self.vault_selector_state.select();
     }
@@ -122,9 +146,17 @@ impl<'a> SplashModalState<'a> {
 
 #[derive(Default)]
 pub struct SplashModal<'a> {
+    chrome: Chrome,
     _lifetime: PhantomData<&'a ()>,
 }
 
+impl SplashModal<'_> {
+    /// Sets the borders, padding and title alignment of the vault list.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome, ..self }
+    }
+}
+
 impl<'a> StatefulWidgetRef for SplashModal<'a> {
     type State = SplashModalState<'a>;
 
@@ -138,9 +170,19 @@ impl<'a> StatefulWidgetRef for SplashModal<'a> {
         ])
         .areas(area);
 
+        let logo = match &state.logo {
+            Some(logo) => Text::from(logo.as_str()),
+            None => Text::from_iter(LOGO),
+        };
+
+        // The logo is left out on screens too small for the logo above the vault list, so that
+        // the vault list stays visible.
+        let show_logo = usize::from(center.width) >= logo.width()
+            && usize::from(center.height) >= SPLASH_MIN_HEIGHT;
+
         let [_, top, bottom, _, help] = Layout::vertical([
             Constraint::Fill(1),
-            Constraint::Length(28),
+            Constraint::Length(if show_logo { 28 } else { 3 }),
             Constraint::Min(6),
             Constraint::Fill(1),
             Constraint::Length(1),
@@ -149,7 +191,7 @@ impl<'a> StatefulWidgetRef for SplashModal<'a> {
         .margin(1)
         .areas(center);
 
-        let [logo, title] =
+        let [logo_area, title] =
             Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(top);
 
         let [_, title, version] = Layout::horizontal([
@@ -165,10 +207,9 @@ impl<'a> StatefulWidgetRef for SplashModal<'a> {
             .flex(Flex::Center)
             .areas(bottom);
 
-        Text::from_iter(LOGO)
-            .dark_gray()
-            .centered()
-            .render(logo, buf);
+        if show_logo {
+            logo.dark_gray().centered().render(logo_area, buf);
+        }
 
         Text::from(TITLE).dark_gray().centered().render(title, buf);
 
@@ -184,6 +225,10 @@ impl<'a> StatefulWidgetRef for SplashModal<'a> {
             .centered()
             .render(help, buf);
 
-        VaultSelector::default().render_ref(bottom, buf, &mut state.vault_selector_state);
+        VaultSelector::default().chrome(self.chrome).render_ref(
+            bottom,
+            buf,
+            &mut state.vault_selector_state,
+        );
     }
 }
@@ -13,6 +13,17 @@ pub struct StatusBarState<'a> {
     active_component_name: &'a str,
     word_count: usize,
     char_count: usize,
+    cursor: Option<((usize, usize), usize)>,
+    history: Option<&'a str>,
+    message: Option<&'a str>,
+    warning: Option<&'a str>,
+    error: Option<&'a str>,
+    recording: bool,
+    count: Option<usize>,
+    unsaved: usize,
+    words_today: usize,
+    timer: Option<&'a str>,
+    count_scope: Option<&'a str>,
 }
 
 impl<'a> StatusBarState<'a> {
@@ -21,6 +32,102 @@ impl<'a> StatusBarState<'a> {
             active_component_name,
             word_count,
             char_count,
+            cursor: None,
+            history: None,
+            message: None,
+            warning: None,
+            error: None,
+            recording: false,
+            count: None,
+            unsaved: 0,
+            words_today: 0,
+            timer: None,
+            count_scope: None,
+        }
+    }
+
+    /// Shows the cursor line and column, and the progress through the note in percents.
+    pub fn with_cursor(self, position: (usize, usize), progress: usize) -> Self {
+        Self {
+            cursor: Some((position, progress)),
+            ..self
+        }
+    }
+
+    /// Shows the previous and next notes of the navigation history around the open note.
+    pub fn with_history(self, history: &'a str) -> Self {
+        Self {
+            history: Some(history),
+            ..self
+        }
+    }
+
+    /// Shows a message next to the active component, e.g. the result of a background task.
+    pub fn with_message(self, message: &'a str) -> Self {
+        Self {
+            message: Some(message),
+            ..self
+        }
+    }
+
+    /// Shows a warning after the message, e.g. about a link to a missing file.
+    pub fn with_warning(self, warning: &'a str) -> Self {
+        Self {
+            warning: Some(warning),
+            ..self
+        }
+    }
+
+    /// Shows the last error after the message until it is dismissed.
+    pub fn with_error(self, error: &'a str) -> Self {
+        Self {
+            error: Some(error),
+            ..self
+        }
+    }
+
+    /// Shows the count typed before a command.
+    pub fn with_count(self, count: usize) -> Self {
+        Self {
+            count: Some(count),
+            ..self
+        }
+    }
+
+    /// Shows the number of notes with unsaved changes.
+    pub fn with_unsaved(self, unsaved: usize) -> Self {
+        Self { unsaved, ..self }
+    }
+
+    /// Shows the number of words written today in the vault.
+    pub fn with_words_today(self, words_today: usize) -> Self {
+        Self {
+            words_today,
+            ..self
+        }
+    }
+
+    /// Shows the time left of the focus session.
+    pub fn with_timer(self, timer: &'a str) -> Self {
+        Self {
+            timer: Some(timer),
+            ..self
+        }
+    }
+
+    /// Shows what the counts count when it is not the whole note, e.g. the selection.
+    pub fn with_count_scope(self, count_scope: &'a str) -> Self {
+        Self {
+            count_scope: Some(count_scope),
+            ..self
+        }
+    }
+
+    /// Shows that a macro is being recorded.
+    pub fn with_recording(self) -> Self {
+        Self {
+            recording: true,
+            ..self
         }
     }
 }
@@ -34,9 +141,17 @@ impl<'a> StatefulWidgetRef for StatusBar<'a> {
     type State = StatusBarState<'a>;
 
     fn render_ref(&self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let [left, right] = Layout::horizontal([Constraint::Fill(1), Constraint::Length(28)])
-            .flex(Flex::SpaceBetween)
-            .areas(area);
+        let scope_width = state
+            .count_scope
+            .map_or(0, |scope| scope.chars().count() as u16 + 1);
+
+        let [left, cursor, right] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(if state.cursor.is_some() { 24 } else { 0 }),
+            Constraint::Length(28 + scope_width),
+        ])
+        .flex(Flex::SpaceBetween)
+        .areas(area);
 
         let active_component = [
             Span::from("").dark_gray(),
@@ -48,14 +163,70 @@ impl<'a> StatefulWidgetRef for StatusBar<'a> {
             Span::from(" ").bg(Color::DarkGray),
             Span::from("").dark_gray(),
         ]
-        .to_vec();
+        .into_iter()
+        .chain(state.recording.then(|| Span::from(" ● REC").red().bold()))
+        .chain(
+            (state.unsaved > 0)
+                .then(|| Span::from(format!(" ● {} unsaved", state.unsaved)).yellow()),
+        )
+        .chain(
+            state
+                .timer
+                .map(|timer| Span::from(format!(" ⏱ {timer}")).cyan()),
+        )
+        .chain(
+            (state.words_today > 0)
+                .then(|| Span::from(format!(" ✎ {} today", state.words_today)).magenta()),
+        )
+        .chain(
+            state
+                .count
+                .map(|count| Span::from(format!(" {count}")).bold()),
+        )
+        .chain(
+            state
+                .history
+                .map(|history| Span::from(format!(" {history}")).dark_gray()),
+        )
+        .chain(
+            state
+                .message
+                .map(|message| Span::from(format!(" {message}")).italic()),
+        )
+        .chain(
+            state
+                .warning
+                .map(|warning| Span::from(format!(" ⚠ {warning}")).yellow()),
+        )
+        .chain(
+            state
+                .error
+                .map(|error| Span::from(format!(" ✖ {error}")).red()),
+        )
+        .collect::<Vec<_>>();
 
         Text::from(Line::from(active_component)).render(left, buf);
 
-        let [word_count, char_count] =
-            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
-                .flex(Flex::End)
-                .areas(right);
+        if let Some(((line, col), progress)) = state.cursor {
+            Text::from(format!("Ln {line}, Col {col}  {progress:>3}%"))
+                .right_aligned()
+                .render(cursor, buf);
+        }
+
+        let [count_scope, word_count, char_count] = Layout::horizontal([
+            Constraint::Length(scope_width),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ])
+        .flex(Flex::End)
+        .areas(right);
+
+        if let Some(scope) = state.count_scope {
+            Text::from(scope)
+                .dark_gray()
+                .right_aligned()
+                .render(count_scope, buf);
+        }
 
         Text::from(format!(
             "{} word{}",
@@ -0,0 +1,429 @@
+//! Browser of the tags of the vault. Nested tags, e.g. `#project/alpha`, are shown as a tree under
+//! their parents, and the count of a parent includes the notes of its nested tags. Listing the
+//! notes of a tag lists the notes of its nested tags too.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use basalt_core::obsidian::{Note, Tag, Vault, VaultIndex};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{app::Message as AppMessage, chrome::Chrome, responsive};
+
+/// A tag in the tag tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagNode {
+    /// Full name of the tag without the `#`, e.g. `project/alpha`.
+    pub name: String,
+    /// Nesting level of the tag, 0 for top-level tags.
+    pub depth: usize,
+    /// Number of notes with the tag or one of its nested tags.
+    pub count: usize,
+    pub has_children: bool,
+}
+
+impl TagNode {
+    /// The last part of the name, e.g. `alpha` for `project/alpha`.
+    fn label(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+}
+
+/// Builds the tag tree of the indexed notes, sorted by name with nested tags under their parents.
+/// Parents that no note uses directly, e.g. `project` of `#project/alpha`, are part of the tree.
+pub fn tag_tree(index: &VaultIndex) -> Vec<TagNode> {
+    let mut tags: BTreeMap<Vec<&str>, BTreeSet<&Path>> = BTreeMap::new();
+
+    for (path, note) in index.notes() {
+        for tag in &note.tags {
+            let parts: Vec<_> = tag.split('/').filter(|part| !part.is_empty()).collect();
+            (1..=parts.len()).for_each(|len| {
+                tags.entry(parts[..len].to_vec()).or_default().insert(path);
+            });
+        }
+    }
+
+    let mut tags = tags.into_iter().peekable();
+    let mut tree = vec![];
+
+    while let Some((parts, notes)) = tags.next() {
+        let has_children = tags
+            .peek()
+            .is_some_and(|(next, _)| next.len() > parts.len() && next.starts_with(&parts));
+
+        tree.push(TagNode {
+            name: parts.join("/"),
+            depth: parts.len() - 1,
+            count: notes.len(),
+            has_children,
+        });
+    }
+
+    tree
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    Close,
+    Up,
+    Down,
+    /// Expands the selected tag.
+    Expand,
+    /// Collapses the selected tag, or selects its parent if it is collapsed already.
+    Collapse,
+    /// Lists the notes of the selected tag, or opens the selected note.
+    Open,
+    /// Goes back from the notes to the tag tree.
+    Back,
+}
+
+pub fn update<'a>(
+    message: &Message,
+    vault: Option<&Vault>,
+    index: &VaultIndex,
+    state: &mut TagBrowserModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Toggle if state.visible => state.hide(),
+        Message::Toggle => {
+            state.vault_path = vault?.path.clone();
+            state.visible = true;
+            state.notes = None;
+            state.refresh(index);
+        }
+        Message::Close => state.hide(),
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::Expand => {
+            if let Some(tag) = state.selected_tag().filter(|tag| tag.has_children) {
+                let name = tag.name.clone();
+                state.collapsed.remove(&name);
+            }
+        }
+        Message::Collapse if state.notes.is_some() => state.notes = None,
+        Message::Collapse => state.collapse(),
+        Message::Back => state.notes = None,
+        Message::Open => match &state.notes {
+            Some((tag, notes)) => {
+                let note = state
+                    .notes_state
+                    .selected()
+                    .and_then(|index| notes.get(index))?
+                    .clone();
+                let offset = tag_offset(&note, tag);
+
+                state.hide();
+                return Some(AppMessage::OpenNoteAt(note, offset));
+            }
+            None => {
+                let tag = state.selected_tag()?.name.clone();
+                let notes: Vec<_> = index.tagged(&tag).into_iter().map(Note::from).collect();
+
+                state.notes_state.select((!notes.is_empty()).then_some(0));
+                state.notes = Some((tag, notes));
+            }
+        },
+    };
+
+    None
+}
+
+/// Returns the byte offset of the first tag of the note that is the tag or nested under it.
+fn tag_offset(note: &Note, tag: &str) -> usize {
+    let contents = Note::read_to_string(note).unwrap_or_default();
+
+    Tag::parse(&contents)
+        .into_iter()
+        .find(|found| found.matches(tag))
+        .map(|found| found.range.start.saturating_sub(1))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TagBrowserModalState {
+    pub(crate) tags: Vec<TagNode>,
+    /// Names of the collapsed tags, kept while the modal is closed.
+    pub(crate) collapsed: BTreeSet<String>,
+    pub(crate) list_state: ListState,
+    /// The tag whose notes are listed instead of the tag tree, with the notes.
+    pub(crate) notes: Option<(String, Vec<Note>)>,
+    pub(crate) notes_state: ListState,
+    pub(crate) vault_path: PathBuf,
+    pub(crate) visible: bool,
+}
+
+impl TagBrowserModalState {
+    /// Rebuilds the tag tree from the index, keeping the selected tag selected.
+    pub fn refresh(&mut self, index: &VaultIndex) {
+        let selected = self.selected_tag().map(|tag| tag.name.clone());
+        self.tags = tag_tree(index);
+
+        let visible = self.visible_tags();
+        let position = selected
+            .and_then(|name| visible.iter().position(|tag| tag.name == name))
+            .unwrap_or_default();
+        self.list_state
+            .select((!visible.is_empty()).then(|| position.min(visible.len() - 1)));
+    }
+
+    /// Returns the tags whose parents are all expanded.
+    fn visible_tags(&self) -> Vec<&TagNode> {
+        self.tags
+            .iter()
+            .filter(|tag| {
+                let mut parent = tag.name.as_str();
+                while let Some((rest, _)) = parent.rsplit_once('/') {
+                    if self.collapsed.contains(rest) {
+                        return false;
+                    }
+                    parent = rest;
+                }
+                true
+            })
+            .collect()
+    }
+
+    pub fn selected_tag(&self) -> Option<&TagNode> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.visible_tags().get(index).copied())
+    }
+
+    fn collapse(&mut self) {
+        let Some(tag) = self.selected_tag() else {
+            return;
+        };
+
+        if tag.has_children && !self.collapsed.contains(&tag.name) {
+            let name = tag.name.clone();
+            self.collapsed.insert(name);
+            return;
+        }
+
+        if let Some((parent, _)) = tag.name.rsplit_once('/') {
+            let position = self
+                .visible_tags()
+                .iter()
+                .position(|tag| tag.name == parent);
+            self.list_state.select(position);
+        }
+    }
+
+    fn len(&self) -> usize {
+        match &self.notes {
+            Some((_, notes)) => notes.len(),
+            None => self.visible_tags().len(),
+        }
+    }
+
+    fn selection_mut(&mut self) -> &mut ListState {
+        match self.notes {
+            Some(_) => &mut self.notes_state,
+            None => &mut self.list_state,
+        }
+    }
+
+    pub fn next(&mut self) {
+        let len = self.len();
+        if len > 0 {
+            let selection = self.selection_mut();
+            let index = selection.selected().map_or(0, |index| index + 1);
+            selection.select(Some(index.min(len - 1)));
+        }
+    }
+
+    pub fn previous(&mut self) {
+        let selection = self.selection_mut();
+        let index = selection.selected().unwrap_or_default();
+        selection.select(Some(index.saturating_sub(1)));
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn list_item(&self, tag: &TagNode) -> ListItem<'static> {
+        let marker = match (tag.has_children, self.collapsed.contains(&tag.name)) {
+            (false, _) => "  ",
+            (true, false) => "▾ ",
+            (true, true) => "▸ ",
+        };
+
+        ListItem::new(Line::from(vec![
+            Span::from("  ".repeat(tag.depth)),
+            Span::from(marker).dark_gray(),
+            Span::from(format!("#{}", tag.label())),
+            Span::from(format!(" {}", tag.count)).dark_gray(),
+        ]))
+    }
+
+    fn note_item(&self, note: &Note) -> ListItem<'static> {
+        ListItem::new(
+            note.path
+                .strip_prefix(&self.vault_path)
+                .unwrap_or(&note.path)
+                .with_extension("")
+                .to_string_lossy()
+                .to_string(),
+        )
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(60), Constraint::Percentage(60))
+}
+
+#[derive(Default)]
+pub struct TagBrowserModal {
+    chrome: Chrome,
+}
+
+impl TagBrowserModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for TagBrowserModal {
+    type State = TagBrowserModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let (title, summary, hints) = match &state.notes {
+            Some((tag, notes)) => (
+                format!(" #{tag} "),
+                format!(" {} notes ", notes.len()),
+                " (enter) open · (h) back · (esc) close ",
+            ),
+            None => (
+                " Tags ".to_string(),
+                format!(" {} tags ", state.tags.len()),
+                " (enter) notes · (h/l) collapse/expand · (esc) close ",
+            ),
+        };
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(title)
+            .title(Line::from(summary).alignment(Alignment::Right))
+            .title_bottom(Line::from(hints).alignment(Alignment::Right));
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        let (items, list_state): (Vec<_>, _) = match &state.notes {
+            Some((_, notes)) => (
+                notes.iter().map(|note| state.note_item(note)).collect(),
+                &mut state.notes_state,
+            ),
+            None => (
+                state
+                    .visible_tags()
+                    .into_iter()
+                    .map(|tag| state.list_item(tag))
+                    .collect(),
+                &mut state.list_state,
+            ),
+        };
+
+        if items.is_empty() {
+            Paragraph::new("No tags in the vault")
+                .dark_gray()
+                .italic()
+                .render(inner_area, buf);
+            return;
+        }
+
+        StatefulWidget::render(
+            List::new(items)
+                .highlight_style(Style::default().reversed().dark_gray())
+                .highlight_symbol(""),
+            inner_area,
+            buf,
+            list_state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> VaultIndex {
+        let mut index = VaultIndex::default();
+        [
+            ("vault/Alpha.md", "#project/alpha #idea"),
+            ("vault/Beta.md", "#project/beta/draft #project-x"),
+            ("vault/Gamma.md", "#project/alpha #project"),
+        ]
+        .into_iter()
+        .for_each(|(path, contents)| index.insert(&Note::from(Path::new(path)), contents));
+        index
+    }
+
+    #[test]
+    fn test_tag_tree() {
+        let tree: Vec<_> = tag_tree(&index())
+            .into_iter()
+            .map(|tag| (tag.name, tag.depth, tag.count, tag.has_children))
+            .collect();
+
+        assert_eq!(
+            tree,
+            [
+                ("idea".to_string(), 0, 1, false),
+                ("project".into(), 0, 3, true),
+                ("project/alpha".into(), 1, 2, false),
+                ("project/beta".into(), 1, 1, true),
+                ("project/beta/draft".into(), 2, 1, false),
+                ("project-x".into(), 0, 1, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update() {
+        let index = index();
+        let vault = Vault {
+            path: "vault".into(),
+            ..Default::default()
+        };
+        let mut state = TagBrowserModalState::default();
+
+        update(&Message::Toggle, Some(&vault), &index, &mut state);
+        update(&Message::Down, Some(&vault), &index, &mut state);
+        update(&Message::Collapse, Some(&vault), &index, &mut state);
+        assert_eq!(state.visible_tags().len(), 3);
+
+        update(&Message::Open, Some(&vault), &index, &mut state);
+        let (tag, notes) = state.notes.clone().unwrap();
+        assert_eq!(tag, "project");
+        assert_eq!(notes.len(), 3);
+
+        update(&Message::Back, Some(&vault), &index, &mut state);
+        update(&Message::Expand, Some(&vault), &index, &mut state);
+        update(&Message::Down, Some(&vault), &index, &mut state);
+        update(&Message::Down, Some(&vault), &index, &mut state);
+        update(&Message::Collapse, Some(&vault), &index, &mut state);
+        assert_eq!(state.selected_tag().unwrap().name, "project/beta");
+        update(&Message::Collapse, Some(&vault), &index, &mut state);
+        update(&Message::Collapse, Some(&vault), &index, &mut state);
+        assert_eq!(state.selected_tag().unwrap().name, "project");
+    }
+}
@@ -0,0 +1,223 @@
+//! Harness for rendering tests. Runs the application in [headless](crate::headless) mode and feeds
+//! it key sequences as if they were pressed, so the rendered screen can be compared with
+//! snapshots.
+//!
+//! ```ignore
+//! let config = testing::config("experimental_editor = true");
+//! let vault = testing::vault("basalt-testing-edit", &[("Inbox.md", "# Inbox\n")]);
+//! let mut app = testing::app(&config, vec![&vault], 80, 20);
+//!
+//! app.keys("enter enter i").type_text("Hello");
+//! assert_snapshot!(app.render());
+//! ```
+use std::{fs, path::PathBuf, sync::LazyLock, sync::Once};
+
+use basalt_core::obsidian::Vault;
+
+use crate::{
+    app,
+    config::{self, Config},
+    headless::Headless,
+};
+
+static VERSION: LazyLock<String> = LazyLock::new(app::version);
+
+/// Points the data, cache, config and state directories to a temporary directory, so that
+/// opening a vault does not write the session of the user. The data and state directories start
+/// empty, so that the words written and the note positions of earlier test runs do not show up.
+pub(crate) fn isolate_user_dirs() {
+    static ONCE: Once = Once::new();
+
+    ONCE.call_once(|| {
+        let dir = std::env::temp_dir().join("basalt-testing-home");
+        std::env::set_var("XDG_DATA_HOME", dir.join("data"));
+        std::env::set_var("XDG_CACHE_HOME", dir.join("cache"));
+        std::env::set_var("XDG_CONFIG_HOME", dir.join("config"));
+        std::env::set_var("XDG_STATE_HOME", dir.join("state"));
+        _ = fs::remove_dir_all(dir.join("data"));
+        _ = fs::remove_dir_all(dir.join("state"));
+    });
+}
+
+/// Returns the base configuration with the user configuration text layered over it.
+///
+/// # Panics
+///
+/// Panics if the user configuration has problems.
+pub fn config<'a>(user_config: &str) -> Config<'a> {
+    let (config, problems) = config::from_user_config(Some(user_config));
+    assert_eq!(problems, vec![], "user configuration has problems");
+    config
+}
+
+/// Creates a vault with the notes in a fresh directory under the temporary directory. The notes
+/// are given as paths relative to the vault and their contents.
+pub fn vault(name: &str, notes: &[(&str, &str)]) -> Vault {
+    let path: PathBuf = std::env::temp_dir().join(name);
+    _ = fs::remove_dir_all(&path);
+
+    notes.iter().for_each(|(note, contents)| {
+        let note = path.join(note);
+        fs::create_dir_all(note.parent().unwrap()).unwrap();
+        fs::write(note, contents).unwrap();
+    });
+
+    Vault {
+        name: name.to_string(),
+        path,
+        ..Default::default()
+    }
+}
+
+/// Returns a xorshift generator of pseudo-random numbers for randomized tests. The seed is fixed by
+/// the test, so that a failure can be reproduced.
+pub fn xorshift(mut seed: u64) -> impl FnMut() -> usize {
+    move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize
+    }
+}
+
+/// Starts the application on the splash screen with the vaults, on a virtual terminal of the
+/// size.
+pub fn app<'a>(
+    config: &'a Config<'a>,
+    vaults: Vec<&'a Vault>,
+    width: u16,
+    height: u16,
+) -> Headless<'a> {
+    isolate_user_dirs();
+    Headless::new(config, &VERSION, vaults, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use insta::assert_snapshot;
+
+    use super::*;
+    use crate::{app::Message, quit_modal, session::Session};
+
+    const NOTE: &str = indoc! {"
+        # Projects
+
+        Ideas for the *garden*.
+
+        ## Shed
+
+        - Paint the door
+        - Fix the roof
+
+        ## Pond
+    "};
+
+    fn notes() -> [(&'static str, &'static str); 3] {
+        [
+            ("Projects.md", NOTE),
+            ("Inbox.md", "# Inbox\n"),
+            ("Archive/2024.md", "# 2024\n"),
+        ]
+    }
+
+    #[test]
+    fn test_splash() {
+        let config = config("");
+        let vault = vault("basalt-testing-splash", &notes());
+        let mut app = app(&config, vec![&vault], 80, 24);
+
+        assert_snapshot!(app.render());
+    }
+
+    #[test]
+    fn test_splash_pin_and_hide() {
+        let config = config("");
+        let vaults = [
+            "basalt-testing-pin-a",
+            "basalt-testing-pin-b",
+            "basalt-testing-pin-c",
+        ]
+        .map(|name| vault(name, &[("Inbox.md", "# Inbox\n")]));
+
+        // Start from a clean slate, as the session outlives the test run.
+        isolate_user_dirs();
+        let mut session = Session::load();
+        session
+            .pinned_vaults
+            .retain(|name| !name.starts_with("basalt-testing-pin"));
+        session
+            .hidden_vaults
+            .retain(|name| !name.starts_with("basalt-testing-pin"));
+        _ = session.save();
+
+        let mut app = app(&config, vaults.iter().collect(), 80, 40);
+
+        // Pin the last vault to the top, then hide the first one.
+        assert_snapshot!(app.keys("j j p j x").render());
+        // Hidden vaults can be listed, and shown again.
+        assert_snapshot!(app.keys(". k x .").render());
+    }
+
+    #[test]
+    fn test_explorer() {
+        let config = config("");
+        let vault = vault("basalt-testing-explorer", &notes());
+        let mut app = app(&config, vec![&vault], 80, 16);
+
+        assert_snapshot!(app.keys("enter").render());
+    }
+
+    #[test]
+    fn test_editor_read() {
+        let config = config("");
+        let vault = vault("basalt-testing-read", &notes());
+        let mut app = app(&config, vec![&vault], 80, 16);
+
+        assert_snapshot!(app.keys("enter j j enter").render());
+    }
+
+    #[test]
+    fn test_editor_edit() {
+        let config = config("experimental_editor = true");
+        let vault = vault("basalt-testing-edit", &notes());
+        let mut app = app(&config, vec![&vault], 80, 16);
+
+        app.keys("enter j j enter tab j j i end")
+            .type_text(" Plant tomatoes.");
+        assert_snapshot!(app.render());
+    }
+
+    #[test]
+    fn test_outline() {
+        let config = config("");
+        let vault = vault("basalt-testing-outline", &notes());
+        let mut app = app(&config, vec![&vault], 80, 16);
+
+        assert_snapshot!(app.keys("enter j j enter ctrl+o").render());
+    }
+
+    #[test]
+    fn test_modals() {
+        let config = config("");
+        let vault = vault("basalt-testing-modals", &notes());
+
+        let tests = [
+            ("help_modal", "enter ?"),
+            ("vault_selector_modal", "enter ctrl+g"),
+            ("error_log_modal", "enter shift+e"),
+        ];
+
+        tests.into_iter().for_each(|(name, keys)| {
+            let mut app = app(&config, vec![&vault], 80, 16);
+            assert_snapshot!(name, app.keys(keys).render());
+        });
+
+        let mut app = app(&config, vec![&vault], 80, 16);
+        app.keys("enter")
+            .send(Message::QuitModal(quit_modal::Message::Open(vec![
+                "Inbox".into()
+            ])));
+        assert_snapshot!("quit_modal", app.render());
+    }
+}
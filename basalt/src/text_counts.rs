@@ -1,5 +1,109 @@
-/// A wrapper type representing the number of characters in a string. **All** characters are
-/// counted for.
+//! Word and character counts of note contents.
+use std::borrow::Cow;
+
+use serde::Deserialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::note_editor::markdown_parser::{self, MarkdownNode, Node};
+
+/// Which text of a note is counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CountMode {
+    /// The note contents as written, including markdown syntax.
+    #[default]
+    Raw,
+    /// The prose of the note, without markdown syntax, frontmatter and code blocks.
+    Prose,
+}
+
+/// How much of the open note the status bar counts. A selection in edit mode is counted instead in
+/// either scope.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CountScope {
+    /// The whole note.
+    #[default]
+    Note,
+    /// The block at the cursor.
+    Block,
+}
+
+impl CountScope {
+    pub fn next(self) -> Self {
+        match self {
+            CountScope::Note => CountScope::Block,
+            CountScope::Block => CountScope::Note,
+        }
+    }
+}
+
+/// Returns the text without the frontmatter block at the start.
+pub(crate) fn strip_frontmatter(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return text;
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return &rest[offset..];
+        }
+    }
+
+    text
+}
+
+/// Collects the text of the nodes, one line per node. Code blocks and raw blocks, e.g.
+/// tables and HTML, are skipped.
+fn collect_prose(nodes: Vec<Node>, prose: &mut String) {
+    nodes.into_iter().for_each(|node| match node.markdown_node {
+        MarkdownNode::Heading { text, .. }
+        | MarkdownNode::Paragraph { text }
+        | MarkdownNode::Item { text }
+        | MarkdownNode::TaskListItem { text, .. } => {
+            prose.push_str(&String::from(text));
+            prose.push('\n');
+        }
+        MarkdownNode::BlockQuote { nodes, .. } | MarkdownNode::List { nodes, .. } => {
+            collect_prose(nodes, prose)
+        }
+        MarkdownNode::CodeBlock { .. } | MarkdownNode::Raw { .. } => {}
+    });
+}
+
+impl CountMode {
+    /// Returns the text that is counted in this mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use basalt_tui::text_counts::CountMode;
+    ///
+    /// let text = "---\ntags: [idea]\n---\n# **Bold** idea\n\n```rust\nfn main() {}\n```";
+    ///
+    /// assert_eq!(CountMode::Raw.text(text), text);
+    /// assert_eq!(CountMode::Prose.text(text), "Bold idea\n");
+    /// ```
+    pub fn text(self, text: &str) -> Cow<'_, str> {
+        match self {
+            CountMode::Raw => Cow::Borrowed(text),
+            CountMode::Prose => {
+                let mut prose = String::new();
+                collect_prose(
+                    markdown_parser::from_str(strip_frontmatter(text)),
+                    &mut prose,
+                );
+                Cow::Owned(prose)
+            }
+        }
+    }
+}
+
+/// A wrapper type representing the number of characters in a string. Characters are counted as
+/// user-perceived characters (grapheme clusters), so that e.g. `é` written with a combining
+/// accent or an emoji with modifiers is a single character.
 ///
 /// Character count can be created from an `usize` directly or computed from a `&str`.
 #[derive(Default, Clone, Debug, PartialEq)]
@@ -13,7 +117,7 @@ impl From<usize> for CharCount {
 
 impl From<&str> for CharCount {
     fn from(value: &str) -> Self {
-        value.chars().count().into()
+        value.graphemes(true).count().into()
     }
 }
 
@@ -25,10 +129,9 @@ impl From<CharCount> for usize {
 
 /// A wrapper type representing the number of words in a string.
 ///
-/// Can be created from a `usize` directly or computed from a `&str` by counting the number of
-/// whitespace-separated words, after removing special markdown characters.
-///
-/// markdown characters: * _ ` < > ? ! [ ] ( ) = ~ # +
+/// Can be created from a `usize` directly or computed from a `&str` by counting the words
+/// between Unicode word boundaries. Punctuation and markdown symbols like `#` or `-` are not
+/// words, and scripts written without spaces, like Chinese, count each character as a word.
 #[derive(Default, Clone, Debug, PartialEq)]
 pub struct WordCount(usize);
 
@@ -46,15 +149,7 @@ impl From<usize> for WordCount {
 
 impl From<&str> for WordCount {
     fn from(value: &str) -> Self {
-        let special_symbols = [
-            '*', '_', '`', '<', '>', '?', '!', '[', ']', '(', ')', '=', '~', '#', '+',
-        ];
-
-        value
-            .replace(special_symbols, "")
-            .split_whitespace()
-            .count()
-            .into()
+        value.unicode_words().count().into()
     }
 }
 
@@ -88,7 +183,7 @@ mod tests {
                 - [x] Completed task
 
                 - [?] Completed task"#},
-                (WordCount(10), CharCount(64)),
+                (WordCount(7), CharCount(64)),
             ),
             (
                 indoc! {r#"## Quotes
@@ -98,7 +193,7 @@ mod tests {
                 > Human beings face ever more complex and urgent problems, and their effectiveness in dealing with these problems is a matter that is critical to the stability and continued progress of society.
                 >
                 >- Doug Engelbart, 1961"#},
-                (WordCount(47), CharCount(294)),
+                (WordCount(46), CharCount(294)),
             ),
         ];
 
@@ -110,4 +205,52 @@ mod tests {
             )
         });
     }
+
+    #[test]
+    fn test_unicode_counts() {
+        let tests = [
+            ("cafe\u{301} au lait", (WordCount(3), CharCount(12))),
+            ("👍🏽 done", (WordCount(1), CharCount(6))),
+            ("日本語のテキスト", (WordCount(5), CharCount(8))),
+            ("well-known e.g. don't", (WordCount(4), CharCount(21))),
+        ];
+
+        tests.into_iter().for_each(|(input, expected)| {
+            assert_eq!(
+                (WordCount::from(input), CharCount::from(input)),
+                expected,
+                "With input {input}"
+            )
+        });
+    }
+
+    #[test]
+    fn test_prose_counts() {
+        let content = indoc! {r#"---
+            title: Counting
+            tags: [words]
+            ---
+            # Prose _counts_
+
+            - [x] [Linked](Note.md) item
+
+            > Quoted **text**
+
+            ```rust
+            let code = "not counted";
+            ```"#};
+
+        let prose = CountMode::Prose.text(content);
+        assert_eq!(
+            (
+                WordCount::from(prose.as_ref()),
+                CharCount::from(prose.as_ref())
+            ),
+            (WordCount(6), CharCount(37))
+        );
+        assert_eq!(
+            WordCount::from(CountMode::Raw.text(content).as_ref()),
+            WordCount(17)
+        );
+    }
 }
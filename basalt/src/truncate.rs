@@ -0,0 +1,66 @@
+//! Truncation of names that do not fit their column. Names are shortened in the middle, so that
+//! both the start and the end stay readable, as dated and numbered notes often differ only at the
+//! end, e.g. `2024-05-01 Meeting…view` and `2024-05-01 Meeting…ning`.
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Returns the characters from the start of the text that fit the width.
+fn fitting(chars: impl Iterator<Item = char>, width: usize) -> Vec<char> {
+    let mut used = 0;
+    chars
+        .take_while(|c| {
+            used += c.width().unwrap_or_default();
+            used <= width
+        })
+        .collect()
+}
+
+/// Shortens the text to the width in columns by replacing its middle with `…`. Text that fits is
+/// returned as is.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::truncate;
+///
+/// assert_eq!(truncate::middle("Meeting notes", 20), "Meeting notes");
+/// assert_eq!(truncate::middle("2024-05-01 Roadmap review", 12), "2024-0…eview");
+/// assert_eq!(truncate::middle("Meeting notes", 1), "…");
+/// ```
+pub fn middle(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let available = width.saturating_sub(1);
+    let head = fitting(text.chars(), available.div_ceil(2));
+    let tail = fitting(text.chars().rev(), available / 2);
+
+    match width {
+        0 => String::new(),
+        _ => head
+            .into_iter()
+            .chain(['…'])
+            .chain(tail.into_iter().rev())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_middle() {
+        let tests = [
+            ("Inbox", 5, "Inbox"),
+            ("Inventory", 5, "In…ry"),
+            ("Inventory", 6, "Inv…ry"),
+            ("Inventory", 0, ""),
+            ("日本語のノート", 8, "日本…ト"),
+        ];
+
+        tests.into_iter().for_each(|(text, width, expected)| {
+            assert_eq!(middle(text, width), expected, "With {text} in {width}");
+        });
+    }
+}
@@ -0,0 +1,87 @@
+//! Type-ahead in lists, like in file managers: typing the start of a name selects the first item
+//! with that name. Characters typed within [`TIMEOUT`] of each other are joined into one query,
+//! after a pause typing starts a new query.
+use std::time::{Duration, Instant};
+
+/// Longest pause between characters that continue the query.
+pub const TIMEOUT: Duration = Duration::from_millis(1000);
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TypeAhead {
+    query: String,
+    typed_at: Option<Instant>,
+}
+
+impl TypeAhead {
+    /// Adds the character typed at `now` to the query and returns the query. The query starts
+    /// over when the previous character was typed longer than [`TIMEOUT`] ago.
+    pub fn push(&mut self, c: char, now: Instant) -> &str {
+        if !self.is_active(now) {
+            self.query.clear();
+        }
+
+        self.query.push(c);
+        self.typed_at = Some(now);
+        &self.query
+    }
+
+    /// Returns whether the previous character was typed within [`TIMEOUT`], so that the next
+    /// character continues the query.
+    pub fn is_active(&self, now: Instant) -> bool {
+        self.typed_at
+            .is_some_and(|typed_at| now.duration_since(typed_at) <= TIMEOUT)
+    }
+}
+
+/// Returns the index of the first label that starts with the query, ignoring case. Labels are
+/// searched from `start` on and then from the top, so the selection stays on the item under it
+/// while it matches.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::type_ahead::find;
+///
+/// let labels = ["Inbox", "Ideas", "Projects", "Inventory"];
+///
+/// assert_eq!(find(labels, 0, "inv"), Some(3));
+/// assert_eq!(find(labels, 2, "i"), Some(3));
+/// assert_eq!(find(labels, 0, "shed"), None);
+/// ```
+pub fn find<'a>(
+    labels: impl IntoIterator<Item = &'a str>,
+    start: usize,
+    query: &str,
+) -> Option<usize> {
+    let query = query.to_lowercase();
+    let matches: Vec<usize> = labels
+        .into_iter()
+        .enumerate()
+        .filter(|(_, label)| label.trim_start().to_lowercase().starts_with(&query))
+        .map(|(index, _)| index)
+        .collect();
+
+    matches
+        .iter()
+        .find(|index| **index >= start)
+        .or(matches.first())
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push() {
+        let start = Instant::now();
+        let mut type_ahead = TypeAhead::default();
+
+        assert_eq!(type_ahead.push('p', start), "p");
+        assert_eq!(type_ahead.push('r', start + TIMEOUT / 2), "pr");
+        assert_eq!(type_ahead.push('o', start + TIMEOUT), "pro");
+        assert!(type_ahead.is_active(start + TIMEOUT * 2));
+        assert!(!type_ahead.is_active(start + TIMEOUT * 2 + Duration::from_millis(1)));
+        assert_eq!(type_ahead.push('i', start + TIMEOUT * 3), "i");
+    }
+}
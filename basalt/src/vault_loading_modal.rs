@@ -0,0 +1,234 @@
+use std::{
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use basalt_core::obsidian::Vault;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent},
+    layout::{Alignment, Constraint, Flex, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Clear, Gauge, Padding, StatefulWidget, Widget},
+};
+
+use crate::{
+    app::Message as AppMessage, chrome::Chrome, responsive, splash_modal::SplashModalState,
+    vault_selector_modal,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Entries of the vault at the path that have been read, and the total number of entries.
+    Progress(PathBuf, usize, usize),
+    /// Startup command of the `[vault_startup]` section running in the vault at the path, before
+    /// the vault is scanned.
+    Running(PathBuf, String),
+    Cancel,
+}
+
+/// Cancelling goes back to where the vault was selected: the vault selector when another vault is
+/// open, or the splash screen.
+pub fn update<'a>(
+    message: &Message,
+    has_open_vault: bool,
+    splash_modal: &mut SplashModalState<'a>,
+    state: &mut VaultLoadingModalState<'a>,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Progress(path, done, total) => {
+            if state.is_loading(path) {
+                state.running = None;
+                state.progress = (*done, *total);
+            }
+        }
+        Message::Running(path, command) => {
+            if state.is_loading(path) {
+                state.running = Some(command.clone());
+            }
+        }
+        Message::Cancel => {
+            state.vault = None;
+            state.visible = false;
+            match has_open_vault {
+                true => {
+                    return Some(AppMessage::VaultSelectorModal(
+                        vault_selector_modal::Message::Toggle,
+                    ))
+                }
+                false => splash_modal.show(),
+            }
+        }
+    };
+
+    None
+}
+
+/// Vault loading modal key mappings cannot be modified through configuration, as the modal is
+/// only shown while the vault is scanned.
+pub fn handle_event(key: &KeyEvent) -> Option<Message> {
+    match key.code {
+        KeyCode::Esc => Some(Message::Cancel),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VaultLoadingModalState<'a> {
+    /// The vault that is being scanned, `None` once the scan is done or cancelled.
+    pub(crate) vault: Option<&'a Vault>,
+    pub(crate) progress: (usize, usize),
+    /// Startup command that is running, until the scan starts.
+    pub(crate) running: Option<String>,
+    pub(crate) visible: bool,
+}
+
+impl<'a> VaultLoadingModalState<'a> {
+    /// Shows the modal for the vault that starts being scanned.
+    pub fn start(&mut self, vault: &'a Vault) {
+        self.vault = Some(vault);
+        self.progress = (0, 0);
+        self.running = None;
+        self.visible = true;
+    }
+
+    /// Returns `true` if the vault at the path is being scanned. Results of a cancelled scan,
+    /// which still runs to the end in the background, are ignored.
+    pub fn is_loading(&self, path: &Path) -> bool {
+        self.vault.is_some_and(|vault| vault.path == path)
+    }
+
+    /// Hides the modal and returns the vault, if the vault at the path was being scanned.
+    pub fn finish(&mut self, path: &Path) -> Option<&'a Vault> {
+        if !self.is_loading(path) {
+            return None;
+        }
+
+        self.visible = false;
+        self.vault.take()
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Length(60), Constraint::Length(7))
+}
+
+#[derive(Default)]
+pub struct VaultLoadingModal<'a> {
+    chrome: Chrome,
+    _lifetime: PhantomData<&'a ()>,
+}
+
+impl VaultLoadingModal<'_> {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome, ..self }
+    }
+}
+
+impl<'a> StatefulWidget for VaultLoadingModal<'a> {
+    type State = VaultLoadingModalState<'a>;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let name = state
+            .vault
+            .map(|vault| vault.name.as_str())
+            .unwrap_or_default();
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::uniform(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(" Opening {name} "))
+            .title_bottom(Line::from(" (esc) cancel ").alignment(Alignment::Right));
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        let [gauge] = Layout::vertical([Constraint::Length(1)])
+            .flex(Flex::Center)
+            .areas(inner_area);
+
+        // The total is not known before the top-level folders of the vault have been listed.
+        let (done, total) = state.progress;
+        let (ratio, label) = match (&state.running, total) {
+            (Some(command), _) => (0.0, format!("Running {command}…")),
+            (None, 0) => (0.0, "Scanning…".to_string()),
+            (None, total) => (
+                done as f64 / total as f64,
+                format!("Scanning {done}/{total}"),
+            ),
+        };
+
+        Gauge::default()
+            .gauge_style(Style::default().fg(Color::Magenta))
+            .ratio(ratio)
+            .label(label)
+            .render(gauge, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn test_render_vault_loading() {
+        let vault = Vault {
+            name: "Notes".into(),
+            path: "notes".into(),
+            ..Default::default()
+        };
+
+        let mut splash_modal = SplashModalState::default();
+        let mut state = VaultLoadingModalState::default();
+        state.start(&vault);
+
+        [
+            Message::Progress("notes".into(), 3, 12),
+            Message::Progress("other".into(), 9, 9),
+        ]
+        .iter()
+        .for_each(|message| _ = update(message, false, &mut splash_modal, &mut state));
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal
+            .draw(|frame| {
+                VaultLoadingModal::default().render(frame.area(), frame.buffer_mut(), &mut state)
+            })
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+
+        let running = Message::Running("notes".into(), "git pull".into());
+        _ = update(&running, false, &mut splash_modal, &mut state);
+        assert_eq!(state.running.as_deref(), Some("git pull"));
+        _ = update(
+            &Message::Progress("notes".into(), 0, 12),
+            false,
+            &mut splash_modal,
+            &mut state,
+        );
+        assert_eq!(state.running, None);
+
+        assert_eq!(state.finish(Path::new("other")), None);
+        assert_eq!(state.finish(Path::new("notes")), Some(&vault));
+        assert!(!state.visible);
+
+        state.start(&vault);
+        assert_eq!(
+            update(&Message::Cancel, false, &mut splash_modal, &mut state),
+            None
+        );
+        assert!(splash_modal.visible);
+        assert_eq!(state.finish(Path::new("notes")), None);
+    }
+}
@@ -0,0 +1,787 @@
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use basalt_core::obsidian::{code_ranges, Link, LinkIndex, LinkKind, Note, Tag, Vault};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Flex, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{
+        Clear, Gauge, List, ListItem, ListState, Padding, Paragraph, StatefulWidget, Widget,
+    },
+};
+
+use crate::{
+    app::Message as AppMessage,
+    background::Job,
+    chrome::Chrome,
+    date_format,
+    replace_modal::{self, Match},
+    responsive,
+};
+
+/// Folder under the vault root where the original notes are copied before they are replaced.
+/// Hidden folders are not part of the vault, so the copies do not show up in the explorer.
+const BACKUP_DIR: &str = ".basalt/backup";
+
+/// The matches in one note.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoteMatches {
+    pub note: Note,
+    /// Content of the note when it was searched.
+    pub content: String,
+    pub matches: Vec<Match>,
+    /// Whether each match is replaced. All matches are replaced by default.
+    pub accepted: Vec<bool>,
+}
+
+/// Finds the matches in the notes. Notes without matches are left out.
+pub fn find_in_notes(
+    notes: Vec<(Note, String)>,
+    pattern: &str,
+    replacement: &str,
+) -> Result<Vec<NoteMatches>, String> {
+    let mut found = vec![];
+
+    for (note, content) in notes {
+        let matches = replace_modal::find_matches(&content, pattern, replacement)?;
+        if !matches.is_empty() {
+            found.push(NoteMatches {
+                note,
+                content,
+                accepted: vec![true; matches.len()],
+                matches,
+            });
+        }
+    }
+
+    Ok(found)
+}
+
+/// Finds the tags to rename from `from` to `to` in the notes, including the tags nested under
+/// `from`, so that `#a/b` becomes `#c/b` when `#a` is renamed to `#c`. Notes without the tag are
+/// left out.
+pub fn find_tags(notes: Vec<(Note, String)>, from: &str, to: &str) -> Vec<NoteMatches> {
+    notes
+        .into_iter()
+        .filter_map(|(note, content)| {
+            let matches: Vec<_> = Tag::parse(&content)
+                .into_iter()
+                .filter_map(|tag| {
+                    let replacement = tag.renamed(from, to)?;
+                    Some(Match {
+                        range: tag.range,
+                        replacement,
+                    })
+                })
+                .collect();
+
+            (!matches.is_empty()).then(|| NoteMatches {
+                note,
+                content,
+                accepted: vec![true; matches.len()],
+                matches,
+            })
+        })
+        .collect()
+}
+
+/// Returns the range of the text of the ATX heading on the line, e.g. `Pond` in `## Pond`.
+fn heading_text(line: &str) -> Option<Range<usize>> {
+    let line = line.trim_end();
+    let level = line.len() - line.trim_start_matches('#').len();
+    let rest = &line[level..];
+
+    ((1..=6).contains(&level) && rest.starts_with([' ', '\t'])).then(|| {
+        let start = line.len() - rest.trim_start().len();
+        start..line.len()
+    })
+}
+
+/// Returns the range of the heading of a link anchor that starts at `start`, right after the
+/// `#`, with the heading as written in the link. Anchors of subheadings, e.g. `[[Note#A#B]]`, and
+/// of blocks are left out.
+fn anchor_heading(content: &str, start: usize, kind: LinkKind) -> Option<(Range<usize>, String)> {
+    let rest = &content[start..];
+    let (ends, terminators): (&[char], &[char]) = match kind {
+        LinkKind::Wiki => (&['|', ']'], &['|', ']', '#', '^', '\n']),
+        LinkKind::Markdown => (&[')', ' '], &[')', ' ', '#', '\n']),
+    };
+
+    let end = rest.find(terminators)?;
+    rest[end..].starts_with(ends).then_some(())?;
+
+    let heading = rest[..end].trim_end();
+    let heading = match kind {
+        LinkKind::Wiki => heading.to_string(),
+        LinkKind::Markdown => heading.replace("%20", " "),
+    };
+
+    Some((start..start + rest[..end].trim_end().len(), heading))
+}
+
+/// Finds the heading `from` of the note at `path` and the links to it across the notes, e.g.
+/// `[[Note#From]]`, `[[#From]]` within the note and `[Text](Note.md#From)`, to rename them to
+/// `to`. Links are resolved with the index. Notes without the heading or links to it are left out.
+pub fn find_heading_links(
+    notes: Vec<(Note, String)>,
+    index: &LinkIndex,
+    path: &Path,
+    from: &str,
+    to: &str,
+) -> Vec<NoteMatches> {
+    notes
+        .into_iter()
+        .filter_map(|(note, content)| {
+            let code = code_ranges(&content);
+            let in_code = |index: usize| code.iter().any(|range| range.contains(&index));
+            let mut matches = vec![];
+
+            let mut anchor = |start: usize, kind: LinkKind| {
+                if let Some((range, heading)) = anchor_heading(&content, start, kind) {
+                    if heading == from {
+                        matches.push(Match {
+                            range,
+                            replacement: match kind {
+                                LinkKind::Wiki => to.to_string(),
+                                LinkKind::Markdown => to.replace(' ', "%20"),
+                            },
+                        });
+                    }
+                }
+            };
+
+            for link in Link::parse(&content) {
+                if index.resolve(&note.path, &link).as_deref() != Some(path) {
+                    continue;
+                }
+
+                let after = &content[link.range.end..];
+                let spaces = after.len() - after.trim_start_matches(' ').len();
+                if after[spaces..].starts_with('#') {
+                    anchor(link.range.end + spaces + 1, link.kind);
+                }
+            }
+
+            if note.path == path {
+                content
+                    .match_indices("[[#")
+                    .filter(|(start, _)| !in_code(*start))
+                    .for_each(|(start, _)| anchor(start + 3, LinkKind::Wiki));
+
+                let mut offset = 0;
+                for line in content.split_inclusive('\n') {
+                    match heading_text(line) {
+                        Some(range) if !in_code(offset) && line[range.clone()] == *from => matches
+                            .push(Match {
+                                range: offset + range.start..offset + range.end,
+                                replacement: to.to_string(),
+                            }),
+                        _ => {}
+                    }
+                    offset += line.len();
+                }
+            }
+
+            matches.sort_by_key(|m| m.range.start);
+
+            (!matches.is_empty()).then(|| NoteMatches {
+                note,
+                content,
+                accepted: vec![true; matches.len()],
+                matches,
+            })
+        })
+        .collect()
+}
+
+/// What was changed by [`apply`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VaultReplaceReport {
+    pub notes: Vec<Note>,
+    pub replaced: usize,
+    pub backup_dir: Option<PathBuf>,
+}
+
+/// Writes the accepted replacements to the notes, so that either all notes are replaced or none.
+/// Nothing is written if a note was changed on disk after it was searched, and notes that were
+/// already written are restored if writing a note fails.
+///
+/// When a backup directory is given, the original notes are copied there first with their paths
+/// relative to the vault root.
+pub fn apply(
+    vault_path: &Path,
+    notes: &[NoteMatches],
+    backup_dir: Option<&Path>,
+) -> Result<VaultReplaceReport, String> {
+    let changes: Vec<_> = notes
+        .iter()
+        .filter(|matches| matches.accepted.contains(&true))
+        .map(|matches| {
+            let replaced =
+                replace_modal::replace(&matches.content, &matches.matches, &matches.accepted);
+            (matches, replaced)
+        })
+        .collect();
+
+    for (matches, _) in &changes {
+        let content = Note::read_to_string_strict(&matches.note).map_err(|err| err.to_string())?;
+        if content != matches.content {
+            return Err(format!(
+                "{} was changed after the search",
+                matches.note.name
+            ));
+        }
+    }
+
+    if let Some(dir) = backup_dir {
+        for (matches, _) in &changes {
+            let path = dir.join(
+                matches
+                    .note
+                    .path
+                    .strip_prefix(vault_path)
+                    .unwrap_or(&matches.note.path),
+            );
+
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, &matches.content))
+                .map_err(|err| format!("Could not back up {}: {err}", matches.note.name))?;
+        }
+    }
+
+    for (i, (matches, replaced)) in changes.iter().enumerate() {
+        if let Err(err) = Note::write(&matches.note, replaced.clone()) {
+            for (matches, _) in &changes[..i] {
+                _ = Note::write(&matches.note, matches.content.clone());
+            }
+
+            return Err(format!("Could not write {}: {err}", matches.note.name));
+        }
+    }
+
+    Ok(VaultReplaceReport {
+        replaced: changes
+            .iter()
+            .map(|(matches, _)| matches.accepted.iter().filter(|a| **a).count())
+            .sum(),
+        notes: changes
+            .into_iter()
+            .map(|(matches, _)| matches.note.clone())
+            .collect(),
+        backup_dir: backup_dir.map(Path::to_path_buf),
+    })
+}
+
+/// A row in the list, either a note or one of its matches.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Row {
+    Note(usize),
+    Match(usize, usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Open {
+        pattern: String,
+        replacement: String,
+    },
+    /// Lists the tags to rename across the vault, with the tags nested under them.
+    RenameTag {
+        from: String,
+        to: String,
+    },
+    /// Lists the heading of the note to rename, with the links to it across the vault.
+    RenameHeading {
+        path: PathBuf,
+        from: String,
+        to: String,
+    },
+    Close,
+    Up,
+    Down,
+    Toggle,
+    ToggleBackup,
+    Apply,
+    Progress(usize, usize),
+    Loaded(Result<Vec<NoteMatches>, String>),
+}
+
+pub fn update<'a>(
+    message: &Message,
+    vault: Option<&Vault>,
+    state: &mut VaultReplaceModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Open {
+            pattern,
+            replacement,
+        } => {
+            let vault = vault?;
+
+            *state = VaultReplaceModalState {
+                pattern: pattern.clone(),
+                vault_path: vault.path.clone(),
+                progress: Some((0, 0)),
+                backup: true,
+                visible: true,
+                ..Default::default()
+            };
+
+            return Some(AppMessage::RunJob(Job::VaultReplace(
+                vault.clone(),
+                pattern.clone(),
+                replacement.clone(),
+            )));
+        }
+        Message::RenameTag { from, to } => {
+            let vault = vault?;
+
+            *state = VaultReplaceModalState {
+                pattern: format!("#{from} with #{to}"),
+                vault_path: vault.path.clone(),
+                progress: Some((0, 0)),
+                backup: true,
+                visible: true,
+                ..Default::default()
+            };
+
+            return Some(AppMessage::RunJob(Job::RenameTag(
+                vault.clone(),
+                from.clone(),
+                to.clone(),
+            )));
+        }
+        Message::RenameHeading { path, from, to } => {
+            let vault = vault?;
+
+            *state = VaultReplaceModalState {
+                pattern: format!("#{from} with #{to}"),
+                vault_path: vault.path.clone(),
+                progress: Some((0, 0)),
+                backup: true,
+                visible: true,
+                ..Default::default()
+            };
+
+            return Some(AppMessage::RunJob(Job::RenameHeading(
+                vault.clone(),
+                path.clone(),
+                from.clone(),
+                to.clone(),
+            )));
+        }
+        Message::Close => state.hide(),
+        Message::Up => state.previous(),
+        Message::Down => state.next(),
+        Message::Toggle => state.toggle(),
+        Message::ToggleBackup => state.backup = !state.backup,
+        Message::Apply => {
+            if state.progress.is_some() || state.error.is_some() {
+                return None;
+            }
+
+            let backup_dir = state.backup.then(|| {
+                state
+                    .vault_path
+                    .join(BACKUP_DIR)
+                    .join(date_format::timestamp())
+            });
+
+            let result = apply(&state.vault_path, &state.notes, backup_dir.as_deref());
+            state.hide();
+
+            return Some(match result {
+                Ok(report) if report.notes.is_empty() => {
+                    AppMessage::SetStatus("No matches replaced".into())
+                }
+                Ok(report) => AppMessage::VaultReplaced(report),
+                Err(err) => AppMessage::Error(format!("No notes were changed: {err}")),
+            });
+        }
+        Message::Progress(done, total) => state.progress = Some((*done, *total)),
+        Message::Loaded(Ok(notes)) => {
+            state.progress = None;
+            state.notes = notes.clone();
+            state.list_state.select((!notes.is_empty()).then_some(0));
+        }
+        Message::Loaded(Err(err)) => {
+            state.progress = None;
+            state.error = Some(err.clone());
+        }
+    };
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VaultReplaceModalState {
+    pub(crate) pattern: String,
+    pub(crate) notes: Vec<NoteMatches>,
+    pub(crate) list_state: ListState,
+    /// Whether the notes are backed up before they are replaced.
+    pub(crate) backup: bool,
+    /// Number of searched notes and total number of notes while the search is running.
+    pub(crate) progress: Option<(usize, usize)>,
+    pub(crate) error: Option<String>,
+    pub(crate) vault_path: PathBuf,
+    pub(crate) visible: bool,
+}
+
+impl VaultReplaceModalState {
+    fn rows(&self) -> Vec<Row> {
+        self.notes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, note)| {
+                std::iter::once(Row::Note(i))
+                    .chain((0..note.matches.len()).map(move |j| Row::Match(i, j)))
+            })
+            .collect()
+    }
+
+    fn select(&mut self, index: usize) {
+        let len = self.rows().len();
+        self.list_state.select(match len {
+            0 => None,
+            _ => Some(index.min(len - 1)),
+        });
+    }
+
+    pub fn next(&mut self) {
+        let index = self.list_state.selected().map_or(0, |index| index + 1);
+        self.select(index);
+    }
+
+    pub fn previous(&mut self) {
+        let index = self.list_state.selected().unwrap_or_default();
+        self.select(index.saturating_sub(1));
+    }
+
+    /// Toggles the selected match, or all matches of the selected note.
+    pub fn toggle(&mut self) {
+        let Some(row) = self
+            .list_state
+            .selected()
+            .and_then(|index| self.rows().get(index).copied())
+        else {
+            return;
+        };
+
+        match row {
+            Row::Note(i) => {
+                let accepted = &mut self.notes[i].accepted;
+                let all = accepted.iter().all(|accepted| *accepted);
+                accepted.fill(!all);
+            }
+            Row::Match(i, j) => {
+                let accepted = &mut self.notes[i].accepted[j];
+                *accepted = !*accepted;
+            }
+        }
+    }
+
+    fn accepted(&self) -> usize {
+        self.notes
+            .iter()
+            .flat_map(|note| &note.accepted)
+            .filter(|accepted| **accepted)
+            .count()
+    }
+
+    pub fn hide(&mut self) {
+        *self = Self::default();
+    }
+
+    fn list_item(&self, row: Row) -> ListItem<'static> {
+        let checkbox = |accepted: bool| match accepted {
+            true => Span::from("[x] ").green(),
+            false => Span::from("[ ] ").dark_gray(),
+        };
+
+        match row {
+            Row::Note(i) => {
+                let note = &self.notes[i];
+                let path = note
+                    .note
+                    .path
+                    .strip_prefix(&self.vault_path)
+                    .unwrap_or(&note.note.path)
+                    .to_string_lossy()
+                    .to_string();
+
+                ListItem::new(Line::from(vec![
+                    checkbox(note.accepted.iter().all(|accepted| *accepted)),
+                    Span::from(path).bold(),
+                    Span::from(format!(
+                        " {}/{}",
+                        note.accepted.iter().filter(|accepted| **accepted).count(),
+                        note.matches.len()
+                    ))
+                    .dark_gray(),
+                ]))
+            }
+            Row::Match(i, j) => {
+                let note = &self.notes[i];
+                let (line_number, line) =
+                    replace_modal::preview_line(&note.content, &note.matches[j]);
+
+                let mut spans = vec![
+                    Span::from("    "),
+                    checkbox(note.accepted[j]),
+                    Span::from(format!("{line_number:>4}: ")).dark_gray(),
+                ];
+                spans.extend(line.spans);
+
+                ListItem::new(Line::from(spans))
+            }
+        }
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Percentage(80), Constraint::Percentage(80))
+}
+
+#[derive(Default)]
+pub struct VaultReplaceModal {
+    chrome: Chrome,
+}
+
+impl VaultReplaceModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for VaultReplaceModal {
+    type State = VaultReplaceModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let backup = match state.backup {
+            true => "on",
+            false => "off",
+        };
+
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::horizontal(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(format!(" Replace {} in vault ", state.pattern))
+            .title(
+                Line::from(format!(
+                    " {} of {} matches in {} notes ",
+                    state.accepted(),
+                    state
+                        .notes
+                        .iter()
+                        .map(|note| note.matches.len())
+                        .sum::<usize>(),
+                    state.notes.len()
+                ))
+                .alignment(Alignment::Right),
+            )
+            .title_bottom(
+                Line::from(format!(
+                    " (space) toggle · (b) backup: {backup} · (enter) replace · (esc) close "
+                ))
+                .alignment(Alignment::Right),
+            );
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if let Some((done, total)) = state.progress {
+            let [gauge] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(inner_area);
+
+            Gauge::default()
+                .gauge_style(Style::default().magenta())
+                .ratio(if total == 0 {
+                    0.0
+                } else {
+                    done as f64 / total as f64
+                })
+                .label(format!("Searching notes {done}/{total}"))
+                .render(gauge, buf);
+            return;
+        }
+
+        if let Some(error) = &state.error {
+            Paragraph::new(error.as_str()).red().render(inner_area, buf);
+            return;
+        }
+
+        if state.notes.is_empty() {
+            Paragraph::new("No matches")
+                .dark_gray()
+                .italic()
+                .render(inner_area, buf);
+            return;
+        }
+
+        let items: Vec<_> = state
+            .rows()
+            .into_iter()
+            .map(|row| state.list_item(row))
+            .collect();
+
+        StatefulWidget::render(
+            List::new(items).highlight_style(Style::default().reversed().dark_gray()),
+            inner_area,
+            buf,
+            &mut state.list_state,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_tags() {
+        let note = |name: &str, content: &str| {
+            (
+                Note {
+                    name: name.into(),
+                    path: format!("{name}.md").into(),
+                },
+                content.to_string(),
+            )
+        };
+
+        let notes = vec![
+            note(
+                "A",
+                "---\ntags: [project]\n---\n#Project/alpha #projects `#project`",
+            ),
+            note("B", "#idea"),
+        ];
+
+        let found = find_tags(notes, "project", "work");
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            replace_modal::replace(&found[0].content, &found[0].matches, &found[0].accepted),
+            "---\ntags: [work]\n---\n#work/alpha #projects `#project`"
+        );
+    }
+
+    #[test]
+    fn test_find_heading_links() {
+        let note = |name: &str, content: &str| {
+            (
+                Note {
+                    name: name.into(),
+                    path: format!("vault/{name}.md").into(),
+                },
+                content.to_string(),
+            )
+        };
+
+        let notes = vec![
+            note(
+                "Garden",
+                "# Garden\n\n## Old Pond\n\nSee [[#Old Pond]].\n\n```\n## Old Pond\n```\n",
+            ),
+            note(
+                "Plans",
+                "[[Garden#Old Pond|pond]] [p](Garden.md#Old%20Pond) [[Garden#Old Ponds]] `[[Garden#Old Pond]]`",
+            ),
+            note("Shed", "[[Shed#Old Pond]]"),
+        ];
+        let index = LinkIndex::new(
+            Path::new("vault"),
+            notes.iter().map(|(note, _)| note.path.clone()).collect(),
+        );
+
+        let found = find_heading_links(
+            notes,
+            &index,
+            Path::new("vault/Garden.md"),
+            "Old Pond",
+            "New Pond",
+        );
+        let replaced: Vec<_> = found
+            .iter()
+            .map(|found| replace_modal::replace(&found.content, &found.matches, &found.accepted))
+            .collect();
+
+        assert_eq!(
+            replaced,
+            [
+                "# Garden\n\n## New Pond\n\nSee [[#New Pond]].\n\n```\n## Old Pond\n```\n",
+                "[[Garden#New Pond|pond]] [p](Garden.md#New%20Pond) [[Garden#Old Ponds]] `[[Garden#Old Pond]]`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let vault_path = std::env::temp_dir().join("basalt_vault_replace_test");
+        _ = fs::remove_dir_all(&vault_path);
+        fs::create_dir_all(vault_path.join("Folder")).unwrap();
+
+        let notes: Vec<_> = [("A", "old old"), ("Folder/B", "old"), ("C", "new")]
+            .into_iter()
+            .map(|(name, content)| {
+                let path = vault_path.join(format!("{name}.md"));
+                fs::write(&path, content).unwrap();
+                (
+                    Note {
+                        name: name.into(),
+                        path,
+                    },
+                    content.to_string(),
+                )
+            })
+            .collect();
+
+        let mut found = find_in_notes(notes, "old", "new").unwrap();
+        assert_eq!(found.len(), 2);
+
+        found[0].accepted[1] = false;
+
+        // Notes changed after the search are not overwritten.
+        fs::write(vault_path.join("Folder/B.md"), "changed").unwrap();
+        assert!(apply(&vault_path, &found, None).is_err());
+        assert_eq!(
+            fs::read_to_string(vault_path.join("A.md")).unwrap(),
+            "old old"
+        );
+
+        fs::write(vault_path.join("Folder/B.md"), "old").unwrap();
+        let backup_dir = vault_path.join(BACKUP_DIR);
+        let report = apply(&vault_path, &found, Some(&backup_dir)).unwrap();
+
+        assert_eq!(report.replaced, 2);
+        assert_eq!(report.notes.len(), 2);
+        assert_eq!(
+            fs::read_to_string(vault_path.join("A.md")).unwrap(),
+            "new old"
+        );
+        assert_eq!(
+            fs::read_to_string(vault_path.join("Folder/B.md")).unwrap(),
+            "new"
+        );
+        assert_eq!(
+            fs::read_to_string(backup_dir.join("Folder/B.md")).unwrap(),
+            "old"
+        );
+
+        _ = fs::remove_dir_all(&vault_path);
+    }
+}
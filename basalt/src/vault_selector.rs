@@ -1,29 +1,98 @@
 use std::marker::PhantomData;
 
+use crate::{chrome::Chrome, session::Session, type_ahead};
 use basalt_core::obsidian::Vault;
+
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
+    layout::{Alignment, Rect},
     style::{Color, Style, Stylize},
-    widgets::{Block, BorderType, List, ListItem, ListState, StatefulWidgetRef},
+    text::Line,
+    widgets::{List, ListItem, ListState, Padding, StatefulWidgetRef},
 };
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct VaultSelectorState<'a> {
     pub(crate) selected_item_index: Option<usize>,
+    /// The listed vaults, in the order given by the session.
     pub(crate) items: Vec<&'a Vault>,
+    /// All discovered vaults, including the hidden ones.
+    all: Vec<&'a Vault>,
+    session: Session,
+    show_hidden: bool,
     list_state: ListState,
 }
 
 impl<'a> VaultSelectorState<'a> {
     pub fn new(items: Vec<&'a Vault>) -> Self {
         VaultSelectorState {
+            all: items.clone(),
             items,
             selected_item_index: None,
+            session: Session::default(),
+            show_hidden: false,
             list_state: ListState::default().with_selected(Some(0)),
         }
     }
 
+    /// Orders the vaults by the pinned and recently opened vaults of the session and leaves out
+    /// the hidden ones. The highlighted vault stays highlighted if it is still listed, otherwise
+    /// the cursor stays where it was.
+    pub fn arrange(&mut self, session: Session) {
+        let highlighted = self.highlighted().map(|vault| vault.path.clone());
+
+        self.items = session.arrange(&self.all, self.show_hidden);
+        self.session = session;
+
+        let index = highlighted
+            .and_then(|path| self.items.iter().position(|vault| vault.path == path))
+            .or(self.list_state.selected())
+            .unwrap_or_default();
+        self.list_state
+            .select(Some(index.min(self.items.len().saturating_sub(1))));
+    }
+
+    /// Returns all discovered vaults, including the hidden ones.
+    pub fn all(&self) -> &[&'a Vault] {
+        &self.all
+    }
+
+    /// Returns the vault under the cursor.
+    pub fn highlighted(&self) -> Option<&'a Vault> {
+        self.list_state
+            .selected()
+            .and_then(|index| self.items.get(index).copied())
+    }
+
+    /// Pins the highlighted vault to the top of the list, or unpins it.
+    pub fn toggle_pinned(&mut self) {
+        self.update_session(Session::toggle_pinned);
+    }
+
+    /// Hides the highlighted vault from the list, or shows it again.
+    pub fn toggle_hidden(&mut self) {
+        self.update_session(Session::toggle_hidden);
+    }
+
+    /// Lists the hidden vaults too, so they can be opened or shown again.
+    pub fn toggle_show_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.arrange(self.session.clone());
+    }
+
+    /// Applies the change to the highlighted vault in the stored session, which may have been
+    /// changed since the list was arranged, e.g. by opening a vault.
+    fn update_session(&mut self, change: impl FnOnce(&mut Session, &str)) {
+        let Some(vault) = self.highlighted() else {
+            return;
+        };
+
+        let mut session = Session::load();
+        change(&mut session, &vault.name);
+        _ = session.save();
+        self.arrange(session);
+    }
+
     pub fn select(&mut self) {
         self.selected_item_index = self.list_state.selected();
     }
@@ -44,7 +113,7 @@ impl<'a> VaultSelectorState<'a> {
         let index = self
             .list_state
             .selected()
-            .map(|i| (i + 1).min(self.items.len() - 1));
+            .map(|i| (i + 1).min(self.items.len().saturating_sub(1)));
 
         self.list_state.select(index);
     }
@@ -52,13 +121,46 @@ impl<'a> VaultSelectorState<'a> {
     pub fn previous(&mut self) {
         self.list_state.select_previous();
     }
+
+    /// Moves the cursor to the first vault whose name starts with the typed query. Returns `false`
+    /// if no vault matches.
+    pub fn type_ahead(&mut self, query: &str) -> bool {
+        let names = self.items.iter().map(|vault| vault.name.as_str());
+        let start = self.list_state.selected().unwrap_or_default();
+        let index = type_ahead::find(names, start, query);
+
+        if index.is_some() {
+            self.list_state.select(index);
+        }
+        index.is_some()
+    }
+
+    pub fn first(&mut self) {
+        if !self.items.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    pub fn last(&mut self) {
+        if !self.items.is_empty() {
+            self.list_state.select(Some(self.items.len() - 1));
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct VaultSelector<'a> {
+    chrome: Chrome,
     _lifetime: PhantomData<&'a ()>,
 }
 
+impl VaultSelector<'_> {
+    /// Sets the borders, padding and title alignment of the vault list.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome, ..self }
+    }
+}
+
 impl<'a> StatefulWidgetRef for VaultSelector<'a> {
     type State = VaultSelectorState<'a>;
 
@@ -67,21 +169,30 @@ impl<'a> StatefulWidgetRef for VaultSelector<'a> {
             .items
             .iter()
             .map(|item| {
-                if item.open {
-                    ListItem::new(format!("◆ {}", item.name))
-                } else {
-                    ListItem::new(format!("  {}", item.name))
+                let marker = match (item.open, state.session.is_pinned(&item.name)) {
+                    (_, true) => "★",
+                    (true, false) => "◆",
+                    (false, false) => " ",
+                };
+
+                match state.session.is_hidden(&item.name) {
+                    true => ListItem::new(
+                        Line::from(format!("{marker} {} (hidden)", item.name)).dark_gray(),
+                    ),
+                    false => ListItem::new(format!("{marker} {}", item.name)),
                 }
             })
             .collect();
 
         List::new(items)
             .block(
-                Block::bordered()
+                self.chrome
+                    .block(false)
                     .dark_gray()
+                    .padding(self.chrome.padding(Padding::ZERO))
                     .title(" Vaults ")
-                    .title_style(Style::default().italic().bold())
-                    .border_type(BorderType::Rounded),
+                    .title_alignment(self.chrome.title_alignment(Alignment::Left))
+                    .title_style(Style::default().italic().bold()),
             )
             .fg(Color::default())
             .highlight_style(Style::new().reversed().dark_gray())
@@ -3,12 +3,15 @@ use std::marker::PhantomData;
 use basalt_core::obsidian::Vault;
 use ratatui::{
     buffer::Buffer,
-    layout::{Constraint, Flex, Layout, Rect},
+    layout::{Constraint, Rect},
     widgets::{Clear, ScrollbarState, StatefulWidget, StatefulWidgetRef, Widget},
 };
 
 use crate::{
     app::Message as AppMessage,
+    chrome::Chrome,
+    responsive,
+    session::Session,
     vault_selector::{VaultSelector, VaultSelectorState},
 };
 
@@ -17,8 +20,15 @@ pub enum Message {
     Toggle,
     Up,
     Down,
+    /// Selects the first vault.
+    GotoTop,
+    /// Selects the last vault.
+    GotoBottom,
     Select,
     Close,
+    Pin,
+    Hide,
+    ShowHidden,
 }
 
 pub fn update<'a>(
@@ -28,8 +38,18 @@ pub fn update<'a>(
     match message {
         Message::Up => state.previous(),
         Message::Down => state.next(),
-        Message::Toggle => state.toggle_visibility(),
+        Message::GotoTop => state.vault_selector_state.first(),
+        Message::GotoBottom => state.vault_selector_state.last(),
+        Message::Toggle => {
+            state.toggle_visibility();
+            if state.visible {
+                state.vault_selector_state.arrange(Session::load());
+            }
+        }
         Message::Close => state.hide(),
+        Message::Pin => state.vault_selector_state.toggle_pinned(),
+        Message::Hide => state.vault_selector_state.toggle_hidden(),
+        Message::ShowHidden => state.vault_selector_state.toggle_show_hidden(),
         Message::Select => {
             state.select();
             if let Some(vault) = state.selected_item() {
@@ -93,16 +113,18 @@ impl<'a> VaultSelectorModalState<'a> {
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct VaultSelectorModal<'a> {
+    chrome: Chrome,
     _lifetime: PhantomData<&'a ()>,
 }
 
 impl VaultSelectorModal<'_> {
-    fn modal_area(self, area: Rect) -> Rect {
-        let vertical = Layout::vertical([Constraint::Percentage(50)]).flex(Flex::Center);
-        let horizontal = Layout::horizontal([Constraint::Length(60)]).flex(Flex::Center);
-        let [area] = vertical.areas(area);
-        let [area] = horizontal.areas(area);
-        area
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome, ..self }
+    }
+
+    fn modal_area(&self, area: Rect) -> Rect {
+        responsive::modal_area(area, Constraint::Length(60), Constraint::Percentage(50))
     }
 }
 
@@ -115,7 +137,11 @@ impl<'a> StatefulWidget for VaultSelectorModal<'a> {
     {
         let area = self.modal_area(area);
         Widget::render(Clear, area, buf);
-        VaultSelector::default().render_ref(area, buf, &mut state.vault_selector_state);
+        VaultSelector::default().chrome(self.chrome).render_ref(
+            area,
+            buf,
+            &mut state.vault_selector_state,
+        );
     }
 }
 
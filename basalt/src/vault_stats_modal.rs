@@ -0,0 +1,381 @@
+use std::{fs, path::Path};
+
+use basalt_core::obsidian::{LinkIndex, Note, Vault};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Flex, Layout, Rect, Size},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Clear, Gauge, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{
+    app::{calc_scroll_amount, Message as AppMessage, ScrollAmount},
+    background::Job,
+    chrome::Chrome,
+    responsive,
+    text_counts::{CountMode, WordCount},
+    writing_stats::{self, WritingStats},
+};
+
+const MOST_LINKED_COUNT: usize = 10;
+
+/// Number of days shown in the sparkline of the words written.
+const WRITING_DAYS: u64 = 30;
+
+/// Statistics of a vault, computed with [`VaultStats::compute`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VaultStats {
+    pub note_count: usize,
+    pub word_count: usize,
+    pub attachment_count: usize,
+    /// Total size of the attachments in bytes.
+    pub attachment_size: u64,
+    /// Vault relative paths of notes without incoming or outgoing links.
+    pub orphans: Vec<String>,
+    /// Vault relative paths of the most linked files with the number of linking notes.
+    pub most_linked: Vec<(String, usize)>,
+}
+
+impl VaultStats {
+    /// Reads all notes in the vault and computes the statistics, counting the words of the text
+    /// in the count mode. The `progress` callback is called after each read note with the number
+    /// of read notes and the total number of notes.
+    pub fn compute(
+        vault: &Vault,
+        count_mode: CountMode,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Self {
+        let relative = |path: &Path| {
+            path.strip_prefix(&vault.path)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string()
+        };
+
+        let files = vault.files();
+        let (notes, attachments): (Vec<_>, Vec<_>) = files
+            .iter()
+            .partition(|path| path.extension().is_some_and(|ext| ext == "md"));
+
+        let mut index = LinkIndex::new(&vault.path, files.clone());
+        let mut word_count = 0;
+
+        for (i, path) in notes.iter().enumerate() {
+            let note = Note::from(path.as_path());
+
+            let contents = Note::read_to_string(&note).unwrap_or_default();
+            word_count += usize::from(WordCount::from(count_mode.text(&contents).as_ref()));
+            index.insert(&note, &contents);
+
+            progress(i + 1, notes.len());
+        }
+
+        Self {
+            note_count: notes.len(),
+            word_count,
+            attachment_count: attachments.len(),
+            attachment_size: attachments
+                .iter()
+                .filter_map(|path| fs::metadata(path).ok())
+                .map(|metadata| metadata.len())
+                .sum(),
+            orphans: index.orphans().into_iter().map(relative).collect(),
+            most_linked: index
+                .link_counts()
+                .into_iter()
+                .take(MOST_LINKED_COUNT)
+                .map(|(path, count)| (relative(path), count))
+                .collect(),
+        }
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Toggle,
+    Close,
+    ScrollUp(ScrollAmount),
+    ScrollDown(ScrollAmount),
+    Progress(usize, usize),
+    Loaded(VaultStats),
+}
+
+pub fn update<'a>(
+    message: &Message,
+    screen_size: Size,
+    vault: Option<&Vault>,
+    count_mode: CountMode,
+    state: &mut VaultStatsModalState,
+) -> Option<AppMessage<'a>> {
+    match message {
+        Message::Toggle if state.visible => state.hide(),
+        Message::Toggle => {
+            let vault = vault?;
+            state.visible = true;
+            state.scroll = 0;
+
+            let today = chrono::Local::now().date_naive();
+            let writing = WritingStats::load();
+            state.writing = writing.last_days(&vault.name, today, WRITING_DAYS);
+            state.streak = writing.streak(&vault.name, today);
+
+            // Statistics are always recomputed, as the vault might have changed on disk.
+            if state.progress.is_none() {
+                state.progress = Some((0, 0));
+                return Some(AppMessage::RunJob(Job::VaultStats(
+                    vault.clone(),
+                    count_mode,
+                )));
+            }
+        }
+        Message::Close => state.hide(),
+        Message::ScrollUp(scroll_amount) => {
+            state.scroll = state.scroll.saturating_sub(calc_scroll_amount(
+                scroll_amount,
+                modal_area(Rect::new(0, 0, screen_size.width, screen_size.height))
+                    .height
+                    .into(),
+            ));
+        }
+        Message::ScrollDown(scroll_amount) => {
+            state.scroll = state
+                .scroll
+                .saturating_add(calc_scroll_amount(
+                    scroll_amount,
+                    modal_area(Rect::new(0, 0, screen_size.width, screen_size.height))
+                        .height
+                        .into(),
+                ))
+                .min(state.lines().len().saturating_sub(1));
+        }
+        Message::Progress(done, total) => state.progress = Some((*done, *total)),
+        Message::Loaded(stats) => {
+            state.progress = None;
+            state.stats = Some(stats.clone());
+        }
+    };
+
+    None
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VaultStatsModalState {
+    pub(crate) stats: Option<VaultStats>,
+    /// Number of scanned notes and total number of notes while the scan is running.
+    pub(crate) progress: Option<(usize, usize)>,
+    pub(crate) scroll: usize,
+    /// Words written in the vault on each of the last days, the oldest first.
+    pub(crate) writing: Vec<usize>,
+    /// Days in a row words were written in the vault.
+    pub(crate) streak: usize,
+    pub(crate) visible: bool,
+}
+
+impl VaultStatsModalState {
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let Some(stats) = &self.stats else {
+            return vec![];
+        };
+
+        let row = |label: &str, value: String| {
+            Line::from(vec![
+                Span::from(format!("{label:<16}")).dark_gray(),
+                Span::from(value).bold(),
+            ])
+        };
+
+        let list = |title: &str, items: Vec<String>| {
+            [Line::default(), Line::from(title.to_string()).bold()]
+                .into_iter()
+                .chain(match items.is_empty() {
+                    true => vec![Line::from("  None").dark_gray().italic()],
+                    false => items
+                        .into_iter()
+                        .map(|item| Line::from(format!("  {item}")))
+                        .collect(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        [
+            row("Notes", stats.note_count.to_string()),
+            row("Words", stats.word_count.to_string()),
+            row(
+                "Attachments",
+                format!(
+                    "{} ({})",
+                    stats.attachment_count,
+                    format_size(stats.attachment_size)
+                ),
+            ),
+            row("Orphan notes", stats.orphans.len().to_string()),
+            row(
+                "Written today",
+                format!("{} words", self.writing.last().copied().unwrap_or_default()),
+            ),
+            row(
+                "Streak",
+                match self.streak {
+                    1 => "1 day".into(),
+                    streak => format!("{streak} days"),
+                },
+            ),
+            Line::default(),
+            Line::from(format!("Written in the last {WRITING_DAYS} days")).bold(),
+            Line::from(format!("  {}", writing_stats::sparkline(&self.writing))).magenta(),
+            Line::from(format!(
+                "  {} words, at most {} a day",
+                self.writing.iter().sum::<usize>(),
+                self.writing.iter().max().copied().unwrap_or_default()
+            ))
+            .dark_gray(),
+        ]
+        .into_iter()
+        .chain(list(
+            "Most linked notes",
+            stats
+                .most_linked
+                .iter()
+                .map(|(path, count)| format!("{count:>4}  {path}"))
+                .collect(),
+        ))
+        .chain(list("Orphan notes", stats.orphans.clone()))
+        .collect()
+    }
+}
+
+fn modal_area(area: Rect) -> Rect {
+    responsive::modal_area(area, Constraint::Length(70), Constraint::Percentage(60))
+}
+
+#[derive(Default)]
+pub struct VaultStatsModal {
+    chrome: Chrome,
+}
+
+impl VaultStatsModal {
+    /// Sets the borders, padding and title alignment of the modal.
+    pub fn chrome(self, chrome: Chrome) -> Self {
+        Self { chrome }
+    }
+}
+
+impl StatefulWidget for VaultStatsModal {
+    type State = VaultStatsModalState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block = self
+            .chrome
+            .block(false)
+            .dark_gray()
+            .padding(self.chrome.padding(Padding::uniform(1)))
+            .title_alignment(self.chrome.title_alignment(Alignment::Left))
+            .title_style(Style::default().italic().bold())
+            .title(" Vault Statistics ")
+            .title(Line::from(" (esc) ").alignment(ratatui::layout::Alignment::Right));
+
+        let area = modal_area(area);
+        let inner_area = block.inner(area);
+
+        Widget::render(Clear, area, buf);
+        Widget::render(block, area, buf);
+
+        if let Some((done, total)) = state.progress {
+            let [gauge] = Layout::vertical([Constraint::Length(1)])
+                .flex(Flex::Center)
+                .areas(inner_area);
+
+            Gauge::default()
+                .gauge_style(Style::default().fg(Color::Magenta))
+                .ratio(if total == 0 {
+                    0.0
+                } else {
+                    done as f64 / total as f64
+                })
+                .label(format!("Scanning notes {done}/{total}"))
+                .render(gauge, buf);
+
+            return;
+        }
+
+        Paragraph::new(state.lines())
+            .fg(Color::default())
+            .scroll((state.scroll as u16, 0))
+            .render(inner_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn test_compute() {
+        let vault = testing::vault(
+            "basalt-vault-stats-compute",
+            &[
+                ("Inbox.md", "# Inbox\n\nSee [[Ideas]] and ![[pond.png]]\n"),
+                ("Ideas.md", "# Ideas\n\nBack to [[Inbox]]\n"),
+                ("Projects/Plans.md", "Also [[Ideas]]\n"),
+                ("Lonely.md", "Nobody links here\n\n```\nlet x = 1;\n```\n"),
+                ("pond.png", "12345"),
+                ("scan.pdf", "123"),
+            ],
+        );
+
+        let mut progress = Vec::new();
+        let stats = VaultStats::compute(&vault, CountMode::Raw, |done, total| {
+            progress.push((done, total))
+        });
+        assert_eq!(progress, [(1, 4), (2, 4), (3, 4), (4, 4)]);
+        assert_eq!(
+            stats,
+            VaultStats {
+                note_count: 4,
+                word_count: 17,
+                attachment_count: 2,
+                attachment_size: 8,
+                orphans: vec!["Lonely.md".into()],
+                most_linked: vec![
+                    ("Ideas.md".into(), 2),
+                    ("Inbox.md".into(), 1),
+                    ("pond.png".into(), 1)
+                ],
+            }
+        );
+
+        // Code blocks are not counted as words of the prose.
+        let stats = VaultStats::compute(&vault, CountMode::Prose, |_, _| {});
+        assert_eq!(stats.word_count, 14);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    }
+}
@@ -0,0 +1,148 @@
+//! Watches the open vault for files that are added, removed or modified outside Basalt, e.g.
+//! notes renamed in Obsidian or synced from another device. The files of the vault are compared
+//! periodically on a background thread, and the changes are delivered to the main loop as a
+//! message.
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use basalt_core::obsidian::{Note, Vault, VaultIndex};
+
+use crate::{app::Message, background};
+
+/// Time between the comparisons of the vault files.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Files of the vault with their modification times.
+pub type Files = BTreeMap<PathBuf, Option<SystemTime>>;
+
+/// Returns the files of the vault with their modification times.
+fn snapshot(vault: &Vault) -> Files {
+    vault
+        .files()
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified());
+            (path, modified.ok())
+        })
+        .collect()
+}
+
+/// Files that were removed from, added to and modified in the vault between two comparisons.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Changes {
+    pub removed: Vec<PathBuf>,
+    pub added: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+}
+
+impl Changes {
+    /// Returns the changes between the files before and after.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use basalt_tui::watcher::{Changes, Files};
+    ///
+    /// let (then, now) = (Some(UNIX_EPOCH), Some(UNIX_EPOCH + Duration::from_secs(1)));
+    /// let before = Files::from([("Ideas.md".into(), then), ("Inbox.md".into(), then)]);
+    /// let after = Files::from([("Inbox.md".into(), now), ("Plans.md".into(), now)]);
+    ///
+    /// assert_eq!(
+    ///     Changes::between(&before, &after),
+    ///     Changes {
+    ///         removed: vec!["Ideas.md".into()],
+    ///         added: vec!["Plans.md".into()],
+    ///         modified: vec!["Inbox.md".into()],
+    ///     }
+    /// );
+    /// ```
+    pub fn between(before: &Files, after: &Files) -> Self {
+        Self {
+            removed: before
+                .keys()
+                .filter(|path| !after.contains_key(*path))
+                .cloned()
+                .collect(),
+            added: after
+                .keys()
+                .filter(|path| !before.contains_key(*path))
+                .cloned()
+                .collect(),
+            modified: after
+                .iter()
+                .filter(|(path, modified)| {
+                    before.get(*path).is_some_and(|before| before != *modified)
+                })
+                .map(|(path, _)| path.clone())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.modified.is_empty()
+    }
+
+    /// Brings the index up to date with the changes. Only the added and modified notes are read.
+    pub fn update_index(&self, index: &mut VaultIndex) {
+        self.removed.iter().for_each(|path| index.remove(path));
+        self.added
+            .iter()
+            .chain(&self.modified)
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .for_each(|path| {
+                index.update_note(&Note::from(path.as_path()));
+            });
+    }
+}
+
+/// Compares the files of the vault until it is dropped.
+#[derive(Debug)]
+pub struct Watcher {
+    stopped: Arc<AtomicBool>,
+}
+
+impl Watcher {
+    /// Starts watching the vault on a new thread. The changes are sent as
+    /// [`Message::VaultFilesChanged`].
+    pub fn spawn(vault: Vault, sender: background::Sender) -> Self {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let watching = stopped.clone();
+
+        thread::spawn(move || {
+            let mut files = snapshot(&vault);
+
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                if watching.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                let current = snapshot(&vault);
+                let changes = Changes::between(&files, &current);
+
+                if !changes.is_empty() {
+                    sender.send(Message::VaultFilesChanged(vault.path.clone(), changes));
+                    files = current;
+                }
+            }
+        });
+
+        Self { stopped }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,133 @@
+//! Clips web pages into notes. The page is fetched with `curl` and its readable content is
+//! converted to markdown.
+use std::process;
+
+use crate::html_to_markdown;
+
+/// How long fetching the page may take in seconds.
+const FETCH_TIMEOUT: &str = "30";
+
+/// Characters that are not allowed in note names, by Obsidian or the file system.
+const INVALID_NAME_CHARS: &[char] = &[
+    '/', '\\', ':', '*', '?', '"', '<', '>', '|', '#', '^', '[', ']',
+];
+
+/// The readable content of a web page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebClip {
+    pub url: String,
+    pub title: String,
+    pub markdown: String,
+}
+
+/// Returns `true` if the URL is a http or https address, the only ones that are clipped.
+pub fn is_web_url(url: &str) -> bool {
+    ["http://", "https://"].into_iter().any(|scheme| {
+        url.get(..scheme.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(scheme))
+    })
+}
+
+impl WebClip {
+    /// Fetches the page and extracts its readable content.
+    pub fn fetch(url: &str) -> Result<Self, String> {
+        let output = process::Command::new("curl")
+            .args(["--silent", "--show-error", "--location", "--fail"])
+            .args(["--max-time", FETCH_TIMEOUT])
+            .args([
+                "--user-agent",
+                concat!("basalt/", env!("CARGO_PKG_VERSION")),
+            ])
+            // Only web pages are fetched, not local files or other protocols, also when
+            // following redirects.
+            .args(["--proto", "=http,https", "--proto-redir", "=http,https"])
+            .arg("--")
+            .arg(url)
+            .output()
+            .map_err(|err| format!("Could not run curl: {err}"))?;
+
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+
+        Ok(Self::from_html(
+            url,
+            &String::from_utf8_lossy(&output.stdout),
+        ))
+    }
+
+    pub fn from_html(url: &str, html: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            title: html_to_markdown::title(html).unwrap_or_else(|| url.to_string()),
+            markdown: html_to_markdown::convert_readable(html, url),
+        }
+    }
+
+    /// Name of a new note for the clip, the title without characters that are not allowed in
+    /// note names.
+    pub fn note_name(&self) -> String {
+        let name = self
+            .title
+            .replace(INVALID_NAME_CHARS, " ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match name.as_str() {
+            "" | "." | ".." => "Web clip".to_string(),
+            _ => name,
+        }
+    }
+
+    /// Contents of a new note for the clip, with the source in the frontmatter.
+    pub fn note_contents(&self, clipped: &str) -> String {
+        format!(
+            "---\ntitle: \"{}\"\nsource: {}\nclipped: {clipped}\n---\n\n{}",
+            self.title.replace('"', "\\\""),
+            self.url,
+            self.markdown
+        )
+    }
+
+    /// Text inserted into an existing note, with a link to the source.
+    pub fn insert_text(&self) -> String {
+        format!(
+            "Source: [{}]({})\n\n{}",
+            self.title.replace(['[', ']'], ""),
+            self.url,
+            self.markdown
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_html() {
+        let clip = WebClip::from_html(
+            "https://example.com/post",
+            "<title>A: \"post\"</title><article><p>Text</p></article>",
+        );
+
+        assert_eq!(clip.note_name(), "A post");
+        assert_eq!(
+            clip.note_contents("2026-10-14"),
+            "---\ntitle: \"A: \\\"post\\\"\"\nsource: https://example.com/post\nclipped: 2026-10-14\n---\n\nText\n"
+        );
+
+        let clip = WebClip::from_html("https://example.com", "<title>Wait... v1.2</title>");
+        assert_eq!(clip.note_name(), "Wait... v1.2");
+    }
+
+    #[test]
+    fn test_is_web_url() {
+        assert!(is_web_url("https://example.com/post"));
+        assert!(is_web_url("HTTP://example.com"));
+        assert!(!is_web_url("file:///etc/passwd"));
+        assert!(!is_web_url("--output=notes.md"));
+        assert!(!is_web_url("example.com"));
+    }
+}
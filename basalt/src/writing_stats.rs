@@ -0,0 +1,145 @@
+//! Words written per day in each vault, persisted between application runs in the state
+//! directory. Words count when they are added in the note editor, removing words does not take
+//! them back, so that rewriting a paragraph counts as writing.
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::dirs;
+
+/// Number of days the counts are kept for.
+const KEPT_DAYS: u64 = 365;
+
+/// Characters of the sparkline from the lowest to the highest count.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WritingStats {
+    /// Words written by vault name and day, e.g. `2024-05-01`.
+    pub vaults: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+fn stats_path() -> Option<PathBuf> {
+    dirs::state_dir().map(|dir| dir.join("writing_stats.toml"))
+}
+
+fn day(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+impl WritingStats {
+    /// Reads the statistics from disk. A missing or unreadable file results in no statistics.
+    pub fn load() -> Self {
+        stats_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the statistics to disk.
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = stats_path().ok_or(std::io::ErrorKind::NotFound)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(
+            path,
+            toml::to_string(self).map_err(|err| std::io::Error::other(err.to_string()))?,
+        )
+    }
+
+    /// Adds the words written on the date in the vault. Counts older than a year are dropped.
+    pub fn add(&mut self, vault: &str, date: NaiveDate, words: usize) {
+        let days = self.vaults.entry(vault.to_string()).or_default();
+        *days.entry(day(date)).or_default() += words;
+
+        if let Some(oldest) = date.checked_sub_days(Days::new(KEPT_DAYS)) {
+            days.retain(|written_on, _| *written_on > day(oldest));
+        }
+    }
+
+    /// Returns the words written on the date in the vault.
+    pub fn words(&self, vault: &str, date: NaiveDate) -> usize {
+        self.vaults
+            .get(vault)
+            .and_then(|days| days.get(&day(date)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the words written in the vault on each of the days up to and including `today`,
+    /// the oldest first.
+    pub fn last_days(&self, vault: &str, today: NaiveDate, days: u64) -> Vec<usize> {
+        (0..days)
+            .rev()
+            .filter_map(|ago| today.checked_sub_days(Days::new(ago)))
+            .map(|date| self.words(vault, date))
+            .collect()
+    }
+
+    /// Returns the number of days in a row words were written in the vault, up to today. A
+    /// streak that ended yesterday still counts, as there is time left to write today.
+    pub fn streak(&self, vault: &str, today: NaiveDate) -> usize {
+        let written = |ago: u64| {
+            today
+                .checked_sub_days(Days::new(ago))
+                .is_some_and(|date| self.words(vault, date) > 0)
+        };
+
+        let start = if written(0) { 0 } else { 1 };
+        (start..).take_while(|ago| written(*ago)).count()
+    }
+}
+
+/// Draws the counts as a line of bars, scaled to the highest count. Days without words are drawn
+/// as spaces.
+///
+/// # Examples
+///
+/// ```
+/// use basalt_tui::writing_stats::sparkline;
+///
+/// assert_eq!(sparkline(&[0, 100, 400, 800]), " ▁▄█");
+/// ```
+pub fn sparkline(counts: &[usize]) -> String {
+    let max = counts.iter().copied().max().unwrap_or_default();
+
+    counts
+        .iter()
+        .map(|count| match count {
+            0 => ' ',
+            count => BARS[count * (BARS.len() - 1) / max],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writing_stats() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+        let mut stats = WritingStats::default();
+
+        [(1, 120), (3, 40), (4, 200), (4, 15), (5, 60)]
+            .into_iter()
+            .for_each(|(day, words)| stats.add("Notes", date(day), words));
+        stats.add("Work", date(2), 500);
+
+        assert_eq!(stats.words("Notes", date(4)), 215);
+        assert_eq!(stats.last_days("Notes", date(5), 5), [120, 0, 40, 215, 60]);
+        assert_eq!(stats.streak("Notes", date(5)), 3);
+        assert_eq!(stats.streak("Notes", date(6)), 3);
+        assert_eq!(stats.streak("Notes", date(7)), 0);
+        assert_eq!(stats.streak("Work", date(5)), 0);
+
+        let next_year = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        stats.add("Notes", next_year, 10);
+        assert_eq!(stats.vaults["Notes"].len(), 1);
+    }
+}